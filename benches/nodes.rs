@@ -0,0 +1,104 @@
+//! Tick and process benchmarks of major nodes and of networks of different sizes.
+//! Run with `cargo bench --features bench --bench nodes`.
+
+#![allow(clippy::precedence)]
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use fundsp::hacker32::*;
+
+fn node(
+    name: &'static str,
+    unit: impl AudioUnit32 + 'static,
+) -> (&'static str, Box<dyn AudioUnit32>) {
+    (name, Box::new(unit))
+}
+
+/// Major nodes. Each is fed with noise so that its inputs are connected.
+fn nodes() -> Vec<(&'static str, Box<dyn AudioUnit32>)> {
+    vec![
+        node("sine", sine_hz(110.0)),
+        node("saw", saw_hz(110.0)),
+        node("square", square_hz(110.0)),
+        node("organ", organ_hz(110.0)),
+        node("pink", pink()),
+        node("lowpass", noise() >> lowpass_hz(1000.0, 1.0)),
+        node("highpass", noise() >> highpass_hz(1000.0, 1.0)),
+        node("bell", noise() >> bell_hz(1000.0, 1.0, 2.0)),
+        node("butterpass", noise() >> butterpass_hz(1000.0)),
+        node("resonator", noise() >> resonator_hz(1000.0, 100.0)),
+        node("moog", noise() >> moog_hz(1000.0, 0.5)),
+        node("pluck", zero() >> pluck(110.0, 0.5, 0.5)),
+        node("delay", noise() >> delay(0.01)),
+        node("chorus", noise() >> chorus(0, 0.015, 0.005, 0.5)),
+        node(
+            "phaser",
+            noise() >> phaser(0.5, |t| sin_hz(0.1, t) * 0.5 + 0.5),
+        ),
+        node("limiter", noise() >> limiter((0.1, 1.0))),
+        node("oversample", noise() >> oversample(pass())),
+        node("reverb", noise() >> split() >> reverb_stereo(10.0, 2.0)),
+    ]
+}
+
+/// Network of `size` units in series.
+fn chain_net(size: usize) -> Net32 {
+    let mut net = Net32::new(0, 1);
+    net.chain(Box::new(noise()));
+    for i in 0..size {
+        net.chain(Box::new(lowpole_hz(1000.0 + 10.0 * i as f32)));
+    }
+    net
+}
+
+fn node_benchmark(c: &mut Criterion) {
+    for (name, mut unit) in nodes() {
+        let mut group = c.benchmark_group(name);
+        group.throughput(Throughput::Elements(MAX_BUFFER_SIZE as u64));
+        let mut output = vec![0.0; unit.outputs()];
+        group.bench_function("tick", |b| {
+            b.iter(|| {
+                for _ in 0..MAX_BUFFER_SIZE {
+                    unit.tick(&[], &mut output);
+                }
+                black_box(&output);
+            })
+        });
+        let mut buffer = Buffer::<f32>::with_channels(unit.outputs());
+        group.bench_function("process", |b| {
+            b.iter(|| {
+                unit.process(MAX_BUFFER_SIZE, &[], buffer.self_mut());
+                black_box(buffer.at(0));
+            })
+        });
+        group.finish();
+    }
+}
+
+fn net_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("net");
+    group.throughput(Throughput::Elements(MAX_BUFFER_SIZE as u64));
+    for size in [1, 10, 100, 1000] {
+        let mut net = chain_net(size);
+        net.allocate();
+        let mut output = [0.0];
+        group.bench_with_input(BenchmarkId::new("tick", size), &size, |b, _| {
+            b.iter(|| {
+                for _ in 0..MAX_BUFFER_SIZE {
+                    net.tick(&[], &mut output);
+                }
+                black_box(&output);
+            })
+        });
+        let mut buffer = Buffer::<f32>::with_channels(1);
+        group.bench_with_input(BenchmarkId::new("process", size), &size, |b, _| {
+            b.iter(|| {
+                net.process(MAX_BUFFER_SIZE, &[], buffer.self_mut());
+                black_box(buffer.at(0));
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, node_benchmark, net_benchmark);
+criterion_main!(benches);
@@ -15,9 +15,12 @@
 //! connected MIDI input device it finds, and play the corresponding pitches with the volume moderated by
 //! an `adsr_live()` envelope.
 
+use super::audionode::*;
+use super::math::*;
 use super::prelude::{clamp01, envelope2, lerp, shared, var, An, EnvelopeIn, Frame, U1};
-use super::shared::Atomic;
-use super::Float;
+use super::shared::{Atomic, Shared};
+use super::signal::*;
+use super::{Float, DEFAULT_SR};
 
 pub fn adsr_live<F: Float + Atomic>(
     attack: F,
@@ -25,16 +28,42 @@ pub fn adsr_live<F: Float + Atomic>(
     sustain: F,
     release: F,
 ) -> An<EnvelopeIn<F, F, impl Fn(F, &Frame<F, U1>) -> F + Sized + Clone, U1, F>> {
+    adsr_envelope(attack, decay, sustain, release, None)
+}
+
+/// ADSR envelope that sets `done` to one when the release has finished
+/// and back to zero when a new attack starts.
+pub fn adsr_live_done<F: Float + Atomic>(
+    attack: F,
+    decay: F,
+    sustain: F,
+    release: F,
+    done: &Shared<F>,
+) -> An<EnvelopeIn<F, F, impl Fn(F, &Frame<F, U1>) -> F + Clone, U1, F>> {
+    adsr_envelope(attack, decay, sustain, release, Some(done.clone()))
+}
+
+fn adsr_envelope<F: Float + Atomic>(
+    attack: F,
+    decay: F,
+    sustain: F,
+    release: F,
+    done: Option<Shared<F>>,
+) -> An<EnvelopeIn<F, F, impl Fn(F, &Frame<F, U1>) -> F + Clone, U1, F>> {
     let neg1 = F::from_f64(-1.0);
     let zero = F::from_f64(0.0);
     let a = shared(neg1);
     let b = shared(neg1);
     let attack_start = var(&a);
     let release_start = var(&b);
-    envelope2(move |time, control| {
+    let has_done = done.is_some();
+    let mut envelope = envelope2(move |time, control| {
         if attack_start.value() < zero && control > zero {
             attack_start.set_value(time);
             release_start.set_value(neg1);
+            if let Some(done) = &done {
+                done.set_value(zero);
+            }
         } else if release_start.value() < zero && control <= zero {
             release_start.set_value(time);
             attack_start.set_value(neg1);
@@ -42,9 +71,18 @@ pub fn adsr_live<F: Float + Atomic>(
         clamp01(if release_start.value() < zero {
             ads(attack, decay, sustain, time - attack_start.value())
         } else {
-            releasing(sustain, release, time - release_start.value())
+            let release_time = time - release_start.value();
+            if release_time > release {
+                if let Some(done) = &done {
+                    done.set_value(F::from_f64(1.0));
+                }
+            }
+            releasing(sustain, release, release_time)
         })
-    })
+    });
+    // Writing the done flag is a side effect: keep the envelope even if its output is unused.
+    envelope.0.set_side_effects(has_done);
+    envelope
 }
 
 fn ads<F: Float>(attack: F, decay: F, sustain: F, time: F) -> F {
@@ -67,3 +105,172 @@ fn releasing<F: Float>(sustain: F, release: F, release_time: F) -> F {
         lerp(sustain, F::from_f64(0.0), release_time / release)
     }
 }
+
+/// Attack overshoot target of the exponential envelope. The attack charges toward this level
+/// and ends when it reaches 1.0, like the capacitor of an analog envelope generator.
+const ATTACK_TARGET: f64 = 1.5;
+
+/// Exponential decay and release times are the times it takes to move 60 dB closer to the target.
+const DECAY_LOG: f64 = 6.907755278982137;
+
+/// Level below which the release of the exponential envelope has finished.
+const RELEASE_FLOOR: f64 = 1.0e-3;
+
+/// Envelope stage of `AdsrExp`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Stage {
+    Attack,
+    Decay,
+    Release,
+    Idle,
+}
+
+/// ADSR envelope with RC-style exponential curves.
+/// Retriggering starts a new attack from the current level.
+/// Setting: (attack, decay, sustain, release).
+/// - Input 0: gate (attack starts when > 0, release starts when <= 0)
+/// - Output 0: envelope level from 0.0 to 1.0
+#[derive(Clone)]
+pub struct AdsrExp<T: Float + Atomic> {
+    attack: T,
+    decay: T,
+    sustain: T,
+    release: T,
+    stage: Stage,
+    level: f64,
+    gate: bool,
+    attack_coefficient: f64,
+    decay_coefficient: f64,
+    release_coefficient: f64,
+    done: Option<Shared<T>>,
+    sample_rate: f64,
+}
+
+impl<T: Float + Atomic> AdsrExp<T> {
+    /// Create new exponential ADSR envelope with `attack`, `decay` and `release` times
+    /// in seconds and `sustain` level in 0...1.
+    pub fn new(attack: T, decay: T, sustain: T, release: T) -> Self {
+        let mut node = Self {
+            attack,
+            decay,
+            sustain,
+            release,
+            stage: Stage::Idle,
+            level: 0.0,
+            gate: false,
+            attack_coefficient: 0.0,
+            decay_coefficient: 0.0,
+            release_coefficient: 0.0,
+            done: None,
+            sample_rate: DEFAULT_SR,
+        };
+        node.set_sample_rate(DEFAULT_SR);
+        node
+    }
+
+    /// Set `done` to one when the release has finished and back to zero when a new attack starts.
+    pub fn set_done(&mut self, done: &Shared<T>) {
+        self.done = Some(done.clone());
+    }
+
+    /// Per-sample coefficient of a one-pole segment with time constant `tau` seconds.
+    fn coefficient(&self, tau: f64) -> f64 {
+        if tau > 0.0 {
+            exp_dsp(-1.0 / (tau * self.sample_rate))
+        } else {
+            0.0
+        }
+    }
+
+    fn update_coefficients(&mut self) {
+        // The attack reaches 1.0 after `attack` seconds when starting from zero.
+        let attack_ratio = ATTACK_TARGET / (ATTACK_TARGET - 1.0);
+        self.attack_coefficient = self.coefficient(self.attack.to_f64() / log(attack_ratio));
+        self.decay_coefficient = self.coefficient(self.decay.to_f64() / DECAY_LOG);
+        self.release_coefficient = self.coefficient(self.release.to_f64() / DECAY_LOG);
+    }
+
+    fn set_done_value(&self, value: f64) {
+        if let Some(done) = &self.done {
+            done.set_value(T::from_f64(value));
+        }
+    }
+}
+
+impl<T: Float + Atomic> AudioNode for AdsrExp<T> {
+    const ID: u64 = 120;
+    type Sample = T;
+    type Inputs = U1;
+    type Outputs = U1;
+    type Setting = (T, T, T, T);
+
+    fn set(&mut self, setting: Self::Setting) {
+        (self.attack, self.decay, self.sustain, self.release) = setting;
+        self.update_coefficients();
+    }
+
+    fn reset(&mut self) {
+        self.stage = Stage::Idle;
+        self.level = 0.0;
+        self.gate = false;
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate;
+        self.update_coefficients();
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let gate = input[0] > T::zero();
+        if gate && !self.gate {
+            self.stage = Stage::Attack;
+            self.set_done_value(0.0);
+        } else if !gate && self.gate && self.stage != Stage::Idle {
+            self.stage = Stage::Release;
+        }
+        self.gate = gate;
+        match self.stage {
+            Stage::Attack => {
+                self.level = ATTACK_TARGET + (self.level - ATTACK_TARGET) * self.attack_coefficient;
+                if self.level >= 1.0 {
+                    self.level = 1.0;
+                    self.stage = Stage::Decay;
+                }
+            }
+            Stage::Decay => {
+                let sustain = self.sustain.to_f64();
+                self.level = sustain + (self.level - sustain) * self.decay_coefficient;
+            }
+            Stage::Release => {
+                self.level *= self.release_coefficient;
+                if self.level < RELEASE_FLOOR {
+                    self.level = 0.0;
+                    self.stage = Stage::Idle;
+                    self.set_done_value(1.0);
+                }
+            }
+            Stage::Idle => (),
+        }
+        [T::from_f64(self.level)].into()
+    }
+
+    fn input_kind(&self, _input: usize) -> PortKind {
+        PortKind::Trigger
+    }
+
+    fn output_kind(&self, _output: usize) -> PortKind {
+        PortKind::Control
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        Routing::Arbitrary.propagate(input, self.outputs())
+    }
+
+    fn has_side_effects(&self) -> bool {
+        self.done.is_some()
+    }
+}
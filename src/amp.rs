@@ -0,0 +1,351 @@
+//! Guitar amplifier simulation components.
+
+use super::audionode::*;
+use super::math::*;
+use super::signal::*;
+use super::*;
+use num_complex::Complex64;
+use numeric_array::typenum::*;
+
+/// Guitar amplifier model for `amp_sim`.
+/// Tone controls are knob positions in 0...1.
+#[derive(Clone, Debug)]
+pub struct AmpModel {
+    /// Cutoff of the input highpass filter in Hz.
+    pub input_cutoff: f64,
+    /// Amplitude gain of each preamp stage. The number of stages is the length of this vector.
+    pub stages: Vec<f64>,
+    /// Static bias of the preamp stages. Nonzero bias produces even harmonics.
+    pub bias: f64,
+    /// Amount of dynamic bias shift. Loud signals shift the operating point of each stage,
+    /// which compresses and darkens the sound.
+    pub bias_shift: f64,
+    /// Bass control of the tone stack.
+    pub bass: f64,
+    /// Middle control of the tone stack.
+    pub middle: f64,
+    /// Treble control of the tone stack.
+    pub treble: f64,
+    /// Presence control: high shelf boost from 0 to 9 dB above 3.5 kHz.
+    pub presence: f64,
+    /// Output amplitude gain.
+    pub level: f64,
+    /// Cabinet impulse response at the sample rate of the amplifier.
+    /// The cabinet stage is bypassed if the impulse response is empty.
+    pub cabinet: Vec<f64>,
+}
+
+impl AmpModel {
+    /// Clean amplifier with a single, lightly driven stage.
+    pub fn clean() -> Self {
+        Self {
+            input_cutoff: 60.0,
+            stages: vec![1.5],
+            bias: 0.05,
+            bias_shift: 0.1,
+            bass: 0.5,
+            middle: 0.5,
+            treble: 0.6,
+            presence: 0.3,
+            level: 1.5,
+            cabinet: Vec::new(),
+        }
+    }
+
+    /// Crunchy amplifier with two stages.
+    pub fn crunch() -> Self {
+        Self {
+            input_cutoff: 80.0,
+            stages: vec![4.0, 3.0],
+            bias: 0.15,
+            bias_shift: 0.3,
+            bass: 0.5,
+            middle: 0.6,
+            treble: 0.6,
+            presence: 0.5,
+            level: 1.0,
+            cabinet: Vec::new(),
+        }
+    }
+
+    /// High gain lead amplifier with four stages.
+    pub fn lead() -> Self {
+        Self {
+            input_cutoff: 120.0,
+            stages: vec![8.0, 6.0, 4.0, 2.0],
+            bias: 0.2,
+            bias_shift: 0.5,
+            bass: 0.4,
+            middle: 0.8,
+            treble: 0.5,
+            presence: 0.6,
+            level: 0.8,
+            cabinet: Vec::new(),
+        }
+    }
+
+    /// Use `impulse` response for the cabinet.
+    pub fn with_cabinet(mut self, impulse: &[f64]) -> Self {
+        self.cabinet = impulse.to_vec();
+        self
+    }
+}
+
+/// Interactive three band tone stack modeled after the Fender Bassman
+/// passive network (D. T. Yeh and J. O. Smith, "Discretization of the '59 Fender
+/// Bassman Tone Stack", DAFx 2006). Bass, middle and treble controls
+/// are in 0...1 and interact with each other like in the analog circuit.
+/// The bass control has a logarithmic taper.
+/// Setting: (bass, middle, treble).
+/// - Input 0: input signal
+/// - Output 0: filtered signal
+#[derive(Clone)]
+pub struct ToneStack<T: Real> {
+    bass: T,
+    middle: T,
+    treble: T,
+    b: [f64; 4],
+    a: [f64; 4],
+    state: [f64; 3],
+    sample_rate: f64,
+}
+
+impl<T: Real> ToneStack<T> {
+    /// Create a new tone stack with `bass`, `middle` and `treble` controls in 0...1.
+    pub fn new(bass: T, middle: T, treble: T) -> Self {
+        let mut node = Self {
+            bass,
+            middle,
+            treble,
+            b: [0.0; 4],
+            a: [0.0; 4],
+            state: [0.0; 3],
+            sample_rate: DEFAULT_SR,
+        };
+        node.update_coefficients();
+        node
+    }
+
+    /// Set tone controls.
+    pub fn set_tone(&mut self, bass: T, middle: T, treble: T) {
+        self.bass = bass;
+        self.middle = middle;
+        self.treble = treble;
+        self.update_coefficients();
+    }
+
+    /// Calculate the analog transfer function from component values
+    /// and discretize it with the bilinear transform.
+    fn update_coefficients(&mut self) {
+        let (c1, c2, c3) = (250.0e-12, 20.0e-9, 20.0e-9);
+        let (r1, r2, r3, r4) = (250.0e3, 1.0e6, 25.0e3, 56.0e3);
+        let l = exp((clamp01(self.bass.to_f64()) - 1.0) * 3.4);
+        let m = clamp01(self.middle.to_f64());
+        let t = clamp01(self.treble.to_f64());
+        let mm = m * m;
+        let c123 = c1 * c2 * c3;
+
+        let b1 = t * c1 * r1 + m * c3 * r3 + l * (c1 * r2 + c2 * r2) + (c1 * r3 + c2 * r3);
+        let b2 = t * (c1 * c2 * r1 * r4 + c1 * c3 * r1 * r4)
+            - mm * (c1 * c3 * r3 * r3 + c2 * c3 * r3 * r3)
+            + m * (c1 * c3 * r1 * r3 + c1 * c3 * r3 * r3 + c2 * c3 * r3 * r3)
+            + l * (c1 * c2 * r1 * r2 + c1 * c2 * r2 * r4 + c1 * c3 * r2 * r4)
+            + l * m * (c1 * c3 * r2 * r3 + c2 * c3 * r2 * r3)
+            + (c1 * c2 * r1 * r3 + c1 * c2 * r3 * r4 + c1 * c3 * r3 * r4);
+        let b3 = l * m * c123 * (r1 * r2 * r3 + r2 * r3 * r4)
+            - mm * c123 * (r1 * r3 * r3 + r3 * r3 * r4)
+            + m * c123 * (r1 * r3 * r3 + r3 * r3 * r4)
+            + t * c123 * r1 * r3 * r4
+            - t * m * c123 * r1 * r3 * r4
+            + t * l * c123 * r1 * r2 * r4;
+        let a0 = 1.0;
+        let a1 = (c1 * r1 + c1 * r3 + c2 * r3 + c2 * r4 + c3 * r4)
+            + m * c3 * r3
+            + l * (c1 * r2 + c2 * r2);
+        let a2 = m
+            * (c1 * c3 * r1 * r3 - c2 * c3 * r3 * r4 + c1 * c3 * r3 * r3 + c2 * c3 * r3 * r3)
+            + l * m * (c1 * c3 * r2 * r3 + c2 * c3 * r2 * r3)
+            - mm * (c1 * c3 * r3 * r3 + c2 * c3 * r3 * r3)
+            + l * (c1 * c2 * r2 * r4 + c1 * c2 * r1 * r2 + c1 * c3 * r2 * r4 + c2 * c3 * r2 * r4)
+            + (c1 * c2 * r1 * r4
+                + c1 * c3 * r1 * r4
+                + c1 * c2 * r3 * r4
+                + c1 * c2 * r1 * r3
+                + c1 * c3 * r3 * r4
+                + c2 * c3 * r3 * r4);
+        let a3 = l * m * c123 * (r1 * r2 * r3 + r2 * r3 * r4)
+            - mm * c123 * (r1 * r3 * r3 + r3 * r3 * r4)
+            + m * c123 * (r3 * r3 * r4 + r1 * r3 * r3 - r1 * r3 * r4)
+            + l * c123 * r1 * r2 * r4
+            + c123 * r1 * r3 * r4;
+
+        let c = 2.0 * self.sample_rate;
+        let (c2, c3) = (c * c, c * c * c);
+        let b = [
+            -b1 * c - b2 * c2 - b3 * c3,
+            -b1 * c + b2 * c2 + 3.0 * b3 * c3,
+            b1 * c + b2 * c2 - 3.0 * b3 * c3,
+            b1 * c - b2 * c2 + b3 * c3,
+        ];
+        let a = [
+            -a0 - a1 * c - a2 * c2 - a3 * c3,
+            -3.0 * a0 - a1 * c + a2 * c2 + 3.0 * a3 * c3,
+            -3.0 * a0 + a1 * c + a2 * c2 - 3.0 * a3 * c3,
+            -a0 + a1 * c - a2 * c2 + a3 * c3,
+        ];
+        for i in 0..4 {
+            self.b[i] = b[i] / a[0];
+            self.a[i] = a[i] / a[0];
+        }
+    }
+}
+
+impl<T: Real> AudioNode for ToneStack<T> {
+    const ID: u64 = 81;
+    type Sample = T;
+    type Inputs = U1;
+    type Outputs = U1;
+    type Setting = (T, T, T);
+
+    fn set(&mut self, (bass, middle, treble): Self::Setting) {
+        self.set_tone(bass, middle, treble);
+    }
+
+    fn reset(&mut self) {
+        self.state = [0.0; 3];
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        if self.sample_rate != sample_rate {
+            self.sample_rate = sample_rate;
+            self.update_coefficients();
+        }
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        // Transposed direct form II.
+        let x = input[0].to_f64();
+        let y = self.b[0] * x + self.state[0];
+        self.state[0] = self.b[1] * x - self.a[1] * y + self.state[1];
+        self.state[1] = self.b[2] * x - self.a[2] * y + self.state[2];
+        self.state[2] = self.b[3] * x - self.a[3] * y;
+        [convert(y)].into()
+    }
+
+    fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        output[0] = input[0].filter(0.0, |r| {
+            let z1 = Complex64::from_polar(1.0, -TAU * frequency / self.sample_rate);
+            let mut z = Complex64::new(1.0, 0.0);
+            let mut numerator = Complex64::default();
+            let mut denominator = Complex64::default();
+            for i in 0..4 {
+                numerator += self.b[i] * z;
+                denominator += self.a[i] * z;
+                z *= z1;
+            }
+            r * numerator / denominator
+        });
+        output
+    }
+}
+
+/// State of a single preamp stage.
+#[derive(Clone, Default)]
+struct PreampStage<T: Real> {
+    gain: T,
+    /// Smoothed positive excursion of the stage input, which drives bias shift.
+    envelope: T,
+    /// DC blocker state.
+    x1: T,
+    y1: T,
+}
+
+/// Multi-stage tube style preamp. Each stage applies gain, bias and `tanh` saturation,
+/// followed by a coupling highpass filter. Positive excursions shift the bias
+/// of each stage dynamically.
+/// - Input 0: input signal
+/// - Output 0: distorted signal
+#[derive(Clone)]
+pub struct Preamp<T: Real> {
+    stages: Vec<PreampStage<T>>,
+    bias: T,
+    bias_shift: T,
+    level: T,
+    /// Coupling filter pole.
+    coupling: T,
+    /// Per-sample envelope smoothing factor.
+    smoothing: T,
+}
+
+impl<T: Real> Preamp<T> {
+    /// Create a new preamp with the given stage `gains`, static `bias`,
+    /// dynamic `bias_shift` amount and output `level`.
+    pub fn new(gains: &[T], bias: T, bias_shift: T, level: T) -> Self {
+        let mut node = Self {
+            stages: gains
+                .iter()
+                .map(|gain| PreampStage {
+                    gain: *gain,
+                    ..PreampStage::default()
+                })
+                .collect(),
+            bias,
+            bias_shift,
+            level,
+            coupling: T::zero(),
+            smoothing: T::zero(),
+        };
+        node.set_sample_rate(DEFAULT_SR);
+        node
+    }
+}
+
+impl<T: Real> AudioNode for Preamp<T> {
+    const ID: u64 = 82;
+    type Sample = T;
+    type Inputs = U1;
+    type Outputs = U1;
+    type Setting = ();
+
+    fn reset(&mut self) {
+        for stage in self.stages.iter_mut() {
+            stage.envelope = T::zero();
+            stage.x1 = T::zero();
+            stage.y1 = T::zero();
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.coupling = T::from_f64(exp(-TAU * 10.0 / sample_rate));
+        self.smoothing = T::from_f64(1.0 - exp(-1.0 / (0.02 * sample_rate)));
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let mut x = input[0];
+        let rest = tanh(self.bias);
+        for stage in self.stages.iter_mut() {
+            let v = x * stage.gain + self.bias - self.bias_shift * stage.envelope;
+            stage.envelope += (max(v, T::zero()) - stage.envelope) * self.smoothing;
+            let y = tanh(v) - rest;
+            x = y - stage.x1 + self.coupling * stage.y1;
+            stage.x1 = y;
+            stage.y1 = x;
+        }
+        [x * self.level].into()
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        output[0] = input[0].distort(0.0);
+        output
+    }
+}
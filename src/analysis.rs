@@ -0,0 +1,144 @@
+//! Frequency response analysis.
+
+use super::audiounit::*;
+use super::math::*;
+use super::*;
+use num_complex::Complex64;
+
+/// How the frequency response in a `BodePlot` was obtained.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BodeMethod {
+    /// Exact response from signal flow analysis (`AudioUnit64::response`).
+    Route,
+    /// Fourier transform of a measured impulse response.
+    /// Used when signal flow analysis cannot produce a response,
+    /// for example, when the unit contains nonlinear processing.
+    Impulse,
+}
+
+/// Frequency response of an audio unit as magnitude, phase and group delay.
+#[derive(Clone, Debug)]
+pub struct BodePlot {
+    /// How the response was obtained.
+    pub method: BodeMethod,
+    /// Analysis frequencies in Hz, logarithmically spaced from 10 Hz to 20 kHz.
+    pub frequency: Vec<f64>,
+    /// Magnitude response in dB.
+    pub magnitude: Vec<f64>,
+    /// Unwrapped phase response in radians.
+    pub phase: Vec<f64>,
+    /// Group delay in seconds.
+    pub group_delay: Vec<f64>,
+}
+
+/// Length of measured impulse responses in samples.
+const IMPULSE_LENGTH: usize = 65536;
+
+/// Relative frequency offset used in estimating group delay from impulse responses.
+const GROUP_DELAY_OFFSET: f64 = 1.0e-4;
+
+/// Evaluate the Fourier transform of `impulse` at `frequency` Hz.
+fn transform(impulse: &[f64], frequency: f64) -> Complex64 {
+    // Rotate a phasor instead of calling sin and cos for every sample.
+    let step = Complex64::from_polar(1.0, -TAU * frequency / DEFAULT_SR);
+    let mut phasor = Complex64::new(1.0, 0.0);
+    let mut result = Complex64::new(0.0, 0.0);
+    for (i, x) in impulse.iter().enumerate() {
+        result += phasor * *x;
+        phasor *= step;
+        // Renormalize the phasor periodically to keep it on the unit circle.
+        if i & 0xff == 0xff {
+            phasor /= phasor.norm();
+        }
+    }
+    result
+}
+
+/// Compute a Bode plot of `unit` at `resolution` frequencies between 10 Hz and 20 kHz.
+/// The response is measured from output 0 with all inputs driven
+/// at the default sample rate. The unit itself is not modified.
+/// The exact response from signal flow analysis is used if it is available for every frequency;
+/// otherwise, the response is computed from a measured impulse response of 65536 samples.
+/// `BodePlot::method` tells which one was used.
+///
+/// ### Example: Lowpass Filter Response
+/// ```
+/// use fundsp::hacker::*;
+/// let plot = bode_plot(&lowpass_hz(1000.0, 1.0), 100);
+/// assert!(plot.method == BodeMethod::Route);
+/// let plot = bode_plot(&(lowpass_hz(1000.0, 1.0) >> shape(Shape::Tanh(0.01))), 100);
+/// assert!(plot.method == BodeMethod::Impulse);
+/// ```
+pub fn bode_plot(unit: &dyn AudioUnit64, resolution: usize) -> BodePlot {
+    assert!(unit.inputs() > 0 && unit.outputs() > 0);
+    assert!(resolution > 1);
+    let mut unit = dyn_clone::clone_box(unit);
+    unit.set_sample_rate(DEFAULT_SR);
+    unit.allocate();
+    unit.reset();
+
+    let frequency: Vec<f64> = (0..resolution)
+        .map(|i| xerp(10.0, 20000.0, i as f64 / (resolution - 1) as f64))
+        .collect();
+
+    // Evaluate the response and group delay at each frequency.
+    let mut response = Vec::with_capacity(resolution);
+    let mut method = BodeMethod::Route;
+    for (&f, r) in frequency.iter().zip(unit.response_batch(0, &frequency)) {
+        match (r, unit.group_delay(0, f)) {
+            (Some(r), Some(delay)) => response.push((r, delay)),
+            _ => {
+                method = BodeMethod::Impulse;
+                break;
+            }
+        }
+    }
+
+    if method == BodeMethod::Impulse {
+        let mut impulse = vec![0.0; IMPULSE_LENGTH];
+        let mut input = vec![0.0; unit.inputs()];
+        let mut output = vec![0.0; unit.outputs()];
+        input.fill(1.0);
+        for x in impulse.iter_mut() {
+            unit.tick(&input, &mut output);
+            *x = output[0];
+            input.fill(0.0);
+        }
+        response.clear();
+        for &f in frequency.iter() {
+            let r0 = transform(&impulse, f * (1.0 - GROUP_DELAY_OFFSET));
+            let r1 = transform(&impulse, f * (1.0 + GROUP_DELAY_OFFSET));
+            let delay = -(r1 / r0).arg() / (TAU * 2.0 * GROUP_DELAY_OFFSET * f);
+            response.push((transform(&impulse, f), delay));
+        }
+    }
+
+    let mut magnitude = Vec::with_capacity(resolution);
+    let mut phase = Vec::with_capacity(resolution);
+    let mut group_delay = Vec::with_capacity(resolution);
+    // Previous response, unwrapped phase, group delay and frequency.
+    let mut previous: Option<(Complex64, f64, f64, f64)> = None;
+    for (&f, &(r, delay)) in frequency.iter().zip(response.iter()) {
+        magnitude.push(amp_db(r.norm()));
+        group_delay.push(delay);
+        // Unwrap phase using the phase change predicted from group delay.
+        let p = match previous {
+            Some((previous_r, previous_phase, previous_delay, previous_f)) => {
+                let predicted = -0.5 * (delay + previous_delay) * TAU * (f - previous_f);
+                let error = (r / previous_r).arg() - predicted;
+                previous_phase + predicted + error - TAU * round(error / TAU)
+            }
+            None => r.arg(),
+        };
+        phase.push(p);
+        previous = Some((r, p, delay, f));
+    }
+
+    BodePlot {
+        method,
+        frequency,
+        magnitude,
+        phase,
+        group_delay,
+    }
+}
@@ -7,6 +7,7 @@ use super::signal::*;
 use super::*;
 use num_complex::Complex64;
 use numeric_array::typenum::*;
+use std::any::Any;
 use std::marker::PhantomData;
 
 /// Type-level integer.
@@ -102,7 +103,7 @@ pub trait AudioNode: Clone + Sync + Send {
         input: &Frame<Self::Sample, Self::Inputs>,
     ) -> Frame<Self::Sample, Self::Outputs>;
 
-    /// Process up to 64 (`MAX_BUFFER_SIZE`) samples.
+    /// Process up to `MAX_BUFFER_SIZE` samples.
     /// The number of input and output buffers must match the number of inputs and outputs, respectively.
     /// All input and output buffers must be at least as large as `size`.
     /// If `size` is zero then this is a no-op, which is permitted.
@@ -161,6 +162,41 @@ pub trait AudioNode: Clone + Sync + Send {
         hash.hash(Self::ID)
     }
 
+    /// Whether the node interacts with state outside the graph, for example,
+    /// by writing to shared variables or by receiving settings.
+    /// Network optimizations leave nodes with side effects intact.
+    /// Leaf nodes with side effects should override this.
+    /// The default implementation returns false.
+    fn has_side_effects(&self) -> bool {
+        false
+    }
+
+    /// Kind of signal expected at `input`. Nodes with parameter inputs should override this.
+    /// The default implementation returns `PortKind::Audio`.
+    #[allow(unused_variables)]
+    fn input_kind(&self, input: usize) -> PortKind {
+        PortKind::Audio
+    }
+
+    /// Kind of signal produced at `output`.
+    /// The default implementation returns `PortKind::Audio`.
+    #[allow(unused_variables)]
+    fn output_kind(&self, output: usize) -> PortKind {
+        PortKind::Audio
+    }
+
+    /// Channel layout of the inputs, if declared.
+    /// The default implementation returns `None`.
+    fn input_layout(&self) -> Option<ChannelLayout> {
+        None
+    }
+
+    /// Channel layout of the outputs, if declared.
+    /// The default implementation returns `None`.
+    fn output_layout(&self) -> Option<ChannelLayout> {
+        None
+    }
+
     /// Route constants, latencies and frequency responses at `frequency` Hz
     /// from inputs to outputs. Return output signal.
     #[allow(unused_variables)]
@@ -174,6 +210,14 @@ pub trait AudioNode: Clone + Sync + Send {
         // The default implementation does nothing.
     }
 
+    /// Visit contained nodes tagged with `tag` (see the `tagged` opcode).
+    /// Each tagged node is passed to `f`, where it can be downcast to its concrete type.
+    /// Nodes that contain other nodes should override this.
+    #[allow(unused_variables)]
+    fn visit(&mut self, tag: u64, f: &mut dyn FnMut(&mut dyn Any)) {
+        // The default implementation does nothing.
+    }
+
     // End of interface. There is no need to override the following.
 
     /// Number of inputs.
@@ -223,6 +267,52 @@ pub trait AudioNode: Clone + Sync + Send {
         }
     }
 
+    /// Evaluate frequency response of `output` at each of `frequencies` (in Hz).
+    /// The input configuration is set up once for the whole batch.
+    /// Return `None` for each frequency where there is no response or it could not be calculated.
+    ///
+    /// ### Example
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let response = lowpass_hz(1000.0, 1.0).response_batch(0, &[100.0, 1000.0, 10000.0]);
+    /// assert!(response.len() == 3 && response[0].unwrap().norm() > response[2].unwrap().norm());
+    /// ```
+    fn response_batch(&mut self, output: usize, frequencies: &[f64]) -> Vec<Option<Complex64>> {
+        assert!(output < self.outputs());
+        let mut input = new_signal_frame(self.inputs());
+        for i in 0..self.inputs() {
+            input[i] = Signal::Response(Complex64::new(1.0, 0.0), 0.0);
+        }
+        frequencies
+            .iter()
+            .map(|&frequency| match self.route(&input, frequency)[output] {
+                Signal::Response(rx, _) => Some(rx),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Evaluate group delay of `output` in seconds at `frequency` Hz.
+    /// The delay is estimated from the phase slope of the frequency response.
+    /// Return `None` if there is no response or it could not be calculated.
+    ///
+    /// ### Example
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let delay = pass().group_delay(0, 1000.0).unwrap();
+    /// assert!((delay * DEFAULT_SR - pass().latency().unwrap()).abs() < 1.0e-6);
+    /// ```
+    fn group_delay(&mut self, output: usize, frequency: f64) -> Option<f64> {
+        let delta = 1.0e-4 * max(frequency, 10.0);
+        let response = self.response_batch(output, &[frequency - delta, frequency + delta]);
+        match (response[0], response[1]) {
+            (Some(r0), Some(r1)) if r0.norm() > 0.0 && r1.norm() > 0.0 => {
+                Some(-(r1 / r0).arg() / (TAU * 2.0 * delta))
+            }
+            _ => None,
+        }
+    }
+
     /// Evaluate frequency response of `output` in dB at `frequency` Hz.
     /// Any linear response can be composed.
     /// Return `None` if there is no response or it could not be calculated.
@@ -1028,6 +1118,27 @@ where
         self.y.ping(probe, self.x.ping(probe, hash.hash(Self::ID)))
     }
 
+    fn visit(&mut self, tag: u64, f: &mut dyn FnMut(&mut dyn Any)) {
+        self.x.visit(tag, f);
+        self.y.visit(tag, f);
+    }
+
+    fn has_side_effects(&self) -> bool {
+        self.x.has_side_effects() || self.y.has_side_effects()
+    }
+
+    fn input_kind(&self, input: usize) -> PortKind {
+        if input < X::Inputs::USIZE {
+            self.x.input_kind(input)
+        } else {
+            self.y.input_kind(input - X::Inputs::USIZE)
+        }
+    }
+
+    fn output_kind(&self, output: usize) -> PortKind {
+        self.x.output_kind(output)
+    }
+
     fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
         let mut signal_x = self.x.route(input, frequency);
         let signal_y = self.y.route(
@@ -1264,6 +1375,30 @@ where
         self.x.ping(probe, hash.hash(Self::ID))
     }
 
+    fn visit(&mut self, tag: u64, f: &mut dyn FnMut(&mut dyn Any)) {
+        self.x.visit(tag, f);
+    }
+
+    fn has_side_effects(&self) -> bool {
+        self.x.has_side_effects()
+    }
+
+    fn input_kind(&self, input: usize) -> PortKind {
+        self.x.input_kind(input)
+    }
+
+    fn output_kind(&self, output: usize) -> PortKind {
+        self.x.output_kind(output)
+    }
+
+    fn input_layout(&self) -> Option<ChannelLayout> {
+        self.x.input_layout()
+    }
+
+    fn output_layout(&self) -> Option<ChannelLayout> {
+        self.x.output_layout()
+    }
+
     fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
         let mut signal_x = self.x.route(input, frequency);
         for i in 0..Self::Outputs::USIZE {
@@ -1465,6 +1600,31 @@ where
         self.y.ping(probe, self.x.ping(probe, hash.hash(Self::ID)))
     }
 
+    fn visit(&mut self, tag: u64, f: &mut dyn FnMut(&mut dyn Any)) {
+        self.x.visit(tag, f);
+        self.y.visit(tag, f);
+    }
+
+    fn has_side_effects(&self) -> bool {
+        self.x.has_side_effects() || self.y.has_side_effects()
+    }
+
+    fn input_kind(&self, input: usize) -> PortKind {
+        self.x.input_kind(input)
+    }
+
+    fn output_kind(&self, output: usize) -> PortKind {
+        self.y.output_kind(output)
+    }
+
+    fn input_layout(&self) -> Option<ChannelLayout> {
+        self.x.input_layout()
+    }
+
+    fn output_layout(&self) -> Option<ChannelLayout> {
+        self.y.output_layout()
+    }
+
     fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
         self.y.route(&self.x.route(input, frequency), frequency)
     }
@@ -1606,6 +1766,31 @@ where
         self.y.ping(probe, self.x.ping(probe, hash.hash(Self::ID)))
     }
 
+    fn visit(&mut self, tag: u64, f: &mut dyn FnMut(&mut dyn Any)) {
+        self.x.visit(tag, f);
+        self.y.visit(tag, f);
+    }
+
+    fn has_side_effects(&self) -> bool {
+        self.x.has_side_effects() || self.y.has_side_effects()
+    }
+
+    fn input_kind(&self, input: usize) -> PortKind {
+        if input < X::Inputs::USIZE {
+            self.x.input_kind(input)
+        } else {
+            self.y.input_kind(input - X::Inputs::USIZE)
+        }
+    }
+
+    fn output_kind(&self, output: usize) -> PortKind {
+        if output < X::Outputs::USIZE {
+            self.x.output_kind(output)
+        } else {
+            self.y.output_kind(output - X::Outputs::USIZE)
+        }
+    }
+
     fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
         let mut signal_x = self.x.route(input, frequency);
         let signal_y = self.y.route(
@@ -1742,6 +1927,27 @@ where
         self.y.ping(probe, self.x.ping(probe, hash.hash(Self::ID)))
     }
 
+    fn visit(&mut self, tag: u64, f: &mut dyn FnMut(&mut dyn Any)) {
+        self.x.visit(tag, f);
+        self.y.visit(tag, f);
+    }
+
+    fn has_side_effects(&self) -> bool {
+        self.x.has_side_effects() || self.y.has_side_effects()
+    }
+
+    fn input_kind(&self, input: usize) -> PortKind {
+        self.x.input_kind(input)
+    }
+
+    fn output_kind(&self, output: usize) -> PortKind {
+        if output < X::Outputs::USIZE {
+            self.x.output_kind(output)
+        } else {
+            self.y.output_kind(output - X::Outputs::USIZE)
+        }
+    }
+
     fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
         let mut signal_x = self.x.route(input, frequency);
         let signal_y = self.y.route(input, frequency);
@@ -1881,6 +2087,31 @@ where
         self.y.ping(probe, self.x.ping(probe, hash.hash(Self::ID)))
     }
 
+    fn visit(&mut self, tag: u64, f: &mut dyn FnMut(&mut dyn Any)) {
+        self.x.visit(tag, f);
+        self.y.visit(tag, f);
+    }
+
+    fn has_side_effects(&self) -> bool {
+        self.x.has_side_effects() || self.y.has_side_effects()
+    }
+
+    fn input_kind(&self, input: usize) -> PortKind {
+        self.x.input_kind(input)
+    }
+
+    fn output_kind(&self, output: usize) -> PortKind {
+        self.x.output_kind(output)
+    }
+
+    fn input_layout(&self) -> Option<ChannelLayout> {
+        self.x.input_layout()
+    }
+
+    fn output_layout(&self) -> Option<ChannelLayout> {
+        self.x.output_layout()
+    }
+
     fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
         let mut signal_x = self.x.route(input, frequency);
         let signal_y = self.y.route(input, frequency);
@@ -1981,6 +2212,26 @@ impl<X: AudioNode> AudioNode for Thru<X> {
         self.x.ping(probe, hash.hash(Self::ID))
     }
 
+    fn visit(&mut self, tag: u64, f: &mut dyn FnMut(&mut dyn Any)) {
+        self.x.visit(tag, f);
+    }
+
+    fn has_side_effects(&self) -> bool {
+        self.x.has_side_effects()
+    }
+
+    fn input_kind(&self, input: usize) -> PortKind {
+        self.x.input_kind(input)
+    }
+
+    fn output_kind(&self, output: usize) -> PortKind {
+        if output < X::Outputs::USIZE {
+            self.x.output_kind(output)
+        } else {
+            self.x.input_kind(output)
+        }
+    }
+
     fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
         let mut output = self.x.route(input, frequency);
         output[X::Outputs::USIZE..Self::Outputs::USIZE]
@@ -2113,6 +2364,24 @@ where
         hash
     }
 
+    fn visit(&mut self, tag: u64, f: &mut dyn FnMut(&mut dyn Any)) {
+        for x in &mut self.x {
+            x.visit(tag, f);
+        }
+    }
+
+    fn has_side_effects(&self) -> bool {
+        self.x.iter().any(|x| x.has_side_effects())
+    }
+
+    fn input_kind(&self, input: usize) -> PortKind {
+        self.x[0].input_kind(input)
+    }
+
+    fn output_kind(&self, output: usize) -> PortKind {
+        self.x[0].output_kind(output)
+    }
+
     fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
         if self.x.is_empty() {
             return new_signal_frame(self.outputs());
@@ -2266,6 +2535,24 @@ where
         hash
     }
 
+    fn visit(&mut self, tag: u64, f: &mut dyn FnMut(&mut dyn Any)) {
+        for x in self.x.iter_mut() {
+            x.visit(tag, f);
+        }
+    }
+
+    fn has_side_effects(&self) -> bool {
+        self.x.iter().any(|x| x.has_side_effects())
+    }
+
+    fn input_kind(&self, input: usize) -> PortKind {
+        self.x[0].input_kind(input % X::Inputs::USIZE)
+    }
+
+    fn output_kind(&self, output: usize) -> PortKind {
+        self.x[0].output_kind(output % X::Outputs::USIZE)
+    }
+
     fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
         if self.x.is_empty() {
             return new_signal_frame(self.outputs());
@@ -2290,6 +2577,171 @@ where
     }
 }
 
+/// Process `N` channels with similar single output nodes.
+/// The first input of each node receives its own channel,
+/// while the remaining (parameter) inputs are shared by all nodes.
+#[derive(Clone)]
+pub struct PerChannel<N, T, X>
+where
+    N: Size<T>,
+    N: Size<X>,
+    T: Float,
+    X: AudioNode<Sample = T, Outputs = U1>,
+    X::Inputs: Size<T> + Sub<U1>,
+    N: Add<Diff<X::Inputs, U1>>,
+    Sum<N, Diff<X::Inputs, U1>>: Size<T>,
+{
+    _marker: PhantomData<(N, T)>,
+    x: Frame<X, N>,
+}
+
+impl<N, T, X> PerChannel<N, T, X>
+where
+    N: Size<T>,
+    N: Size<X>,
+    T: Float,
+    X: AudioNode<Sample = T, Outputs = U1>,
+    X::Inputs: Size<T> + Sub<U1>,
+    N: Add<Diff<X::Inputs, U1>>,
+    Sum<N, Diff<X::Inputs, U1>>: Size<T>,
+{
+    pub fn new(x: Frame<X, N>) -> Self {
+        assert!(X::Inputs::USIZE > 0);
+        let mut node = PerChannel {
+            _marker: PhantomData,
+            x,
+        };
+        let hash = node.ping(true, AttoHash::new(Self::ID));
+        node.ping(false, hash);
+        node
+    }
+
+    /// Access a contained node.
+    #[inline]
+    pub fn node_mut(&mut self, index: usize) -> &mut X {
+        &mut self.x[index]
+    }
+
+    /// Access a contained node.
+    #[inline]
+    pub fn node(&self, index: usize) -> &X {
+        &self.x[index]
+    }
+}
+
+impl<N, T, X> AudioNode for PerChannel<N, T, X>
+where
+    N: Size<T>,
+    N: Size<X>,
+    T: Float,
+    X: AudioNode<Sample = T, Outputs = U1>,
+    X::Inputs: Size<T> + Sub<U1>,
+    N: Add<Diff<X::Inputs, U1>>,
+    Sum<N, Diff<X::Inputs, U1>>: Size<T>,
+{
+    const ID: u64 = 92;
+    type Sample = T;
+    type Inputs = Sum<N, Diff<X::Inputs, U1>>;
+    type Outputs = N;
+    type Setting = (usize, X::Setting);
+
+    fn set(&mut self, setting: Self::Setting) {
+        let (index, inner) = setting;
+        self.x[index].set(inner);
+    }
+
+    fn reset(&mut self) {
+        self.x.iter_mut().for_each(|node| node.reset());
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.x
+            .iter_mut()
+            .for_each(|node| node.set_sample_rate(sample_rate));
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let mut output: Frame<Self::Sample, Self::Outputs> = Frame::splat(T::zero());
+        for (i, node) in self.x.iter_mut().enumerate() {
+            let node_input = Frame::generate(|j| {
+                if j == 0 {
+                    input[i]
+                } else {
+                    input[N::USIZE + j - 1]
+                }
+            });
+            output[i] = node.tick(&node_input)[0];
+        }
+        output
+    }
+
+    fn process(
+        &mut self,
+        size: usize,
+        input: &[&[Self::Sample]],
+        output: &mut [&mut [Self::Sample]],
+    ) {
+        for i in 0..N::USIZE {
+            let mut node_input: tinyvec::TinyVec<[&[T]; 8]> = tinyvec::TinyVec::new();
+            node_input.push(input[i]);
+            node_input.extend_from_slice(&input[N::USIZE..]);
+            self.x[i].process(size, &node_input, &mut output[i..i + 1]);
+        }
+    }
+
+    #[inline]
+    fn ping(&mut self, probe: bool, hash: AttoHash) -> AttoHash {
+        let mut hash = hash.hash(Self::ID);
+        for x in self.x.iter_mut() {
+            hash = x.ping(probe, hash);
+        }
+        hash
+    }
+
+    fn visit(&mut self, tag: u64, f: &mut dyn FnMut(&mut dyn Any)) {
+        for x in self.x.iter_mut() {
+            x.visit(tag, f);
+        }
+    }
+
+    fn has_side_effects(&self) -> bool {
+        self.x.iter().any(|x| x.has_side_effects())
+    }
+
+    fn input_kind(&self, input: usize) -> PortKind {
+        if input < N::USIZE {
+            self.x[0].input_kind(0)
+        } else {
+            self.x[0].input_kind(input + 1 - N::USIZE)
+        }
+    }
+
+    fn output_kind(&self, _output: usize) -> PortKind {
+        self.x[0].output_kind(0)
+    }
+
+    fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        for i in 0..N::USIZE {
+            let mut node_input = new_signal_frame(X::Inputs::USIZE);
+            node_input[0] = input[i];
+            node_input[1..].copy_from_slice(&input[N::USIZE..]);
+            output[i] = self.x[i].route(&node_input, frequency)[0];
+        }
+        output
+    }
+
+    fn allocate(&mut self) {
+        for x in &mut self.x {
+            x.allocate();
+        }
+    }
+}
+
 /// Combine outputs of a bunch of similar nodes with a binary operation.
 /// Inputs are disjoint.
 /// Outputs are combined channel-wise.
@@ -2427,6 +2879,24 @@ where
         hash
     }
 
+    fn visit(&mut self, tag: u64, f: &mut dyn FnMut(&mut dyn Any)) {
+        for x in self.x.iter_mut() {
+            x.visit(tag, f);
+        }
+    }
+
+    fn has_side_effects(&self) -> bool {
+        self.x.iter().any(|x| x.has_side_effects())
+    }
+
+    fn input_kind(&self, input: usize) -> PortKind {
+        self.x[0].input_kind(input % X::Inputs::USIZE)
+    }
+
+    fn output_kind(&self, output: usize) -> PortKind {
+        self.x[0].output_kind(output)
+    }
+
     fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
         if self.x.is_empty() {
             return new_signal_frame(self.outputs());
@@ -2567,6 +3037,24 @@ where
         hash
     }
 
+    fn visit(&mut self, tag: u64, f: &mut dyn FnMut(&mut dyn Any)) {
+        for x in self.x.iter_mut() {
+            x.visit(tag, f);
+        }
+    }
+
+    fn has_side_effects(&self) -> bool {
+        self.x.iter().any(|x| x.has_side_effects())
+    }
+
+    fn input_kind(&self, input: usize) -> PortKind {
+        self.x[0].input_kind(input)
+    }
+
+    fn output_kind(&self, output: usize) -> PortKind {
+        self.x[0].output_kind(output % X::Outputs::USIZE)
+    }
+
     fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
         if self.x.is_empty() {
             return new_signal_frame(self.outputs());
@@ -2717,6 +3205,24 @@ where
         hash
     }
 
+    fn visit(&mut self, tag: u64, f: &mut dyn FnMut(&mut dyn Any)) {
+        for x in self.x.iter_mut() {
+            x.visit(tag, f);
+        }
+    }
+
+    fn has_side_effects(&self) -> bool {
+        self.x.iter().any(|x| x.has_side_effects())
+    }
+
+    fn input_kind(&self, input: usize) -> PortKind {
+        self.x[0].input_kind(input)
+    }
+
+    fn output_kind(&self, output: usize) -> PortKind {
+        self.x[N::USIZE - 1].output_kind(output)
+    }
+
     fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
         let mut output = self.x[0].route(input, frequency);
         for i in 1..self.x.len() {
@@ -2774,3 +3280,510 @@ impl<N: Size<T>, T: Float> AudioNode for Reverse<N, T> {
         Routing::Reverse.propagate(input, N::USIZE)
     }
 }
+
+/// Dry/wet mix of `N` channels. The dry path is delayed by the latency of the wet node,
+/// rounded to the nearest sample, so that both paths stay aligned.
+#[derive(Clone)]
+pub struct Mix<N, T, X>
+where
+    N: Size<T>,
+    T: Float,
+    X: AudioNode<Sample = T, Inputs = N, Outputs = N>,
+{
+    x: X,
+    /// Wet amount in 0...1.
+    wet: T,
+    /// Dry signal delay line. Its length is the latency of the wet node.
+    buffer: Vec<Frame<T, N>>,
+    index: usize,
+    sample_rate: f64,
+}
+
+impl<N, T, X> Mix<N, T, X>
+where
+    N: Size<T>,
+    T: Float,
+    X: AudioNode<Sample = T, Inputs = N, Outputs = N>,
+{
+    pub fn new(x: X, wet: T) -> Self {
+        let mut node = Mix {
+            x,
+            wet,
+            buffer: Vec::new(),
+            index: 0,
+            sample_rate: DEFAULT_SR,
+        };
+        node.update_latency();
+        let hash = node.ping(true, AttoHash::new(Self::ID));
+        node.ping(false, hash);
+        node
+    }
+
+    /// Wet amount in 0...1.
+    #[inline]
+    pub fn wet(&self) -> T {
+        self.wet
+    }
+
+    /// Set wet amount in 0...1.
+    #[inline]
+    pub fn set_wet(&mut self, wet: T) {
+        self.wet = wet;
+    }
+
+    /// Latency of the dry path in samples.
+    #[inline]
+    pub fn dry_latency(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Access the wet node.
+    #[inline]
+    pub fn node_mut(&mut self) -> &mut X {
+        &mut self.x
+    }
+
+    /// Access the wet node.
+    #[inline]
+    pub fn node(&self) -> &X {
+        &self.x
+    }
+
+    /// Resize the dry delay line to match the current latency of the wet node.
+    fn update_latency(&mut self) {
+        // Measure from a copy so that the state of the wet node is not touched.
+        let latency = self.x.clone().latency().unwrap_or(0.0);
+        let length = round(max(0.0, latency)) as usize;
+        if length != self.buffer.len() {
+            self.buffer = vec![Frame::default(); length];
+        } else {
+            self.buffer.fill(Frame::default());
+        }
+        self.index = 0;
+    }
+
+    /// Push a dry input frame and return the delayed one.
+    #[inline]
+    fn dry(&mut self, input: Frame<T, N>) -> Frame<T, N> {
+        if self.buffer.is_empty() {
+            input
+        } else {
+            let output = std::mem::replace(&mut self.buffer[self.index], input);
+            self.index += 1;
+            if self.index == self.buffer.len() {
+                self.index = 0;
+            }
+            output
+        }
+    }
+}
+
+impl<N, T, X> AudioNode for Mix<N, T, X>
+where
+    N: Size<T>,
+    T: Float,
+    X: AudioNode<Sample = T, Inputs = N, Outputs = N>,
+{
+    const ID: u64 = 97;
+    type Sample = T;
+    type Inputs = N;
+    type Outputs = N;
+    type Setting = T;
+
+    fn set(&mut self, setting: Self::Setting) {
+        self.set_wet(setting);
+    }
+
+    fn reset(&mut self) {
+        self.x.reset();
+        self.buffer.fill(Frame::default());
+        self.index = 0;
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate;
+        self.x.set_sample_rate(sample_rate);
+        self.update_latency();
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let wet = self.x.tick(input);
+        let dry = self.dry(input.clone());
+        dry * Frame::splat(T::one() - self.wet) + wet * Frame::splat(self.wet)
+    }
+
+    fn process(
+        &mut self,
+        size: usize,
+        input: &[&[Self::Sample]],
+        output: &mut [&mut [Self::Sample]],
+    ) {
+        self.x.process(size, input, output);
+        let dry_amount = T::one() - self.wet;
+        for i in 0..size {
+            let dry = self.dry(Frame::generate(|channel| input[channel][i]));
+            for channel in 0..N::USIZE {
+                output[channel][i] = dry[channel] * dry_amount + output[channel][i] * self.wet;
+            }
+        }
+    }
+
+    #[inline]
+    fn ping(&mut self, probe: bool, hash: AttoHash) -> AttoHash {
+        self.x.ping(probe, hash.hash(Self::ID))
+    }
+
+    fn visit(&mut self, tag: u64, f: &mut dyn FnMut(&mut dyn Any)) {
+        self.x.visit(tag, f);
+    }
+
+    fn has_side_effects(&self) -> bool {
+        self.x.has_side_effects()
+    }
+
+    fn input_kind(&self, input: usize) -> PortKind {
+        self.x.input_kind(input)
+    }
+
+    fn output_kind(&self, output: usize) -> PortKind {
+        self.x.output_kind(output)
+    }
+
+    fn input_layout(&self) -> Option<ChannelLayout> {
+        self.x.input_layout()
+    }
+
+    fn output_layout(&self) -> Option<ChannelLayout> {
+        self.x.output_layout()
+    }
+
+    fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
+        let wet = self.x.route(input, frequency);
+        let latency = self.buffer.len() as f64;
+        let mut output = new_signal_frame(self.outputs());
+        for i in 0..N::USIZE {
+            let dry = if latency > 0.0 {
+                input[i].filter(latency, |r| {
+                    r * Complex64::from_polar(1.0, -TAU * latency * frequency / self.sample_rate)
+                })
+            } else {
+                input[i]
+            };
+            let dry = dry.scale(1.0 - self.wet.to_f64());
+            output[i] = dry.combine_linear(
+                wet[i].scale(self.wet.to_f64()),
+                0.0,
+                |x, y| x + y,
+                |x, y| x + y,
+            );
+        }
+        output
+    }
+
+    fn allocate(&mut self) {
+        self.x.allocate();
+    }
+}
+
+/// Declare the kind of signal produced at all outputs of the enclosed node.
+#[derive(Clone)]
+pub struct PortKindOf<X: AudioNode> {
+    x: X,
+    kind: PortKind,
+}
+
+impl<X: AudioNode> PortKindOf<X> {
+    pub fn new(kind: PortKind, x: X) -> Self {
+        let mut node = PortKindOf { x, kind };
+        let hash = node.ping(true, AttoHash::new(Self::ID));
+        node.ping(false, hash);
+        node
+    }
+}
+
+impl<X: AudioNode> AudioNode for PortKindOf<X> {
+    const ID: u64 = 117;
+    type Sample = X::Sample;
+    type Inputs = X::Inputs;
+    type Outputs = X::Outputs;
+    type Setting = X::Setting;
+
+    fn set(&mut self, setting: Self::Setting) {
+        self.x.set(setting);
+    }
+
+    fn reset(&mut self) {
+        self.x.reset();
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.x.set_sample_rate(sample_rate);
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        self.x.tick(input)
+    }
+
+    fn process(
+        &mut self,
+        size: usize,
+        input: &[&[Self::Sample]],
+        output: &mut [&mut [Self::Sample]],
+    ) {
+        self.x.process(size, input, output);
+    }
+
+    fn ping(&mut self, probe: bool, hash: AttoHash) -> AttoHash {
+        self.x.ping(probe, hash.hash(Self::ID))
+    }
+
+    fn visit(&mut self, tag: u64, f: &mut dyn FnMut(&mut dyn Any)) {
+        self.x.visit(tag, f);
+    }
+
+    fn has_side_effects(&self) -> bool {
+        self.x.has_side_effects()
+    }
+
+    fn input_kind(&self, input: usize) -> PortKind {
+        self.x.input_kind(input)
+    }
+
+    fn output_kind(&self, _output: usize) -> PortKind {
+        self.kind
+    }
+
+    fn input_layout(&self) -> Option<ChannelLayout> {
+        self.x.input_layout()
+    }
+
+    fn output_layout(&self) -> Option<ChannelLayout> {
+        self.x.output_layout()
+    }
+
+    fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
+        self.x.route(input, frequency)
+    }
+
+    fn allocate(&mut self) {
+        self.x.allocate();
+    }
+}
+
+/// Declare the channel layout of the inputs and/or outputs of the enclosed node.
+/// The layout applies to the inputs and outputs that have as many channels as the layout.
+#[derive(Clone)]
+pub struct LayoutOf<X: AudioNode> {
+    x: X,
+    layout: ChannelLayout,
+}
+
+impl<X: AudioNode> LayoutOf<X> {
+    pub fn new(layout: ChannelLayout, x: X) -> Self {
+        assert!(
+            X::Inputs::USIZE == layout.channels() || X::Outputs::USIZE == layout.channels(),
+            "Channel layout {} does not match the number of inputs or outputs.",
+            layout
+        );
+        let mut node = LayoutOf { x, layout };
+        let hash = node.ping(true, AttoHash::new(Self::ID));
+        node.ping(false, hash);
+        node
+    }
+}
+
+impl<X: AudioNode> AudioNode for LayoutOf<X> {
+    const ID: u64 = 127;
+    type Sample = X::Sample;
+    type Inputs = X::Inputs;
+    type Outputs = X::Outputs;
+    type Setting = X::Setting;
+
+    fn set(&mut self, setting: Self::Setting) {
+        self.x.set(setting);
+    }
+
+    fn reset(&mut self) {
+        self.x.reset();
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.x.set_sample_rate(sample_rate);
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        self.x.tick(input)
+    }
+
+    fn process(
+        &mut self,
+        size: usize,
+        input: &[&[Self::Sample]],
+        output: &mut [&mut [Self::Sample]],
+    ) {
+        self.x.process(size, input, output);
+    }
+
+    fn ping(&mut self, probe: bool, hash: AttoHash) -> AttoHash {
+        self.x.ping(probe, hash.hash(Self::ID))
+    }
+
+    fn visit(&mut self, tag: u64, f: &mut dyn FnMut(&mut dyn Any)) {
+        self.x.visit(tag, f);
+    }
+
+    fn has_side_effects(&self) -> bool {
+        self.x.has_side_effects()
+    }
+
+    fn input_kind(&self, input: usize) -> PortKind {
+        self.x.input_kind(input)
+    }
+
+    fn output_kind(&self, output: usize) -> PortKind {
+        self.x.output_kind(output)
+    }
+
+    fn input_layout(&self) -> Option<ChannelLayout> {
+        if X::Inputs::USIZE == self.layout.channels() {
+            Some(self.layout)
+        } else {
+            self.x.input_layout()
+        }
+    }
+
+    fn output_layout(&self) -> Option<ChannelLayout> {
+        if X::Outputs::USIZE == self.layout.channels() {
+            Some(self.layout)
+        } else {
+            self.x.output_layout()
+        }
+    }
+
+    fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
+        self.x.route(input, frequency)
+    }
+
+    fn allocate(&mut self) {
+        self.x.allocate();
+    }
+}
+
+/// Tagged node. Tagged nodes can be found inside composed graphs with `AudioNode::visit`.
+#[derive(Clone)]
+pub struct Tagged<X: AudioNode> {
+    x: X,
+    tag: u64,
+}
+
+impl<X: AudioNode + 'static> Tagged<X> {
+    pub fn new(tag: u64, x: X) -> Self {
+        let mut node = Tagged { x, tag };
+        let hash = node.ping(true, AttoHash::new(Self::ID));
+        node.ping(false, hash);
+        node
+    }
+
+    /// Tag of the enclosed node.
+    pub fn tag(&self) -> u64 {
+        self.tag
+    }
+
+    /// Access enclosed node.
+    pub fn node(&self) -> &X {
+        &self.x
+    }
+
+    /// Access enclosed node.
+    pub fn node_mut(&mut self) -> &mut X {
+        &mut self.x
+    }
+}
+
+impl<X: AudioNode + 'static> AudioNode for Tagged<X> {
+    const ID: u64 = 132;
+    type Sample = X::Sample;
+    type Inputs = X::Inputs;
+    type Outputs = X::Outputs;
+    type Setting = X::Setting;
+
+    fn set(&mut self, setting: Self::Setting) {
+        self.x.set(setting);
+    }
+
+    fn reset(&mut self) {
+        self.x.reset();
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.x.set_sample_rate(sample_rate);
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        self.x.tick(input)
+    }
+
+    fn process(
+        &mut self,
+        size: usize,
+        input: &[&[Self::Sample]],
+        output: &mut [&mut [Self::Sample]],
+    ) {
+        self.x.process(size, input, output);
+    }
+
+    fn ping(&mut self, probe: bool, hash: AttoHash) -> AttoHash {
+        self.x.ping(probe, hash.hash(Self::ID))
+    }
+
+    fn visit(&mut self, tag: u64, f: &mut dyn FnMut(&mut dyn Any)) {
+        if tag == self.tag {
+            f(&mut self.x);
+        }
+        self.x.visit(tag, f);
+    }
+
+    fn has_side_effects(&self) -> bool {
+        self.x.has_side_effects()
+    }
+
+    fn input_kind(&self, input: usize) -> PortKind {
+        self.x.input_kind(input)
+    }
+
+    fn output_kind(&self, output: usize) -> PortKind {
+        self.x.output_kind(output)
+    }
+
+    fn input_layout(&self) -> Option<ChannelLayout> {
+        self.x.input_layout()
+    }
+
+    fn output_layout(&self) -> Option<ChannelLayout> {
+        self.x.output_layout()
+    }
+
+    fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
+        self.x.route(input, frequency)
+    }
+
+    fn allocate(&mut self) {
+        self.x.allocate();
+    }
+}
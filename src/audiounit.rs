@@ -15,9 +15,9 @@ use std::fmt::Write;
 /// An audio processor with an object safe interface.
 /// Once constructed, it has a fixed number of inputs and outputs.
 #[duplicate_item(
-    f48       AudioUnit48;
-    [ f64 ]   [ AudioUnit64 ];
-    [ f32 ]   [ AudioUnit32 ];
+    f48       AudioUnit48       Net48;
+    [ f64 ]   [ AudioUnit64 ]   [ super::net::Net64 ];
+    [ f32 ]   [ AudioUnit32 ]   [ super::net::Net32 ];
 )]
 pub trait AudioUnit48: Send + Sync + DynClone {
     /// Reset the input state of the unit to an initial state where it has not processed any data.
@@ -34,7 +34,7 @@ pub trait AudioUnit48: Send + Sync + DynClone {
     /// The length of `input` and `output` must be equal to `inputs` and `outputs`, respectively.
     fn tick(&mut self, input: &[f48], output: &mut [f48]);
 
-    /// Process up to 64 (MAX_BUFFER_SIZE) samples.
+    /// Process up to `MAX_BUFFER_SIZE` samples.
     /// Buffers are supplied as slices. All buffers must have room for at least `size` samples.
     /// If `size` is zero then this is a no-op, which is permitted.
     /// The number of input and output buffers must be equal to `inputs` and `outputs`, respectively.
@@ -77,6 +77,40 @@ pub trait AudioUnit48: Send + Sync + DynClone {
         hash.hash(self.get_id())
     }
 
+    /// Whether the unit interacts with state outside the graph, for example,
+    /// by writing to shared variables or by receiving settings.
+    /// Network optimizations leave units with side effects intact.
+    /// The default implementation returns false.
+    fn has_side_effects(&self) -> bool {
+        false
+    }
+
+    /// Kind of signal expected at `input`.
+    /// The default implementation returns `PortKind::Audio`.
+    #[allow(unused_variables)]
+    fn input_kind(&self, input: usize) -> PortKind {
+        PortKind::Audio
+    }
+
+    /// Kind of signal produced at `output`.
+    /// The default implementation returns `PortKind::Audio`.
+    #[allow(unused_variables)]
+    fn output_kind(&self, output: usize) -> PortKind {
+        PortKind::Audio
+    }
+
+    /// Channel layout of the inputs, if declared.
+    /// The default implementation returns `None`.
+    fn input_layout(&self) -> Option<ChannelLayout> {
+        None
+    }
+
+    /// Channel layout of the outputs, if declared.
+    /// The default implementation returns `None`.
+    fn output_layout(&self) -> Option<ChannelLayout> {
+        None
+    }
+
     /// Memory footprint of this unit in bytes, without counting buffers and other allocations.
     fn footprint(&self) -> usize;
 
@@ -84,6 +118,12 @@ pub trait AudioUnit48: Send + Sync + DynClone {
     /// The default implementation does nothing.
     fn allocate(&mut self) {}
 
+    /// Access this unit as a network, if it is one. Networks use this to flatten subnetworks.
+    /// The default implementation returns `None`.
+    fn as_net_mut(&mut self) -> Option<&mut Net48> {
+        None
+    }
+
     // End of interface. There is no need to override the following.
 
     /// Evaluate frequency response of `output` at `frequency` Hz.
@@ -109,6 +149,52 @@ pub trait AudioUnit48: Send + Sync + DynClone {
         }
     }
 
+    /// Evaluate frequency response of `output` at each of `frequencies` (in Hz).
+    /// The input configuration is set up once for the whole batch.
+    /// Return `None` for each frequency where there is no response or it could not be calculated.
+    ///
+    /// ### Example
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let response = lowpass_hz(1000.0, 1.0).response_batch(0, &[100.0, 1000.0, 10000.0]);
+    /// assert!(response.len() == 3 && response[0].unwrap().norm() > response[2].unwrap().norm());
+    /// ```
+    fn response_batch(&mut self, output: usize, frequencies: &[f64]) -> Vec<Option<Complex64>> {
+        assert!(output < self.outputs());
+        let mut input = new_signal_frame(self.inputs());
+        for i in 0..self.inputs() {
+            input[i] = Signal::Response(Complex64::new(1.0, 0.0), 0.0);
+        }
+        frequencies
+            .iter()
+            .map(|&frequency| match self.route(&input, frequency)[output] {
+                Signal::Response(rx, _) => Some(rx),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Evaluate group delay of `output` in seconds at `frequency` Hz.
+    /// The delay is estimated from the phase slope of the frequency response.
+    /// Return `None` if there is no response or it could not be calculated.
+    ///
+    /// ### Example
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let delay = tick().group_delay(0, 1000.0).unwrap();
+    /// assert!((delay * DEFAULT_SR - tick().latency().unwrap()).abs() < 1.0e-6);
+    /// ```
+    fn group_delay(&mut self, output: usize, frequency: f64) -> Option<f64> {
+        let delta = 1.0e-4 * max(frequency, 10.0);
+        let response = self.response_batch(output, &[frequency - delta, frequency + delta]);
+        match (response[0], response[1]) {
+            (Some(r0), Some(r1)) if r0.norm() > 0.0 && r1.norm() > 0.0 => {
+                Some(-(r1 / r0).arg() / (TAU * 2.0 * delta))
+            }
+            _ => None,
+        }
+    }
+
     /// Evaluate frequency response of `output` in dB at `frequency` Hz.
     /// Any linear response can be composed.
     /// Return `None` if there is no response or it could not be calculated.
@@ -361,6 +447,241 @@ pub trait AudioUnit48: Send + Sync + DynClone {
 )]
 dyn_clone::clone_trait_object!(AudioUnit48);
 
+#[duplicate_item(
+    f48       AudioUnit48;
+    [ f64 ]   [ AudioUnit64 ];
+    [ f32 ]   [ AudioUnit32 ];
+)]
+impl dyn AudioUnit48 {
+    /// Clone this unit, including its current state, into a new, independent unit.
+    /// This can be used to instantiate a stored prototype, for example, once per voice.
+    /// Backends cannot be cloned effectively: a cloned backend no longer receives updates.
+    ///
+    /// ### Example
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let prototype: Box<dyn AudioUnit64> = Box::new(saw_hz(110.0) >> lowpole_hz(1000.0));
+    /// let mut voice1 = prototype.clone_unit();
+    /// let mut voice2 = prototype.clone_unit();
+    /// voice1.get_mono();
+    /// assert!(voice2.get_mono() == prototype.clone_unit().get_mono());
+    /// ```
+    pub fn clone_unit(&self) -> Box<dyn AudioUnit48> {
+        dyn_clone::clone_box(self)
+    }
+}
+
+/// Sample types of the dynamic audio unit traits: `f64` for `AudioUnit64` and `f32` for `AudioUnit32`.
+/// Containers that are generic over sample type, such as `Net<T>`, hold units as `Box<T::Unit>`.
+pub trait UnitFloat: Float + 'static {
+    /// Audio unit trait object of this sample type: `dyn AudioUnit64` or `dyn AudioUnit32`.
+    type Unit: ?Sized + DynAudioUnit<Self>;
+
+    /// Box `node` as an audio unit of this sample type.
+    fn boxed<X: AudioNode<Sample = Self> + Send + Sync + 'static>(node: An<X>) -> Box<Self::Unit>;
+}
+
+/// Audio units of sample type `T` for code that is generic over sample type.
+/// It is implemented by the trait objects `dyn AudioUnit64` and `dyn AudioUnit32`,
+/// which forward to the audio unit traits, and by `Net<T>`.
+/// Clonable implementors are audio units of their sample type.
+pub trait DynAudioUnit<T: UnitFloat>: Send + Sync {
+    /// Reset the input state of the unit to an initial state where it has not processed any data.
+    /// In other words, reset time to zero.
+    fn reset(&mut self);
+
+    /// Set the sample rate of the unit.
+    fn set_sample_rate(&mut self, sample_rate: f64);
+
+    /// Process one sample.
+    /// The length of `input` and `output` must be equal to `inputs` and `outputs`, respectively.
+    fn tick(&mut self, input: &[T], output: &mut [T]);
+
+    /// Process up to `MAX_BUFFER_SIZE` samples.
+    /// All buffers must have room for at least `size` samples.
+    fn process(&mut self, size: usize, input: &[&[T]], output: &mut [&mut [T]]);
+
+    /// Number of inputs to this unit.
+    fn inputs(&self) -> usize;
+
+    /// Number of outputs from this unit.
+    fn outputs(&self) -> usize;
+
+    /// Route constants, latencies and frequency responses at `frequency` Hz
+    /// from inputs to outputs. Return output signal.
+    fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame;
+
+    /// Return an ID code for this type of unit.
+    fn get_id(&self) -> u64;
+
+    /// Ping contained units to obtain a deterministic pseudorandom hash.
+    /// If `probe` is true, then this is a probe for computing the network hash.
+    fn ping(&mut self, probe: bool, hash: AttoHash) -> AttoHash;
+
+    /// Whether the unit interacts with state outside the graph.
+    fn has_side_effects(&self) -> bool;
+
+    /// Kind of signal expected at `input`.
+    fn input_kind(&self, input: usize) -> PortKind;
+
+    /// Kind of signal produced at `output`.
+    fn output_kind(&self, output: usize) -> PortKind;
+
+    /// Channel layout of the inputs, if declared.
+    fn input_layout(&self) -> Option<ChannelLayout>;
+
+    /// Channel layout of the outputs, if declared.
+    fn output_layout(&self) -> Option<ChannelLayout>;
+
+    /// Preallocate all needed memory, including buffers for block processing.
+    fn allocate(&mut self);
+
+    /// Access this unit as a network, if it is one.
+    fn as_net_mut(&mut self) -> Option<&mut super::net::Net<T>>;
+
+    /// Clone this unit, including its current state, into a new, independent unit.
+    fn clone_unit(&self) -> Box<Self>;
+}
+
+#[duplicate_item(
+    f48       AudioUnit48;
+    [ f64 ]   [ AudioUnit64 ];
+    [ f32 ]   [ AudioUnit32 ];
+)]
+impl UnitFloat for f48 {
+    type Unit = dyn AudioUnit48;
+
+    fn boxed<X: AudioNode<Sample = f48> + Send + Sync + 'static>(node: An<X>) -> Box<Self::Unit> {
+        Box::new(node)
+    }
+}
+
+#[duplicate_item(
+    f48       AudioUnit48;
+    [ f64 ]   [ AudioUnit64 ];
+    [ f32 ]   [ AudioUnit32 ];
+)]
+impl DynAudioUnit<f48> for dyn AudioUnit48 {
+    fn reset(&mut self) {
+        AudioUnit48::reset(self);
+    }
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        AudioUnit48::set_sample_rate(self, sample_rate);
+    }
+    #[inline]
+    fn tick(&mut self, input: &[f48], output: &mut [f48]) {
+        AudioUnit48::tick(self, input, output);
+    }
+    #[inline]
+    fn process(&mut self, size: usize, input: &[&[f48]], output: &mut [&mut [f48]]) {
+        AudioUnit48::process(self, size, input, output);
+    }
+    #[inline]
+    fn inputs(&self) -> usize {
+        AudioUnit48::inputs(self)
+    }
+    #[inline]
+    fn outputs(&self) -> usize {
+        AudioUnit48::outputs(self)
+    }
+    fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
+        AudioUnit48::route(self, input, frequency)
+    }
+    fn get_id(&self) -> u64 {
+        AudioUnit48::get_id(self)
+    }
+    fn ping(&mut self, probe: bool, hash: AttoHash) -> AttoHash {
+        AudioUnit48::ping(self, probe, hash)
+    }
+    fn has_side_effects(&self) -> bool {
+        AudioUnit48::has_side_effects(self)
+    }
+    fn input_kind(&self, input: usize) -> PortKind {
+        AudioUnit48::input_kind(self, input)
+    }
+    fn output_kind(&self, output: usize) -> PortKind {
+        AudioUnit48::output_kind(self, output)
+    }
+    fn input_layout(&self) -> Option<ChannelLayout> {
+        AudioUnit48::input_layout(self)
+    }
+    fn output_layout(&self) -> Option<ChannelLayout> {
+        AudioUnit48::output_layout(self)
+    }
+    fn allocate(&mut self) {
+        AudioUnit48::allocate(self);
+    }
+    fn as_net_mut(&mut self) -> Option<&mut super::net::Net<f48>> {
+        AudioUnit48::as_net_mut(self)
+    }
+    fn clone_unit(&self) -> Box<Self> {
+        dyn_clone::clone_box(self)
+    }
+}
+
+#[duplicate_item(
+    f48       AudioUnit48;
+    [ f64 ]   [ AudioUnit64 ];
+    [ f32 ]   [ AudioUnit32 ];
+)]
+impl<U: DynAudioUnit<f48> + Clone> AudioUnit48 for U {
+    fn reset(&mut self) {
+        DynAudioUnit::reset(self);
+    }
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        DynAudioUnit::set_sample_rate(self, sample_rate);
+    }
+    #[inline]
+    fn tick(&mut self, input: &[f48], output: &mut [f48]) {
+        DynAudioUnit::tick(self, input, output);
+    }
+    #[inline]
+    fn process(&mut self, size: usize, input: &[&[f48]], output: &mut [&mut [f48]]) {
+        DynAudioUnit::process(self, size, input, output);
+    }
+    #[inline]
+    fn inputs(&self) -> usize {
+        DynAudioUnit::inputs(self)
+    }
+    #[inline]
+    fn outputs(&self) -> usize {
+        DynAudioUnit::outputs(self)
+    }
+    fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
+        DynAudioUnit::route(self, input, frequency)
+    }
+    fn get_id(&self) -> u64 {
+        DynAudioUnit::get_id(self)
+    }
+    fn ping(&mut self, probe: bool, hash: AttoHash) -> AttoHash {
+        DynAudioUnit::ping(self, probe, hash)
+    }
+    fn has_side_effects(&self) -> bool {
+        DynAudioUnit::has_side_effects(self)
+    }
+    fn input_kind(&self, input: usize) -> PortKind {
+        DynAudioUnit::input_kind(self, input)
+    }
+    fn output_kind(&self, output: usize) -> PortKind {
+        DynAudioUnit::output_kind(self, output)
+    }
+    fn input_layout(&self) -> Option<ChannelLayout> {
+        DynAudioUnit::input_layout(self)
+    }
+    fn output_layout(&self) -> Option<ChannelLayout> {
+        DynAudioUnit::output_layout(self)
+    }
+    fn footprint(&self) -> usize {
+        std::mem::size_of::<Self>()
+    }
+    fn allocate(&mut self) {
+        DynAudioUnit::allocate(self);
+    }
+    fn as_net_mut(&mut self) -> Option<&mut super::net::Net<f48>> {
+        DynAudioUnit::as_net_mut(self)
+    }
+}
+
 #[duplicate_item(
     f48       AudioUnit48;
     [ f64 ]   [ AudioUnit64 ];
@@ -405,6 +726,25 @@ where
     fn ping(&mut self, probe: bool, hash: AttoHash) -> AttoHash {
         self.0.ping(probe, hash)
     }
+    fn has_side_effects(&self) -> bool {
+        self.0.has_side_effects()
+    }
+
+    fn input_kind(&self, input: usize) -> PortKind {
+        self.0.input_kind(input)
+    }
+
+    fn output_kind(&self, output: usize) -> PortKind {
+        self.0.output_kind(output)
+    }
+
+    fn input_layout(&self) -> Option<ChannelLayout> {
+        self.0.input_layout()
+    }
+
+    fn output_layout(&self) -> Option<ChannelLayout> {
+        self.0.output_layout()
+    }
     fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
         self.0.route(input, frequency)
     }
@@ -527,6 +867,25 @@ impl AudioUnit48 for BigBlockAdapter48 {
     fn ping(&mut self, probe: bool, hash: AttoHash) -> AttoHash {
         self.source.ping(probe, hash)
     }
+    fn has_side_effects(&self) -> bool {
+        self.source.has_side_effects()
+    }
+
+    fn input_kind(&self, input: usize) -> PortKind {
+        self.source.input_kind(input)
+    }
+
+    fn output_kind(&self, output: usize) -> PortKind {
+        self.source.output_kind(output)
+    }
+
+    fn input_layout(&self) -> Option<ChannelLayout> {
+        self.source.input_layout()
+    }
+
+    fn output_layout(&self) -> Option<ChannelLayout> {
+        self.source.output_layout()
+    }
     fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
         self.source.route(input, frequency)
     }
@@ -632,6 +991,25 @@ impl AudioUnit48 for BlockRateAdapter48 {
     fn ping(&mut self, probe: bool, hash: AttoHash) -> AttoHash {
         self.unit.ping(probe, hash)
     }
+    fn has_side_effects(&self) -> bool {
+        self.unit.has_side_effects()
+    }
+
+    fn input_kind(&self, input: usize) -> PortKind {
+        self.unit.input_kind(input)
+    }
+
+    fn output_kind(&self, output: usize) -> PortKind {
+        self.unit.output_kind(output)
+    }
+
+    fn input_layout(&self) -> Option<ChannelLayout> {
+        self.unit.input_layout()
+    }
+
+    fn output_layout(&self) -> Option<ChannelLayout> {
+        self.unit.output_layout()
+    }
     fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
         self.unit.route(input, frequency)
     }
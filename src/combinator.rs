@@ -9,6 +9,7 @@ use super::signal::*;
 use super::*;
 use duplicate::duplicate_item;
 use numeric_array::typenum::*;
+use std::any::Any;
 
 /// Trait for multi-channel constants.
 pub trait ConstantFrame: Clone + Sync + Send {
@@ -250,6 +251,22 @@ impl<X: AudioNode> An<X> {
     pub fn filter_stereo(&mut self, x: X::Sample, y: X::Sample) -> (X::Sample, X::Sample) {
         self.0.filter_stereo(x, y)
     }
+    /// Call `f` with each contained node of type `Y` tagged with `tag` (see the `tagged` opcode).
+    /// Nodes of other types are skipped.
+    ///
+    /// ### Example
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let mut node = tagged(1, dc(1.0)) >> lowpass_hz(1000.0, 1.0);
+    /// node.visit_tagged(1, |x: &mut Constant<U1, f64>| x.set(Frame::from([2.0])));
+    /// ```
+    pub fn visit_tagged<Y: AudioNode + 'static>(&mut self, tag: u64, mut f: impl FnMut(&mut Y)) {
+        self.0.visit(tag, &mut |node: &mut dyn Any| {
+            if let Some(node) = node.downcast_mut::<Y>() {
+                f(node);
+            }
+        });
+    }
 }
 
 /// `-` unary operator: Negates node outputs. Any node can be negated.
@@ -0,0 +1,129 @@
+//! Unit conversion components.
+
+use super::audionode::*;
+use super::math::*;
+use super::signal::*;
+use super::*;
+use numeric_array::typenum::*;
+use std::marker::PhantomData;
+
+/// Unit conversions for control signals.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Conversion {
+    /// MIDI note number to frequency in Hz.
+    MidiToHz,
+    /// Frequency in Hz to fractional MIDI note number.
+    HzToMidi,
+    /// Decibels to amplitude gain.
+    DbToAmp,
+    /// Amplitude gain to decibels.
+    AmpToDb,
+    /// Tempo in BPM to the rate in Hz of notes lasting the given division of a whole note.
+    /// For example, 0.25 is a quarter note.
+    BpmToHz(f64),
+}
+
+impl Conversion {
+    /// Convert `x`.
+    #[inline]
+    pub fn convert<T: Real>(&self, x: T) -> T {
+        match self {
+            Conversion::MidiToHz => midi_hz(x),
+            Conversion::HzToMidi => hz_midi(x),
+            Conversion::DbToAmp => db_amp(x),
+            Conversion::AmpToDb => amp_db(x),
+            // A whole note lasts four beats.
+            Conversion::BpmToHz(division) => bpm_hz(x) / T::from_f64(4.0 * division),
+        }
+    }
+
+    /// Kind of signal expected at the input.
+    pub fn input_kind(&self) -> PortKind {
+        match self {
+            Conversion::HzToMidi => PortKind::Frequency,
+            Conversion::AmpToDb => PortKind::Gain,
+            _ => PortKind::Control,
+        }
+    }
+
+    /// Kind of signal produced at the output.
+    pub fn output_kind(&self) -> PortKind {
+        match self {
+            Conversion::MidiToHz | Conversion::BpmToHz(_) => PortKind::Frequency,
+            Conversion::DbToAmp => PortKind::Gain,
+            _ => PortKind::Control,
+        }
+    }
+}
+
+/// Unit converter for control signals.
+/// Unlike a `map` closure, it propagates constants in signal flow analysis.
+/// - Input 0: value to convert
+/// - Output 0: converted value
+#[derive(Clone)]
+pub struct Convert<T: Real> {
+    _marker: PhantomData<T>,
+    conversion: Conversion,
+}
+
+impl<T: Real> Convert<T> {
+    /// Create new unit converter.
+    pub fn new(conversion: Conversion) -> Self {
+        if let Conversion::BpmToHz(division) = conversion {
+            assert!(division > 0.0);
+        }
+        Self {
+            _marker: PhantomData,
+            conversion,
+        }
+    }
+
+    /// Unit conversion.
+    pub fn conversion(&self) -> Conversion {
+        self.conversion
+    }
+}
+
+impl<T: Real> AudioNode for Convert<T> {
+    const ID: u64 = 118;
+    type Sample = T;
+    type Inputs = U1;
+    type Outputs = U1;
+    type Setting = ();
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        [self.conversion.convert(input[0])].into()
+    }
+
+    fn process(
+        &mut self,
+        size: usize,
+        input: &[&[Self::Sample]],
+        output: &mut [&mut [Self::Sample]],
+    ) {
+        for (y, x) in output[0][..size].iter_mut().zip(input[0][..size].iter()) {
+            *y = self.conversion.convert(*x);
+        }
+    }
+
+    fn input_kind(&self, _input: usize) -> PortKind {
+        self.conversion.input_kind()
+    }
+
+    fn output_kind(&self, _output: usize) -> PortKind {
+        self.conversion.output_kind()
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        output[0] = match input[0] {
+            Signal::Value(x) => Signal::Value(self.conversion.convert(x)),
+            x => x.distort(0.0),
+        };
+        output
+    }
+}
@@ -0,0 +1,212 @@
+//! Convolution components.
+
+use super::audionode::*;
+use super::math::*;
+use super::signal::*;
+use super::*;
+use num_complex::Complex64;
+use numeric_array::typenum::*;
+use rustfft::algorithm::Radix4;
+use rustfft::Fft;
+use rustfft::FftDirection;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+/// Length of impulse response partitions in samples.
+const PARTITION: usize = 64;
+
+/// Zero latency convolver with a uniformly partitioned FFT algorithm.
+/// The first partition of the impulse response is convolved directly
+/// in the time domain, the rest are convolved in the frequency domain
+/// one block at a time.
+/// The impulse response is assumed to be at the sample rate of the node.
+/// Allocates: impulse response spectra and input history.
+/// - Input 0: input
+/// - Output 0: convolved input
+#[derive(Clone)]
+pub struct Convolver<T: Float> {
+    /// The complete impulse response, used for frequency response analysis.
+    impulse: Vec<f64>,
+    /// History of recent inputs for direct convolution (ring buffer).
+    history: Vec<f64>,
+    /// Index of the latest input in the history.
+    history_i: usize,
+    /// Spectra of impulse response partitions after the first one.
+    partitions: Vec<Vec<Complex64>>,
+    /// Frequency domain delay line of input block spectra.
+    spectra: Vec<Vec<Complex64>>,
+    /// Index of the latest input block spectrum.
+    spectrum_i: usize,
+    /// Previous and current input blocks.
+    block: Vec<f64>,
+    /// Frequency domain convolution result for the current block.
+    tail: Vec<f64>,
+    /// Position inside the current block.
+    j: usize,
+    forward: Arc<dyn Fft<f64>>,
+    inverse: Arc<dyn Fft<f64>>,
+    work: Vec<Complex64>,
+    accumulator: Vec<Complex64>,
+    scratch: Vec<Complex64>,
+    /// Latency of the impulse response in samples, reported in signal flow analysis.
+    latency: f64,
+    sample_rate: f64,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Float> Convolver<T> {
+    /// Create a new convolver from `impulse` response.
+    pub fn new(impulse: &[T]) -> Self {
+        let impulse: Vec<f64> = impulse.iter().map(|x| x.to_f64()).collect();
+        let forward: Arc<dyn Fft<f64>> =
+            Arc::new(Radix4::new(PARTITION * 2, FftDirection::Forward));
+        let inverse: Arc<dyn Fft<f64>> =
+            Arc::new(Radix4::new(PARTITION * 2, FftDirection::Inverse));
+        let mut partitions = Vec::new();
+        let mut chunks = impulse.chunks(PARTITION);
+        chunks.next();
+        for chunk in chunks {
+            let mut spectrum = vec![Complex64::default(); PARTITION * 2];
+            for (x, y) in spectrum.iter_mut().zip(chunk.iter()) {
+                *x = Complex64::new(*y, 0.0);
+            }
+            forward.process(&mut spectrum);
+            partitions.push(spectrum);
+        }
+        let scratch_length = std::cmp::max(
+            forward.get_inplace_scratch_len(),
+            inverse.get_inplace_scratch_len(),
+        );
+        Self {
+            history: vec![0.0; PARTITION],
+            history_i: 0,
+            spectra: vec![vec![Complex64::default(); PARTITION * 2]; partitions.len()],
+            partitions,
+            spectrum_i: 0,
+            block: vec![0.0; PARTITION * 2],
+            tail: vec![0.0; PARTITION],
+            j: 0,
+            forward,
+            inverse,
+            work: vec![Complex64::default(); PARTITION * 2],
+            accumulator: vec![Complex64::default(); PARTITION * 2],
+            scratch: vec![Complex64::default(); scratch_length],
+            latency: 0.0,
+            sample_rate: DEFAULT_SR,
+            impulse,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Length of the impulse response in samples.
+    pub fn length(&self) -> usize {
+        self.impulse.len()
+    }
+
+    /// Set latency of the impulse response in samples for signal flow analysis,
+    /// for example, the group delay of a linear phase filter. The default is zero.
+    pub fn set_latency(&mut self, latency: f64) {
+        self.latency = latency;
+    }
+
+    /// Convolve the latest input block with the impulse response tail.
+    fn process_block(&mut self) {
+        for (x, y) in self.work.iter_mut().zip(self.block.iter()) {
+            *x = Complex64::new(*y, 0.0);
+        }
+        self.forward
+            .process_with_scratch(&mut self.work, &mut self.scratch);
+        let n = self.spectra.len();
+        self.spectrum_i = (self.spectrum_i + 1) % n;
+        self.spectra[self.spectrum_i].copy_from_slice(&self.work);
+        self.accumulator.fill(Complex64::default());
+        for (p, partition) in self.partitions.iter().enumerate() {
+            let spectrum = &self.spectra[(self.spectrum_i + n - p) % n];
+            for ((a, x), h) in self
+                .accumulator
+                .iter_mut()
+                .zip(spectrum.iter())
+                .zip(partition.iter())
+            {
+                *a += x * h;
+            }
+        }
+        self.inverse
+            .process_with_scratch(&mut self.accumulator, &mut self.scratch);
+        let z = 1.0 / (PARTITION * 2) as f64;
+        for (y, x) in self
+            .tail
+            .iter_mut()
+            .zip(self.accumulator[PARTITION..].iter())
+        {
+            *y = x.re * z;
+        }
+        self.block.copy_within(PARTITION.., 0);
+    }
+}
+
+impl<T: Float> AudioNode for Convolver<T> {
+    const ID: u64 = 80;
+    type Sample = T;
+    type Inputs = U1;
+    type Outputs = U1;
+    type Setting = ();
+
+    fn reset(&mut self) {
+        self.history.fill(0.0);
+        self.history_i = 0;
+        for spectrum in self.spectra.iter_mut() {
+            spectrum.fill(Complex64::default());
+        }
+        self.spectrum_i = 0;
+        self.block.fill(0.0);
+        self.tail.fill(0.0);
+        self.j = 0;
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate;
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let x = input[0].to_f64();
+        let mask = PARTITION - 1;
+        self.history_i = (self.history_i + 1) & mask;
+        self.history[self.history_i] = x;
+        let mut output = self.tail[self.j];
+        for (i, h) in self.impulse[..min(PARTITION, self.impulse.len())]
+            .iter()
+            .enumerate()
+        {
+            output += h * self.history[(self.history_i.wrapping_sub(i)) & mask];
+        }
+        self.block[PARTITION + self.j] = x;
+        self.j += 1;
+        if self.j == PARTITION {
+            self.j = 0;
+            if !self.partitions.is_empty() {
+                self.process_block();
+            }
+        }
+        [convert(output)].into()
+    }
+
+    fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        output[0] = input[0].filter(self.latency, |r| {
+            let z1 = Complex64::from_polar(1.0, -TAU * frequency / self.sample_rate);
+            let mut z = Complex64::new(1.0, 0.0);
+            let mut x = Complex64::default();
+            for h in self.impulse.iter() {
+                x += *h * z;
+                z *= z1;
+            }
+            r * x
+        });
+        output
+    }
+}
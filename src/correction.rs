@@ -0,0 +1,144 @@
+//! Room, loudspeaker and headphone correction from measured responses.
+
+use super::math::*;
+use std::io::{Error, ErrorKind, Result};
+use std::path::Path;
+
+/// Measured magnitude response of a loudspeaker or headphone.
+/// It consists of (frequency Hz, level dB) points in ascending order of frequency.
+///
+/// ### Example: Correcting A Treble Peak
+/// ```
+/// use fundsp::hacker::*;
+/// let text = "* Measurement data\n* Freq(Hz) SPL(dB) Phase(degrees)\n\
+///     20 80 0\n1000 80 0\n4000 86 0\n20000 80 0\n";
+/// let measurement = Measurement::from_text(text).unwrap();
+/// let correction = measurement.correction(20.0, 20000.0, 6.0);
+/// let gain = correction.iter().find(|(f, _)| *f >= 4000.0).unwrap().1;
+/// assert!(gain < 0.7);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Measurement {
+    /// Measured (frequency Hz, level dB) in ascending order of frequency.
+    points: Vec<(f64, f64)>,
+}
+
+/// Parse error in a measurement file.
+fn invalid(message: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, message)
+}
+
+impl Measurement {
+    /// Create measurement from (frequency Hz, level dB) `points`.
+    /// Points are sorted by frequency and points at or below 0 Hz are ignored.
+    pub fn new(points: &[(f64, f64)]) -> Self {
+        let mut points: Vec<(f64, f64)> = points.iter().copied().filter(|p| p.0 > 0.0).collect();
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Self { points }
+    }
+
+    /// Parse a measurement in text format, such as CSV or a REW text export.
+    /// Each data line starts with frequency in Hz and level in dB, separated by
+    /// commas, semicolons, tabs or spaces. Any further columns, such as phase, are ignored.
+    /// Comment lines starting with `*`, `#` or `;` and header lines are skipped.
+    pub fn from_text(text: &str) -> Result<Self> {
+        let mut points = Vec::new();
+        for line in text.lines().map(|line| line.trim()) {
+            if line.is_empty() || line.starts_with(['*', '#', ';']) {
+                continue;
+            }
+            let mut fields = line
+                .split([',', ';', '\t', ' '])
+                .filter(|field| !field.is_empty());
+            // Lines that do not start with a number are column headers.
+            let frequency: f64 = match fields.next().map(|field| field.parse()) {
+                Some(Ok(frequency)) => frequency,
+                _ => continue,
+            };
+            let level: f64 = fields
+                .next()
+                .and_then(|field| field.parse().ok())
+                .ok_or_else(|| invalid("Missing level."))?;
+            points.push((frequency, level));
+        }
+        let measurement = Self::new(&points);
+        if measurement.points.is_empty() {
+            return Err(invalid("No measurement data."));
+        }
+        Ok(measurement)
+    }
+
+    /// Load a measurement from a text file. See `from_text` for the format.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_text(&std::fs::read_to_string(path)?)
+    }
+
+    /// Measured (frequency Hz, level dB) points in ascending order of frequency.
+    pub fn points(&self) -> &[(f64, f64)] {
+        &self.points
+    }
+
+    /// Measured level in dB at `frequency` Hz. Levels are interpolated
+    /// linearly in log frequency and held constant beyond the measured range.
+    pub fn level_db(&self, frequency: f64) -> f64 {
+        let i = self.points.partition_point(|p| p.0 <= frequency);
+        if self.points.is_empty() {
+            0.0
+        } else if i == 0 {
+            self.points[0].1
+        } else if i == self.points.len() {
+            self.points[i - 1].1
+        } else {
+            let (f0, l0) = self.points[i - 1];
+            let (f1, l1) = self.points[i];
+            lerp(l0, l1, log2(frequency / f0) / log2(f1 / f0))
+        }
+    }
+
+    /// Return a copy of the measurement smoothed over a window
+    /// that is `octaves` wide, for example, 1/6 octave.
+    pub fn smoothed(&self, octaves: f64) -> Self {
+        let half = octaves * 0.5;
+        let points = self
+            .points
+            .iter()
+            .map(|&(f, _)| {
+                let (sum, count) = self
+                    .points
+                    .iter()
+                    .filter(|p| abs(log2(p.0 / f)) <= half)
+                    .fold((0.0, 0.0), |(sum, count), p| (sum + p.1, count + 1.0));
+                (f, sum / count)
+            })
+            .collect();
+        Self { points }
+    }
+
+    /// Average level in dB between `low` and `high` Hz, weighted evenly per octave.
+    pub fn average_db(&self, low: f64, high: f64) -> f64 {
+        let steps = max(1, round(log2(high / low) * 24.0) as usize);
+        (0..=steps)
+            .map(|i| self.level_db(low * pow(high / low, i as f64 / steps as f64)))
+            .sum::<f64>()
+            / (steps + 1) as f64
+    }
+
+    /// Correction response that flattens the measurement between `low` and `high` Hz,
+    /// as (frequency Hz, amplitude gain) points for `fir_from_response`.
+    /// Correction is relative to the average level in the band.
+    /// Boosts are limited to `max_boost` dB and cuts to 24 dB.
+    /// Outside the band, the correction fades to unity gain within half an octave.
+    pub fn correction(&self, low: f64, high: f64, max_boost: f64) -> Vec<(f64, f64)> {
+        assert!(low > 0.0 && high > low);
+        let reference = self.average_db(low, high);
+        let steps = max(1, round(log2(high / low) * 24.0) as usize);
+        let mut points = vec![(low / SQRT_2, 1.0)];
+        for i in 0..=steps {
+            let f = low * pow(high / low, i as f64 / steps as f64);
+            let gain = clamp(-24.0, max_boost, reference - self.level_db(f));
+            points.push((f, db_amp(gain)));
+        }
+        points.push((high * SQRT_2, 1.0));
+        points
+    }
+}
@@ -134,7 +134,22 @@ impl<T: Float> AudioNode for Delay<T> {
     }
 }
 
-/// Variable delay line using cubic interpolation.
+/// Interpolation strategy of a variable delay line.
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub enum Interpolation {
+    /// Catmull-Rom cubic spline. A good all-round choice.
+    #[default]
+    Cubic,
+    /// 4-point Lagrange polynomial. Smooth when the delay is modulated,
+    /// for example, in chorus and flanger effects, at the cost of some high frequency loss.
+    Lagrange,
+    /// First order Thiran allpass. Flat magnitude response and accurate phase delay
+    /// for physical models, but transients appear if the delay is changed quickly.
+    /// Delays shorter than 1.5 samples are lengthened to 1.5 samples.
+    Thiran,
+}
+
+/// Variable delay line using cubic, Lagrange or Thiran allpass interpolation.
 /// The number of taps is `N`.
 /// Allocates: the delay line.
 /// - Input 0: input
@@ -152,6 +167,9 @@ where
     sample_rate: T,
     min_delay: T,
     max_delay: T,
+    interpolation: Interpolation,
+    /// Thiran allpass state (previous input, previous output) for each tap.
+    allpass: Vec<(T, T)>,
     _marker: PhantomData<N>,
 }
 
@@ -171,11 +189,33 @@ where
             sample_rate: T::zero(),
             min_delay,
             max_delay,
+            interpolation: Interpolation::Cubic,
+            allpass: vec![(T::zero(), T::zero()); N::USIZE],
             _marker: PhantomData::default(),
         };
         node.set_sample_rate(DEFAULT_SR);
         node
     }
+
+    /// Create a tapped delay line with the given interpolation strategy.
+    /// Minimum and maximum delays are specified in seconds.
+    pub fn with_interpolation(min_delay: T, max_delay: T, interpolation: Interpolation) -> Self {
+        let mut node = Self::new(min_delay, max_delay);
+        node.interpolation = interpolation;
+        node
+    }
+
+    /// Interpolation strategy.
+    #[inline]
+    pub fn interpolation(&self) -> Interpolation {
+        self.interpolation
+    }
+
+    /// Set interpolation strategy. Resets Thiran allpass state.
+    pub fn set_interpolation(&mut self, interpolation: Interpolation) {
+        self.interpolation = interpolation;
+        self.allpass.fill((T::zero(), T::zero()));
+    }
 }
 
 impl<N, T> AudioNode for Tap<N, T>
@@ -193,6 +233,7 @@ where
     fn reset(&mut self) {
         self.i = 0;
         self.buffer.fill(T::zero());
+        self.allpass.fill((T::zero(), T::zero()));
     }
 
     fn set_sample_rate(&mut self, sample_rate: f64) {
@@ -216,6 +257,295 @@ where
         for tap_i in 1..N::USIZE + 1 {
             let tap =
                 clamp(self.min_delay, self.max_delay, convert(input[tap_i])) * self.sample_rate;
+            if self.interpolation == Interpolation::Thiran {
+                // Read at integer delay `m` and let the allpass supply the rest, 0.5...1.5 samples.
+                let tap = max(T::from_f64(1.5), tap);
+                let m =
+                    unsafe { f32::to_int_unchecked::<usize>((tap - T::from_f64(0.5)).to_f32()) };
+                let d = tap - T::new(m as i64);
+                let a = (T::one() - d) / (T::one() + d);
+                let x = self.buffer[(self.i + self.buffer.len() - m) & mask];
+                let (x1, y1) = self.allpass[tap_i - 1];
+                let y = a * (x - y1) + x1;
+                self.allpass[tap_i - 1] = (x, y);
+                output += y;
+                continue;
+            }
+            let tap_floor = unsafe { f32::to_int_unchecked::<usize>(tap.to_f32()) };
+            let tap_i1 = self.i + (self.buffer.len() - tap_floor);
+            let tap_i0 = (tap_i1 + 1) & mask;
+            let tap_i2 = (tap_i1.wrapping_sub(1)) & mask;
+            let tap_i3 = (tap_i1.wrapping_sub(2)) & mask;
+            let tap_i1 = tap_i1 & mask;
+            let tap_d = tap - T::new(tap_floor as i64);
+            let y0 = self.buffer[tap_i0];
+            let y1 = self.buffer[tap_i1];
+            let y2 = self.buffer[tap_i2];
+            let y3 = self.buffer[tap_i3];
+            output += match self.interpolation {
+                Interpolation::Lagrange => lagrange(y0, y1, y2, y3, tap_d),
+                _ => spline(y0, y1, y2, y3, tap_d),
+            };
+        }
+        self.buffer[self.i] = input[0];
+        self.i = (self.i + 1) & mask;
+        [output].into()
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        output[0] = input[0].distort(self.min_delay.to_f64() * self.sample_rate.to_f64());
+        output
+    }
+}
+
+/// String ensemble effect in the style of bucket brigade ensemble units.
+/// A number of voices read from a shared delay line, each modulated
+/// by a slow and a fast sine LFO with phases spread evenly across voices.
+/// The delayed signal is darkened with a one-pole lowpass filter.
+/// Allocates: the delay line.
+/// Setting: (depth, rate).
+/// - Input 0: input
+/// - Output 0: ensemble output, including original signal
+#[derive(Clone)]
+pub struct Ensemble<T: Real> {
+    buffer: Vec<T>,
+    i: usize,
+    sample_rate: T,
+    voices: usize,
+    depth: T,
+    rate: T,
+    slow_phase: T,
+    fast_phase: T,
+    tone: T,
+    tone_coeff: T,
+}
+
+impl<T: Real> Ensemble<T> {
+    /// Base delay in seconds, before modulation.
+    const BASE_DELAY: f64 = 0.005;
+    /// Cutoff frequency of the tone filter in Hz.
+    const TONE_CUTOFF: f64 = 7000.0;
+    /// Frequency ratio of the fast LFO to the slow LFO.
+    const FAST_RATIO: f64 = 9.0;
+    /// Amplitude of the fast LFO relative to the slow LFO.
+    const FAST_AMOUNT: f64 = 0.2;
+
+    /// Create a new ensemble with the given number of `voices`,
+    /// modulation `depth` in seconds and slow LFO `rate` in Hz.
+    pub fn new(voices: usize, depth: T, rate: T) -> Self {
+        assert!(voices > 0);
+        assert!(depth >= T::zero());
+        let mut node = Ensemble {
+            buffer: vec![],
+            i: 0,
+            sample_rate: T::zero(),
+            voices,
+            depth,
+            rate,
+            slow_phase: T::zero(),
+            fast_phase: T::zero(),
+            tone: T::zero(),
+            tone_coeff: T::zero(),
+        };
+        node.set_sample_rate(DEFAULT_SR);
+        node
+    }
+
+    /// Set modulation `depth` in seconds and slow LFO `rate` in Hz.
+    /// The delay line is not reallocated: depth is limited
+    /// to what fits in the line allocated for the original depth.
+    pub fn set_modulation(&mut self, depth: T, rate: T) {
+        assert!(depth >= T::zero());
+        let capacity = T::new((self.buffer.len() - 2) as i64) / self.sample_rate
+            - T::from_f64(Self::BASE_DELAY);
+        self.depth = min(depth, capacity);
+        self.rate = rate;
+    }
+}
+
+impl<T: Real> AudioNode for Ensemble<T> {
+    const ID: u64 = 79;
+    type Sample = T;
+    type Inputs = U1;
+    type Outputs = U1;
+    type Setting = (T, T);
+
+    fn set(&mut self, setting: Self::Setting) {
+        self.set_modulation(setting.0, setting.1);
+    }
+
+    fn reset(&mut self) {
+        self.i = 0;
+        self.buffer.fill(T::zero());
+        self.slow_phase = T::zero();
+        self.fast_phase = T::zero();
+        self.tone = T::zero();
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        let sample_rate = T::from_f64(sample_rate);
+        if self.sample_rate != sample_rate {
+            let max_delay = T::from_f64(Self::BASE_DELAY) + self.depth;
+            let buffer_length = ceil(max_delay * sample_rate) + T::new(2);
+            let buffer_length = (buffer_length.to_f64() as usize).next_power_of_two();
+            self.sample_rate = sample_rate;
+            self.tone_coeff = T::one() - exp(T::from_f64(-TAU * Self::TONE_CUTOFF) / sample_rate);
+            self.buffer.resize(buffer_length, T::zero());
+            self.reset();
+        }
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let mask = self.buffer.len() - 1;
+        self.buffer[self.i] = input[0];
+        let base = T::from_f64(Self::BASE_DELAY);
+        let fast_amount = T::from_f64(Self::FAST_AMOUNT);
+        let slow_amount = T::one() - fast_amount;
+        let tau = T::from_f64(TAU);
+        let mut wet = T::zero();
+        for voice in 0..self.voices {
+            let offset = T::new(voice as i64) / T::new(self.voices as i64);
+            let lfo = slow_amount * sin((self.slow_phase + offset) * tau)
+                + fast_amount * sin((self.fast_phase + offset) * tau);
+            let tap = (base + self.depth * (T::one() + lfo) * T::from_f64(0.5)) * self.sample_rate;
+            let tap_floor = unsafe { f32::to_int_unchecked::<usize>(tap.to_f32()) };
+            let tap_d = tap - T::new(tap_floor as i64);
+            let i0 = (self.i + self.buffer.len() - tap_floor) & mask;
+            let i1 = (i0 + self.buffer.len() - 1) & mask;
+            wet += lerp(self.buffer[i0], self.buffer[i1], tap_d);
+        }
+        wet /= T::new(self.voices as i64);
+        self.tone += (wet - self.tone) * self.tone_coeff;
+        let rate = self.rate / self.sample_rate;
+        self.slow_phase += rate;
+        self.slow_phase -= floor(self.slow_phase);
+        self.fast_phase += rate * T::from_f64(Self::FAST_RATIO);
+        self.fast_phase -= floor(self.fast_phase);
+        self.i = (self.i + 1) & mask;
+        [(input[0] + self.tone) * T::from_f64(0.5)].into()
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        output[0] = input[0].distort(0.0);
+        output
+    }
+}
+
+/// Mono chorus with 4 delayed voices mixed with the original signal.
+/// Voice `k` (1...4) is delayed by `k` times the base separation plus a variation
+/// modulated by smooth noise at a slightly different frequency for each voice.
+/// Allocates: the delay line.
+/// Setting: (separation, variation, modulation frequency).
+/// - Input 0: input
+/// - Output 0: chorus output, including original signal
+#[derive(Clone)]
+pub struct Chorus<T: Real> {
+    buffer: Vec<T>,
+    i: usize,
+    sample_rate: T,
+    separation: T,
+    variation: T,
+    mod_frequency: T,
+    /// Noise seed of each voice.
+    seed: [i64; 4],
+    /// Noise position of each voice.
+    phase: [f64; 4],
+}
+
+impl<T: Real> Chorus<T> {
+    /// Number of delayed voices.
+    const VOICES: usize = 4;
+    /// Modulation frequency offset between consecutive voices in Hz.
+    const FREQUENCY_SPREAD: f64 = 0.02;
+
+    /// Create a new chorus. `seed` is the LFO seed, `separation` is the base voice separation
+    /// in seconds, `variation` is the delay variation in seconds and
+    /// `mod_frequency` is the delay modulation frequency in Hz.
+    pub fn new(seed: i64, separation: T, variation: T, mod_frequency: T) -> Self {
+        assert!(separation >= T::zero());
+        assert!(variation >= T::zero());
+        let mut node = Chorus {
+            buffer: vec![],
+            i: 0,
+            sample_rate: T::zero(),
+            separation,
+            variation,
+            mod_frequency,
+            seed: [
+                seed,
+                hash(seed),
+                hash(seed ^ 0xfedcba),
+                hash(seed ^ 0xfedcb),
+            ],
+            phase: [0.0; 4],
+        };
+        node.set_sample_rate(DEFAULT_SR);
+        node
+    }
+
+    /// Set base voice `separation` and delay `variation` in seconds and
+    /// delay modulation frequency `mod_frequency` in Hz. The delay line is not reallocated:
+    /// delays are limited to what fits in the line allocated for the original parameters.
+    pub fn set_parameters(&mut self, separation: T, variation: T, mod_frequency: T) {
+        assert!(separation >= T::zero());
+        assert!(variation >= T::zero());
+        self.separation = separation;
+        self.variation = variation;
+        self.mod_frequency = mod_frequency;
+    }
+}
+
+impl<T: Real> AudioNode for Chorus<T> {
+    const ID: u64 = 103;
+    type Sample = T;
+    type Inputs = U1;
+    type Outputs = U1;
+    type Setting = (T, T, T);
+
+    fn set(&mut self, setting: Self::Setting) {
+        self.set_parameters(setting.0, setting.1, setting.2);
+    }
+
+    fn reset(&mut self) {
+        self.i = 0;
+        self.buffer.fill(T::zero());
+        self.phase = [0.0; 4];
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        let sample_rate = T::from_f64(sample_rate);
+        if self.sample_rate != sample_rate {
+            let max_delay = self.separation * T::new(Self::VOICES as i64) + self.variation;
+            let buffer_length = ceil(max_delay * sample_rate) + T::new(3);
+            let buffer_length = (buffer_length.to_f64() as usize).next_power_of_two();
+            self.sample_rate = sample_rate;
+            self.buffer.resize(buffer_length, T::zero());
+            self.reset();
+        }
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let mask = self.buffer.len() - 1;
+        let max_tap = T::new(self.buffer.len() as i64 - 3);
+        let mut output = input[0];
+        for voice in 0..Self::VOICES {
+            let separation = self.separation * T::new(voice as i64 + 1);
+            let delay = lerp11(
+                separation,
+                separation + self.variation,
+                T::from_f64(spline_noise(self.seed[voice], self.phase[voice])),
+            );
+            let tap = clamp(T::zero(), max_tap, delay * self.sample_rate);
             let tap_floor = unsafe { f32::to_int_unchecked::<usize>(tap.to_f32()) };
             let tap_i1 = self.i + (self.buffer.len() - tap_floor);
             let tap_i0 = (tap_i1 + 1) & mask;
@@ -230,15 +560,174 @@ where
                 self.buffer[tap_i3],
                 tap_d,
             );
+            self.phase[voice] += (self.mod_frequency.to_f64()
+                + Self::FREQUENCY_SPREAD * voice as f64)
+                / self.sample_rate.to_f64();
         }
         self.buffer[self.i] = input[0];
         self.i = (self.i + 1) & mask;
+        [output * T::from_f64(0.2)].into()
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        output[0] = input[0].distort(0.0);
+        output
+    }
+}
+
+/// Tempo synchronized delay. The delay time is a note `division` of a whole note
+/// (for example, 0.25 for a quarter note or 0.375 for a dotted eighth)
+/// at the tempo given in beats per minute (quarter notes per minute) in the tempo input.
+/// When the delay time changes, the read head crossfades to the new position.
+/// Allocates: the delay line.
+/// Setting: division.
+/// - Input 0: input
+/// - Input 1: tempo (BPM)
+/// - Output 0: delayed input
+#[derive(Clone)]
+pub struct DelaySync<T: Real> {
+    buffer: Vec<T>,
+    i: usize,
+    sample_rate: f64,
+    division: f64,
+    /// Delay of the current read head in samples.
+    head: usize,
+    /// Delay of the read head being faded in, in samples.
+    next_head: usize,
+    /// Crossfade position in 0...1. Crossfade is finished at 1.
+    fade: T,
+    /// Crossfade increment per sample.
+    fade_step: T,
+    /// Whether the read head has been placed since reset.
+    primed: bool,
+}
+
+impl<T: Real> DelaySync<T> {
+    /// Slowest supported tempo in BPM. Determines the length of the delay line.
+    pub const MIN_TEMPO: f64 = 20.0;
+    /// Fastest supported tempo in BPM.
+    pub const MAX_TEMPO: f64 = 1000.0;
+    /// Crossfade time of read head changes in seconds.
+    const FADE_TIME: f64 = 0.05;
+
+    /// Create a new tempo synchronized delay. `division` is the delay time
+    /// as a fraction of a whole note.
+    pub fn new(division: f64) -> Self {
+        assert!(division > 0.0);
+        let mut node = DelaySync {
+            buffer: vec![],
+            i: 0,
+            sample_rate: 0.0,
+            division,
+            head: 1,
+            next_head: 1,
+            fade: T::one(),
+            fade_step: T::zero(),
+            primed: false,
+        };
+        node.set_sample_rate(DEFAULT_SR);
+        node
+    }
+
+    /// Delay time as a fraction of a whole note.
+    #[inline]
+    pub fn division(&self) -> f64 {
+        self.division
+    }
+
+    /// Set delay time as a fraction of a whole note. The delay line is not reallocated:
+    /// at slow tempos, delays longer than the original division allows are clamped.
+    pub fn set_division(&mut self, division: f64) {
+        assert!(division > 0.0);
+        self.division = division;
+    }
+
+    /// Delay in samples at `tempo` BPM.
+    #[inline]
+    fn delay_samples(&self, tempo: f64) -> usize {
+        let tempo = clamp(Self::MIN_TEMPO, Self::MAX_TEMPO, tempo);
+        let delay = round(240.0 * self.division / tempo * self.sample_rate) as usize;
+        clamp(1, self.buffer.len() - 1, delay)
+    }
+
+    #[inline]
+    fn read(&self, delay: usize) -> T {
+        self.buffer[(self.i + self.buffer.len() - delay) % self.buffer.len()]
+    }
+}
+
+impl<T: Real> AudioNode for DelaySync<T> {
+    const ID: u64 = 98;
+    type Sample = T;
+    type Inputs = U2;
+    type Outputs = U1;
+    type Setting = f64;
+
+    fn set(&mut self, setting: Self::Setting) {
+        self.set_division(setting);
+    }
+
+    fn reset(&mut self) {
+        self.i = 0;
+        self.buffer.fill(T::zero());
+        self.fade = T::one();
+        self.primed = false;
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        if self.sample_rate != sample_rate {
+            self.sample_rate = sample_rate;
+            let max_delay = 240.0 * self.division / Self::MIN_TEMPO * sample_rate;
+            self.buffer.resize(ceil(max_delay) as usize + 2, T::zero());
+            self.fade_step = T::from_f64(1.0 / (Self::FADE_TIME * sample_rate));
+            self.reset();
+        }
+    }
+
+    fn input_kind(&self, input: usize) -> PortKind {
+        match input {
+            1 => PortKind::Control,
+            _ => PortKind::Audio,
+        }
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        self.buffer[self.i] = input[0];
+        let target = self.delay_samples(input[1].to_f64());
+        if !self.primed {
+            self.head = target;
+            self.primed = true;
+        }
+        if self.fade < T::one() {
+            self.fade = min(T::one(), self.fade + self.fade_step);
+            if self.fade >= T::one() {
+                self.head = self.next_head;
+            }
+        } else if target != self.head {
+            // Start fading in a read head at the new delay.
+            self.next_head = target;
+            self.fade = T::zero();
+        }
+        let output = if self.fade < T::one() {
+            lerp(self.read(self.head), self.read(self.next_head), self.fade)
+        } else {
+            self.read(self.head)
+        };
+        self.i += 1;
+        if self.i >= self.buffer.len() {
+            self.i = 0;
+        }
         [output].into()
     }
 
     fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
         let mut output = new_signal_frame(self.outputs());
-        output[0] = input[0].distort(self.min_delay.to_f64() * self.sample_rate.to_f64());
+        output[0] = input[0].distort(self.delay_samples(Self::MAX_TEMPO) as f64);
         output
     }
 }
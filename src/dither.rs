@@ -0,0 +1,149 @@
+//! Dithering and noise shaping for bit depth reduction.
+
+use super::audionode::*;
+use super::math::*;
+use super::signal::*;
+use super::*;
+use funutd::Rnd;
+use numeric_array::*;
+
+/// Noise shaping curve used with dithering.
+/// Noise shaping feeds back the quantization error to move noise
+/// power away from the frequencies where hearing is most sensitive.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum NoiseShaping {
+    /// Plain TPDF dither with a flat noise spectrum.
+    #[default]
+    Flat,
+    /// First order error feedback. Noise rises towards high frequencies at 6 dB/octave.
+    FirstOrder,
+    /// Second order error feedback. Noise rises towards high frequencies at 12 dB/octave.
+    SecondOrder,
+    /// Lipshitz five tap psychoacoustic curve, designed for a sample rate of 44.1 kHz.
+    Lipshitz,
+}
+
+impl NoiseShaping {
+    /// Error feedback filter coefficients, starting from the previous sample.
+    pub fn coefficients(&self) -> &'static [f64] {
+        match self {
+            NoiseShaping::Flat => &[],
+            NoiseShaping::FirstOrder => &[1.0],
+            NoiseShaping::SecondOrder => &[2.0, -1.0],
+            NoiseShaping::Lipshitz => &[2.033, -2.165, 1.959, -1.590, 0.6149],
+        }
+    }
+}
+
+/// Quantizer that applies TPDF dither with optional noise shaping.
+/// Quantizes a single channel of samples in -1...1 to signed integers.
+#[derive(Clone)]
+pub struct Ditherer {
+    rnd: Rnd,
+    shaping: NoiseShaping,
+    /// Largest positive integer value.
+    scale: f64,
+    /// Previous quantization errors in integer units, most recent first.
+    error: [f64; 5],
+}
+
+impl Ditherer {
+    /// Create a quantizer to `bits` bits (2 <= `bits` <= 32) with pseudorandom `seed`.
+    pub fn new(bits: u32, shaping: NoiseShaping, seed: u64) -> Self {
+        assert!((2..=32).contains(&bits));
+        Self {
+            rnd: Rnd::from_u64(seed),
+            shaping,
+            scale: ((1u64 << (bits - 1)) - 1) as f64,
+            error: [0.0; 5],
+        }
+    }
+
+    /// Noise shaping curve.
+    #[inline]
+    pub fn shaping(&self) -> NoiseShaping {
+        self.shaping
+    }
+
+    /// Largest positive integer value of the quantizer.
+    #[inline]
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    /// Reset error feedback state and reseed the dither generator.
+    pub fn reset(&mut self, seed: u64) {
+        self.rnd = Rnd::from_u64(seed);
+        self.error = [0.0; 5];
+    }
+
+    /// Quantize sample `x` in -1...1. Returns an integer in -`scale` - 1...`scale`.
+    #[inline]
+    pub fn quantize(&mut self, x: f64) -> i64 {
+        let mut target = clamp11(x) * self.scale;
+        for (c, e) in self.shaping.coefficients().iter().zip(self.error.iter()) {
+            target -= c * e;
+        }
+        // Triangular dither with a peak amplitude of one least significant bit.
+        let dither = self.rnd.f64() - self.rnd.f64();
+        let value = clamp(-self.scale - 1.0, self.scale, round(target + dither));
+        self.error.copy_within(0..4, 1);
+        // Limit the error so that clipping does not make the feedback loop unstable.
+        self.error[0] = clamp(-1.0, 1.0, value - target);
+        value as i64
+    }
+}
+
+/// Dither and quantize the signal to a lower bit depth.
+/// Output samples are multiples of the least significant bit.
+/// - Input 0: signal in -1...1.
+/// - Output 0: quantized signal.
+#[derive(Clone)]
+pub struct Dither<T> {
+    _marker: std::marker::PhantomData<T>,
+    ditherer: Ditherer,
+    hash: u64,
+}
+
+impl<T: Float> Dither<T> {
+    /// Create a new dithering quantizer to `bits` bits (2 <= `bits` <= 32).
+    pub fn new(bits: u32, shaping: NoiseShaping) -> Self {
+        Self {
+            _marker: std::marker::PhantomData,
+            ditherer: Ditherer::new(bits, shaping, 0),
+            hash: 0,
+        }
+    }
+}
+
+impl<T: Float> AudioNode for Dither<T> {
+    const ID: u64 = 128;
+    type Sample = T;
+    type Inputs = typenum::U1;
+    type Outputs = typenum::U1;
+    type Setting = ();
+
+    fn reset(&mut self) {
+        self.ditherer.reset(self.hash);
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let value = self.ditherer.quantize(input[0].to_f64()) as f64 / self.ditherer.scale();
+        [T::from_f64(value)].into()
+    }
+
+    fn set_hash(&mut self, hash: u64) {
+        self.hash = hash;
+        self.reset();
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        output[0] = input[0].distort(0.0);
+        output
+    }
+}
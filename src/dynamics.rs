@@ -6,6 +6,7 @@ use super::follow::*;
 use super::math::*;
 use super::shared::*;
 use super::signal::*;
+use super::weighting::*;
 use super::*;
 use numeric_array::typenum::*;
 use std::sync::Arc;
@@ -117,7 +118,13 @@ where
     }
 }
 
+/// Length of the interpolation kernel used in true peak detection.
+const TRUE_PEAK_TAPS: usize = 8;
+
 /// Look-ahead limiter.
+/// Optionally, peaks are detected from a 4x oversampled signal (true peak detection)
+/// and output is soft clipped to stay below the ceiling.
+/// Setting: ceiling amplitude.
 #[derive(Clone)]
 pub struct Limiter<T, N, S>
 where
@@ -133,6 +140,17 @@ where
     follower: AFollow<T, T, S>,
     buffer: Vec<Frame<T, N>>,
     index: usize,
+    /// Maximum output amplitude.
+    ceiling: T,
+    /// Whether to detect inter-sample peaks.
+    true_peak: bool,
+    /// Whether to soft clip output to the ceiling.
+    soft_clip: bool,
+    /// Interpolation kernels for the three intermediate phases of true peak detection.
+    kernel: [[T; TRUE_PEAK_TAPS]; 3],
+    /// Input history for true peak detection (ring buffer).
+    history: Vec<Frame<T, N>>,
+    history_index: usize,
 }
 
 impl<T, N, S> Limiter<T, N, S>
@@ -157,6 +175,31 @@ where
         ReduceBuffer::new(Self::buffer_length(sample_rate, lookahead), Maximum::new())
     }
 
+    /// Hann windowed sinc kernels for interpolating at 1/4, 2/4 and 3/4
+    /// between the middle samples of the history.
+    fn true_peak_kernel() -> [[T; TRUE_PEAK_TAPS]; 3] {
+        let mut kernel = [[T::zero(); TRUE_PEAK_TAPS]; 3];
+        let half = TRUE_PEAK_TAPS as f64 / 2.0;
+        for (phase, taps) in kernel.iter_mut().enumerate() {
+            let position = half - 1.0 + (phase + 1) as f64 * 0.25;
+            let mut weights = [0.0; TRUE_PEAK_TAPS];
+            for (k, weight) in weights.iter_mut().enumerate() {
+                let d = position - k as f64;
+                let sinc = if d == 0.0 {
+                    1.0
+                } else {
+                    sin(PI * d) / (PI * d)
+                };
+                *weight = sinc * (0.5 + 0.5 * cos(PI * d / (half + 0.5)));
+            }
+            let total: f64 = weights.iter().sum();
+            for (tap, weight) in taps.iter_mut().zip(weights.iter()) {
+                *tap = T::from_f64(weight / total);
+            }
+        }
+        kernel
+    }
+
     pub fn new(sample_rate: f64, time: S) -> Self {
         let (lookahead, release) = time.broadcast();
         Limiter {
@@ -173,6 +216,78 @@ where
             buffer: vec![],
             reducer: Self::new_buffer(sample_rate, lookahead.to_f64()),
             index: 0,
+            ceiling: T::one(),
+            true_peak: false,
+            soft_clip: false,
+            kernel: Self::true_peak_kernel(),
+            history: vec![Frame::default(); TRUE_PEAK_TAPS],
+            history_index: 0,
+        }
+    }
+
+    /// Set maximum output amplitude `ceiling` (default is 1).
+    pub fn set_ceiling(&mut self, ceiling: T) {
+        assert!(ceiling > T::zero());
+        self.ceiling = ceiling;
+    }
+
+    /// Enable or disable true peak detection (default is disabled).
+    /// True peak detection estimates inter-sample peaks by 4x oversampling
+    /// and adds 4 samples of latency.
+    pub fn set_true_peak(&mut self, true_peak: bool) {
+        if self.true_peak != true_peak {
+            self.true_peak = true_peak;
+            self.reset();
+        }
+    }
+
+    /// Enable or disable the soft clipping stage (default is disabled).
+    /// The soft clipper guarantees that output samples stay below the ceiling.
+    pub fn set_soft_clip(&mut self, soft_clip: bool) {
+        self.soft_clip = soft_clip;
+    }
+
+    /// Latency of the limiter in samples.
+    fn delay_samples(&self) -> usize {
+        self.reducer.length()
+            + if self.true_peak {
+                TRUE_PEAK_TAPS / 2
+            } else {
+                0
+            }
+    }
+
+    /// Push `input` into the true peak history. Returns the delayed frame
+    /// aligned with the estimated peak amplitude.
+    #[inline]
+    fn detect_true_peak(&mut self, input: &Frame<T, N>) -> (Frame<T, N>, T) {
+        self.history[self.history_index] = input.clone();
+        self.history_index = (self.history_index + 1) % TRUE_PEAK_TAPS;
+        let middle = (self.history_index + TRUE_PEAK_TAPS / 2 - 1) % TRUE_PEAK_TAPS;
+        let mut amplitude = T::zero();
+        for channel in 0..N::USIZE {
+            amplitude = max(amplitude, abs(self.history[middle][channel]));
+            for taps in self.kernel.iter() {
+                let mut x = T::zero();
+                for (k, tap) in taps.iter().enumerate() {
+                    x += *tap * self.history[(self.history_index + k) % TRUE_PEAK_TAPS][channel];
+                }
+                amplitude = max(amplitude, abs(x));
+            }
+        }
+        (self.history[middle].clone(), amplitude)
+    }
+
+    /// Soft clip `x` to stay below the ceiling. The curve is linear up to 80% of the ceiling.
+    #[inline]
+    fn clip(&self, x: T) -> T {
+        let knee = T::from_f64(0.8) * self.ceiling;
+        let y = abs(x);
+        if y <= knee {
+            x
+        } else {
+            let range = self.ceiling - knee;
+            signum(x) * (knee + range * tanh((y - knee) / range))
         }
     }
 }
@@ -187,7 +302,11 @@ where
     type Sample = T;
     type Inputs = N;
     type Outputs = N;
-    type Setting = ();
+    type Setting = T;
+
+    fn set(&mut self, setting: Self::Setting) {
+        self.set_ceiling(setting);
+    }
 
     fn reset(&mut self) {
         self.set_sample_rate(self.sample_rate);
@@ -201,8 +320,11 @@ where
             self.reducer = Self::new_buffer(sample_rate, self.lookahead);
         }
         self.follower.set_sample_rate(sample_rate);
+        self.follower.reset();
         self.reducer.clear();
         self.buffer.clear();
+        self.history.fill(Frame::default());
+        self.history_index = 0;
     }
 
     #[inline]
@@ -210,26 +332,40 @@ where
         &mut self,
         input: &Frame<Self::Sample, Self::Inputs>,
     ) -> Frame<Self::Sample, Self::Outputs> {
-        let amplitude = input.iter().fold(T::zero(), |amp, &x| max(amp, abs(x)));
+        let (input, amplitude) = if self.true_peak {
+            self.detect_true_peak(input)
+        } else {
+            (
+                input.clone(),
+                input.iter().fold(T::zero(), |amp, &x| max(amp, abs(x))),
+            )
+        };
         self.reducer.set(self.index, amplitude);
         if self.buffer.len() < self.reducer.length() {
             // We are filling up the initial buffer.
-            self.buffer.push(input.clone());
+            self.buffer.push(input);
             if self.buffer.len() == self.reducer.length() {
                 // When the buffer is full, start following from its total peak.
-                self.follower.set_value(self.reducer.total());
+                self.follower.set_value(self.reducer.total() / self.ceiling);
             }
             self.advance();
             Frame::default()
         } else {
             let output = self.buffer[self.index].clone();
-            self.buffer[self.index] = input.clone();
+            self.buffer[self.index] = input;
             // Leave some headroom.
-            self.follower
-                .filter_mono(max(T::one(), self.reducer.total() * T::from_f64(1.10)));
+            self.follower.filter_mono(max(
+                T::one(),
+                self.reducer.total() * T::from_f64(1.10) / self.ceiling,
+            ));
             self.advance();
             let limit = self.follower.value();
-            output * Frame::splat(T::from_f64(1.0) / limit)
+            let output = output * Frame::splat(T::from_f64(1.0) / limit);
+            if self.soft_clip {
+                Frame::generate(|i| self.clip(output[i]))
+            } else {
+                output
+            }
         }
     }
 
@@ -237,7 +373,7 @@ where
         let mut output = new_signal_frame(self.outputs());
         for i in 0..N::USIZE {
             // We pretend that the limiter does not alter the frequency response.
-            output[i] = input[i].delay(self.reducer.length() as f64);
+            output[i] = input[i].delay(self.delay_samples() as f64);
         }
         output
     }
@@ -250,8 +386,306 @@ where
     }
 }
 
+/// Look-ahead limiter with an audio-rate ceiling input.
+/// The ceiling is given in dB and is read at the time a sample enters the limiter.
+/// - Inputs 0 to N - 1: input signal
+/// - Input N: ceiling (dB)
+/// - Outputs 0 to N - 1: limited signal
+#[derive(Clone)]
+pub struct LimiterMod<T, N, S>
+where
+    T: Real,
+    N: Size<T> + Add<U1>,
+    <N as Add<U1>>::Output: Size<T>,
+    S: ScalarOrPair<Sample = T>,
+{
+    limiter: Limiter<T, N, S>,
+}
+
+impl<T, N, S> LimiterMod<T, N, S>
+where
+    T: Real,
+    N: Size<T> + Add<U1>,
+    <N as Add<U1>>::Output: Size<T>,
+    S: ScalarOrPair<Sample = T>,
+{
+    pub fn new(sample_rate: f64, time: S) -> Self {
+        Self {
+            limiter: Limiter::new(sample_rate, time),
+        }
+    }
+
+    /// Enable or disable true peak detection (default is disabled).
+    pub fn set_true_peak(&mut self, true_peak: bool) {
+        self.limiter.set_true_peak(true_peak);
+    }
+
+    /// Enable or disable the soft clipping stage (default is disabled).
+    pub fn set_soft_clip(&mut self, soft_clip: bool) {
+        self.limiter.set_soft_clip(soft_clip);
+    }
+}
+
+impl<T, N, S> AudioNode for LimiterMod<T, N, S>
+where
+    T: Real,
+    N: Size<T> + Add<U1>,
+    <N as Add<U1>>::Output: Size<T>,
+    S: ScalarOrPair<Sample = T>,
+{
+    const ID: u64 = 96;
+    type Sample = T;
+    type Inputs = <N as Add<U1>>::Output;
+    type Outputs = N;
+    type Setting = ();
+
+    fn reset(&mut self) {
+        self.limiter.reset();
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.limiter.set_sample_rate(sample_rate);
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        // Clamp the ceiling to keep gain computations finite.
+        self.limiter.ceiling = db_amp(max(T::from_f64(-200.0), input[N::USIZE]));
+        self.limiter.tick(&Frame::generate(|i| input[i]))
+    }
+
+    fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
+        self.limiter.route(input, frequency)
+    }
+
+    fn allocate(&mut self) {
+        self.limiter.allocate();
+    }
+}
+
+/// Automatic gain control. Adjusts gain slowly to keep long-term level near a target.
+/// Gain is held during silence, so that noise floor is not amplified.
+/// Setting: (target, max gain, speed).
+/// - Input 0: input signal
+/// - Output 0: gain adjusted signal
+#[derive(Clone)]
+pub struct Agc<T: Real> {
+    /// Target RMS level in dB.
+    target: T,
+    /// Maximum boost and cut in dB.
+    max_gain: T,
+    /// Maximum rate of gain change in dB per second.
+    speed: T,
+    /// Current gain in dB.
+    gain: T,
+    /// Smoothed mean square of input.
+    level: T,
+    /// Per-sample smoothing factor of level estimation.
+    smoothing: T,
+    /// Fast mean square of input for gating.
+    gate_level: T,
+    /// Per-sample smoothing factor of the gate detector.
+    gate_smoothing: T,
+    /// Maximum gain change per sample in dB.
+    step: T,
+    sample_rate: f64,
+}
+
+impl<T: Real> Agc<T> {
+    /// Level estimation timescale in seconds.
+    const LEVEL_TIME: f64 = 0.4;
+    /// Gate detector timescale in seconds.
+    const GATE_TIME: f64 = 0.005;
+    /// Gate threshold in dB. Level estimate and gain are frozen when input level is below it.
+    const GATE: f64 = -50.0;
+
+    /// Create new AGC. Long-term level is adjusted toward `target` dB
+    /// with gain limited to `max_gain` dB in either direction,
+    /// changing at most `speed` dB per second.
+    pub fn new(sample_rate: f64, target: T, max_gain: T, speed: T) -> Self {
+        assert!(max_gain >= T::zero());
+        assert!(speed > T::zero());
+        let mut node = Self {
+            target,
+            max_gain,
+            speed,
+            gain: T::zero(),
+            level: T::zero(),
+            smoothing: T::zero(),
+            gate_level: T::zero(),
+            gate_smoothing: T::zero(),
+            step: T::zero(),
+            sample_rate,
+        };
+        node.set_sample_rate(sample_rate);
+        node
+    }
+
+    /// Current gain in dB.
+    pub fn gain(&self) -> T {
+        self.gain
+    }
+
+    /// Set `target` level in dB, `max_gain` in dB and `speed` in dB per second.
+    /// The current gain is kept.
+    pub fn set_parameters(&mut self, target: T, max_gain: T, speed: T) {
+        assert!(max_gain >= T::zero());
+        assert!(speed > T::zero());
+        self.target = target;
+        self.max_gain = max_gain;
+        self.speed = speed;
+        self.step = speed / T::from_f64(self.sample_rate);
+    }
+}
+
+impl<T: Real> AudioNode for Agc<T> {
+    const ID: u64 = 83;
+    type Sample = T;
+    type Inputs = U1;
+    type Outputs = U1;
+    type Setting = (T, T, T);
+
+    fn set(&mut self, setting: Self::Setting) {
+        self.set_parameters(setting.0, setting.1, setting.2);
+    }
+
+    fn reset(&mut self) {
+        self.gain = T::zero();
+        self.level = T::zero();
+        self.gate_level = T::zero();
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate;
+        self.smoothing = T::from_f64(1.0 - exp(-1.0 / (Self::LEVEL_TIME * sample_rate)));
+        self.gate_smoothing = T::from_f64(1.0 - exp(-1.0 / (Self::GATE_TIME * sample_rate)));
+        self.step = self.speed / T::from_f64(sample_rate);
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let x = input[0];
+        self.gate_level += (x * x - self.gate_level) * self.gate_smoothing;
+        if self.gate_level > T::from_f64(squared(db_amp(Self::GATE))) {
+            self.level += (x * x - self.level) * self.smoothing;
+            let level = amp_db(sqrt(self.level));
+            let goal = clamp(-self.max_gain, self.max_gain, self.target - level);
+            self.gain = clamp(self.gain - self.step, self.gain + self.step, goal);
+        }
+        [x * db_amp(self.gain)].into()
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        output[0] = input[0].distort(0.0);
+        output
+    }
+}
+
+/// Gain control in dB. Gain changes ramp linearly in amplitude, which avoids clicks.
+/// A gain of negative infinity dB mutes the signal.
+/// Setting: gain in dB.
+/// - Input 0: input signal
+/// - Output 0: signal with gain applied
+#[derive(Clone)]
+pub struct Fader<T: Real> {
+    /// Target gain in dB.
+    db: T,
+    /// Target amplitude.
+    target: T,
+    /// Current amplitude.
+    gain: T,
+    /// Amplitude increment per sample during a ramp.
+    step: T,
+    /// Samples left in the current ramp.
+    ramp: usize,
+    sample_rate: f64,
+}
+
+impl<T: Real> Fader<T> {
+    /// Duration of gain ramps in seconds.
+    pub const RAMP_TIME: f64 = 0.02;
+
+    /// Create new fader with initial gain `db` dB.
+    pub fn new(sample_rate: f64, db: T) -> Self {
+        let target = db_amp(db);
+        Self {
+            db,
+            target,
+            gain: target,
+            step: T::zero(),
+            ramp: 0,
+            sample_rate,
+        }
+    }
+
+    /// Target gain in dB.
+    pub fn gain_db(&self) -> T {
+        self.db
+    }
+
+    /// Set gain to `db` dB. Negative infinity mutes.
+    /// Gain ramps to the new value in `Fader::RAMP_TIME` seconds.
+    pub fn set_gain_db(&mut self, db: T) {
+        self.db = db;
+        self.target = db_amp(db);
+        self.ramp = max(1.0, round(Self::RAMP_TIME * self.sample_rate)) as usize;
+        self.step = (self.target - self.gain) / T::new(self.ramp as i64);
+    }
+}
+
+impl<T: Real> AudioNode for Fader<T> {
+    const ID: u64 = 104;
+    type Sample = T;
+    type Inputs = U1;
+    type Outputs = U1;
+    type Setting = T;
+
+    fn set(&mut self, setting: Self::Setting) {
+        self.set_gain_db(setting);
+    }
+
+    fn reset(&mut self) {
+        self.gain = self.target;
+        self.ramp = 0;
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate;
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        if self.ramp > 0 {
+            self.ramp -= 1;
+            self.gain = if self.ramp == 0 {
+                self.target
+            } else {
+                self.gain + self.step
+            };
+        }
+        [input[0] * self.gain].into()
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        output[0] = input[0].scale(self.target.to_f64());
+        output
+    }
+}
+
 /// Transient filter. Multiply the signal with a fade-in curve.
 /// After fade-in, pass signal through.
+/// Setting: fade-in duration.
 /// - Input 0: input signal
 /// - Output 0: filtered signal
 #[derive(Default, Clone)]
@@ -272,6 +706,12 @@ impl<T: Float, F: Real> Declick<T, F> {
         node.set_sample_rate(sample_rate);
         node
     }
+
+    /// Set fade-in `duration` in seconds. Takes effect from the next reset
+    /// if the fade-in is already finished.
+    pub fn set_duration(&mut self, duration: F) {
+        self.duration = duration;
+    }
 }
 
 impl<T: Float, F: Real> AudioNode for Declick<T, F> {
@@ -279,7 +719,11 @@ impl<T: Float, F: Real> AudioNode for Declick<T, F> {
     type Sample = T;
     type Inputs = U1;
     type Outputs = U1;
-    type Setting = ();
+    type Setting = F;
+
+    fn set(&mut self, setting: Self::Setting) {
+        self.set_duration(setting);
+    }
 
     fn reset(&mut self) {
         self.t = F::zero();
@@ -348,6 +792,31 @@ pub enum Meter {
     /// RMS meter with smoothing timescale in seconds.
     /// Smoothing timescale is the time it takes for level estimation to move halfway to a new level.
     Rms(f64),
+    /// Stereo correlation meter with smoothing timescale in seconds.
+    /// The level is in -1...1: 1 for identical channels, 0 for unrelated channels
+    /// and -1 for channels in opposite phase. A mono signal has correlation 1.
+    Correlation(f64),
+    /// Stereo balance meter with smoothing timescale in seconds.
+    /// The level is the RMS difference of the channels in -1...1 from left (-1) to right (1).
+    /// A mono signal has balance 0.
+    Balance(f64),
+    /// VU meter. Average responding with 300 ms rise time to 99% of a steady level.
+    /// The scale is calibrated to read the RMS level of a sine wave.
+    Vu,
+    /// Quasi-peak programme meter (IEC 60268-10 type II). 10 ms integration time
+    /// and 24 dB fall time of 2.8 seconds.
+    Ppm,
+    /// Digital peak meter with instant attack. The peak is held for `hold` seconds
+    /// (first argument) and then decays with a timescale in seconds (second argument).
+    /// Decay timescale is the time it takes for the level to fall halfway.
+    PeakHold(f64, f64),
+    /// True RMS meter over a sliding rectangular window with length in seconds.
+    /// Allocates: the window.
+    WindowRms(f64),
+    /// RMS meter with frequency weighting (first argument) and smoothing timescale in seconds
+    /// (second argument). For example, `Meter::Weighted(Weighting::A, 0.125)`
+    /// reads A-weighted levels with a fast time weighting.
+    Weighted(Weighting, f64),
 }
 
 impl Meter {
@@ -361,8 +830,26 @@ impl Meter {
 pub struct MeterState<T: Real> {
     /// Per-sample smoothing calculated from smoothing timescale.
     smoothing: T,
-    /// Current meter level.
+    /// Per-sample attack coefficient for ballistic meters.
+    attack: T,
+    /// Current meter level. In stereo modes, this is left channel power.
     state: T,
+    /// Right channel power in stereo modes. Intermediate stage of the VU meter.
+    right: T,
+    /// Cross channel power in stereo modes.
+    cross: T,
+    /// Peak hold length in samples.
+    hold: usize,
+    /// Samples left in the current peak hold.
+    hold_left: usize,
+    /// Squared samples in the RMS window.
+    window: Vec<T>,
+    /// Next write position in the RMS window.
+    index: usize,
+    /// Running sum of the RMS window.
+    sum: f64,
+    /// Weighting filters for the left and right channels.
+    weighting: [WeightingFilter<T>; 2],
 }
 
 impl<T: Real> MeterState<T> {
@@ -370,7 +857,16 @@ impl<T: Real> MeterState<T> {
     pub fn new(meter: Meter) -> Self {
         let mut state = Self {
             smoothing: T::zero(),
+            attack: T::zero(),
             state: T::zero(),
+            right: T::zero(),
+            cross: T::zero(),
+            hold: 0,
+            hold_left: 0,
+            window: Vec::new(),
+            index: 0,
+            sum: 0.0,
+            weighting: Default::default(),
         };
         state.set_sample_rate(meter, DEFAULT_SR);
         state
@@ -379,6 +875,13 @@ impl<T: Real> MeterState<T> {
     /// Reset meter state.
     pub fn reset(&mut self, _meter: Meter) {
         self.state = T::zero();
+        self.right = T::zero();
+        self.cross = T::zero();
+        self.hold_left = 0;
+        self.window.fill(T::zero());
+        self.index = 0;
+        self.sum = 0.0;
+        self.weighting.iter_mut().for_each(|x| x.reset());
     }
 
     /// Set meter sample rate.
@@ -389,6 +892,36 @@ impl<T: Real> MeterState<T> {
             }
             Meter::Peak(timescale) => timescale,
             Meter::Rms(timescale) => timescale,
+            Meter::Correlation(timescale) => timescale,
+            Meter::Balance(timescale) => timescale,
+            Meter::Vu => {
+                // Two identical first order stages reach 99% of a step in 6.64 time constants.
+                self.smoothing = T::from_f64(exp(-6.64 / (0.3 * sample_rate)));
+                return;
+            }
+            Meter::Ppm => {
+                // The attack time constant is tuned so that a 10 ms tone burst reads 4 dB below steady state.
+                self.attack = T::from_f64(1.0 - exp(-1.0 / (0.0052 * sample_rate)));
+                self.smoothing = T::from_f64(db_amp(-24.0 / 2.8 / sample_rate));
+                return;
+            }
+            Meter::PeakHold(hold, timescale) => {
+                self.hold = round(hold * sample_rate) as usize;
+                timescale
+            }
+            Meter::WindowRms(length) => {
+                let length = max(1.0, round(length * sample_rate)) as usize;
+                self.window.resize(length, T::zero());
+                self.reset(meter);
+                return;
+            }
+            Meter::Weighted(weighting, timescale) => {
+                self.weighting = [
+                    WeightingFilter::new(weighting, sample_rate),
+                    WeightingFilter::new(weighting, sample_rate),
+                ];
+                timescale
+            }
         };
         self.smoothing = T::from_f64(pow(0.5, 1.0 / (timescale * sample_rate)));
     }
@@ -403,6 +936,74 @@ impl<T: Real> MeterState<T> {
                 self.state =
                     self.state * self.smoothing + squared(value) * (T::one() - self.smoothing)
             }
+            Meter::Weighted(_, _) => {
+                let value = self.weighting[0].tick(value);
+                self.state =
+                    self.state * self.smoothing + squared(value) * (T::one() - self.smoothing)
+            }
+            Meter::Correlation(_) | Meter::Balance(_) => self.tick_stereo(meter, value, value),
+            Meter::Vu => {
+                let a = T::one() - self.smoothing;
+                self.right = self.right * self.smoothing + abs(value) * a;
+                self.state = self.state * self.smoothing + self.right * a;
+            }
+            Meter::Ppm => {
+                let value = abs(value);
+                if value > self.state {
+                    self.state += (value - self.state) * self.attack;
+                } else {
+                    self.state *= self.smoothing;
+                }
+            }
+            Meter::PeakHold(_, _) => {
+                let value = abs(value);
+                if value >= self.state {
+                    self.state = value;
+                    self.hold_left = self.hold;
+                } else if self.hold_left > 0 {
+                    self.hold_left -= 1;
+                } else {
+                    self.state = max(self.state * self.smoothing, value);
+                }
+            }
+            Meter::WindowRms(_) => {
+                let value = squared(value);
+                self.sum += value.to_f64() - self.window[self.index].to_f64();
+                self.window[self.index] = value;
+                self.index += 1;
+                if self.index >= self.window.len() {
+                    self.index = 0;
+                    // Recalculate the sum once per window to keep rounding errors from accumulating.
+                    self.sum = self.window.iter().map(|x| x.to_f64()).sum();
+                }
+            }
+        }
+    }
+
+    /// Process a stereo input sample. Single channel modes meter both channels:
+    /// `Meter::Sample` stores their average, RMS modes their mean power
+    /// and the rest the louder channel.
+    #[inline]
+    pub fn tick_stereo(&mut self, meter: Meter, left: T, right: T) {
+        match meter {
+            Meter::Sample => self.state = (left + right) * T::from_f64(0.5),
+            Meter::Rms(_) | Meter::WindowRms(_) => self.tick(
+                meter,
+                sqrt((squared(left) + squared(right)) * T::from_f64(0.5)),
+            ),
+            Meter::Weighted(_, _) => {
+                let left = self.weighting[0].tick(left);
+                let right = self.weighting[1].tick(right);
+                let power = (squared(left) + squared(right)) * T::from_f64(0.5);
+                self.state = self.state * self.smoothing + power * (T::one() - self.smoothing);
+            }
+            Meter::Correlation(_) | Meter::Balance(_) => {
+                let a = T::one() - self.smoothing;
+                self.state = self.state * self.smoothing + squared(left) * a;
+                self.right = self.right * self.smoothing + squared(right) * a;
+                self.cross = self.cross * self.smoothing + left * right * a;
+            }
+            _ => self.tick(meter, max(abs(left), abs(right))),
         }
     }
 
@@ -412,7 +1013,29 @@ impl<T: Real> MeterState<T> {
         match meter {
             Meter::Sample => self.state,
             Meter::Peak(_) => self.state,
-            Meter::Rms(_) => sqrt(self.state),
+            Meter::Rms(_) | Meter::Weighted(_, _) => sqrt(self.state),
+            Meter::Correlation(_) => {
+                let power = sqrt(self.state * self.right);
+                if power > T::from_f64(1.0e-20) {
+                    clamp11(self.cross / power)
+                } else {
+                    T::zero()
+                }
+            }
+            Meter::Balance(_) => {
+                let left = sqrt(self.state);
+                let right = sqrt(self.right);
+                if left + right > T::from_f64(1.0e-10) {
+                    (right - left) / (right + left)
+                } else {
+                    T::zero()
+                }
+            }
+            // The mean absolute value of a sine wave is 2 * sqrt(2) / pi times its RMS level.
+            Meter::Vu => self.state * T::from_f64(PI / (2.0 * SQRT_2)),
+            Meter::Ppm => self.state,
+            Meter::PeakHold(_, _) => self.state,
+            Meter::WindowRms(_) => T::from_f64(sqrt(max(0.0, self.sum) / self.window.len() as f64)),
         }
     }
 }
@@ -479,6 +1102,59 @@ impl<T: Real> AudioNode for MeterNode<T> {
     }
 }
 
+/// Meters a stereo input and outputs a summary according to the chosen metering mode.
+/// Stereo field modes `Meter::Correlation` and `Meter::Balance` compare the channels;
+/// other modes meter both channels together.
+/// - Input 0: left signal
+/// - Input 1: right signal
+/// - Output 0: input summary
+#[derive(Clone)]
+pub struct StereoMeterNode<T: Real> {
+    meter: Meter,
+    state: MeterState<T>,
+}
+
+impl<T: Real> StereoMeterNode<T> {
+    /// Create a new stereo metering node.
+    pub fn new(meter: Meter) -> Self {
+        Self {
+            meter,
+            state: MeterState::new(meter),
+        }
+    }
+}
+
+impl<T: Real> AudioNode for StereoMeterNode<T> {
+    const ID: u64 = 108;
+    type Sample = T;
+    type Inputs = U2;
+    type Outputs = U1;
+    type Setting = ();
+
+    fn reset(&mut self) {
+        self.state.reset(self.meter);
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.state.set_sample_rate(self.meter, sample_rate);
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        self.state.tick_stereo(self.meter, input[0], input[1]);
+        [self.state.level(self.meter)].into()
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        output[0] = input[0].combine_nonlinear(input[1], 0.0);
+        output
+    }
+}
+
 /// Pass through input unchanged.
 /// Summary of the input signal is placed in a shared variable.
 pub struct Monitor<T: Real + Atomic> {
@@ -554,6 +1230,102 @@ impl<T: Real + Atomic> AudioNode for Monitor<T> {
         output[0][..size].clone_from_slice(&input[0][..size]);
     }
 
+    fn has_side_effects(&self) -> bool {
+        true
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        input.clone()
+    }
+}
+
+/// Pass through stereo input unchanged.
+/// Summary of the input signal is placed in a shared variable.
+/// Stereo field modes `Meter::Correlation` and `Meter::Balance` compare the channels;
+/// other modes meter both channels together.
+/// - Input 0: left signal
+/// - Input 1: right signal
+/// - Output 0: left signal
+/// - Output 1: right signal
+pub struct StereoMonitor<T: Real + Atomic> {
+    meter: Meter,
+    state: MeterState<T>,
+    shared: Arc<T::Storage>,
+}
+
+impl<T: Real + Atomic> Clone for StereoMonitor<T> {
+    fn clone(&self) -> Self {
+        Self {
+            meter: self.meter,
+            state: self.state.clone(),
+            shared: Arc::clone(&self.shared),
+        }
+    }
+}
+
+impl<T: Real + Atomic> StereoMonitor<T> {
+    /// Create a new stereo monitor node.
+    pub fn new(shared: &Shared<T>, meter: Meter) -> Self {
+        Self {
+            meter,
+            state: MeterState::new(meter),
+            shared: Arc::clone(shared.get_shared()),
+        }
+    }
+}
+
+impl<T: Real + Atomic> AudioNode for StereoMonitor<T> {
+    const ID: u64 = 94;
+    type Sample = T;
+    type Inputs = U2;
+    type Outputs = U2;
+    type Setting = ();
+
+    fn reset(&mut self) {
+        self.state.reset(self.meter);
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.state.set_sample_rate(self.meter, sample_rate);
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        self.state.tick_stereo(self.meter, input[0], input[1]);
+        T::store(&self.shared, self.state.level(self.meter));
+        *input
+    }
+
+    fn process(
+        &mut self,
+        size: usize,
+        input: &[&[Self::Sample]],
+        output: &mut [&mut [Self::Sample]],
+    ) {
+        if size == 0 {
+            return;
+        }
+        if self.meter.latest_only() {
+            self.state
+                .tick_stereo(self.meter, input[0][size - 1], input[1][size - 1]);
+        } else {
+            for i in 0..size {
+                self.state.tick_stereo(self.meter, input[0][i], input[1][i]);
+            }
+        }
+        // For efficiency, store the value only once per block.
+        T::store(&self.shared, self.state.level(self.meter));
+        output[0][..size].clone_from_slice(&input[0][..size]);
+        output[1][..size].clone_from_slice(&input[1][..size]);
+    }
+
+    fn has_side_effects(&self) -> bool {
+        true
+    }
+
     fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
         input.clone()
     }
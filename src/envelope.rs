@@ -3,8 +3,10 @@
 use super::audionode::*;
 use super::combinator::*;
 use super::math::*;
+use super::signal::*;
 use super::*;
 use numeric_array::*;
+use std::any::Any;
 use std::marker::PhantomData;
 
 /// Sample a time varying function.
@@ -219,6 +221,8 @@ where
     sample_duration: F,
     /// Deterministic pseudorandom phase.
     hash: u64,
+    /// Whether the envelope function has side effects.
+    side_effects: bool,
     _marker: PhantomData<I>,
 }
 
@@ -248,6 +252,7 @@ where
             interval,
             sample_duration: F::zero(),
             hash: 0,
+            side_effects: false,
             _marker: PhantomData::default(),
         };
         node.set_sample_rate(sample_rate);
@@ -255,6 +260,12 @@ where
         node
     }
 
+    /// Declare whether the envelope function has side effects, such as writing to shared variables.
+    /// Envelopes with side effects are not pruned from networks when their output is unused.
+    pub fn set_side_effects(&mut self, side_effects: bool) {
+        self.side_effects = side_effects;
+    }
+
     /// Move to the next segment.
     fn next_segment(&mut self, input: &Frame<T, I>) {
         if self.t_0 == F::zero() && self.t_1 == F::zero() {
@@ -364,4 +375,172 @@ where
         self.hash = hash;
         self.t_hash = hash;
     }
+
+    fn has_side_effects(&self) -> bool {
+        self.side_effects
+    }
+}
+
+/// Control rate wrapper. Evaluates the enclosed node once every `divisor` samples
+/// and interpolates its outputs linearly in between. Outputs lag behind the enclosed node
+/// by up to `divisor` samples. The enclosed node runs at the sample rate divided by `divisor`.
+/// Inputs are sampled when the enclosed node is evaluated.
+/// Setting: forwarded to the enclosed node.
+/// - Input(s): inputs of the enclosed node.
+/// - Output(s): interpolated outputs of the enclosed node.
+#[derive(Clone)]
+pub struct ControlRate<T, X>
+where
+    T: Float,
+    X: AudioNode<Sample = T>,
+    X::Inputs: Size<T>,
+    X::Outputs: Size<T>,
+{
+    x: X,
+    /// Samples per evaluation.
+    divisor: usize,
+    /// Samples left until the next evaluation.
+    countdown: usize,
+    /// Whether the enclosed node has been evaluated since reset.
+    started: bool,
+    /// Value at next sample.
+    value: Frame<T, X::Outputs>,
+    /// Value at the end of the current segment.
+    target: Frame<T, X::Outputs>,
+    /// Value delta per sample.
+    delta: Frame<T, X::Outputs>,
+}
+
+impl<T, X> ControlRate<T, X>
+where
+    T: Float,
+    X: AudioNode<Sample = T>,
+    X::Inputs: Size<T>,
+    X::Outputs: Size<T>,
+{
+    /// Create new control rate wrapper that evaluates `node` every `divisor` samples (`divisor` > 0).
+    pub fn new(sample_rate: f64, divisor: usize, mut node: X) -> Self {
+        assert!(divisor > 0);
+        node.set_sample_rate(sample_rate / divisor as f64);
+        let hash = node.ping(true, AttoHash::new(Self::ID));
+        node.ping(false, hash);
+        Self {
+            x: node,
+            divisor,
+            countdown: 0,
+            started: false,
+            value: Frame::default(),
+            target: Frame::default(),
+            delta: Frame::default(),
+        }
+    }
+
+    /// Number of samples between evaluations of the enclosed node.
+    #[inline]
+    pub fn divisor(&self) -> usize {
+        self.divisor
+    }
+
+    /// Access enclosed node.
+    #[inline]
+    pub fn node(&self) -> &X {
+        &self.x
+    }
+
+    /// Access enclosed node.
+    #[inline]
+    pub fn node_mut(&mut self) -> &mut X {
+        &mut self.x
+    }
+}
+
+impl<T, X> AudioNode for ControlRate<T, X>
+where
+    T: Float,
+    X: AudioNode<Sample = T>,
+    X::Inputs: Size<T>,
+    X::Outputs: Size<T>,
+{
+    const ID: u64 = 109;
+    type Sample = T;
+    type Inputs = X::Inputs;
+    type Outputs = X::Outputs;
+    type Setting = X::Setting;
+
+    fn set(&mut self, setting: Self::Setting) {
+        self.x.set(setting);
+    }
+
+    fn reset(&mut self) {
+        self.x.reset();
+        self.countdown = 0;
+        self.started = false;
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.x.set_sample_rate(sample_rate / self.divisor as f64);
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        if self.countdown == 0 {
+            let target = self.x.tick(input);
+            if self.started {
+                let divisor = T::new(self.divisor as i64);
+                self.delta = Frame::generate(|i| (target[i] - self.target[i]) / divisor);
+            } else {
+                self.value = target.clone();
+                self.delta = Frame::default();
+                self.started = true;
+            }
+            self.target = target;
+            self.countdown = self.divisor;
+        }
+        self.countdown -= 1;
+        if self.countdown == 0 {
+            // Land exactly on the target at the end of the segment.
+            self.value = self.target.clone();
+        } else {
+            self.value += &self.delta;
+        }
+        self.value.clone()
+    }
+
+    fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
+        let mut output = self.x.route(input, frequency);
+        for channel in output.iter_mut() {
+            if let Signal::Value(_) = channel {
+                continue;
+            }
+            *channel = channel.distort(self.divisor as f64);
+        }
+        output
+    }
+
+    fn ping(&mut self, probe: bool, hash: AttoHash) -> AttoHash {
+        self.x.ping(probe, hash.hash(Self::ID))
+    }
+
+    fn visit(&mut self, tag: u64, f: &mut dyn FnMut(&mut dyn Any)) {
+        self.x.visit(tag, f);
+    }
+
+    fn has_side_effects(&self) -> bool {
+        self.x.has_side_effects()
+    }
+
+    fn input_kind(&self, input: usize) -> PortKind {
+        self.x.input_kind(input)
+    }
+
+    fn output_kind(&self, output: usize) -> PortKind {
+        self.x.output_kind(output)
+    }
+
+    fn allocate(&mut self) {
+        self.x.allocate();
+    }
 }
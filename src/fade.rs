@@ -0,0 +1,235 @@
+//! Sample accurate fade-in and fade-out scheduling for any node.
+
+use super::audionode::*;
+use super::math::*;
+use super::sequencer::Fade;
+use super::signal::*;
+use super::*;
+use std::any::Any;
+
+/// Fade command for a `Faded` node. Times are in seconds from the latest reset.
+/// A start time that has already passed starts the fade immediately.
+#[derive(Clone, Default)]
+pub enum FadeSetting {
+    /// Fade in from silence: (start time, fade duration).
+    /// Cancels any scheduled fade-out.
+    In(f64, f64),
+    /// Fade out to silence: (start time, fade duration).
+    Out(f64, f64),
+    /// Cancel fades and play at full level.
+    #[default]
+    Cancel,
+}
+
+/// Fade envelope applied to the outputs of the enclosed node.
+/// Fades start and end on sample boundaries.
+/// Setting: `FadeSetting`.
+/// - Input(s): inputs of the enclosed node.
+/// - Output(s): faded outputs of the enclosed node.
+#[derive(Clone)]
+pub struct Faded<X: AudioNode> {
+    x: X,
+    ease: Fade,
+    sample_rate: f64,
+    /// Current time in samples since reset.
+    time: u64,
+    /// Fade-in start and duration in seconds.
+    fade_in: (f64, f64),
+    /// Fade-out start and duration in seconds, if scheduled.
+    fade_out: Option<(f64, f64)>,
+    /// Fade-in as (start, end) in samples.
+    in_samples: (u64, u64),
+    /// Fade-out as (start, end) in samples.
+    out_samples: Option<(u64, u64)>,
+}
+
+impl<X: AudioNode> Faded<X> {
+    /// Create new faded node with fade curve `ease`.
+    /// Fade in starts at time zero and lasts `fade_in` seconds.
+    /// If `fade_out` is given as (start time, duration), then fade out is scheduled as well.
+    pub fn new(x: X, ease: Fade, fade_in: f64, fade_out: Option<(f64, f64)>) -> Self {
+        assert!(fade_in >= 0.0);
+        let mut node = Self {
+            x,
+            ease,
+            sample_rate: DEFAULT_SR,
+            time: 0,
+            fade_in: (0.0, fade_in),
+            fade_out,
+            in_samples: (0, 0),
+            out_samples: None,
+        };
+        node.update_samples();
+        let hash = node.ping(true, AttoHash::new(Self::ID));
+        node.ping(false, hash);
+        node
+    }
+
+    /// Current time in seconds since the latest reset.
+    pub fn time(&self) -> f64 {
+        self.time as f64 / self.sample_rate
+    }
+
+    /// Access enclosed node.
+    pub fn node(&self) -> &X {
+        &self.x
+    }
+
+    /// Access enclosed node.
+    pub fn node_mut(&mut self) -> &mut X {
+        &mut self.x
+    }
+
+    /// Fade in starting at `time` seconds over `duration` seconds. Cancels any scheduled fade-out.
+    pub fn fade_in_at(&mut self, time: f64, duration: f64) {
+        self.fade_in = (max(time, self.time()), max(0.0, duration));
+        self.fade_out = None;
+        self.update_samples();
+    }
+
+    /// Fade out starting at `time` seconds over `duration` seconds.
+    pub fn fade_out_at(&mut self, time: f64, duration: f64) {
+        self.fade_out = Some((max(time, self.time()), max(0.0, duration)));
+        self.update_samples();
+    }
+
+    /// Cancel all fades and play at full level.
+    pub fn cancel(&mut self) {
+        self.fade_in = (0.0, 0.0);
+        self.fade_out = None;
+        self.update_samples();
+    }
+
+    /// Convert fade times to samples.
+    fn update_samples(&mut self) {
+        let to_samples = |time: f64| round(time * self.sample_rate) as u64;
+        let (start, duration) = self.fade_in;
+        self.in_samples = (to_samples(start), to_samples(start + duration));
+        self.out_samples = self
+            .fade_out
+            .map(|(start, duration)| (to_samples(start), to_samples(start + duration)));
+    }
+
+    /// Gain at time `time` in samples.
+    #[inline]
+    fn gain(&self, time: u64) -> X::Sample {
+        let (in_start, in_end) = self.in_samples;
+        let mut gain = if time >= in_end {
+            1.0
+        } else if time < in_start {
+            0.0
+        } else {
+            self.ease
+                .at((time - in_start) as f64 / (in_end - in_start) as f64)
+        };
+        if let Some((out_start, out_end)) = self.out_samples {
+            if time >= out_end {
+                gain = 0.0;
+            } else if time >= out_start {
+                gain *= self
+                    .ease
+                    .at((out_end - time) as f64 / (out_end - out_start) as f64);
+            }
+        }
+        X::Sample::from_f64(gain)
+    }
+
+    /// Whether the gain is one over the samples from `time` to `time` + `size`.
+    #[inline]
+    fn is_unity(&self, time: u64, size: usize) -> bool {
+        time >= self.in_samples.1
+            && match self.out_samples {
+                Some((out_start, _)) => out_start >= time + size as u64,
+                None => true,
+            }
+    }
+}
+
+impl<X: AudioNode> AudioNode for Faded<X> {
+    const ID: u64 = 124;
+    type Sample = X::Sample;
+    type Inputs = X::Inputs;
+    type Outputs = X::Outputs;
+    type Setting = FadeSetting;
+
+    fn set(&mut self, setting: Self::Setting) {
+        match setting {
+            FadeSetting::In(time, duration) => self.fade_in_at(time, duration),
+            FadeSetting::Out(time, duration) => self.fade_out_at(time, duration),
+            FadeSetting::Cancel => self.cancel(),
+        }
+    }
+
+    fn reset(&mut self) {
+        self.x.reset();
+        self.time = 0;
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.x.set_sample_rate(sample_rate);
+        self.time = round(self.time() * sample_rate) as u64;
+        self.sample_rate = sample_rate;
+        self.update_samples();
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let output = self.x.tick(input);
+        let gain = self.gain(self.time);
+        self.time += 1;
+        output * Frame::splat(gain)
+    }
+
+    fn process(
+        &mut self,
+        size: usize,
+        input: &[&[Self::Sample]],
+        output: &mut [&mut [Self::Sample]],
+    ) {
+        self.x.process(size, input, output);
+        if !self.is_unity(self.time, size) {
+            for i in 0..size {
+                let gain = self.gain(self.time + i as u64);
+                for channel in output.iter_mut() {
+                    channel[i] *= gain;
+                }
+            }
+        }
+        self.time += size as u64;
+    }
+
+    fn ping(&mut self, probe: bool, hash: AttoHash) -> AttoHash {
+        self.x.ping(probe, hash.hash(Self::ID))
+    }
+
+    fn visit(&mut self, tag: u64, f: &mut dyn FnMut(&mut dyn Any)) {
+        self.x.visit(tag, f);
+    }
+
+    fn has_side_effects(&self) -> bool {
+        self.x.has_side_effects()
+    }
+
+    fn input_kind(&self, input: usize) -> PortKind {
+        self.x.input_kind(input)
+    }
+
+    fn output_kind(&self, output: usize) -> PortKind {
+        self.x.output_kind(output)
+    }
+
+    fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
+        let mut output = self.x.route(input, frequency);
+        for signal in output.iter_mut() {
+            *signal = signal.distort(0.0);
+        }
+        output
+    }
+
+    fn allocate(&mut self) {
+        self.x.allocate();
+    }
+}
@@ -0,0 +1,551 @@
+//! Spectral feature extraction: centroid, flatness, rolloff, MFCC, chroma and key.
+
+use super::audionode::*;
+use super::math::*;
+use super::shared::*;
+use super::signal::*;
+use super::*;
+use num_complex::Complex64;
+use numeric_array::typenum::*;
+use rustfft::algorithm::Radix4;
+use rustfft::Fft;
+use rustfft::FftDirection;
+use std::sync::Arc;
+
+/// Window length of feature monitors in samples.
+pub const FEATURE_WINDOW: usize = 2048;
+
+/// Number of mel bands in MFCC analysis.
+pub const MFCC_BANDS: usize = 26;
+
+/// Number of mel-frequency cepstral coefficients computed.
+pub const MFCC_COEFFICIENTS: usize = 13;
+
+/// Lowest frequency in Hz included in chroma vectors.
+pub const CHROMA_LOW: f64 = 55.0;
+
+/// Highest frequency in Hz included in chroma vectors.
+pub const CHROMA_HIGH: f64 = 5000.0;
+
+/// Timescale in seconds of chroma averaging for key estimation in feature monitors.
+pub const KEY_TIMESCALE: f64 = 2.0;
+
+/// Krumhansl-Kessler major key profile starting from the tonic.
+const MAJOR_PROFILE: [f64; 12] = [
+    6.35, 2.23, 3.48, 2.33, 4.38, 4.09, 2.52, 5.19, 2.39, 3.66, 2.29, 2.88,
+];
+
+/// Krumhansl-Kessler minor key profile starting from the tonic.
+const MINOR_PROFILE: [f64; 12] = [
+    6.33, 2.68, 3.52, 5.38, 2.60, 3.53, 2.54, 4.75, 3.98, 2.69, 3.34, 3.17,
+];
+
+/// Names of pitch classes starting from C.
+const PITCH_CLASS: [&str; 12] = [
+    "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B",
+];
+
+/// Mode of a musical key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyMode {
+    /// Major key.
+    Major,
+    /// Minor key.
+    Minor,
+}
+
+/// Musical key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Key {
+    /// Pitch class of the tonic: 0 = C, 1 = C#, ..., 11 = B.
+    pub tonic: usize,
+    /// Major or minor.
+    pub mode: KeyMode,
+}
+
+impl Key {
+    /// Estimate the key of a 12-bin `chroma` vector (starting from C) by correlating it
+    /// with the Krumhansl-Kessler key profiles. A silent chroma vector is estimated as C major.
+    ///
+    /// ### Example
+    /// ```
+    /// use fundsp::hacker::*;
+    /// // Pitch classes of an A minor triad.
+    /// let mut chroma = [0.0; 12];
+    /// chroma[9] = 1.0;
+    /// chroma[0] = 1.0;
+    /// chroma[4] = 1.0;
+    /// assert!(Key::estimate(&chroma) == Key { tonic: 9, mode: KeyMode::Minor });
+    /// ```
+    pub fn estimate(chroma: &[f64; 12]) -> Key {
+        let mut best = Key {
+            tonic: 0,
+            mode: KeyMode::Major,
+        };
+        let mut best_correlation = f64::NEG_INFINITY;
+        for (mode, profile) in [
+            (KeyMode::Major, &MAJOR_PROFILE),
+            (KeyMode::Minor, &MINOR_PROFILE),
+        ] {
+            for tonic in 0..12 {
+                let correlation = pearson(|i| chroma[(tonic + i) % 12], |i| profile[i]);
+                if correlation > best_correlation {
+                    best_correlation = correlation;
+                    best = Key { tonic, mode };
+                }
+            }
+        }
+        best
+    }
+
+    /// Index of the key: 0...11 for major keys from C, 12...23 for minor keys from C.
+    pub fn index(&self) -> usize {
+        match self.mode {
+            KeyMode::Major => self.tonic,
+            KeyMode::Minor => self.tonic + 12,
+        }
+    }
+
+    /// Key with index `index` (0...23).
+    pub fn from_index(index: usize) -> Key {
+        assert!(index < 24);
+        Key {
+            tonic: index % 12,
+            mode: if index < 12 {
+                KeyMode::Major
+            } else {
+                KeyMode::Minor
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for Key {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.mode {
+            KeyMode::Major => write!(f, "{} major", PITCH_CLASS[self.tonic]),
+            KeyMode::Minor => write!(f, "{} minor", PITCH_CLASS[self.tonic]),
+        }
+    }
+}
+
+/// Pearson correlation of two 12-element sequences. Returns 0 if either is constant.
+fn pearson(x: impl Fn(usize) -> f64, y: impl Fn(usize) -> f64) -> f64 {
+    let mean_x = (0..12).map(&x).sum::<f64>() / 12.0;
+    let mean_y = (0..12).map(&y).sum::<f64>() / 12.0;
+    let mut xy = 0.0;
+    let mut xx = 0.0;
+    let mut yy = 0.0;
+    for i in 0..12 {
+        let dx = x(i) - mean_x;
+        let dy = y(i) - mean_y;
+        xy += dx * dy;
+        xx += dx * dx;
+        yy += dy * dy;
+    }
+    if xx > 1.0e-20 && yy > 1.0e-20 {
+        xy / sqrt(xx * yy)
+    } else {
+        0.0
+    }
+}
+
+/// Spectral descriptor of a window of audio.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Feature {
+    /// Spectral centroid in Hz: the magnitude weighted mean frequency.
+    /// It correlates with the perceived brightness of a sound.
+    Centroid,
+    /// Spectral flatness in 0...1: the geometric mean of the power spectrum
+    /// divided by its arithmetic mean. It is high for noise and near 0 for tones.
+    Flatness,
+    /// Rolloff frequency in Hz below which the given fraction (for example, 0.85)
+    /// of spectral energy lies.
+    Rolloff(f64),
+    /// Mel-frequency cepstral coefficient with the given index (0 to 12).
+    /// Coefficient 0 is proportional to the log energy of the window.
+    Mfcc(usize),
+    /// Chroma of the given pitch class (0 = C, ..., 11 = B) in 0...1,
+    /// relative to the strongest pitch class.
+    Chroma(usize),
+    /// Estimated key as a key index (see `Key::index`).
+    /// Feature monitors estimate the key from chroma averaged over `KEY_TIMESCALE` seconds.
+    Key,
+}
+
+/// Convert frequency in Hz to mels.
+fn hz_mel(f: f64) -> f64 {
+    2595.0 * log10(1.0 + f / 700.0)
+}
+
+/// Convert mels to frequency in Hz.
+fn mel_hz(mel: f64) -> f64 {
+    700.0 * (exp10(mel / 2595.0) - 1.0)
+}
+
+/// Spectral feature analyzer. Each call to `analyze` transforms a Hann windowed
+/// frame of samples into a power spectrum and computes features from it.
+#[derive(Clone)]
+pub struct SpectralFeatures {
+    length: usize,
+    sample_rate: f64,
+    /// Hann window.
+    hann: Vec<f64>,
+    fft: Arc<dyn Fft<f64>>,
+    buffer: Vec<Complex64>,
+    scratch: Vec<Complex64>,
+    /// Power spectrum from DC to the Nyquist frequency.
+    power: Vec<f64>,
+    /// Triangular mel filter weights, one vector of bin weights per band.
+    mel: Vec<Vec<f64>>,
+    /// Log energies of mel bands in the latest frame.
+    mel_energy: Vec<f64>,
+    /// Mel-frequency cepstral coefficients of the latest frame.
+    mfcc: Vec<f64>,
+    /// Pitch class of each bin, or 12 if the bin is outside the chroma range.
+    pitch_class: Vec<usize>,
+    /// Chroma vector of the latest frame.
+    chroma: [f64; 12],
+}
+
+impl SpectralFeatures {
+    /// Create new analyzer. Window `length` is a power of two and at least 16.
+    pub fn new(length: usize, sample_rate: f64) -> Self {
+        assert!(length.is_power_of_two() && length >= 16);
+        let fft: Arc<dyn Fft<f64>> = Arc::new(Radix4::new(length, FftDirection::Forward));
+        let scratch_length = fft.get_inplace_scratch_len();
+        let mut analyzer = Self {
+            length,
+            sample_rate,
+            hann: (0..length)
+                .map(|i| 0.5 - 0.5 * cos(TAU * i as f64 / length as f64))
+                .collect(),
+            fft,
+            buffer: vec![Complex64::default(); length],
+            scratch: vec![Complex64::default(); scratch_length],
+            power: vec![0.0; length / 2 + 1],
+            mel: Vec::new(),
+            mel_energy: vec![0.0; MFCC_BANDS],
+            mfcc: vec![0.0; MFCC_COEFFICIENTS],
+            pitch_class: Vec::new(),
+            chroma: [0.0; 12],
+        };
+        analyzer.set_sample_rate(sample_rate);
+        analyzer
+    }
+
+    /// Window length in samples.
+    pub fn length(&self) -> usize {
+        self.length
+    }
+
+    /// Number of bins from DC to the Nyquist frequency, inclusive.
+    pub fn bins(&self) -> usize {
+        self.power.len()
+    }
+
+    /// Center frequency of `bin` in Hz.
+    pub fn frequency(&self, bin: usize) -> f64 {
+        bin as f64 * self.sample_rate / self.length as f64
+    }
+
+    /// Sample rate in Hz.
+    pub fn sample_rate(&self) -> f64 {
+        self.sample_rate
+    }
+
+    /// Set sample rate. Features are reported in Hz at this sample rate.
+    pub fn set_sample_rate(&mut self, sample_rate: f64) {
+        if sample_rate == self.sample_rate && !self.mel.is_empty() {
+            return;
+        }
+        self.sample_rate = sample_rate;
+        // Mel bands are spaced evenly on the mel scale from DC to the Nyquist frequency.
+        let top = hz_mel(sample_rate * 0.5);
+        let edge = |i: usize| mel_hz(top * i as f64 / (MFCC_BANDS + 1) as f64);
+        let bins = self.bins();
+        self.mel = (0..MFCC_BANDS)
+            .map(|band| {
+                let (low, center, high) = (edge(band), edge(band + 1), edge(band + 2));
+                (0..bins)
+                    .map(|bin| {
+                        let f = self.frequency(bin);
+                        if f <= low || f >= high {
+                            0.0
+                        } else if f <= center {
+                            (f - low) / (center - low)
+                        } else {
+                            (high - f) / (high - center)
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        self.pitch_class = (0..bins)
+            .map(|bin| {
+                let f = self.frequency(bin);
+                if (CHROMA_LOW..=CHROMA_HIGH).contains(&f) {
+                    (round(12.0 * log2(f / 440.0)) as i64 + 69).rem_euclid(12) as usize
+                } else {
+                    12
+                }
+            })
+            .collect();
+    }
+
+    /// Analyze a frame of `samples`. If there are fewer samples than the window length,
+    /// then the frame is padded with zeros. Extra samples are ignored.
+    pub fn analyze<T: Float>(&mut self, samples: &[T]) {
+        for (i, x) in self.buffer.iter_mut().enumerate() {
+            let sample = samples.get(i).map_or(0.0, |x| x.to_f64());
+            *x = Complex64::new(sample * self.hann[i], 0.0);
+        }
+        self.fft
+            .process_with_scratch(&mut self.buffer, &mut self.scratch);
+        // Normalize so that a full scale sine wave has a total power of about 1/2.
+        let z = 1.0 / (self.length as f64 * self.length as f64 * 0.375);
+        let bins = self.bins();
+        for (i, power) in self.power.iter_mut().enumerate() {
+            let weight = if i == 0 || i == bins - 1 { 1.0 } else { 2.0 };
+            *power = self.buffer[i].norm_sqr() * weight * z;
+        }
+        for (energy, weights) in self.mel_energy.iter_mut().zip(self.mel.iter()) {
+            let sum = weights
+                .iter()
+                .zip(self.power.iter())
+                .map(|(w, p)| w * p)
+                .sum::<f64>();
+            *energy = log(sum + 1.0e-10);
+        }
+        self.chroma = [0.0; 12];
+        for (&class, &power) in self.pitch_class.iter().zip(self.power.iter()) {
+            if class < 12 {
+                self.chroma[class] += power;
+            }
+        }
+        let strongest = self.chroma.iter().fold(0.0, |a, &x| max(a, x));
+        if strongest > 1.0e-20 {
+            for x in self.chroma.iter_mut() {
+                *x /= strongest;
+            }
+        } else {
+            self.chroma = [0.0; 12];
+        }
+        let m = MFCC_BANDS as f64;
+        // Orthonormal DCT-II of log mel band energies.
+        for (k, c) in self.mfcc.iter_mut().enumerate() {
+            let scale = if k == 0 { sqrt(1.0 / m) } else { sqrt(2.0 / m) };
+            *c = scale
+                * self
+                    .mel_energy
+                    .iter()
+                    .enumerate()
+                    .map(|(band, e)| e * cos(PI * k as f64 * (band as f64 + 0.5) / m))
+                    .sum::<f64>();
+        }
+    }
+
+    /// Power spectrum of the latest frame from DC to the Nyquist frequency.
+    pub fn power(&self) -> &[f64] {
+        &self.power
+    }
+
+    /// Spectral centroid of the latest frame in Hz. Silent frames have centroid 0.
+    pub fn centroid(&self) -> f64 {
+        let mut total = 0.0;
+        let mut moment = 0.0;
+        for (i, &power) in self.power.iter().enumerate() {
+            let magnitude = sqrt(power);
+            total += magnitude;
+            moment += magnitude * self.frequency(i);
+        }
+        if total > 1.0e-20 {
+            moment / total
+        } else {
+            0.0
+        }
+    }
+
+    /// Spectral flatness of the latest frame in 0...1. Silent frames have flatness 0.
+    pub fn flatness(&self) -> f64 {
+        let bins = self.bins() as f64;
+        let mean = self.power.iter().sum::<f64>() / bins;
+        if mean > 1.0e-20 {
+            let log_mean = self.power.iter().map(|&p| log(p + 1.0e-20)).sum::<f64>() / bins;
+            min(1.0, exp(log_mean) / mean)
+        } else {
+            0.0
+        }
+    }
+
+    /// Frequency in Hz below which `fraction` of spectral energy of the latest frame lies.
+    /// Silent frames have rolloff 0.
+    pub fn rolloff(&self, fraction: f64) -> f64 {
+        let total = self.power.iter().sum::<f64>();
+        if total <= 1.0e-20 {
+            return 0.0;
+        }
+        let threshold = clamp01(fraction) * total;
+        let mut energy = 0.0;
+        for (i, &power) in self.power.iter().enumerate() {
+            energy += power;
+            if energy >= threshold {
+                return self.frequency(i);
+            }
+        }
+        self.frequency(self.bins() - 1)
+    }
+
+    /// Mel-frequency cepstral coefficients of the latest frame.
+    pub fn mfcc(&self) -> &[f64] {
+        &self.mfcc
+    }
+
+    /// Chroma vector of the latest frame, starting from C. The strongest pitch class has chroma 1.
+    /// Silent frames have zero chroma.
+    pub fn chroma(&self) -> &[f64; 12] {
+        &self.chroma
+    }
+
+    /// Estimated key of the latest frame.
+    pub fn key(&self) -> Key {
+        Key::estimate(&self.chroma)
+    }
+
+    /// Value of `feature` for the latest frame.
+    pub fn feature(&self, feature: Feature) -> f64 {
+        match feature {
+            Feature::Centroid => self.centroid(),
+            Feature::Flatness => self.flatness(),
+            Feature::Rolloff(fraction) => self.rolloff(fraction),
+            Feature::Mfcc(index) => self.mfcc[index],
+            Feature::Chroma(class) => self.chroma[class],
+            Feature::Key => self.key().index() as f64,
+        }
+    }
+}
+
+/// Feature monitor. Passes through input unchanged.
+/// Spectral features of the input are placed in shared variables.
+/// Features are computed from windows of `FEATURE_WINDOW` samples every quarter window.
+/// - Input 0: signal
+/// - Output 0: signal
+pub struct FeatureMonitor<T: Real + Atomic> {
+    analyzer: SpectralFeatures,
+    targets: Vec<(Feature, Arc<T::Storage>)>,
+    /// History of recent inputs (ring buffer).
+    history: Vec<f64>,
+    /// Index of the next input in the history.
+    history_i: usize,
+    /// Latest window in time order.
+    frame: Vec<f64>,
+    /// Position inside the current hop.
+    j: usize,
+    /// Chroma averaged for key estimation.
+    chroma: [f64; 12],
+}
+
+impl<T: Real + Atomic> Clone for FeatureMonitor<T> {
+    fn clone(&self) -> Self {
+        Self {
+            analyzer: self.analyzer.clone(),
+            targets: self
+                .targets
+                .iter()
+                .map(|(feature, shared)| (*feature, Arc::clone(shared)))
+                .collect(),
+            history: self.history.clone(),
+            history_i: self.history_i,
+            frame: self.frame.clone(),
+            j: self.j,
+            chroma: self.chroma,
+        }
+    }
+}
+
+impl<T: Real + Atomic> FeatureMonitor<T> {
+    /// Create a new feature monitor that stores each feature in its shared variable.
+    pub fn new(targets: &[(Feature, Shared<T>)]) -> Self {
+        for (feature, _) in targets {
+            match feature {
+                Feature::Mfcc(index) => assert!(*index < MFCC_COEFFICIENTS),
+                Feature::Chroma(class) => assert!(*class < 12),
+                _ => (),
+            }
+        }
+        Self {
+            analyzer: SpectralFeatures::new(FEATURE_WINDOW, DEFAULT_SR),
+            targets: targets
+                .iter()
+                .map(|(feature, shared)| (*feature, Arc::clone(shared.get_shared())))
+                .collect(),
+            history: vec![0.0; FEATURE_WINDOW],
+            history_i: 0,
+            frame: vec![0.0; FEATURE_WINDOW],
+            j: 0,
+            chroma: [0.0; 12],
+        }
+    }
+
+    /// Analyze the latest window and store features.
+    fn update(&mut self) {
+        let (older, newer) = self.history.split_at(self.history_i);
+        self.frame[..newer.len()].copy_from_slice(newer);
+        self.frame[newer.len()..].copy_from_slice(older);
+        self.analyzer.analyze(&self.frame);
+        let hop_time = (FEATURE_WINDOW / 4) as f64 / self.analyzer.sample_rate();
+        let decay = exp(-hop_time / KEY_TIMESCALE);
+        for (average, &x) in self.chroma.iter_mut().zip(self.analyzer.chroma().iter()) {
+            *average = *average * decay + x * (1.0 - decay);
+        }
+        for (feature, shared) in self.targets.iter() {
+            let value = match feature {
+                Feature::Key => Key::estimate(&self.chroma).index() as f64,
+                _ => self.analyzer.feature(*feature),
+            };
+            T::store(shared, T::from_f64(value));
+        }
+    }
+}
+
+impl<T: Real + Atomic> AudioNode for FeatureMonitor<T> {
+    const ID: u64 = 125;
+    type Sample = T;
+    type Inputs = U1;
+    type Outputs = U1;
+    type Setting = ();
+
+    fn reset(&mut self) {
+        self.history.fill(0.0);
+        self.history_i = 0;
+        self.j = 0;
+        self.chroma = [0.0; 12];
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.analyzer.set_sample_rate(sample_rate);
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        self.history[self.history_i] = input[0].to_f64();
+        self.history_i = (self.history_i + 1) & (FEATURE_WINDOW - 1);
+        self.j += 1;
+        if self.j == FEATURE_WINDOW / 4 {
+            self.j = 0;
+            self.update();
+        }
+        *input
+    }
+
+    fn has_side_effects(&self) -> bool {
+        true
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        input.clone()
+    }
+}
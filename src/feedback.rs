@@ -4,6 +4,8 @@ use super::audionode::*;
 use super::math::*;
 use super::signal::*;
 use super::*;
+use funutd::Rnd;
+use std::any::Any;
 use std::marker::PhantomData;
 
 /// Diffusive Hadamard feedback matrix.
@@ -74,6 +76,198 @@ impl<N: Size<T>, T: Float> FrameUnop<N, T> for FrameHadamard<N, T> {
     }
 }
 
+/// Feedback matrix applied to the frame, for example, a unitary diffusion matrix
+/// of a feedback delay network. The matrix is stored in row-major order.
+#[derive(Clone)]
+pub struct FrameMatrix<N: Size<T>, T: Float> {
+    matrix: Vec<T>,
+    _marker: PhantomData<N>,
+}
+
+impl<N: Size<T>, T: Float> FrameMatrix<N, T> {
+    /// Create feedback matrix from `N` times `N` coefficients in row-major order.
+    pub fn new(matrix: &[f64]) -> Self {
+        assert!(matrix.len() == N::USIZE * N::USIZE);
+        Self {
+            matrix: matrix.iter().map(|&x| T::from_f64(x)).collect(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Normalized Hadamard matrix. `N` must be a power of two.
+    pub fn hadamard() -> Self {
+        let n = N::USIZE;
+        assert!(n.is_power_of_two());
+        let scale = 1.0 / sqrt(n as f64);
+        let matrix: Vec<f64> = (0..n * n)
+            .map(|i| {
+                // The sign of a Sylvester Hadamard entry is the parity of row AND column.
+                if ((i / n) & (i % n)).count_ones() & 1 == 0 {
+                    scale
+                } else {
+                    -scale
+                }
+            })
+            .collect();
+        Self::new(&matrix)
+    }
+
+    /// Householder reflection `I - 2/N * 1`, which mixes every line into every other line.
+    pub fn householder() -> Self {
+        let n = N::USIZE;
+        let matrix: Vec<f64> = (0..n * n)
+            .map(|i| {
+                let x = -2.0 / n as f64;
+                if i / n == i % n {
+                    1.0 + x
+                } else {
+                    x
+                }
+            })
+            .collect();
+        Self::new(&matrix)
+    }
+
+    /// Random orthogonal matrix from `seed`, orthonormalized with Gram-Schmidt.
+    pub fn orthogonal(seed: u64) -> Self {
+        let n = N::USIZE;
+        let mut rnd = Rnd::from_u64(seed);
+        let mut matrix: Vec<f64> = Vec::with_capacity(n * n);
+        while matrix.len() < n * n {
+            let mut row: Vec<f64> = (0..n).map(|_| rnd.f64() * 2.0 - 1.0).collect();
+            for other in matrix.chunks(n) {
+                let dot: f64 = row.iter().zip(other).map(|(x, y)| x * y).sum();
+                for (x, y) in row.iter_mut().zip(other) {
+                    *x -= dot * y;
+                }
+            }
+            let norm = sqrt(row.iter().map(|x| x * x).sum::<f64>());
+            // Retry rows that are nearly linearly dependent on the previous ones.
+            if norm > 1.0e-3 {
+                matrix.extend(row.iter().map(|x| x / norm));
+            }
+        }
+        Self::new(&matrix)
+    }
+
+    /// Matrix coefficient at `row`, `column`.
+    #[inline]
+    pub fn at(&self, row: usize, column: usize) -> T {
+        self.matrix[row * N::USIZE + column]
+    }
+}
+
+impl<N: Size<T>, T: Float> FrameUnop<N, T> for FrameMatrix<N, T> {
+    #[inline]
+    fn unop(&self, x: &Frame<T, N>) -> Frame<T, N> {
+        Frame::generate(|i| {
+            self.matrix[i * N::USIZE..(i + 1) * N::USIZE]
+                .iter()
+                .zip(x.iter())
+                .fold(T::zero(), |sum, (&m, &x)| sum + m * x)
+        })
+    }
+    // Not implemented.
+    // TODO: A matrix is a special op because of interchannel dependencies.
+    #[inline]
+    fn propagate(&self, _: Signal) -> Signal {
+        panic!()
+    }
+    fn assign(&self, _size: usize, _x: &mut [T]) {
+        panic!()
+    }
+}
+
+/// Decay tracker for feedback structures. It detects when the signal in a feedback loop
+/// has stayed at or below a threshold for a hold time, so that the loop state can be cleared.
+/// Clearing the state prevents the long tail from decaying into denormals,
+/// and processing can be skipped until the input rises above the threshold again.
+#[derive(Clone, Default)]
+pub struct DecayTracker {
+    /// Amplitude threshold. Tracking is disabled if the threshold is zero.
+    threshold: f64,
+    /// Hold time in samples.
+    hold: usize,
+    /// Number of consecutive samples at or below the threshold.
+    quiet: usize,
+}
+
+impl DecayTracker {
+    /// Create a decay tracker with amplitude `threshold` (`threshold` >= 0) and `hold` time in samples.
+    /// Tracking is disabled if the threshold is zero.
+    pub fn new(threshold: f64, hold: usize) -> Self {
+        assert!(threshold >= 0.0);
+        Self {
+            threshold,
+            hold: hold.max(1),
+            quiet: 0,
+        }
+    }
+
+    /// Amplitude threshold.
+    #[inline]
+    pub fn threshold(&self) -> f64 {
+        self.threshold
+    }
+
+    /// Hold time in samples.
+    #[inline]
+    pub fn hold(&self) -> usize {
+        self.hold
+    }
+
+    /// Set hold time in samples.
+    pub fn set_hold(&mut self, hold: usize) {
+        self.hold = hold.max(1);
+    }
+
+    /// Whether tracking is enabled.
+    #[inline]
+    pub fn is_enabled(&self) -> bool {
+        self.threshold > 0.0
+    }
+
+    /// Whether the loop has decayed below the threshold for the hold time.
+    #[inline]
+    pub fn is_quiescent(&self) -> bool {
+        self.is_enabled() && self.quiet >= self.hold
+    }
+
+    /// Whether a sample of absolute value `level` is at or below the threshold.
+    #[inline]
+    pub fn is_quiet(&self, level: f64) -> bool {
+        level <= self.threshold
+    }
+
+    /// Track a sample with absolute value `level`.
+    /// Returns true if the loop has just become quiescent, in which case its state should be cleared.
+    #[inline]
+    pub fn track(&mut self, level: f64) -> bool {
+        if !self.is_enabled() {
+            return false;
+        }
+        if level > self.threshold {
+            self.quiet = 0;
+            false
+        } else {
+            self.quiet += 1;
+            self.quiet == self.hold
+        }
+    }
+
+    /// Restart tracking.
+    #[inline]
+    pub fn reset(&mut self) {
+        self.quiet = 0;
+    }
+}
+
+/// Largest absolute value in `x`.
+#[inline]
+pub(crate) fn peak_level<T: Float>(x: &[T]) -> f64 {
+    x.iter().fold(0.0, |level, x| max(level, abs(x.to_f64())))
+}
+
 /// Mix back output of contained node to its input.
 /// The contained node must have an equal number of inputs and outputs.
 #[derive(Clone)]
@@ -92,6 +286,11 @@ where
     // Feedback operator.
     #[allow(dead_code)]
     feedback: U,
+    // Decay tracking of the loop.
+    decay: DecayTracker,
+    // Hold time of decay tracking in seconds.
+    hold: f64,
+    sample_rate: f64,
 }
 
 impl<N, T, X, U> Feedback<N, T, X, U>
@@ -108,11 +307,29 @@ where
             x,
             value: Frame::default(),
             feedback,
+            decay: DecayTracker::default(),
+            hold: 0.0,
+            sample_rate: DEFAULT_SR,
         };
         let hash = node.ping(true, AttoHash::new(Self::ID));
         node.ping(false, hash);
         node
     }
+
+    /// Enable decay tracking. When the input and output have stayed at or below
+    /// amplitude `threshold` for `hold` seconds, the state of the loop is cleared
+    /// and processing is skipped until the input rises above the threshold again.
+    /// The hold time should be at least the longest delay in the loop.
+    /// A zero threshold disables tracking, which is the default.
+    pub fn set_decay_tracking(&mut self, threshold: T, hold: f64) {
+        self.hold = hold;
+        self.decay = DecayTracker::new(threshold.to_f64(), round(hold * self.sample_rate) as usize);
+    }
+
+    /// Whether the loop has decayed and processing is being skipped.
+    pub fn is_quiescent(&self) -> bool {
+        self.decay.is_quiescent()
+    }
 }
 
 impl<N, T, X, U> AudioNode for Feedback<N, T, X, U>
@@ -133,10 +350,13 @@ where
     fn reset(&mut self) {
         self.x.reset();
         self.value = Frame::default();
+        self.decay.reset();
     }
 
     fn set_sample_rate(&mut self, sample_rate: f64) {
         self.x.set_sample_rate(sample_rate);
+        self.sample_rate = sample_rate;
+        self.decay.set_hold(round(self.hold * sample_rate) as usize);
     }
 
     #[inline]
@@ -144,8 +364,22 @@ where
         &mut self,
         input: &Frame<Self::Sample, Self::Inputs>,
     ) -> Frame<Self::Sample, Self::Outputs> {
+        if self.decay.is_quiescent() {
+            if self.decay.is_quiet(peak_level(input)) {
+                return Frame::default();
+            }
+            self.decay.reset();
+        }
         let output = self.x.tick(&(input + self.value.clone()));
         self.value = self.feedback.unop(&output);
+        if self
+            .decay
+            .track(max(peak_level(input), peak_level(&output)))
+        {
+            // The tail has decayed: clear the loop before it reaches denormal levels.
+            self.x.reset();
+            self.value = Frame::default();
+        }
         output
     }
 
@@ -157,6 +391,30 @@ where
         self.x.ping(probe, hash.hash(Self::ID))
     }
 
+    fn visit(&mut self, tag: u64, f: &mut dyn FnMut(&mut dyn Any)) {
+        self.x.visit(tag, f);
+    }
+
+    fn has_side_effects(&self) -> bool {
+        self.x.has_side_effects()
+    }
+
+    fn input_kind(&self, input: usize) -> PortKind {
+        self.x.input_kind(input)
+    }
+
+    fn output_kind(&self, output: usize) -> PortKind {
+        self.x.output_kind(output)
+    }
+
+    fn input_layout(&self) -> Option<ChannelLayout> {
+        self.x.input_layout()
+    }
+
+    fn output_layout(&self) -> Option<ChannelLayout> {
+        self.x.output_layout()
+    }
+
     fn allocate(&mut self) {
         self.x.allocate();
     }
@@ -185,6 +443,11 @@ where
     // Feedback operator.
     #[allow(dead_code)]
     feedback: U,
+    // Decay tracking of the loop.
+    decay: DecayTracker,
+    // Hold time of decay tracking in seconds.
+    hold: f64,
+    sample_rate: f64,
 }
 
 impl<N, T, X, Y, U> Feedback2<N, T, X, Y, U>
@@ -205,11 +468,29 @@ where
             y,
             value: Frame::default(),
             feedback,
+            decay: DecayTracker::default(),
+            hold: 0.0,
+            sample_rate: DEFAULT_SR,
         };
         let hash = node.ping(true, AttoHash::new(Self::ID));
         node.ping(false, hash);
         node
     }
+
+    /// Enable decay tracking. When the input and output have stayed at or below
+    /// amplitude `threshold` for `hold` seconds, the state of the loop is cleared
+    /// and processing is skipped until the input rises above the threshold again.
+    /// The hold time should be at least the longest delay in the loop.
+    /// A zero threshold disables tracking, which is the default.
+    pub fn set_decay_tracking(&mut self, threshold: T, hold: f64) {
+        self.hold = hold;
+        self.decay = DecayTracker::new(threshold.to_f64(), round(hold * self.sample_rate) as usize);
+    }
+
+    /// Whether the loop has decayed and processing is being skipped.
+    pub fn is_quiescent(&self) -> bool {
+        self.decay.is_quiescent()
+    }
 }
 
 impl<N, T, X, Y, U> AudioNode for Feedback2<N, T, X, Y, U>
@@ -234,11 +515,14 @@ where
         self.x.reset();
         self.y.reset();
         self.value = Frame::default();
+        self.decay.reset();
     }
 
     fn set_sample_rate(&mut self, sample_rate: f64) {
         self.x.set_sample_rate(sample_rate);
         self.y.set_sample_rate(sample_rate);
+        self.sample_rate = sample_rate;
+        self.decay.set_hold(round(self.hold * sample_rate) as usize);
     }
 
     #[inline]
@@ -246,8 +530,23 @@ where
         &mut self,
         input: &Frame<Self::Sample, Self::Inputs>,
     ) -> Frame<Self::Sample, Self::Outputs> {
+        if self.decay.is_quiescent() {
+            if self.decay.is_quiet(peak_level(input)) {
+                return Frame::default();
+            }
+            self.decay.reset();
+        }
         let output = self.x.tick(&(input + self.value.clone()));
         self.value = self.feedback.unop(&self.y.tick(&output));
+        if self
+            .decay
+            .track(max(peak_level(input), peak_level(&output)))
+        {
+            // The tail has decayed: clear the loop before it reaches denormal levels.
+            self.x.reset();
+            self.y.reset();
+            self.value = Frame::default();
+        }
         output
     }
 
@@ -259,6 +558,162 @@ where
         self.y.ping(probe, self.x.ping(probe, hash.hash(Self::ID)))
     }
 
+    fn visit(&mut self, tag: u64, f: &mut dyn FnMut(&mut dyn Any)) {
+        self.x.visit(tag, f);
+        self.y.visit(tag, f);
+    }
+
+    fn has_side_effects(&self) -> bool {
+        self.x.has_side_effects() || self.y.has_side_effects()
+    }
+
+    fn input_kind(&self, input: usize) -> PortKind {
+        self.x.input_kind(input)
+    }
+
+    fn output_kind(&self, output: usize) -> PortKind {
+        self.x.output_kind(output)
+    }
+
+    fn input_layout(&self) -> Option<ChannelLayout> {
+        self.x.input_layout()
+    }
+
+    fn output_layout(&self) -> Option<ChannelLayout> {
+        self.x.output_layout()
+    }
+
+    fn allocate(&mut self) {
+        self.x.allocate();
+    }
+}
+
+/// Feedback delay with insert processing `X` inside the feedback loop.
+/// Each repeat passes through `X` before being attenuated and written back into the delay line.
+/// Insert node `X` must have an equal number of inputs and outputs.
+/// Allocates: the delay line.
+/// - Input(s): signal.
+/// - Output(s): echoes (wet signal only).
+#[derive(Clone)]
+pub struct Echo<N, T, X>
+where
+    N: Size<T>,
+    T: Float,
+    X: AudioNode<Sample = T, Inputs = N, Outputs = N>,
+    X::Inputs: Size<T>,
+    X::Outputs: Size<T>,
+{
+    x: X,
+    buffer: Vec<Frame<T, N>>,
+    i: usize,
+    time: f64,
+    feedback: T,
+    sample_rate: f64,
+}
+
+impl<N, T, X> Echo<N, T, X>
+where
+    N: Size<T>,
+    T: Float,
+    X: AudioNode<Sample = T, Inputs = N, Outputs = N>,
+    X::Inputs: Size<T>,
+    X::Outputs: Size<T>,
+{
+    /// Create a new echo with delay `time` in seconds, rounded to the nearest sample
+    /// (minimum one sample), and `feedback` amount applied after the insert.
+    pub fn new(time: f64, feedback: T, x: X) -> Self {
+        let mut node = Echo {
+            x,
+            buffer: vec![],
+            i: 0,
+            time,
+            feedback,
+            sample_rate: 0.0,
+        };
+        node.set_sample_rate(DEFAULT_SR);
+        let hash = node.ping(true, AttoHash::new(Self::ID));
+        node.ping(false, hash);
+        node
+    }
+
+    /// Feedback amount.
+    #[inline]
+    pub fn feedback(&self) -> T {
+        self.feedback
+    }
+
+    /// Set feedback amount.
+    #[inline]
+    pub fn set_feedback(&mut self, feedback: T) {
+        self.feedback = feedback;
+    }
+}
+
+impl<N, T, X> AudioNode for Echo<N, T, X>
+where
+    N: Size<T>,
+    T: Float,
+    X: AudioNode<Sample = T, Inputs = N, Outputs = N>,
+    X::Inputs: Size<T>,
+    X::Outputs: Size<T>,
+{
+    const ID: u64 = 91;
+    type Sample = T;
+    type Inputs = N;
+    type Outputs = N;
+    type Setting = T;
+
+    fn reset(&mut self) {
+        self.x.reset();
+        self.i = 0;
+        self.buffer.fill(Frame::default());
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.x.set_sample_rate(sample_rate);
+        if self.sample_rate != sample_rate {
+            self.sample_rate = sample_rate;
+            let length = max(1.0, round(self.time * sample_rate)) as usize;
+            self.buffer.resize(length, Frame::default());
+            self.i = 0;
+            self.buffer.fill(Frame::default());
+        }
+    }
+
+    fn set(&mut self, setting: Self::Setting) {
+        self.set_feedback(setting);
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let output = self.x.tick(&self.buffer[self.i]);
+        self.buffer[self.i] = input + output.clone() * Frame::splat(self.feedback);
+        self.i += 1;
+        if self.i >= self.buffer.len() {
+            self.i = 0;
+        }
+        output
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        Routing::Arbitrary.propagate(input, self.outputs())
+    }
+
+    fn ping(&mut self, probe: bool, hash: AttoHash) -> AttoHash {
+        self.x.ping(probe, hash.hash(Self::ID))
+    }
+
+    fn visit(&mut self, tag: u64, f: &mut dyn FnMut(&mut dyn Any)) {
+        self.x.visit(tag, f);
+    }
+
+    fn has_side_effects(&self) -> bool {
+        self.x.has_side_effects()
+    }
+
     fn allocate(&mut self) {
         self.x.allocate();
     }
@@ -32,6 +32,20 @@ impl<F: Real> BiquadCoefs<F> {
         Self { a1, a2, b0, b1, b2 }
     }
 
+    /// Returns settings for a Butterworth highpass filter.
+    /// Cutoff is the -3 dB point of the filter in Hz.
+    pub fn butter_highpass(sample_rate: F, cutoff: F) -> Self {
+        let c = F::from_f64;
+        let f: F = tan(cutoff * c(PI) / sample_rate);
+        let a0r: F = c(1.0) / (c(1.0) + c(SQRT_2) * f + f * f);
+        let a1: F = (c(2.0) * f * f - c(2.0)) * a0r;
+        let a2: F = (c(1.0) - c(SQRT_2) * f + f * f) * a0r;
+        let b0: F = a0r;
+        let b1: F = c(-2.0) * b0;
+        let b2: F = b0;
+        Self { a1, a2, b0, b1, b2 }
+    }
+
     /// Returns settings for a constant-gain bandpass resonator.
     /// The center frequency is given in Hz.
     /// Bandwidth is the difference in Hz between -3 dB points of the filter response.
@@ -209,6 +223,13 @@ impl<T: Float, F: Real, N: Size<T>> AudioNode for ButterLowpass<T, F, N> {
         self.set_cutoff(self.cutoff);
     }
 
+    fn input_kind(&self, input: usize) -> PortKind {
+        match input {
+            1 => PortKind::Frequency,
+            _ => PortKind::Audio,
+        }
+    }
+
     #[inline]
     fn tick(
         &mut self,
@@ -294,6 +315,14 @@ impl<T: Float, F: Real, N: Size<T>> AudioNode for Resonator<T, F, N> {
         self.set_center_bandwidth(self.center, self.bandwidth);
     }
 
+    fn input_kind(&self, input: usize) -> PortKind {
+        match input {
+            1 => PortKind::Frequency,
+            2 => PortKind::Frequency,
+            _ => PortKind::Audio,
+        }
+    }
+
     #[inline]
     fn tick(
         &mut self,
@@ -381,6 +410,13 @@ impl<T: Float, F: Real, N: Size<T>> AudioNode for Lowpole<T, F, N> {
         self.set_cutoff(self.cutoff);
     }
 
+    fn input_kind(&self, input: usize) -> PortKind {
+        match input {
+            1 => PortKind::Frequency,
+            _ => PortKind::Audio,
+        }
+    }
+
     #[inline]
     fn tick(
         &mut self,
@@ -563,16 +599,160 @@ impl<T: Float, F: Float> AudioNode for Pinkpass<T, F> {
     fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
         let mut output = new_signal_frame(self.outputs());
         output[0] = input[0].filter(0.0, |r| {
-            let f = frequency * TAU / self.sample_rate.to_f64();
-            let z1 = Complex64::from_polar(1.0, -f);
-            let pole0 = 0.0555179 / (1.0 - 0.99886 * z1);
-            let pole1 = 0.0750759 / (1.0 - 0.99332 * z1);
-            let pole2 = 0.1538520 / (1.0 - 0.96900 * z1);
-            let pole3 = 0.3104856 / (1.0 - 0.86650 * z1);
-            let pole4 = 0.5329522 / (1.0 - 0.55000 * z1);
-            let pole5 = -0.016898 / (1.0 + 0.7616 * z1);
-            r * (pole0 + pole1 + pole2 + pole3 + pole4 + pole5 + 0.115926 * z1 + 0.5362)
-                * 0.115830421
+            r * pinkpass_response(frequency * TAU / self.sample_rate.to_f64())
+        });
+        output
+    }
+}
+
+/// Frequency response of the pinking filter at angular frequency `f` (radians per sample).
+fn pinkpass_response(f: f64) -> Complex64 {
+    let z1 = Complex64::from_polar(1.0, -f);
+    let pole0 = 0.0555179 / (1.0 - 0.99886 * z1);
+    let pole1 = 0.0750759 / (1.0 - 0.99332 * z1);
+    let pole2 = 0.1538520 / (1.0 - 0.96900 * z1);
+    let pole3 = 0.3104856 / (1.0 - 0.86650 * z1);
+    let pole4 = 0.5329522 / (1.0 - 0.55000 * z1);
+    let pole5 = -0.016898 / (1.0 + 0.7616 * z1);
+    (pole0 + pole1 + pole2 + pole3 + pole4 + pole5 + 0.115926 * z1 + 0.5362) * 0.115830421
+}
+
+/// Number of shelving sections in the tilt filter, spaced one octave apart.
+const TILT_SECTIONS: usize = 10;
+
+/// Lowest shelf frequency of the tilt filter in Hz. The slope extends down to here.
+const TILT_LOW: f64 = 20.0;
+
+/// Pivot frequency of the tilt filter in Hz. Gain at the pivot is unity.
+const TILT_PIVOT: f64 = 1000.0;
+
+/// Spectral tilt filter with an adjustable slope in dB/octave (-6...6).
+/// The slope is built from cascaded 1st order shelves and holds from 20 Hz up to
+/// the high treble. Gain is unity at 1 kHz. A slope of -3 dB/octave is a pinking filter.
+/// Setting: slope in dB/octave.
+/// - Input 0: input signal
+/// - Output 0: filtered signal
+#[derive(Default, Clone)]
+pub struct Tilt<T: Float, F: Real> {
+    _marker: std::marker::PhantomData<T>,
+    slope: F,
+    /// Whether gain is calibrated to be the inverse of `Pinkpass` at the pivot.
+    whitening: bool,
+    b0: [F; TILT_SECTIONS],
+    b1: [F; TILT_SECTIONS],
+    a1: [F; TILT_SECTIONS],
+    state: [F; TILT_SECTIONS],
+    gain: F,
+    sample_rate: f64,
+}
+
+impl<T: Float, F: Real> Tilt<T, F> {
+    /// Create new tilt filter with `slope` in dB/octave (-6...6).
+    pub fn new(slope: F) -> Self {
+        let mut node = Tilt::<T, F> {
+            slope,
+            ..Default::default()
+        };
+        node.set_sample_rate(DEFAULT_SR);
+        node
+    }
+
+    /// Create whitening filter (3 dB/octave highpass), the counterpart to `Pinkpass`.
+    /// Gain is calibrated so that the two in series have unity gain.
+    pub fn whitening() -> Self {
+        let mut node = Tilt::<T, F> {
+            slope: F::from_f64(3.0),
+            whitening: true,
+            ..Default::default()
+        };
+        node.set_sample_rate(DEFAULT_SR);
+        node
+    }
+
+    /// Slope in dB/octave.
+    #[inline]
+    pub fn slope(&self) -> F {
+        self.slope
+    }
+
+    /// Set slope in dB/octave (-6...6).
+    pub fn set_slope(&mut self, slope: F) {
+        self.slope = clamp(F::from_f64(-6.0), F::from_f64(6.0), slope);
+        // A slope of -6 dB/octave places zeros one octave above poles.
+        let shift = pow(2.0, -self.slope.to_f64() / 6.0);
+        let nyquist = 0.45 * self.sample_rate;
+        for i in 0..TILT_SECTIONS {
+            let pole = min(nyquist, TILT_LOW * pow(2.0, i as f64));
+            let zero = min(nyquist, pole * shift);
+            let wp = tan(PI * pole / self.sample_rate);
+            let wz = tan(PI * zero / self.sample_rate);
+            self.b0[i] = F::from_f64((1.0 + wz) / (1.0 + wp));
+            self.b1[i] = F::from_f64((wz - 1.0) / (1.0 + wp));
+            self.a1[i] = F::from_f64((wp - 1.0) / (1.0 + wp));
+        }
+        self.gain = F::one();
+        let pivot = TAU * TILT_PIVOT / self.sample_rate;
+        let mut gain = 1.0 / self.shelf_response(pivot).norm();
+        if self.whitening {
+            gain /= pinkpass_response(pivot).norm();
+        }
+        self.gain = F::from_f64(gain);
+    }
+
+    /// Frequency response at angular frequency `f` (radians per sample).
+    fn shelf_response(&self, f: f64) -> Complex64 {
+        let z1 = Complex64::from_polar(1.0, -f);
+        let mut response = Complex64::new(self.gain.to_f64(), 0.0);
+        for i in 0..TILT_SECTIONS {
+            response *=
+                (self.b0[i].to_f64() + self.b1[i].to_f64() * z1) / (1.0 + self.a1[i].to_f64() * z1);
+        }
+        response
+    }
+}
+
+impl<T: Float, F: Real> AudioNode for Tilt<T, F> {
+    const ID: u64 = 130;
+    type Sample = T;
+    type Inputs = U1;
+    type Outputs = U1;
+    type Setting = F;
+
+    fn set(&mut self, setting: Self::Setting) {
+        self.set_slope(setting);
+    }
+
+    fn reset(&mut self) {
+        self.state = [F::zero(); TILT_SECTIONS];
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        if self.sample_rate != sample_rate {
+            self.sample_rate = sample_rate;
+            self.set_slope(self.slope);
+            self.reset();
+        }
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let mut x: F = convert(input[0]);
+        for i in 0..TILT_SECTIONS {
+            // Transposed direct form II.
+            let y = self.b0[i] * x + self.state[i];
+            self.state[i] = self.b1[i] * x - self.a1[i] * y;
+            x = y;
+        }
+        [convert(x * self.gain)].into()
+    }
+
+    fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        output[0] = input[0].filter(0.0, |r| {
+            r * self.shelf_response(frequency * TAU / self.sample_rate)
         });
         output
     }
@@ -718,6 +898,13 @@ impl<T: Float, F: Real, N: Size<T>> AudioNode for Highpole<T, F, N> {
         self.set_cutoff(self.cutoff);
     }
 
+    fn input_kind(&self, input: usize) -> PortKind {
+        match input {
+            1 => PortKind::Frequency,
+            _ => PortKind::Audio,
+        }
+    }
+
     #[inline]
     fn tick(
         &mut self,
@@ -747,3 +934,122 @@ impl<T: Float, F: Real, N: Size<T>> AudioNode for Highpole<T, F, N> {
         output
     }
 }
+
+/// Linkwitz-Riley (4th order) crossover network splitting the input into `N` bands.
+/// Band edges are given in ascending order. Lower bands are phase compensated
+/// with allpass filters, so the sum of all bands has a flat magnitude response.
+/// Use `recombine` to sum the bands.
+/// - Input 0: input signal
+/// - Outputs 0 to N - 1: bands from lowest to highest
+#[derive(Clone)]
+pub struct Crossover<T: Float, N: Size<T>> {
+    _marker: std::marker::PhantomData<(T, N)>,
+    /// Band edges in Hz.
+    frequencies: Vec<f64>,
+    /// Filter chains of bands except the highest.
+    /// Each chain is a lowpass followed by phase compensating allpasses.
+    bands: Vec<Vec<Biquad<f64, f64>>>,
+    /// Highpass filter chains that pass the rest of the signal to the next split.
+    highs: Vec<Vec<Biquad<f64, f64>>>,
+    sample_rate: f64,
+}
+
+impl<T: Float, N: Size<T>> Crossover<T, N> {
+    /// Create new crossover with `N` - 1 band edge `frequencies` in Hz.
+    pub fn new(frequencies: &[T]) -> Self {
+        assert!(frequencies.len() + 1 == N::USIZE);
+        let mut node = Self {
+            _marker: std::marker::PhantomData,
+            frequencies: frequencies.iter().map(|f| f.to_f64()).collect(),
+            bands: vec![Vec::new(); N::USIZE - 1],
+            highs: vec![Vec::new(); N::USIZE - 1],
+            sample_rate: DEFAULT_SR,
+        };
+        node.update_filters();
+        node
+    }
+
+    /// Band edge frequencies in Hz.
+    pub fn frequencies(&self) -> &[f64] {
+        &self.frequencies
+    }
+
+    /// Calculate filter coefficients.
+    fn update_filters(&mut self) {
+        let n = self.frequencies.len();
+        for k in 0..n {
+            let f = self.frequencies[k];
+            let lowpass = BiquadCoefs::butter_lowpass(self.sample_rate, f);
+            let highpass = BiquadCoefs::butter_highpass(self.sample_rate, f);
+            self.bands[k] = vec![Biquad::with_coefs(lowpass); 2];
+            self.highs[k] = vec![Biquad::with_coefs(highpass); 2];
+            // Lowpass and highpass parts of each split above this band
+            // add up to an allpass with the same poles.
+            for j in k + 1..n {
+                let c = BiquadCoefs::butter_lowpass(self.sample_rate, self.frequencies[j]);
+                let allpass = BiquadCoefs::arbitrary(c.a1, c.a2, c.a2, c.a1, 1.0);
+                self.bands[k].push(Biquad::with_coefs(allpass));
+            }
+        }
+    }
+}
+
+impl<T: Float, N: Size<T>> AudioNode for Crossover<T, N> {
+    const ID: u64 = 84;
+    type Sample = T;
+    type Inputs = typenum::U1;
+    type Outputs = N;
+    type Setting = ();
+
+    fn reset(&mut self) {
+        for filter in self.bands.iter_mut().chain(self.highs.iter_mut()).flatten() {
+            filter.reset();
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        if self.sample_rate != sample_rate {
+            self.sample_rate = sample_rate;
+            self.update_filters();
+            self.reset();
+        }
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let mut output = Frame::default();
+        let mut rest = input[0].to_f64();
+        for k in 0..N::USIZE - 1 {
+            let mut band = rest;
+            for filter in self.bands[k].iter_mut() {
+                band = filter.tick(&[band].into())[0];
+            }
+            output[k] = convert(band);
+            for filter in self.highs[k].iter_mut() {
+                rest = filter.tick(&[rest].into())[0];
+            }
+        }
+        output[N::USIZE - 1] = convert(rest);
+        output
+    }
+
+    fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        let omega = frequency / self.sample_rate;
+        let mut rest = Complex64::new(1.0, 0.0);
+        for k in 0..N::USIZE - 1 {
+            let band = self.bands[k]
+                .iter()
+                .fold(rest, |r, filter| r * filter.coefs().response(omega));
+            output[k] = input[0].filter(0.0, |r| r * band);
+            rest = self.highs[k]
+                .iter()
+                .fold(rest, |r, filter| r * filter.coefs().response(omega));
+        }
+        output[N::USIZE - 1] = input[0].filter(0.0, |r| r * rest);
+        output
+    }
+}
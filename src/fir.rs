@@ -2,11 +2,15 @@
 
 use super::audionode::*;
 use super::combinator::*;
+use super::convolve::*;
 use super::math::*;
 use super::signal::*;
 use super::*;
 use num_complex::Complex64;
 use numeric_array::typenum::*;
+use rustfft::algorithm::Radix4;
+use rustfft::Fft;
+use rustfft::FftDirection;
 
 /// FIR filter.
 /// - Input 0: input signal
@@ -27,6 +31,11 @@ impl<T: Float, N: Size<T>> Fir<T, N> {
             sample_rate: DEFAULT_SR,
         }
     }
+
+    /// Filter weights. The last weight applies to the latest input.
+    pub fn weights(&self) -> &Frame<T, N> {
+        &self.w
+    }
 }
 
 impl<T: Float, N: Size<T>> AudioNode for Fir<T, N> {
@@ -82,3 +91,391 @@ impl<T: Float, N: Size<T>> AudioNode for Fir<T, N> {
         output
     }
 }
+
+/// Linear phase FIR filter designed from an arbitrary magnitude response
+/// with the frequency sampling method and a Blackman window.
+/// The filter is redesigned when the sample rate changes.
+/// It is run with zero latency FFT convolution; the filter itself delays
+/// the signal by (`taps` - 1) / 2 samples, which is reported as its latency.
+/// Allocates: filter design and convolution buffers.
+/// - Input 0: input signal
+/// - Output 0: filtered signal
+#[derive(Clone)]
+pub struct ResponseFir<T: Float> {
+    /// Magnitude response breakpoints as (frequency Hz, amplitude gain).
+    points: Vec<(f64, f64)>,
+    taps: usize,
+    convolver: Convolver<T>,
+    sample_rate: f64,
+}
+
+impl<T: Float> ResponseFir<T> {
+    /// Create new filter with `taps` taps from magnitude response `points`,
+    /// given as (frequency Hz, amplitude gain) in ascending order of frequency.
+    /// The response is interpolated linearly between points
+    /// and held constant beyond the first and last points.
+    pub fn new(points: &[(T, T)], taps: usize) -> Self {
+        assert!(!points.is_empty() && taps > 0);
+        let points: Vec<(f64, f64)> = points
+            .iter()
+            .map(|(f, a)| (f.to_f64(), a.to_f64()))
+            .collect();
+        let weights = design_response_fir(&points, taps, DEFAULT_SR);
+        let mut convolver = Convolver::new(&weights);
+        convolver.set_latency((taps - 1) as f64 * 0.5);
+        Self {
+            points,
+            taps,
+            convolver,
+            sample_rate: DEFAULT_SR,
+        }
+    }
+
+    /// Number of taps.
+    pub fn taps(&self) -> usize {
+        self.taps
+    }
+
+    /// Delay of the filter in samples.
+    pub fn delay_samples(&self) -> f64 {
+        (self.taps - 1) as f64 * 0.5
+    }
+}
+
+/// Design `taps` linear phase FIR weights from magnitude response `points` at `sample_rate` Hz.
+fn design_response_fir<T: Float>(points: &[(f64, f64)], taps: usize, sample_rate: f64) -> Vec<T> {
+    // Sample the response on a dense grid.
+    let length = (taps * 4).next_power_of_two();
+    let gain = |f: f64| -> f64 {
+        let i = points.partition_point(|p| p.0 <= f);
+        if i == 0 {
+            points[0].1
+        } else if i == points.len() {
+            points[i - 1].1
+        } else {
+            let (f0, a0) = points[i - 1];
+            let (f1, a1) = points[i];
+            lerp(a0, a1, (f - f0) / (f1 - f0))
+        }
+    };
+    // Apply linear phase delay of (taps - 1) / 2 samples to center the impulse response.
+    let delay = (taps - 1) as f64 * 0.5;
+    let mut spectrum: Vec<Complex64> = (0..length)
+        .map(|i| {
+            let k = if i <= length / 2 { i } else { length - i } as f64;
+            let phase = -TAU * k * delay / length as f64;
+            let x = Complex64::from_polar(gain(k * sample_rate / length as f64), phase);
+            if i <= length / 2 {
+                x
+            } else {
+                x.conj()
+            }
+        })
+        .collect();
+    let fft = Radix4::new(length, FftDirection::Inverse);
+    fft.process(&mut spectrum);
+    (0..taps)
+        .map(|i| {
+            let window = if taps > 1 {
+                FirWindow::Blackman.at((i as f64 - delay) / delay)
+            } else {
+                1.0
+            };
+            T::from_f64(spectrum[i].re / length as f64 * window)
+        })
+        .collect()
+}
+
+impl<T: Float> AudioNode for ResponseFir<T> {
+    const ID: u64 = 89;
+    type Sample = T;
+    type Inputs = U1;
+    type Outputs = U1;
+    type Setting = ();
+
+    fn reset(&mut self) {
+        self.convolver.reset();
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        if self.sample_rate != sample_rate {
+            self.sample_rate = sample_rate;
+            let weights = design_response_fir(&self.points, self.taps, sample_rate);
+            self.convolver = Convolver::new(&weights);
+            self.convolver.set_latency(self.delay_samples());
+            self.convolver.set_sample_rate(sample_rate);
+        }
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        self.convolver.tick(input)
+    }
+
+    fn process(
+        &mut self,
+        size: usize,
+        input: &[&[Self::Sample]],
+        output: &mut [&mut [Self::Sample]],
+    ) {
+        self.convolver.process(size, input, output);
+    }
+
+    fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
+        self.convolver.route(input, frequency)
+    }
+}
+
+/// Window function for FIR filter design.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum FirWindow {
+    /// Rectangular window: narrowest transition band, poorest stopband.
+    Rectangular,
+    /// Hann window.
+    Hann,
+    /// Hamming window.
+    Hamming,
+    /// Blackman window.
+    Blackman,
+    /// Kaiser window with shape parameter beta. Larger beta trades
+    /// a wider transition band for stronger stopband attenuation.
+    Kaiser(f64),
+}
+
+impl FirWindow {
+    /// Value of the window at `x` in -1...1, where 0 is the center of the window.
+    pub fn at(&self, x: f64) -> f64 {
+        match self {
+            FirWindow::Rectangular => 1.0,
+            FirWindow::Hann => 0.5 + 0.5 * cos(PI * x),
+            FirWindow::Hamming => 0.54 + 0.46 * cos(PI * x),
+            FirWindow::Blackman => 0.42 + 0.5 * cos(PI * x) + 0.08 * cos(TAU * x),
+            FirWindow::Kaiser(beta) => {
+                bessel_i0(beta * sqrt(max(0.0, 1.0 - x * x))) / bessel_i0(*beta)
+            }
+        }
+    }
+}
+
+/// Modified Bessel function of the first kind of order zero.
+fn bessel_i0(x: f64) -> f64 {
+    let mut sum = 1.0;
+    let mut term = 1.0;
+    let y = x * x * 0.25;
+    let mut k = 1.0;
+    while term > sum * 1.0e-16 {
+        term *= y / (k * k);
+        sum += term;
+        k += 1.0;
+    }
+    sum
+}
+
+/// Design parameters of a windowed FIR filter.
+#[derive(Copy, Clone, Debug)]
+enum FirDesign {
+    Lowpass(f64),
+    Highpass(f64),
+    Bandpass(f64, f64),
+    Hilbert,
+}
+
+impl FirDesign {
+    /// Calculate `taps` weights at `sample_rate` Hz using `window`.
+    fn weights<T: Float>(&self, taps: usize, window: FirWindow, sample_rate: f64) -> Vec<T> {
+        let center = (taps - 1) as f64 * 0.5;
+        // Ideal lowpass impulse response with cutoff `f` Hz at time `t` samples from center.
+        let sinc = |f: f64, t: f64| -> f64 {
+            let fc = f / sample_rate;
+            if t == 0.0 {
+                2.0 * fc
+            } else {
+                sin(TAU * fc * t) / (PI * t)
+            }
+        };
+        let mut weights: Vec<f64> = (0..taps)
+            .map(|i| {
+                let t = i as f64 - center;
+                let ideal = match self {
+                    FirDesign::Lowpass(f) => sinc(*f, t),
+                    FirDesign::Highpass(f) => (if t == 0.0 { 1.0 } else { 0.0 }) - sinc(*f, t),
+                    FirDesign::Bandpass(f0, f1) => sinc(*f1, t) - sinc(*f0, t),
+                    FirDesign::Hilbert => {
+                        if round(t) as i64 % 2 != 0 {
+                            2.0 / (PI * t)
+                        } else {
+                            0.0
+                        }
+                    }
+                };
+                let w = if taps > 1 { window.at(t / center) } else { 1.0 };
+                ideal * w
+            })
+            .collect();
+        // Normalize gain to unity in the middle of the passband.
+        let normalize = match self {
+            FirDesign::Lowpass(_) => Some(0.0),
+            FirDesign::Highpass(_) => Some(0.5 * sample_rate),
+            FirDesign::Bandpass(f0, f1) => Some(sqrt(*f0 * *f1)),
+            FirDesign::Hilbert => None,
+        };
+        if let Some(f) = normalize {
+            let mut response = Complex64::default();
+            for (i, w) in weights.iter().enumerate() {
+                response += Complex64::from_polar(*w, -TAU * f / sample_rate * i as f64);
+            }
+            let z = response.norm();
+            if z > 0.0 {
+                weights.iter_mut().for_each(|w| *w /= z);
+            }
+        }
+        weights.iter().map(|w| T::from_f64(*w)).collect()
+    }
+}
+
+/// FIR filter with a runtime chosen number of taps.
+/// Filters designed with `fir_lowpass`, `fir_highpass`, `fir_bandpass` and `fir_hilbert`
+/// are linear phase, are redesigned when the sample rate changes,
+/// and report their delay of (taps - 1) / 2 samples as latency.
+/// Allocates: weights and input history.
+/// - Input 0: input signal
+/// - Output 0: filtered signal
+#[derive(Clone)]
+pub struct FirVec<T: Float> {
+    /// Weights in tap order: the first weight applies to the latest input.
+    weights: Vec<T>,
+    /// Input history stored twice in a row so that the latest inputs are contiguous.
+    history: Vec<T>,
+    /// Index of the latest input in the history.
+    history_i: usize,
+    design: Option<(FirDesign, FirWindow)>,
+    /// Latency reported in signal flow analysis, in samples.
+    latency: f64,
+    sample_rate: f64,
+}
+
+impl<T: Float> FirVec<T> {
+    /// Create new FIR filter from `weights` in tap order:
+    /// the first weight applies to the latest input.
+    pub fn new(weights: &[T]) -> Self {
+        assert!(!weights.is_empty());
+        Self {
+            weights: weights.to_vec(),
+            history: vec![T::zero(); weights.len() * 2],
+            history_i: 0,
+            design: None,
+            latency: 0.0,
+            sample_rate: DEFAULT_SR,
+        }
+    }
+
+    /// Create new windowed linear phase filter.
+    fn with_design(design: FirDesign, taps: usize, window: FirWindow) -> Self {
+        assert!(taps > 0);
+        let mut node = Self::new(&design.weights::<T>(taps, window, DEFAULT_SR));
+        node.design = Some((design, window));
+        node.latency = (taps - 1) as f64 * 0.5;
+        node
+    }
+
+    /// Windowed sinc lowpass filter with `cutoff` Hz and `taps` taps.
+    pub fn lowpass(cutoff: T, taps: usize, window: FirWindow) -> Self {
+        Self::with_design(FirDesign::Lowpass(cutoff.to_f64()), taps, window)
+    }
+
+    /// Windowed sinc highpass filter with `cutoff` Hz and `taps` taps.
+    /// The number of taps must be odd.
+    pub fn highpass(cutoff: T, taps: usize, window: FirWindow) -> Self {
+        assert!(taps & 1 == 1);
+        Self::with_design(FirDesign::Highpass(cutoff.to_f64()), taps, window)
+    }
+
+    /// Windowed sinc bandpass filter with passband from `low` Hz to `high` Hz and `taps` taps.
+    pub fn bandpass(low: T, high: T, taps: usize, window: FirWindow) -> Self {
+        Self::with_design(
+            FirDesign::Bandpass(low.to_f64(), high.to_f64()),
+            taps,
+            window,
+        )
+    }
+
+    /// Windowed Hilbert transformer with `taps` taps. The number of taps must be odd.
+    pub fn hilbert(taps: usize, window: FirWindow) -> Self {
+        assert!(taps & 1 == 1);
+        Self::with_design(FirDesign::Hilbert, taps, window)
+    }
+
+    /// Number of taps.
+    pub fn taps(&self) -> usize {
+        self.weights.len()
+    }
+
+    /// Weights in tap order: the first weight applies to the latest input.
+    pub fn weights(&self) -> &[T] {
+        &self.weights
+    }
+}
+
+impl<T: Float> AudioNode for FirVec<T> {
+    const ID: u64 = 90;
+    type Sample = T;
+    type Inputs = U1;
+    type Outputs = U1;
+    type Setting = ();
+
+    fn reset(&mut self) {
+        self.history.fill(T::zero());
+        self.history_i = 0;
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        if self.sample_rate != sample_rate {
+            self.sample_rate = sample_rate;
+            if let Some((design, window)) = self.design {
+                self.weights = design.weights(self.weights.len(), window, sample_rate);
+            }
+        }
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let n = self.weights.len();
+        self.history_i = if self.history_i == 0 {
+            n - 1
+        } else {
+            self.history_i - 1
+        };
+        self.history[self.history_i] = input[0];
+        self.history[self.history_i + n] = input[0];
+        let mut output = T::zero();
+        for (w, x) in self
+            .weights
+            .iter()
+            .zip(self.history[self.history_i..self.history_i + n].iter())
+        {
+            output += *w * *x;
+        }
+        [output].into()
+    }
+
+    fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        output[0] = input[0].filter(self.latency, |r| {
+            let z1 = Complex64::from_polar(1.0, -TAU * frequency / self.sample_rate);
+            let mut z = Complex64::new(1.0, 0.0);
+            let mut x = Complex64::default();
+            for w in self.weights.iter() {
+                x += w.to_f64() * z;
+                z *= z1;
+            }
+            r * x
+        });
+        output
+    }
+}
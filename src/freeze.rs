@@ -0,0 +1,192 @@
+//! Freeze node that caches the output of an expensive generator.
+
+use super::audionode::*;
+use super::math::*;
+use super::signal::*;
+use super::*;
+use numeric_array::typenum::*;
+use std::any::Any;
+
+/// Duration in seconds of the crossfade at the loop point.
+const LOOP_FADE: f64 = 0.01;
+
+/// Freeze node. Renders `duration` seconds of the enclosed generator into an internal wave
+/// and then plays the wave in a loop. The loop point is crossfaded with equal power.
+/// Call `refresh` or apply a setting to render the generator again. Rendering happens
+/// immediately and takes as long as processing `duration` seconds with the enclosed node.
+/// Allocates: the internal wave.
+/// Setting: setting of the enclosed node, followed by a refresh.
+/// - Output(s): outputs of the enclosed generator.
+#[derive(Clone)]
+pub struct Freeze<X>
+where
+    X: AudioNode<Inputs = U0>,
+    X::Outputs: Size<X::Sample>,
+{
+    x: X,
+    duration: f64,
+    sample_rate: f64,
+    /// Rendered wave, one vector per channel.
+    wave: Vec<Vec<X::Sample>>,
+    /// Playback position in samples.
+    position: usize,
+}
+
+impl<X> Freeze<X>
+where
+    X: AudioNode<Inputs = U0>,
+    X::Outputs: Size<X::Sample>,
+{
+    /// Create new freeze node that renders `duration` seconds of generator `x`.
+    pub fn new(x: X, duration: f64) -> Self {
+        assert!(X::Outputs::USIZE > 0 && duration > 0.0);
+        let mut node = Self {
+            x,
+            duration,
+            sample_rate: 0.0,
+            wave: vec![Vec::new(); X::Outputs::USIZE],
+            position: 0,
+        };
+        node.set_sample_rate(DEFAULT_SR);
+        let hash = node.ping(true, AttoHash::new(Self::ID));
+        node.ping(false, hash);
+        node
+    }
+
+    /// Duration of the rendered loop in seconds.
+    pub fn duration(&self) -> f64 {
+        self.duration
+    }
+
+    /// Length of the rendered loop in samples.
+    pub fn length(&self) -> usize {
+        self.wave[0].len()
+    }
+
+    /// Access enclosed node.
+    pub fn node(&self) -> &X {
+        &self.x
+    }
+
+    /// Access enclosed node. Call `refresh` afterwards to render changes.
+    pub fn node_mut(&mut self) -> &mut X {
+        &mut self.x
+    }
+
+    /// Render the enclosed generator again. Playback continues from the current position.
+    /// Does not allocate.
+    pub fn refresh(&mut self) {
+        let length = self.length();
+        let fade = min(round(LOOP_FADE * self.sample_rate) as usize, length / 2);
+        let input = Frame::default();
+        for i in 0..length {
+            let output = self.x.tick(&input);
+            for (channel, wave) in self.wave.iter_mut().enumerate() {
+                wave[i] = output[channel];
+            }
+        }
+        // Continue rendering past the end and fade the continuation into the beginning,
+        // so the loop point joins with the rendered signal.
+        for i in 0..fade {
+            let output = self.x.tick(&input);
+            let angle = (i as f64 + 0.5) / fade as f64 * (PI * 0.5);
+            let fade_in = X::Sample::from_f64(sin(angle));
+            let fade_out = X::Sample::from_f64(cos(angle));
+            for (channel, wave) in self.wave.iter_mut().enumerate() {
+                wave[i] = wave[i] * fade_in + output[channel] * fade_out;
+            }
+        }
+    }
+}
+
+impl<X> AudioNode for Freeze<X>
+where
+    X: AudioNode<Inputs = U0>,
+    X::Outputs: Size<X::Sample>,
+{
+    const ID: u64 = 123;
+    type Sample = X::Sample;
+    type Inputs = U0;
+    type Outputs = X::Outputs;
+    type Setting = X::Setting;
+
+    fn set(&mut self, setting: Self::Setting) {
+        self.x.set(setting);
+        self.refresh();
+    }
+
+    fn reset(&mut self) {
+        self.position = 0;
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        if self.sample_rate != sample_rate {
+            self.sample_rate = sample_rate;
+            self.x.set_sample_rate(sample_rate);
+            let length = max(1, round(self.duration * sample_rate) as usize);
+            for wave in self.wave.iter_mut() {
+                wave.resize(length, X::Sample::zero());
+            }
+            self.position = 0;
+            self.refresh();
+        }
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        _input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let output = Frame::generate(|channel| self.wave[channel][self.position]);
+        self.position += 1;
+        if self.position == self.length() {
+            self.position = 0;
+        }
+        output
+    }
+
+    fn process(
+        &mut self,
+        size: usize,
+        _input: &[&[Self::Sample]],
+        output: &mut [&mut [Self::Sample]],
+    ) {
+        let length = self.length();
+        let mut i = 0;
+        while i < size {
+            let n = min(size - i, length - self.position);
+            for (channel, wave) in self.wave.iter().enumerate() {
+                output[channel][i..i + n].copy_from_slice(&wave[self.position..self.position + n]);
+            }
+            i += n;
+            self.position += n;
+            if self.position == length {
+                self.position = 0;
+            }
+        }
+    }
+
+    fn ping(&mut self, probe: bool, hash: AttoHash) -> AttoHash {
+        self.x.ping(probe, hash.hash(Self::ID))
+    }
+
+    fn visit(&mut self, tag: u64, f: &mut dyn FnMut(&mut dyn Any)) {
+        self.x.visit(tag, f);
+    }
+
+    fn has_side_effects(&self) -> bool {
+        self.x.has_side_effects()
+    }
+
+    fn output_kind(&self, output: usize) -> PortKind {
+        self.x.output_kind(output)
+    }
+
+    fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
+        self.x.route(input, frequency)
+    }
+
+    fn allocate(&mut self) {
+        self.x.allocate();
+    }
+}
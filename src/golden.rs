@@ -0,0 +1,99 @@
+//! Seeded, reproducible render checksums for pinning DSP behavior.
+//!
+//! A golden checksum is computed by rendering a unit from a known state with
+//! pseudorandom input and hashing the output samples with a stable hash.
+//! Storing the checksum in a test lets downstream users detect changes in
+//! the behavior of a graph across fundsp versions and platforms.
+//!
+//! Floating point results may differ in the last bits between platforms, compilers
+//! and math library implementations. To tolerate such differences, output samples are
+//! rounded to a grid with spacing `GOLDEN_RESOLUTION` before they are hashed.
+//! Larger deviations, such as a changed filter design, change the checksum.
+//! A sample that lands very close to a rounding boundary can still flip
+//! to a neighboring grid point; if a checksum is unstable, pick another seed or length.
+//! Checksums of 32-bit and 64-bit units are not comparable with each other.
+
+use super::audiounit::*;
+use super::buffer::*;
+use super::math::*;
+use super::*;
+use duplicate::duplicate_item;
+
+/// Sample rate used for golden renders.
+pub const GOLDEN_SR: f64 = 44100.0;
+
+/// Spacing of the grid output samples are rounded to before hashing (-96 dB).
+pub const GOLDEN_RESOLUTION: f64 = 1.0 / 65536.0;
+
+/// Hash key of golden checksums. Changing this invalidates all stored checksums.
+const GOLDEN_KEY: u64 = 0x676f6c64656e3031;
+
+/// Grid point of a sample. Non-finite samples map to fixed codes.
+fn golden_quantize(x: f64) -> u64 {
+    if x.is_nan() {
+        u64::MAX
+    } else {
+        // Conversion saturates, which also takes care of infinities.
+        round(x / GOLDEN_RESOLUTION) as i64 as u64
+    }
+}
+
+#[duplicate_item(
+    f48       AudioUnit48       golden_checksum48       verify_golden48;
+    [ f64 ]   [ AudioUnit64 ]   [ golden_checksum ]     [ verify_golden ];
+    [ f32 ]   [ AudioUnit32 ]   [ golden_checksum32 ]   [ verify_golden32 ];
+)]
+/// Compute the golden checksum of `length` samples rendered from `unit`.
+/// The unit is set to sample rate `GOLDEN_SR`, its pseudorandom phase is seeded
+/// with `seed` and it is reset. Inputs, if any, receive white noise derived from `seed`.
+/// Rendering uses block processing. The unit is left in its state after rendering.
+pub fn golden_checksum48(unit: &mut dyn AudioUnit48, seed: u64, length: usize) -> u64 {
+    unit.set_sample_rate(GOLDEN_SR);
+    unit.ping(false, AttoHash::new(seed));
+    unit.reset();
+    let inputs = unit.inputs();
+    let outputs = unit.outputs();
+    let mut input = Buffer::<f48>::with_channels(inputs);
+    let mut output = Buffer::<f48>::with_channels(outputs);
+    let mut hash = AttoHash::new(GOLDEN_KEY)
+        .hash(inputs as u64)
+        .hash(outputs as u64)
+        .hash(length as u64);
+    let mut i = 0;
+    while i < length {
+        let size = min(length - i, MAX_BUFFER_SIZE);
+        for channel in 0..inputs {
+            for (j, x) in input.mut_at(channel)[..size].iter_mut().enumerate() {
+                *x = AttoHash::new(seed)
+                    .hash(channel as u64)
+                    .hash((i + j) as u64)
+                    .hash11();
+            }
+        }
+        unit.process(size, input.self_ref(), output.self_mut());
+        for j in 0..size {
+            for channel in 0..outputs {
+                hash = hash.hash(golden_quantize(output.at(channel)[j] as f64));
+            }
+        }
+        i += size;
+    }
+    hash.state()
+}
+
+#[duplicate_item(
+    f48       AudioUnit48       golden_checksum48       verify_golden48;
+    [ f64 ]   [ AudioUnit64 ]   [ golden_checksum ]     [ verify_golden ];
+    [ f32 ]   [ AudioUnit32 ]   [ golden_checksum32 ]   [ verify_golden32 ];
+)]
+/// Verify that `length` samples rendered from `unit` with `seed` match
+/// a golden `checksum` obtained earlier from the checksum function of the same sample type.
+/// Returns true if the checksum matches.
+pub fn verify_golden48(
+    unit: &mut dyn AudioUnit48,
+    seed: u64,
+    length: usize,
+    checksum: u64,
+) -> bool {
+    golden_checksum48(unit, seed, length) == checksum
+}
@@ -111,6 +111,30 @@ impl<
         granular
     }
 
+    /// Set length of 1 revolution along the helix in seconds.
+    /// Grains already playing are not affected.
+    pub fn set_beat_length(&mut self, beat_length: f48) {
+        assert!(beat_length > 0.0);
+        self.beat_length = beat_length;
+    }
+
+    /// Set number of revolutions until the helix returns to its point of origin.
+    pub fn set_beats_per_cycle(&mut self, beats_per_cycle: usize) {
+        assert!(beats_per_cycle > 0);
+        self.beats_per_cycle = beats_per_cycle;
+    }
+
+    /// Set `inner_radius` and `outer_radius` of the helix.
+    pub fn set_radius(&mut self, inner_radius: f48, outer_radius: f48) {
+        self.inner_radius = inner_radius;
+        self.outer_radius = outer_radius;
+    }
+
+    /// Set amount of random jitter added to sample points on the helix.
+    pub fn set_jitter(&mut self, jitter: f48) {
+        self.jitter = jitter;
+    }
+
     /// Position in space at the given time for the given voice.
     fn helix_position(&mut self, voice: usize, time: f48) -> Vec3a {
         let cycle_length = self.beat_length * self.beats_per_cycle as f48;
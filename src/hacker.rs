@@ -1,28 +1,44 @@
 //! The hacker prelude, a fully 64-bit environment for audio processing.
 
+pub use super::adsr::AdsrExp;
+pub use super::amp::*;
+pub use super::analysis::*;
 pub use super::audionode::*;
 pub use super::audiounit::*;
 pub use super::buffer::*;
 pub use super::combinator::*;
+pub use super::convert::*;
+pub use super::convolve::*;
+pub use super::correction::*;
 pub use super::delay::*;
+pub use super::dither::*;
 pub use super::dynamics::*;
 pub use super::envelope::*;
+pub use super::fade::*;
+pub use super::feature::*;
 pub use super::feedback::*;
 pub use super::filter::*;
 pub use super::fir::*;
 pub use super::follow::*;
+pub use super::freeze::*;
 pub use super::gen::*;
+pub use super::golden::*;
 pub use super::granular::*;
 pub use super::math::*;
+pub use super::modulation::*;
 pub use super::moog::*;
 pub use super::net::*;
 pub use super::noise::*;
 pub use super::oscillator::*;
 pub use super::oversample::*;
 pub use super::pan::*;
+pub use super::partials::*;
+pub use super::rack::*;
 pub use super::realnet::*;
 pub use super::realseq::*;
+pub use super::record::*;
 pub use super::resample::*;
+pub use super::reverb::*;
 pub use super::rez::*;
 pub use super::sequencer::*;
 pub use super::setting::*;
@@ -31,11 +47,17 @@ pub use super::shared::*;
 pub use super::signal::*;
 pub use super::slot::*;
 pub use super::snoop::*;
+pub use super::spectral::*;
 pub use super::svf::*;
 pub use super::system::*;
+pub use super::throughput::*;
+pub use super::trace::*;
+pub use super::tuning::*;
+pub use super::voices::*;
 pub use super::wave::*;
 pub use super::wave_stream::*;
 pub use super::wavetable::*;
+pub use super::weighting::*;
 pub use super::*;
 
 #[cfg(feature = "files")]
@@ -288,6 +310,87 @@ pub fn monitor(shared: &Shared<f64>, meter: Meter) -> An<Monitor<f64>> {
     An(Monitor::new(shared, meter))
 }
 
+/// Stereo monitor node. Passes through stereo input. Communicates via the shared variable
+/// an aspect of the input signal according to the chosen metering mode.
+/// Stereo field modes are `Meter::Correlation` and `Meter::Balance`.
+/// - Input 0: left signal
+/// - Input 1: right signal
+/// - Output 0: left signal
+/// - Output 1: right signal
+///
+/// ### Example: Correlation Meter
+/// ```
+/// use fundsp::hacker::*;
+/// let correlation = shared(0.0);
+/// monitor_stereo(&correlation, Meter::Correlation(0.3));
+/// ```
+pub fn monitor_stereo(shared: &Shared<f64>, meter: Meter) -> An<StereoMonitor<f64>> {
+    An(StereoMonitor::new(shared, meter))
+}
+
+/// Feature monitor node. Passes through input. Communicates via the shared variable
+/// a spectral feature of the input signal, updated every 512 samples from a window of 2048 samples.
+/// - Input 0: signal
+/// - Output 0: signal
+///
+/// ### Example: Brightness Monitor
+/// ```
+/// use fundsp::hacker::*;
+/// let centroid = shared(0.0);
+/// monitor_feature(&centroid, Feature::Centroid);
+/// ```
+pub fn monitor_feature(shared: &Shared<f64>, feature: Feature) -> An<FeatureMonitor<f64>> {
+    An(FeatureMonitor::new(&[(feature, shared.clone())]))
+}
+
+/// Feature monitor node for several features. Passes through input.
+/// Each feature is placed in its shared variable.
+/// Features are updated every 512 samples from a window of 2048 samples.
+/// - Input 0: signal
+/// - Output 0: signal
+///
+/// ### Example: First Three MFCCs
+/// ```
+/// use fundsp::hacker::*;
+/// let mfcc: Vec<_> = (0..3).map(|i| (Feature::Mfcc(i), shared(0.0))).collect();
+/// monitor_features(&mfcc);
+/// ```
+pub fn monitor_features(targets: &[(Feature, Shared<f64>)]) -> An<FeatureMonitor<f64>> {
+    An(FeatureMonitor::new(targets))
+}
+
+/// Recording tap. Passes through input and appends it to shared wave `wave`,
+/// so that stems can be captured from inside a playing graph.
+/// Collect the recording with `SharedWave::update`.
+/// - Input 0: signal
+/// - Output 0: signal
+///
+/// ### Example: Record A Stem
+/// ```
+/// use fundsp::hacker::*;
+/// let mut stem = SharedWave::new(1, 44100.0, 1.0);
+/// let mut node = sine_hz(110.0) >> tap_record(&stem);
+/// for _ in 0..44100 {
+///     node.get_mono();
+/// }
+/// drop(node);
+/// stem.update();
+/// assert!(stem.wave().length() == 44100);
+/// ```
+pub fn tap_record(wave: &SharedWave) -> An<TapRecord<f64, U1>> {
+    An(TapRecord::new(wave))
+}
+
+/// Stereo recording tap. Passes through stereo input and appends it to shared wave `wave`,
+/// which must have two channels. Collect the recording with `SharedWave::update`.
+/// - Input 0: left signal
+/// - Input 1: right signal
+/// - Output 0: left signal
+/// - Output 1: right signal
+pub fn tap_record_stereo(wave: &SharedWave) -> An<TapRecord<f64, U2>> {
+    An(TapRecord::new(wave))
+}
+
 /// Meter node.
 /// Outputs a summary of the input according to the chosen metering mode.
 /// - Input 0: signal
@@ -302,6 +405,68 @@ pub fn meter(meter: Meter) -> An<MeterNode<f64>> {
     An(MeterNode::new(meter))
 }
 
+/// Stereo meter node.
+/// Outputs a summary of the stereo input according to the chosen metering mode.
+/// Stereo field modes are `Meter::Correlation` and `Meter::Balance`.
+/// - Input 0: left signal
+/// - Input 1: right signal
+/// - Output 0: summary
+///
+/// ### Example
+/// ```
+/// use fundsp::hacker::*;
+/// meter_stereo(Meter::Balance(0.3));
+/// ```
+pub fn meter_stereo(meter: Meter) -> An<StereoMeterNode<f64>> {
+    An(StereoMeterNode::new(meter))
+}
+
+/// Phase correlation meter with a smoothing timescale of 300 ms.
+/// The output is in -1...1: 1 for identical channels, 0 for unrelated channels
+/// and -1 for channels in opposite phase. Negative values indicate poor mono compatibility.
+/// - Input 0: left signal
+/// - Input 1: right signal
+/// - Output 0: correlation
+///
+/// ### Example: Check The Correlation Of A Stereo Reverb
+/// ```
+/// use fundsp::hacker::*;
+/// (pass() | pass()) >> reverb_stereo(10.0, 2.0) >> phase_meter();
+/// ```
+pub fn phase_meter() -> An<StereoMeterNode<f64>> {
+    An(StereoMeterNode::new(Meter::Correlation(0.3)))
+}
+
+/// Frequency weighting filter (`Weighting::A`, `B`, `C` or `K`) for level measurement.
+/// A-, B- and C-weighting have unity gain at 1 kHz.
+/// - Input 0: signal
+/// - Output 0: weighted signal
+///
+/// ### Example: A-Weighted RMS Level
+/// ```
+/// use fundsp::hacker::*;
+/// weighting(Weighting::A) >> meter(Meter::Rms(0.1));
+/// ```
+pub fn weighting(curve: Weighting) -> An<WeightingNode<f64, f64>> {
+    An(WeightingNode::new(curve))
+}
+
+/// Mono compatibility check. Outputs the average of the stereo channels in both channels.
+/// - Input 0: left signal
+/// - Input 1: right signal
+/// - Output 0: mono sum
+/// - Output 1: mono sum
+pub fn mono_check() -> An<impl AudioNode<Sample = f64, Inputs = U2, Outputs = U2>> {
+    join::<U2>() >> split::<U2>()
+}
+
+/// Invert phase (polarity) of a signal.
+/// - Input 0: signal
+/// - Output 0: inverted signal
+pub fn invert_phase() -> An<impl AudioNode<Sample = f64, Inputs = U1, Outputs = U1>> {
+    -pass()
+}
+
 /// Mono sink. Input is discarded.
 /// -Input 0: signal
 pub fn sink() -> An<Sink<U1, f64>> {
@@ -438,6 +603,69 @@ pub fn butterpass_hz(f: f64) -> An<ButterLowpass<f64, f64, U1>> {
     super::prelude::butterpass_hz(f)
 }
 
+/// Linkwitz-Riley (4th order) crossover that splits the input into `N` phase coherent bands
+/// at `N` - 1 ascending band edge `frequencies` (Hz). The bands sum to a flat magnitude response.
+/// - Input 0: audio
+/// - Outputs 0 to N - 1: bands from lowest to highest
+///
+/// ### Example: Multiband Saturation
+/// ```
+/// use fundsp::hacker::*;
+/// noise() >> crossover::<U3>(&[200.0, 2000.0]) >> (shape(Shape::Tanh(2.0)) | pass() | shape(Shape::Tanh(0.5))) >> recombine::<U3>();
+/// ```
+pub fn crossover<N: Size<f64>>(frequencies: &[f64]) -> An<Crossover<f64, N>> {
+    An(Crossover::new(frequencies))
+}
+
+/// Sum `N` bands from `crossover` back into one signal.
+/// - Inputs 0 to N - 1: bands
+/// - Output 0: sum of bands
+pub fn recombine<N>() -> An<Reduce<N, f64, Pass<f64>, FrameAdd<U1, f64>>>
+where
+    N: Size<f64> + Size<Pass<f64>>,
+    U1: Mul<N>,
+    <U1 as Mul<N>>::Output: Size<f64>,
+{
+    super::prelude::recombine::<N, f64>()
+}
+
+/// Multiband processing. Split the input into `N` bands with `splitter`,
+/// process band `i` with node `f(i)` and join the bands with `recombine`.
+/// For example, the splitter can be a `crossover` and the joiner `recombine()`.
+/// - Input(s): inputs of `splitter`.
+/// - Output(s): outputs of `recombine`.
+///
+/// ### Example: Multiband Saturation
+/// ```
+/// use fundsp::hacker::*;
+/// noise()
+///     >> parallel_bands::<U3, _, _, _, _>(
+///         crossover(&[200.0, 2000.0]),
+///         |i| shape(Shape::Tanh(1.0 + i as f64)),
+///         recombine::<U3>(),
+///     );
+/// ```
+pub fn parallel_bands<N, S, X, R, F>(
+    splitter: An<S>,
+    f: F,
+    recombine: An<R>,
+) -> An<Pipe<f64, Pipe<f64, S, MultiStack<N, f64, X>>, R>>
+where
+    N: Size<f64> + Size<X>,
+    X: AudioNode<Sample = f64>,
+    X::Inputs: Size<f64> + Mul<N>,
+    X::Outputs: Size<f64> + Mul<N>,
+    <X::Inputs as Mul<N>>::Output: Size<f64>,
+    <X::Outputs as Mul<N>>::Output: Size<f64>,
+    S: AudioNode<Sample = f64, Outputs = <X::Inputs as Mul<N>>::Output>,
+    S::Inputs: Size<f64>,
+    R: AudioNode<Sample = f64, Inputs = <X::Outputs as Mul<N>>::Output>,
+    R::Outputs: Size<f64>,
+    F: Fn(i64) -> An<X>,
+{
+    super::prelude::parallel_bands(splitter, f, recombine)
+}
+
 /// One-pole lowpass filter (1st order).
 /// - Input 0: audio
 /// - Input 1: cutoff frequency (Hz)
@@ -523,6 +751,26 @@ pub fn resonator_hz(center: f64, bandwidth: f64) -> An<Resonator<f64, f64, U1>>
     super::prelude::resonator_hz(center, bandwidth)
 }
 
+/// Bank of `N` parallel bandpass filters with a single input and summed output.
+/// Closure `f` maps band index 0...`N` - 1 to (center, Q, gain), where gain is the peak amplitude gain of the band.
+/// Setting: (band index, (center, Q, gain)).
+/// - Input 0: audio
+/// - Output 0: filtered audio
+///
+/// ### Example: Formant Filter
+/// ```
+/// use fundsp::hacker::*;
+/// let formants = [(730.0, 8.0, 1.0), (1090.0, 10.0, 0.5), (2440.0, 12.0, 0.25)];
+/// saw_hz(110.0) >> filter_bank::<U3, _>(|i| formants[i]);
+/// ```
+pub fn filter_bank<N, F>(f: F) -> An<FilterBank<N, f64, f64>>
+where
+    N: Size<f64>,
+    F: Fn(usize) -> (f64, f64, f64),
+{
+    super::prelude::filter_bank(f)
+}
+
 /// An arbitrary biquad filter with coefficients in normalized form.
 /// - Input 0: signal
 /// - Output 0: filtered signal
@@ -779,6 +1027,64 @@ pub fn adsr_live(
     super::adsr::adsr_live(attack, decay, sustain, release)
 }
 
+/// ADSR envelope like `adsr_live` that signals completion through `done`.
+/// `done` is set to one when the release has finished and back to zero when a new attack starts,
+/// so that voice allocators can reclaim voices as soon as they go silent.
+/// - Input 0: control start of attack and release
+/// - Output 0: scaled ADSR value from 0.0 to 1.0
+///
+/// ### Example: Voice Completion
+/// ```
+/// use fundsp::hacker::*;
+/// let done = shared(0.0);
+/// let envelope = adsr_live_done(0.01, 0.1, 0.5, 0.2, &done);
+/// assert!(done.value() == 0.0);
+/// ```
+pub fn adsr_live_done(
+    attack: f64,
+    decay: f64,
+    sustain: f64,
+    release: f64,
+    done: &Shared<f64>,
+) -> An<EnvelopeIn<f64, f64, impl Fn(f64, &Frame<f64, U1>) -> f64 + Clone, U1, f64>> {
+    super::adsr::adsr_live_done(attack, decay, sustain, release, done)
+}
+
+/// ADSR envelope with RC-style exponential curves. The attack charges toward an overshoot level
+/// and ends at 1.0 after `attack` seconds, like an analog envelope generator.
+/// Decay toward `sustain` level and release toward zero fall by 60 dB in `decay` and `release` seconds.
+/// Retriggering starts a new attack from the current level.
+/// Setting: (attack, decay, sustain, release).
+/// - Input 0: gate (attack starts when > 0, release starts when <= 0)
+/// - Output 0: envelope level from 0.0 to 1.0
+///
+/// ### Example: Percussive Pluck
+/// ```
+/// use fundsp::hacker::*;
+/// let gate = shared(1.0);
+/// var(&gate) >> adsr_exp(0.002, 0.3, 0.0, 0.1) * saw_hz(220.0);
+/// ```
+pub fn adsr_exp(attack: f64, decay: f64, sustain: f64, release: f64) -> An<AdsrExp<f64>> {
+    An(AdsrExp::new(attack, decay, sustain, release))
+}
+
+/// Exponential ADSR envelope like `adsr_exp` that signals completion through `done`.
+/// `done` is set to one when the release has finished and back to zero when a new attack starts.
+/// Setting: (attack, decay, sustain, release).
+/// - Input 0: gate (attack starts when > 0, release starts when <= 0)
+/// - Output 0: envelope level from 0.0 to 1.0
+pub fn adsr_exp_done(
+    attack: f64,
+    decay: f64,
+    sustain: f64,
+    release: f64,
+    done: &Shared<f64>,
+) -> An<AdsrExp<f64>> {
+    let mut node = AdsrExp::new(attack, decay, sustain, release);
+    node.set_done(done);
+    An(node)
+}
+
 /// Maximum Length Sequence noise generator from an `n`-bit sequence (1 <= `n` <= 31).
 /// - Output 0: repeating white noise sequence of only -1 and 1 values.
 ///
@@ -853,6 +1159,35 @@ pub fn hold_hz(
     (pass() | dc(f)) >> hold(variability)
 }
 
+/// Noise burst excitation for physical models. A rising edge in the gate input
+/// starts a Hann windowed burst of colored noise lasting `duration` seconds.
+/// `color` in 0...1 is the brightness of the noise from dark (0) to white (1).
+/// Setting: variability in 0...1 is the randomness in the duration and level of individual bursts.
+/// - Input 0: gate.
+/// - Output 0: noise burst.
+///
+/// ### Example: Plucked String Excited By A Noise Burst
+/// ```
+/// use fundsp::hacker::*;
+/// lfo(|t| sin_hz(2.0, t)) >> burst(0.005, 0.7) >> pluck(220.0, 0.5, 0.5);
+/// ```
+pub fn burst(duration: f64, color: f64) -> An<Burst<f64>> {
+    An(Burst::new(duration, color))
+}
+
+/// Impulse train at `frequency` Hz. Impulses fall on the nearest sample.
+/// Setting: variability in 0...1 is the randomness in the timing and level of individual impulses.
+/// - Output 0: impulse train.
+///
+/// ### Example: Pulsed Resonator
+/// ```
+/// use fundsp::hacker::*;
+/// impulse_train(110.0) >> resonator_hz(880.0, 20.0);
+/// ```
+pub fn impulse_train(frequency: f64) -> An<ImpulseTrain<f64>> {
+    An(ImpulseTrain::new(frequency))
+}
+
 /// FIR filter.
 /// - Input 0: signal.
 /// - Output 0: filtered signal.
@@ -866,6 +1201,90 @@ pub fn fir<X: ConstantFrame<Sample = f64>>(weights: X) -> An<Fir<f64, X::Size>>
     An(Fir::new(weights))
 }
 
+/// Windowed sinc lowpass FIR filter with `cutoff` Hz and `taps` taps, designed with `window`.
+/// The filter is linear phase and delays the signal by (`taps` - 1) / 2 samples.
+/// Allocates: weights and input history.
+/// - Input 0: signal
+/// - Output 0: filtered signal
+///
+/// ### Example: Antialiasing Filter
+/// ```
+/// use fundsp::hacker::*;
+/// noise() >> fir_lowpass(8000.0, 127, FirWindow::Kaiser(8.0));
+/// ```
+pub fn fir_lowpass(cutoff: f64, taps: usize, window: FirWindow) -> An<FirVec<f64>> {
+    An(FirVec::lowpass(cutoff, taps, window))
+}
+
+/// Windowed sinc highpass FIR filter with `cutoff` Hz and `taps` taps (`taps` is odd),
+/// designed with `window`. The filter is linear phase and delays the signal by (`taps` - 1) / 2 samples.
+/// Allocates: weights and input history.
+/// - Input 0: signal
+/// - Output 0: filtered signal
+pub fn fir_highpass(cutoff: f64, taps: usize, window: FirWindow) -> An<FirVec<f64>> {
+    An(FirVec::highpass(cutoff, taps, window))
+}
+
+/// Windowed sinc bandpass FIR filter with passband from `low` Hz to `high` Hz and `taps` taps,
+/// designed with `window`. The filter is linear phase and delays the signal by (`taps` - 1) / 2 samples.
+/// Allocates: weights and input history.
+/// - Input 0: signal
+/// - Output 0: filtered signal
+pub fn fir_bandpass(low: f64, high: f64, taps: usize, window: FirWindow) -> An<FirVec<f64>> {
+    An(FirVec::bandpass(low, high, taps, window))
+}
+
+/// Hilbert transformer FIR filter with `taps` taps (`taps` is odd) and a Blackman window.
+/// Shifts the phase of the signal by -90 degrees and delays it by (`taps` - 1) / 2 samples.
+/// Allocates: weights and input history.
+/// - Input 0: signal
+/// - Output 0: phase shifted signal
+///
+/// ### Example: Analytic Signal
+/// ```
+/// use fundsp::hacker::*;
+/// noise() >> (delay(63.0 / 44100.0) ^ fir_hilbert(127));
+/// ```
+pub fn fir_hilbert(taps: usize) -> An<FirVec<f64>> {
+    An(FirVec::hilbert(taps, FirWindow::Blackman))
+}
+
+/// Linear phase FIR filter with `taps` taps designed from an arbitrary magnitude response.
+/// The response is given as `points` of (frequency Hz, amplitude gain) in ascending order
+/// of frequency and is interpolated linearly between them.
+/// The filter delays the signal by (`taps` - 1) / 2 samples.
+/// Allocates: filter design and convolution buffers.
+/// - Input 0: signal
+/// - Output 0: filtered signal
+///
+/// ### Example: Brickwall Lowpass At 1 kHz
+/// ```
+/// use fundsp::hacker::*;
+/// noise() >> fir_from_response(&[(0.0, 1.0), (1000.0, 1.0), (1100.0, 0.0)], 511);
+/// ```
+pub fn fir_from_response(points: &[(f64, f64)], taps: usize) -> An<ResponseFir<f64>> {
+    An(ResponseFir::new(points, taps))
+}
+
+/// Correction filter that flattens the response of `measurement` between 20 Hz and 20 kHz,
+/// for example, at the end of a monitoring chain. The measurement is smoothed to 1/6 octave
+/// and boosts are limited to 6 dB. The filter is linear phase with `taps` taps
+/// and delays the signal by (`taps` - 1) / 2 samples.
+/// For other settings, use `fir_from_response` with `Measurement::correction`.
+/// Allocates: filter design and convolution buffers.
+/// - Input 0: signal
+/// - Output 0: corrected signal
+///
+/// ### Example: Headphone Correction
+/// ```no_run
+/// use fundsp::hacker::*;
+/// let measurement = Measurement::load("headphones.txt").unwrap();
+/// let monitor = correction_fir(&measurement, 2047) | correction_fir(&measurement, 2047);
+/// ```
+pub fn correction_fir(measurement: &Measurement, taps: usize) -> An<ResponseFir<f64>> {
+    super::prelude::correction_fir(measurement, taps)
+}
+
 /// Create a 3-point symmetric FIR from desired `gain` (`gain` >= 0) at the Nyquist frequency.
 /// Results in a monotonic low-pass filter when `gain` < 1.
 /// - Input 0: signal.
@@ -874,6 +1293,24 @@ pub fn fir3(gain: f64) -> Fir<f64, U3> {
     super::prelude::fir3(gain)
 }
 
+/// Zero latency convolution with `impulse` response.
+/// The impulse response is assumed to be at the sample rate of the node.
+/// Allocates: impulse response spectra and input history.
+/// - Input 0: signal.
+/// - Output 0: convolved signal.
+///
+/// ### Example: Convolve With A Short Echo
+/// ```
+/// use fundsp::hacker::*;
+/// let mut impulse = vec![0.0; 1000];
+/// impulse[0] = 1.0;
+/// impulse[999] = 0.5;
+/// convolve(&impulse);
+/// ```
+pub fn convolve(impulse: &[f64]) -> An<Convolver<f64>> {
+    An(Convolver::new(impulse))
+}
+
 /// Single sample delay.
 /// - Input 0: signal.
 /// - Output 0: delayed signal.
@@ -915,6 +1352,26 @@ pub fn delay(t: f64) -> An<Delay<f64>> {
     An(Delay::new(t))
 }
 
+/// Tempo synchronized delay of note `division` (fraction of a whole note),
+/// for example, 0.25 for a quarter note or 0.375 for a dotted eighth note.
+/// The delay time follows the tempo input and is rounded to the nearest sample.
+/// Tempo changes are crossfaded in 50 ms. Supported tempos are 20 to 1000 BPM.
+/// Allocates: the delay line.
+/// Setting: division.
+/// - Input 0: signal.
+/// - Input 1: tempo (BPM).
+/// - Output 0: delayed signal.
+///
+/// ### Example: Dotted Eighth Delay
+/// ```
+/// use fundsp::hacker::*;
+/// let tempo = shared(120.0);
+/// (pass() | var(&tempo)) >> delay_sync(0.375);
+/// ```
+pub fn delay_sync(division: f64) -> An<DelaySync<f64>> {
+    An(DelaySync::new(division))
+}
+
 /// Tapped delay line with cubic interpolation.
 /// Minimum and maximum delay times are in seconds.
 /// Allocates: the delay line.
@@ -952,7 +1409,49 @@ where
     An(Tap::new(min_delay, max_delay))
 }
 
-/// 2x oversample enclosed `node`.
+/// Tapped delay line with interpolation strategy `interpolation`:
+/// `Interpolation::Cubic`, `Interpolation::Lagrange` or `Interpolation::Thiran`.
+/// Minimum and maximum delay times are in seconds.
+/// Allocates: the delay line.
+/// - Input 0: signal.
+/// - Input 1: delay time in seconds.
+/// - Output 0: delayed signal.
+///
+/// ### Example: Chorus Voice
+/// ```
+/// use fundsp::hacker::*;
+/// pass() & (pass() | lfo(|t| lerp11(0.01, 0.02, sin_hz(0.5, t)))) >> tap_interpolated(0.01, 0.02, Interpolation::Lagrange);
+/// ```
+pub fn tap_interpolated(
+    min_delay: f64,
+    max_delay: f64,
+    interpolation: Interpolation,
+) -> An<Tap<U1, f64>> {
+    An(Tap::with_interpolation(min_delay, max_delay, interpolation))
+}
+
+/// Tapped delay line with interpolation strategy `interpolation`.
+/// The number of taps is `N`.
+/// Minimum and maximum delay times are in seconds.
+/// Allocates: the delay line.
+/// - Input 0: signal.
+/// - Inputs 1...N: delay time in seconds.
+/// - Output 0: delayed signal.
+pub fn multitap_interpolated<N>(
+    min_delay: f64,
+    max_delay: f64,
+    interpolation: Interpolation,
+) -> An<Tap<N, f64>>
+where
+    N: Size<f64> + Add<U1>,
+    <N as Add<U1>>::Output: Size<f64>,
+{
+    An(Tap::with_interpolation(min_delay, max_delay, interpolation))
+}
+
+/// 2x oversample enclosed `node` using minimum phase filters.
+/// Latency is about 2 samples at low frequencies plus half the latency of `node`
+/// (which is measured at the inner sample rate).
 /// - Inputs and outputs: from `node`.
 ///
 /// ### Example: Oversampled FM Oscillator
@@ -973,6 +1472,54 @@ where
     An(Oversampler::new(DEFAULT_SR, node.0))
 }
 
+/// 2x oversample enclosed `node` using linear phase filters.
+/// Latency is exactly `OVERSAMPLE_LINEAR_LATENCY` (42) samples plus half the latency of `node`
+/// (which is measured at the inner sample rate).
+/// - Inputs and outputs: from `node`.
+///
+/// ### Example: Oversampled Saturation
+/// ```
+/// use fundsp::hacker::*;
+/// let mut x = oversample_linear(shape(Shape::Tanh(2.0)));
+/// assert_eq!(x.latency(), Some(42.0));
+/// ```
+pub fn oversample_linear<X>(node: An<X>) -> An<Oversampler<f64, X>>
+where
+    X: AudioNode<Sample = f64>,
+    X::Inputs: Size<f64>,
+    X::Outputs: Size<f64>,
+    X::Inputs: Size<Frame<f64, U128>>,
+    X::Outputs: Size<Frame<f64, U128>>,
+{
+    super::prelude::oversample_linear(node)
+}
+
+/// 2x oversample enclosed `node` using linear phase filters
+/// and process a reference copy of `node` at the base sample rate, delayed to align with it.
+/// Subtracting the two sets of outputs measures the effect of oversampling, such as aliasing.
+/// - Inputs: from `node`.
+/// - Outputs: oversampled outputs of `node`, followed by reference outputs of `node`.
+///
+/// ### Example: Oversampling A/B Test
+/// ```
+/// use fundsp::hacker::*;
+/// let mut x = oversample_latency_compensated(shape(Shape::Tanh(4.0))) >> (pass() - pass());
+/// ```
+pub fn oversample_latency_compensated<X>(
+    node: An<X>,
+) -> An<Branch<f64, Oversampler<f64, X>, OversampleReference<f64, X>>>
+where
+    X: AudioNode<Sample = f64>,
+    X::Inputs: Size<f64>,
+    X::Outputs: Size<f64>,
+    X::Inputs: Size<Frame<f64, U128>>,
+    X::Outputs: Size<Frame<f64, U128>>,
+    X::Outputs: Add<X::Outputs>,
+    <X::Outputs as Add<X::Outputs>>::Output: Size<f64>,
+{
+    super::prelude::oversample_latency_compensated(node)
+}
+
 /// Resample enclosed generator `node` using cubic interpolation
 /// at speed obtained from input 0, where 1 is the original speed.
 /// Input 0: Sampling speed.
@@ -992,6 +1539,28 @@ where
     An(Resampler::new(DEFAULT_SR, node.0))
 }
 
+/// Evaluate enclosed control `node` once every `divisor` samples (`divisor` > 0)
+/// and interpolate its outputs linearly in between. Saves CPU when `node`
+/// is expensive and changes slowly, for example, an LFO or envelope feeding many parameters.
+/// Outputs lag behind `node` by up to `divisor` samples.
+/// Setting: forwarded to `node`.
+/// - Input(s): inputs of `node`, sampled once every `divisor` samples.
+/// - Output(s): interpolated outputs of `node`.
+///
+/// ### Example: Control Rate LFO
+/// ```
+/// use fundsp::hacker::*;
+/// control_rate(32, lfo(|t| xerp11(110.0, 220.0, sin_hz(0.5, t)))) >> sine();
+/// ```
+pub fn control_rate<X>(divisor: usize, node: An<X>) -> An<ControlRate<f64, X>>
+where
+    X: AudioNode<Sample = f64>,
+    X::Inputs: Size<f64>,
+    X::Outputs: Size<f64>,
+{
+    An(ControlRate::new(DEFAULT_SR, divisor, node.0))
+}
+
 /// Mix output of enclosed circuit `node` back to its input.
 /// Feedback circuit `node` must have an equal number of inputs and outputs.
 /// - Input(s): signal.
@@ -1039,6 +1608,31 @@ where
     An(Feedback2::new(node.0, loopback.0, FrameId::new()))
 }
 
+/// Echo with delay `time` in seconds, `feedback` amount and `insert` processing
+/// (damping, pitch shifting, bitcrushing...) inside the feedback loop.
+/// Every repeat passes through `insert` before it is attenuated by `feedback`
+/// and written back into the delay line.
+/// Insert node `insert` must have an equal number of inputs and outputs.
+/// The output contains only the echoes; mix in the dry signal with `pass() & echo(...)`.
+/// Setting: feedback amount.
+/// - Input(s): signal.
+/// - Output(s): echoes.
+///
+/// ### Example: Damped Echo
+/// ```
+/// use fundsp::hacker::*;
+/// pass() & echo(0.25, 0.5, lowpole_hz(2000.0));
+/// ```
+pub fn echo<N, X>(time: f64, feedback: f64, insert: An<X>) -> An<Echo<N, f64, X>>
+where
+    N: Size<f64>,
+    X: AudioNode<Sample = f64, Inputs = N, Outputs = N>,
+    X::Inputs: Size<f64>,
+    X::Outputs: Size<f64>,
+{
+    An(Echo::new(time, feedback, insert.0))
+}
+
 /// Transform channels freely. Accounted as non-linear processing for signal flow.
 ///
 /// ### Example: Max Operator
@@ -1085,6 +1679,7 @@ pub fn dcblock() -> An<DCBlock<f64, f64>> {
 }
 
 /// Apply 10 ms of fade-in to signal at time zero.
+/// Setting: fade-in duration.
 /// - Input 0: input signal
 /// - Output 0: signal with fade-in
 pub fn declick() -> An<Declick<f64, f64>> {
@@ -1092,6 +1687,7 @@ pub fn declick() -> An<Declick<f64, f64>> {
 }
 
 /// Apply `t` seconds of fade-in to signal at time zero.
+/// Setting: fade-in duration.
 /// - Input 0: input signal
 /// - Output 0: signal with fade-in
 pub fn declick_s(t: f64) -> An<Declick<f64, f64>> {
@@ -1106,6 +1702,7 @@ pub fn shape_fn<S: Fn(f64) -> f64 + Clone + Send + Sync>(f: S) -> An<ShaperFn<f6
 }
 
 /// Shape signal according to shaping mode.
+/// Setting: shape.
 /// - Input 0: input signal
 /// - Output 0: shaped signal
 ///
@@ -1118,6 +1715,105 @@ pub fn shape(mode: Shape<f64>) -> An<Shaper<f64>> {
     super::prelude::shape(mode)
 }
 
+/// Sum `N` signals on a bus with soft `tanh` saturation, antialiased with ADAA.
+/// Drive (`drive` > 0) is gain applied before saturation. Small signals pass at unity gain.
+/// Setting: drive.
+/// - Input(s): `N` signals to sum
+/// - Output 0: saturated sum
+///
+/// ### Example: Analog Style Summing Of Four Tracks
+/// ```
+/// use fundsp::hacker::*;
+/// bus_saturate::<U4>(2.0);
+/// ```
+pub fn bus_saturate<N: Size<f64>>(drive: f64) -> An<BusSaturate<N, f64>> {
+    An(BusSaturate::new(drive))
+}
+
+/// Convert MIDI note numbers to frequency in Hz.
+/// - Input 0: MIDI note number
+/// - Output 0: frequency (Hz)
+///
+/// ### Example: Pitch Control
+/// ```
+/// use fundsp::hacker::*;
+/// let note = shared(60.0);
+/// var(&note) >> midi_to_hz() >> saw();
+/// ```
+pub fn midi_to_hz() -> An<Convert<f64>> {
+    An(Convert::new(Conversion::MidiToHz))
+}
+
+/// Convert frequency in Hz to fractional MIDI note numbers.
+/// - Input 0: frequency (Hz)
+/// - Output 0: MIDI note number
+pub fn hz_to_midi() -> An<Convert<f64>> {
+    An(Convert::new(Conversion::HzToMidi))
+}
+
+/// Convert decibels to amplitude gain.
+/// - Input 0: level (dB)
+/// - Output 0: amplitude gain
+pub fn db_to_amp() -> An<Convert<f64>> {
+    An(Convert::new(Conversion::DbToAmp))
+}
+
+/// Convert amplitude gain to decibels. Zero gain converts to negative infinity.
+/// - Input 0: amplitude gain
+/// - Output 0: level (dB)
+pub fn amp_to_db() -> An<Convert<f64>> {
+    An(Convert::new(Conversion::AmpToDb))
+}
+
+/// Convert tempo in BPM to the rate in Hz of notes lasting `division` (`division` > 0)
+/// of a whole note. For example, 0.25 is a quarter note.
+/// - Input 0: tempo (BPM)
+/// - Output 0: note rate (Hz)
+///
+/// ### Example: Tremolo Synchronized To Eighth Notes
+/// ```
+/// use fundsp::hacker::*;
+/// let tempo = shared(120.0);
+/// pass() * (var(&tempo) >> bpm_to_hz(0.125) >> sine() * 0.5 + 0.5);
+/// ```
+pub fn bpm_to_hz(division: f64) -> An<Convert<f64>> {
+    An(Convert::new(Conversion::BpmToHz(division)))
+}
+
+/// Convert MIDI note numbers to frequency in Hz in `tuning`.
+/// Fractional note numbers are interpolated exponentially between adjacent keys.
+/// - Input 0: MIDI note number
+/// - Output 0: frequency (Hz)
+///
+/// ### Example: 19-Tone Equal Temperament
+/// ```
+/// use fundsp::hacker::*;
+/// let tuning = std::sync::Arc::new(Tuning::equal(19));
+/// let note = shared(60.0);
+/// var(&note) >> midi_to_hz_tuned(&tuning) >> saw();
+/// ```
+pub fn midi_to_hz_tuned(tuning: &Arc<Tuning>) -> An<TunedPitch<f64>> {
+    An(TunedPitch::new(tuning))
+}
+
+/// Shape signal with an audio-rate drive input.
+/// Drive is input gain for the clipping and distortion modes
+/// and a multiplier of the number of levels in `Shape::Crush` and `Shape::SoftCrush`.
+/// Drive 1 is equal to `shape(mode)`.
+/// Setting: shape.
+/// - Input 0: input signal
+/// - Input 1: drive
+/// - Output 0: shaped signal
+///
+/// ### Example: Distortion Swell
+/// ```
+/// use fundsp::hacker::*;
+/// (pass() | envelope(|t| 1.0 + t)) >> shape_mod(Shape::Tanh(1.0));
+/// ```
+pub fn shape_mod(mode: Shape<f64>) -> An<ShaperMod<f64>> {
+    super::prelude::shape_mod(mode)
+}
+
 /// Clip signal to -1...1.
 /// - Input 0: input signal
 /// - Output 0: clipped signal
@@ -1161,6 +1857,145 @@ pub fn pan(pan: f64) -> An<Panner<f64, U1>> {
     An(Panner::new(pan))
 }
 
+/// Mono tremolo with LFO `rate` in Hz, `depth` in 0...1 and LFO waveform `shape`.
+/// Setting: (rate, depth).
+/// - Input 0: signal
+/// - Output 0: modulated signal
+///
+/// ### Example: Square Wave Tremolo
+/// ```
+/// use fundsp::hacker::*;
+/// saw_hz(110.0) >> tremolo(5.0, 0.5, LfoShape::Square);
+/// ```
+pub fn tremolo(rate: f64, depth: f64, shape: LfoShape) -> An<Tremolo<f64, U1, U0>> {
+    An(Tremolo::new(rate, depth, shape, 0.0))
+}
+
+/// Stereo tremolo with LFO `rate` in Hz, `depth` in 0...1, LFO waveform `shape`
+/// and LFO phase `offset` of the right channel in cycles (for example, 0.25).
+/// Setting: (rate, depth).
+/// - Input 0: left signal
+/// - Input 1: right signal
+/// - Output 0: modulated left signal
+/// - Output 1: modulated right signal
+pub fn tremolo_stereo(
+    rate: f64,
+    depth: f64,
+    shape: LfoShape,
+    offset: f64,
+) -> An<Tremolo<f64, U2, U0>> {
+    An(Tremolo::new(rate, depth, shape, offset))
+}
+
+/// Tempo synchronized mono tremolo with LFO period of note `division` (fraction of a whole note),
+/// `depth` in 0...1 and LFO waveform `shape`.
+/// Setting: (division, depth).
+/// - Input 0: signal
+/// - Input 1: tempo (BPM)
+/// - Output 0: modulated signal
+pub fn tremolo_sync(division: f64, depth: f64, shape: LfoShape) -> An<Tremolo<f64, U1, U1>> {
+    An(Tremolo::new(division, depth, shape, 0.0))
+}
+
+/// Auto-panner with LFO `rate` in Hz, `depth` in 0...1 and LFO waveform `shape`.
+/// Setting: (rate, depth).
+/// - Input 0: signal
+/// - Output 0: left channel
+/// - Output 1: right channel
+///
+/// ### Example: Slow Auto-Pan
+/// ```
+/// use fundsp::hacker::*;
+/// saw_hz(110.0) >> autopan(0.25, 1.0, LfoShape::Sine);
+/// ```
+pub fn autopan(rate: f64, depth: f64, shape: LfoShape) -> An<Autopan<f64, U0>> {
+    An(Autopan::new(rate, depth, shape))
+}
+
+/// Tempo synchronized auto-panner with LFO period of note `division` (fraction of a whole note),
+/// `depth` in 0...1 and LFO waveform `shape`.
+/// Setting: (division, depth).
+/// - Input 0: signal
+/// - Input 1: tempo (BPM)
+/// - Output 0: left channel
+/// - Output 1: right channel
+pub fn autopan_sync(division: f64, depth: f64, shape: LfoShape) -> An<Autopan<f64, U1>> {
+    An(Autopan::new(division, depth, shape))
+}
+
+/// Haas effect stereo placement with `delay` in ms (-40...40) and `level` in dB
+/// of the delayed channel. Positive delays place the source to the left, negative to the right.
+/// Frequencies below 200 Hz are not delayed, which keeps the bass mono compatible.
+/// Allocates: the delay line.
+/// Setting: (delay, level).
+/// - Input 0: mono signal
+/// - Output 0: left output
+/// - Output 1: right output
+/// - Output 2: mono compatibility in 0...1 (1 is fully compatible)
+///
+/// ### Example: Place A Source To The Left
+/// ```
+/// use fundsp::hacker::*;
+/// noise() >> haas(10.0, -1.0) >> (pass() | pass() | sink());
+/// ```
+pub fn haas(delay: f64, level: f64) -> An<Haas<f64>> {
+    An(Haas::new(delay, level))
+}
+
+/// Downmix from `M` to `N` channels using ITU-R BS.775 coefficients, e.g., 7.1 to 5.1 or 5.1 to stereo.
+/// Supported layouts are mono, stereo, quad, 5.1 and 7.1. Channel orders are:
+/// stereo (L, R); quad (L, R, Ls, Rs); 5.1 (L, R, C, LFE, Ls, Rs);
+/// and 7.1 (L, R, C, LFE, Lss, Rss, Lrs, Rrs). The LFE channel is discarded.
+/// - Input(s): `M` channels.
+/// - Output(s): `N` channels.
+///
+/// ### Example: Fold Down 5.1 To Stereo
+/// ```
+/// use fundsp::hacker::*;
+/// downmix::<U6, U2>();
+/// ```
+pub fn downmix<M, N>() -> An<Remix<M, N, f64>>
+where
+    M: Size<f64>,
+    N: Size<f64>,
+{
+    An(Remix::downmix())
+}
+
+/// Upmix from `M` to `N` channels with strategy `mode`.
+/// Supported layouts are mono, stereo, quad, 5.1 and 7.1.
+/// `Upmix::Direct` places channels in their counterparts, leaving the rest silent.
+/// `Upmix::Spread` also fills silent front and surround channels from their neighbors at -3 dB.
+/// - Input(s): `M` channels.
+/// - Output(s): `N` channels.
+///
+/// ### Example: Spread Stereo To 5.1
+/// ```
+/// use fundsp::hacker::*;
+/// upmix::<U2, U6>(Upmix::Spread);
+/// ```
+pub fn upmix<M, N>(mode: Upmix) -> An<Remix<M, N, f64>>
+where
+    M: Size<f64>,
+    N: Size<f64>,
+{
+    An(Remix::upmix(mode))
+}
+
+/// Dither and quantize the signal to `bits` bits (2 <= `bits` <= 32) with TPDF dither
+/// and noise `shaping`. Use before sinks that truncate samples to a lower bit depth.
+/// - Input 0: signal in -1...1
+/// - Output 0: quantized signal
+///
+/// ### Example: Dither Stereo Output To 16 Bits
+/// ```
+/// use fundsp::hacker::*;
+/// dither(16, NoiseShaping::SecondOrder) | dither(16, NoiseShaping::SecondOrder);
+/// ```
+pub fn dither(bits: u32, shaping: NoiseShaping) -> An<Dither<f64>> {
+    An(Dither::new(bits, shaping))
+}
+
 /// Parameter follower filter with halfway response time `t` seconds.
 /// - Input 0: input signal
 /// - Output 0: smoothed signal
@@ -1175,9 +2010,60 @@ pub fn follow<S: ScalarOrPair<Sample = f64>>(t: S) -> An<AFollow<f64, f64, S>> {
     An(AFollow::new(DEFAULT_SR, t))
 }
 
+/// Automatic gain control. Adjusts gain slowly to keep long-term RMS level near `target_db` dB
+/// (for example, -20.0). Gain is limited to `max_gain_db` dB of boost or cut (for example, 20.0)
+/// and changes at most `speed` dB per second (for example, 3.0).
+/// Gain is held while input is silent.
+/// Setting: (target, max gain, speed).
+/// - Input 0: signal
+/// - Output 0: gain adjusted signal
+///
+/// ### Example: Level Microphone Input
+/// ```
+/// use fundsp::hacker::*;
+/// pink() >> agc(-20.0, 20.0, 3.0);
+/// ```
+pub fn agc(target_db: f64, max_gain_db: f64, speed: f64) -> An<Agc<f64>> {
+    An(Agc::new(DEFAULT_SR, target_db, max_gain_db, speed))
+}
+
+/// Gain control with initial gain `db` dB. Gain changes are ramped
+/// linearly in amplitude in 20 ms. Negative infinity dB mutes the signal.
+/// Setting: gain in dB.
+/// - Input 0: signal
+/// - Output 0: signal with gain applied
+///
+/// ### Example: Attenuate By 6 dB
+/// ```
+/// use fundsp::hacker::*;
+/// let (sender, node) = listen(gain_db(-6.0));
+/// let graph = sine_hz(440.0) >> node;
+/// sender.try_send(-12.0).expect("Cannot send setting.");
+/// ```
+pub fn gain_db(db: f64) -> An<Fader<f64>> {
+    An(Fader::new(DEFAULT_SR, db))
+}
+
+/// Fader with initial gain of 0 dB. Gain changes are ramped
+/// linearly in amplitude in 20 ms. Negative infinity dB mutes the signal.
+/// Setting: gain in dB.
+/// - Input 0: signal
+/// - Output 0: signal with gain applied
+///
+/// ### Example: Mute A Signal Smoothly
+/// ```
+/// use fundsp::hacker::*;
+/// let (sender, node) = listen(fader());
+/// sender.try_send(f64::NEG_INFINITY).expect("Cannot send setting.");
+/// ```
+pub fn fader() -> An<Fader<f64>> {
+    An(Fader::new(DEFAULT_SR, 0.0))
+}
+
 /// Look-ahead limiter with `(attack, release)` times in seconds.
 /// Look-ahead is equal to the attack time.
 /// Allocates: look-ahead buffers.
+/// Setting: ceiling amplitude.
 /// - Input 0: signal
 /// - Output 0: signal limited to -1...1
 pub fn limiter<S: ScalarOrPair<Sample = f64>>(time: S) -> An<Limiter<f64, U1, S>> {
@@ -1187,6 +2073,7 @@ pub fn limiter<S: ScalarOrPair<Sample = f64>>(time: S) -> An<Limiter<f64, U1, S>
 /// Stereo look-ahead limiter with `(attack, release)` times in seconds.
 /// Look-ahead is equal to the attack time.
 /// Allocates: look-ahead buffers.
+/// Setting: ceiling amplitude.
 /// - Input 0: left signal
 /// - Input 1: right signal
 /// - Output 0: left signal limited to -1...1
@@ -1195,6 +2082,80 @@ pub fn limiter_stereo<S: ScalarOrPair<Sample = f64>>(time: S) -> An<Limiter<f64,
     An(Limiter::new(DEFAULT_SR, time))
 }
 
+/// Look-ahead limiter with `(attack, release)` times in seconds
+/// and an audio-rate ceiling input in dB.
+/// Look-ahead is equal to the attack time.
+/// Allocates: look-ahead buffers.
+/// - Input 0: signal
+/// - Input 1: ceiling (dB)
+/// - Output 0: signal limited to the ceiling
+///
+/// ### Example: Ceiling Ramp
+/// ```
+/// use fundsp::hacker::*;
+/// (pink() | envelope(|t| -6.0 * t)) >> limiter_mod((0.005, 0.1));
+/// ```
+pub fn limiter_mod<S: ScalarOrPair<Sample = f64>>(time: S) -> An<LimiterMod<f64, U1, S>> {
+    An(LimiterMod::new(DEFAULT_SR, time))
+}
+
+/// Stereo look-ahead limiter with `(attack, release)` times in seconds
+/// and an audio-rate ceiling input in dB.
+/// Look-ahead is equal to the attack time.
+/// Allocates: look-ahead buffers.
+/// - Input 0: left signal
+/// - Input 1: right signal
+/// - Input 2: ceiling (dB)
+/// - Output 0: left signal limited to the ceiling
+/// - Output 1: right signal limited to the ceiling
+pub fn limiter_stereo_mod<S: ScalarOrPair<Sample = f64>>(time: S) -> An<LimiterMod<f64, U2, S>> {
+    An(LimiterMod::new(DEFAULT_SR, time))
+}
+
+/// Look-ahead limiter with `(attack, release)` times in seconds, true peak detection
+/// and a soft clipping stage that keeps output below `ceiling` dB (for example, -1.0).
+/// Look-ahead is equal to the attack time. True peak detection adds 4 samples of latency.
+/// Allocates: look-ahead buffers.
+/// Setting: ceiling amplitude.
+/// - Input 0: signal
+/// - Output 0: signal limited to the ceiling
+///
+/// ### Example: Limit To -1 dBTP
+/// ```
+/// use fundsp::hacker::*;
+/// pink() >> limiter_true_peak((0.005, 0.1), -1.0);
+/// ```
+pub fn limiter_true_peak<S: ScalarOrPair<Sample = f64>>(
+    time: S,
+    ceiling: f64,
+) -> An<Limiter<f64, U1, S>> {
+    let mut limiter = Limiter::new(DEFAULT_SR, time);
+    limiter.set_ceiling(db_amp(ceiling));
+    limiter.set_true_peak(true);
+    limiter.set_soft_clip(true);
+    An(limiter)
+}
+
+/// Stereo look-ahead limiter with `(attack, release)` times in seconds, true peak detection
+/// and a soft clipping stage that keeps output below `ceiling` dB (for example, -1.0).
+/// Look-ahead is equal to the attack time. True peak detection adds 4 samples of latency.
+/// Allocates: look-ahead buffers.
+/// Setting: ceiling amplitude.
+/// - Input 0: left signal
+/// - Input 1: right signal
+/// - Output 0: left signal limited to the ceiling
+/// - Output 1: right signal limited to the ceiling
+pub fn limiter_stereo_true_peak<S: ScalarOrPair<Sample = f64>>(
+    time: S,
+    ceiling: f64,
+) -> An<Limiter<f64, U2, S>> {
+    let mut limiter = Limiter::new(DEFAULT_SR, time);
+    limiter.set_ceiling(db_amp(ceiling));
+    limiter.set_true_peak(true);
+    limiter.set_soft_clip(true);
+    An(limiter)
+}
+
 /// Pinking filter.
 /// - Input 0: input signal
 /// - Output 0: filtered signal
@@ -1202,6 +2163,30 @@ pub fn pinkpass() -> An<Pinkpass<f64, f64>> {
     An(Pinkpass::new())
 }
 
+/// Whitening filter (3 dB/octave highpass), the counterpart to `pinkpass`.
+/// Gain is calibrated so that `pinkpass() >> whitepass()` has unity gain.
+/// Setting: slope in dB/octave.
+/// - Input 0: input signal
+/// - Output 0: filtered signal
+///
+/// ### Example (White Noise From Pink Noise)
+/// ```
+/// use fundsp::hacker::*;
+/// let db = (pinkpass() >> whitepass()).response_db(0, 1000.0).unwrap();
+/// assert!(db.abs() < 1.0e-3);
+/// ```
+pub fn whitepass() -> An<Tilt<f64, f64>> {
+    An(Tilt::whitening())
+}
+
+/// Spectral tilt filter with `slope` in dB/octave (-6...6) and unity gain at 1 kHz.
+/// Setting: slope in dB/octave.
+/// - Input 0: input signal
+/// - Output 0: filtered signal
+pub fn tilt(slope: f64) -> An<Tilt<f64, f64>> {
+    An(Tilt::new(slope))
+}
+
 /// Pink noise.
 /// - Output 0: pink noise
 pub fn pink() -> An<Pipe<f64, Noise<f64>, Pinkpass<f64, f64>>> {
@@ -1259,6 +2244,35 @@ where
     An(Feedback2::new(x.0, y.0, FrameHadamard::new()))
 }
 
+/// Feedback delay network with explicit delay times, feedback matrix and damping.
+/// There are `N` delay lines with `delays` in seconds, each followed by damping filter `damping(i)`.
+/// Line outputs are mixed back to line inputs through `matrix`, for example,
+/// `FrameMatrix::hadamard()`, `FrameMatrix::householder()` or `FrameMatrix::orthogonal(seed)`.
+/// The feedback loop adds one sample to each delay.
+/// - Inputs: input signal to each delay line.
+/// - Outputs: output of each delay line.
+///
+/// ### Example: Mono Reverb
+/// ```
+/// use fundsp::hacker::*;
+/// split() >> fdn_matrix::<U4, _, _>(&[0.0297, 0.0371, 0.0411, 0.0437], FrameMatrix::householder(), |_| lowpole_hz(5000.0) * 0.9) >> join();
+/// ```
+pub fn fdn_matrix<N, X, F>(
+    delays: &[f64],
+    matrix: FrameMatrix<N, f64>,
+    damping: F,
+) -> An<Feedback<N, f64, MultiStack<N, f64, Pipe<f64, Delay<f64>, X>>, FrameMatrix<N, f64>>>
+where
+    N: Size<f64> + Size<Pipe<f64, Delay<f64>, X>>,
+    X: AudioNode<Sample = f64, Inputs = U1, Outputs = U1>,
+    U1: Mul<N, Output = N>,
+    F: Fn(i64) -> An<X>,
+{
+    assert!(delays.len() == N::USIZE);
+    let lines = stack::<N, _, _>(|i| delay(delays[i as usize]) >> damping(i));
+    An(Feedback::new(lines.0, matrix))
+}
+
 /// Bus `N` similar nodes from indexed generator `f`.
 /// - Input(s): from `f`.
 /// - Output(s): from `f`.
@@ -1337,6 +2351,134 @@ where
     super::prelude::stackf(f)
 }
 
+/// Process `N` channels with similar nodes from indexed generator `f`.
+/// Each node gets its own channel in its first input,
+/// while the rest of its inputs (parameters) are shared between all channels.
+/// Nodes from `f` must have exactly one output.
+/// - Input(s): `N` channels of signal, followed by the parameter inputs of `f`.
+/// - Output(s): `N` channels of processed signal.
+///
+/// ### Example: Stereo Moog Filter With Shared Cutoff And Q
+/// ```
+/// use fundsp::hacker::*;
+/// (multipass::<U2>() | dc((1000.0, 0.5))) >> per_channel::<U2, _, _>(|_| moog());
+/// ```
+pub fn per_channel<N, X, F>(f: F) -> An<PerChannel<N, f64, X>>
+where
+    N: Size<f64>,
+    N: Size<X>,
+    X: AudioNode<Sample = f64, Outputs = U1>,
+    X::Inputs: Size<f64> + Sub<U1>,
+    N: Add<<X::Inputs as Sub<U1>>::Output>,
+    <N as Add<<X::Inputs as Sub<U1>>::Output>>::Output: Size<f64>,
+    F: Fn(i64) -> An<X>,
+{
+    super::prelude::per_channel(f)
+}
+
+/// Mix the output of `x` (wet signal) with its input (dry signal).
+/// `wet` is the wet amount in 0...1. The dry signal is delayed by the latency of `x`,
+/// rounded to the nearest sample, which keeps parallel processing free of comb filtering.
+/// Setting: wet amount.
+/// - Input(s): input signal.
+/// - Output(s): mixed signal.
+///
+/// ### Example: Parallel Limiting
+/// ```
+/// use fundsp::hacker::*;
+/// mix(limiter((0.005, 0.1)), 0.5);
+/// ```
+pub fn mix<N, X>(x: An<X>, wet: f64) -> An<Mix<N, f64, X>>
+where
+    N: Size<f64>,
+    X: AudioNode<Sample = f64, Inputs = N, Outputs = N>,
+{
+    super::prelude::mix(x, wet)
+}
+
+/// Declare that all outputs of `x` produce signals of `kind`.
+/// Port kinds let graph editors flag mismatched connections; processing is unaffected.
+/// Setting: setting of `x`.
+/// - Input(s): inputs of `x`.
+/// - Output(s): outputs of `x`.
+///
+/// ### Example: Trigger Source
+/// ```
+/// use fundsp::hacker::*;
+/// port_kind(PortKind::Trigger, impulse_train(4.0)) >> burst(0.005, 0.7);
+/// ```
+pub fn port_kind<X: AudioNode<Sample = f64>>(kind: PortKind, x: An<X>) -> An<PortKindOf<X>> {
+    super::prelude::port_kind(kind, x)
+}
+
+/// Declare the channel layout of `x`. The layout applies to the inputs and outputs of `x`
+/// that have as many channels as the layout. Networks check layouts when piping units together.
+/// Processing is unaffected.
+/// Setting: setting of `x`.
+/// - Input(s): inputs of `x`.
+/// - Output(s): outputs of `x`.
+///
+/// ### Example: Stereo Source
+/// ```
+/// use fundsp::hacker::*;
+/// channel_layout(ChannelLayout::Stereo, noise() >> pan(0.0));
+/// ```
+pub fn channel_layout<X: AudioNode<Sample = f64>>(
+    layout: ChannelLayout,
+    x: An<X>,
+) -> An<LayoutOf<X>> {
+    super::prelude::channel_layout(layout, x)
+}
+
+/// Tag `x` with `tag` so it can be found inside a composed graph with `AudioNode::visit`
+/// or `An::visit_tagged`. Tags need not be unique: all nodes with a matching tag are visited.
+/// Processing is unaffected.
+/// Setting: setting of `x`.
+/// - Input(s): inputs of `x`.
+/// - Output(s): outputs of `x`.
+///
+/// ### Example: Change The Envelope Of A Voice
+/// ```
+/// use fundsp::hacker::*;
+/// let mut voice = tagged(1, adsr_exp(0.01, 0.1, 0.5, 0.2)) * sine_hz(440.0);
+/// voice.visit_tagged(1, |x: &mut AdsrExp<f64>| x.set((0.02, 0.2, 0.6, 0.3)));
+/// ```
+pub fn tagged<X: AudioNode<Sample = f64> + 'static>(tag: u64, x: An<X>) -> An<Tagged<X>> {
+    super::prelude::tagged(tag, x)
+}
+
+/// Voice pool of `n` (`n` > 0) independent clones of `prototype` with summed outputs.
+/// Each voice gets its own group of inputs and its own pseudorandom phase.
+/// - Input(s): inputs of `prototype` for voice 0, followed by inputs for voice 1, etc.
+/// - Output(s): sum of voice outputs.
+///
+/// ### Example: Polyphonic Synth
+/// ```
+/// use fundsp::hacker::*;
+/// // The prototype has pitch and gate inputs.
+/// let prototype = (pass() | adsr_live(0.01, 0.1, 0.5, 0.2)) >> saw() * pass() >> lowpole_hz(2000.0);
+/// let synth = voices(4, Box::new(prototype));
+/// assert_eq!(synth.inputs(), 8);
+/// ```
+pub fn voices(n: usize, prototype: Box<dyn AudioUnit64>) -> Voices64 {
+    Voices64::new(n, prototype)
+}
+
+/// Measure the throughput of `unit` in samples per second, both with `tick` and `process`.
+/// Each path is measured for at least 0.1 seconds. The unit is reset afterwards.
+/// Use this to compare patches across machines and versions of the library.
+///
+/// ### Example
+/// ```
+/// use fundsp::hacker::*;
+/// let mut patch = saw_hz(110.0) >> moog_hz(1000.0, 0.5);
+/// let throughput = throughput(&mut patch);
+/// println!("{:.0} x real time", throughput.process_realtime(44100.0));
+/// ```
+pub fn throughput(unit: &mut dyn AudioUnit64) -> Throughput {
+    throughput64(unit)
+}
+
 /// Branch into `N` similar nodes from indexed generator `f`.
 /// - Input(s): from `f`.
 /// - Output(s): `N` times `f`.
@@ -1482,6 +2624,9 @@ where
 /// Stereo reverb.
 /// `room_size` is in meters. An average room size is 10 meters.
 /// `time` is approximate reverberation time to -60 dB in seconds.
+/// The inputs are decorrelated with allpass filters and the delay lines are modulated
+/// slowly to reduce ringing.
+/// Setting: (modulation depth in seconds, modulation rate in Hz).
 /// - Input 0: left signal
 /// - Input 1: right signal
 /// - Output 0: reverberated left signal
@@ -1492,13 +2637,30 @@ where
 /// use fundsp::hacker::*;
 /// multipass() & 0.2 * reverb_stereo(10.0, 5.0);
 /// ```
-pub fn reverb_stereo(
-    room_size: f64,
-    time: f64,
-) -> An<impl AudioNode<Sample = f64, Inputs = U2, Outputs = U2>> {
+pub fn reverb_stereo(room_size: f64, time: f64) -> An<Reverb<f64>> {
     super::prelude::reverb_stereo::<f64>(room_size, time)
 }
 
+/// Stereo reverb with selectable quality tier.
+/// `room_size` is in meters. An average room size is 10 meters.
+/// `time` is approximate reverberation time to -60 dB in seconds.
+/// `quality` selects the number of delay lines (8, 16 or 32) and the modulation update rate.
+/// Reverbs of different quality can be exchanged at runtime in a `Slot`.
+/// Setting: (modulation depth in seconds, modulation rate in Hz).
+/// - Input 0: left signal
+/// - Input 1: right signal
+/// - Output 0: reverberated left signal
+/// - Output 1: reverberated right signal
+///
+/// ### Example: Low CPU Reverb
+/// ```
+/// use fundsp::hacker::*;
+/// multipass() & 0.2 * reverb_stereo_quality(10.0, 2.0, ReverbQuality::Low);
+/// ```
+pub fn reverb_stereo_quality(room_size: f64, time: f64, quality: ReverbQuality) -> An<Reverb<f64>> {
+    super::prelude::reverb_stereo_quality::<f64>(room_size, time, quality)
+}
+
 /// Saw-like discrete summation formula oscillator.
 /// - Input 0: frequency in Hz
 /// - Input 1: roughness in 0...1 is the attenuation of successive partials.
@@ -1531,9 +2693,40 @@ pub fn dsf_square_r(roughness: f64) -> An<Dsf<f64, U1>> {
     An(Dsf::new(DEFAULT_SR, 2.0, roughness))
 }
 
+/// Phase distortion oscillator (Casio CZ style) with distortion `curve`
+/// and distortion `amount` in 0...1. Amount 0 produces a cosine wave.
+/// Setting: amount.
+/// - Input 0: frequency in Hz
+/// - Output 0: phase distorted wave
+///
+/// ### Example: CZ Style Saw
+/// ```
+/// use fundsp::hacker::*;
+/// dc(110.0) >> phase_distortion(PdCurve::Saw, 0.5);
+/// ```
+pub fn phase_distortion(curve: PdCurve, amount: f64) -> An<PhaseDistortion<f64, U1>> {
+    An(PhaseDistortion::new(DEFAULT_SR, curve, amount))
+}
+
+/// Phase distortion oscillator (Casio CZ style) with distortion `curve`
+/// and distortion amount input.
+/// - Input 0: frequency in Hz
+/// - Input 1: distortion amount in 0...1
+/// - Output 0: phase distorted wave
+///
+/// ### Example: Resonance Sweep
+/// ```
+/// use fundsp::hacker::*;
+/// (dc(110.0) | lfo(|t| sin_hz(0.2, t) * 0.5 + 0.5)) >> phase_distortion_mod(PdCurve::Resonance);
+/// ```
+pub fn phase_distortion_mod(curve: PdCurve) -> An<PhaseDistortion<f64, U2>> {
+    An(PhaseDistortion::new(DEFAULT_SR, curve, 0.0))
+}
+
 /// Karplus-Strong plucked string oscillator with `frequency` in Hz.
 /// High frequency damping is in 0...1.
 /// Allocates: pluck buffer.
+/// Setting: (gain per second, high frequency damping).
 /// - Input 0: string excitation
 /// - Output 0: oscillator output
 ///
@@ -1642,6 +2835,22 @@ pub fn hammond_hz(f: f64) -> An<Pipe<f64, Constant<U1, f64>, WaveSynth<'static,
     constant(f) >> hammond()
 }
 
+/// Wavetable oscillator that scans across wavetable `frames` (at least one).
+/// Adjacent frames are interpolated linearly.
+/// - Input 0: frequency in Hz
+/// - Input 1: table position in 0...1, where 0 is the first frame and 1 is the last frame
+/// - Output 0: audio
+///
+/// ### Example: Modulated Table Position
+/// ```
+/// use fundsp::hacker::*;
+/// let frames = [&*TRIANGLE_TABLE, &*SOFT_SAW_TABLE, &*ORGAN_TABLE];
+/// (dc(110.0) | lfo(|t| sin_hz(0.5, t) * 0.5 + 0.5)) >> wave_scan(&frames);
+/// ```
+pub fn wave_scan(frames: &[&'static Wavetable]) -> An<WaveScan<'static, f64>> {
+    An(WaveScan::new(DEFAULT_SR, frames))
+}
+
 /// Lowpass filter.
 /// - Input 0: audio
 /// - Input 1: cutoff frequency (Hz)
@@ -1955,6 +3164,15 @@ pub fn pulse() -> An<super::prelude::PulseWave<f64>> {
     super::prelude::pulse()
 }
 
+/// Oscillator bank that resynthesizes a sinusoidal model obtained from `Wave64::analyze_partials`.
+/// Sounds can be stretched in time and transposed independently.
+/// - Input 0: playback speed (1 = original)
+/// - Input 1: frequency ratio (1 = original)
+/// - Output 0: resynthesized audio
+pub fn resynth_partials(partials: &Arc<Partials>) -> An<ResynthPartials<f64>> {
+    An(ResynthPartials::new(partials))
+}
+
 /// Play back a channel of a Wave64.
 /// Optional loop point is the index to jump to at the end of the wave.
 /// - Output 0: wave
@@ -1999,6 +3217,53 @@ pub fn wave64_at(
     ))
 }
 
+/// Play back a channel of a Wave64 starting from sample `start_point`, inclusive,
+/// and ending at sample `end_point`, exclusive, looping from `loop_point` in `LoopMode` `mode`.
+/// Setting: gate (`false` releases the sustain loop in `LoopMode::Release`).
+/// - Output 0: wave
+///
+/// ### Example: Sustain Loop With Release
+/// ```
+/// use fundsp::hacker::*;
+/// let wave = std::sync::Arc::new(Wave64::render(44100.0, 1.0, &mut (white())));
+/// let mut player = wave64_loop(&wave, 0, 0, 44100, 11025, LoopMode::Release(22050));
+/// player.set(false);
+/// ```
+pub fn wave64_loop(
+    wave: &Arc<Wave64>,
+    channel: usize,
+    start_point: usize,
+    end_point: usize,
+    loop_point: usize,
+    mode: LoopMode,
+) -> An<Wave64Player<f64>> {
+    let mut player = Wave64Player::new(wave, channel, start_point, end_point, Some(loop_point));
+    player.set_loop_mode(mode);
+    An(player)
+}
+
+/// Play back a channel of a Wave64, restarting on each rising edge of the trigger input.
+/// Playback starts at a random offset of up to `jitter` seconds into the wave
+/// (0 = always from the start). A retriggered sound is faded out over `declick` seconds
+/// (for example, 0.002) and a randomly offset start is faded in over the same time.
+/// - Input 0: trigger (rising edge through zero restarts playback)
+/// - Output 0: wave
+///
+/// ### Example: Drum Pattern At 120 BPM
+/// ```
+/// use fundsp::hacker::*;
+/// let wave = std::sync::Arc::new(Wave64::render(44100.0, 0.1, &mut (white() * 0.5)));
+/// lfo(|t| if t % 0.5 < 0.25 { 1.0 } else { 0.0 }) >> wave64_trigger(&wave, 0, 0.0, 0.002);
+/// ```
+pub fn wave64_trigger(
+    wave: &Arc<Wave64>,
+    channel: usize,
+    jitter: f64,
+    declick: f64,
+) -> An<Wave64Trigger<f64>> {
+    An(Wave64Trigger::new(wave, channel, jitter, declick))
+}
+
 /// Play back a channel of a Wave32.
 /// Optional loop point is the index to jump to at the end of the wave.
 /// - Output 0: wave
@@ -2043,11 +3308,60 @@ pub fn wave32_at(
     ))
 }
 
+/// Play back a channel of a Wave32 starting from sample `start_point`, inclusive,
+/// and ending at sample `end_point`, exclusive, looping from `loop_point` in `LoopMode` `mode`.
+/// Setting: gate (`false` releases the sustain loop in `LoopMode::Release`).
+/// - Output 0: wave
+///
+/// ### Example: Sustain Loop With Release
+/// ```
+/// use fundsp::hacker::*;
+/// let wave = std::sync::Arc::new(Wave32::render(44100.0, 1.0, &mut (fundsp::hacker32::white())));
+/// let mut player = wave32_loop(&wave, 0, 0, 44100, 11025, LoopMode::Release(22050));
+/// player.set(false);
+/// ```
+pub fn wave32_loop(
+    wave: &Arc<Wave32>,
+    channel: usize,
+    start_point: usize,
+    end_point: usize,
+    loop_point: usize,
+    mode: LoopMode,
+) -> An<Wave32Player<f64>> {
+    let mut player = Wave32Player::new(wave, channel, start_point, end_point, Some(loop_point));
+    player.set_loop_mode(mode);
+    An(player)
+}
+
+/// Play back a channel of a Wave32, restarting on each rising edge of the trigger input.
+/// Playback starts at a random offset of up to `jitter` seconds into the wave
+/// (0 = always from the start). A retriggered sound is faded out over `declick` seconds
+/// (for example, 0.002) and a randomly offset start is faded in over the same time.
+/// - Input 0: trigger (rising edge through zero restarts playback)
+/// - Output 0: wave
+///
+/// ### Example: Drum Pattern At 120 BPM
+/// ```
+/// use fundsp::hacker::*;
+/// let wave = std::sync::Arc::new(Wave32::render(44100.0, 0.1, &mut (fundsp::hacker32::white() * 0.5)));
+/// lfo(|t| if t % 0.5 < 0.25 { 1.0 } else { 0.0 }) >> wave32_trigger(&wave, 0, 0.0, 0.002);
+/// ```
+pub fn wave32_trigger(
+    wave: &Arc<Wave32>,
+    channel: usize,
+    jitter: f64,
+    declick: f64,
+) -> An<Wave32Trigger<f64>> {
+    An(Wave32Trigger::new(wave, channel, jitter, declick))
+}
+
 /// Mono chorus, 5 voices. For stereo, stack two of these using different seed values.
 /// `seed`: LFO seed.
 /// `separation`: base voice separation in seconds (for example, 0.015).
 /// `variation`: delay variation in seconds (for example, 0.005).
 /// `mod_frequency`: delay modulation frequency (for example, 0.2).
+/// Allocates: the delay line.
+/// Setting: (separation, variation, mod_frequency).
 /// - Input 0: audio.
 /// - Output 0: chorused audio, including original signal.
 ///
@@ -2056,15 +3370,172 @@ pub fn wave32_at(
 /// use fundsp::hacker::*;
 /// saw_hz(110.0) >> chorus(0, 0.015, 0.005, 0.5);
 /// ```
-pub fn chorus(
-    seed: i64,
-    separation: f64,
-    variation: f64,
-    mod_frequency: f64,
-) -> An<impl AudioNode<Sample = f64, Inputs = U1, Outputs = U1>> {
+pub fn chorus(seed: i64, separation: f64, variation: f64, mod_frequency: f64) -> An<Chorus<f64>> {
     super::prelude::chorus::<f64>(seed, separation, variation, mod_frequency)
 }
 
+/// Mono string ensemble with `voices` modulated delay voices (for example, 3).
+/// `depth`: delay modulation depth in seconds (for example, 0.003).
+/// `rate`: slow modulation frequency in Hz (for example, 0.6).
+/// Unlike `chorus`, voice modulation phases are spread evenly and the delayed signal is lowpass filtered.
+/// Allocates: the delay line.
+/// Setting: (depth, rate).
+/// - Input 0: audio.
+/// - Output 0: ensemble audio, including original signal.
+///
+/// ### Example: String Machine Saw
+/// ```
+/// use fundsp::hacker::*;
+/// saw_hz(220.0) >> ensemble(3, 0.003, 0.6);
+/// ```
+pub fn ensemble(voices: usize, depth: f64, rate: f64) -> An<Ensemble<f64>> {
+    An(Ensemble::new(voices, depth, rate))
+}
+
+/// Interactive three band tone stack with `bass`, `middle` and `treble` controls in 0...1.
+/// Modeled after the passive tone stack of a classic tube amplifier.
+/// Setting: (bass, middle, treble).
+/// - Input 0: signal.
+/// - Output 0: filtered signal.
+pub fn tone_stack(bass: f64, middle: f64, treble: f64) -> An<ToneStack<f64>> {
+    An(ToneStack::new(bass, middle, treble))
+}
+
+/// Guitar amplifier and cabinet simulation from `model`.
+/// The signal chain is an input highpass filter, oversampled multi-stage preamp
+/// with dynamic bias shift, tone stack, presence shelf and cabinet convolution.
+/// Allocates: cabinet impulse response and preamp stages.
+/// - Input 0: guitar signal.
+/// - Output 0: amplified signal.
+///
+/// ### Example: Crunchy Guitar Amp
+/// ```
+/// use fundsp::hacker::*;
+/// let cabinet = [0.5, 0.3, 0.1, -0.05];
+/// saw_hz(110.0) >> amp_sim(&AmpModel::crunch().with_cabinet(&cabinet));
+/// ```
+pub fn amp_sim(model: &AmpModel) -> An<impl AudioNode<Sample = f64, Inputs = U1, Outputs = U1>> {
+    super::prelude::amp_sim::<f64>(model)
+}
+
+/// Exciter: generates harmonics from the signal above `frequency` Hz
+/// and mixes them back in, scaled by `amount` (for example, 0.5).
+/// Saturation produces odd harmonics and rectification even harmonics.
+/// - Input 0: audio.
+/// - Output 0: excited audio, including original signal.
+///
+/// ### Example: Brighten A Pad
+/// ```
+/// use fundsp::hacker::*;
+/// saw_hz(110.0) >> lowpass_hz(1000.0, 1.0) >> exciter(0.5, 3000.0);
+/// ```
+pub fn exciter(
+    amount: f64,
+    frequency: f64,
+) -> An<impl AudioNode<Sample = f64, Inputs = U1, Outputs = U1>> {
+    super::prelude::exciter::<f64>(amount, frequency)
+}
+
+/// Psychoacoustic bass enhancer. Synthesizes harmonics of fundamentals below 120 Hz
+/// so that they are perceived on small speakers, mixing them back in scaled by `amount` (for example, 0.5).
+/// - Input 0: audio.
+/// - Output 0: enhanced audio, including original signal.
+///
+/// ### Example: Enhance A Bass Line
+/// ```
+/// use fundsp::hacker::*;
+/// sine_hz(50.0) >> bass_enhance(0.5);
+/// ```
+pub fn bass_enhance(amount: f64) -> An<impl AudioNode<Sample = f64, Inputs = U1, Outputs = U1>> {
+    super::prelude::bass_enhance::<f64>(amount)
+}
+
+/// Sub-octave generator. Tracks the pitch of a monophonic input from its zero crossings
+/// and mixes in sine layers one and two octaves below with levels `mix1` and `mix2`.
+/// Call `set_square(true)` on the node for square wave layers.
+/// Setting: (`mix1`, `mix2`).
+/// - Input 0: audio.
+/// - Output 0: audio with sub-octave layers, including original signal.
+///
+/// ### Example: Octave Pedal On A Bass Note
+/// ```
+/// use fundsp::hacker::*;
+/// sine_hz(82.0) >> suboctave(0.7, 0.3);
+/// ```
+pub fn suboctave(mix1: f64, mix2: f64) -> An<SubOctave<f64>> {
+    An(SubOctave::new(mix1, mix2))
+}
+
+/// Spectral freeze. While frozen, holds the spectrum of the input and resynthesizes it
+/// continuously. Drift randomizes phases of the held spectrum. Latency is 2048 samples.
+/// - Input 0: audio
+/// - Input 1: freeze (hold when > 0)
+/// - Input 2: drift amount in 0...1
+/// - Output 0: frozen audio
+///
+/// ### Example: Freeze Pad
+/// ```
+/// use fundsp::hacker::*;
+/// (saw_hz(110.0) | dc((1.0, 0.2))) >> spectral_freeze();
+/// ```
+pub fn spectral_freeze() -> An<SpectralFreeze<f64>> {
+    An(SpectralFreeze::new())
+}
+
+/// Spectral delay with per-bin delay times. With positive `tilt`, delay grows linearly
+/// with frequency up to `tilt` seconds at the Nyquist frequency.
+/// With negative `tilt`, low frequencies are delayed up to -`tilt` seconds instead.
+/// Latency is 2048 samples.
+/// Allocates: spectral delay line.
+/// - Input 0: audio
+/// - Output 0: delayed audio
+///
+/// ### Example: Rising Noise Sweeps
+/// ```
+/// use fundsp::hacker::*;
+/// noise() * lfo(|t| if t % 1.0 < 0.05 { 1.0 } else { 0.0 }) >> spectral_delay(0.5);
+/// ```
+pub fn spectral_delay(tilt: f64) -> An<SpectralDelay<f64>> {
+    An(SpectralDelay::new(tilt))
+}
+
+/// Spectral noise reduction by up to `reduction_db` dB (for example, 20.0).
+/// The noise profile is learned from input while the learn input is positive.
+/// It can also be learned from quiet passages automatically with `Denoise::set_gate`.
+/// Latency is 2048 samples.
+/// - Input 0: audio
+/// - Input 1: learn noise profile (when > 0)
+/// - Output 0: denoised audio
+///
+/// ### Example: Learn Noise Profile During The First Second
+/// ```
+/// use fundsp::hacker::*;
+/// (noise() * 0.01 | lfo(|t| if t < 1.0 { 1.0 } else { 0.0 })) >> denoise(20.0);
+/// ```
+pub fn denoise(reduction_db: f64) -> An<Denoise<f64>> {
+    An(Denoise::new(reduction_db))
+}
+
+/// Paulstretch player. Plays back `channel` of `wave` stretched in time by factor `stretch` (`stretch` > 0),
+/// for example, 8.0. Windows of 0.25 seconds are resynthesized with randomized phases,
+/// which smears the sound into a texture. See also `Wave64::paulstretch` for offline stretching.
+/// Setting: stretch factor.
+/// - Output 0: stretched wave
+///
+/// ### Example: Stretch A Chord
+/// ```
+/// use fundsp::hacker::*;
+/// let wave = Wave64::render(44100.0, 1.0, &mut ((saw_hz(110.0) + saw_hz(165.0)) * 0.5));
+/// let player = paulstretch(&wave, 0, 20.0);
+/// ```
+pub fn paulstretch(wave: &Wave64, channel: usize, stretch: f64) -> An<PaulStretch<f64>> {
+    An(PaulStretch::new(
+        wave.channel(channel),
+        wave.sample_rate(),
+        stretch,
+    ))
+}
+
 /// Mono flanger.
 /// `feedback_amount`: amount of feedback (for example, 0.9 or -0.9). Negative feedback inverts feedback phase.
 /// `minimum_delay`: minimum delay in seconds (for example, 0.005).
@@ -2163,6 +3634,81 @@ pub fn timer(shared: &Shared<f64>) -> An<Timer<f64>> {
     An(Timer::new(DEFAULT_SR, shared))
 }
 
+/// Trace resets, sample rate changes and settings of `node` with `tracer`.
+/// Events are recorded without allocation and can be received from the `Trace` frontend.
+/// - Input(s): from `node`.
+/// - Output(s): from `node`.
+///
+/// ### Example: Tracing Resets
+/// ```
+/// use fundsp::hacker::*;
+/// let (mut events, tracer) = Trace::new(16);
+/// let mut node = trace(&tracer, sine());
+/// node.reset();
+/// assert!(events.get().unwrap().kind == TraceKind::Reset);
+/// ```
+pub fn trace<X: AudioNode<Sample = f64>>(tracer: &Tracer, node: An<X>) -> An<Traced<X>> {
+    An(Traced::new(tracer, node.0))
+}
+
+/// Fade in `node` from silence over `time` seconds with fade curve `ease`.
+/// The fade starts at the beginning and after every reset. Fades are sample accurate.
+/// More fades can be scheduled at runtime with `FadeSetting`.
+/// Setting: `FadeSetting`.
+/// - Input(s): from `node`.
+/// - Output(s): from `node`.
+///
+/// ### Example: Declicked Start
+/// ```
+/// use fundsp::hacker::*;
+/// fade_in(Fade::Power, 0.01, saw_hz(110.0));
+/// ```
+pub fn fade_in<X: AudioNode<Sample = f64>>(ease: Fade, time: f64, node: An<X>) -> An<Faded<X>> {
+    An(Faded::new(node.0, ease, time, None))
+}
+
+/// Fade out `node` to silence starting at `start` seconds and lasting `time` seconds,
+/// with fade curve `ease`. Time is measured from the latest reset. Fades are sample accurate.
+/// More fades can be scheduled at runtime with `FadeSetting`.
+/// Setting: `FadeSetting`.
+/// - Input(s): from `node`.
+/// - Output(s): from `node`.
+///
+/// ### Example: Stop After One Second
+/// ```
+/// use fundsp::hacker::*;
+/// fade_out_at(Fade::Linear, 1.0, 0.1, saw_hz(110.0));
+/// ```
+pub fn fade_out_at<X: AudioNode<Sample = f64>>(
+    ease: Fade,
+    start: f64,
+    time: f64,
+    node: An<X>,
+) -> An<Faded<X>> {
+    An(Faded::new(node.0, ease, 0.0, Some((start, time))))
+}
+
+/// Freeze generator `node`. Renders `seconds` of `node` once and then plays the rendering in a loop.
+/// Saves CPU for static pads and textures. The loop point is crossfaded.
+/// Rendering happens immediately when the node is created, when the sample rate changes,
+/// on `Freeze::refresh` and when a setting is applied.
+/// Allocates: the rendering.
+/// Setting: setting of `node`, followed by a new rendering.
+/// - Output(s): from `node`.
+///
+/// ### Example: Frozen Pad
+/// ```
+/// use fundsp::hacker::*;
+/// freeze((saw_hz(110.0) & saw_hz(110.5) & saw_hz(111.0)) >> lowpass_hz(1000.0, 1.0), 4.0);
+/// ```
+pub fn freeze<X>(node: An<X>, seconds: f64) -> An<Freeze<X>>
+where
+    X: AudioNode<Sample = f64, Inputs = U0>,
+    X::Outputs: Size<f64>,
+{
+    An(Freeze::new(node.0, seconds))
+}
+
 /// Snoop node for sharing audio data with a frontend thread.
 /// The latest samples buffer has room for at least `capacity` samples.
 /// Returns (frontend, backend).
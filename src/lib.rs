@@ -26,8 +26,20 @@ use std::ops::{BitAnd, BitOr, BitXor, Not, Shl, Shr};
 /// Default sample rate is 44.1 kHz.
 pub const DEFAULT_SR: f64 = 44_100.0;
 
-/// Maximum buffer size for block processing is 64 samples.
-pub const MAX_BUFFER_SIZE: usize = 64;
+/// Maximum buffer size for block processing. The default is 64 samples.
+/// Features `block_16`, `block_32`, `block_128` and `block_256` select other sizes.
+/// If several are enabled, then the largest is used.
+pub const MAX_BUFFER_SIZE: usize = if cfg!(feature = "block_256") {
+    256
+} else if cfg!(feature = "block_128") {
+    128
+} else if cfg!(feature = "block_32") {
+    32
+} else if cfg!(feature = "block_16") {
+    16
+} else {
+    64
+};
 
 /// Number abstraction.
 pub trait Num:
@@ -248,32 +260,47 @@ macro_rules! impl_real {
 impl_real! { f32, f64 }
 
 pub mod adsr;
+pub mod amp;
+pub mod analysis;
 pub mod audionode;
 pub mod audiounit;
 pub mod buffer;
 pub mod combinator;
+pub mod convert;
+pub mod convolve;
+pub mod correction;
 pub mod delay;
+pub mod dither;
 pub mod dynamics;
 pub mod envelope;
+pub mod fade;
+pub mod feature;
 pub mod feedback;
 pub mod filter;
 pub mod fir;
 pub mod follow;
+pub mod freeze;
 pub mod gen;
+pub mod golden;
 pub mod granular;
 pub mod hacker;
 pub mod hacker32;
 pub mod math;
+pub mod modulation;
 pub mod moog;
 pub mod net;
 pub mod noise;
 pub mod oscillator;
 pub mod oversample;
 pub mod pan;
+pub mod partials;
 pub mod prelude;
+pub mod rack;
 pub mod realnet;
 pub mod realseq;
+pub mod record;
 pub mod resample;
+pub mod reverb;
 pub mod rez;
 pub mod sequencer;
 pub mod setting;
@@ -283,11 +310,17 @@ pub mod signal;
 pub mod slot;
 pub mod snoop;
 pub mod sound;
+pub mod spectral;
 pub mod svf;
 pub mod system;
+pub mod throughput;
+pub mod trace;
+pub mod tuning;
+pub mod voices;
 pub mod wave;
 pub mod wave_stream;
 pub mod wavetable;
+pub mod weighting;
 
 #[cfg(feature = "files")]
 pub mod read;
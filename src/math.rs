@@ -104,6 +104,136 @@ pub fn tanh<T: Real>(x: T) -> T {
     x.tanh()
 }
 
+/// Number of segments in the sine table of `sin_fast`.
+const SIN_TABLE_SIZE: usize = 1024;
+
+/// Sine of `x` (-pi <= `x` <= pi) from its Taylor series, for building tables at compile time.
+const fn sin_taylor(x: f64) -> f64 {
+    let mut term = x;
+    let mut sum = x;
+    let mut n = 1;
+    while n < 20 {
+        term = -term * x * x / ((2 * n) * (2 * n + 1)) as f64;
+        sum += term;
+        n += 1;
+    }
+    sum
+}
+
+/// Build the sine table of `sin_fast` with a guard point at the end.
+const fn sin_table() -> [f64; SIN_TABLE_SIZE + 1] {
+    let mut table = [0.0; SIN_TABLE_SIZE + 1];
+    let mut i = 0;
+    while i <= SIN_TABLE_SIZE {
+        let x = i as f64 * TAU / SIN_TABLE_SIZE as f64;
+        // Keep the argument in -pi...pi, where the series converges quickly.
+        table[i] = if i <= SIN_TABLE_SIZE / 2 {
+            sin_taylor(x)
+        } else {
+            sin_taylor(x - TAU)
+        };
+        i += 1;
+    }
+    table
+}
+
+/// Sine table of `sin_fast`, computed at compile time.
+static SIN_TABLE: [f64; SIN_TABLE_SIZE + 1] = sin_table();
+
+/// Fast sine approximation from a table with linear interpolation.
+/// Maximum absolute error is 5.0e-6.
+#[inline]
+pub fn sin_fast<T: Real>(x: T) -> T {
+    let t = x * T::from_f64(SIN_TABLE_SIZE as f64 / TAU);
+    let i = floor(t);
+    let w = t - i;
+    let i = (i.to_i64() & (SIN_TABLE_SIZE as i64 - 1)) as usize;
+    let y0 = T::from_f64(SIN_TABLE[i]);
+    let y1 = T::from_f64(SIN_TABLE[i + 1]);
+    y0 + w * (y1 - y0)
+}
+
+/// Fast cosine approximation from a table with linear interpolation.
+/// Maximum absolute error is 5.0e-6.
+#[inline]
+pub fn cos_fast<T: Real>(x: T) -> T {
+    sin_fast(x + T::from_f64(PI * 0.5))
+}
+
+/// Fast power of 2 approximation with a polynomial.
+/// Maximum relative error is 1.0e-8. The argument is clamped to -1022...1023.
+#[inline]
+pub fn exp2_fast<T: Real>(x: T) -> T {
+    let x = clamp(T::new(-1022), T::new(1023), x);
+    let i = round(x);
+    // Taylor series of 2^f in -0.5...0.5.
+    let f = (x - i) * T::from_f64(LN_2);
+    let p = T::from_f64(1.0 / 5040.0);
+    let p = p * f + T::from_f64(1.0 / 720.0);
+    let p = p * f + T::from_f64(1.0 / 120.0);
+    let p = p * f + T::from_f64(1.0 / 24.0);
+    let p = p * f + T::from_f64(1.0 / 6.0);
+    let p = p * f + T::from_f64(0.5);
+    let p = p * f + T::one();
+    let p = p * f + T::one();
+    p * T::from_f64(f64::from_bits(((i.to_i64() + 1023) as u64) << 52))
+}
+
+/// Fast exponential function approximation with a polynomial.
+/// Maximum relative error is 1.0e-8.
+#[inline]
+pub fn exp_fast<T: Real>(x: T) -> T {
+    exp2_fast(x * T::from_f64(std::f64::consts::LOG2_E))
+}
+
+/// Fast hyperbolic tangent approximation with a polynomial.
+/// Maximum absolute error is 1.0e-8.
+#[inline]
+pub fn tanh_fast<T: Real>(x: T) -> T {
+    let e = exp_fast(clamp(T::new(-20), T::new(20), x) * T::new(2));
+    (e - T::one()) / (e + T::one())
+}
+
+/// Sine used internally by oscillators.
+/// It is `sin_fast` if the `fast-math` feature is enabled.
+#[inline]
+pub(crate) fn sin_dsp<T: Real>(x: T) -> T {
+    #[cfg(feature = "fast-math")]
+    return sin_fast(x);
+    #[cfg(not(feature = "fast-math"))]
+    return sin(x);
+}
+
+/// Cosine used internally by oscillators.
+/// It is `cos_fast` if the `fast-math` feature is enabled.
+#[inline]
+pub(crate) fn cos_dsp<T: Real>(x: T) -> T {
+    #[cfg(feature = "fast-math")]
+    return cos_fast(x);
+    #[cfg(not(feature = "fast-math"))]
+    return cos(x);
+}
+
+/// Exponential function used internally by envelopes.
+/// It is `exp_fast` if the `fast-math` feature is enabled.
+#[inline]
+pub(crate) fn exp_dsp<T: Real>(x: T) -> T {
+    #[cfg(feature = "fast-math")]
+    return exp_fast(x);
+    #[cfg(not(feature = "fast-math"))]
+    return exp(x);
+}
+
+/// Hyperbolic tangent used internally by waveshapers.
+/// It is `tanh_fast` if the `fast-math` feature is enabled.
+#[inline]
+pub(crate) fn tanh_dsp<T: Real>(x: T) -> T {
+    #[cfg(feature = "fast-math")]
+    return tanh_fast(x);
+    #[cfg(not(feature = "fast-math"))]
+    return tanh(x);
+}
+
 /// sqrt(2)
 pub const SQRT_2: f64 = std::f64::consts::SQRT_2;
 /// pi
@@ -357,6 +487,19 @@ pub fn spline<T: Num>(y0: T, y1: T, y2: T, y3: T, x: T) -> T {
                 + x * (T::new(3) * (y1 - y2) + y3 - y0)))
 }
 
+/// 4-point, 3rd order Lagrange interpolation.
+/// Interpolates between `y1` (returns `y1` when `x` = 0) and `y2` (returns `y2` when `x` = 1)
+/// with a cubic polynomial that passes through all four points.
+/// It is smoother than `spline` when modulated but has less accurate phase.
+#[inline]
+pub fn lagrange<T: Num>(y0: T, y1: T, y2: T, y3: T, x: T) -> T {
+    let xm1 = x - T::one();
+    let xm2 = x - T::new(2);
+    let xp1 = x + T::one();
+    (xp1 * x * xm1 * y3 - x * xm1 * xm2 * y0) / T::new(6)
+        + (xp1 * xm1 * xm2 * y1 - xp1 * x * xm2 * y2) / T::new(2)
+}
+
 /// Monotonic cubic interpolation via Steffen's method. The result never overshoots.
 /// It is first order continuous. Interpolates between `y1` (at `x` = 0) and `y2` (at `x` = 1)
 /// while using the previous (`y0`) and next (`y3`) values to influence slopes.
@@ -557,6 +700,19 @@ pub fn midi_hz<T: Real>(x: T) -> T {
     T::new(440) * exp2((x - T::new(69)) / T::new(12))
 }
 
+/// Convert frequency `f` in Hz (`f` > 0) to a fractional MIDI note number.
+/// This is the inverse of `midi_hz`.
+///
+/// ### Example
+/// ```
+/// use fundsp::hacker::*;
+/// assert!(hz_midi(440.0) == 69.0);
+/// ```
+#[inline]
+pub fn hz_midi<T: Real>(f: T) -> T {
+    T::new(69) + T::new(12) * log2(f / T::new(440))
+}
+
 /// Convert BPM (beats per minute) to Hz.
 #[inline]
 pub fn bpm_hz<T: Real>(bpm: T) -> T {
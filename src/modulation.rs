@@ -0,0 +1,270 @@
+//! Modulation effects: tremolo and auto-panner.
+
+use super::audionode::*;
+use super::math::*;
+use super::pan::*;
+use super::signal::*;
+use super::*;
+use numeric_array::typenum::*;
+use std::marker::PhantomData;
+
+/// LFO waveforms of modulation effects.
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub enum LfoShape {
+    /// Sine wave.
+    #[default]
+    Sine,
+    /// Triangle wave.
+    Triangle,
+    /// Square wave.
+    Square,
+    /// Rising sawtooth wave.
+    Saw,
+}
+
+impl LfoShape {
+    /// Value of the waveform in -1...1 at `phase` in 0...1.
+    /// Phase 0 is at the waveform maximum, except for the sawtooth,
+    /// which rises from -1 to 1 during the cycle.
+    #[inline]
+    pub fn at<T: Real>(&self, phase: T) -> T {
+        match self {
+            LfoShape::Sine => cos_dsp(phase * T::from_f64(TAU)),
+            LfoShape::Triangle => T::one() - T::new(4) * abs(phase - round(phase)),
+            LfoShape::Square => {
+                if phase < T::from_f64(0.25) || phase >= T::from_f64(0.75) {
+                    T::one()
+                } else {
+                    -T::one()
+                }
+            }
+            LfoShape::Saw => T::new(2) * phase - T::one(),
+        }
+    }
+}
+
+/// LFO phase accumulator shared by the modulation effects.
+/// Rate is in Hz, or a note division of a whole note if the LFO is tempo synchronized.
+#[derive(Clone)]
+struct ModulationLfo {
+    rate: f64,
+    phase: f64,
+    sample_rate: f64,
+}
+
+impl ModulationLfo {
+    fn new(rate: f64) -> Self {
+        Self {
+            rate,
+            phase: 0.0,
+            sample_rate: DEFAULT_SR,
+        }
+    }
+
+    /// Advance phase. If `tempo` (BPM) is given, then rate is a note division.
+    #[inline]
+    fn advance(&mut self, tempo: Option<f64>) {
+        let frequency = match tempo {
+            Some(tempo) => max(0.0, tempo) / (240.0 * self.rate),
+            None => self.rate,
+        };
+        self.phase += frequency / self.sample_rate;
+        self.phase -= floor(self.phase);
+    }
+}
+
+/// Tremolo effect. Periodically modulates the amplitude of `N` channels.
+/// If `M` is `U1`, then the LFO is synchronized to a tempo input
+/// and rate is a note division of a whole note.
+/// Setting: (rate, depth).
+/// - Inputs 0 to N - 1: input signal
+/// - Input N (optional): tempo (BPM)
+/// - Outputs 0 to N - 1: modulated signal
+#[derive(Clone)]
+pub struct Tremolo<T: Real, N: Size<T>, M: Size<T>> {
+    _marker: PhantomData<(N, M)>,
+    lfo: ModulationLfo,
+    depth: T,
+    shape: LfoShape,
+    offset: T,
+}
+
+impl<T: Real, N: Size<T>, M: Size<T>> Tremolo<T, N, M> {
+    /// Create new tremolo. `rate` is in Hz, or a note division if `M` is `U1`.
+    /// `depth` is in 0...1, where 1 modulates amplitude all the way to zero.
+    /// The LFO phase of channel `i` is offset by `i * offset` cycles.
+    pub fn new(rate: f64, depth: T, shape: LfoShape, offset: T) -> Self {
+        assert!(M::USIZE <= 1);
+        assert!(rate > 0.0);
+        Self {
+            _marker: PhantomData,
+            lfo: ModulationLfo::new(rate),
+            depth,
+            shape,
+            offset,
+        }
+    }
+
+    /// Set LFO `rate` and modulation `depth`.
+    pub fn set_modulation(&mut self, rate: f64, depth: T) {
+        assert!(rate > 0.0);
+        self.lfo.rate = rate;
+        self.depth = depth;
+    }
+
+    /// Set LFO waveform.
+    pub fn set_shape(&mut self, shape: LfoShape) {
+        self.shape = shape;
+    }
+
+    /// Set LFO phase offset between consecutive channels in cycles.
+    pub fn set_offset(&mut self, offset: T) {
+        self.offset = offset;
+    }
+}
+
+impl<T, N, M> AudioNode for Tremolo<T, N, M>
+where
+    T: Real,
+    N: Size<T> + Add<M>,
+    M: Size<T>,
+    <N as Add<M>>::Output: Size<T>,
+{
+    const ID: u64 = 105;
+    type Sample = T;
+    type Inputs = Sum<N, M>;
+    type Outputs = N;
+    type Setting = (f64, T);
+
+    fn set(&mut self, setting: Self::Setting) {
+        self.set_modulation(setting.0, setting.1);
+    }
+
+    fn reset(&mut self) {
+        self.lfo.phase = 0.0;
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.lfo.sample_rate = sample_rate;
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let phase = T::from_f64(self.lfo.phase);
+        let half = T::from_f64(0.5);
+        let output = Frame::generate(|i| {
+            let x = self.shape.at(fract(phase + self.offset * T::new(i as i64)));
+            input[i] * (T::one() - self.depth * half * (T::one() - x))
+        });
+        let tempo = if M::USIZE > 0 {
+            Some(input[N::USIZE].to_f64())
+        } else {
+            None
+        };
+        self.lfo.advance(tempo);
+        output
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        for i in 0..N::USIZE {
+            output[i] = input[i].distort(0.0);
+        }
+        output
+    }
+}
+
+/// Auto-panner. Periodically pans a mono signal between the left and right channels
+/// with equal power panning. If `M` is `U1`, then the LFO is synchronized
+/// to a tempo input and rate is a note division of a whole note.
+/// Setting: (rate, depth).
+/// - Input 0: mono signal
+/// - Input 1 (optional): tempo (BPM)
+/// - Output 0: left output
+/// - Output 1: right output
+#[derive(Clone)]
+pub struct Autopan<T: Real, M: Size<T>> {
+    _marker: PhantomData<M>,
+    lfo: ModulationLfo,
+    depth: T,
+    shape: LfoShape,
+}
+
+impl<T: Real, M: Size<T>> Autopan<T, M> {
+    /// Create new auto-panner. `rate` is in Hz, or a note division if `M` is `U1`.
+    /// `depth` is in 0...1, where 1 pans the signal all the way to each side.
+    pub fn new(rate: f64, depth: T, shape: LfoShape) -> Self {
+        assert!(M::USIZE <= 1);
+        assert!(rate > 0.0);
+        Self {
+            _marker: PhantomData,
+            lfo: ModulationLfo::new(rate),
+            depth,
+            shape,
+        }
+    }
+
+    /// Set LFO `rate` and modulation `depth`.
+    pub fn set_modulation(&mut self, rate: f64, depth: T) {
+        assert!(rate > 0.0);
+        self.lfo.rate = rate;
+        self.depth = depth;
+    }
+
+    /// Set LFO waveform.
+    pub fn set_shape(&mut self, shape: LfoShape) {
+        self.shape = shape;
+    }
+}
+
+impl<T, M> AudioNode for Autopan<T, M>
+where
+    T: Real,
+    M: Size<T>,
+    U1: Add<M>,
+    <U1 as Add<M>>::Output: Size<T>,
+{
+    const ID: u64 = 106;
+    type Sample = T;
+    type Inputs = Sum<U1, M>;
+    type Outputs = U2;
+    type Setting = (f64, T);
+
+    fn set(&mut self, setting: Self::Setting) {
+        self.set_modulation(setting.0, setting.1);
+    }
+
+    fn reset(&mut self) {
+        self.lfo.phase = 0.0;
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.lfo.sample_rate = sample_rate;
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let pan = self.depth * self.shape.at(T::from_f64(self.lfo.phase));
+        let (left, right) = pan_weights(pan);
+        let tempo = if M::USIZE > 0 {
+            Some(input[1].to_f64())
+        } else {
+            None
+        };
+        self.lfo.advance(tempo);
+        [input[0] * left, input[0] * right].into()
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        output[0] = input[0].distort(0.0);
+        output[1] = input[0].distort(0.0);
+        output
+    }
+}
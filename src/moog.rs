@@ -84,6 +84,14 @@ impl<T: Float, F: Real, N: Size<T>> AudioNode for Moog<T, F, N> {
         self.set_cutoff_q(self.cutoff, self.q);
     }
 
+    fn input_kind(&self, input: usize) -> PortKind {
+        match input {
+            1 => PortKind::Frequency,
+            2 => PortKind::Q,
+            _ => PortKind::Audio,
+        }
+    }
+
     #[inline]
     fn tick(
         &mut self,
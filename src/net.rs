@@ -4,13 +4,21 @@ use super::audionode::*;
 use super::audiounit::*;
 use super::buffer::*;
 use super::combinator::*;
+use super::delay::Delay;
 use super::math::*;
 use super::realnet::*;
+use super::sequencer::Fade;
+use super::shared::*;
 use super::signal::*;
+use super::snoop::*;
+use super::trace::*;
 use super::*;
 use duplicate::duplicate_item;
+use rsor::Slice;
 use std::collections::HashMap;
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
 use thingbuf::mpsc::blocking::{channel, Receiver, Sender};
 
 pub type NodeIndex = usize;
@@ -33,6 +41,39 @@ impl NodeId {
 
 const ID: u64 = 63;
 
+/// Pointer to vertices that can be shared between threads during parallel processing.
+#[cfg(feature = "rayon")]
+#[derive(Clone, Copy)]
+struct VertexPointer<T>(*mut T);
+
+#[cfg(feature = "rayon")]
+impl<T> VertexPointer<T> {
+    /// Access the pointer. Closures must capture the whole wrapper, not the raw pointer field.
+    #[inline]
+    fn get(self) -> *mut T {
+        self.0
+    }
+}
+
+#[cfg(feature = "rayon")]
+unsafe impl<T> Send for VertexPointer<T> {}
+#[cfg(feature = "rayon")]
+unsafe impl<T> Sync for VertexPointer<T> {}
+
+/// View of `slot` in a buffer arena made of `MAX_BUFFER_SIZE` sample slots.
+/// The caller must guarantee that the slot exists and is not written to while the view is alive.
+#[inline]
+unsafe fn arena_ref<'a, T>(arena: *const T, slot: usize) -> &'a [T] {
+    std::slice::from_raw_parts(arena.add(slot * MAX_BUFFER_SIZE), MAX_BUFFER_SIZE)
+}
+
+/// Mutable view of `slot` in a buffer arena made of `MAX_BUFFER_SIZE` sample slots.
+/// The caller must guarantee that the slot exists and is not accessed elsewhere while the view is alive.
+#[inline]
+unsafe fn arena_mut<'a, T>(arena: *mut T, slot: usize) -> &'a mut [T] {
+    std::slice::from_raw_parts_mut(arena.add(slot * MAX_BUFFER_SIZE), MAX_BUFFER_SIZE)
+}
+
 /// Input or output port.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
 pub enum Port {
@@ -45,65 +86,355 @@ pub enum Port {
     Zero,
 }
 
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug)]
 pub struct Edge {
     pub source: Port,
     pub target: Port,
+    /// Whether this is a feedback edge. Feedback edges delay the signal by one sample,
+    /// so they may close cycles.
+    pub feedback: bool,
+    /// Gain applied to the signal. Negative gains invert the phase.
+    pub gain: f64,
+}
+
+impl Default for Edge {
+    fn default() -> Self {
+        edge(Port::Zero, Port::Zero)
+    }
+}
+
+impl Edge {
+    /// Return this edge reconnected from `source` to `target`.
+    /// The gain is kept, as is the feedback flag if the source is a node output.
+    fn moved(self, source: Port, target: Port) -> Edge {
+        Edge {
+            source,
+            target,
+            feedback: self.feedback && matches!(source, Port::Local(_, _)),
+            gain: self.gain,
+        }
+    }
 }
 
 /// Create an edge from source to target.
 pub fn edge(source: Port, target: Port) -> Edge {
-    Edge { source, target }
+    Edge {
+        source,
+        target,
+        feedback: false,
+        gain: 1.0,
+    }
 }
 
-#[duplicate_item(
-    f48       Vertex48       AudioUnit48;
-    [ f64 ]   [ Vertex64 ]   [ AudioUnit64 ];
-    [ f32 ]   [ Vertex32 ]   [ AudioUnit32 ];
-)]
+/// Connection between ports of mismatched kinds, for example,
+/// a trigger output connected to a frequency input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PortMismatch {
+    /// Source node.
+    pub source: NodeId,
+    /// Source node output.
+    pub source_port: PortIndex,
+    /// Kind of signal produced at the source.
+    pub source_kind: PortKind,
+    /// Target node.
+    pub target: NodeId,
+    /// Target node input.
+    pub target_port: PortIndex,
+    /// Kind of signal expected at the target.
+    pub target_kind: PortKind,
+}
+
+/// Error in the structure of a network.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NetError {
+    /// The network contains a cycle, so units cannot be ordered for processing.
+    Cycle,
+}
+
+impl std::fmt::Display for NetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NetError::Cycle => write!(f, "Cycle detected"),
+        }
+    }
+}
+
+impl std::error::Error for NetError {}
+
+/// Edit of a network. Edits can be sent to the thread that owns the network
+/// and applied there with `Net::apply`.
+#[derive(Default)]
+pub enum NetEdit<T: UnitFloat> {
+    /// Nothing.
+    #[default]
+    Null,
+    /// Add a unit with the given ID. Create the ID with `NodeId::new`.
+    Push(NodeId, Box<T::Unit>),
+    /// Remove a unit. Connections from the unit are replaced with zeros.
+    Remove(NodeId),
+    /// Replace a unit, retaining its connections.
+    Replace(NodeId, Box<T::Unit>),
+    /// Connect (source, source port) to (target, target port).
+    Connect(NodeId, PortIndex, NodeId, PortIndex),
+    /// Disconnect (target, target port).
+    Disconnect(NodeId, PortIndex),
+    /// Connect (global input) to (target, target port).
+    ConnectInput(PortIndex, NodeId, PortIndex),
+    /// Connect (source, source port) to (global output).
+    ConnectOutput(NodeId, PortIndex, PortIndex),
+    /// Disconnect (global output).
+    DisconnectOutput(PortIndex),
+    /// Set the gain of the edge into (target, target port).
+    SetEdgeGain(NodeId, PortIndex, f64),
+    /// Bypass or restore a unit.
+    SetBypass(NodeId, bool),
+    /// Mute or unmute a unit.
+    SetMute(NodeId, bool),
+}
+
+impl<T: UnitFloat> Clone for NetEdit<T> {
+    fn clone(&self) -> Self {
+        NetEdit::Null
+    }
+}
+
+/// Edit of a 64-bit network.
+pub type NetEdit64 = NetEdit<f64>;
+
+/// Edit of a 32-bit network.
+pub type NetEdit32 = NetEdit<f32>;
+
+/// Level of a signal over the last processed block.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SignalLevel {
+    /// Peak absolute value.
+    pub peak: f64,
+    /// RMS level.
+    pub rms: f64,
+}
+
+/// Level meter of a port. Levels are written by the processing network
+/// and can be read from other threads.
+#[derive(Clone)]
+struct LevelMeter {
+    peak: Shared<f64>,
+    rms: Shared<f64>,
+}
+
+impl LevelMeter {
+    fn new() -> Self {
+        Self {
+            peak: Shared::new(0.0),
+            rms: Shared::new(0.0),
+        }
+    }
+
+    /// Measure levels of a block.
+    #[inline]
+    fn measure<T: Float>(&self, x: &[T]) {
+        let mut peak = 0.0;
+        let mut square = 0.0;
+        for &x in x {
+            let x = x.to_f64();
+            peak = max(peak, abs(x));
+            square += x * x;
+        }
+        self.peak.set_value(peak);
+        self.rms.set_value(sqrt(square / max(1.0, x.len() as f64)));
+    }
+
+    fn level(&self) -> SignalLevel {
+        SignalLevel {
+            peak: self.peak.value(),
+            rms: self.rms.value(),
+        }
+    }
+}
+
+/// Watchdog that mutes expensive units when processing takes too long.
 #[derive(Clone)]
+struct Watchdog {
+    /// Maximum fraction of real time a block may take.
+    budget: f64,
+    /// Number of consecutive blocks over budget that trigger muting.
+    blocks: usize,
+    /// Number of consecutive blocks over budget so far.
+    overruns: usize,
+}
+
+/// Duration of bypass and mute ramps in seconds.
+const BYPASS_RAMP_TIME: f64 = 0.005;
+
+/// Click-free bypass and mute of a vertex.
+#[derive(Clone)]
+struct Bypass {
+    /// Whether the unit is bypassed, passing its inputs to its outputs.
+    bypass: bool,
+    /// Whether the outputs of the vertex are silenced.
+    mute: bool,
+    /// Gain of the unit output, which ramps to zero when bypassed.
+    wet: f64,
+    /// Gain of the vertex output, which ramps to zero when muted.
+    level: f64,
+    /// Ramp increment per sample.
+    step: f64,
+}
+
+impl Bypass {
+    fn new() -> Self {
+        Self {
+            bypass: false,
+            mute: false,
+            wet: 1.0,
+            level: 1.0,
+            step: 1.0 / (BYPASS_RAMP_TIME * DEFAULT_SR),
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.step = 1.0 / (BYPASS_RAMP_TIME * sample_rate);
+    }
+
+    /// Whether the outputs of the vertex are affected.
+    #[inline]
+    fn is_active(&self) -> bool {
+        self.bypass || self.mute || self.wet < 1.0 || self.level < 1.0
+    }
+
+    /// Advance ramps by one sample. Returns (wet, level).
+    #[inline]
+    fn advance(&mut self) -> (f64, f64) {
+        let step = self.step;
+        let ramp = |x: f64, target: bool| {
+            if target {
+                max(0.0, x - step)
+            } else {
+                min(1.0, x + step)
+            }
+        };
+        self.wet = ramp(self.wet, self.bypass);
+        self.level = ramp(self.level, self.mute);
+        (self.wet, self.level)
+    }
+
+    /// Apply bypass and mute to one sample of unit `output`.
+    #[inline]
+    fn tick<T: Float>(&mut self, input: &[T], output: &mut [T]) {
+        let (wet, level) = self.advance();
+        for (port, y) in output.iter_mut().enumerate() {
+            let x = input.get(port).map_or(0.0, |x| x.to_f64());
+            *y = T::from_f64((y.to_f64() * wet + x * (1.0 - wet)) * level);
+        }
+    }
+
+    /// Apply bypass and mute to `size` samples of unit `output`.
+    fn process<T: Float>(&mut self, size: usize, input: &[&[T]], output: &mut [&mut [T]]) {
+        for i in 0..size {
+            let (wet, level) = self.advance();
+            for (port, y) in output.iter_mut().enumerate() {
+                let x = input.get(port).map_or(0.0, |x| x[i].to_f64());
+                let y = &mut y[i];
+                *y = T::from_f64((y.to_f64() * wet + x * (1.0 - wet)) * level);
+            }
+        }
+    }
+}
+
 /// Individual AudioUnits are vertices in the graph.
-struct Vertex48 {
+struct Vertex<T: UnitFloat> {
     /// The unit.
-    pub unit: Box<dyn AudioUnit48>,
+    pub unit: Box<T::Unit>,
     /// Edges connecting into this vertex. The length is equal to the number of inputs.
     pub source: Vec<Edge>,
-    /// Input buffers. The length is equal to the number of inputs.
-    pub input: Buffer<f48>,
-    /// Output buffers. The length is equal to the number of outputs.
-    pub output: Buffer<f48>,
+    /// Buffer arena slots of the outputs. The length is equal to the number of outputs.
+    pub output_slot: Vec<usize>,
+    /// Buffer arena slots for inputs scaled by edge gains. The length is equal to the number of inputs.
+    /// Only inputs of edges with gain have a slot.
+    gained_slot: Vec<usize>,
     /// Input for tick iteration. The length is equal to the number of inputs.
-    pub tick_input: Vec<f48>,
+    pub tick_input: Vec<T>,
     /// Output for tick iteration. The length is equal to the number of outputs.
-    pub tick_output: Vec<f48>,
+    pub tick_output: Vec<T>,
     /// Stable, globally unique ID for this vertex.
     pub id: NodeId,
-    /// This is set if all vertex inputs are sourced from matching outputs of the indicated node.
-    /// We can then omit copying and use the node outputs directly.
-    pub source_vertex: Option<NodeIndex>,
+    /// Optional name of this vertex.
+    pub name: Option<String>,
     /// Network revision in which this vertex was changed last.
     pub changed: u64,
+    /// Output level meters. This is empty if metering is disabled.
+    meter: Vec<LevelMeter>,
+    /// Probes tapping output ports of this vertex.
+    probe: Vec<(PortIndex, SnoopBackend<T>)>,
+    /// Processing time of the last block in seconds. This is measured only by the watchdog.
+    cost: f64,
+    /// Whether the unit has been muted by the watchdog.
+    /// The flag is shared between a frontend and the versions committed to its backend.
+    muted: Arc<AtomicBool>,
+    /// Previous sample of each input that is connected with a feedback edge.
+    feedback: Vec<T>,
+    /// Bypass and mute of the vertex.
+    bypass: Bypass,
+    /// Input slices for parallel processing. Capacity is reserved when the order is determined.
+    #[cfg(feature = "rayon")]
+    slice: Slice<[T]>,
+    /// Output slices for parallel processing. Capacity is reserved when the order is determined.
+    #[cfg(feature = "rayon")]
+    output_slice: Slice<[T]>,
 }
 
-#[duplicate_item(
-    f48       Vertex48       AudioUnit48;
-    [ f64 ]   [ Vertex64 ]   [ AudioUnit64 ];
-    [ f32 ]   [ Vertex32 ]   [ AudioUnit32 ];
-)]
-impl Vertex48 {
-    pub fn new(id: NodeId, index: NodeIndex, unit: Box<dyn AudioUnit48>) -> Self {
+/// Clones share level meters and probes with the original, so levels and signals
+/// of the versions in a backend can be read through the frontend.
+/// The watchdog mute flag is not shared: clones can be muted independently.
+impl<T: UnitFloat> Clone for Vertex<T> {
+    fn clone(&self) -> Self {
+        Self {
+            unit: self.unit.clone_unit(),
+            source: self.source.clone(),
+            output_slot: self.output_slot.clone(),
+            gained_slot: self.gained_slot.clone(),
+            tick_input: self.tick_input.clone(),
+            tick_output: self.tick_output.clone(),
+            id: self.id,
+            name: self.name.clone(),
+            changed: self.changed,
+            meter: self.meter.clone(),
+            probe: self.probe.clone(),
+            cost: self.cost,
+            muted: Arc::new(AtomicBool::new(self.is_muted())),
+            feedback: self.feedback.clone(),
+            bypass: self.bypass.clone(),
+            #[cfg(feature = "rayon")]
+            slice: Slice::with_capacity(self.slice.capacity()),
+            #[cfg(feature = "rayon")]
+            output_slice: Slice::with_capacity(self.output_slice.capacity()),
+        }
+    }
+}
+
+impl<T: UnitFloat> Vertex<T> {
+    pub fn new(id: NodeId, index: NodeIndex, unit: Box<T::Unit>) -> Self {
         let inputs = unit.inputs();
         let outputs = unit.outputs();
         let mut vertex = Self {
             unit,
             source: vec![],
-            input: Buffer::with_channels(inputs),
-            output: Buffer::with_channels(outputs),
-            tick_input: vec![0.0; inputs],
-            tick_output: vec![0.0; outputs],
+            output_slot: vec![0; outputs],
+            gained_slot: vec![0; inputs],
+            tick_input: vec![T::zero(); inputs],
+            tick_output: vec![T::zero(); outputs],
             id,
-            source_vertex: None,
+            name: None,
             changed: 0,
+            meter: Vec::new(),
+            probe: Vec::new(),
+            cost: 0.0,
+            muted: Arc::new(AtomicBool::new(false)),
+            feedback: vec![T::zero(); inputs],
+            bypass: Bypass::new(),
+            #[cfg(feature = "rayon")]
+            slice: Slice::new(),
+            #[cfg(feature = "rayon")]
+            output_slice: Slice::new(),
         };
         for i in 0..vertex.inputs() {
             vertex.source.push(edge(Port::Zero, Port::Local(index, i)));
@@ -123,56 +454,94 @@ impl Vertex48 {
         self.tick_output.len()
     }
 
-    /// Update source vertex shortcut.
-    pub fn update_source_vertex(&mut self) {
-        self.source_vertex = None;
-        if self.inputs() == 0 {
-            return;
+    /// Preallocate everything.
+    pub fn allocate(&mut self) {
+        self.unit.allocate();
+    }
+
+    /// Reuse a spare vertex for `unit`, which must have the same number of inputs
+    /// and outputs as the vertex. No allocations are made.
+    pub fn recycle(&mut self, id: NodeId, index: NodeIndex, unit: Box<T::Unit>) {
+        assert!(unit.inputs() == self.inputs() && unit.outputs() == self.outputs());
+        self.unit = unit;
+        for (i, source) in self.source.iter_mut().enumerate() {
+            *source = edge(Port::Zero, Port::Local(index, i));
         }
-        let mut source_node = 0;
-        for i in 0..self.inputs() {
-            match self.source[i].source {
-                Port::Local(node, port) => {
-                    if port != i {
-                        return;
-                    }
-                    if i == 0 {
-                        source_node = node;
-                    } else if source_node != node {
-                        return;
-                    }
-                }
-                _ => {
-                    return;
-                }
-            }
+        self.tick_input.fill(T::zero());
+        self.tick_output.fill(T::zero());
+        self.feedback.fill(T::zero());
+        self.id = id;
+        self.name = None;
+        self.changed = 0;
+        self.meter.clear();
+        self.probe.clear();
+        self.cost = 0.0;
+        // The flag may still be shared with a version in the backend.
+        self.muted = Arc::new(AtomicBool::new(false));
+        self.bypass = Bypass::new();
+    }
+
+    /// Whether the unit has been muted by the watchdog.
+    #[inline]
+    pub fn is_muted(&self) -> bool {
+        self.muted.load(Ordering::Relaxed)
+    }
+
+    /// Feed the first `size` samples of the output buffers in `arena` to meters and probes.
+    /// The output slots must not be written to during the call.
+    #[inline]
+    unsafe fn observe(&mut self, arena: *const T, size: usize) {
+        for (port, meter) in self.meter.iter().enumerate() {
+            meter.measure(&arena_ref(arena, self.output_slot[port])[..size]);
+        }
+        for (port, probe) in self.probe.iter_mut() {
+            probe.record(&arena_ref(arena, self.output_slot[*port])[..size]);
         }
-        self.source_vertex = Some(source_node);
     }
 
-    /// Preallocate everything.
-    pub fn allocate(&mut self) {
-        self.unit.allocate();
+    /// Feed the latest tick outputs to meters and probes.
+    #[inline]
+    fn observe_tick(&mut self) {
+        for (meter, x) in self.meter.iter().zip(self.tick_output.iter()) {
+            meter.measure(&[*x]);
+        }
+        for (port, probe) in self.probe.iter_mut() {
+            probe.record(&self.tick_output[*port..*port + 1]);
+        }
     }
 }
 
-#[duplicate_item(
-    f48       Net48       Vertex48       AudioUnit48;
-    [ f64 ]   [ Net64 ]   [ Vertex64 ]   [ AudioUnit64 ];
-    [ f32 ]   [ Net32 ]   [ Vertex32 ]   [ AudioUnit32 ];
-)]
 /// Network unit. It can contain other units and maintain connections between them.
 /// Outputs of the network are sourced from user specified unit outputs or global inputs.
+/// Units whose outputs do not reach any global output are not processed,
+/// unless they have side effects, like monitors do.
+///
+/// The sample type is `f64` (`Net64`) or `f32` (`Net32`).
+/// Networks can also be built and processed in code that is generic over sample type.
+///
+/// ### Example (Generic Network)
+/// ```
+/// use fundsp::prelude::*;
+/// fn gain_net<T: UnitFloat>(gain: T) -> Net<T> {
+///     let mut net = Net::new(1, 1);
+///     net.chain(T::boxed(pass::<T>() * dc(gain)));
+///     net
+/// }
+/// let mut net = gain_net(0.5f32);
+/// let mut output = [0.0];
+/// net.tick(&[2.0], &mut output);
+/// assert!(output[0] == 1.0);
+/// ```
 #[derive(Default)]
-pub struct Net48 {
+pub struct Net<T: UnitFloat> {
     /// Global input buffers.
-    input: Buffer<f48>,
+    input: Buffer<T>,
     /// Global output buffers.
-    output: Buffer<f48>,
+    output: Buffer<T>,
     /// Sources of global outputs.
     output_edge: Vec<Edge>,
     /// Vertices of the graph.
-    vertex: Vec<Vertex48>,
+    vertex: Vec<Vertex<T>>,
     /// Ordering of vertex evaluation.
     order: Option<Vec<NodeIndex>>,
     /// Translation map from node ID to vertex index.
@@ -180,22 +549,63 @@ pub struct Net48 {
     /// Current sample rate.
     sample_rate: f64,
     /// Optional frontend.
-    front: Option<(Sender<Net48>, Receiver<Net48>)>,
-    /// Number of inputs in the backend. This is for checking consistency during commits.
-    backend_inputs: usize,
-    /// Number of outputs in the backend. This is for checking consistency during commits.
-    backend_outputs: usize,
+    front: Option<(Sender<Net<T>>, Receiver<Net<T>>)>,
+    /// Number of scheduled commits that have not taken effect in the backend yet.
+    /// The counter is shared between a frontend and its backend.
+    pending: Arc<AtomicUsize>,
     /// Revision number. This is used by frontends and backends only.
     /// The revision is incremented after each commit.
     revision: u64,
+    /// Crossfade (shape, time in seconds) requested for a committed version.
+    /// This is used by frontends and backends only.
+    commit_fade: Option<(Fade, T)>,
+    /// Backend time in seconds at which a committed version takes effect, if scheduled.
+    /// This is used by frontends and backends only.
+    commit_time: Option<f64>,
+    /// Input slices of the vertex being processed.
+    slice: Slice<[T]>,
+    /// Output slices of the vertex being processed.
+    output_slice: Slice<[T]>,
+    /// Buffer arena for block processing, made of `MAX_BUFFER_SIZE` sample slots.
+    /// Vertex outputs and gained inputs are assigned slots when the order is determined.
+    /// Slot 0 is never written to: it is the source of zero inputs.
+    arena: Vec<T>,
+    /// Optional tracer for network events.
+    tracer: Option<Tracer>,
+    /// Whether signal levels are metered.
+    metering: bool,
+    /// Global input level meters. This is empty if metering is disabled.
+    input_meter: Vec<LevelMeter>,
+    /// Optional watchdog for processing time.
+    watchdog: Option<Watchdog>,
+    /// Error encountered while ordering units, if any. The network outputs silence while set.
+    error: Option<NetError>,
+    /// Whether there are feedback edges. Then the network is processed one sample at a time.
+    has_feedback: bool,
+    /// Global input frame for processing one sample at a time.
+    tick_input: Vec<T>,
+    /// Global output frame for processing one sample at a time.
+    tick_output: Vec<T>,
+    /// Whether independent units are processed in parallel.
+    parallel: bool,
+    /// Units grouped by dependency level. Units on the same level are independent.
+    #[cfg(feature = "rayon")]
+    level: Vec<Vec<NodeIndex>>,
+    /// Spare vertices that are reused when units are added.
+    pool: Vec<Vertex<T>>,
+    /// Storage of the last invalidated order, kept for reuse.
+    spare_order: Vec<NodeIndex>,
+    /// Scratch space for counting inputs while ordering.
+    scratch_count: Vec<usize>,
+    /// Scratch space for vertex flags while ordering.
+    scratch_flag: Vec<bool>,
+    /// Scratch space for the start of the successor range of each vertex while ordering.
+    scratch_start: Vec<usize>,
+    /// Scratch space for successors of vertices while ordering.
+    scratch_successor: Vec<NodeIndex>,
 }
 
-#[duplicate_item(
-    f48       Net48       Vertex48       AudioUnit48;
-    [ f64 ]   [ Net64 ]   [ Vertex64 ]   [ AudioUnit64 ];
-    [ f32 ]   [ Net32 ]   [ Vertex32 ]   [ AudioUnit32 ];
-)]
-impl Clone for Net48 {
+impl<T: UnitFloat> Clone for Net<T> {
     fn clone(&self) -> Self {
         Self {
             input: self.input.clone(),
@@ -207,21 +617,37 @@ impl Clone for Net48 {
             sample_rate: self.sample_rate,
             // Frontend is never cloned.
             front: None,
-            backend_inputs: self.backend_inputs,
-            backend_outputs: self.backend_outputs,
+            pending: Arc::new(AtomicUsize::new(0)),
             revision: self.revision,
+            commit_fade: None,
+            commit_time: None,
+            slice: Slice::new(),
+            output_slice: Slice::new(),
+            arena: self.arena.clone(),
+            tracer: self.tracer.clone(),
+            metering: self.metering,
+            input_meter: self.input_meter.clone(),
+            watchdog: self.watchdog.clone(),
+            error: self.error,
+            has_feedback: self.has_feedback,
+            tick_input: self.tick_input.clone(),
+            tick_output: self.tick_output.clone(),
+            parallel: self.parallel,
+            #[cfg(feature = "rayon")]
+            level: self.level.clone(),
+            pool: self.pool.clone(),
+            spare_order: Vec::with_capacity(self.spare_order.capacity()),
+            scratch_count: Vec::with_capacity(self.scratch_count.capacity()),
+            scratch_flag: Vec::with_capacity(self.scratch_flag.capacity()),
+            scratch_start: Vec::with_capacity(self.scratch_start.capacity()),
+            scratch_successor: Vec::with_capacity(self.scratch_successor.capacity()),
         }
     }
 }
 
-#[duplicate_item(
-    f48       Net48       NetBackend48       Vertex48       AudioUnit48;
-    [ f64 ]   [ Net64 ]   [ NetBackend64 ]   [ Vertex64 ]   [ AudioUnit64 ];
-    [ f32 ]   [ Net32 ]   [ NetBackend32 ]   [ Vertex32 ]   [ AudioUnit32 ];
-)]
-impl Net48 {
+impl<T: UnitFloat> Net<T> {
     /// Create a new network with the given number of inputs and outputs.
-    /// The number of inputs and outputs is fixed after construction.
+    /// The number of inputs and outputs can be changed later with `set_inputs` and `set_outputs`.
     /// Network global outputs are initialized to zero.
     ///
     /// ### Example (Sine Oscillator)
@@ -229,7 +655,7 @@ impl Net48 {
     /// use fundsp::hacker::*;
     /// let mut net = Net64::new(1, 1);
     /// net.chain(Box::new(sine()));
-    /// net.check();
+    /// net.check().unwrap();
     /// ```
     pub fn new(inputs: usize, outputs: usize) -> Self {
         let mut net = Self {
@@ -241,9 +667,30 @@ impl Net48 {
             node_index: HashMap::new(),
             sample_rate: DEFAULT_SR,
             front: None,
-            backend_inputs: inputs,
-            backend_outputs: outputs,
+            pending: Arc::new(AtomicUsize::new(0)),
             revision: 0,
+            commit_fade: None,
+            commit_time: None,
+            slice: Slice::new(),
+            output_slice: Slice::new(),
+            arena: vec![T::zero(); MAX_BUFFER_SIZE],
+            tracer: None,
+            metering: false,
+            input_meter: Vec::new(),
+            watchdog: None,
+            error: None,
+            has_feedback: false,
+            tick_input: vec![T::zero(); inputs],
+            tick_output: vec![T::zero(); outputs],
+            parallel: false,
+            #[cfg(feature = "rayon")]
+            level: Vec::new(),
+            pool: Vec::new(),
+            spare_order: Vec::new(),
+            scratch_count: Vec::new(),
+            scratch_flag: Vec::new(),
+            scratch_start: Vec::new(),
+            scratch_successor: Vec::new(),
         };
         for channel in 0..outputs {
             net.output_edge
@@ -262,20 +709,159 @@ impl Net48 {
     /// let id = net.push(Box::new(sine()));
     /// net.pipe_input(id);
     /// net.pipe_output(id);
-    /// net.check();
+    /// net.check().unwrap();
     /// ```
-    pub fn push(&mut self, mut unit: Box<dyn AudioUnit48>) -> NodeId {
+    pub fn push(&mut self, unit: Box<T::Unit>) -> NodeId {
+        let id = NodeId::new();
+        self.push_id(id, unit);
+        id
+    }
+
+    /// Add a new unit to the network with the given ID.
+    /// A spare vertex of matching shape is reused if one has been reserved.
+    fn push_id(&mut self, id: NodeId, mut unit: Box<T::Unit>) {
+        assert!(!self.node_index.contains_key(&id));
         unit.set_sample_rate(self.sample_rate);
         let index = self.vertex.len();
-        let id = NodeId::new();
-        let vertex = Vertex48::new(id, index, unit);
+        let spare = self.pool.iter().position(|vertex| {
+            vertex.inputs() == unit.inputs() && vertex.outputs() == unit.outputs()
+        });
+        let mut vertex = match spare {
+            Some(spare) => {
+                let mut vertex = self.pool.swap_remove(spare);
+                vertex.recycle(id, index, unit);
+                vertex
+            }
+            None => Vertex::new(id, index, unit),
+        };
+        vertex.bypass.set_sample_rate(self.sample_rate);
+        if self.metering {
+            vertex.meter = (0..vertex.outputs()).map(|_| LevelMeter::new()).collect();
+        }
         self.vertex.push(vertex);
         self.node_index.insert(id, index);
         // Note. We have designed the hash to depend on vertices but not edges.
         let hash = self.ping(true, AttoHash::new(ID));
         self.ping(false, hash);
         self.invalidate_order();
-        id
+    }
+
+    /// Create a new network with the given number of inputs and outputs and
+    /// room for `capacity` units. Together with `reserve`, this allows
+    /// a network to be edited in the audio thread without allocating memory.
+    ///
+    /// ### Example
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let mut net = Net64::with_capacity(0, 1, 16);
+    /// net.reserve(8, 0, 1);
+    /// ```
+    pub fn with_capacity(inputs: usize, outputs: usize, capacity: usize) -> Self {
+        let mut net = Self::new(inputs, outputs);
+        net.vertex.reserve(capacity);
+        net.node_index.reserve(capacity);
+        net.pool.reserve(capacity);
+        net.spare_order.reserve(capacity);
+        net.scratch_count.reserve(capacity);
+        net.scratch_flag.reserve(capacity);
+        net.scratch_start.reserve(capacity + 1);
+        net.scratch_successor.reserve(capacity);
+        net
+    }
+
+    /// Preallocate `count` spare vertices for units with the given number of `inputs` and `outputs`.
+    /// Adding a unit of matching shape reuses a spare vertex, and removing a unit
+    /// returns its vertex to the spares if there is room, so these edits do not allocate,
+    /// provided that the network was created with enough capacity.
+    /// Metering, names and parallel processing still allocate.
+    ///
+    /// ### Example (Allocation-Free Editing)
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let mut net = Net64::with_capacity(0, 1, 4);
+    /// net.reserve(2, 0, 1);
+    /// // These edits can be made in the audio thread.
+    /// let id = NodeId::new();
+    /// net.apply(NetEdit64::Push(id, Box::new(sine_hz(110.0))));
+    /// net.apply(NetEdit64::ConnectOutput(id, 0, 0));
+    /// assert!(net.size() == 1);
+    /// ```
+    pub fn reserve(&mut self, count: usize, inputs: usize, outputs: usize) {
+        self.pool.reserve(count);
+        // Reserve arena slots for the outputs and gained inputs of the new units.
+        self.arena
+            .reserve(count * (inputs + outputs) * MAX_BUFFER_SIZE);
+        for _ in 0..count {
+            // Spare vertices hold a placeholder unit. Boxing it does not allocate.
+            let mut vertex = Vertex::new(NodeId::new(), 0, T::boxed(An(Pass::<T>::new())));
+            vertex.source.resize(inputs, Edge::default());
+            vertex.output_slot.resize(outputs, 0);
+            vertex.gained_slot.resize(inputs, 0);
+            vertex.tick_input.resize(inputs, T::zero());
+            vertex.tick_output.resize(outputs, T::zero());
+            vertex.feedback.resize(inputs, T::zero());
+            vertex.meter.reserve(outputs);
+            self.pool.push(vertex);
+        }
+    }
+
+    /// Apply an edit to the network. Returns the unit that was removed or replaced, if any.
+    /// The returned unit should be dropped outside the audio thread.
+    /// Adding units allocates unless spare vertices have been reserved with `reserve`.
+    ///
+    /// ### Example
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let mut net = Net64::new(1, 1);
+    /// let id = NodeId::new();
+    /// net.apply(NetEdit64::Push(id, Box::new(lowpass_hz(1000.0, 1.0))));
+    /// net.apply(NetEdit64::ConnectInput(0, id, 0));
+    /// net.apply(NetEdit64::ConnectOutput(id, 0, 0));
+    /// let removed = net.apply(NetEdit64::Remove(id));
+    /// assert!(removed.is_some() && net.size() == 0);
+    /// ```
+    pub fn apply(&mut self, edit: NetEdit<T>) -> Option<Box<T::Unit>> {
+        match edit {
+            NetEdit::Null => None,
+            NetEdit::Push(id, unit) => {
+                self.push_id(id, unit);
+                None
+            }
+            NetEdit::Remove(id) => Some(self.remove(id)),
+            NetEdit::Replace(id, unit) => Some(self.replace(id, unit)),
+            NetEdit::Connect(source, source_port, target, target_port) => {
+                self.connect(source, source_port, target, target_port);
+                None
+            }
+            NetEdit::Disconnect(target, target_port) => {
+                self.disconnect(target, target_port);
+                None
+            }
+            NetEdit::ConnectInput(global_input, target, target_port) => {
+                self.connect_input(global_input, target, target_port);
+                None
+            }
+            NetEdit::ConnectOutput(source, source_port, global_output) => {
+                self.connect_output(source, source_port, global_output);
+                None
+            }
+            NetEdit::DisconnectOutput(global_output) => {
+                self.disconnect_output(global_output);
+                None
+            }
+            NetEdit::SetEdgeGain(target, target_port, gain) => {
+                self.set_edge_gain(target, target_port, gain);
+                None
+            }
+            NetEdit::SetBypass(node, bypass) => {
+                self.set_bypass(node, bypass);
+                None
+            }
+            NetEdit::SetMute(node, mute) => {
+                self.set_mute(node, mute);
+                None
+            }
+        }
     }
 
     /// Whether we have calculated the order vector.
@@ -285,7 +871,10 @@ impl Net48 {
 
     /// Invalidate any precalculated order.
     fn invalidate_order(&mut self) {
-        self.order = None;
+        // Keep the storage of the order, so that it can be reused without allocation.
+        if let Some(order) = self.order.take() {
+            self.spare_order = order;
+        }
     }
 
     /// Remove `node` from network. Returns the unit that was removed.
@@ -301,9 +890,9 @@ impl Net48 {
     /// net.connect_output(id2, 0, 0);
     /// net.remove(id1);
     /// assert!(net.size() == 1);
-    /// net.check();
+    /// net.check().unwrap();
     /// ```
-    pub fn remove(&mut self, node: NodeId) -> Box<dyn AudioUnit48> {
+    pub fn remove(&mut self, node: NodeId) -> Box<T::Unit> {
         self.remove_2(node, false)
     }
 
@@ -322,27 +911,29 @@ impl Net48 {
     /// net.remove_link(id2);
     /// assert!(net.size() == 1);
     /// assert!(net.filter_mono(1.0) == 2.0);
-    /// net.check();
+    /// net.check().unwrap();
     /// ```
-    pub fn remove_link(&mut self, node: NodeId) -> Box<dyn AudioUnit48> {
+    pub fn remove_link(&mut self, node: NodeId) -> Box<T::Unit> {
         self.remove_2(node, true)
     }
 
     /// Remove `node` from network. If `link` is false then connections from the unit
     /// are replaced with zeros; if `link` is true then connections are replaced
     /// by matching inputs of the unit, and the number of inputs must be equal to the number of outputs.
-    fn remove_2(&mut self, node: NodeId, link: bool) -> Box<dyn AudioUnit48> {
+    fn remove_2(&mut self, node: NodeId, link: bool) -> Box<T::Unit> {
         let node_index = self.node_index[&node];
         assert!(!link || self.vertex[node_index].inputs() == self.vertex[node_index].outputs());
         // Replace all global ports that use an output of the node.
         for channel in 0..self.outputs() {
             if let Port::Local(index, port) = self.output_edge[channel].source {
                 if index == node_index {
-                    self.output_edge[channel].source = if link {
-                        self.vertex[node_index].source[port].source
+                    if link {
+                        let removed = self.vertex[node_index].source[port];
+                        self.output_edge[channel].source = removed.source;
+                        self.output_edge[channel].gain *= removed.gain;
                     } else {
-                        Port::Zero
-                    };
+                        self.output_edge[channel].source = Port::Zero;
+                    }
                 }
             }
         }
@@ -351,11 +942,16 @@ impl Net48 {
             for channel in 0..self.vertex[vertex].inputs() {
                 if let Port::Local(index, port) = self.vertex[vertex].source[channel].source {
                     if index == node_index {
-                        self.vertex[vertex].source[channel].source = if link {
-                            self.vertex[node_index].source[port].source
+                        let removed = self.vertex[node_index].source[port];
+                        let edge = &mut self.vertex[vertex].source[channel];
+                        if link {
+                            edge.source = removed.source;
+                            edge.feedback |= removed.feedback;
+                            edge.gain *= removed.gain;
                         } else {
-                            Port::Zero
-                        };
+                            edge.source = Port::Zero;
+                            edge.feedback = false;
+                        }
                     }
                 }
             }
@@ -390,7 +986,15 @@ impl Net48 {
         }
         self.invalidate_order();
 
-        self.vertex.pop().unwrap().unit
+        let mut vertex = self.vertex.pop().unwrap();
+        if self.pool.len() < self.pool.capacity() {
+            // Keep the vertex as a spare. Boxing the placeholder unit does not allocate.
+            let unit = std::mem::replace(&mut vertex.unit, T::boxed(An(Pass::<T>::new())));
+            self.pool.push(vertex);
+            unit
+        } else {
+            vertex.unit
+        }
     }
 
     /// Replaces the given node in the network.
@@ -406,16 +1010,13 @@ impl Net48 {
     /// let id = net.push(Box::new(saw_hz(220.0)));
     /// net.pipe_output(id);
     /// net.replace(id, Box::new(square_hz(220.0)));
-    /// net.check();
+    /// net.check().unwrap();
     /// ```
-    pub fn replace(
-        &mut self,
-        node: NodeId,
-        mut unit: Box<dyn AudioUnit48>,
-    ) -> Box<dyn AudioUnit48> {
+    pub fn replace(&mut self, node: NodeId, mut unit: Box<T::Unit>) -> Box<T::Unit> {
         let node_index = self.node_index[&node];
         assert_eq!(unit.inputs(), self.vertex[node_index].inputs());
         assert_eq!(unit.outputs(), self.vertex[node_index].outputs());
+        unit.set_sample_rate(self.sample_rate);
         std::mem::swap(&mut self.vertex[node_index].unit, &mut unit);
         self.vertex[node_index].changed = self.revision;
         unit
@@ -434,7 +1035,7 @@ impl Net48 {
     /// net.connect(id1, 0, id2, 0);
     /// net.pipe_input(id1);
     /// net.pipe_output(id2);
-    /// net.check();
+    /// net.check().unwrap();
     /// ```
     pub fn connect(
         &mut self,
@@ -449,88 +1050,294 @@ impl Net48 {
         self.connect_index(source_index, source_port, target_index, target_port);
     }
 
-    /// Disconnect `node` input `port`, replacing it with zero input.
+    /// Connect the given unit output (`source`, `source_port`)
+    /// to the given unit input (`target`, `target_port`), scaling the signal by `gain`.
+    /// Negative gains invert the phase. Mix levels can be set this way
+    /// without inserting extra multiplier nodes.
     ///
-    /// ### Example
+    /// ### Example (Phase Cancellation)
     /// ```
     /// use fundsp::hacker::*;
     /// let mut net = Net64::new(1, 1);
-    /// let id = net.chain(Box::new(pass()));
-    /// assert!(net.filter_mono(1.0) == 1.0);
-    /// net.disconnect(id, 0);
+    /// let id1 = net.push(Box::new(pass()));
+    /// let id2 = net.push(Box::new(pass() + pass()));
+    /// net.connect_input(0, id1, 0);
+    /// net.connect(id1, 0, id2, 0);
+    /// net.connect_with_gain(id1, 0, id2, 1, -1.0);
+    /// net.connect_output(id2, 0, 0);
     /// assert!(net.filter_mono(1.0) == 0.0);
-    /// net.check();
     /// ```
-    pub fn disconnect(&mut self, node: NodeId, port: PortIndex) {
-        let node_index = self.node_index[&node];
-        self.vertex[node_index].source[port].source = Port::Zero;
-        self.invalidate_order();
-    }
-
-    /// Connect the given unit output (`source`, `source_port`)
-    /// to the given unit input (`target`, `target_port`).
-    fn connect_index(
+    pub fn connect_with_gain(
         &mut self,
-        source: NodeIndex,
+        source: NodeId,
         source_port: PortIndex,
-        target: NodeIndex,
+        target: NodeId,
         target_port: PortIndex,
+        gain: f64,
     ) {
-        self.vertex[target].source[target_port] = edge(
-            Port::Local(source, source_port),
-            Port::Local(target, target_port),
-        );
-        self.invalidate_order();
+        self.connect(source, source_port, target, target_port);
+        self.vertex[self.node_index[&target]].source[target_port].gain = gain;
     }
 
-    /// Connect the node input (`target`, `target_port`)
-    /// to the network input `global_input`.
+    /// Set the gain of the edge connected to input `port` of `node`.
+    /// Negative gains invert the phase.
+    pub fn set_edge_gain(&mut self, node: NodeId, port: PortIndex, gain: f64) {
+        let node_index = self.node_index[&node];
+        self.vertex[node_index].source[port].gain = gain;
+    }
+
+    /// Gain of the edge connected to input `port` of `node`.
+    pub fn edge_gain(&self, node: NodeId, port: PortIndex) -> f64 {
+        self.vertex[self.node_index[&node]].source[port].gain
+    }
+
+    /// Bypass `node` or restore it. A bypassed unit passes its inputs to its outputs:
+    /// input `i` goes to output `i` and any extra outputs are silent.
+    /// The unit keeps processing, and changes are crossfaded over a few milliseconds.
+    /// If the network has a backend, then the change is carried over with the next commit.
     ///
-    /// ### Example (Saw Wave)
+    /// ### Example
     /// ```
     /// use fundsp::hacker::*;
     /// let mut net = Net64::new(1, 1);
-    /// let id = net.push(Box::new(saw()));
-    /// net.connect_input(0, id, 0);
-    /// net.connect_output(id, 0, 0);
-    /// net.check();
+    /// let id = net.chain(Box::new(mul(2.0)));
+    /// net.set_bypass(id, true);
+    /// for _ in 0..1000 {
+    ///     net.filter_mono(1.0);
+    /// }
+    /// assert!(net.filter_mono(1.0) == 1.0);
     /// ```
-    pub fn connect_input(
-        &mut self,
-        global_input: PortIndex,
-        target: NodeId,
-        target_port: PortIndex,
-    ) {
-        let target_index = self.node_index[&target];
-        self.connect_input_index(global_input, target_index, target_port);
+    pub fn set_bypass(&mut self, node: NodeId, bypass: bool) {
+        let node_index = self.node_index[&node];
+        self.vertex[node_index].bypass.bypass = bypass;
     }
 
-    /// Connect the node input (`target`, `target_port`)
-    /// to the network input `global_input`.
-    fn connect_input_index(
-        &mut self,
-        global_input: PortIndex,
-        target: NodeIndex,
-        target_port: PortIndex,
-    ) {
-        self.vertex[target].source[target_port] =
-            edge(Port::Global(global_input), Port::Local(target, target_port));
-        self.invalidate_order();
+    /// Whether `node` is bypassed.
+    pub fn bypass(&self, node: NodeId) -> bool {
+        self.vertex[self.node_index[&node]].bypass.bypass
     }
 
-    /// Pipe global input to node `target`.
-    /// Number of node inputs must match the number of network inputs.
+    /// Mute `node` or unmute it. The outputs of a muted unit are silent.
+    /// The unit keeps processing, and changes are ramped over a few milliseconds.
+    /// If the network has a backend, then the change is carried over with the next commit.
+    /// This is independent of muting by the watchdog.
+    pub fn set_mute(&mut self, node: NodeId, mute: bool) {
+        let node_index = self.node_index[&node];
+        self.vertex[node_index].bypass.mute = mute;
+    }
+
+    /// Whether `node` has been muted with `set_mute`.
+    pub fn mute(&self, node: NodeId) -> bool {
+        self.vertex[self.node_index[&node]].bypass.mute
+    }
+
+    /// Connect the given unit output (`source`, `source_port`)
+    /// to the given unit input (`target`, `target_port`) with a feedback edge.
+    /// The input receives the output from the previous sample, so feedback edges
+    /// may close cycles and a unit may be connected to itself.
+    /// Networks that contain feedback edges are processed one sample at a time.
     ///
-    /// ### Example (Stereo Filter)
+    /// ### Example (Recursive Filter)
     /// ```
     /// use fundsp::hacker::*;
-    /// let mut net = Net64::new(2, 2);
-    /// let id = net.push(Box::new(peak_hz(1000.0, 1.0) | peak_hz(1000.0, 1.0)));
-    /// net.pipe_input(id);
-    /// net.pipe_output(id);
-    /// net.check();
+    /// // y[n] = x[n] + 0.5 * y[n - 1].
+    /// let mut net = Net64::new(1, 1);
+    /// let sum = net.push(Box::new(pass() + pass()));
+    /// let gain = net.push(Box::new(mul(0.5)));
+    /// net.connect_input(0, sum, 0);
+    /// net.connect(sum, 0, gain, 0);
+    /// net.connect_feedback(gain, 0, sum, 1);
+    /// net.connect_output(sum, 0, 0);
+    /// assert!(net.filter_mono(1.0) == 1.0);
+    /// assert!(net.filter_mono(0.0) == 0.5);
+    /// assert!(net.filter_mono(0.0) == 0.25);
     /// ```
-    pub fn pipe_input(&mut self, target: NodeId) {
+    pub fn connect_feedback(
+        &mut self,
+        source: NodeId,
+        source_port: PortIndex,
+        target: NodeId,
+        target_port: PortIndex,
+    ) {
+        let source_index = self.node_index[&source];
+        let target_index = self.node_index[&target];
+        self.connect_index(source_index, source_port, target_index, target_port);
+        self.vertex[target_index].source[target_port].feedback = true;
+    }
+
+    /// Check whether connecting unit output (`source`, `source_port`)
+    /// to unit input (`target`, `target_port`) would mismatch port kinds.
+    /// Graph editors can call this to warn about a connection before making it.
+    /// Returns the mismatch, if any.
+    ///
+    /// ### Example (Trigger Connected To Cutoff)
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let mut net = Net64::new(0, 1);
+    /// let id1 = net.push(Box::new(port_kind(PortKind::Trigger, impulse_train(4.0))));
+    /// let id2 = net.push(Box::new(lowpass()));
+    /// assert!(net.port_mismatch(id1, 0, id2, 0).is_none());
+    /// assert!(net.port_mismatch(id1, 0, id2, 1).is_some());
+    /// ```
+    pub fn port_mismatch(
+        &self,
+        source: NodeId,
+        source_port: PortIndex,
+        target: NodeId,
+        target_port: PortIndex,
+    ) -> Option<PortMismatch> {
+        let source_kind = self.vertex[self.node_index[&source]]
+            .unit
+            .output_kind(source_port);
+        let target_kind = self.vertex[self.node_index[&target]]
+            .unit
+            .input_kind(target_port);
+        if source_kind.is_compatible(target_kind) {
+            None
+        } else {
+            Some(PortMismatch {
+                source,
+                source_port,
+                source_kind,
+                target,
+                target_port,
+                target_kind,
+            })
+        }
+    }
+
+    /// List connections between units that mismatch port kinds.
+    pub fn port_mismatches(&self) -> Vec<PortMismatch> {
+        let mut mismatches = Vec::new();
+        for vertex in self.vertex.iter() {
+            for edge in vertex.source.iter() {
+                if let (Port::Local(source, source_port), Port::Local(_, target_port)) =
+                    (edge.source, edge.target)
+                {
+                    if let Some(mismatch) = self.port_mismatch(
+                        self.vertex[source].id,
+                        source_port,
+                        vertex.id,
+                        target_port,
+                    ) {
+                        mismatches.push(mismatch);
+                    }
+                }
+            }
+        }
+        mismatches
+    }
+
+    /// Check whether the declared output channel layout of `source`
+    /// mismatches the declared input channel layout of `target`.
+    /// Returns the mismatched (output, input) layouts, if any.
+    ///
+    /// ### Example (5.1 Source Into A Stereo Effect)
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let mut net = Net64::new(0, 2);
+    /// let id1 = net.push(Box::new(channel_layout(ChannelLayout::Surround51, multisplit::<U1, U6>())));
+    /// let id2 = net.push(Box::new(downmix::<U2, U1>()));
+    /// let id3 = net.push(Box::new(downmix::<U6, U2>()));
+    /// assert!(net.layout_mismatch(id1, id2).is_some());
+    /// assert!(net.layout_mismatch(id1, id3).is_none());
+    /// ```
+    pub fn layout_mismatch(
+        &self,
+        source: NodeId,
+        target: NodeId,
+    ) -> Option<(ChannelLayout, ChannelLayout)> {
+        let output = self.vertex[self.node_index[&source]].unit.output_layout();
+        let input = self.vertex[self.node_index[&target]].unit.input_layout();
+        match (output, input) {
+            (Some(output), Some(input)) if output != input => Some((output, input)),
+            _ => None,
+        }
+    }
+
+    /// Disconnect `node` input `port`, replacing it with zero input.
+    ///
+    /// ### Example
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let mut net = Net64::new(1, 1);
+    /// let id = net.chain(Box::new(pass()));
+    /// assert!(net.filter_mono(1.0) == 1.0);
+    /// net.disconnect(id, 0);
+    /// assert!(net.filter_mono(1.0) == 0.0);
+    /// net.check().unwrap();
+    /// ```
+    pub fn disconnect(&mut self, node: NodeId, port: PortIndex) {
+        let node_index = self.node_index[&node];
+        self.vertex[node_index].source[port].source = Port::Zero;
+        self.vertex[node_index].source[port].feedback = false;
+        self.invalidate_order();
+    }
+
+    /// Connect the given unit output (`source`, `source_port`)
+    /// to the given unit input (`target`, `target_port`).
+    fn connect_index(
+        &mut self,
+        source: NodeIndex,
+        source_port: PortIndex,
+        target: NodeIndex,
+        target_port: PortIndex,
+    ) {
+        self.vertex[target].source[target_port] = edge(
+            Port::Local(source, source_port),
+            Port::Local(target, target_port),
+        );
+        self.invalidate_order();
+    }
+
+    /// Connect the node input (`target`, `target_port`)
+    /// to the network input `global_input`.
+    ///
+    /// ### Example (Saw Wave)
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let mut net = Net64::new(1, 1);
+    /// let id = net.push(Box::new(saw()));
+    /// net.connect_input(0, id, 0);
+    /// net.connect_output(id, 0, 0);
+    /// net.check().unwrap();
+    /// ```
+    pub fn connect_input(
+        &mut self,
+        global_input: PortIndex,
+        target: NodeId,
+        target_port: PortIndex,
+    ) {
+        let target_index = self.node_index[&target];
+        self.connect_input_index(global_input, target_index, target_port);
+    }
+
+    /// Connect the node input (`target`, `target_port`)
+    /// to the network input `global_input`.
+    fn connect_input_index(
+        &mut self,
+        global_input: PortIndex,
+        target: NodeIndex,
+        target_port: PortIndex,
+    ) {
+        self.vertex[target].source[target_port] =
+            edge(Port::Global(global_input), Port::Local(target, target_port));
+        self.invalidate_order();
+    }
+
+    /// Pipe global input to node `target`.
+    /// Number of node inputs must match the number of network inputs.
+    ///
+    /// ### Example (Stereo Filter)
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let mut net = Net64::new(2, 2);
+    /// let id = net.push(Box::new(peak_hz(1000.0, 1.0) | peak_hz(1000.0, 1.0)));
+    /// net.pipe_input(id);
+    /// net.pipe_output(id);
+    /// net.check().unwrap();
+    /// ```
+    pub fn pipe_input(&mut self, target: NodeId) {
         let target_index = self.node_index[&target];
         assert_eq!(self.vertex[target_index].inputs(), self.inputs());
         for i in 0..self.inputs() {
@@ -582,7 +1389,7 @@ impl Net48 {
     /// let id = net.push(Box::new(multipass() & reverb_stereo(10.0, 1.0)));
     /// net.pipe_input(id);
     /// net.pipe_output(id);
-    /// net.check();
+    /// net.check().unwrap();
     /// ```
     pub fn pipe_output(&mut self, source: NodeId) {
         let source_index = self.node_index[&source];
@@ -602,7 +1409,7 @@ impl Net48 {
     /// let mut net = Net32::new(2, 2);
     /// net.pass_through(0, 0);
     /// net.pass_through(1, 1);
-    /// net.check();
+    /// net.check().unwrap();
     /// ```
     pub fn pass_through(&mut self, input: PortIndex, output: PortIndex) {
         self.output_edge[output] = edge(Port::Global(input), Port::Global(output));
@@ -611,6 +1418,7 @@ impl Net48 {
 
     /// Connect `source` node outputs to `target` node inputs.
     /// The number of outputs in `source` and number of inputs in `target` must match.
+    /// If both declare a channel layout, then the layouts must match as well.
     ///
     /// ### Example (Panned Sine Wave)
     /// ```
@@ -620,15 +1428,27 @@ impl Net48 {
     /// let id2 = net.push(Box::new(pan(0.0)));
     /// net.pipe(id1, id2);
     /// net.pipe_output(id2);
-    /// net.check();
+    /// net.check().unwrap();
     /// ```
     pub fn pipe(&mut self, source: NodeId, target: NodeId) {
         let source_index = self.node_index[&source];
         let target_index = self.node_index[&target];
         assert_eq!(
             self.vertex[source_index].outputs(),
-            self.vertex[target_index].inputs()
+            self.vertex[target_index].inputs(),
+            "Pipe: mismatched connectivity from {} to {}.",
+            self.describe(source_index),
+            self.describe(target_index)
         );
+        if let Some((output, input)) = self.layout_mismatch(source, target) {
+            panic!(
+                "Pipe: mismatched channel layouts ({} outputs of {} versus {} inputs of {}).",
+                output,
+                self.describe(source_index),
+                input,
+                self.describe(target_index)
+            );
+        }
         for channel in 0..self.vertex[target_index].inputs() {
             self.vertex[target_index].source[channel] = edge(
                 Port::Local(source_index, channel),
@@ -638,6 +1458,59 @@ impl Net48 {
         self.invalidate_order();
     }
 
+    /// Change the number of global inputs to `inputs`. New inputs are not connected.
+    /// Connections from removed inputs are replaced with zeros.
+    /// This is useful for adapting a network when the number of device channels changes.
+    /// If the network has a backend, then the change takes effect there with the next commit.
+    /// The backend switches to a version with a different number of inputs or outputs
+    /// without crossfading and reallocates its buffers when it does.
+    ///
+    /// ### Example
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let mut net = Net64::new(1, 1);
+    /// net.chain(Box::new(mul(2.0)));
+    /// net.set_inputs(2);
+    /// assert!(net.inputs() == 2);
+    /// let mut output = [0.0];
+    /// net.tick(&[1.0, 0.0], &mut output);
+    /// assert!(output[0] == 2.0);
+    /// ```
+    pub fn set_inputs(&mut self, inputs: usize) {
+        let disconnect_removed = |source: &mut Edge| {
+            if let Port::Global(port) = source.source {
+                if port >= inputs {
+                    *source = edge(Port::Zero, source.target);
+                }
+            }
+        };
+        for vertex in self.vertex.iter_mut() {
+            vertex.source.iter_mut().for_each(disconnect_removed);
+        }
+        self.output_edge.iter_mut().for_each(disconnect_removed);
+        self.input.resize(inputs);
+        self.tick_input.resize(inputs, T::zero());
+        if self.metering {
+            self.input_meter.resize_with(inputs, LevelMeter::new);
+        }
+        self.invalidate_order();
+    }
+
+    /// Change the number of global outputs to `outputs`. New outputs are silent.
+    /// This is useful for adapting a network when the number of device channels changes.
+    /// If the network has a backend, then the change takes effect there with the next commit,
+    /// as with `set_inputs`.
+    pub fn set_outputs(&mut self, outputs: usize) {
+        self.output_edge.truncate(outputs);
+        for channel in self.output_edge.len()..outputs {
+            self.output_edge
+                .push(edge(Port::Zero, Port::Global(channel)));
+        }
+        self.output.resize(outputs);
+        self.tick_output.resize(outputs, T::zero());
+        self.invalidate_order();
+    }
+
     /// Number of nodes in the network.
     pub fn size(&self) -> usize {
         self.vertex.len()
@@ -655,9 +1528,9 @@ impl Net48 {
     /// let mut net = Net32::new(1, 1);
     /// net.chain(Box::new(lowpass_hz(2000.0, 1.0)));
     /// net.chain(Box::new(highpass_hz(1000.0, 1.0)));
-    /// net.check();
+    /// net.check().unwrap();
     /// ```
-    pub fn chain(&mut self, unit: Box<dyn AudioUnit48>) -> NodeId {
+    pub fn chain(&mut self, unit: Box<T::Unit>) -> NodeId {
         let unit_inputs = unit.inputs();
         let unit_outputs = unit.outputs();
         let id = self.push(unit);
@@ -676,208 +1549,1420 @@ impl Net48 {
     }
 
     /// Access node.
-    pub fn node(&self, node: NodeId) -> &dyn AudioUnit48 {
+    pub fn node(&self, node: NodeId) -> &T::Unit {
         &*self.vertex[self.node_index[&node]].unit
     }
 
     /// Access mutable node. Note that any changes made via this method
     /// are not accounted in the backend. This can be used to, e.g.,
     /// query for frequency responses.
-    pub fn node_mut(&mut self, node: NodeId) -> &mut dyn AudioUnit48 {
+    pub fn node_mut(&mut self, node: NodeId) -> &mut T::Unit {
         &mut *self.vertex[self.node_index[&node]].unit
     }
 
+    /// Set the name of `node`. Names can be used to look up nodes with `node_by_name`.
+    /// Names should be unique: if several nodes have the same name,
+    /// lookup returns one of them.
+    ///
+    /// ### Example
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let mut net = Net64::new(1, 1);
+    /// let id = net.chain(Box::new(lowpass_hz(1000.0, 1.0)));
+    /// net.set_name(id, "filter");
+    /// assert!(net.node_by_name("filter") == Some(id));
+    /// assert!(net.name(id) == Some("filter"));
+    /// ```
+    pub fn set_name(&mut self, node: NodeId, name: &str) {
+        self.vertex[self.node_index[&node]].name = Some(name.to_string());
+    }
+
+    /// Remove the name of `node`.
+    pub fn clear_name(&mut self, node: NodeId) {
+        self.vertex[self.node_index[&node]].name = None;
+    }
+
+    /// Name of `node`, if any.
+    pub fn name(&self, node: NodeId) -> Option<&str> {
+        self.vertex[self.node_index[&node]].name.as_deref()
+    }
+
+    /// Find the node with `name`, if any.
+    pub fn node_by_name(&self, name: &str) -> Option<NodeId> {
+        self.vertex
+            .iter()
+            .find(|vertex| vertex.name.as_deref() == Some(name))
+            .map(|vertex| vertex.id)
+    }
+
+    /// Describe the network structure in the Graphviz DOT language.
+    /// Units are labeled with their names, if any, or their IDs, and show their input ports
+    /// on top and output ports at the bottom. Feedback edges are dashed
+    /// and edges with non-unity gains are labeled with the gain.
+    /// The result can be rendered with, for example, `dot -Tsvg`.
+    ///
+    /// ### Example
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let mut net = Net64::new(1, 1);
+    /// let id = net.chain(Box::new(lowpass_hz(1000.0, 1.0)));
+    /// net.set_name(id, "filter");
+    /// let dot = net.to_dot();
+    /// assert!(dot.starts_with("digraph"));
+    /// assert!(dot.contains("filter"));
+    /// ```
+    pub fn to_dot(&self) -> String {
+        use std::fmt::Write;
+        /// Record label with the given ports on top, title and ports at the bottom.
+        fn record(top: (&str, usize), title: &str, bottom: (&str, usize)) -> String {
+            let ports = |(prefix, count): (&str, usize)| {
+                (0..count)
+                    .map(|i| format!("<{}{}> {}", prefix, i, i))
+                    .collect::<Vec<_>>()
+                    .join("|")
+            };
+            let mut label = String::new();
+            if top.1 > 0 {
+                label.push_str(&format!("{{{}}}|", ports(top)));
+            }
+            // Escape the title for a quoted record label.
+            for c in title.chars() {
+                match c {
+                    '\\' | '"' | '{' | '}' | '|' | '<' | '>' => label.push('\\'),
+                    _ => (),
+                }
+                label.push(c);
+            }
+            if bottom.1 > 0 {
+                label.push_str(&format!("|{{{}}}", ports(bottom)));
+            }
+            format!("{{{}}}", label)
+        }
+        let mut dot = String::from("digraph net {\n    node [shape=record];\n");
+        if self.inputs() > 0 {
+            let _ = writeln!(
+                dot,
+                "    input [label=\"{}\"];",
+                record(("", 0), "input", ("o", self.inputs()))
+            );
+        }
+        if self.outputs() > 0 {
+            let _ = writeln!(
+                dot,
+                "    output [label=\"{}\"];",
+                record(("i", self.outputs()), "output", ("", 0))
+            );
+        }
+        for (index, vertex) in self.vertex.iter().enumerate() {
+            let _ = writeln!(
+                dot,
+                "    n{} [label=\"{}\"];",
+                index,
+                record(
+                    ("i", vertex.inputs()),
+                    &vertex
+                        .name
+                        .clone()
+                        .unwrap_or_else(|| format!("{:?}", vertex.id)),
+                    ("o", vertex.outputs())
+                )
+            );
+        }
+        let edges = self
+            .vertex
+            .iter()
+            .enumerate()
+            .flat_map(|(index, vertex)| {
+                vertex
+                    .source
+                    .iter()
+                    .enumerate()
+                    .map(move |(port, edge)| (format!("n{}:i{}", index, port), *edge))
+            })
+            .chain(
+                self.output_edge
+                    .iter()
+                    .enumerate()
+                    .map(|(port, edge)| (format!("output:i{}", port), *edge)),
+            );
+        for (target, edge) in edges {
+            let source = match edge.source {
+                Port::Local(index, port) => format!("n{}:o{}", index, port),
+                Port::Global(port) => format!("input:o{}", port),
+                Port::Zero => continue,
+            };
+            let mut attributes = Vec::new();
+            if edge.feedback {
+                attributes.push(String::from("style=dashed"));
+            }
+            if edge.gain != 1.0 {
+                attributes.push(format!("label=\"{}\"", edge.gain));
+            }
+            if attributes.is_empty() {
+                let _ = writeln!(dot, "    {} -> {};", source, target);
+            } else {
+                let _ = writeln!(
+                    dot,
+                    "    {} -> {} [{}];",
+                    source,
+                    target,
+                    attributes.join(", ")
+                );
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Describe the node at `index` for messages: its name, if any, or its ID.
+    fn describe(&self, index: NodeIndex) -> String {
+        match &self.vertex[index].name {
+            Some(name) => format!("\"{}\"", name),
+            None => format!("{:?}", self.vertex[index].id),
+        }
+    }
+
     /// Compute and store node order for this network.
+    /// Vertices whose outputs reach neither a global output nor a unit with side effects
+    /// are left out of the order and are not processed.
+    /// If a cycle is detected, then the order is left empty and the error is recorded.
     fn determine_order(&mut self) {
-        for vertex in self.vertex.iter_mut() {
-            vertex.update_source_vertex();
+        // Storage is reused, so ordering does not allocate if there is enough capacity.
+        let mut order = std::mem::take(&mut self.spare_order);
+        let mut inputs_left = std::mem::take(&mut self.scratch_count);
+        let mut start = std::mem::take(&mut self.scratch_start);
+        let mut successor = std::mem::take(&mut self.scratch_successor);
+        order.clear();
+        let ordered =
+            self.determine_order_in(&mut order, &mut inputs_left, &mut start, &mut successor);
+        self.scratch_count = inputs_left;
+        self.scratch_start = start;
+        self.scratch_successor = successor;
+        if !ordered {
+            order.clear();
+            self.error = Some(NetError::Cycle);
+            self.order = Some(order);
+            return;
         }
-        let mut order = Vec::new();
-        if !self.determine_order_in(&mut order) {
-            panic!("Cycle detected");
+        self.error = None;
+        self.has_feedback = self
+            .vertex
+            .iter()
+            .any(|vertex| vertex.source.iter().any(|edge| edge.feedback));
+        let mut flag = std::mem::take(&mut self.scratch_flag);
+        self.live_vertices(&order, &mut flag);
+        order.retain(|&index| flag[index]);
+        self.scratch_flag = flag;
+        #[cfg(feature = "rayon")]
+        if self.parallel {
+            self.level = self.determine_levels(&order);
+        }
+        self.assign_slots(&order);
+        if let Some(tracer) = &self.tracer {
+            for &index in order.iter() {
+                tracer.record(Some(self.vertex[index].id), TraceKind::Order);
+            }
         }
         self.order = Some(order);
     }
 
-    /// Determine node order in the supplied vector. Returns true if successful, false
-    /// if a cycle was detected.
-    fn determine_order_in(&self, order: &mut Vec<NodeIndex>) -> bool {
-        let mut vertices_left = self.vertex.len();
-        let mut vertex_left = vec![true; self.vertex.len()];
-        // Note about contents of the edge vector.
-        // Each node input appears there exactly once.
-        // Sources, however, are not unique or guaranteed to appear.
-        let mut all_edges: Vec<Edge> = Vec::new();
-        for vertex in self.vertex.iter() {
-            for edge in &vertex.source {
-                all_edges.push(*edge);
+    /// Assign buffer arena slots to vertex outputs and gained inputs in processing `order`.
+    /// The outputs of a vertex are released for reuse once all of its targets have been processed.
+    /// Slots are never shared by the inputs and outputs of a vertex.
+    /// In parallel processing, units on the same level run simultaneously, so slots are not reused.
+    fn assign_slots(&mut self, order: &[NodeIndex]) {
+        fn next_slot(free: &mut Vec<usize>, slots: &mut usize) -> usize {
+            free.pop().unwrap_or_else(|| {
+                *slots += 1;
+                *slots - 1
+            })
+        }
+        #[cfg(feature = "rayon")]
+        let reuse = !self.parallel;
+        #[cfg(not(feature = "rayon"))]
+        let reuse = true;
+        let mut last = std::mem::take(&mut self.scratch_count);
+        let mut free = std::mem::take(&mut self.scratch_successor);
+        // Find the last position in the order where the outputs of each vertex are read.
+        last.clear();
+        last.resize(self.vertex.len(), 0);
+        for (position, &index) in order.iter().enumerate() {
+            last[index] = max(last[index], position);
+            for edge in self.vertex[index].source.iter() {
+                if let Port::Local(source, _) = edge.source {
+                    last[source] = max(last[source], position);
+                }
             }
         }
-
-        let mut inputs_left = vec![0; self.vertex.len()];
-        for i in 0..inputs_left.len() {
-            inputs_left[i] = self.vertex[i].unit.inputs();
-            if inputs_left[i] == 0 {
-                vertex_left[i] = false;
-                order.push(i);
-                vertices_left -= 1;
+        // Global outputs are read after all units have been processed.
+        for edge in self.output_edge.iter() {
+            if let Port::Local(source, _) = edge.source {
+                last[source] = usize::MAX;
             }
         }
-
-        // Start from network inputs.
-        for (_, edge) in all_edges.iter().enumerate() {
-            if let (Port::Global(_) | Port::Zero, Port::Local(vertex, _)) =
-                (edge.source, edge.target)
-            {
-                if vertex_left[vertex] {
-                    inputs_left[vertex] -= 1;
-                    if inputs_left[vertex] == 0 {
-                        vertex_left[vertex] = false;
-                        order.push(vertex);
-                        vertices_left -= 1;
+        free.clear();
+        // Slot 0 holds zeros.
+        let mut slots = 1;
+        for (position, &index) in order.iter().enumerate() {
+            // Every input gets a gain slot, so that edge gains can be changed without reordering.
+            let vertex = &mut self.vertex[index];
+            for slot in vertex.gained_slot.iter_mut() {
+                *slot = next_slot(&mut free, &mut slots);
+            }
+            for slot in vertex.output_slot.iter_mut() {
+                *slot = next_slot(&mut free, &mut slots);
+            }
+            if !reuse {
+                // Parallel processing collects slices per vertex, so reserve them here.
+                #[cfg(feature = "rayon")]
+                {
+                    if vertex.slice.capacity() < vertex.inputs() {
+                        vertex.slice = Slice::with_capacity(vertex.inputs());
+                    }
+                    if vertex.output_slice.capacity() < vertex.outputs() {
+                        vertex.output_slice = Slice::with_capacity(vertex.outputs());
+                    }
+                }
+                continue;
+            }
+            // Gain slots are needed only while the vertex is processed.
+            free.extend_from_slice(&vertex.gained_slot);
+            for channel in 0..self.vertex[index].inputs() {
+                if let Port::Local(source, _) = self.vertex[index].source[channel].source {
+                    if last[source] == position {
+                        // Mark the source as released.
+                        last[source] = usize::MAX;
+                        free.extend_from_slice(&self.vertex[source].output_slot);
                     }
                 }
             }
+            if last[index] == position {
+                last[index] = usize::MAX;
+                free.extend_from_slice(&self.vertex[index].output_slot);
+            }
         }
-        while vertices_left > 0 {
-            let mut progress = false;
-            for (_i, edge) in all_edges.iter().enumerate() {
-                if let (Port::Local(source, _), Port::Local(target, _)) = (edge.source, edge.target)
-                {
-                    if !vertex_left[source] && vertex_left[target] {
-                        progress = true;
-                        inputs_left[target] -= 1;
-                        if inputs_left[target] == 0 {
-                            vertex_left[target] = false;
-                            order.push(target);
-                            vertices_left -= 1;
+        self.arena.resize(slots * MAX_BUFFER_SIZE, T::zero());
+        self.scratch_count = last;
+        self.scratch_successor = free;
+    }
+
+    /// Find vertices that contribute to global outputs or have side effects
+    /// and flag them in `live`. The `order` of vertices must be topological.
+    fn live_vertices(&self, order: &[NodeIndex], live: &mut Vec<bool>) {
+        live.clear();
+        live.extend(
+            self.vertex
+                .iter()
+                .map(|vertex| vertex.unit.has_side_effects()),
+        );
+        for edge in self.output_edge.iter() {
+            if let Port::Local(index, _) = edge.source {
+                live[index] = true;
+            }
+        }
+        // Targets appear after their sources, so liveness propagates in reverse order.
+        // Feedback edges may point backwards, so repeat until nothing changes.
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for &index in order.iter().rev() {
+                if live[index] {
+                    for edge in self.vertex[index].source.iter() {
+                        if let Port::Local(source, _) = edge.source {
+                            if !live[source] {
+                                live[source] = true;
+                                changed = true;
+                            }
                         }
                     }
                 }
             }
-            if !progress {
-                return false;
+        }
+    }
+
+    /// Group the vertices of a topological `order` by dependency level.
+    /// Each vertex is placed one level after the deepest of its sources.
+    #[cfg(feature = "rayon")]
+    fn determine_levels(&self, order: &[NodeIndex]) -> Vec<Vec<NodeIndex>> {
+        let mut depth = vec![0; self.vertex.len()];
+        let mut level: Vec<Vec<NodeIndex>> = Vec::new();
+        for &index in order.iter() {
+            let d = self.vertex[index]
+                .source
+                .iter()
+                .filter_map(|edge| match edge.source {
+                    Port::Local(source, _) if !edge.feedback => Some(depth[source] + 1),
+                    _ => None,
+                })
+                .max()
+                .unwrap_or(0);
+            depth[index] = d;
+            if level.len() <= d {
+                level.resize(d + 1, Vec::new());
             }
+            level[d].push(index);
         }
-        true
+        level
     }
 
-    /// Wrap arbitrary unit in a network.
+    /// Process the vertices of the order level by level,
+    /// processing the vertices of each level in parallel.
+    #[cfg(feature = "rayon")]
+    fn process_levels(&mut self, size: usize, input: &[&[T]], timed: bool) {
+        use rayon::prelude::*;
+        let vertex = VertexPointer(self.vertex.as_mut_ptr());
+        let arena = VertexPointer(self.arena.as_mut_ptr());
+        for level in self.level.iter() {
+            // Safety: vertices on the same level do not depend on each other,
+            // so each vertex is written by one thread only and sources are only read.
+            // Arena slots are not reused in parallel processing.
+            let process = |&index: &NodeIndex| unsafe {
+                Self::process_vertex(vertex, arena, index, size, input, timed)
+            };
+            if level.len() > 1 {
+                level.par_iter().for_each(process);
+            } else {
+                level.iter().for_each(process);
+            }
+        }
+    }
+
+    /// Process vertex `index`. The caller must guarantee that the vertex is not
+    /// accessed elsewhere and that its sources are not written to during the call.
+    #[cfg(feature = "rayon")]
+    unsafe fn process_vertex(
+        vertices: VertexPointer<Vertex<T>>,
+        arena: VertexPointer<T>,
+        index: NodeIndex,
+        size: usize,
+        input: &[&[T]],
+        timed: bool,
+    ) {
+        let vertex = &mut *vertices.get().add(index);
+        let arena = arena.get();
+        if vertex.is_muted() {
+            for &slot in vertex.output_slot.iter() {
+                arena_mut(arena, slot)[..size].fill(T::zero());
+            }
+            vertex.observe(arena, size);
+            return;
+        }
+        let start = if timed { Some(Instant::now()) } else { None };
+        for channel in 0..vertex.inputs() {
+            let edge = vertex.source[channel];
+            if edge.gain == 1.0 {
+                continue;
+            }
+            let source = match edge.source {
+                Port::Zero => continue,
+                Port::Global(port) => input[port],
+                Port::Local(source, port) => {
+                    arena_ref(arena, (*vertices.get().add(source)).output_slot[port])
+                }
+            };
+            let gain = T::from_f64(edge.gain);
+            for (y, x) in arena_mut(arena, vertex.gained_slot[channel])[..size]
+                .iter_mut()
+                .zip(source[..size].iter())
+            {
+                *y = *x * gain;
+            }
+        }
+        // Parallel processing cannot share the slices of the network,
+        // so inputs and outputs are collected into the slices of the vertex.
+        let vertex_input =
+            vertex
+                .slice
+                .from_iter(vertex.source.iter().enumerate().map(
+                    |(channel, edge)| match edge.source {
+                        Port::Zero => arena_ref(arena, 0),
+                        _ if edge.gain != 1.0 => arena_ref(arena, vertex.gained_slot[channel]),
+                        Port::Global(port) => input[port],
+                        Port::Local(source, port) => {
+                            arena_ref(arena, (*vertices.get().add(source)).output_slot[port])
+                        }
+                    },
+                ));
+        let vertex_output = vertex.output_slice.from_iter_mut(
+            vertex
+                .output_slot
+                .iter()
+                .map(|&slot| arena_mut(arena, slot)),
+        );
+        vertex.unit.process(size, vertex_input, vertex_output);
+        if vertex.bypass.is_active() {
+            vertex.bypass.process(size, vertex_input, vertex_output);
+        }
+        if let Some(start) = start {
+            vertex.cost = start.elapsed().as_secs_f64();
+        }
+        vertex.observe(arena, size);
+    }
+
+    /// Enable or disable parallel processing. When enabled, units that do not
+    /// depend on each other are processed in parallel in a thread pool during block processing.
+    /// This pays off in large networks with many independent units, such as polyphonic patches.
+    /// Parallel processing requires the `rayon` feature: without it, this setting has no effect.
+    /// Networks with feedback edges are always processed serially. Parallel processing
+    /// allocates memory, so it is not suitable for strictly real-time safe contexts.
+    /// The default is disabled.
     ///
-    /// ### Example (Conditional Processing)
+    /// ### Example
     /// ```
     /// use fundsp::hacker::*;
-    /// let mut net = Net64::wrap(Box::new(square_hz(440.0)));
-    /// let add_filter = true;
-    /// if add_filter {
-    ///     net = net >> lowpass_hz(880.0, 1.0);
-    /// }
+    /// let mut net = Net64::wrap(Box::new(noise() >> lowpass_hz(1000.0, 1.0)))
+    ///     + Net64::wrap(Box::new(noise() >> highpass_hz(1000.0, 1.0)));
+    /// net.set_parallel(true);
+    /// assert!(net.is_parallel());
     /// ```
-    pub fn wrap(unit: Box<dyn AudioUnit48>) -> Net48 {
-        let mut net = Net48::new(unit.inputs(), unit.outputs());
-        let id = net.push(unit);
-        if net.inputs() > 0 {
-            net.pipe_input(id);
-        }
-        if net.outputs() > 0 {
-            net.pipe_output(id);
-        }
+    pub fn set_parallel(&mut self, enabled: bool) {
+        self.parallel = enabled;
+        self.invalidate_order();
+    }
+
+    /// Whether parallel processing is enabled.
+    pub fn is_parallel(&self) -> bool {
+        self.parallel
+    }
+
+    /// Determine node order in the supplied vector. Returns true if successful, false
+    /// if a cycle was detected.
+    /// This is Kahn's algorithm, which runs in time linear in the number of vertices and edges.
+    /// The vectors `inputs_left`, `start` and `successor` are scratch space.
+    /// No memory is allocated if they have enough capacity.
+    fn determine_order_in(
+        &self,
+        order: &mut Vec<NodeIndex>,
+        inputs_left: &mut Vec<usize>,
+        start: &mut Vec<usize>,
+        successor: &mut Vec<NodeIndex>,
+    ) -> bool {
+        let vertices = self.vertex.len();
+        // Count the local inputs of each vertex and the local outputs of each vertex.
+        // Each node input has exactly one edge. Feedback edges do not constrain the order.
+        inputs_left.clear();
+        inputs_left.resize(vertices, 0);
+        start.clear();
+        start.resize(vertices + 1, 0);
+        for (index, vertex) in self.vertex.iter().enumerate() {
+            for edge in vertex.source.iter() {
+                if let (false, Port::Local(source, _)) = (edge.feedback, edge.source) {
+                    inputs_left[index] += 1;
+                    start[source + 1] += 1;
+                }
+            }
+        }
+        // Lay out the successors of each vertex contiguously.
+        for index in 0..vertices {
+            start[index + 1] += start[index];
+        }
+        successor.clear();
+        successor.resize(start[vertices], 0);
+        for (index, vertex) in self.vertex.iter().enumerate() {
+            for edge in vertex.source.iter() {
+                if let (false, Port::Local(source, _)) = (edge.feedback, edge.source) {
+                    successor[start[source]] = index;
+                    start[source] += 1;
+                }
+            }
+        }
+        // Each start now points to the end of its range, so shift them back.
+        for index in (0..vertices).rev() {
+            start[index + 1] = start[index];
+        }
+        start[0] = 0;
+
+        // The order vector doubles as the queue of vertices whose sources are all done.
+        let first = order.len();
+        for (index, &count) in inputs_left.iter().enumerate() {
+            if count == 0 {
+                order.push(index);
+            }
+        }
+        let mut next = first;
+        while next < order.len() {
+            let index = order[next];
+            next += 1;
+            for &target in successor[start[index]..start[index + 1]].iter() {
+                inputs_left[target] -= 1;
+                if inputs_left[target] == 0 {
+                    order.push(target);
+                }
+            }
+        }
+        order.len() - first == vertices
+    }
+
+    /// Wrap arbitrary unit in a network.
+    ///
+    /// ### Example (Conditional Processing)
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let mut net = Net64::wrap(Box::new(square_hz(440.0)));
+    /// let add_filter = true;
+    /// if add_filter {
+    ///     net = net >> lowpass_hz(880.0, 1.0);
+    /// }
+    /// ```
+    pub fn wrap(unit: Box<T::Unit>) -> Net<T> {
+        let mut net = Net::new(unit.inputs(), unit.outputs());
+        let id = net.push(unit);
+        if net.inputs() > 0 {
+            net.pipe_input(id);
+        }
+        if net.outputs() > 0 {
+            net.pipe_output(id);
+        }
+        net
+    }
+
+    /// Create a network that outputs a scalar value on all channels.
+    ///
+    /// ### Example
+    /// ```
+    /// use fundsp::hacker32::*;
+    /// let mut net = Net32::scalar(2, 1.0);
+    /// assert!(net.get_stereo() == (1.0, 1.0));
+    /// ```
+    pub fn scalar(channels: usize, scalar: T) -> Net<T> {
+        let mut net = Net::new(0, channels);
+        let id = net.push(T::boxed(super::prelude::dc(scalar)));
+        for i in 0..channels {
+            net.connect_output(id, 0, i);
+        }
         net
     }
 
-    /// Create a network that outputs a scalar value on all channels.
-    ///
-    /// ### Example
-    /// ```
-    /// use fundsp::hacker32::*;
-    /// let mut net = Net32::scalar(2, 1.0);
-    /// assert!(net.get_stereo() == (1.0, 1.0));
-    /// ```
-    pub fn scalar(channels: usize, scalar: f48) -> Net48 {
-        let mut net = Net48::new(0, channels);
-        let id = net.push(Box::new(super::prelude::dc(scalar)));
-        for i in 0..channels {
-            net.connect_output(id, 0, i);
+    /// Route signals from global `input` through all vertices at `frequency` Hz.
+    /// Returns output signals of each vertex.
+    fn route_vertices(&mut self, input: &SignalFrame, frequency: f64) -> Vec<SignalFrame> {
+        let mut inner_signal: Vec<SignalFrame> = vec![];
+        for vertex in self.vertex.iter() {
+            inner_signal.push(new_signal_frame(vertex.unit.outputs()));
+        }
+        // Dead branches are included in the analysis.
+        // If there is a cycle, then all signals are unknown.
+        let mut order = Vec::new();
+        if !self.determine_order_in(
+            &mut order,
+            &mut Vec::new(),
+            &mut Vec::new(),
+            &mut Vec::new(),
+        ) {
+            return inner_signal;
+        }
+        for unit_index in order {
+            let mut input_signal = new_signal_frame(self.vertex[unit_index].unit.inputs());
+            for channel in 0..self.vertex[unit_index].unit.inputs() {
+                if self.vertex[unit_index].source[channel].feedback {
+                    // Signals arriving via feedback edges are not analyzed.
+                    input_signal[channel] = Signal::Unknown;
+                    continue;
+                }
+                let edge = self.vertex[unit_index].source[channel];
+                match edge.source {
+                    Port::Local(j, port) => {
+                        input_signal[channel] = inner_signal[j][port].scale(edge.gain)
+                    }
+                    Port::Global(j) => input_signal[channel] = input[j].scale(edge.gain),
+                    Port::Zero => input_signal[channel] = Signal::Value(0.0),
+                }
+            }
+            inner_signal[unit_index] = self.vertex[unit_index].unit.route(&input_signal, frequency);
+        }
+        inner_signal
+    }
+
+    /// Number of inputs and global outputs sourced from output `port` of vertex `index`.
+    fn consumers(&self, index: NodeIndex, port: PortIndex) -> usize {
+        let source = Port::Local(index, port);
+        self.vertex
+            .iter()
+            .flat_map(|vertex| vertex.source.iter())
+            .chain(self.output_edge.iter())
+            .filter(|edge| edge.source == source)
+            .count()
+    }
+
+    /// Optimize the network by folding constants and fusing gains and offsets.
+    /// Nodes with constant outputs, such as `dc(2.0) >> mul(3.0)`, are replaced with `dc` nodes
+    /// and constant nodes whose outputs are not used are removed.
+    /// Chains of memoryless mono nodes that apply a gain and an offset,
+    /// such as `mul` and `add`, are fused into single nodes, and identity nodes are removed.
+    /// Nodes with side effects, such as monitors, are left intact.
+    /// Constants are derived with signal flow analysis. Folded nodes output their
+    /// steady state values from the start and ignore any later edits made to them.
+    ///
+    /// ### Example (Folding Constants)
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let mut net = Net64::new(1, 1);
+    /// let id1 = net.push(Box::new(dc(2.0)));
+    /// let id2 = net.push(Box::new(mul(3.0)));
+    /// let id3 = net.push(Box::new(add(1.0)));
+    /// let id4 = net.push(Box::new(mul(0.5)));
+    /// net.connect(id1, 0, id2, 0);
+    /// net.pipe_input(id3);
+    /// net.connect(id3, 0, id4, 0);
+    /// let id5 = net.push(Box::new(pass() * pass()));
+    /// net.connect(id2, 0, id5, 0);
+    /// net.connect(id4, 0, id5, 1);
+    /// net.pipe_output(id5);
+    /// assert!(net.size() == 5);
+    /// net.optimize();
+    /// assert!(net.size() == 3);
+    /// assert!(net.filter_mono(1.0) == 6.0);
+    /// net.check().unwrap();
+    /// ```
+    pub fn optimize(&mut self) {
+        self.fold_constants();
+        self.fuse_affine();
+    }
+
+    /// Replace constant outputs that feed non-constant nodes or global outputs with `dc` nodes.
+    /// Then remove constant nodes whose outputs are not used.
+    fn fold_constants(&mut self) {
+        let mut input = new_signal_frame(self.inputs());
+        input.fill(Signal::Latency(0.0));
+        let signal = self.route_vertices(&input, 0.0);
+        let constant: Vec<bool> = (0..self.size())
+            .map(|index| {
+                let vertex = &self.vertex[index];
+                vertex.outputs() > 0
+                    && !vertex.unit.has_side_effects()
+                    && signal[index].iter().all(|x| matches!(x, Signal::Value(_)))
+            })
+            .collect();
+        // Plain constants are kept as they are.
+        let fold: Vec<bool> = (0..self.size())
+            .map(|index| {
+                constant[index]
+                    && (self.vertex[index].inputs() > 0
+                        || self.vertex[index].unit.get_id()
+                            != <Constant<super::prelude::U1, T> as AudioNode>::ID)
+            })
+            .collect();
+        let constant_id: Vec<NodeId> = (0..self.size())
+            .filter(|&index| constant[index])
+            .map(|index| self.vertex[index].id)
+            .collect();
+        let mut folded: HashMap<(NodeIndex, PortIndex), NodeIndex> = HashMap::new();
+        let vertices = self.size();
+        for target in 0..=vertices {
+            // Index `vertices` stands for the global outputs.
+            let edges = if target < vertices {
+                if constant[target] {
+                    continue;
+                }
+                self.vertex[target].inputs()
+            } else {
+                self.outputs()
+            };
+            for channel in 0..edges {
+                let source = if target < vertices {
+                    self.vertex[target].source[channel].source
+                } else {
+                    self.output_edge[channel].source
+                };
+                if let Port::Local(index, port) = source {
+                    if index < vertices && fold[index] {
+                        let dc_index = match folded.get(&(index, port)) {
+                            Some(&dc_index) => dc_index,
+                            None => {
+                                let value = match signal[index][port] {
+                                    Signal::Value(value) => value,
+                                    _ => 0.0,
+                                };
+                                let id =
+                                    self.push(T::boxed(super::prelude::dc(T::from_f64(value))));
+                                let dc_index = self.node_index[&id];
+                                folded.insert((index, port), dc_index);
+                                dc_index
+                            }
+                        };
+                        if target < vertices {
+                            self.vertex[target].source[channel].source = Port::Local(dc_index, 0);
+                        } else {
+                            self.output_edge[channel].source = Port::Local(dc_index, 0);
+                        }
+                    }
+                }
+            }
+        }
+        // Remove unused constants until there are none left.
+        let mut removed = true;
+        while removed {
+            removed = false;
+            for &id in constant_id.iter() {
+                if let Some(&index) = self.node_index.get(&id) {
+                    if (0..self.vertex[index].outputs())
+                        .all(|port| self.consumers(index, port) == 0)
+                    {
+                        self.remove(id);
+                        removed = true;
+                    }
+                }
+            }
+        }
+        self.invalidate_order();
+    }
+
+    /// If vertex `index` is a memoryless mono node that applies a gain and an offset,
+    /// return (gain, offset).
+    fn affine(&mut self, index: NodeIndex) -> Option<(f64, f64)> {
+        let unit = &mut self.vertex[index].unit;
+        if unit.inputs() != 1 || unit.outputs() != 1 || unit.has_side_effects() {
+            return None;
+        }
+        let mut input = new_signal_frame(1);
+        input[0] = Signal::Value(0.0);
+        let offset = match unit.route(&input, 0.0)[0] {
+            Signal::Value(offset) => offset,
+            _ => return None,
+        };
+        input[0] = Signal::Value(1.0);
+        let gain = match unit.route(&input, 0.0)[0] {
+            Signal::Value(value) => value - offset,
+            _ => return None,
+        };
+        // Check that the node is linear and has a flat response without latency.
+        input[0] = Signal::Value(-2.0);
+        let tolerance = 1.0e-9 * max(1.0, abs(gain) + abs(offset));
+        match unit.route(&input, 0.0)[0] {
+            Signal::Value(value) if abs(value - (offset - 2.0 * gain)) <= tolerance => (),
+            _ => return None,
+        }
+        for frequency in [10.0, 1000.0, 10000.0] {
+            input[0] = Signal::Response(num_complex::Complex64::new(1.0, 0.0), 0.0);
+            match unit.route(&input, frequency)[0] {
+                Signal::Response(response, latency)
+                    if latency == 0.0
+                        && response.im == 0.0
+                        && abs(response.re - gain) <= tolerance => {}
+                _ => return None,
+            }
+        }
+        Some((gain, offset))
+    }
+
+    /// Fuse chains of memoryless mono nodes that apply a gain and an offset
+    /// and remove identity nodes.
+    fn fuse_affine(&mut self) {
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for index in 0..self.size() {
+                let (gain, offset) = match self.affine(index) {
+                    Some(affine) => affine,
+                    None => continue,
+                };
+                if gain == 1.0 && offset == 0.0 {
+                    self.remove_link(self.vertex[index].id);
+                    changed = true;
+                    break;
+                }
+                if let Port::Local(source, 0) = self.vertex[index].source[0].source {
+                    if self.vertex[index].source[0].feedback || self.consumers(source, 0) != 1 {
+                        continue;
+                    }
+                    if let Some((source_gain, source_offset)) = self.affine(source) {
+                        // The gain of the edge between the nodes is fused as well.
+                        let gain = gain * self.vertex[index].source[0].gain;
+                        let gain2 = gain * source_gain;
+                        let offset2 = gain * source_offset + offset;
+                        let unit: Box<T::Unit> = if offset2 == 0.0 {
+                            T::boxed(super::prelude::mul(T::from_f64(gain2)))
+                        } else if gain2 == 1.0 {
+                            T::boxed(super::prelude::add(T::from_f64(offset2)))
+                        } else {
+                            T::boxed(
+                                super::prelude::mul(T::from_f64(gain2))
+                                    >> super::prelude::add(T::from_f64(offset2)),
+                            )
+                        };
+                        self.replace(self.vertex[index].id, unit);
+                        let input = self.vertex[source].source[0];
+                        self.vertex[index].source[0] =
+                            input.moved(input.source, Port::Local(index, 0));
+                        self.remove(self.vertex[source].id);
+                        changed = true;
+                        break;
+                    }
+                }
+            }
+        }
+        self.invalidate_order();
+    }
+
+    /// Merge subnetworks, that is, units that are networks themselves, into this network.
+    /// Flattening is recursive. Afterwards, all units are evaluated in a single order
+    /// without copying signals across network boundaries.
+    /// Units of subnetworks keep their IDs, names and edge gains.
+    /// The IDs of flattened subnetworks become invalid.
+    ///
+    /// ### Example
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let block = Net64::wrap(Box::new(lowpass_hz(1000.0, 1.0))) >> mul(0.5);
+    /// let mut net = Net64::new(1, 1);
+    /// net.chain(Box::new(block));
+    /// net.chain(Box::new(highpass_hz(100.0, 1.0)));
+    /// assert!(net.size() == 2);
+    /// net.flatten();
+    /// assert!(net.size() == 3);
+    /// net.check().unwrap();
+    /// ```
+    pub fn flatten(&mut self) {
+        while let Some(index) =
+            (0..self.size()).find(|&i| self.vertex[i].unit.as_net_mut().is_some())
+        {
+            let outer_id = self.vertex[index].id;
+            let (inputs, outputs) = (self.vertex[index].inputs(), self.vertex[index].outputs());
+            let mut inner = match self.vertex[index].unit.as_net_mut() {
+                Some(net) => std::mem::replace(net, Net::new(inputs, outputs)),
+                None => unreachable!(),
+            };
+            inner.disambiguate_ids(self);
+            self.adopt_sample_rate(&mut inner);
+            let offset = self.size();
+            let outer_source = self.vertex[index].source.clone();
+            for (i, mut vertex) in inner.vertex.into_iter().enumerate() {
+                let node = offset + i;
+                for port in 0..vertex.inputs() {
+                    let inner_edge = vertex.source[port];
+                    vertex.source[port] = match inner_edge.source {
+                        Port::Local(source, source_port) => inner_edge.moved(
+                            Port::Local(source + offset, source_port),
+                            Port::Local(node, port),
+                        ),
+                        Port::Global(input) => {
+                            let outer = outer_source[input];
+                            let mut moved = inner_edge.moved(outer.source, Port::Local(node, port));
+                            moved.feedback |= outer.feedback;
+                            moved.gain *= outer.gain;
+                            moved
+                        }
+                        Port::Zero => edge(Port::Zero, Port::Local(node, port)),
+                    };
+                }
+                vertex.changed = self.revision;
+                vertex.meter = if self.metering {
+                    (0..vertex.outputs()).map(|_| LevelMeter::new()).collect()
+                } else {
+                    Vec::new()
+                };
+                self.node_index.insert(vertex.id, node);
+                self.vertex.push(vertex);
+            }
+            // Connect consumers of the subnetwork directly to its insides.
+            let resolve = |consumer: Edge| -> Edge {
+                match consumer.source {
+                    Port::Local(source, port) if source == index => {
+                        let output = inner.output_edge[port];
+                        let mut resolved = match output.source {
+                            Port::Local(node, node_port) => consumer
+                                .moved(Port::Local(node + offset, node_port), consumer.target),
+                            Port::Global(input) => {
+                                let outer = outer_source[input];
+                                let mut resolved = consumer.moved(outer.source, consumer.target);
+                                resolved.feedback |= outer.feedback;
+                                resolved.gain *= outer.gain;
+                                resolved
+                            }
+                            Port::Zero => edge(Port::Zero, consumer.target),
+                        };
+                        resolved.gain *= output.gain;
+                        resolved
+                    }
+                    _ => consumer,
+                }
+            };
+            for vertex in self.vertex.iter_mut() {
+                for edge in vertex.source.iter_mut() {
+                    *edge = resolve(*edge);
+                }
+            }
+            for edge in self.output_edge.iter_mut() {
+                *edge = resolve(*edge);
+            }
+            self.remove(outer_id);
+        }
+        self.invalidate_order();
+    }
+
+    /// Insert delays that compensate for latency differences between parallel paths,
+    /// such as a dry path alongside a look-ahead limiter. The inputs of each unit
+    /// and the global outputs are aligned to the path with the most latency.
+    /// Latencies are measured with signal flow analysis and rounded to whole samples.
+    /// Feedback edges and paths with unknown latency are not compensated.
+    /// Latencies depend on the sample rate, so set it before compensating.
+    /// Returns the number of delays inserted.
+    ///
+    /// ### Example (Parallel Limiter)
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let mut net = Net64::new(1, 1);
+    /// let id1 = net.push(Box::new(limiter(0.01)));
+    /// let id2 = net.push(Box::new(pass() + pass()));
+    /// net.connect_input(0, id1, 0);
+    /// net.connect_input(0, id2, 0);
+    /// net.connect(id1, 0, id2, 1);
+    /// net.pipe_output(id2);
+    /// assert!(net.latency() == Some(0.0));
+    /// assert!(net.compensate_latency() == 1);
+    /// assert!(net.latency() == limiter(0.01).latency());
+    /// net.check().unwrap();
+    /// ```
+    pub fn compensate_latency(&mut self) -> usize {
+        let mut order = Vec::new();
+        if !self.determine_order_in(
+            &mut order,
+            &mut Vec::new(),
+            &mut Vec::new(),
+            &mut Vec::new(),
+        ) {
+            return 0;
+        }
+        let mut input = new_signal_frame(self.inputs());
+        for i in 0..self.inputs() {
+            input[i] = Signal::Latency(0.0);
+        }
+        let mut inner_signal: Vec<SignalFrame> = vec![];
+        for vertex in self.vertex.iter() {
+            inner_signal.push(new_signal_frame(vertex.unit.outputs()));
+        }
+        let signal = |inner_signal: &[SignalFrame], edge: Edge| -> Signal {
+            match edge.source {
+                _ if edge.feedback => Signal::Unknown,
+                Port::Local(j, port) => inner_signal[j][port].scale(edge.gain),
+                Port::Global(j) => input[j].scale(edge.gain),
+                Port::Zero => Signal::Value(0.0),
+            }
+        };
+        let mut inserted = 0;
+        for unit_index in order {
+            let mut input_signal = new_signal_frame(self.vertex[unit_index].inputs());
+            for channel in 0..self.vertex[unit_index].inputs() {
+                input_signal[channel] =
+                    signal(&inner_signal, self.vertex[unit_index].source[channel]);
+            }
+            if let Some(target) = max_latency(&input_signal) {
+                for channel in 0..input_signal.len() {
+                    if let Some(samples) = latency_deficit(input_signal[channel], target) {
+                        let source = self.vertex[unit_index].source[channel];
+                        let target_port = Port::Local(unit_index, channel);
+                        let index = self.push_delay(source, samples);
+                        self.vertex[unit_index].source[channel] =
+                            edge(Port::Local(index, 0), target_port);
+                        input_signal[channel] = input_signal[channel].delay(samples);
+                        let mut delayed = new_signal_frame(1);
+                        delayed[0] = input_signal[channel];
+                        inner_signal.push(delayed);
+                        inserted += 1;
+                    }
+                }
+            }
+            inner_signal[unit_index] = self.vertex[unit_index].unit.route(&input_signal, 1.0);
+        }
+        let mut output_signal = new_signal_frame(self.outputs());
+        for channel in 0..self.outputs() {
+            output_signal[channel] = signal(&inner_signal, self.output_edge[channel]);
+        }
+        if let Some(target) = max_latency(&output_signal) {
+            for channel in 0..output_signal.len() {
+                if let Some(samples) = latency_deficit(output_signal[channel], target) {
+                    let index = self.push_delay(self.output_edge[channel], samples);
+                    self.output_edge[channel] = edge(Port::Local(index, 0), Port::Global(channel));
+                    inserted += 1;
+                }
+            }
+        }
+        self.invalidate_order();
+        inserted
+    }
+
+    /// Add a delay of `samples` samples fed from the source of `source_edge`.
+    /// Returns the index of the new vertex.
+    fn push_delay(&mut self, source_edge: Edge, samples: f64) -> NodeIndex {
+        let index = self.vertex.len();
+        self.push_id(
+            NodeId::new(),
+            T::boxed(An(Delay::<T>::new(samples / self.sample_rate))),
+        );
+        self.vertex[index].source[0] = source_edge.moved(source_edge.source, Port::Local(index, 0));
+        index
+    }
+
+    /// Error encountered while ordering units for processing, if any.
+    /// While there is an error, the network outputs silence. The error is cleared
+    /// when the network is processed again after the problem has been fixed.
+    ///
+    /// ### Example (Accidental Feedback Loop)
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let mut net = Net64::new(0, 1);
+    /// let id1 = net.push(Box::new(add(1.0)));
+    /// let id2 = net.push(Box::new(mul(0.5)));
+    /// net.connect(id1, 0, id2, 0);
+    /// net.connect(id2, 0, id1, 0);
+    /// net.connect_output(id2, 0, 0);
+    /// assert!(net.get_mono() == 0.0);
+    /// assert!(net.error() == Some(NetError::Cycle));
+    /// ```
+    pub fn error(&self) -> Option<NetError> {
+        self.error
+    }
+
+    /// Check the network. Returns an error if units cannot be ordered for processing.
+    /// Panics if the internal state of the network is inconsistent.
+    pub fn check(&self) -> Result<(), NetError> {
+        assert_eq!(self.input.channels(), self.inputs());
+        assert_eq!(self.output.channels(), self.outputs());
+        assert_eq!(self.output_edge.len(), self.outputs());
+        assert_eq!(self.node_index.len(), self.size());
+        for channel in 0..self.outputs() {
+            assert_eq!(self.output_edge[channel].target, Port::Global(channel));
+            match self.output_edge[channel].source {
+                Port::Local(node, port) => {
+                    assert!(node < self.size());
+                    assert!(port < self.vertex[node].outputs());
+                }
+                Port::Global(port) => {
+                    assert!(port < self.inputs());
+                }
+                _ => (),
+            }
+        }
+        for index in 0..self.size() {
+            assert_eq!(self.node_index[&self.vertex[index].id], index);
+            assert_eq!(self.vertex[index].source.len(), self.vertex[index].inputs());
+            assert_eq!(
+                self.vertex[index].gained_slot.len(),
+                self.vertex[index].inputs()
+            );
+            assert_eq!(
+                self.vertex[index].output_slot.len(),
+                self.vertex[index].outputs()
+            );
+            assert_eq!(
+                self.vertex[index].tick_input.len(),
+                self.vertex[index].inputs()
+            );
+            assert_eq!(
+                self.vertex[index].tick_output.len(),
+                self.vertex[index].outputs()
+            );
+            for channel in 0..self.vertex[index].inputs() {
+                assert_eq!(
+                    self.vertex[index].source[channel].target,
+                    Port::Local(index, channel)
+                );
+                match self.vertex[index].source[channel].source {
+                    Port::Local(node, port) => {
+                        assert!(node < self.size());
+                        assert!(node != index || self.vertex[index].source[channel].feedback);
+                        assert!(port < self.vertex[node].outputs());
+                    }
+                    Port::Global(port) => {
+                        assert!(port < self.inputs());
+                    }
+                    _ => (),
+                }
+            }
+        }
+        if self.determine_order_in(
+            &mut Vec::new(),
+            &mut Vec::new(),
+            &mut Vec::new(),
+            &mut Vec::new(),
+        ) {
+            Ok(())
+        } else {
+            Err(NetError::Cycle)
+        }
+    }
+
+    /// Disambiguate IDs in this network so they don't conflict with those in `other` network.
+    /// Conflict is possible as a result of cloning and recombination.
+    fn disambiguate_ids(&mut self, other: &Net<T>) {
+        for i in 0..self.size() {
+            let id = self.vertex[i].id;
+            if other.node_index.contains_key(&id) {
+                self.node_index.remove(&id);
+                let new_id = NodeId::new();
+                self.vertex[i].id = new_id;
+                self.node_index.insert(new_id, i);
+            }
+        }
+    }
+
+    /// Set the sample rate of units in `other` network to ours.
+    /// Units that are combined into a network must run at its sample rate.
+    fn adopt_sample_rate(&self, other: &mut Net<T>) {
+        if other.sample_rate != self.sample_rate {
+            other.sample_rate = self.sample_rate;
+            for vertex in &mut other.vertex {
+                vertex.unit.set_sample_rate(self.sample_rate);
+            }
+        }
+    }
+
+    /// Sample rate of the network.
+    pub fn sample_rate(&self) -> f64 {
+        self.sample_rate
+    }
+
+    /// Record network events with `tracer`: resets, sample rate changes,
+    /// evaluation order and, in the backend, switches to committed versions.
+    /// Events concerning the whole network have no node ID.
+    /// Tracers can be set in the frontend and are carried over to the backend with commits.
+    ///
+    /// ### Example (Tracing Evaluation Order)
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let (mut trace, tracer) = Trace::new(64);
+    /// let mut net = Net64::new(0, 1);
+    /// let id = net.chain(Box::new(dc(1.0)));
+    /// net.set_tracer(&tracer);
+    /// net.get_mono();
+    /// let event = trace.get().unwrap();
+    /// assert!(event.node == Some(id) && event.kind == TraceKind::Order);
+    /// ```
+    pub fn set_tracer(&mut self, tracer: &Tracer) {
+        self.tracer = Some(tracer.clone());
+        self.invalidate_order();
+    }
+
+    /// Record a network event, if we have a tracer.
+    pub(crate) fn trace(&self, kind: TraceKind) {
+        if let Some(tracer) = &self.tracer {
+            tracer.record(None, kind);
+        }
+    }
+
+    /// Enable or disable metering of signal levels. When enabled, the network keeps
+    /// the peak and RMS levels of unit outputs and global inputs over the last processed block,
+    /// so that, for example, edges can be colored by signal activity.
+    /// Levels are shared with the backend, which measures them after the next commit.
+    ///
+    /// ### Example (Metering Output Levels)
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let mut net = Net64::new(0, 1);
+    /// let id = net.chain(Box::new(dc(0.5)));
+    /// net.set_metering(true);
+    /// net.get_mono();
+    /// assert!(net.output_level(id, 0).peak == 0.5);
+    /// ```
+    pub fn set_metering(&mut self, enabled: bool) {
+        self.metering = enabled;
+        for vertex in self.vertex.iter_mut() {
+            vertex.meter = if enabled {
+                (0..vertex.outputs()).map(|_| LevelMeter::new()).collect()
+            } else {
+                Vec::new()
+            };
+        }
+        self.input_meter = if enabled {
+            (0..self.inputs()).map(|_| LevelMeter::new()).collect()
+        } else {
+            Vec::new()
+        };
+    }
+
+    /// Whether signal levels are metered.
+    pub fn is_metering(&self) -> bool {
+        self.metering
+    }
+
+    /// Level of output `port` of `node` over the last processed block.
+    /// This is the level of all edges leaving the port. Levels are zero if metering is disabled.
+    pub fn output_level(&self, node: NodeId, port: PortIndex) -> SignalLevel {
+        let vertex = &self.vertex[self.node_index[&node]];
+        assert!(port < vertex.outputs());
+        vertex
+            .meter
+            .get(port)
+            .map_or(SignalLevel::default(), |meter| meter.level())
+    }
+
+    /// Level of global input `port` over the last processed block.
+    /// Levels are zero if metering is disabled.
+    pub fn input_level(&self, port: PortIndex) -> SignalLevel {
+        assert!(port < self.inputs());
+        self.input_meter
+            .get(port)
+            .map_or(SignalLevel::default(), |meter| meter.level())
+    }
+
+    /// Snapshot of output levels of all metered units over the last processed block.
+    pub fn level_snapshot(&self) -> HashMap<NodeId, Vec<SignalLevel>> {
+        self.vertex
+            .iter()
+            .filter(|vertex| !vertex.meter.is_empty())
+            .map(|vertex| {
+                (
+                    vertex.id,
+                    vertex.meter.iter().map(|meter| meter.level()).collect(),
+                )
+            })
+            .collect()
+    }
+
+    /// Tap output `port` of `node` without changing the network topology.
+    /// The signal is copied into the returned `Snoop` during processing,
+    /// which keeps the latest `capacity` samples. This is the signal of all edges
+    /// leaving the port, before any edge gains. The snoop must be polled
+    /// with `update` or `get`. Probes are carried over to the backend with commits.
+    /// Units that do not reach any output are not processed, so their probes receive no data.
+    ///
+    /// ### Example (Scope On An Internal Bus)
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let mut net = Net64::new(0, 1);
+    /// let id = net.chain(Box::new(dc(0.5)));
+    /// net.chain(Box::new(mul(2.0)));
+    /// let mut probe = net.probe(id, 0, 4096);
+    /// for _ in 0..MAX_BUFFER_SIZE {
+    ///     net.get_mono();
+    /// }
+    /// probe.update();
+    /// assert!(probe.at(0) == 0.5);
+    /// ```
+    pub fn probe(&mut self, node: NodeId, port: PortIndex, capacity: usize) -> Snoop<T> {
+        let vertex = &mut self.vertex[self.node_index[&node]];
+        assert!(port < vertex.outputs());
+        let (snoop, backend) = Snoop::new(capacity);
+        vertex.probe.push((port, backend));
+        snoop
+    }
+
+    /// Remove all probes from `node`.
+    pub fn remove_probes(&mut self, node: NodeId) {
+        self.vertex[self.node_index[&node]].probe.clear();
+    }
+
+    /// Guard against processing overruns. When enabled, the network measures
+    /// the processing time of each block and of each unit in it. If `blocks` consecutive blocks
+    /// take longer than `budget` times their duration in real time, then the most expensive
+    /// unit that is still playing is muted: its outputs become zero and it is not processed anymore.
+    /// Muting repeats if overruns continue. Muted units are reported by `muted_nodes`
+    /// and, if there is a tracer, as `TraceKind::Mute` events.
+    /// The watchdog is carried over to the backend with commits. Only block processing is timed.
+    ///
+    /// ### Example (Muting A Unit Over Budget)
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let mut net = Net64::new(0, 1);
+    /// let id = net.chain(Box::new(dc(1.0)));
+    /// // A zero budget mutes a unit after every block.
+    /// net.set_watchdog(0.0, 1);
+    /// let mut output = [0.0; 64];
+    /// net.process(64, &[], &mut [&mut output]);
+    /// assert!(net.muted_nodes() == vec![id]);
+    /// net.process(64, &[], &mut [&mut output]);
+    /// assert!(output[0] == 0.0);
+    /// ```
+    pub fn set_watchdog(&mut self, budget: f64, blocks: usize) {
+        assert!(budget >= 0.0 && blocks > 0);
+        self.watchdog = Some(Watchdog {
+            budget,
+            blocks,
+            overruns: 0,
+        });
+    }
+
+    /// Disable the watchdog. Units that have been muted stay muted until `unmute` is called.
+    pub fn clear_watchdog(&mut self) {
+        self.watchdog = None;
+    }
+
+    /// Whether the watchdog is enabled.
+    pub fn has_watchdog(&self) -> bool {
+        self.watchdog.is_some()
+    }
+
+    /// Whether `node` has been muted by the watchdog.
+    pub fn is_muted(&self, node: NodeId) -> bool {
+        self.vertex[self.node_index[&node]].is_muted()
+    }
+
+    /// Units muted by the watchdog, in the order they appear in the network.
+    /// Mutings made in the backend are visible in the frontend.
+    pub fn muted_nodes(&self) -> Vec<NodeId> {
+        self.vertex
+            .iter()
+            .filter(|vertex| vertex.is_muted())
+            .map(|vertex| vertex.id)
+            .collect()
+    }
+
+    /// Resume processing `node` after it has been muted by the watchdog.
+    /// The unit is reset as it has missed processing.
+    /// If the network has a backend, then the change is carried over with the next commit.
+    pub fn unmute(&mut self, node: NodeId) {
+        let index = self.node_index[&node];
+        let vertex = &mut self.vertex[index];
+        if vertex.is_muted() {
+            vertex.unit.reset();
+            vertex.muted = Arc::new(AtomicBool::new(false));
+            vertex.changed = self.revision;
         }
-        net
     }
 
-    /// Check internal consistency of the network. Panic if something is wrong.
-    pub fn check(&self) {
-        assert_eq!(self.input.channels(), self.inputs());
-        assert_eq!(self.output.channels(), self.outputs());
-        assert_eq!(self.output_edge.len(), self.outputs());
-        assert_eq!(self.node_index.len(), self.size());
-        for channel in 0..self.outputs() {
-            assert_eq!(self.output_edge[channel].target, Port::Global(channel));
-            match self.output_edge[channel].source {
-                Port::Local(node, port) => {
-                    assert!(node < self.size());
-                    assert!(port < self.vertex[node].outputs());
-                }
-                Port::Global(port) => {
-                    assert!(port < self.inputs());
+    /// Check elapsed processing time of a block of `size` samples against the watchdog budget.
+    /// Mute the most expensive unit if the budget has been exceeded too many times in a row.
+    fn watch(&mut self, size: usize, elapsed: f64) {
+        if let Some(watchdog) = &mut self.watchdog {
+            if elapsed < watchdog.budget * size as f64 / self.sample_rate {
+                watchdog.overruns = 0;
+                return;
+            }
+            watchdog.overruns += 1;
+            if watchdog.overruns < watchdog.blocks {
+                return;
+            }
+            watchdog.overruns = 0;
+            let mut expensive: Option<NodeIndex> = None;
+            for &index in self.order.get_or_insert(Vec::new()).iter() {
+                let vertex = &self.vertex[index];
+                if !vertex.is_muted()
+                    && expensive.map_or(true, |other| vertex.cost > self.vertex[other].cost)
+                {
+                    expensive = Some(index);
                 }
-                _ => (),
             }
-        }
-        for index in 0..self.size() {
-            assert_eq!(self.node_index[&self.vertex[index].id], index);
-            assert_eq!(self.vertex[index].source.len(), self.vertex[index].inputs());
-            assert_eq!(
-                self.vertex[index].input.channels(),
-                self.vertex[index].inputs()
-            );
-            assert_eq!(
-                self.vertex[index].output.channels(),
-                self.vertex[index].outputs()
-            );
-            assert_eq!(
-                self.vertex[index].tick_input.len(),
-                self.vertex[index].inputs()
-            );
-            assert_eq!(
-                self.vertex[index].tick_output.len(),
-                self.vertex[index].outputs()
-            );
-            for channel in 0..self.vertex[index].inputs() {
-                assert_eq!(
-                    self.vertex[index].source[channel].target,
-                    Port::Local(index, channel)
-                );
-                match self.vertex[index].source[channel].source {
-                    Port::Local(node, port) => {
-                        assert!(node < self.size());
-                        assert!(node != index);
-                        assert!(port < self.vertex[node].outputs());
-                    }
-                    Port::Global(port) => {
-                        assert!(port < self.inputs());
-                    }
-                    _ => (),
+            if let Some(index) = expensive {
+                self.vertex[index].muted.store(true, Ordering::Relaxed);
+                if let Some(tracer) = &self.tracer {
+                    tracer.record(Some(self.vertex[index].id), TraceKind::Mute);
                 }
             }
         }
     }
 
-    /// Disambiguate IDs in this network so they don't conflict with those in `other` network.
-    /// Conflict is possible as a result of cloning and recombination.
-    fn disambiguate_ids(&mut self, other: &Net48) {
-        for i in 0..self.size() {
-            let id = self.vertex[i].id;
-            if other.node_index.contains_key(&id) {
-                self.node_index.remove(&id);
-                let new_id = NodeId::new();
-                self.vertex[i].id = new_id;
-                self.node_index.insert(new_id, i);
-            }
-        }
+    /// Counter of scheduled commits pending in the backend. This is an internal function.
+    pub(crate) fn pending_counter(&self) -> Arc<AtomicUsize> {
+        self.pending.clone()
+    }
+
+    /// Crossfade requested for this version, if any. This is an internal function.
+    pub(crate) fn commit_fade_request(&self) -> Option<(Fade, T)> {
+        self.commit_fade.clone()
+    }
+
+    /// Backend time requested for this version, if any. This is an internal function.
+    pub(crate) fn commit_time_request(&self) -> Option<f64> {
+        self.commit_time
     }
 
     /// Migrate existing units to the new network. This is an internal function.
-    pub(crate) fn migrate(&mut self, new: &mut Net48) {
+    pub(crate) fn migrate(&mut self, new: &mut Net<T>) {
         for (id, &index) in self.node_index.iter() {
             if let Some(&new_index) = new.node_index.get(id) {
+                // Bypass and mute ramps continue from where they are.
+                new.vertex[new_index].bypass.wet = self.vertex[index].bypass.wet;
+                new.vertex[new_index].bypass.level = self.vertex[index].bypass.level;
                 // We may use the existing unit if no changes have been made since our last update.
                 if new.vertex[new_index].changed <= self.revision {
                     std::mem::swap(
@@ -905,14 +2990,12 @@ impl Net48 {
     /// net.commit();
     /// assert!(backend.get_mono() == 2.0);
     /// ```
-    pub fn backend(&mut self) -> NetBackend48 {
+    pub fn backend(&mut self) -> NetBackend<T> {
         assert!(!self.has_backend());
         // Create huge channel buffers to make sure we don't run out of space easily.
         let (sender_a, receiver_a) = channel(1024);
         let (sender_b, receiver_b) = channel(1024);
         self.front = Some((sender_a, receiver_b));
-        self.backend_inputs = self.inputs();
-        self.backend_outputs = self.outputs();
         if !self.is_ordered() {
             self.determine_order();
         }
@@ -920,9 +3003,11 @@ impl Net48 {
         // Send over the original nodes to the backend.
         // This is necessary if the nodes contain any backends, which cannot be cloned effectively.
         std::mem::swap(&mut net.vertex, &mut self.vertex);
+        self.share_mute_flags(&net);
+        net.pending = self.pending.clone();
         net.allocate();
         self.revision += 1;
-        NetBackend48::new(sender_b, receiver_a, net)
+        NetBackend::new(sender_b, receiver_a, net)
     }
 
     /// Returns whether this network has a backend.
@@ -933,13 +3018,76 @@ impl Net48 {
     /// Commit changes made to this frontend to the backend.
     /// This may be called only if the network has a backend.
     pub fn commit(&mut self) {
+        self.commit_version(None, None);
+    }
+
+    /// Commit changes made to this frontend to the backend,
+    /// crossfading from the previous version to the new one over `fade_time` seconds.
+    /// Existing units are not migrated: the previous version keeps running while it fades out
+    /// and the new version fades in starting from the state of the frontend.
+    /// If a crossfade is already in progress, the new version is held until it has completed.
+    /// This may be called only if the network has a backend.
+    ///
+    /// ### Example
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let mut net = Net64::new(0, 1);
+    /// let id = net.chain(Box::new(dc(1.0)));
+    /// let mut backend = net.backend();
+    /// net.replace(id, Box::new(dc(2.0)));
+    /// net.commit_fade(Fade::Smooth, 0.01);
+    /// let x = backend.get_mono();
+    /// assert!(x >= 1.0 && x < 1.01);
+    /// for _ in 0..450 {
+    ///     backend.get_mono();
+    /// }
+    /// assert!(backend.get_mono() == 2.0);
+    /// ```
+    pub fn commit_fade(&mut self, fade: Fade, fade_time: T) {
+        self.commit_version(Some((fade, fade_time)), None);
+    }
+
+    /// Commit changes made to this frontend to the backend, scheduled to take effect
+    /// when the backend clock reaches `time` seconds. The switch is sample accurate.
+    /// The backend clock counts samples processed since the backend was created or last reset.
+    /// Versions are applied in commit order: any version committed after a scheduled version
+    /// is held until the scheduled version has taken effect.
+    /// If `time` has already passed, the version takes effect immediately.
+    /// At most `MAX_SCHEDULED` scheduled commits can be pending in the backend.
+    /// Returns `false`, without committing anything, if there is no room for another one.
+    /// This may be called only if the network has a backend.
+    ///
+    /// ### Example
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let mut net = Net64::new(0, 1);
+    /// let id = net.chain(Box::new(dc(1.0)));
+    /// let mut backend = net.backend();
+    /// backend.set_sample_rate(1000.0);
+    /// net.replace(id, Box::new(dc(2.0)));
+    /// assert!(net.commit_at(0.1));
+    /// for _ in 0..100 {
+    ///     assert!(backend.get_mono() == 1.0);
+    /// }
+    /// assert!(backend.get_mono() == 2.0);
+    /// ```
+    pub fn commit_at(&mut self, time: f64) -> bool {
         assert!(self.has_backend());
-        if self.inputs() != self.backend_inputs {
-            panic!("The number of inputs has changed since last commit. The number of inputs must stay the same.");
+        if self.pending.load(Ordering::Relaxed) >= MAX_SCHEDULED {
+            return false;
         }
-        if self.outputs() != self.backend_outputs {
-            panic!("The number of outputs has changed since last commit. The number of outputs must stay the same.");
+        self.pending.fetch_add(1, Ordering::Relaxed);
+        if self.commit_version(None, Some(time)) {
+            true
+        } else {
+            self.pending.fetch_sub(1, Ordering::Relaxed);
+            false
         }
+    }
+
+    /// Send the current version to the backend. Returns whether it was sent.
+    fn commit_version(&mut self, fade: Option<(Fade, T)>, time: Option<f64>) -> bool {
+        assert!(self.has_backend());
         if !self.is_ordered() {
             self.determine_order();
         }
@@ -947,86 +3095,142 @@ impl Net48 {
         // Send over the original nodes to the backend.
         // This is necessary if the nodes contain any backends, which cannot be cloned effectively.
         std::mem::swap(&mut net.vertex, &mut self.vertex);
+        self.share_mute_flags(&net);
+        net.commit_fade = fade;
+        net.commit_time = time;
         // Preallocate all necessary memory.
         net.allocate();
+        let mut sent = false;
         if let Some((sender, receiver)) = &mut self.front {
             // Deallocate all previous versions.
             while receiver.try_recv().is_ok() {}
             // Send the new version over.
-            if sender.try_send(net).is_ok() {}
+            sent = sender.try_send(net).is_ok();
         }
         self.revision += 1;
+        sent
+    }
+
+    /// Share watchdog mute flags with a version that is being sent to the backend,
+    /// so mutings made in the backend are visible in the frontend.
+    fn share_mute_flags(&mut self, sent: &Net<T>) {
+        for (vertex, sent_vertex) in self.vertex.iter_mut().zip(sent.vertex.iter()) {
+            vertex.muted = sent_vertex.muted.clone();
+        }
     }
 
     /// Resolve new frontend for a binary combination.
-    fn resolve_frontend(&mut self, other: &mut Net48) {
+    fn resolve_frontend(&mut self, other: &mut Net<T>) {
         if self.has_backend() && other.has_backend() {
             panic!("Cannot combine two frontends.");
         }
         if other.has_backend() {
             std::mem::swap(&mut self.front, &mut other.front);
-            self.backend_inputs = other.backend_inputs;
-            self.backend_outputs = other.backend_outputs;
+            std::mem::swap(&mut self.pending, &mut other.pending);
             self.revision = other.revision;
         }
     }
 }
 
-#[duplicate_item(
-    f48       Net48       Vertex48       AudioUnit48         Callback48;
-    [ f64 ]   [ Net64 ]   [ Vertex64 ]   [ AudioUnit64 ]   [ Callback64 ];
-    [ f32 ]   [ Net32 ]   [ Vertex32 ]   [ AudioUnit32 ]   [ Callback32 ];
-)]
-impl AudioUnit48 for Net48 {
-    fn inputs(&self) -> usize {
+impl<T: UnitFloat> Net<T> {
+    /// Number of inputs of the network.
+    pub fn inputs(&self) -> usize {
         self.input.channels()
     }
 
-    fn outputs(&self) -> usize {
+    /// Number of outputs of the network.
+    pub fn outputs(&self) -> usize {
         self.output.channels()
     }
 
-    fn set_sample_rate(&mut self, sample_rate: f64) {
+    /// Set the sample rate of the network and all its units.
+    pub fn set_sample_rate(&mut self, sample_rate: f64) {
         self.sample_rate = sample_rate;
         for vertex in &mut self.vertex {
             vertex.unit.set_sample_rate(sample_rate);
+            vertex.bypass.set_sample_rate(sample_rate);
         }
+        self.trace(TraceKind::SampleRate);
         // Take the opportunity to unload some calculations.
         if !self.is_ordered() {
             self.determine_order();
         }
     }
 
-    fn reset(&mut self) {
+    /// Reset the network and all its units to an initial state.
+    pub fn reset(&mut self) {
         for vertex in &mut self.vertex {
             vertex.unit.reset();
+            vertex.feedback.fill(T::zero());
         }
+        self.trace(TraceKind::Reset);
         // Take the opportunity to unload some calculations.
         if !self.is_ordered() {
             self.determine_order();
         }
     }
 
-    fn tick(&mut self, input: &[f48], output: &mut [f48]) {
+    /// Process one sample.
+    pub fn tick(&mut self, input: &[T], output: &mut [T]) {
         if !self.is_ordered() {
             self.determine_order();
         }
+        if self.error.is_some() {
+            output.fill(T::zero());
+            return;
+        }
+        for (meter, x) in self.input_meter.iter().zip(input.iter()) {
+            meter.measure(&[*x]);
+        }
         // Iterate units in network order.
         for &node_index in self.order.get_or_insert(Vec::new()).iter() {
             for channel in 0..self.vertex[node_index].inputs() {
-                match self.vertex[node_index].source[channel].source {
-                    Port::Zero => self.vertex[node_index].tick_input[channel] = 0.0,
-                    Port::Global(port) => self.vertex[node_index].tick_input[channel] = input[port],
-                    Port::Local(source, port) => {
-                        self.vertex[node_index].tick_input[channel] =
-                            self.vertex[source].tick_output[port]
+                let edge = self.vertex[node_index].source[channel];
+                let value = if edge.feedback {
+                    self.vertex[node_index].feedback[channel]
+                } else {
+                    match edge.source {
+                        Port::Zero => T::zero(),
+                        Port::Global(port) => input[port],
+                        Port::Local(source, port) => self.vertex[source].tick_output[port],
                     }
-                }
+                };
+                self.vertex[node_index].tick_input[channel] = if edge.gain == 1.0 {
+                    value
+                } else {
+                    value * T::from_f64(edge.gain)
+                };
             }
             let vertex = &mut self.vertex[node_index];
-            vertex
-                .unit
-                .tick(&vertex.tick_input, &mut vertex.tick_output);
+            if vertex.is_muted() {
+                vertex.tick_output.fill(T::zero());
+            } else {
+                vertex
+                    .unit
+                    .tick(&vertex.tick_input, &mut vertex.tick_output);
+                if vertex.bypass.is_active() {
+                    vertex
+                        .bypass
+                        .tick(&vertex.tick_input, &mut vertex.tick_output);
+                }
+            }
+            vertex.observe_tick();
+        }
+
+        // Feedback edges latch their source outputs for the next sample.
+        if self.has_feedback {
+            for index in 0..self.vertex.len() {
+                for channel in 0..self.vertex[index].inputs() {
+                    let edge = self.vertex[index].source[channel];
+                    if edge.feedback {
+                        self.vertex[index].feedback[channel] = match edge.source {
+                            Port::Local(source, port) => self.vertex[source].tick_output[port],
+                            Port::Global(port) => input[port],
+                            Port::Zero => T::zero(),
+                        };
+                    }
+                }
+            }
         }
 
         // Then we set the global outputs.
@@ -1034,71 +3238,156 @@ impl AudioUnit48 for Net48 {
             match self.output_edge[channel].source {
                 Port::Global(port) => output[channel] = input[port],
                 Port::Local(node, port) => output[channel] = self.vertex[node].tick_output[port],
-                Port::Zero => output[channel] = 0.0,
+                Port::Zero => output[channel] = T::zero(),
+            }
+            let gain = self.output_edge[channel].gain;
+            if gain != 1.0 {
+                output[channel] *= T::from_f64(gain);
             }
         }
     }
 
-    fn process(&mut self, size: usize, input: &[&[f48]], output: &mut [&mut [f48]]) {
+    /// Process up to `MAX_BUFFER_SIZE` samples.
+    pub fn process(&mut self, size: usize, input: &[&[T]], output: &mut [&mut [T]]) {
         if !self.is_ordered() {
             self.determine_order();
         }
-        // Iterate units in network order.
-        for &node_index in self.order.get_or_insert(Vec::new()).iter() {
-            if let Some(source_node) = self.vertex[node_index].source_vertex {
-                // We can source inputs directly from a source vertex.
-                let ptr = &mut self.vertex[source_node].output as *mut Buffer<f48>;
-                let vertex = &mut self.vertex[node_index];
-                // Safety: we know there is no aliasing, as self connections are prohibited.
-                unsafe {
-                    vertex
-                        .unit
-                        .process(size, (*ptr).self_ref(), vertex.output.self_mut());
+        if self.error.is_some() {
+            for channel in output.iter_mut() {
+                channel[..size].fill(T::zero());
+            }
+            return;
+        }
+        if self.has_feedback {
+            // Feedback edges have a delay of one sample, so process one sample at a time.
+            let mut tick_input = std::mem::take(&mut self.tick_input);
+            let mut tick_output = std::mem::take(&mut self.tick_output);
+            tick_input.resize(self.inputs(), T::zero());
+            tick_output.resize(self.outputs(), T::zero());
+            for i in 0..size {
+                for (channel, x) in tick_input.iter_mut().enumerate() {
+                    *x = input[channel][i];
                 }
-            } else {
-                let ptr = &mut self.vertex[node_index].input as *mut Buffer<f48>;
-                // Gather inputs for this vertex.
-                for channel in 0..self.vertex[node_index].inputs() {
-                    // Safety: we know there is no aliasing, as self connections are prohibited.
-                    unsafe {
-                        match self.vertex[node_index].source[channel].source {
-                            Port::Zero => (*ptr).mut_at(channel)[..size].fill(0.0),
-                            Port::Global(port) => {
-                                (*ptr).mut_at(channel)[..size].copy_from_slice(&input[port][..size])
-                            }
+                self.tick(&tick_input, &mut tick_output);
+                for (channel, x) in tick_output.iter().enumerate() {
+                    output[channel][i] = *x;
+                }
+            }
+            self.tick_input = tick_input;
+            self.tick_output = tick_output;
+            return;
+        }
+        for (meter, x) in self.input_meter.iter().zip(input.iter()) {
+            meter.measure(&x[..size]);
+        }
+        let start = self.watchdog.as_ref().map(|_| Instant::now());
+        #[cfg(feature = "rayon")]
+        let parallel = self.parallel;
+        #[cfg(not(feature = "rayon"))]
+        let parallel = false;
+        if parallel {
+            #[cfg(feature = "rayon")]
+            self.process_levels(size, input, start.is_some());
+        } else {
+            // Iterate units in network order.
+            let arena = self.arena.as_mut_ptr();
+            for &node_index in self.order.get_or_insert(Vec::new()).iter() {
+                let vertex = &mut self.vertex[node_index] as *mut Vertex<T>;
+                // Safety: vertices read and write the buffer arena through the slots assigned
+                // to them. Outputs of a vertex never share slots with its inputs or with
+                // the outputs of other vertices that are still needed.
+                unsafe {
+                    if (*vertex).is_muted() {
+                        for &slot in (*vertex).output_slot.iter() {
+                            arena_mut(arena, slot)[..size].fill(T::zero());
+                        }
+                        (*vertex).observe(arena, size);
+                        continue;
+                    }
+                    let unit_start = start.map(|_| Instant::now());
+                    // Inputs are read directly from source slots without copying.
+                    // Zero inputs are read from slot 0, which is never written to.
+                    // Inputs of edges with gain are scaled into the gain slots of the vertex.
+                    for channel in 0..(*vertex).inputs() {
+                        let edge = (*vertex).source[channel];
+                        if edge.gain == 1.0 {
+                            continue;
+                        }
+                        let source = match edge.source {
+                            Port::Zero => continue,
+                            Port::Global(port) => input[port],
                             Port::Local(source, port) => {
-                                (*ptr).mut_at(channel)[..size]
-                                    .copy_from_slice(&self.vertex[source].output.at(port)[..size]);
+                                arena_ref(arena, self.vertex[source].output_slot[port])
                             }
+                        };
+                        let gain = T::from_f64(edge.gain);
+                        let target = &mut arena_mut(arena, (*vertex).gained_slot[channel])[..size];
+                        for (y, x) in target.iter_mut().zip(source[..size].iter()) {
+                            *y = *x * gain;
                         }
                     }
-                }
-                let vertex = &mut self.vertex[node_index];
-                // Safety: we know there is no aliasing, as self connections are prohibited.
-                unsafe {
-                    vertex
-                        .unit
-                        .process(size, (*ptr).self_ref(), vertex.output.self_mut());
+                    let vertex_input =
+                        self.slice
+                            .from_iter((*vertex).source.iter().enumerate().map(
+                                |(channel, edge)| match edge.source {
+                                    Port::Zero => arena_ref(arena, 0),
+                                    _ if edge.gain != 1.0 => {
+                                        arena_ref(arena, (*vertex).gained_slot[channel])
+                                    }
+                                    Port::Global(port) => input[port],
+                                    Port::Local(source, port) => {
+                                        arena_ref(arena, self.vertex[source].output_slot[port])
+                                    }
+                                },
+                            ));
+                    let vertex_output = self.output_slice.from_iter_mut(
+                        (*vertex)
+                            .output_slot
+                            .iter()
+                            .map(|&slot| arena_mut(arena, slot)),
+                    );
+                    (*vertex).unit.process(size, vertex_input, vertex_output);
+                    if (*vertex).bypass.is_active() {
+                        (*vertex).bypass.process(size, vertex_input, vertex_output);
+                    }
+                    if let Some(unit_start) = unit_start {
+                        (*vertex).cost = unit_start.elapsed().as_secs_f64();
+                    }
+                    (*vertex).observe(arena, size);
                 }
             }
         }
+        if let Some(start) = start {
+            self.watch(size, start.elapsed().as_secs_f64());
+        }
 
         // Then we set the global outputs.
         for channel in 0..output.len() {
             match self.output_edge[channel].source {
                 Port::Global(port) => output[channel][..size].copy_from_slice(&input[port][..size]),
-                Port::Local(node, port) => output[channel][..size]
-                    .copy_from_slice(&self.vertex[node].output.at(port)[..size]),
-                Port::Zero => output[channel][..size].fill(0.0),
+                Port::Local(node, port) => {
+                    let slot = self.vertex[node].output_slot[port];
+                    output[channel][..size].copy_from_slice(
+                        &self.arena[slot * MAX_BUFFER_SIZE..slot * MAX_BUFFER_SIZE + size],
+                    )
+                }
+                Port::Zero => output[channel][..size].fill(T::zero()),
+            }
+            let gain = self.output_edge[channel].gain;
+            if gain != 1.0 {
+                let gain = T::from_f64(gain);
+                output[channel][..size].iter_mut().for_each(|x| *x *= gain);
             }
         }
     }
 
-    fn get_id(&self) -> u64 {
+    /// Return an ID code for networks.
+    pub fn get_id(&self) -> u64 {
         ID
     }
 
-    fn ping(&mut self, probe: bool, hash: AttoHash) -> AttoHash {
+    /// Ping the units of the network for randomization, or hash the network.
+    pub fn ping(&mut self, probe: bool, hash: AttoHash) -> AttoHash {
         let mut hash = hash.hash(ID);
         for x in self.vertex.iter_mut() {
             hash = x.unit.ping(probe, hash);
@@ -1106,33 +3395,87 @@ impl AudioUnit48 for Net48 {
         hash
     }
 
-    fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
-        let mut inner_signal: Vec<SignalFrame> = vec![];
+    /// Whether some unit of the network has side effects.
+    pub fn has_side_effects(&self) -> bool {
+        self.vertex
+            .iter()
+            .any(|vertex| vertex.unit.has_side_effects())
+    }
+
+    /// Kind of signal expected at network input `input`.
+    pub fn input_kind(&self, input: usize) -> PortKind {
+        // Report the first specific kind expected by a unit connected to the input.
         for vertex in self.vertex.iter() {
-            inner_signal.push(new_signal_frame(vertex.unit.outputs()));
+            for edge in vertex.source.iter() {
+                if let (Port::Global(port), Port::Local(_, target_port)) =
+                    (edge.source, edge.target)
+                {
+                    let kind = vertex.unit.input_kind(target_port);
+                    if port == input && kind != PortKind::Audio {
+                        return kind;
+                    }
+                }
+            }
         }
-        if !self.is_ordered() {
-            self.determine_order();
+        PortKind::Audio
+    }
+
+    /// Kind of signal produced at network output `output`.
+    pub fn output_kind(&self, output: usize) -> PortKind {
+        match self.output_edge[output].source {
+            Port::Local(node, port) => self.vertex[node].unit.output_kind(port),
+            Port::Global(port) => self.input_kind(port),
+            Port::Zero => PortKind::Audio,
         }
-        for &unit_index in self.order.as_mut().unwrap().iter() {
-            let mut input_signal = new_signal_frame(self.vertex[unit_index].unit.inputs());
-            for channel in 0..self.vertex[unit_index].unit.inputs() {
-                match self.vertex[unit_index].source[channel].source {
-                    Port::Local(j, port) => input_signal[channel] = inner_signal[j][port],
-                    Port::Global(j) => input_signal[channel] = input[j],
-                    Port::Zero => input_signal[channel] = Signal::Value(0.0),
-                }
+    }
+
+    /// Channel layout of the network inputs, if known.
+    pub fn input_layout(&self) -> Option<ChannelLayout> {
+        // The layout is known if a unit receives all network inputs in order.
+        let inputs = self.inputs();
+        self.vertex
+            .iter()
+            .find(|vertex| {
+                inputs > 0
+                    && vertex.inputs() == inputs
+                    && vertex
+                        .source
+                        .iter()
+                        .enumerate()
+                        .all(|(i, edge)| matches!(edge.source, Port::Global(port) if port == i))
+            })
+            .and_then(|vertex| vertex.unit.input_layout())
+    }
+
+    /// Channel layout of the network outputs, if known.
+    pub fn output_layout(&self) -> Option<ChannelLayout> {
+        // The layout is known if the network outputs all outputs of a unit in order.
+        if let Some(Port::Local(node, _)) = self.output_edge.first().map(|edge| edge.source) {
+            if self.vertex[node].outputs() == self.outputs()
+                && self
+                    .output_edge
+                    .iter()
+                    .enumerate()
+                    .all(|(i, edge)| matches!(edge.source, Port::Local(n, port) if n == node && port == i))
+            {
+                return self.vertex[node].unit.output_layout();
             }
-            inner_signal[unit_index] = self.vertex[unit_index].unit.route(&input_signal, frequency);
         }
+        None
+    }
+
+    /// Route constants, latencies and frequency responses at `frequency` Hz through the network.
+    pub fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
+        let inner_signal = self.route_vertices(input, frequency);
 
         // Then we set the global outputs.
         let mut output_signal = new_signal_frame(self.outputs());
         for channel in 0..self.outputs() {
+            let gain = self.output_edge[channel].gain;
             match self.output_edge[channel].source {
-                Port::Global(port) => output_signal[channel] = input[port],
+                Port::Global(port) => output_signal[channel] = input[port].scale(gain),
                 Port::Local(node, port) => {
-                    output_signal[channel] = inner_signal[node][port];
+                    output_signal[channel] = inner_signal[node][port].scale(gain);
                 }
                 Port::Zero => output_signal[channel] = Signal::Value(0.0),
             }
@@ -1140,28 +3483,110 @@ impl AudioUnit48 for Net48 {
         output_signal
     }
 
-    fn footprint(&self) -> usize {
-        std::mem::size_of::<Self>()
-    }
-
-    fn allocate(&mut self) {
+    /// Preallocate all needed memory, including in the units of the network.
+    pub fn allocate(&mut self) {
         if !self.is_ordered() {
             self.determine_order();
         }
         for vertex in self.vertex.iter_mut() {
             vertex.allocate();
         }
+        self.tick_input.resize(self.input.channels(), T::zero());
+        self.tick_output.resize(self.output.channels(), T::zero());
     }
 }
 
-#[duplicate_item(
-    f48       Net48       Vertex48       AudioUnit48;
-    [ f64 ]   [ Net64 ]   [ Vertex64 ]   [ AudioUnit64 ];
-    [ f32 ]   [ Net32 ]   [ Vertex32 ]   [ AudioUnit32 ];
-)]
-impl Net48 {
+impl<T: UnitFloat> DynAudioUnit<T> for Net<T> {
+    fn reset(&mut self) {
+        Net::reset(self);
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        Net::set_sample_rate(self, sample_rate);
+    }
+
+    #[inline]
+    fn tick(&mut self, input: &[T], output: &mut [T]) {
+        Net::tick(self, input, output);
+    }
+
+    #[inline]
+    fn process(&mut self, size: usize, input: &[&[T]], output: &mut [&mut [T]]) {
+        Net::process(self, size, input, output);
+    }
+
+    fn inputs(&self) -> usize {
+        Net::inputs(self)
+    }
+
+    fn outputs(&self) -> usize {
+        Net::outputs(self)
+    }
+
+    fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
+        Net::route(self, input, frequency)
+    }
+
+    fn get_id(&self) -> u64 {
+        Net::get_id(self)
+    }
+
+    fn ping(&mut self, probe: bool, hash: AttoHash) -> AttoHash {
+        Net::ping(self, probe, hash)
+    }
+
+    fn has_side_effects(&self) -> bool {
+        Net::has_side_effects(self)
+    }
+
+    fn input_kind(&self, input: usize) -> PortKind {
+        Net::input_kind(self, input)
+    }
+
+    fn output_kind(&self, output: usize) -> PortKind {
+        Net::output_kind(self, output)
+    }
+
+    fn input_layout(&self) -> Option<ChannelLayout> {
+        Net::input_layout(self)
+    }
+
+    fn output_layout(&self) -> Option<ChannelLayout> {
+        Net::output_layout(self)
+    }
+
+    fn allocate(&mut self) {
+        Net::allocate(self);
+    }
+
+    fn as_net_mut(&mut self) -> Option<&mut Net<T>> {
+        Some(self)
+    }
+
+    fn clone_unit(&self) -> Box<Self> {
+        Box::new(self.clone())
+    }
+}
+
+/// Largest known latency in `signal`, in samples.
+fn max_latency(signal: &[Signal]) -> Option<f64> {
+    signal
+        .iter()
+        .filter_map(|x| x.latency())
+        .reduce(|x, y| x.max(y))
+}
+
+/// Number of whole samples by which `signal` falls short of latency `target`, if any.
+fn latency_deficit(signal: Signal, target: f64) -> Option<f64> {
+    signal
+        .latency()
+        .map(|latency| round(target - latency))
+        .filter(|&samples| samples >= 1.0)
+}
+
+impl<T: UnitFloat> Net<T> {
     /// Given net A, create and return net !A.
-    pub fn thru_op(mut net: Net48) -> Net48 {
+    pub fn thru_op(mut net: Net<T>) -> Net<T> {
         let outputs = net.outputs();
         net.output.resize(net.inputs());
         net.output_edge
@@ -1173,7 +3598,7 @@ impl Net48 {
     }
 
     /// Given nets A and B, create and return net A ^ B.
-    pub fn branch_op(mut net1: Net48, mut net2: Net48) -> Net48 {
+    pub fn branch_op(mut net1: Net<T>, mut net2: Net<T>) -> Net<T> {
         if net1.inputs() != net2.inputs() {
             panic!(
                 "Branch: mismatched inputs ({} versus {}).",
@@ -1182,6 +3607,7 @@ impl Net48 {
             );
         }
         net2.disambiguate_ids(&net1);
+        net1.adopt_sample_rate(&mut net2);
         let offset = net1.vertex.len();
         let output_offset = net1.outputs();
         let outputs = net1.outputs() + net2.outputs();
@@ -1191,16 +3617,17 @@ impl Net48 {
         for i in output_offset..net1.output_edge.len() {
             match net1.output_edge[i].source {
                 Port::Local(source_node, source_port) => {
-                    net1.output_edge[i] = edge(
+                    net1.output_edge[i] = net1.output_edge[i].moved(
                         Port::Local(source_node + offset, source_port),
                         Port::Global(i),
                     );
                 }
                 Port::Global(source_port) => {
-                    net1.output_edge[i] = edge(Port::Global(source_port), Port::Global(i));
+                    net1.output_edge[i] =
+                        net1.output_edge[i].moved(Port::Global(source_port), Port::Global(i));
                 }
                 Port::Zero => {
-                    net1.output_edge[i] = edge(Port::Zero, Port::Global(i));
+                    net1.output_edge[i] = net1.output_edge[i].moved(Port::Zero, Port::Global(i));
                 }
             }
         }
@@ -1209,17 +3636,18 @@ impl Net48 {
             for port in 0..net1.vertex[node].inputs() {
                 match net1.vertex[node].source[port].source {
                     Port::Local(source_node, source_port) => {
-                        net1.vertex[node].source[port] = edge(
-                            Port::Local(source_node + offset, source_port),
-                            Port::Local(node, port),
-                        );
+                        // Keep the feedback flag of the edge.
+                        net1.vertex[node].source[port].source =
+                            Port::Local(source_node + offset, source_port);
+                        net1.vertex[node].source[port].target = Port::Local(node, port);
                     }
                     Port::Global(source_port) => {
-                        net1.vertex[node].source[port] =
-                            edge(Port::Global(source_port), Port::Local(node, port));
+                        net1.vertex[node].source[port] = net1.vertex[node].source[port]
+                            .moved(Port::Global(source_port), Port::Local(node, port));
                     }
                     Port::Zero => {
-                        net1.vertex[node].source[port] = edge(Port::Zero, Port::Local(node, port));
+                        net1.vertex[node].source[port] = net1.vertex[node].source[port]
+                            .moved(Port::Zero, Port::Local(node, port));
                     }
                 }
             }
@@ -1230,8 +3658,9 @@ impl Net48 {
     }
 
     /// Given nets A and B, create and return net A | B.
-    pub fn stack_op(mut net1: Net48, mut net2: Net48) -> Net48 {
+    pub fn stack_op(mut net1: Net<T>, mut net2: Net<T>) -> Net<T> {
         net2.disambiguate_ids(&net1);
+        net1.adopt_sample_rate(&mut net2);
         let offset = net1.vertex.len();
         let output_offset = net1.outputs();
         let input_offset = net1.inputs();
@@ -1244,17 +3673,17 @@ impl Net48 {
         for i in output_offset..net1.output_edge.len() {
             match net1.output_edge[i].source {
                 Port::Local(source_node, source_port) => {
-                    net1.output_edge[i] = edge(
+                    net1.output_edge[i] = net1.output_edge[i].moved(
                         Port::Local(source_node + offset, source_port),
                         Port::Global(i),
                     );
                 }
                 Port::Global(source_port) => {
-                    net1.output_edge[i] =
-                        edge(Port::Global(source_port + input_offset), Port::Global(i));
+                    net1.output_edge[i] = net1.output_edge[i]
+                        .moved(Port::Global(source_port + input_offset), Port::Global(i));
                 }
                 Port::Zero => {
-                    net1.output_edge[i] = edge(Port::Zero, Port::Global(i));
+                    net1.output_edge[i] = net1.output_edge[i].moved(Port::Zero, Port::Global(i));
                 }
             }
         }
@@ -1263,19 +3692,20 @@ impl Net48 {
             for port in 0..net1.vertex[node].inputs() {
                 match net1.vertex[node].source[port].source {
                     Port::Local(source_node, source_port) => {
-                        net1.vertex[node].source[port] = edge(
-                            Port::Local(source_node + offset, source_port),
-                            Port::Local(node, port),
-                        );
+                        // Keep the feedback flag of the edge.
+                        net1.vertex[node].source[port].source =
+                            Port::Local(source_node + offset, source_port);
+                        net1.vertex[node].source[port].target = Port::Local(node, port);
                     }
                     Port::Global(source_port) => {
-                        net1.vertex[node].source[port] = edge(
+                        net1.vertex[node].source[port] = net1.vertex[node].source[port].moved(
                             Port::Global(source_port + input_offset),
                             Port::Local(node, port),
                         );
                     }
                     Port::Zero => {
-                        net1.vertex[node].source[port] = edge(Port::Zero, Port::Local(node, port));
+                        net1.vertex[node].source[port] = net1.vertex[node].source[port]
+                            .moved(Port::Zero, Port::Local(node, port));
                     }
                 }
             }
@@ -1286,11 +3716,11 @@ impl Net48 {
     }
 
     /// Given nets A and B and binary operator op, create and return net A op B.
-    pub fn bin_op<B: FrameBinop<super::prelude::U1, f48> + Sync + Send + 'static>(
-        mut net1: Net48,
-        mut net2: Net48,
+    pub fn bin_op<B: FrameBinop<super::prelude::U1, T> + Sync + Send + 'static>(
+        mut net1: Net<T>,
+        mut net2: Net<T>,
         op: B,
-    ) -> Net48 {
+    ) -> Net<T> {
         if net1.outputs() != net2.outputs() {
             panic!(
                 "Binary operation: mismatched outputs ({} versus {}).",
@@ -1299,6 +3729,7 @@ impl Net48 {
             );
         }
         net2.disambiguate_ids(&net1);
+        net1.adopt_sample_rate(&mut net2);
         let output1 = net1.output_edge.clone();
         let output2 = net2.output_edge.clone();
         let input_offset = net1.inputs();
@@ -1310,29 +3741,30 @@ impl Net48 {
             net1.node_index.insert(net1.vertex[node].id, node);
             for port in 0..net1.vertex[node].inputs() {
                 match net1.vertex[node].source[port].source {
-                    Port::Local(source_node, source_port) => {
-                        net1.vertex[node].source[port] = edge(
-                            Port::Local(source_node + offset, source_port),
-                            Port::Local(node, port),
-                        );
+                    Port::Local(source_node, source_port) => {
+                        // Keep the feedback flag of the edge.
+                        net1.vertex[node].source[port].source =
+                            Port::Local(source_node + offset, source_port);
+                        net1.vertex[node].source[port].target = Port::Local(node, port);
                     }
                     Port::Global(source_port) => {
-                        net1.vertex[node].source[port] = edge(
+                        net1.vertex[node].source[port] = net1.vertex[node].source[port].moved(
                             Port::Global(source_port + input_offset),
                             Port::Local(node, port),
                         );
                     }
                     Port::Zero => {
-                        net1.vertex[node].source[port] = edge(Port::Zero, Port::Local(node, port));
+                        net1.vertex[node].source[port] = net1.vertex[node].source[port]
+                            .moved(Port::Zero, Port::Local(node, port));
                     }
                 }
             }
         }
         let add_offset = net1.vertex.len();
         for i in 0..net1.outputs() {
-            net1.push(Box::new(An(Binop::<f48, _, _, _>::new(
-                Pass::<f48>::new(),
-                Pass::<f48>::new(),
+            net1.push(T::boxed(An(Binop::<T, _, _, _>::new(
+                Pass::<T>::new(),
+                Pass::<T>::new(),
                 op.clone(),
             ))));
             net1.connect_output_index(add_offset + i, 0, i);
@@ -1347,6 +3779,7 @@ impl Net48 {
                 }
                 _ => (),
             }
+            net1.vertex[add_offset + i].source[0].gain = output1[i].gain;
         }
         for i in 0..output2.len() {
             match output2[i].source {
@@ -1358,6 +3791,7 @@ impl Net48 {
                 }
                 _ => (),
             }
+            net1.vertex[add_offset + i].source[1].gain = output2[i].gain;
         }
         net1.invalidate_order();
         net1.resolve_frontend(&mut net2);
@@ -1365,7 +3799,7 @@ impl Net48 {
     }
 
     /// Given nets A and B, create and return net A & B.
-    pub fn bus_op(mut net1: Net48, mut net2: Net48) -> Net48 {
+    pub fn bus_op(mut net1: Net<T>, mut net2: Net<T>) -> Net<T> {
         if net1.inputs() != net2.inputs() {
             panic!(
                 "Bus: mismatched inputs ({} versus {}).",
@@ -1381,6 +3815,7 @@ impl Net48 {
             );
         }
         net2.disambiguate_ids(&net1);
+        net1.adopt_sample_rate(&mut net2);
         let output1 = net1.output_edge.clone();
         let output2 = net2.output_edge.clone();
         let offset = net1.vertex.len();
@@ -1390,26 +3825,27 @@ impl Net48 {
             for port in 0..net1.vertex[node].inputs() {
                 match net1.vertex[node].source[port].source {
                     Port::Local(source_node, source_port) => {
-                        net1.vertex[node].source[port] = edge(
-                            Port::Local(source_node + offset, source_port),
-                            Port::Local(node, port),
-                        );
+                        // Keep the feedback flag of the edge.
+                        net1.vertex[node].source[port].source =
+                            Port::Local(source_node + offset, source_port);
+                        net1.vertex[node].source[port].target = Port::Local(node, port);
                     }
                     Port::Global(source_port) => {
-                        net1.vertex[node].source[port] =
-                            edge(Port::Global(source_port), Port::Local(node, port));
+                        net1.vertex[node].source[port] = net1.vertex[node].source[port]
+                            .moved(Port::Global(source_port), Port::Local(node, port));
                     }
                     Port::Zero => {
-                        net1.vertex[node].source[port] = edge(Port::Zero, Port::Local(node, port));
+                        net1.vertex[node].source[port] = net1.vertex[node].source[port]
+                            .moved(Port::Zero, Port::Local(node, port));
                     }
                 }
             }
         }
         let add_offset = net1.vertex.len();
         for i in 0..net1.outputs() {
-            net1.push(Box::new(An(Binop::<f48, _, _, _>::new(
-                Pass::<f48>::new(),
-                Pass::<f48>::new(),
+            net1.push(T::boxed(An(Binop::<T, _, _, _>::new(
+                Pass::<T>::new(),
+                Pass::<T>::new(),
                 FrameAdd::new(),
             ))));
             net1.connect_output_index(add_offset + i, 0, i);
@@ -1424,6 +3860,7 @@ impl Net48 {
                 }
                 _ => (),
             }
+            net1.vertex[add_offset + i].source[0].gain = output1[i].gain;
         }
         for i in 0..output2.len() {
             match output2[i].source {
@@ -1435,6 +3872,7 @@ impl Net48 {
                 }
                 _ => (),
             }
+            net1.vertex[add_offset + i].source[1].gain = output2[i].gain;
         }
         net1.invalidate_order();
         net1.resolve_frontend(&mut net2);
@@ -1442,7 +3880,7 @@ impl Net48 {
     }
 
     /// Given nets A and B, create and return net A >> B.
-    pub fn pipe_op(mut net1: Net48, mut net2: Net48) -> Net48 {
+    pub fn pipe_op(mut net1: Net<T>, mut net2: Net<T>) -> Net<T> {
         if net1.outputs() != net2.inputs() {
             panic!(
                 "Pipe: mismatched connectivity ({} outputs versus {} inputs).",
@@ -1450,7 +3888,16 @@ impl Net48 {
                 net2.inputs()
             );
         }
+        if let (Some(output), Some(input)) = (net1.output_layout(), net2.input_layout()) {
+            if output != input {
+                panic!(
+                    "Pipe: mismatched channel layouts ({} outputs versus {} inputs).",
+                    output, input
+                );
+            }
+        }
         net2.disambiguate_ids(&net1);
+        net1.adopt_sample_rate(&mut net2);
         let offset = net1.vertex.len();
         net1.vertex.append(&mut net2.vertex);
         // Adjust local ports.
@@ -1459,19 +3906,20 @@ impl Net48 {
             for port in 0..net1.vertex[node].inputs() {
                 match net1.vertex[node].source[port].source {
                     Port::Local(source_node, source_port) => {
-                        net1.vertex[node].source[port] = edge(
-                            Port::Local(source_node + offset, source_port),
-                            Port::Local(node, port),
-                        );
+                        // Keep the feedback flag of the edge.
+                        net1.vertex[node].source[port].source =
+                            Port::Local(source_node + offset, source_port);
+                        net1.vertex[node].source[port].target = Port::Local(node, port);
                     }
                     Port::Global(source_port) => {
-                        net1.vertex[node].source[port] = edge(
-                            net1.output_edge[source_port].source,
-                            Port::Local(node, port),
-                        );
+                        let output = net1.output_edge[source_port];
+                        let edge = &mut net1.vertex[node].source[port];
+                        *edge = edge.moved(output.source, Port::Local(node, port));
+                        edge.gain *= output.gain;
                     }
                     Port::Zero => {
-                        net1.vertex[node].source[port] = edge(Port::Zero, Port::Local(node, port));
+                        net1.vertex[node].source[port] = net1.vertex[node].source[port]
+                            .moved(Port::Zero, Port::Local(node, port));
                     }
                 }
             }
@@ -1483,14 +3931,16 @@ impl Net48 {
         for output_port in 0..net1.outputs() {
             match net1.output_edge[output_port].source {
                 Port::Local(source_node, source_port) => {
-                    net1.output_edge[output_port] = edge(
+                    net1.output_edge[output_port] = net1.output_edge[output_port].moved(
                         Port::Local(source_node + offset, source_port),
                         Port::Global(output_port),
                     );
                 }
                 Port::Global(source_port) => {
-                    net1.output_edge[output_port] =
-                        edge(output_edge1[source_port].source, Port::Global(output_port));
+                    let output = output_edge1[source_port];
+                    let edge = &mut net1.output_edge[output_port];
+                    *edge = edge.moved(output.source, Port::Global(output_port));
+                    edge.gain *= output.gain;
                 }
                 _ => (),
             }
@@ -1501,256 +3951,310 @@ impl Net48 {
     }
 }
 
-#[duplicate_item(
-    f48       Net48       AudioUnit48;
-    [ f64 ]   [ Net64 ]   [ AudioUnit64 ];
-    [ f32 ]   [ Net32 ]   [ AudioUnit32 ];
-)]
-impl std::ops::Not for Net48 {
-    type Output = Net48;
+impl<T: UnitFloat> std::ops::Not for Net<T> {
+    type Output = Net<T>;
     #[inline]
     fn not(self) -> Self::Output {
-        Net48::thru_op(self)
+        Net::thru_op(self)
     }
 }
 
-#[duplicate_item(
-    f48       Net48       AudioUnit48;
-    [ f64 ]   [ Net64 ]   [ AudioUnit64 ];
-    [ f32 ]   [ Net32 ]   [ AudioUnit32 ];
-)]
-impl std::ops::Neg for Net48 {
-    type Output = Net48;
+impl<T: UnitFloat> std::ops::Neg for Net<T> {
+    type Output = Net<T>;
     #[inline]
     fn neg(self) -> Self::Output {
         // TODO. Optimize this.
         let n = self.outputs();
-        Net48::scalar(n, f48::zero()) - self
+        Net::scalar(n, T::zero()) - self
     }
 }
 
-#[duplicate_item(
-    f48       Net48       AudioUnit48;
-    [ f64 ]   [ Net64 ]   [ AudioUnit64 ];
-    [ f32 ]   [ Net32 ]   [ AudioUnit32 ];
-)]
-impl std::ops::Shr<Net48> for Net48 {
-    type Output = Net48;
+impl<T: UnitFloat> std::ops::Shr<Net<T>> for Net<T> {
+    type Output = Net<T>;
     #[inline]
-    fn shr(self, y: Net48) -> Self::Output {
-        Net48::pipe_op(self, y)
+    fn shr(self, y: Net<T>) -> Self::Output {
+        Net::pipe_op(self, y)
     }
 }
 
-#[duplicate_item(
-    f48       Net48       AudioUnit48;
-    [ f64 ]   [ Net64 ]   [ AudioUnit64 ];
-    [ f32 ]   [ Net32 ]   [ AudioUnit32 ];
-)]
-impl<X> std::ops::Shr<An<X>> for Net48
+impl<T: UnitFloat, X> std::ops::Shr<An<X>> for Net<T>
 where
-    X: AudioNode<Sample = f48> + std::marker::Send + Sync + 'static,
+    X: AudioNode<Sample = T> + std::marker::Send + Sync + 'static,
 {
-    type Output = Net48;
+    type Output = Net<T>;
     #[inline]
     fn shr(self, y: An<X>) -> Self::Output {
-        Net48::pipe_op(self, Net48::wrap(Box::new(y)))
+        Net::pipe_op(self, Net::wrap(T::boxed(y)))
     }
 }
 
-#[duplicate_item(
-    f48       Net48       AudioUnit48;
-    [ f64 ]   [ Net64 ]   [ AudioUnit64 ];
-    [ f32 ]   [ Net32 ]   [ AudioUnit32 ];
-)]
-impl<X> std::ops::Shr<Net48> for An<X>
+impl<T: UnitFloat, X> std::ops::Shr<Net<T>> for An<X>
 where
-    X: AudioNode<Sample = f48> + std::marker::Send + Sync + 'static,
+    X: AudioNode<Sample = T> + std::marker::Send + Sync + 'static,
 {
-    type Output = Net48;
+    type Output = Net<T>;
     #[inline]
-    fn shr(self, y: Net48) -> Self::Output {
-        Net48::pipe_op(Net48::wrap(Box::new(self)), y)
+    fn shr(self, y: Net<T>) -> Self::Output {
+        Net::pipe_op(Net::wrap(T::boxed(self)), y)
     }
 }
 
-#[duplicate_item(
-    f48       Net48       AudioUnit48;
-    [ f64 ]   [ Net64 ]   [ AudioUnit64 ];
-    [ f32 ]   [ Net32 ]   [ AudioUnit32 ];
-)]
-impl std::ops::BitAnd<Net48> for Net48 {
-    type Output = Net48;
+impl<T: UnitFloat> std::ops::BitAnd<Net<T>> for Net<T> {
+    type Output = Net<T>;
     #[inline]
-    fn bitand(self, y: Net48) -> Self::Output {
-        Net48::bus_op(self, y)
+    fn bitand(self, y: Net<T>) -> Self::Output {
+        Net::bus_op(self, y)
     }
 }
 
-#[duplicate_item(
-    f48       Net48       AudioUnit48;
-    [ f64 ]   [ Net64 ]   [ AudioUnit64 ];
-    [ f32 ]   [ Net32 ]   [ AudioUnit32 ];
-)]
-impl<X> std::ops::BitAnd<An<X>> for Net48
+impl<T: UnitFloat, X> std::ops::BitAnd<An<X>> for Net<T>
 where
-    X: AudioNode<Sample = f48> + std::marker::Send + Sync + 'static,
+    X: AudioNode<Sample = T> + std::marker::Send + Sync + 'static,
 {
-    type Output = Net48;
+    type Output = Net<T>;
     #[inline]
     fn bitand(self, y: An<X>) -> Self::Output {
-        Net48::bus_op(self, Net48::wrap(Box::new(y)))
+        Net::bus_op(self, Net::wrap(T::boxed(y)))
     }
 }
 
-#[duplicate_item(
-    f48       Net48       AudioUnit48;
-    [ f64 ]   [ Net64 ]   [ AudioUnit64 ];
-    [ f32 ]   [ Net32 ]   [ AudioUnit32 ];
-)]
-impl<X> std::ops::BitAnd<Net48> for An<X>
+impl<T: UnitFloat, X> std::ops::BitAnd<Net<T>> for An<X>
 where
-    X: AudioNode<Sample = f48> + std::marker::Send + Sync + 'static,
+    X: AudioNode<Sample = T> + std::marker::Send + Sync + 'static,
 {
-    type Output = Net48;
+    type Output = Net<T>;
     #[inline]
-    fn bitand(self, y: Net48) -> Self::Output {
-        Net48::bus_op(Net48::wrap(Box::new(self)), y)
+    fn bitand(self, y: Net<T>) -> Self::Output {
+        Net::bus_op(Net::wrap(T::boxed(self)), y)
     }
 }
 
-#[duplicate_item(
-    f48       Net48       AudioUnit48;
-    [ f64 ]   [ Net64 ]   [ AudioUnit64 ];
-    [ f32 ]   [ Net32 ]   [ AudioUnit32 ];
-)]
-impl std::ops::BitOr<Net48> for Net48 {
-    type Output = Net48;
+impl<T: UnitFloat> std::ops::BitOr<Net<T>> for Net<T> {
+    type Output = Net<T>;
     #[inline]
-    fn bitor(self, y: Net48) -> Self::Output {
-        Net48::stack_op(self, y)
+    fn bitor(self, y: Net<T>) -> Self::Output {
+        Net::stack_op(self, y)
     }
 }
 
-#[duplicate_item(
-    f48       Net48       AudioUnit48;
-    [ f64 ]   [ Net64 ]   [ AudioUnit64 ];
-    [ f32 ]   [ Net32 ]   [ AudioUnit32 ];
-)]
-impl<X> std::ops::BitOr<An<X>> for Net48
+impl<T: UnitFloat, X> std::ops::BitOr<An<X>> for Net<T>
 where
-    X: AudioNode<Sample = f48> + std::marker::Send + Sync + 'static,
+    X: AudioNode<Sample = T> + std::marker::Send + Sync + 'static,
 {
-    type Output = Net48;
+    type Output = Net<T>;
     #[inline]
     fn bitor(self, y: An<X>) -> Self::Output {
-        Net48::stack_op(self, Net48::wrap(Box::new(y)))
+        Net::stack_op(self, Net::wrap(T::boxed(y)))
     }
 }
 
-#[duplicate_item(
-    f48       Net48       AudioUnit48;
-    [ f64 ]   [ Net64 ]   [ AudioUnit64 ];
-    [ f32 ]   [ Net32 ]   [ AudioUnit32 ];
-)]
-impl<X> std::ops::BitOr<Net48> for An<X>
+impl<T: UnitFloat, X> std::ops::BitOr<Net<T>> for An<X>
 where
-    X: AudioNode<Sample = f48> + std::marker::Send + Sync + 'static,
+    X: AudioNode<Sample = T> + std::marker::Send + Sync + 'static,
 {
-    type Output = Net48;
+    type Output = Net<T>;
     #[inline]
-    fn bitor(self, y: Net48) -> Self::Output {
-        Net48::stack_op(Net48::wrap(Box::new(self)), y)
+    fn bitor(self, y: Net<T>) -> Self::Output {
+        Net::stack_op(Net::wrap(T::boxed(self)), y)
     }
 }
 
-#[duplicate_item(
-    f48       Net48       AudioUnit48;
-    [ f64 ]   [ Net64 ]   [ AudioUnit64 ];
-    [ f32 ]   [ Net32 ]   [ AudioUnit32 ];
-)]
-impl std::ops::BitXor<Net48> for Net48 {
-    type Output = Net48;
+impl<T: UnitFloat> std::ops::BitXor<Net<T>> for Net<T> {
+    type Output = Net<T>;
     #[inline]
-    fn bitxor(self, y: Net48) -> Self::Output {
-        Net48::branch_op(self, y)
+    fn bitxor(self, y: Net<T>) -> Self::Output {
+        Net::branch_op(self, y)
     }
 }
 
-#[duplicate_item(
-    f48       Net48       AudioUnit48;
-    [ f64 ]   [ Net64 ]   [ AudioUnit64 ];
-    [ f32 ]   [ Net32 ]   [ AudioUnit32 ];
-)]
-impl<X> std::ops::BitXor<An<X>> for Net48
+impl<T: UnitFloat, X> std::ops::BitXor<An<X>> for Net<T>
 where
-    X: AudioNode<Sample = f48> + std::marker::Send + Sync + 'static,
+    X: AudioNode<Sample = T> + std::marker::Send + Sync + 'static,
 {
-    type Output = Net48;
+    type Output = Net<T>;
     #[inline]
     fn bitxor(self, y: An<X>) -> Self::Output {
-        Net48::branch_op(self, Net48::wrap(Box::new(y)))
+        Net::branch_op(self, Net::wrap(T::boxed(y)))
     }
 }
 
-#[duplicate_item(
-    f48       Net48       AudioUnit48;
-    [ f64 ]   [ Net64 ]   [ AudioUnit64 ];
-    [ f32 ]   [ Net32 ]   [ AudioUnit32 ];
-)]
-impl<X> std::ops::BitXor<Net48> for An<X>
+impl<T: UnitFloat, X> std::ops::BitXor<Net<T>> for An<X>
 where
-    X: AudioNode<Sample = f48> + std::marker::Send + Sync + 'static,
+    X: AudioNode<Sample = T> + std::marker::Send + Sync + 'static,
 {
-    type Output = Net48;
+    type Output = Net<T>;
     #[inline]
-    fn bitxor(self, y: Net48) -> Self::Output {
-        Net48::branch_op(Net48::wrap(Box::new(self)), y)
+    fn bitxor(self, y: Net<T>) -> Self::Output {
+        Net::branch_op(Net::wrap(T::boxed(self)), y)
     }
 }
 
-#[duplicate_item(
-    f48       Net48       AudioUnit48;
-    [ f64 ]   [ Net64 ]   [ AudioUnit64 ];
-    [ f32 ]   [ Net32 ]   [ AudioUnit32 ];
-)]
-impl std::ops::Add<Net48> for Net48 {
-    type Output = Net48;
+impl<T: UnitFloat> std::ops::Add<Net<T>> for Net<T> {
+    type Output = Net<T>;
     #[inline]
-    fn add(self, y: Net48) -> Self::Output {
-        Net48::bin_op(self, y, FrameAdd::new())
+    fn add(self, y: Net<T>) -> Self::Output {
+        Net::bin_op(self, y, FrameAdd::new())
     }
 }
 
-#[duplicate_item(
-    f48       Net48       AudioUnit48;
-    [ f64 ]   [ Net64 ]   [ AudioUnit64 ];
-    [ f32 ]   [ Net32 ]   [ AudioUnit32 ];
-)]
-impl<X> std::ops::Add<An<X>> for Net48
+impl<T: UnitFloat, X> std::ops::Add<An<X>> for Net<T>
 where
-    X: AudioNode<Sample = f48> + std::marker::Send + Sync + 'static,
+    X: AudioNode<Sample = T> + std::marker::Send + Sync + 'static,
 {
-    type Output = Net48;
+    type Output = Net<T>;
     #[inline]
     fn add(self, y: An<X>) -> Self::Output {
-        Net48::bin_op(self, Net48::wrap(Box::new(y)), FrameAdd::new())
+        Net::bin_op(self, Net::wrap(T::boxed(y)), FrameAdd::new())
+    }
+}
+
+impl<T: UnitFloat, X> std::ops::Add<Net<T>> for An<X>
+where
+    X: AudioNode<Sample = T> + std::marker::Send + Sync + 'static,
+{
+    type Output = Net<T>;
+    #[inline]
+    fn add(self, y: Net<T>) -> Self::Output {
+        Net::bin_op(Net::wrap(T::boxed(self)), y, FrameAdd::new())
+    }
+}
+
+impl<T: UnitFloat> std::ops::Sub<Net<T>> for Net<T> {
+    type Output = Net<T>;
+    #[inline]
+    fn sub(self, y: Net<T>) -> Self::Output {
+        Net::bin_op(self, y, FrameSub::new())
+    }
+}
+
+impl<T: UnitFloat, X> std::ops::Sub<An<X>> for Net<T>
+where
+    X: AudioNode<Sample = T> + std::marker::Send + Sync + 'static,
+{
+    type Output = Net<T>;
+    #[inline]
+    fn sub(self, y: An<X>) -> Self::Output {
+        Net::bin_op(self, Net::wrap(T::boxed(y)), FrameSub::new())
+    }
+}
+
+impl<T: UnitFloat, X> std::ops::Sub<Net<T>> for An<X>
+where
+    X: AudioNode<Sample = T> + std::marker::Send + Sync + 'static,
+{
+    type Output = Net<T>;
+    #[inline]
+    fn sub(self, y: Net<T>) -> Self::Output {
+        Net::bin_op(Net::wrap(T::boxed(self)), y, FrameSub::new())
+    }
+}
+
+impl<T: UnitFloat> std::ops::Mul<Net<T>> for Net<T> {
+    type Output = Net<T>;
+    #[inline]
+    fn mul(self, y: Net<T>) -> Self::Output {
+        Net::bin_op(self, y, FrameMul::new())
+    }
+}
+
+impl<T: UnitFloat, X> std::ops::Mul<An<X>> for Net<T>
+where
+    X: AudioNode<Sample = T> + std::marker::Send + Sync + 'static,
+{
+    type Output = Net<T>;
+    #[inline]
+    fn mul(self, y: An<X>) -> Self::Output {
+        Net::bin_op(self, Net::wrap(T::boxed(y)), FrameMul::new())
+    }
+}
+
+impl<T: UnitFloat, X> std::ops::Mul<Net<T>> for An<X>
+where
+    X: AudioNode<Sample = T> + std::marker::Send + Sync + 'static,
+{
+    type Output = Net<T>;
+    #[inline]
+    fn mul(self, y: Net<T>) -> Self::Output {
+        Net::bin_op(Net::wrap(T::boxed(self)), y, FrameMul::new())
+    }
+}
+
+impl<T: UnitFloat> From<Box<T::Unit>> for Net<T> {
+    fn from(unit: Box<T::Unit>) -> Self {
+        Net::wrap(unit)
+    }
+}
+
+impl<T: UnitFloat> std::ops::Shr<Box<T::Unit>> for Net<T> {
+    type Output = Net<T>;
+    #[inline]
+    fn shr(self, y: Box<T::Unit>) -> Self::Output {
+        Net::pipe_op(self, Net::wrap(y))
+    }
+}
+
+impl<T: UnitFloat> std::ops::BitAnd<Box<T::Unit>> for Net<T> {
+    type Output = Net<T>;
+    #[inline]
+    fn bitand(self, y: Box<T::Unit>) -> Self::Output {
+        Net::bus_op(self, Net::wrap(y))
+    }
+}
+
+impl<T: UnitFloat> std::ops::BitOr<Box<T::Unit>> for Net<T> {
+    type Output = Net<T>;
+    #[inline]
+    fn bitor(self, y: Box<T::Unit>) -> Self::Output {
+        Net::stack_op(self, Net::wrap(y))
+    }
+}
+
+impl<T: UnitFloat> std::ops::BitXor<Box<T::Unit>> for Net<T> {
+    type Output = Net<T>;
+    #[inline]
+    fn bitxor(self, y: Box<T::Unit>) -> Self::Output {
+        Net::branch_op(self, Net::wrap(y))
+    }
+}
+
+impl<T: UnitFloat> std::ops::Add<Box<T::Unit>> for Net<T> {
+    type Output = Net<T>;
+    #[inline]
+    fn add(self, y: Box<T::Unit>) -> Self::Output {
+        Net::bin_op(self, Net::wrap(y), FrameAdd::new())
+    }
+}
+
+impl<T: UnitFloat> std::ops::Sub<Box<T::Unit>> for Net<T> {
+    type Output = Net<T>;
+    #[inline]
+    fn sub(self, y: Box<T::Unit>) -> Self::Output {
+        Net::bin_op(self, Net::wrap(y), FrameSub::new())
+    }
+}
+
+impl<T: UnitFloat> std::ops::Mul<Box<T::Unit>> for Net<T> {
+    type Output = Net<T>;
+    #[inline]
+    fn mul(self, y: Box<T::Unit>) -> Self::Output {
+        Net::bin_op(self, Net::wrap(y), FrameMul::new())
     }
 }
 
+// Operators with a scalar operand, or with a boxed unit on the left, are implemented
+// per sample type: a generic scalar on the right would overlap with the operators above,
+// and the orphan rule forbids generic implementations for a bare `T` or `Box<T::Unit>` on the left.
 #[duplicate_item(
     f48       Net48       AudioUnit48;
     [ f64 ]   [ Net64 ]   [ AudioUnit64 ];
     [ f32 ]   [ Net32 ]   [ AudioUnit32 ];
 )]
-impl<X> std::ops::Add<Net48> for An<X>
-where
-    X: AudioNode<Sample = f48> + std::marker::Send + Sync + 'static,
-{
+impl std::ops::Add<f48> for Net48 {
     type Output = Net48;
     #[inline]
-    fn add(self, y: Net48) -> Self::Output {
-        Net48::bin_op(Net48::wrap(Box::new(self)), y, FrameAdd::new())
+    fn add(self, y: f48) -> Self::Output {
+        let n = self.outputs();
+        self + Net48::scalar(n, y)
     }
 }
 
@@ -1759,11 +4263,12 @@ where
     [ f64 ]   [ Net64 ]   [ AudioUnit64 ];
     [ f32 ]   [ Net32 ]   [ AudioUnit32 ];
 )]
-impl std::ops::Sub<Net48> for Net48 {
+impl std::ops::Add<Net48> for f48 {
     type Output = Net48;
     #[inline]
-    fn sub(self, y: Net48) -> Self::Output {
-        Net48::bin_op(self, y, FrameSub::new())
+    fn add(self, y: Net48) -> Self::Output {
+        let n = y.outputs();
+        Net48::scalar(n, self) + y
     }
 }
 
@@ -1772,14 +4277,12 @@ impl std::ops::Sub<Net48> for Net48 {
     [ f64 ]   [ Net64 ]   [ AudioUnit64 ];
     [ f32 ]   [ Net32 ]   [ AudioUnit32 ];
 )]
-impl<X> std::ops::Sub<An<X>> for Net48
-where
-    X: AudioNode<Sample = f48> + std::marker::Send + Sync + 'static,
-{
+impl std::ops::Sub<f48> for Net48 {
     type Output = Net48;
     #[inline]
-    fn sub(self, y: An<X>) -> Self::Output {
-        Net48::bin_op(self, Net48::wrap(Box::new(y)), FrameSub::new())
+    fn sub(self, y: f48) -> Self::Output {
+        let n = self.outputs();
+        self - Net48::scalar(n, y)
     }
 }
 
@@ -1788,14 +4291,12 @@ where
     [ f64 ]   [ Net64 ]   [ AudioUnit64 ];
     [ f32 ]   [ Net32 ]   [ AudioUnit32 ];
 )]
-impl<X> std::ops::Sub<Net48> for An<X>
-where
-    X: AudioNode<Sample = f48> + std::marker::Send + Sync + 'static,
-{
+impl std::ops::Sub<Net48> for f48 {
     type Output = Net48;
     #[inline]
     fn sub(self, y: Net48) -> Self::Output {
-        Net48::bin_op(Net48::wrap(Box::new(self)), y, FrameSub::new())
+        let n = y.outputs();
+        Net48::scalar(n, self) - y
     }
 }
 
@@ -1804,11 +4305,12 @@ where
     [ f64 ]   [ Net64 ]   [ AudioUnit64 ];
     [ f32 ]   [ Net32 ]   [ AudioUnit32 ];
 )]
-impl std::ops::Mul<Net48> for Net48 {
+impl std::ops::Mul<f48> for Net48 {
     type Output = Net48;
     #[inline]
-    fn mul(self, y: Net48) -> Self::Output {
-        Net48::bin_op(self, y, FrameMul::new())
+    fn mul(self, y: f48) -> Self::Output {
+        let n = self.outputs();
+        self * Net48::scalar(n, y)
     }
 }
 
@@ -1817,14 +4319,12 @@ impl std::ops::Mul<Net48> for Net48 {
     [ f64 ]   [ Net64 ]   [ AudioUnit64 ];
     [ f32 ]   [ Net32 ]   [ AudioUnit32 ];
 )]
-impl<X> std::ops::Mul<An<X>> for Net48
-where
-    X: AudioNode<Sample = f48> + std::marker::Send + Sync + 'static,
-{
+impl std::ops::Mul<Net48> for f48 {
     type Output = Net48;
     #[inline]
-    fn mul(self, y: An<X>) -> Self::Output {
-        Net48::bin_op(self, Net48::wrap(Box::new(y)), FrameMul::new())
+    fn mul(self, y: Net48) -> Self::Output {
+        let n = y.outputs();
+        Net48::scalar(n, self) * y
     }
 }
 
@@ -1833,14 +4333,11 @@ where
     [ f64 ]   [ Net64 ]   [ AudioUnit64 ];
     [ f32 ]   [ Net32 ]   [ AudioUnit32 ];
 )]
-impl<X> std::ops::Mul<Net48> for An<X>
-where
-    X: AudioNode<Sample = f48> + std::marker::Send + Sync + 'static,
-{
+impl std::ops::Shr<Net48> for Box<dyn AudioUnit48> {
     type Output = Net48;
     #[inline]
-    fn mul(self, y: Net48) -> Self::Output {
-        Net48::bin_op(Net48::wrap(Box::new(self)), y, FrameMul::new())
+    fn shr(self, y: Net48) -> Self::Output {
+        Net48::pipe_op(Net48::wrap(self), y)
     }
 }
 
@@ -1849,12 +4346,11 @@ where
     [ f64 ]   [ Net64 ]   [ AudioUnit64 ];
     [ f32 ]   [ Net32 ]   [ AudioUnit32 ];
 )]
-impl std::ops::Add<f48> for Net48 {
+impl std::ops::BitAnd<Net48> for Box<dyn AudioUnit48> {
     type Output = Net48;
     #[inline]
-    fn add(self, y: f48) -> Self::Output {
-        let n = self.outputs();
-        self + Net48::scalar(n, y)
+    fn bitand(self, y: Net48) -> Self::Output {
+        Net48::bus_op(Net48::wrap(self), y)
     }
 }
 
@@ -1863,12 +4359,11 @@ impl std::ops::Add<f48> for Net48 {
     [ f64 ]   [ Net64 ]   [ AudioUnit64 ];
     [ f32 ]   [ Net32 ]   [ AudioUnit32 ];
 )]
-impl std::ops::Add<Net48> for f48 {
+impl std::ops::BitOr<Net48> for Box<dyn AudioUnit48> {
     type Output = Net48;
     #[inline]
-    fn add(self, y: Net48) -> Self::Output {
-        let n = y.outputs();
-        Net48::scalar(n, self) + y
+    fn bitor(self, y: Net48) -> Self::Output {
+        Net48::stack_op(Net48::wrap(self), y)
     }
 }
 
@@ -1877,12 +4372,11 @@ impl std::ops::Add<Net48> for f48 {
     [ f64 ]   [ Net64 ]   [ AudioUnit64 ];
     [ f32 ]   [ Net32 ]   [ AudioUnit32 ];
 )]
-impl std::ops::Sub<f48> for Net48 {
+impl std::ops::BitXor<Net48> for Box<dyn AudioUnit48> {
     type Output = Net48;
     #[inline]
-    fn sub(self, y: f48) -> Self::Output {
-        let n = self.outputs();
-        self - Net48::scalar(n, y)
+    fn bitxor(self, y: Net48) -> Self::Output {
+        Net48::branch_op(Net48::wrap(self), y)
     }
 }
 
@@ -1891,12 +4385,11 @@ impl std::ops::Sub<f48> for Net48 {
     [ f64 ]   [ Net64 ]   [ AudioUnit64 ];
     [ f32 ]   [ Net32 ]   [ AudioUnit32 ];
 )]
-impl std::ops::Sub<Net48> for f48 {
+impl std::ops::Add<Net48> for Box<dyn AudioUnit48> {
     type Output = Net48;
     #[inline]
-    fn sub(self, y: Net48) -> Self::Output {
-        let n = y.outputs();
-        Net48::scalar(n, self) - y
+    fn add(self, y: Net48) -> Self::Output {
+        Net48::bin_op(Net48::wrap(self), y, FrameAdd::new())
     }
 }
 
@@ -1905,12 +4398,11 @@ impl std::ops::Sub<Net48> for f48 {
     [ f64 ]   [ Net64 ]   [ AudioUnit64 ];
     [ f32 ]   [ Net32 ]   [ AudioUnit32 ];
 )]
-impl std::ops::Mul<f48> for Net48 {
+impl std::ops::Sub<Net48> for Box<dyn AudioUnit48> {
     type Output = Net48;
     #[inline]
-    fn mul(self, y: f48) -> Self::Output {
-        let n = self.outputs();
-        self * Net48::scalar(n, y)
+    fn sub(self, y: Net48) -> Self::Output {
+        Net48::bin_op(Net48::wrap(self), y, FrameSub::new())
     }
 }
 
@@ -1919,11 +4411,16 @@ impl std::ops::Mul<f48> for Net48 {
     [ f64 ]   [ Net64 ]   [ AudioUnit64 ];
     [ f32 ]   [ Net32 ]   [ AudioUnit32 ];
 )]
-impl std::ops::Mul<Net48> for f48 {
+impl std::ops::Mul<Net48> for Box<dyn AudioUnit48> {
     type Output = Net48;
     #[inline]
     fn mul(self, y: Net48) -> Self::Output {
-        let n = y.outputs();
-        Net48::scalar(n, self) * y
+        Net48::bin_op(Net48::wrap(self), y, FrameMul::new())
     }
 }
+
+/// 64-bit network.
+pub type Net64 = Net<f64>;
+
+/// 32-bit network.
+pub type Net32 = Net<f32>;
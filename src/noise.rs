@@ -265,6 +265,13 @@ impl<T: Float> AudioNode for Hold<T> {
         self.sample_duration = 1.0 / sample_rate;
     }
 
+    fn input_kind(&self, input: usize) -> PortKind {
+        match input {
+            1 => PortKind::Frequency,
+            _ => PortKind::Audio,
+        }
+    }
+
     #[inline]
     fn tick(
         &mut self,
@@ -294,3 +301,234 @@ impl<T: Float> AudioNode for Hold<T> {
         output
     }
 }
+
+/// Noise burst excitation for physical models. A rising edge in the gate input
+/// (a transition from zero or below to above zero) starts a Hann windowed burst
+/// of lowpass filtered white noise.
+/// Setting: variability in 0...1 is the randomness in the duration and level of individual bursts.
+/// - Input 0: gate.
+/// - Output 0: noise burst.
+#[derive(Default, Clone)]
+pub struct Burst<T> {
+    rnd: Rnd,
+    hash: u64,
+    duration: f64,
+    color: T,
+    variability: T,
+    sample_rate: f64,
+    /// Lowpass filter coefficient.
+    coefficient: T,
+    /// Gain that compensates for the power lost in filtering.
+    gain: T,
+    /// Lowpass filter state.
+    state: T,
+    /// Level of the current burst.
+    level: T,
+    /// Length of the current burst in samples.
+    length: usize,
+    /// Position in the current burst in samples.
+    position: usize,
+    /// Previous gate value.
+    gate: T,
+}
+
+impl<T: Float> Burst<T> {
+    /// Create new noise burst generator. Bursts last `duration` seconds.
+    /// `color` in 0...1 sets the brightness of the noise:
+    /// the lowpass cutoff moves exponentially from 100 Hz at 0 to unfiltered white noise at 1.
+    pub fn new(duration: f64, color: T) -> Self {
+        assert!(duration > 0.0);
+        let mut node = Self {
+            duration,
+            color: clamp01(color),
+            ..Self::default()
+        };
+        node.reset();
+        node.set_sample_rate(DEFAULT_SR);
+        node
+    }
+    /// Variability is the randomness in the duration and level of individual bursts in 0...1.
+    #[inline]
+    pub fn variability(&self) -> T {
+        self.variability
+    }
+    /// Set variability. Variability is the randomness in the duration and level
+    /// of individual bursts in 0...1.
+    #[inline]
+    pub fn set_variability(&mut self, variability: T) {
+        self.variability = variability;
+    }
+    /// Start a new burst.
+    fn trigger(&mut self) {
+        let variability = self.variability.to_f64();
+        let duration = self.duration * lerp(1.0 - variability, 1.0 + variability, self.rnd.f64());
+        self.length = max(1.0, round(duration * self.sample_rate)) as usize;
+        self.level = T::from_f64(1.0 - variability * self.rnd.f64());
+        self.position = 0;
+        self.state = T::zero();
+    }
+}
+
+impl<T: Float> AudioNode for Burst<T> {
+    const ID: u64 = 99;
+    type Sample = T;
+    type Inputs = typenum::U1;
+    type Outputs = typenum::U1;
+    type Setting = T;
+
+    fn set(&mut self, setting: T) {
+        self.set_variability(setting);
+    }
+
+    fn reset(&mut self) {
+        self.rnd = Rnd::from_u64(self.hash);
+        self.state = T::zero();
+        self.length = 0;
+        self.position = 0;
+        self.gate = T::zero();
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate;
+        let color = self.color.to_f64();
+        let nyquist = 0.5 * sample_rate;
+        let cutoff = xerp(100.0, nyquist, color);
+        let coefficient = if color >= 1.0 || cutoff >= nyquist {
+            1.0
+        } else {
+            1.0 - exp(-TAU * cutoff / sample_rate)
+        };
+        self.coefficient = T::from_f64(coefficient);
+        // Ratio of output to input power of the one-pole lowpass with white noise input.
+        self.gain = T::from_f64(1.0 / sqrt(coefficient / (2.0 - coefficient)));
+    }
+
+    fn input_kind(&self, input: usize) -> PortKind {
+        match input {
+            0 => PortKind::Trigger,
+            _ => PortKind::Audio,
+        }
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        if input[0] > T::zero() && self.gate <= T::zero() {
+            self.trigger();
+        }
+        self.gate = input[0];
+        if self.position < self.length {
+            let noise = T::from_f32(self.rnd.f32_in(-1.0, 1.0));
+            self.state += (noise - self.state) * self.coefficient;
+            let window = sin(PI * (self.position as f64 + 0.5) / self.length as f64);
+            self.position += 1;
+            [self.state * self.gain * self.level * T::from_f64(window * window)].into()
+        } else {
+            [T::zero()].into()
+        }
+    }
+
+    fn set_hash(&mut self, hash: u64) {
+        self.hash = hash;
+        self.reset();
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        output[0] = input[0].distort(0.0);
+        output
+    }
+}
+
+/// Impulse train excitation for physical models. Emits single sample impulses
+/// at a fixed frequency, starting with an impulse at time zero.
+/// Setting: variability in 0...1 is the randomness in the timing and level of individual impulses.
+/// - Output 0: impulse train.
+#[derive(Default, Clone)]
+pub struct ImpulseTrain<T> {
+    rnd: Rnd,
+    hash: u64,
+    frequency: f64,
+    variability: T,
+    sample_duration: f64,
+    t: f64,
+    next_t: f64,
+}
+
+impl<T: Float> ImpulseTrain<T> {
+    /// Create new impulse train at `frequency` Hz.
+    pub fn new(frequency: f64) -> Self {
+        assert!(frequency > 0.0);
+        let mut node = Self {
+            frequency,
+            ..Self::default()
+        };
+        node.reset();
+        node.set_sample_rate(DEFAULT_SR);
+        node
+    }
+    /// Variability is the randomness in the timing and level of individual impulses in 0...1.
+    #[inline]
+    pub fn variability(&self) -> T {
+        self.variability
+    }
+    /// Set variability. Variability is the randomness in the timing and level
+    /// of individual impulses in 0...1.
+    #[inline]
+    pub fn set_variability(&mut self, variability: T) {
+        self.variability = variability;
+    }
+}
+
+impl<T: Float> AudioNode for ImpulseTrain<T> {
+    const ID: u64 = 100;
+    type Sample = T;
+    type Inputs = typenum::U0;
+    type Outputs = typenum::U1;
+    type Setting = T;
+
+    fn set(&mut self, setting: T) {
+        self.set_variability(setting);
+    }
+
+    fn reset(&mut self) {
+        self.rnd = Rnd::from_u64(self.hash);
+        self.t = 0.0;
+        self.next_t = 0.0;
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_duration = 1.0 / sample_rate;
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        _input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        // Compare against the midpoint of the sample to round impulse times to the nearest sample.
+        let output = if self.t + 0.5 * self.sample_duration > self.next_t {
+            let variability = self.variability.to_f64();
+            self.next_t +=
+                lerp(1.0 - variability, 1.0 + variability, self.rnd.f64()) / self.frequency;
+            T::from_f64(1.0 - variability * self.rnd.f64())
+        } else {
+            T::zero()
+        };
+        self.t += self.sample_duration;
+        [output].into()
+    }
+
+    fn set_hash(&mut self, hash: u64) {
+        self.hash = hash;
+        self.reset();
+    }
+
+    fn route(&mut self, _input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        output[0] = Signal::Latency(0.0);
+        output
+    }
+}
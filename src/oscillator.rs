@@ -61,6 +61,13 @@ impl<T: Real> AudioNode for Sine<T> {
         self.sample_duration = convert(1.0 / sample_rate);
     }
 
+    fn input_kind(&self, input: usize) -> PortKind {
+        match input {
+            0 => PortKind::Frequency,
+            _ => PortKind::Audio,
+        }
+    }
+
     #[inline]
     fn tick(
         &mut self,
@@ -71,7 +78,7 @@ impl<T: Real> AudioNode for Sine<T> {
         while self.phase > T::one() {
             self.phase -= T::one();
         }
-        [sin(self.phase * T::from_f64(TAU))].into()
+        [sin_dsp(self.phase * T::from_f64(TAU))].into()
     }
 
     fn process(
@@ -82,7 +89,7 @@ impl<T: Real> AudioNode for Sine<T> {
     ) {
         for i in 0..size {
             self.phase += input[0][i] * self.sample_duration;
-            output[0][i] = sin(self.phase * T::from_f64(TAU));
+            output[0][i] = sin_dsp(self.phase * T::from_f64(TAU));
         }
         self.phase -= self.phase.floor();
     }
@@ -172,6 +179,14 @@ impl<T: Real, N: Size<T>> AudioNode for Dsf<T, N> {
         self.sample_duration = convert(1.0 / sample_rate);
     }
 
+    fn input_kind(&self, input: usize) -> PortKind {
+        match input {
+            0 => PortKind::Frequency,
+            1 => PortKind::Control,
+            _ => PortKind::Audio,
+        }
+    }
+
     #[inline]
     fn tick(
         &mut self,
@@ -204,6 +219,7 @@ impl<T: Real, N: Size<T>> AudioNode for Dsf<T, N> {
 
 /// Karplus-Strong oscillator.
 /// Allocates: pluck buffer.
+/// Setting: (gain per second, high frequency damping).
 /// - Input 0: extra string excitation.
 /// - Output 0: plucked string.
 #[derive(Clone)]
@@ -235,6 +251,14 @@ impl<T: Float> Pluck<T> {
         }
     }
 
+    /// Set string damping. Gain per second is the amplitude left after one second
+    /// and high frequency damping is in 0...1. The string is not re-plucked.
+    pub fn set_damping(&mut self, gain_per_second: T, high_frequency_damping: T) {
+        self.gain = T::from_f64(pow(gain_per_second.to_f64(), 1.0 / self.frequency.to_f64()));
+        let damping = super::prelude::fir3(T::one() - high_frequency_damping);
+        self.damping.set(*damping.weights());
+    }
+
     fn initialize_line(&mut self) {
         // Allpass filter delay is in epsilon ... epsilon + 1.
         let epsilon = 0.2;
@@ -266,7 +290,11 @@ impl<T: Float> AudioNode for Pluck<T> {
     type Sample = T;
     type Inputs = typenum::U1;
     type Outputs = typenum::U1;
-    type Setting = ();
+    type Setting = (T, T);
+
+    fn set(&mut self, setting: Self::Setting) {
+        self.set_damping(setting.0, setting.1);
+    }
 
     fn reset(&mut self) {
         self.damping.reset();
@@ -357,6 +385,13 @@ impl<T: Float> AudioNode for Rossler<T> {
         self.sr = convert(sample_rate);
     }
 
+    fn input_kind(&self, input: usize) -> PortKind {
+        match input {
+            0 => PortKind::Frequency,
+            _ => PortKind::Audio,
+        }
+    }
+
     #[inline]
     fn tick(
         &mut self,
@@ -423,6 +458,13 @@ impl<T: Float> AudioNode for Lorenz<T> {
         self.sr = convert(sample_rate);
     }
 
+    fn input_kind(&self, input: usize) -> PortKind {
+        match input {
+            0 => PortKind::Frequency,
+            _ => PortKind::Audio,
+        }
+    }
+
     #[inline]
     fn tick(
         &mut self,
@@ -449,3 +491,319 @@ impl<T: Float> AudioNode for Lorenz<T> {
         output
     }
 }
+
+/// Pitch synchronous sub-octave generator. Tracks the period of the input
+/// from its positive zero crossings and synthesizes layers one and two octaves below,
+/// following the amplitude envelope of the input.
+/// The layers are sine waves by default; see `set_square`.
+/// Setting: (level of -1 octave layer, level of -2 octave layer).
+/// - Input 0: audio
+/// - Output 0: audio mixed with sub-octave layers
+#[derive(Clone)]
+pub struct SubOctave<T: Real> {
+    mix1: T,
+    mix2: T,
+    square: bool,
+    sample_rate: f64,
+    // Lowpass coefficient and state of the zero crossing detector.
+    detect_coeff: f64,
+    detect: f64,
+    // Envelope follower coefficient and two stage state.
+    smoothing: f64,
+    rectified: f64,
+    envelope: f64,
+    armed: bool,
+    // Samples since the latest crossing.
+    since: f64,
+    // Latest period estimate in samples.
+    period: f64,
+    // Crossing counter modulo 4.
+    count: u32,
+    phase1: f64,
+    phase2: f64,
+}
+
+impl<T: Real> SubOctave<T> {
+    /// Create new sub-octave generator with layer levels `mix1` (-1 octave) and `mix2` (-2 octaves).
+    pub fn new(mix1: T, mix2: T) -> Self {
+        let mut node = SubOctave {
+            mix1,
+            mix2,
+            square: false,
+            sample_rate: DEFAULT_SR,
+            detect_coeff: 0.0,
+            detect: 0.0,
+            smoothing: 0.0,
+            rectified: 0.0,
+            envelope: 0.0,
+            armed: false,
+            since: 0.0,
+            period: 0.0,
+            count: 0,
+            phase1: 0.0,
+            phase2: 0.0,
+        };
+        node.set_sample_rate(DEFAULT_SR);
+        node
+    }
+
+    /// Synthesize square wave (`true`) or sine wave (`false`) layers.
+    #[inline]
+    pub fn set_square(&mut self, square: bool) {
+        self.square = square;
+    }
+
+    /// Set layer levels for -1 octave (`mix1`) and -2 octaves (`mix2`).
+    #[inline]
+    pub fn set_mix(&mut self, mix1: T, mix2: T) {
+        self.mix1 = mix1;
+        self.mix2 = mix2;
+    }
+
+    #[inline]
+    fn layer(&self, phase: f64) -> f64 {
+        let x = sin_dsp(phase * TAU);
+        if self.square {
+            signum(x)
+        } else {
+            x
+        }
+    }
+}
+
+impl<T: Real> AudioNode for SubOctave<T> {
+    const ID: u64 = 93;
+    type Sample = T;
+    type Inputs = typenum::U1;
+    type Outputs = typenum::U1;
+    type Setting = (T, T);
+
+    fn reset(&mut self) {
+        self.detect = 0.0;
+        self.rectified = 0.0;
+        self.envelope = 0.0;
+        self.armed = false;
+        self.since = 0.0;
+        self.period = self.sample_rate / 100.0;
+        self.count = 0;
+        self.phase1 = 0.0;
+        self.phase2 = 0.0;
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate;
+        // The detector keeps the fundamental of bass and guitar signals while suppressing overtones.
+        self.detect_coeff = 1.0 - exp(-TAU * 800.0 / sample_rate);
+        self.smoothing = 1.0 - exp(-1.0 / (0.010 * sample_rate));
+        self.reset();
+    }
+
+    fn set(&mut self, setting: Self::Setting) {
+        self.set_mix(setting.0, setting.1);
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let x = input[0].to_f64();
+        let previous = self.detect;
+        self.detect += (x - self.detect) * self.detect_coeff;
+        self.rectified += (abs(x) - self.rectified) * self.smoothing;
+        self.envelope += (self.rectified - self.envelope) * self.smoothing;
+        self.since += 1.0;
+
+        // Hysteresis prevents noise from triggering spurious crossings.
+        if self.detect < -0.1 * self.envelope {
+            self.armed = true;
+        }
+        if self.armed && previous < 0.0 && self.detect >= 0.0 {
+            self.armed = false;
+            // Time elapsed since the interpolated crossing point, in samples.
+            let ago = self.detect / (self.detect - previous);
+            let period = self.since - ago;
+            if period >= self.sample_rate / 2000.0 && period <= self.sample_rate / 20.0 {
+                self.period = period;
+            }
+            self.since = ago;
+            self.count = (self.count + 1) & 3;
+            self.phase1 = (self.count & 1) as f64 * 0.5 + ago * 0.5 / self.period;
+            self.phase2 = self.count as f64 * 0.25 + ago * 0.25 / self.period;
+        } else {
+            self.phase1 = (self.phase1 + 0.5 / self.period).fract();
+            self.phase2 = (self.phase2 + 0.25 / self.period).fract();
+        }
+
+        // The mean absolute value of a sine wave is 2 / pi times its peak.
+        let amplitude = self.envelope * PI * 0.5;
+        let sub1 = self.layer(self.phase1) * amplitude;
+        let sub2 = self.layer(self.phase2) * amplitude;
+        [input[0] + self.mix1 * convert(sub1) + self.mix2 * convert(sub2)].into()
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        Routing::Arbitrary.propagate(input, self.outputs())
+    }
+}
+
+/// Phase distortion curves.
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub enum PdCurve {
+    /// Sawtooth. The rising half of the cycle is compressed toward the start of the cycle.
+    #[default]
+    Saw,
+    /// Square wave. Each half of the cycle is compressed, holding the waveform at its extremes.
+    Square,
+    /// Pulse. The cycle is compressed toward its start, holding the waveform at its maximum.
+    Pulse,
+    /// Resonance. A cosine at a raised frequency is windowed by a falling ramp.
+    /// Produces a resonant, filter sweep like sound as amount increases.
+    Resonance,
+}
+
+impl PdCurve {
+    /// Value of the waveform at `phase` in 0...1 with distortion `amount` in 0...1.
+    /// Amount 0 produces a cosine wave, except for the resonance curve,
+    /// which produces a cosine windowed by a falling ramp.
+    #[inline]
+    pub fn at<T: Real>(&self, phase: T, amount: T) -> T {
+        let half = T::from_f64(0.5);
+        // Limit the steepness of the distorted phase.
+        let amount = clamp01(amount) * T::from_f64(0.98);
+        let warped = match self {
+            PdCurve::Saw => {
+                let knee = half - half * amount;
+                if phase < knee {
+                    phase * half / knee
+                } else {
+                    half + (phase - knee) * half / (T::one() - knee)
+                }
+            }
+            PdCurve::Square => {
+                let knee = half - half * amount;
+                if phase < half {
+                    min(half, phase * half / knee)
+                } else {
+                    half + min(half, (phase - half) * half / knee)
+                }
+            }
+            PdCurve::Pulse => min(T::one(), phase / (T::one() - amount)),
+            PdCurve::Resonance => {
+                let ratio = T::one() + amount * T::new(15);
+                return (T::one() - phase) * cos_dsp(phase * ratio * T::from_f64(TAU));
+            }
+        };
+        cos_dsp(warped * T::from_f64(TAU))
+    }
+}
+
+/// Phase distortion oscillator in the style of the Casio CZ series.
+/// Number of inputs is `N`, either 1 or 2.
+/// The oscillator is not bandlimited.
+/// Setting: amount.
+/// - Input 0: frequency in Hz.
+/// - Input 1 (optional): distortion amount in 0...1.
+/// - Output 0: phase distorted wave.
+#[derive(Clone)]
+pub struct PhaseDistortion<T: Real, N: Size<T>> {
+    curve: PdCurve,
+    amount: T,
+    phase: T,
+    sample_duration: T,
+    hash: u64,
+    _marker: PhantomData<N>,
+}
+
+impl<T: Real, N: Size<T>> PhaseDistortion<T, N> {
+    /// Create new phase distortion oscillator with distortion `amount` in 0...1.
+    pub fn new(sample_rate: f64, curve: PdCurve, amount: T) -> Self {
+        let mut node = PhaseDistortion {
+            curve,
+            amount,
+            phase: T::zero(),
+            sample_duration: T::zero(),
+            hash: 0,
+            _marker: PhantomData,
+        };
+        node.reset();
+        node.set_sample_rate(sample_rate);
+        node
+    }
+
+    /// Distortion curve.
+    #[inline]
+    pub fn curve(&self) -> PdCurve {
+        self.curve
+    }
+
+    /// Set distortion curve.
+    #[inline]
+    pub fn set_curve(&mut self, curve: PdCurve) {
+        self.curve = curve;
+    }
+
+    /// Distortion amount in 0...1.
+    #[inline]
+    pub fn amount(&self) -> T {
+        self.amount
+    }
+
+    /// Set distortion amount in 0...1.
+    #[inline]
+    pub fn set_amount(&mut self, amount: T) {
+        self.amount = amount;
+    }
+}
+
+impl<T: Real, N: Size<T>> AudioNode for PhaseDistortion<T, N> {
+    const ID: u64 = 112;
+    type Sample = T;
+    type Inputs = N;
+    type Outputs = typenum::U1;
+    type Setting = T;
+
+    fn set(&mut self, setting: Self::Setting) {
+        self.set_amount(setting);
+    }
+
+    fn reset(&mut self) {
+        self.phase = T::from_f64(rnd(self.hash as i64));
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_duration = convert(1.0 / sample_rate);
+    }
+
+    fn input_kind(&self, input: usize) -> PortKind {
+        match input {
+            0 => PortKind::Frequency,
+            1 => PortKind::Control,
+            _ => PortKind::Audio,
+        }
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        if N::USIZE > 1 {
+            self.amount = input[1];
+        }
+        self.phase += input[0] * self.sample_duration;
+        self.phase -= self.phase.floor();
+        [self.curve.at(self.phase, self.amount)].into()
+    }
+
+    fn set_hash(&mut self, hash: u64) {
+        self.hash = hash;
+        self.reset();
+    }
+
+    fn route(&mut self, _input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        output[0] = Signal::Latency(0.0);
+        output
+    }
+}
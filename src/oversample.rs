@@ -4,14 +4,16 @@ use super::audionode::*;
 use super::math::*;
 use super::signal::*;
 use super::*;
+use num_complex::Complex64;
 use numeric_array::typenum::*;
+use std::any::Any;
 
 // Coefficients from https://fiiir.com/, a linear phase Kaiser windowed filter with
 // normalized frequency cutoff 0.22, transition band 0.06 and 80 dB stopband attenuation.
 // Gain is -1.5 dB at 0.21 (18522 Hz @ 88.2 kHz) and -79 dB at 0.25.
-const _HALFBAND_LINEAR_LEN: usize = 85;
+const HALFBAND_LINEAR_LEN: usize = 85;
 #[allow(clippy::excessive_precision)]
-const _HALFBAND_LINEAR: [f32; _HALFBAND_LINEAR_LEN] = [
+const HALFBAND_LINEAR: [f32; HALFBAND_LINEAR_LEN] = [
     0.000020220200441046,
     0.000004861974285292,
     -0.000061492255405391,
@@ -151,36 +153,74 @@ const HALFBAND_MIN: [f32; HALFBAND_MIN_LEN] = [
     -9.41945265e-04,
 ];
 
+/// Latency of the linear phase oversampler in samples at the outer sample rate.
+/// Both the upsampling and the downsampling filters delay the signal by half their length
+/// at the inner sample rate.
+pub const OVERSAMPLE_LINEAR_LATENCY: usize = (HALFBAND_LINEAR_LEN - 1) / 2;
+
 #[inline]
-fn tick_even<T: Float>(v: &Frame<T, U128>, j: usize) -> T {
-    let j = j + 0x80 - HALFBAND_MIN_LEN;
+fn tick_even<T: Float>(v: &Frame<T, U128>, j: usize, h: &[f32]) -> T {
+    let j = j + 0x80 - h.len();
     let mut output = T::zero();
-    for i in 0..HALFBAND_MIN_LEN / 2 + 1 {
-        output += v[(j + i * 2) & 0x7f] * T::from_f32(HALFBAND_MIN[i * 2]);
+    for i in 0..h.len() / 2 + 1 {
+        output += v[(j + i * 2) & 0x7f] * T::from_f32(h[h.len() - 1 - i * 2]);
     }
     output * T::new(2)
 }
 
 #[inline]
-fn tick_odd<T: Float>(v: &Frame<T, U128>, j: usize) -> T {
-    let j = j + 0x80 - HALFBAND_MIN_LEN;
+fn tick_odd<T: Float>(v: &Frame<T, U128>, j: usize, h: &[f32]) -> T {
+    let j = j + 0x80 - h.len();
     let mut output = T::zero();
-    for i in 0..HALFBAND_MIN_LEN / 2 {
-        output += v[(j + i * 2 + 1) & 0x7f] * T::from_f32(HALFBAND_MIN[i * 2 + 1]);
+    for i in 0..h.len() / 2 {
+        output += v[(j + i * 2 + 1) & 0x7f] * T::from_f32(h[h.len() - 2 - i * 2]);
     }
     output * T::new(2)
 }
 
 #[inline]
-fn tick<T: Float>(v: &Frame<T, U128>, j: usize) -> T {
-    let j = j + 0x80 - HALFBAND_MIN_LEN;
+fn tick<T: Float>(v: &Frame<T, U128>, j: usize, h: &[f32]) -> T {
+    let j = j + 0x80 - h.len();
     let mut output = T::zero();
-    for i in 0..HALFBAND_MIN_LEN {
-        output += v[(j + i) & 0x7f] * T::from_f32(HALFBAND_MIN[i]);
+    for i in 0..h.len() {
+        output += v[(j + i) & 0x7f] * T::from_f32(h[h.len() - 1 - i]);
     }
     output
 }
 
+/// Frequency response of FIR filter `h` at normalized frequency `f` (in cycles per sample).
+fn fir_response(h: &[f32], f: f64) -> Complex64 {
+    h.iter()
+        .enumerate()
+        .fold(Complex64::new(0.0, 0.0), |acc, (n, &x)| {
+            acc + Complex64::from_polar(x as f64, -TAU * f * n as f64)
+        })
+}
+
+/// Group delay of FIR filter `h` at DC in samples.
+fn fir_dc_delay(h: &[f32]) -> f64 {
+    let sum: f64 = h.iter().map(|&x| x as f64).sum();
+    let moment: f64 = h
+        .iter()
+        .enumerate()
+        .map(|(n, &x)| n as f64 * x as f64)
+        .sum();
+    moment / sum
+}
+
+/// Multiply signal latency by `factor`. Used to convert between sample rates.
+fn scale_latency(signal: Signal, factor: f64) -> Signal {
+    match signal {
+        Signal::Latency(l) => Signal::Latency(l * factor),
+        Signal::Response(response, l) => Signal::Response(response, l * factor),
+        x => x,
+    }
+}
+
+/// 2x oversampler. By default, minimum phase halfband filters are used,
+/// which have a low latency that depends on frequency: about 4 samples at low frequencies.
+/// In linear phase mode, latency is `OVERSAMPLE_LINEAR_LATENCY` (42) samples at all frequencies.
+/// Latency of the enclosed node is halved as it is measured at the inner sample rate.
 #[derive(Clone)]
 pub struct Oversampler<T, X>
 where
@@ -195,6 +235,8 @@ where
     inv: Frame<Frame<T, U128>, X::Inputs>,
     outv: Frame<Frame<T, U128>, X::Outputs>,
     j: usize,
+    linear_phase: bool,
+    sample_rate: f64,
 }
 
 impl<T, X> Oversampler<T, X>
@@ -217,9 +259,24 @@ where
             inv: Frame::default(),
             outv: Frame::default(),
             j: 0,
+            linear_phase: false,
+            sample_rate,
         }
     }
 
+    /// Create new oversampler with linear phase filters. 2x oversamples enclosed node.
+    /// Latency is `OVERSAMPLE_LINEAR_LATENCY` samples plus half the latency of the node.
+    pub fn new_linear_phase(sample_rate: f64, node: X) -> Self {
+        let mut oversampler = Self::new(sample_rate, node);
+        oversampler.linear_phase = true;
+        oversampler
+    }
+
+    /// Whether linear phase filters are in use.
+    pub fn linear_phase(&self) -> bool {
+        self.linear_phase
+    }
+
     // Access enclosed node.
     pub fn node(&self) -> &X {
         &self.x
@@ -229,6 +286,16 @@ where
     pub fn node_mut(&mut self) -> &mut X {
         &mut self.x
     }
+
+    /// Halfband filter coefficients in use.
+    #[inline]
+    fn coefficients(&self) -> &'static [f32] {
+        if self.linear_phase {
+            &HALFBAND_LINEAR
+        } else {
+            &HALFBAND_MIN
+        }
+    }
 }
 
 impl<T, X> AudioNode for Oversampler<T, X>
@@ -253,6 +320,7 @@ where
     }
 
     fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate;
         let inner_sr = sample_rate * 2.0;
         self.x.set_sample_rate(inner_sr);
     }
@@ -262,11 +330,12 @@ where
         &mut self,
         input: &Frame<Self::Sample, Self::Inputs>,
     ) -> Frame<Self::Sample, Self::Outputs> {
+        let h = self.coefficients();
         for channel in 0..Self::Inputs::USIZE {
             self.inv[channel][self.j] = input[channel];
         }
         let over_input: Frame<T, Self::Inputs> =
-            Frame::generate(|channel| tick_even(&self.inv[channel], self.j + 1));
+            Frame::generate(|channel| tick_even(&self.inv[channel], self.j + 1, h));
         let over_output = self.x.tick(&over_input);
         for channel in 0..Self::Outputs::USIZE {
             self.outv[channel][self.j] = over_output[channel];
@@ -276,25 +345,189 @@ where
             self.inv[channel][self.j] = T::zero();
         }
         let over_input2: Frame<T, Self::Inputs> =
-            Frame::generate(|channel| tick_odd(&self.inv[channel], self.j + 1));
+            Frame::generate(|channel| tick_odd(&self.inv[channel], self.j + 1, h));
         let over_output2 = self.x.tick(&over_input2);
         for channel in 0..Self::Outputs::USIZE {
             self.outv[channel][self.j] = over_output2[channel];
         }
         let output: Frame<T, Self::Outputs> =
-            Frame::generate(|channel| tick(&self.outv[channel], self.j));
+            Frame::generate(|channel| tick(&self.outv[channel], self.j, h));
         self.j = (self.j + 1) & 0x7f;
         output
     }
 
     fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
-        self.x.route(input, frequency)
+        // Latencies of the enclosed node are measured at the inner sample rate.
+        let mut inner = new_signal_frame(Self::Inputs::USIZE);
+        for channel in 0..Self::Inputs::USIZE {
+            inner[channel] = scale_latency(input[channel], 2.0);
+        }
+        let inner = self.x.route(&inner, frequency);
+        let h = self.coefficients();
+        let latency = if self.linear_phase {
+            OVERSAMPLE_LINEAR_LATENCY as f64
+        } else {
+            fir_dc_delay(h)
+        };
+        // The signal goes through the upsampling and the downsampling filter.
+        let response = fir_response(h, frequency / (2.0 * self.sample_rate));
+        let response = response * response;
+        let mut output = new_signal_frame(Self::Outputs::USIZE);
+        for channel in 0..Self::Outputs::USIZE {
+            output[channel] = match scale_latency(inner[channel], 0.5) {
+                Signal::Response(r, l) => Signal::Response(r * response, l),
+                x => x,
+            };
+            // Apply filter latency without changing the response, which already includes the delay.
+            output[channel] = output[channel].delay(latency);
+        }
+        output
+    }
+
+    fn ping(&mut self, probe: bool, hash: AttoHash) -> AttoHash {
+        self.x.ping(probe, hash.hash(Self::ID))
+    }
+
+    fn visit(&mut self, tag: u64, f: &mut dyn FnMut(&mut dyn Any)) {
+        self.x.visit(tag, f);
+    }
+
+    fn has_side_effects(&self) -> bool {
+        self.x.has_side_effects()
+    }
+
+    fn input_kind(&self, input: usize) -> PortKind {
+        self.x.input_kind(input)
+    }
+
+    fn output_kind(&self, output: usize) -> PortKind {
+        self.x.output_kind(output)
+    }
+
+    fn allocate(&mut self) {
+        self.x.allocate();
+    }
+}
+
+/// Reference path for A/B comparisons with a linear phase oversampler.
+/// Processes the enclosed node at the base sample rate and delays its outputs
+/// by `OVERSAMPLE_LINEAR_LATENCY` samples, aligning them with the oversampled outputs.
+#[derive(Clone)]
+pub struct OversampleReference<T, X>
+where
+    T: Float,
+    X: AudioNode<Sample = T>,
+    X::Outputs: Size<T>,
+    X::Outputs: Size<Frame<T, U128>>,
+{
+    x: X,
+    outv: Frame<Frame<T, U128>, X::Outputs>,
+    j: usize,
+    sample_rate: f64,
+}
+
+impl<T, X> OversampleReference<T, X>
+where
+    T: Float,
+    X: AudioNode<Sample = T>,
+    X::Outputs: Size<T>,
+    X::Outputs: Size<Frame<T, U128>>,
+{
+    /// Create new reference path for enclosed node.
+    pub fn new(sample_rate: f64, mut node: X) -> Self {
+        node.set_sample_rate(sample_rate);
+        let hash = node.ping(true, AttoHash::new(Self::ID));
+        node.ping(false, hash);
+        Self {
+            x: node,
+            outv: Frame::default(),
+            j: 0,
+            sample_rate,
+        }
+    }
+
+    // Access enclosed node.
+    pub fn node(&self) -> &X {
+        &self.x
+    }
+
+    // Access enclosed node.
+    pub fn node_mut(&mut self) -> &mut X {
+        &mut self.x
+    }
+}
+
+impl<T, X> AudioNode for OversampleReference<T, X>
+where
+    T: Float,
+    X: AudioNode<Sample = T>,
+    X::Outputs: Size<T>,
+    X::Outputs: Size<Frame<T, U128>>,
+{
+    const ID: u64 = 102;
+    type Sample = T;
+    type Inputs = X::Inputs;
+    type Outputs = X::Outputs;
+    type Setting = ();
+
+    fn reset(&mut self) {
+        self.x.reset();
+        self.outv = Frame::default();
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate;
+        self.x.set_sample_rate(sample_rate);
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let output = self.x.tick(input);
+        let read = (self.j + 0x80 - OVERSAMPLE_LINEAR_LATENCY) & 0x7f;
+        let delayed = Frame::generate(|channel| {
+            self.outv[channel][self.j] = output[channel];
+            self.outv[channel][read]
+        });
+        self.j = (self.j + 1) & 0x7f;
+        delayed
+    }
+
+    fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
+        let mut output = self.x.route(input, frequency);
+        for channel in 0..Self::Outputs::USIZE {
+            output[channel] = output[channel].filter(OVERSAMPLE_LINEAR_LATENCY as f64, |r| {
+                r * Complex64::from_polar(
+                    1.0,
+                    -TAU * frequency / self.sample_rate * OVERSAMPLE_LINEAR_LATENCY as f64,
+                )
+            });
+        }
+        output
     }
 
     fn ping(&mut self, probe: bool, hash: AttoHash) -> AttoHash {
         self.x.ping(probe, hash.hash(Self::ID))
     }
 
+    fn visit(&mut self, tag: u64, f: &mut dyn FnMut(&mut dyn Any)) {
+        self.x.visit(tag, f);
+    }
+
+    fn has_side_effects(&self) -> bool {
+        self.x.has_side_effects()
+    }
+
+    fn input_kind(&self, input: usize) -> PortKind {
+        self.x.input_kind(input)
+    }
+
+    fn output_kind(&self, output: usize) -> PortKind {
+        self.x.output_kind(output)
+    }
+
     fn allocate(&mut self) {
         self.x.allocate();
     }
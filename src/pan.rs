@@ -4,12 +4,13 @@ use super::audionode::*;
 use super::math::*;
 use super::signal::*;
 use super::*;
+use num_complex::Complex64;
 use numeric_array::*;
 use std::marker::PhantomData;
 
 /// Return equal power pan weights for pan value in -1...1.
 #[inline]
-fn pan_weights<T: Real>(value: T) -> (T, T) {
+pub(crate) fn pan_weights<T: Real>(value: T) -> (T, T) {
     let angle = (clamp11(value) + T::one()) * T::from_f64(PI * 0.25);
     (cos(angle), sin(angle))
 }
@@ -95,3 +96,462 @@ impl<T: Real, N: Size<T>> AudioNode for Panner<T, N> {
         output
     }
 }
+
+/// Haas effect stereo placement of a mono source. One channel is delayed by a few milliseconds,
+/// which moves the apparent source toward the other channel.
+/// To keep the bass mono compatible, only frequencies above `Haas::CROSSOVER`
+/// are delayed and scaled. The third output measures mono compatibility.
+/// Allocates: the delay line.
+/// Setting: (delay in ms, level in dB).
+/// - Input 0: mono signal
+/// - Output 0: left output
+/// - Output 1: right output
+/// - Output 2: mono compatibility in 0...1
+#[derive(Clone)]
+pub struct Haas<T: Real> {
+    buffer: Vec<T>,
+    i: usize,
+    sample_rate: f64,
+    /// Delay in ms. Positive delays are applied to the right channel, negative to the left.
+    delay: T,
+    /// Amplitude of the delayed high band.
+    gain: T,
+    /// Crossover lowpass coefficient.
+    lowpass: T,
+    /// Crossover lowpass state of the direct signal.
+    low_direct: T,
+    /// Crossover lowpass state of the delayed signal.
+    low_delayed: T,
+    /// Compatibility meter smoothing coefficient.
+    smoothing: T,
+    /// Smoothed power of the mono sum.
+    mono_power: T,
+    /// Smoothed power of the stereo channels.
+    stereo_power: T,
+}
+
+impl<T: Real> Haas<T> {
+    /// Maximum delay in ms.
+    pub const MAX_DELAY: f64 = 40.0;
+    /// Crossover frequency in Hz. The delay is not applied below it.
+    pub const CROSSOVER: f64 = 200.0;
+    /// Time constant of the mono compatibility meter in seconds.
+    const METER_TIME: f64 = 0.3;
+
+    /// Create new Haas effect with `delay` in ms (-40...40) and `level` in dB of the delayed channel.
+    /// Positive delays move the source to the left, negative to the right.
+    pub fn new(delay: T, level: T) -> Self {
+        let mut node = Self {
+            buffer: vec![],
+            i: 0,
+            sample_rate: 0.0,
+            delay: T::zero(),
+            gain: T::one(),
+            lowpass: T::zero(),
+            low_direct: T::zero(),
+            low_delayed: T::zero(),
+            smoothing: T::zero(),
+            mono_power: T::zero(),
+            stereo_power: T::zero(),
+        };
+        node.set_haas(delay, level);
+        node.set_sample_rate(DEFAULT_SR);
+        node
+    }
+
+    /// Set `delay` in ms (-40...40) and `level` in dB of the delayed channel.
+    pub fn set_haas(&mut self, delay: T, level: T) {
+        let max_delay = T::from_f64(Self::MAX_DELAY);
+        self.delay = clamp(-max_delay, max_delay, delay);
+        self.gain = db_amp(level);
+    }
+
+    /// Current mono compatibility in 0...1: the power of the mono sum relative to the power
+    /// of the stereo channels. 1 is fully compatible, 0.5 is uncorrelated and 0 cancels out.
+    pub fn mono_compatibility(&self) -> T {
+        if self.stereo_power > T::from_f64(1.0e-12) {
+            min(T::one(), self.mono_power / self.stereo_power)
+        } else {
+            T::one()
+        }
+    }
+}
+
+impl<T: Real> AudioNode for Haas<T> {
+    const ID: u64 = 107;
+    type Sample = T;
+    type Inputs = typenum::U1;
+    type Outputs = typenum::U3;
+    type Setting = (T, T);
+
+    fn set(&mut self, setting: Self::Setting) {
+        self.set_haas(setting.0, setting.1);
+    }
+
+    fn reset(&mut self) {
+        self.i = 0;
+        self.buffer.fill(T::zero());
+        self.low_direct = T::zero();
+        self.low_delayed = T::zero();
+        self.mono_power = T::zero();
+        self.stereo_power = T::zero();
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        if self.sample_rate != sample_rate {
+            self.sample_rate = sample_rate;
+            let length = ceil(Self::MAX_DELAY * 0.001 * sample_rate) as usize + 2;
+            self.buffer.resize(length.next_power_of_two(), T::zero());
+            self.lowpass = T::from_f64(1.0 - exp(-TAU * Self::CROSSOVER / sample_rate));
+            self.smoothing = T::from_f64(1.0 - exp(-1.0 / (Self::METER_TIME * sample_rate)));
+            self.reset();
+        }
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let mask = self.buffer.len() - 1;
+        let x = input[0];
+        self.buffer[self.i] = x;
+        let tap = abs(self.delay) * T::from_f64(0.001 * self.sample_rate);
+        let tap_floor = unsafe { f32::to_int_unchecked::<usize>(tap.to_f32()) };
+        let tap_d = tap - T::new(tap_floor as i64);
+        let i0 = (self.i + self.buffer.len() - tap_floor) & mask;
+        let i1 = (i0 + self.buffer.len() - 1) & mask;
+        let delayed = lerp(self.buffer[i0], self.buffer[i1], tap_d);
+        self.i = (self.i + 1) & mask;
+        self.low_direct += (x - self.low_direct) * self.lowpass;
+        self.low_delayed += (delayed - self.low_delayed) * self.lowpass;
+        let wet = self.low_direct + self.gain * (delayed - self.low_delayed);
+        let (left, right) = if self.delay >= T::zero() {
+            (x, wet)
+        } else {
+            (wet, x)
+        };
+        self.mono_power += (squared(left + right) - self.mono_power) * self.smoothing;
+        self.stereo_power +=
+            (T::new(2) * (squared(left) + squared(right)) - self.stereo_power) * self.smoothing;
+        [left, right, self.mono_compatibility()].into()
+    }
+
+    fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        let a = self.lowpass.to_f64();
+        let z1 = Complex64::from_polar(1.0, -TAU * frequency / self.sample_rate);
+        let low = a / (1.0 - (1.0 - a) * z1);
+        let delay = Complex64::from_polar(1.0, -TAU * frequency * abs(self.delay.to_f64()) * 0.001);
+        let wet = low + self.gain.to_f64() * (1.0 - low) * delay;
+        let (left, right) = if self.delay >= T::zero() {
+            (Complex64::new(1.0, 0.0), wet)
+        } else {
+            (wet, Complex64::new(1.0, 0.0))
+        };
+        output[0] = input[0].filter(0.0, |r| r * left);
+        output[1] = input[0].filter(0.0, |r| r * right);
+        output[2] = input[0].distort(0.0);
+        output
+    }
+}
+
+/// Upmix strategy.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Upmix {
+    /// Channels are placed in their counterparts in the larger layout.
+    /// Mono goes to the center channel, or to left and right at -3 dB if there is no center.
+    /// Channels missing from the source layout stay silent.
+    #[default]
+    Direct,
+    /// As `Direct`, and silent front left and right channels receive the center channel at -3 dB,
+    /// while silent surround channels receive the front channel on the same side at -3 dB.
+    Spread,
+}
+
+/// Gain of -3 dB.
+const REMIX_HALF_POWER: f64 = std::f64::consts::FRAC_1_SQRT_2;
+
+/// Speaker positions of the supported channel layouts.
+/// Channel orders are: mono (M); stereo (L, R); quad (L, R, Ls, Rs);
+/// 5.1 (L, R, C, LFE, Ls, Rs); and 7.1 (L, R, C, LFE, Lss, Rss, Lrs, Rrs).
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Speaker {
+    Left,
+    Right,
+    Center,
+    Lfe,
+    LeftSurround,
+    RightSurround,
+    LeftRear,
+    RightRear,
+}
+
+/// Speakers of a layout with `channels` channels. Mono is a single center speaker.
+fn remix_layout(channels: usize) -> &'static [Speaker] {
+    use Speaker::*;
+    match channels {
+        1 => &[Center],
+        2 => &[Left, Right],
+        4 => &[Left, Right, LeftSurround, RightSurround],
+        6 => &[Left, Right, Center, Lfe, LeftSurround, RightSurround],
+        8 => &[
+            Left,
+            Right,
+            Center,
+            Lfe,
+            LeftSurround,
+            RightSurround,
+            LeftRear,
+            RightRear,
+        ],
+        _ => panic!("Unsupported channel layout with {} channels.", channels),
+    }
+}
+
+/// Mixing matrix of a single downmix step to a smaller layout with `to` channels.
+/// Rows are outputs and columns are inputs.
+fn downmix_step(from: usize, to: usize) -> (usize, Vec<Vec<f64>>) {
+    let h = REMIX_HALF_POWER;
+    match (from, to) {
+        // 7.1 to 5.1: side and rear surrounds are combined.
+        (8, _) => (
+            6,
+            vec![
+                vec![1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+                vec![0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+                vec![0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+                vec![0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0],
+                vec![0.0, 0.0, 0.0, 0.0, h, 0.0, h, 0.0],
+                vec![0.0, 0.0, 0.0, 0.0, 0.0, h, 0.0, h],
+            ],
+        ),
+        // 5.1 to quad: center is split into left and right, LFE is discarded.
+        (6, 4) => (
+            4,
+            vec![
+                vec![1.0, 0.0, h, 0.0, 0.0, 0.0],
+                vec![0.0, 1.0, h, 0.0, 0.0, 0.0],
+                vec![0.0, 0.0, 0.0, 0.0, 1.0, 0.0],
+                vec![0.0, 0.0, 0.0, 0.0, 0.0, 1.0],
+            ],
+        ),
+        // 5.1 to stereo (ITU-R BS.775). LFE is discarded.
+        (6, _) => (
+            2,
+            vec![
+                vec![1.0, 0.0, h, 0.0, h, 0.0],
+                vec![0.0, 1.0, h, 0.0, 0.0, h],
+            ],
+        ),
+        // Quad to stereo.
+        (4, _) => (2, vec![vec![1.0, 0.0, h, 0.0], vec![0.0, 1.0, 0.0, h]]),
+        // Stereo to mono.
+        (2, _) => (1, vec![vec![h, h]]),
+        _ => panic!("Cannot downmix {} channels to {} channels.", from, to),
+    }
+}
+
+/// Downmix matrix from `from` channels to `to` channels. Rows are outputs and columns are inputs.
+/// Downmixes to smaller layouts are composed of consecutive steps.
+/// For example, 5.1 to mono results in the ITU coefficients
+/// M = 0.707 L + 0.707 R + C + 0.5 Ls + 0.5 Rs.
+fn downmix_matrix(from: usize, to: usize) -> Vec<Vec<f64>> {
+    remix_layout(from);
+    remix_layout(to);
+    assert!(
+        from >= to,
+        "Cannot downmix {} channels to {} channels.",
+        from,
+        to
+    );
+    let mut matrix: Vec<Vec<f64>> = (0..from)
+        .map(|i| (0..from).map(|j| if i == j { 1.0 } else { 0.0 }).collect())
+        .collect();
+    let mut channels = from;
+    while channels > to {
+        let (next, step) = downmix_step(channels, to);
+        matrix = step
+            .iter()
+            .map(|row| {
+                (0..from)
+                    .map(|j| (0..channels).map(|k| row[k] * matrix[k][j]).sum())
+                    .collect()
+            })
+            .collect();
+        channels = next;
+    }
+    assert!(
+        channels == to,
+        "Cannot downmix {} channels to {} channels.",
+        from,
+        to
+    );
+    matrix
+}
+
+/// Upmix matrix from `from` channels to `to` channels with strategy `mode`.
+/// Rows are outputs and columns are inputs.
+fn upmix_matrix(from: usize, to: usize, mode: Upmix) -> Vec<Vec<f64>> {
+    use Speaker::*;
+    let h = REMIX_HALF_POWER;
+    let source = remix_layout(from);
+    let target = remix_layout(to);
+    assert!(
+        from <= to,
+        "Cannot upmix {} channels to {} channels.",
+        from,
+        to
+    );
+    let mut matrix = vec![vec![0.0; from]; to];
+    let position = |speaker: Speaker| target.iter().position(|&x| x == speaker);
+    for (j, &speaker) in source.iter().enumerate() {
+        match (speaker, position(speaker)) {
+            (_, Some(i)) => matrix[i][j] = 1.0,
+            // Mono to a layout without center.
+            (Center, None) => {
+                matrix[position(Left).unwrap()][j] = h;
+                matrix[position(Right).unwrap()][j] = h;
+            }
+            _ => (),
+        }
+        // Surrounds of smaller layouts are shared by side and rear surrounds.
+        if to == 8 {
+            match speaker {
+                LeftSurround => {
+                    matrix[4][j] = h;
+                    matrix[6][j] = h;
+                }
+                RightSurround => {
+                    matrix[5][j] = h;
+                    matrix[7][j] = h;
+                }
+                _ => (),
+            }
+        }
+    }
+    if mode == Upmix::Spread {
+        let direct = matrix.clone();
+        let silent = |i: usize| direct[i].iter().all(|&x| x == 0.0);
+        for (i, &speaker) in target.iter().enumerate() {
+            if !silent(i) {
+                continue;
+            }
+            let neighbor = match speaker {
+                Left | Right => position(Center),
+                LeftSurround | LeftRear => position(Left),
+                RightSurround | RightRear => position(Right),
+                _ => None,
+            };
+            if let Some(neighbor) = neighbor {
+                // Fill from the direct or already spread row of the neighbor.
+                let row: Vec<f64> = matrix[neighbor].iter().map(|&x| x * h).collect();
+                matrix[i] = row;
+            }
+        }
+    }
+    matrix
+}
+
+/// Channel count adapter that mixes `M` input channels into `N` output channels
+/// with a fixed matrix. Supported layouts are mono, stereo, quad, 5.1 and 7.1.
+/// Channel orders are: mono (M); stereo (L, R); quad (L, R, Ls, Rs);
+/// 5.1 (L, R, C, LFE, Ls, Rs); and 7.1 (L, R, C, LFE, Lss, Rss, Lrs, Rrs).
+/// - Inputs: `M` channels.
+/// - Outputs: `N` channels.
+#[derive(Clone)]
+pub struct Remix<M: Size<T>, N: Size<T>, T: Float> {
+    _marker: PhantomData<(M, N)>,
+    /// Mixing matrix. Rows are outputs and columns are inputs.
+    matrix: Vec<Vec<T>>,
+}
+
+impl<M: Size<T>, N: Size<T>, T: Float> Remix<M, N, T> {
+    /// Create downmix from `M` channels to `N` channels using ITU coefficients.
+    /// Panics if the layouts are not supported or `N` is greater than `M`.
+    pub fn downmix() -> Self {
+        Self::from_matrix(downmix_matrix(M::USIZE, N::USIZE))
+    }
+
+    /// Create upmix from `M` channels to `N` channels with strategy `mode`.
+    /// Panics if the layouts are not supported or `M` is greater than `N`.
+    pub fn upmix(mode: Upmix) -> Self {
+        Self::from_matrix(upmix_matrix(M::USIZE, N::USIZE, mode))
+    }
+
+    fn from_matrix(matrix: Vec<Vec<f64>>) -> Self {
+        Self {
+            _marker: PhantomData,
+            matrix: matrix
+                .iter()
+                .map(|row| row.iter().map(|&x| T::from_f64(x)).collect())
+                .collect(),
+        }
+    }
+
+    /// Coefficient from input `input` to output `output`.
+    pub fn coefficient(&self, output: usize, input: usize) -> T {
+        self.matrix[output][input]
+    }
+}
+
+impl<M: Size<T>, N: Size<T>, T: Float> AudioNode for Remix<M, N, T> {
+    const ID: u64 = 126;
+    type Sample = T;
+    type Inputs = M;
+    type Outputs = N;
+    type Setting = ();
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        Frame::generate(|i| {
+            self.matrix[i]
+                .iter()
+                .zip(input.iter())
+                .fold(T::zero(), |sum, (&c, &x)| sum + c * x)
+        })
+    }
+
+    fn process(
+        &mut self,
+        size: usize,
+        input: &[&[Self::Sample]],
+        output: &mut [&mut [Self::Sample]],
+    ) {
+        for (row, output) in self.matrix.iter().zip(output.iter_mut()) {
+            output[..size].fill(T::zero());
+            for (&c, input) in row.iter().zip(input.iter()) {
+                if c != T::zero() {
+                    for (y, &x) in output[..size].iter_mut().zip(input[..size].iter()) {
+                        *y += c * x;
+                    }
+                }
+            }
+        }
+    }
+
+    fn input_layout(&self) -> Option<ChannelLayout> {
+        ChannelLayout::speakers(M::USIZE)
+    }
+
+    fn output_layout(&self) -> Option<ChannelLayout> {
+        ChannelLayout::speakers(N::USIZE)
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        for (i, row) in self.matrix.iter().enumerate() {
+            output[i] = row
+                .iter()
+                .enumerate()
+                .filter(|(_, &c)| c != T::zero())
+                .fold(Signal::Value(0.0), |sum, (j, &c)| {
+                    sum.combine_linear(input[j].scale(c.to_f64()), 0.0, |x, y| x + y, |x, y| x + y)
+                });
+        }
+        output
+    }
+}
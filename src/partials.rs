@@ -0,0 +1,372 @@
+//! Sinusoidal modeling: partial tracking and resynthesis.
+
+use super::audionode::*;
+use super::math::*;
+use super::signal::*;
+use super::*;
+use num_complex::Complex64;
+use numeric_array::typenum::*;
+use rustfft::algorithm::Radix4;
+use rustfft::Fft;
+use rustfft::FftDirection;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+/// Analysis window length in samples.
+const ANALYSIS_WINDOW: usize = 2048;
+
+/// Distance between analysis frames in samples.
+const ANALYSIS_HOP: usize = 256;
+
+/// Partials shorter than this many frames are discarded as noise.
+const MIN_FRAMES: usize = 3;
+
+/// Sinusoidal partial: a trajectory of frequency and amplitude sampled once per frame.
+#[derive(Clone, Debug, Default)]
+pub struct Partial {
+    /// Index of the first frame of the partial.
+    pub start: usize,
+    /// Frequency in Hz at each frame.
+    pub frequency: Vec<f32>,
+    /// Amplitude at each frame.
+    pub amplitude: Vec<f32>,
+}
+
+impl Partial {
+    /// Index of the frame after the last frame of the partial.
+    pub fn end(&self) -> usize {
+        self.start + self.frequency.len()
+    }
+
+    /// Amplitude weighted mean frequency in Hz.
+    pub fn mean_frequency(&self) -> f64 {
+        let mut sum = 0.0;
+        let mut weight = 0.0;
+        for (f, a) in self.frequency.iter().zip(self.amplitude.iter()) {
+            sum += *f as f64 * *a as f64;
+            weight += *a as f64;
+        }
+        if weight > 0.0 {
+            sum / weight
+        } else {
+            0.0
+        }
+    }
+
+    /// Frequency and amplitude at fractional `frame`. Amplitude fades to zero
+    /// over one frame before the start and after the end of the partial,
+    /// while frequency is held.
+    pub fn at(&self, frame: f64) -> (f64, f64) {
+        let value = |i: i64| -> (f64, f64) {
+            let j = i - self.start as i64;
+            let n = self.frequency.len() as i64;
+            let k = clamp(0, n - 1, j) as usize;
+            let a = if j >= 0 && j < n {
+                self.amplitude[k] as f64
+            } else {
+                0.0
+            };
+            (self.frequency[k] as f64, a)
+        };
+        let i = floor(frame);
+        let t = frame - i;
+        let (f0, a0) = value(i as i64);
+        let (f1, a1) = value(i as i64 + 1);
+        (lerp(f0, f1, t), lerp(a0, a1, t))
+    }
+}
+
+/// Sinusoidal model of a sound as a set of partial trajectories.
+/// Obtain one with `Wave64::analyze_partials` and play it back with `resynth_partials`.
+#[derive(Clone, Debug, Default)]
+pub struct Partials {
+    /// Time between consecutive frames in seconds.
+    pub hop: f64,
+    /// Total number of frames.
+    pub frames: usize,
+    /// Partial trajectories.
+    pub partials: Vec<Partial>,
+}
+
+impl Partials {
+    /// Analyze mono `samples` at `sample_rate` Hz. At most `max_partials`
+    /// spectral peaks are tracked in each frame.
+    pub fn analyze(sample_rate: f64, samples: &[f64], max_partials: usize) -> Self {
+        let length = ANALYSIS_WINDOW;
+        let fft = Radix4::new(length, FftDirection::Forward);
+        let window: Vec<f64> = (0..length)
+            .map(|i| 0.5 - 0.5 * cos(TAU * i as f64 / length as f64))
+            .collect();
+        let mut buffer = vec![Complex64::default(); length];
+        let mut magnitude = vec![0.0; length / 2 + 1];
+        let bin_width = sample_rate / length as f64;
+        let frames = samples.len() / ANALYSIS_HOP + 1;
+        let mut partials: Vec<Partial> = Vec::new();
+        // Indices of partials that continue into the current frame.
+        let mut active: Vec<usize> = Vec::new();
+        let mut peaks: Vec<(f64, f64)> = Vec::new();
+        let mut pairs: Vec<(f64, usize, usize)> = Vec::new();
+
+        for frame in 0..frames {
+            // Frames are centered at multiples of the hop.
+            let center = (frame * ANALYSIS_HOP) as i64;
+            for (i, x) in buffer.iter_mut().enumerate() {
+                let j = center + i as i64 - (length / 2) as i64;
+                let sample = if j >= 0 && (j as usize) < samples.len() {
+                    samples[j as usize]
+                } else {
+                    0.0
+                };
+                *x = Complex64::new(sample * window[i], 0.0);
+            }
+            fft.process(&mut buffer);
+            for (m, x) in magnitude.iter_mut().zip(buffer.iter()) {
+                *m = x.norm();
+            }
+
+            // Find spectral peaks and refine them with parabolic interpolation
+            // of log magnitude. A sinusoid of amplitude A peaks at A * length / 4.
+            let loudest = magnitude.iter().fold(0.0, |a, &m| max(a, m));
+            let threshold = max(
+                loudest * db_amp(-60.0),
+                length as f64 * 0.25 * db_amp(-90.0),
+            );
+            peaks.clear();
+            for i in 1..length / 2 {
+                let m = magnitude[i];
+                if m > threshold && m > magnitude[i - 1] && m >= magnitude[i + 1] {
+                    let a = max(magnitude[i - 1], 1.0e-30).ln();
+                    let b = m.ln();
+                    let c = max(magnitude[i + 1], 1.0e-30).ln();
+                    let p = 0.5 * (a - c) / (a - 2.0 * b + c);
+                    let frequency = (i as f64 + p) * bin_width;
+                    let amplitude = exp(b - 0.25 * (a - c) * p) * 4.0 / length as f64;
+                    peaks.push((frequency, amplitude));
+                }
+            }
+            peaks.sort_by(|x, y| y.1.total_cmp(&x.1));
+            peaks.truncate(max_partials);
+
+            // Continue active partials greedily with the nearest peaks.
+            pairs.clear();
+            for (a, &p) in active.iter().enumerate() {
+                let f = *partials[p].frequency.last().unwrap() as f64;
+                for (k, peak) in peaks.iter().enumerate() {
+                    let distance = abs(peak.0 - f);
+                    if distance < f * 0.03 + bin_width {
+                        pairs.push((distance, a, k));
+                    }
+                }
+            }
+            pairs.sort_by(|x, y| x.0.total_cmp(&y.0));
+            let mut continued = vec![false; active.len()];
+            let mut claimed = vec![false; peaks.len()];
+            let mut next_active = Vec::new();
+            for &(_, a, k) in pairs.iter() {
+                if !continued[a] && !claimed[k] {
+                    continued[a] = true;
+                    claimed[k] = true;
+                    let partial = &mut partials[active[a]];
+                    partial.frequency.push(peaks[k].0 as f32);
+                    partial.amplitude.push(peaks[k].1 as f32);
+                    next_active.push(active[a]);
+                }
+            }
+            for (k, peak) in peaks.iter().enumerate() {
+                if !claimed[k] {
+                    next_active.push(partials.len());
+                    partials.push(Partial {
+                        start: frame,
+                        frequency: vec![peak.0 as f32],
+                        amplitude: vec![peak.1 as f32],
+                    });
+                }
+            }
+            active = next_active;
+        }
+
+        partials.retain(|p| p.frequency.len() >= MIN_FRAMES);
+        Self {
+            hop: ANALYSIS_HOP as f64 / sample_rate,
+            frames,
+            partials,
+        }
+    }
+
+    /// Duration in seconds.
+    pub fn duration(&self) -> f64 {
+        self.frames as f64 * self.hop
+    }
+
+    /// Morph between this model (`amount` = 0) and `other` (`amount` = 1).
+    /// Partials are paired by nearest mean frequency; frequencies of pairs
+    /// are interpolated exponentially and amplitudes linearly. Unpaired partials
+    /// are faded out. Durations are interpolated and both models are time scaled to match.
+    pub fn morph(&self, other: &Partials, amount: f64) -> Partials {
+        let amount = clamp01(amount);
+        let other_frames = other.frames as f64 * other.hop / self.hop;
+        let frames = std::cmp::max(
+            1,
+            round(lerp(self.frames as f64, other_frames, amount)) as usize,
+        );
+
+        // Pair partials greedily by log frequency distance.
+        let mut pairs: Vec<(f64, usize, usize)> = Vec::new();
+        for (i, p) in self.partials.iter().enumerate() {
+            for (j, q) in other.partials.iter().enumerate() {
+                let distance = abs(log(
+                    max(p.mean_frequency(), 1.0) / max(q.mean_frequency(), 1.0)
+                ));
+                pairs.push((distance, i, j));
+            }
+        }
+        pairs.sort_by(|x, y| x.0.total_cmp(&y.0));
+        let mut paired_self: Vec<Option<usize>> = vec![None; self.partials.len()];
+        let mut paired_other = vec![false; other.partials.len()];
+        for &(_, i, j) in pairs.iter() {
+            if paired_self[i].is_none() && !paired_other[j] {
+                paired_self[i] = Some(j);
+                paired_other[j] = true;
+            }
+        }
+
+        // Position of morphed `frame` in the frames of `model`.
+        let position = |model: &Partials, frame: usize| -> f64 {
+            if frames > 1 {
+                frame as f64 / (frames - 1) as f64 * (std::cmp::max(model.frames, 1) - 1) as f64
+            } else {
+                0.0
+            }
+        };
+        let render = |sources: &[(&Partials, &Partial, f64)]| -> Option<Partial> {
+            let mut partial = Partial::default();
+            for frame in 0..frames {
+                let mut log_frequency = 0.0;
+                let mut frequency_weight = 0.0;
+                let mut amplitude = 0.0;
+                for &(model, source, weight) in sources.iter() {
+                    let (f, a) = source.at(position(model, frame));
+                    amplitude += a * weight;
+                    if a > 0.0 {
+                        log_frequency += log(max(f, 1.0)) * weight;
+                        frequency_weight += weight;
+                    }
+                }
+                if amplitude > 0.0 {
+                    if partial.frequency.is_empty() {
+                        partial.start = frame;
+                    }
+                    // Fill any gap inside the partial with silence.
+                    while partial.end() < frame {
+                        let f = *partial.frequency.last().unwrap();
+                        partial.frequency.push(f);
+                        partial.amplitude.push(0.0);
+                    }
+                    partial
+                        .frequency
+                        .push(exp(log_frequency / frequency_weight) as f32);
+                    partial.amplitude.push(amplitude as f32);
+                }
+            }
+            if partial.frequency.is_empty() {
+                None
+            } else {
+                Some(partial)
+            }
+        };
+
+        let mut partials = Vec::new();
+        for (i, p) in self.partials.iter().enumerate() {
+            let sources = match paired_self[i] {
+                Some(j) => vec![(self, p, 1.0 - amount), (other, &other.partials[j], amount)],
+                None => vec![(self, p, 1.0 - amount)],
+            };
+            partials.extend(render(&sources));
+        }
+        for (j, q) in other.partials.iter().enumerate() {
+            if !paired_other[j] {
+                partials.extend(render(&[(other, q, amount)]));
+            }
+        }
+        Partials {
+            hop: self.hop,
+            frames,
+            partials,
+        }
+    }
+}
+
+/// Oscillator bank that resynthesizes a sinusoidal model.
+/// Playback speed and transposition are controlled from inputs.
+/// - Input 0: playback speed (1 = original)
+/// - Input 1: frequency ratio (1 = original)
+/// - Output 0: resynthesized signal
+#[derive(Clone)]
+pub struct ResynthPartials<T: Float> {
+    _marker: PhantomData<T>,
+    partials: Arc<Partials>,
+    /// Oscillator phases in 0...1.
+    phase: Vec<f64>,
+    /// Playback position in frames.
+    position: f64,
+    sample_rate: f64,
+}
+
+impl<T: Float> ResynthPartials<T> {
+    /// Create new resynthesizer for `partials`.
+    pub fn new(partials: &Arc<Partials>) -> Self {
+        Self {
+            _marker: PhantomData,
+            partials: partials.clone(),
+            phase: vec![0.0; partials.partials.len()],
+            position: 0.0,
+            sample_rate: DEFAULT_SR,
+        }
+    }
+}
+
+impl<T: Float> AudioNode for ResynthPartials<T> {
+    const ID: u64 = 88;
+    type Sample = T;
+    type Inputs = U2;
+    type Outputs = U1;
+    type Setting = ();
+
+    fn reset(&mut self) {
+        self.phase.fill(0.0);
+        self.position = 0.0;
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate;
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let ratio = input[1].to_f64() / self.sample_rate;
+        let mut output = 0.0;
+        for (partial, phase) in self.partials.partials.iter().zip(self.phase.iter_mut()) {
+            if self.position > partial.start as f64 - 1.0 && self.position < partial.end() as f64 {
+                let (f, a) = partial.at(self.position);
+                output += a * sin(*phase * TAU);
+                *phase += f * ratio;
+                *phase -= floor(*phase);
+            }
+        }
+        let speed = input[0].to_f64();
+        self.position = max(
+            0.0,
+            self.position + speed / (self.partials.hop * self.sample_rate),
+        );
+        [convert(output)].into()
+    }
+
+    fn route(&mut self, _input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        output[0] = Signal::Latency(0.0);
+        output
+    }
+}
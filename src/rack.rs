@@ -0,0 +1,199 @@
+//! Effects rack: an ordered chain of bypassable effect slots.
+
+use super::audiounit::*;
+use super::net::*;
+use super::sequencer::*;
+use super::slot::*;
+use super::*;
+use duplicate::duplicate_item;
+
+#[duplicate_item(
+    f48       Net48       Slot48       SlotBackend48       EffectsRack48       AudioUnit48;
+    [ f64 ]   [ Net64 ]   [ Slot64 ]   [ SlotBackend64 ]   [ EffectsRack64 ]   [ AudioUnit64 ];
+    [ f32 ]   [ Net32 ]   [ Slot32 ]   [ SlotBackend32 ]   [ EffectsRack32 ]   [ AudioUnit32 ];
+)]
+/// Effects rack frontend. Contains an ordered chain of effects that
+/// all have the same number of inputs and outputs.
+/// Effects can be inserted, removed, reordered and bypassed by index.
+/// Each edit builds a new chain that replaces the playing one with a crossfade.
+/// The rack is played by its backend, which is returned from `EffectsRack64::new`.
+///
+/// ### Example: Chorus And Reverb
+/// ```
+/// use fundsp::hacker::*;
+/// let (mut rack, mut backend) = EffectsRack64::new(1);
+/// rack.push(Box::new(chorus(0, 0.0, 0.01, 0.2)));
+/// rack.push(Box::new(split::<U2>() >> reverb_stereo(10.0, 2.0) >> join()));
+/// rack.set_bypass(0, true);
+/// rack.swap(0, 1);
+/// assert_eq!(backend.filter_mono(0.0), 0.0);
+/// ```
+pub struct EffectsRack48 {
+    channels: usize,
+    effects: Vec<Box<dyn AudioUnit48>>,
+    bypass: Vec<bool>,
+    fade: Fade,
+    fade_time: f48,
+    sample_rate: f64,
+    slot: Slot48,
+}
+
+#[duplicate_item(
+    f48       Net48       Slot48       SlotBackend48       EffectsRack48       AudioUnit48;
+    [ f64 ]   [ Net64 ]   [ Slot64 ]   [ SlotBackend64 ]   [ EffectsRack64 ]   [ AudioUnit64 ];
+    [ f32 ]   [ Net32 ]   [ Slot32 ]   [ SlotBackend32 ]   [ EffectsRack32 ]   [ AudioUnit32 ];
+)]
+impl EffectsRack48 {
+    /// Create a new, empty rack with `channels` inputs and outputs.
+    /// An empty rack passes its input through.
+    /// Returns (frontend, backend) pair.
+    pub fn new(channels: usize) -> (EffectsRack48, SlotBackend48) {
+        let mut net = Net48::new(channels, channels);
+        for channel in 0..channels {
+            net.pass_through(channel, channel);
+        }
+        let (slot, backend) = Slot48::new(Box::new(net));
+        let rack = EffectsRack48 {
+            channels,
+            effects: Vec::new(),
+            bypass: Vec::new(),
+            fade: Fade::Smooth,
+            fade_time: 0.05,
+            sample_rate: DEFAULT_SR,
+            slot,
+        };
+        (rack, backend)
+    }
+
+    /// Number of inputs and outputs.
+    #[inline]
+    pub fn channels(&self) -> usize {
+        self.channels
+    }
+
+    /// Number of effect slots.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.effects.len()
+    }
+
+    /// Whether the rack has no effect slots.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.effects.is_empty()
+    }
+
+    /// Crossfade time in seconds.
+    #[inline]
+    pub fn fade_time(&self) -> f48 {
+        self.fade_time
+    }
+
+    /// Set crossfade shape and time (in seconds) for subsequent edits.
+    /// The default is a smooth fade of 50 ms.
+    pub fn set_fade(&mut self, fade: Fade, fade_time: f48) {
+        self.fade = fade;
+        self.fade_time = fade_time;
+    }
+
+    /// Set the sample rate of effects sent to the backend.
+    /// The backend itself is not affected.
+    pub fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate;
+    }
+
+    /// Access the effect at `index`.
+    pub fn effect(&self, index: usize) -> &dyn AudioUnit48 {
+        &*self.effects[index]
+    }
+
+    /// Add an effect to the end of the chain. Returns its index.
+    pub fn push(&mut self, unit: Box<dyn AudioUnit48>) -> usize {
+        self.insert(self.effects.len(), unit);
+        self.effects.len() - 1
+    }
+
+    /// Insert an effect at `index`, moving later effects one slot down the chain.
+    pub fn insert(&mut self, index: usize, unit: Box<dyn AudioUnit48>) {
+        assert_eq!(unit.inputs(), self.channels);
+        assert_eq!(unit.outputs(), self.channels);
+        self.effects.insert(index, unit);
+        self.bypass.insert(index, false);
+        self.update();
+    }
+
+    /// Remove the effect at `index` and return it.
+    pub fn remove(&mut self, index: usize) -> Box<dyn AudioUnit48> {
+        let unit = self.effects.remove(index);
+        self.bypass.remove(index);
+        self.update();
+        unit
+    }
+
+    /// Replace the effect at `index`. Returns the previous effect.
+    /// The bypass state of the slot is kept.
+    pub fn replace(&mut self, index: usize, unit: Box<dyn AudioUnit48>) -> Box<dyn AudioUnit48> {
+        assert_eq!(unit.inputs(), self.channels);
+        assert_eq!(unit.outputs(), self.channels);
+        let previous = std::mem::replace(&mut self.effects[index], unit);
+        self.update();
+        previous
+    }
+
+    /// Swap the effects at indices `a` and `b`.
+    pub fn swap(&mut self, a: usize, b: usize) {
+        self.effects.swap(a, b);
+        self.bypass.swap(a, b);
+        self.update();
+    }
+
+    /// Move the effect at index `from` so that it ends up at index `to`.
+    pub fn move_to(&mut self, from: usize, to: usize) {
+        let unit = self.effects.remove(from);
+        let bypass = self.bypass.remove(from);
+        self.effects.insert(to, unit);
+        self.bypass.insert(to, bypass);
+        self.update();
+    }
+
+    /// Whether the effect at `index` is bypassed.
+    #[inline]
+    pub fn is_bypassed(&self, index: usize) -> bool {
+        self.bypass[index]
+    }
+
+    /// Bypass (`true`) or enable (`false`) the effect at `index`.
+    pub fn set_bypass(&mut self, index: usize, bypass: bool) {
+        if self.bypass[index] != bypass {
+            self.bypass[index] = bypass;
+            self.update();
+        }
+    }
+
+    /// Build the current chain of enabled effects as a network.
+    pub fn build(&self) -> Net48 {
+        let mut net = Net48::new(self.channels, self.channels);
+        let mut empty = true;
+        for (unit, bypass) in self.effects.iter().zip(self.bypass.iter()) {
+            if !bypass {
+                net.chain(unit.clone());
+                empty = false;
+            }
+        }
+        if empty {
+            for channel in 0..self.channels {
+                net.pass_through(channel, channel);
+            }
+        }
+        net
+    }
+
+    /// Send the current chain to the backend.
+    fn update(&mut self) {
+        let mut net = self.build();
+        net.set_sample_rate(self.sample_rate);
+        net.allocate();
+        self.slot
+            .set(self.fade.clone(), self.fade_time, Box::new(net));
+    }
+}
@@ -24,7 +24,9 @@ pub type WaveError = Error;
 impl WaveStream48 {
     /// Load first track of audio file from the given path.
     /// Supported formats are anything that Symphonia can read.
-    pub async fn load<P: AsRef<Path>>(path: P) -> WaveResult<std::sync::Arc<tokio::sync::RwLock<WaveStream48>>> {
+    pub async fn load<P: AsRef<Path>>(
+        path: P,
+    ) -> WaveResult<std::sync::Arc<tokio::sync::RwLock<WaveStream48>>> {
         WaveStream48::load_track(path, None).await
     }
 
@@ -46,7 +48,10 @@ impl WaveStream48 {
     /// Load audio file from the given path. Track can be optionally selected.
     /// If not selected, the first track with a known codec will be loaded.
     /// Supported formats are anything that Symphonia can read.
-    pub async fn load_track<P: AsRef<Path>>(path: P, track: Option<usize>) -> WaveResult<std::sync::Arc<tokio::sync::RwLock<WaveStream48>>> {
+    pub async fn load_track<P: AsRef<Path>>(
+        path: P,
+        track: Option<usize>,
+    ) -> WaveResult<std::sync::Arc<tokio::sync::RwLock<WaveStream48>>> {
         let path = path.as_ref();
         let mut hint = Hint::new();
 
@@ -62,16 +67,17 @@ impl WaveStream48 {
         };
         log::info!(" file openned");
         //let w = std::Arc::new(RwLock())
-        let (tx, mut rx) = tokio::sync::mpsc::channel::<std::sync::Arc<tokio::sync::RwLock<WaveStream48>>>(1);
+        let (tx, mut rx) =
+            tokio::sync::mpsc::channel::<std::sync::Arc<tokio::sync::RwLock<WaveStream48>>>(1);
         let _r = tokio::spawn(async move {
             let _r = WaveStream48::decode(source, track, hint, tx).await;
             log::info!("load complete");
         });
         let w = rx.recv().await;
         log::info!("received");
-        if let Some(wave) = w{
+        if let Some(wave) = w {
             Ok(wave)
-        }else{
+        } else {
             log::info!("error");
             Err(WaveError::Unsupported("error"))
         }
@@ -82,7 +88,7 @@ impl WaveStream48 {
         source: Box<dyn MediaSource>,
         track: Option<usize>,
         hint: Hint,
-        tx: tokio::sync::mpsc::Sender<std::sync::Arc<tokio::sync::RwLock<WaveStream48>>>
+        tx: tokio::sync::mpsc::Sender<std::sync::Arc<tokio::sync::RwLock<WaveStream48>>>,
     ) -> WaveResult<()> {
         let stream = MediaSourceStream::new(source, Default::default());
 
@@ -117,7 +123,7 @@ impl WaveStream48 {
                     _ => return Err(Error::DecodeError("Could not find track.")),
                 };
                 log::info!("Codec params: {:#?}", track.codec_params);
-                let frames = track.codec_params.n_frames.map(|f|f as usize);
+                let frames = track.codec_params.n_frames.map(|f| f as usize);
 
                 let decode_opts = DecoderOptions::default();
 
@@ -147,7 +153,13 @@ impl WaveStream48 {
                             if wave.is_none() {
                                 let spec = *decoded.spec();
                                 println!("Spec: {:#?}", spec);
-                                let w = std::sync::Arc::new(tokio::sync::RwLock::new(WaveStream48::new(spec.channels.count(), spec.rate as f64, frames)));
+                                let w = std::sync::Arc::new(tokio::sync::RwLock::new(
+                                    WaveStream48::new(
+                                        spec.channels.count(),
+                                        spec.rate as f64,
+                                        frames,
+                                    ),
+                                ));
                                 log::info!("send");
                                 tx.send(w.clone()).await;
                                 wave = Some(w);
@@ -206,11 +218,7 @@ impl WaveStream48 {
                                     }
                                     for i in 0..buffer_len {
                                         let len = wave_output.len();
-                                        wave_output.set(
-                                            channel,
-                                            len - buffer_len + i,
-                                            x[i],
-                                        );
+                                        wave_output.set(channel, len - buffer_len + i, x[i]);
                                     }
                                 }
                             }
@@ -223,3 +231,135 @@ impl WaveStream48 {
         }
     }
 }
+
+#[duplicate_item(
+    f48       DiskStream48;
+    [ f64 ]   [ DiskStream64 ];
+    [ f32 ]   [ DiskStream32 ];
+)]
+impl DiskStream48 {
+    /// Open first track of audio file from the given path for streaming.
+    /// Only a sliding window of `window` seconds of audio is kept in memory.
+    /// The window is prefetched by a background task as playback proceeds.
+    /// Supported formats are anything that Symphonia can read.
+    pub async fn open<P: AsRef<Path>>(
+        path: P,
+        window: f64,
+    ) -> WaveResult<std::sync::Arc<tokio::sync::RwLock<DiskStream48>>> {
+        assert!(window > 0.0);
+        let path = path.as_ref();
+        let mut hint = Hint::new();
+
+        if let Some(extension) = path.extension() {
+            if let Some(extension_str) = extension.to_str() {
+                hint.with_extension(extension_str);
+            }
+        }
+        let source: Box<dyn MediaSource> = match File::open(path) {
+            Ok(file) => Box::new(file),
+            Err(error) => return Err(Error::IoError(error)),
+        };
+        let (tx, mut rx) =
+            tokio::sync::mpsc::channel::<std::sync::Arc<tokio::sync::RwLock<DiskStream48>>>(1);
+        tokio::spawn(async move {
+            if let Err(error) = DiskStream48::stream(source, hint, window, tx).await {
+                log::warn!("disk stream error: {}", error);
+            }
+        });
+        match rx.recv().await {
+            Some(stream) => Ok(stream),
+            None => Err(WaveError::Unsupported("could not open stream")),
+        }
+    }
+
+    /// Decode first track with a known codec from the given source into a sliding window.
+    async fn stream(
+        source: Box<dyn MediaSource>,
+        hint: Hint,
+        window: f64,
+        tx: tokio::sync::mpsc::Sender<std::sync::Arc<tokio::sync::RwLock<DiskStream48>>>,
+    ) -> WaveResult<()> {
+        let stream = MediaSourceStream::new(source, Default::default());
+        let format_opts = FormatOptions {
+            enable_gapless: false,
+            ..Default::default()
+        };
+        let metadata_opts: MetadataOptions = Default::default();
+        let mut reader = symphonia::default::get_probe()
+            .format(&hint, stream, &format_opts, &metadata_opts)?
+            .format;
+
+        let track = match reader
+            .tracks()
+            .iter()
+            .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
+        {
+            Some(track) => track,
+            _ => return Err(Error::DecodeError("Could not find track.")),
+        };
+        let track_id = track.id;
+        let mut decoder = symphonia::default::get_codecs()
+            .make(&track.codec_params, &DecoderOptions::default())?;
+
+        let mut shared: Option<(
+            std::sync::Arc<tokio::sync::RwLock<DiskStream48>>,
+            std::sync::Arc<tokio::sync::Notify>,
+        )> = None;
+
+        loop {
+            let packet = match reader.next_packet() {
+                Ok(packet) => packet,
+                Err(err) => {
+                    // The end of the stream is reported as an error.
+                    if let Some((stream, _)) = shared {
+                        stream.write().await.set_loaded();
+                        return Ok(());
+                    } else {
+                        return Err(err);
+                    }
+                }
+            };
+
+            // If the packet does not belong to the selected track, skip it.
+            if packet.track_id() != track_id {
+                continue;
+            }
+
+            let decoded = decoder.decode(&packet)?;
+            let spec = *decoded.spec();
+            if shared.is_none() {
+                let capacity = std::cmp::max(1, (window * spec.rate as f64).round() as usize);
+                let stream = DiskStream48::new(spec.channels.count(), spec.rate as f64, capacity);
+                let space = stream.space();
+                let stream = std::sync::Arc::new(tokio::sync::RwLock::new(stream));
+                if tx.send(stream.clone()).await.is_err() {
+                    return Ok(());
+                }
+                shared = Some((stream, space));
+            }
+            let (stream, space) = shared.as_ref().unwrap();
+
+            let mut dest = AudioBuffer::<f48>::new(decoded.capacity() as u64, spec);
+            decoded.convert(&mut dest);
+            let frames = dest.frames();
+
+            // Wait until there is room in the window.
+            loop {
+                {
+                    let mut stream = stream.write().await;
+                    if stream.has_room(frames) {
+                        for channel in 0..stream.channels() {
+                            stream.extend(channel, dest.chan(channel));
+                        }
+                        break;
+                    }
+                }
+                // Stop streaming if nobody is listening anymore.
+                if std::sync::Arc::strong_count(stream) == 1 {
+                    return Ok(());
+                }
+                space.notified().await;
+            }
+        }
+    }
+}
@@ -1,87 +1,377 @@
 //! Real-time friendly backend for Net64 and Net32.
 
 use super::audiounit::*;
+use super::buffer::*;
 use super::math::*;
 use super::net::*;
+use super::sequencer::Fade;
 use super::signal::*;
+use super::trace::*;
 use duplicate::duplicate_item;
+use rsor::Slice;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 use thingbuf::mpsc::blocking::{channel, Receiver, Sender};
 
-#[duplicate_item(
-    f48       Net48       NetBackend48       Vertex48       AudioUnit48;
-    [ f64 ]   [ Net64 ]   [ NetBackend64 ]   [ Vertex64 ]   [ AudioUnit64 ];
-    [ f32 ]   [ Net32 ]   [ NetBackend32 ]   [ Vertex32 ]   [ AudioUnit32 ];
-)]
-pub struct NetBackend48 {
+/// Maximum number of scheduled commits that can be pending in a backend.
+/// Further scheduled commits are rejected by the frontend.
+pub const MAX_SCHEDULED: usize = 64;
+
+/// Fixed capacity queue of versions waiting to take effect.
+/// It does not allocate after it has been created.
+#[derive(Clone)]
+struct Schedule<T: UnitFloat> {
+    /// Versions with their start times in seconds.
+    entries: Vec<Option<(f64, Net<T>)>>,
+    /// Index of the first version.
+    head: usize,
+    /// Number of versions in the queue.
+    len: usize,
+}
+
+impl<T: UnitFloat> Schedule<T> {
+    /// Create an empty queue with room for `capacity` versions.
+    fn new(capacity: usize) -> Self {
+        Self {
+            entries: (0..capacity).map(|_| None).collect(),
+            head: 0,
+            len: 0,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn index(&self, i: usize) -> usize {
+        (self.head + i) % self.entries.len()
+    }
+
+    fn front(&self) -> Option<&(f64, Net<T>)> {
+        if self.is_empty() {
+            None
+        } else {
+            self.entries[self.head].as_ref()
+        }
+    }
+
+    fn back_mut(&mut self) -> Option<&mut (f64, Net<T>)> {
+        if self.is_empty() {
+            None
+        } else {
+            let i = self.index(self.len - 1);
+            self.entries[i].as_mut()
+        }
+    }
+
+    /// Add a version to the back of the queue. If the queue is full, the version is returned.
+    fn push_back(&mut self, entry: (f64, Net<T>)) -> Result<(), (f64, Net<T>)> {
+        if self.len == self.entries.len() {
+            return Err(entry);
+        }
+        let i = self.index(self.len);
+        self.entries[i] = Some(entry);
+        self.len += 1;
+        Ok(())
+    }
+
+    fn pop_front(&mut self) -> Option<(f64, Net<T>)> {
+        if self.is_empty() {
+            return None;
+        }
+        let entry = self.entries[self.head].take();
+        self.head = self.index(1);
+        self.len -= 1;
+        entry
+    }
+
+    /// Iterate over queued versions.
+    fn iter(&self) -> impl Iterator<Item = &Net<T>> {
+        self.entries.iter().flatten().map(|(_, net)| net)
+    }
+
+    /// Iterate mutably over queued versions.
+    fn iter_mut(&mut self) -> impl Iterator<Item = &mut Net<T>> {
+        self.entries.iter_mut().flatten().map(|(_, net)| net)
+    }
+}
+
+/// Real-time friendly backend for a network of sample type `T`.
+/// Create one with `Net::backend`.
+pub struct NetBackend<T: UnitFloat> {
     /// For sending versions for deallocation back to the frontend.
-    sender: Sender<Net48>,
+    sender: Sender<Net<T>>,
     /// For receiving new versions from the frontend.
-    receiver: Receiver<Net48>,
-    net: Net48,
+    receiver: Receiver<Net<T>>,
+    net: Net<T>,
+    /// Version we are crossfading to, if any.
+    next: Option<Net<T>>,
+    /// Latest version received during a crossfade.
+    latest: Option<Net<T>>,
+    fade: Fade,
+    /// Crossfade time in seconds.
+    fade_time: T,
+    /// Crossfade phase from 0 to 1.
+    fade_phase: T,
+    /// Output buffer for the next version.
+    buffer: Buffer<T>,
+    tick: Vec<T>,
+    /// Scheduled versions with their start times in seconds, in commit order.
+    /// Immediate versions committed after a scheduled version also wait here.
+    scheduled: Schedule<T>,
+    /// Number of scheduled commits that have not taken effect yet.
+    /// The counter is shared with the frontend.
+    pending: Arc<AtomicUsize>,
+    /// Backend clock in samples.
+    sample: u64,
+    /// Input channels for parts of a block.
+    input_slice: Slice<[T]>,
+    /// Output channels for parts of a block.
+    output_slice: Slice<[T]>,
 }
 
-#[duplicate_item(
-    f48       Net48       NetBackend48       Vertex48       AudioUnit48;
-    [ f64 ]   [ Net64 ]   [ NetBackend64 ]   [ Vertex64 ]   [ AudioUnit64 ];
-    [ f32 ]   [ Net32 ]   [ NetBackend32 ]   [ Vertex32 ]   [ AudioUnit32 ];
-)]
-impl Clone for NetBackend48 {
+/// Backend of a 64-bit network.
+pub type NetBackend64 = NetBackend<f64>;
+
+/// Backend of a 32-bit network.
+pub type NetBackend32 = NetBackend<f32>;
+
+impl<T: UnitFloat> Clone for NetBackend<T> {
     fn clone(&self) -> Self {
         // Allocate a dummy channel.
         let (sender, receiver) = channel(1);
-        NetBackend48 {
+        NetBackend {
             sender,
             receiver,
             net: self.net.clone(),
+            next: self.next.clone(),
+            latest: self.latest.clone(),
+            fade: self.fade.clone(),
+            fade_time: self.fade_time,
+            fade_phase: self.fade_phase,
+            buffer: Buffer::with_channels(self.net.outputs()),
+            tick: self.tick.clone(),
+            scheduled: self.scheduled.clone(),
+            // The clone settles its own copies of scheduled versions.
+            pending: Arc::new(AtomicUsize::new(
+                self.scheduled
+                    .iter()
+                    .filter(|net| net.commit_time_request().is_some())
+                    .count(),
+            )),
+            sample: self.sample,
+            input_slice: Slice::new(),
+            output_slice: Slice::new(),
         }
     }
 }
 
-#[duplicate_item(
-    f48       Net48       NetBackend48       Vertex48       AudioUnit48;
-    [ f64 ]   [ Net64 ]   [ NetBackend64 ]   [ Vertex64 ]   [ AudioUnit64 ];
-    [ f32 ]   [ Net32 ]   [ NetBackend32 ]   [ Vertex32 ]   [ AudioUnit32 ];
-)]
-impl NetBackend48 {
+impl<T: UnitFloat> NetBackend<T> {
     /// Create new backend.
-    pub fn new(sender: Sender<Net48>, receiver: Receiver<Net48>, net: Net48) -> Self {
+    pub fn new(sender: Sender<Net<T>>, receiver: Receiver<Net<T>>, net: Net<T>) -> Self {
+        let outputs = net.outputs();
+        let pending = net.pending_counter();
         Self {
             sender,
             receiver,
             net,
+            next: None,
+            latest: None,
+            fade: Fade::Smooth,
+            fade_time: T::zero(),
+            fade_phase: T::zero(),
+            buffer: Buffer::with_channels(outputs),
+            tick: vec![T::zero(); outputs],
+            // Room for every scheduled version and an immediate version after each of them.
+            scheduled: Schedule::new(2 * MAX_SCHEDULED + 1),
+            pending,
+            sample: 0,
+            input_slice: Slice::new(),
+            output_slice: Slice::new(),
         }
     }
 
+    /// Backend clock in seconds. This counts samples processed since the backend
+    /// was created or last reset. Scheduled commits are timed against this clock.
+    pub fn time(&self) -> f64 {
+        self.sample as f64 / self.net.sample_rate()
+    }
+
     /// Handle changes made to the backend.
     fn handle_messages(&mut self) {
-        let mut latest_net: Option<Net48> = None;
+        let mut latest_net: Option<Net<T>> = None;
         #[allow(clippy::while_let_loop)]
         loop {
             match self.receiver.try_recv() {
                 Ok(net) => {
-                    if let Some(net) = latest_net {
-                        // This is not the latest network, send it back immediately for deallocation.
-                        if self.sender.try_send(net).is_ok() {}
+                    if net.commit_time_request().is_none() && self.scheduled.is_empty() {
+                        if let Some(net) = latest_net {
+                            // This is not the latest network, send it back immediately for deallocation.
+                            if self.sender.try_send(net).is_ok() {}
+                        }
+                        latest_net = Some(net)
+                    } else {
+                        // Immediate versions committed before this one take effect now.
+                        if let Some(net) = latest_net.take() {
+                            self.receive(net);
+                        }
+                        if net.commit_time_request().is_none() {
+                            if let Some((_, back)) = self.scheduled.back_mut() {
+                                if back.commit_time_request().is_none() {
+                                    // Only the latest of consecutive immediate versions takes effect.
+                                    let previous = std::mem::replace(back, net);
+                                    if self.sender.try_send(previous).is_ok() {}
+                                    continue;
+                                }
+                            }
+                        }
+                        // A version cannot take effect before versions committed earlier.
+                        let time = self.scheduled.back_mut().map_or(0.0, |(time, _)| *time);
+                        let time = net.commit_time_request().unwrap_or(0.0).max(time);
+                        if let Err((_, net)) = self.scheduled.push_back((time, net)) {
+                            // The queue is full. The frontend limits pending commits, so this should not happen.
+                            self.settle(&net);
+                            if self.sender.try_send(net).is_ok() {}
+                        }
                     }
-                    latest_net = Some(net)
                 }
                 _ => break,
             }
         }
-        if let Some(mut net) = latest_net {
-            // Migrate existing nodes to the new network.
-            self.net.migrate(&mut net);
-            std::mem::swap(&mut net, &mut self.net);
-            // Send the previous network back for deallocation.
-            if self.sender.try_send(net).is_ok() {}
+        if let Some(net) = latest_net {
+            self.receive(net);
+        }
+        self.apply_scheduled();
+    }
+
+    /// Receive a version that is due now.
+    fn receive(&mut self, net: Net<T>) {
+        if self.next.is_some() {
+            // A crossfade is in progress. Hold on to the latest version until it has completed.
+            if let Some(latest) = self.latest.replace(net) {
+                if self.sender.try_send(latest).is_ok() {}
+            }
+        } else {
+            self.adopt(net);
+        }
+    }
+
+    /// Convert a scheduled time in seconds to a backend clock sample.
+    fn time_to_sample(&self, time: f64) -> u64 {
+        // Negative times saturate to zero.
+        (time * self.net.sample_rate()).round() as u64
+    }
+
+    /// Receive scheduled versions that are due.
+    fn apply_scheduled(&mut self) {
+        while let Some((time, _)) = self.scheduled.front() {
+            if self.time_to_sample(*time) > self.sample {
+                break;
+            }
+            if let Some((_, net)) = self.scheduled.pop_front() {
+                self.settle(&net);
+                self.receive(net);
+            }
+        }
+    }
+
+    /// Stop counting a version as pending if it was scheduled.
+    fn settle(&self, net: &Net<T>) {
+        if net.commit_time_request().is_some() {
+            self.pending.fetch_sub(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Number of samples until the next scheduled version takes effect, at most `size`.
+    fn samples_until_scheduled(&self, size: usize) -> usize {
+        match self.scheduled.front() {
+            Some((time, _)) => {
+                let due = self.time_to_sample(*time).saturating_sub(self.sample);
+                min(due, size as u64) as usize
+            }
+            None => size,
         }
     }
+
+    /// Process a part of a block in which no scheduled version takes effect.
+    fn process_part(&mut self, size: usize, input: &[&[T]], output: &mut [&mut [T]]) {
+        self.net.process(size, input, output);
+        let n = min(size, self.fade_samples_left());
+        if let Some(next) = &mut self.next {
+            let delta = T::one() / (self.fade_time * T::from_f64(self.net.sample_rate()));
+            next.process(size, input, self.buffer.get_mut(self.tick.len()));
+            for (channel, x) in output.iter_mut().enumerate() {
+                let y = self.buffer.at(channel);
+                let mut phase = self.fade_phase;
+                for i in 0..n {
+                    x[i] = x[i] * self.fade.at(T::one() - phase) + y[i] * self.fade.at(phase);
+                    phase += delta;
+                }
+                x[n..size].copy_from_slice(&y[n..size]);
+            }
+            self.fade_phase += T::from_f64(n as f64) * delta;
+            if n < size || self.fade_phase >= T::one() {
+                // The switch happens at a block boundary.
+                self.next_phase();
+            }
+        }
+        self.sample += size as u64;
+    }
+
+    /// Switch to a new version, either immediately or by starting a crossfade.
+    fn adopt(&mut self, mut net: Net<T>) {
+        // The frontend may have been built at a different sample rate.
+        // Update new units before existing units are migrated.
+        if net.sample_rate() != self.net.sample_rate() {
+            net.set_sample_rate(self.net.sample_rate());
+        }
+        net.trace(TraceKind::Commit);
+        // Versions with a different number of inputs or outputs cannot be crossfaded.
+        let reshape = net.inputs() != self.net.inputs() || net.outputs() != self.net.outputs();
+        match net.commit_fade_request() {
+            Some((fade, fade_time)) if fade_time > T::zero() && !reshape => {
+                self.fade = fade;
+                self.fade_time = fade_time;
+                self.fade_phase = T::zero();
+                self.next = Some(net);
+            }
+            _ => {
+                // Migrate existing nodes to the new network.
+                self.net.migrate(&mut net);
+                std::mem::swap(&mut net, &mut self.net);
+                // Send the previous network back for deallocation.
+                if self.sender.try_send(net).is_ok() {}
+                if reshape {
+                    self.buffer.resize(self.net.outputs());
+                    self.tick.resize(self.net.outputs(), T::zero());
+                }
+            }
+        }
+    }
+
+    /// We have faded to the next version, now switch to the latest version, if any.
+    fn next_phase(&mut self) {
+        if let Some(mut next) = self.next.take() {
+            std::mem::swap(&mut next, &mut self.net);
+            if self.sender.try_send(next).is_ok() {}
+        }
+        if let Some(latest) = self.latest.take() {
+            self.adopt(latest);
+        }
+    }
+
+    /// Number of samples left in the current crossfade.
+    fn fade_samples_left(&self) -> usize {
+        ((T::one() - self.fade_phase) * self.fade_time * T::from_f64(self.net.sample_rate()))
+            .ceil()
+            .to_f64() as usize
+    }
 }
 
 #[duplicate_item(
-    f48       Net48       NetBackend48       Vertex48       AudioUnit48;
-    [ f64 ]   [ Net64 ]   [ NetBackend64 ]   [ Vertex64 ]   [ AudioUnit64 ];
-    [ f32 ]   [ Net32 ]   [ NetBackend32 ]   [ Vertex32 ]   [ AudioUnit32 ];
+    f48       NetBackend48       AudioUnit48;
+    [ f64 ]   [ NetBackend64 ]   [ AudioUnit64 ];
+    [ f32 ]   [ NetBackend32 ]   [ AudioUnit32 ];
 )]
 impl AudioUnit48 for NetBackend48 {
     fn inputs(&self) -> usize {
@@ -93,23 +383,68 @@ impl AudioUnit48 for NetBackend48 {
     }
 
     fn reset(&mut self) {
+        // Complete any crossfades in progress.
+        while self.next.is_some() {
+            self.next_phase();
+        }
         self.net.reset();
+        self.sample = 0;
         self.handle_messages();
     }
 
     fn set_sample_rate(&mut self, sample_rate: f64) {
         self.net.set_sample_rate(sample_rate);
+        if let Some(next) = &mut self.next {
+            next.set_sample_rate(sample_rate);
+        }
+        if let Some(latest) = &mut self.latest {
+            latest.set_sample_rate(sample_rate);
+        }
         self.handle_messages();
     }
 
+    #[allow(clippy::unnecessary_cast)]
     fn tick(&mut self, input: &[f48], output: &mut [f48]) {
         self.handle_messages();
         self.net.tick(input, output);
+        if let Some(next) = &mut self.next {
+            next.tick(input, &mut self.tick);
+            let fade_in = self.fade.at(self.fade_phase);
+            let fade_out = self.fade.at(1.0 - self.fade_phase);
+            for (x, y) in output.iter_mut().zip(self.tick.iter()) {
+                *x = *x * fade_out + *y * fade_in;
+            }
+            self.fade_phase += 1.0 / (self.fade_time * self.net.sample_rate() as f48);
+            if self.fade_phase >= 1.0 {
+                self.next_phase();
+            }
+        }
+        self.sample += 1;
     }
 
     fn process(&mut self, size: usize, input: &[&[f48]], output: &mut [&mut [f48]]) {
         self.handle_messages();
-        self.net.process(size, input, output);
+        let mut offset = 0;
+        while offset < size {
+            // Split the block where the next scheduled version takes effect.
+            let n = self.samples_until_scheduled(size - offset);
+            if n == size {
+                self.process_part(size, input, output);
+            } else {
+                let mut input_slice = std::mem::replace(&mut self.input_slice, Slice::new());
+                let mut output_slice = std::mem::replace(&mut self.output_slice, Slice::new());
+                self.process_part(
+                    n,
+                    input_slice.from_iter(input.iter().map(|x| &x[offset..offset + n])),
+                    output_slice
+                        .from_iter_mut(output.iter_mut().map(|x| &mut x[offset..offset + n])),
+                );
+                self.input_slice = input_slice;
+                self.output_slice = output_slice;
+            }
+            offset += n;
+            self.apply_scheduled();
+        }
     }
 
     fn get_id(&self) -> u64 {
@@ -121,6 +456,10 @@ impl AudioUnit48 for NetBackend48 {
         self.net.ping(probe, hash)
     }
 
+    fn has_side_effects(&self) -> bool {
+        true
+    }
+
     fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
         self.handle_messages();
         self.net.route(input, frequency)
@@ -132,5 +471,14 @@ impl AudioUnit48 for NetBackend48 {
 
     fn allocate(&mut self) {
         self.net.allocate();
+        if let Some(next) = &mut self.next {
+            next.allocate();
+        }
+        if let Some(latest) = &mut self.latest {
+            latest.allocate();
+        }
+        for net in self.scheduled.iter_mut() {
+            net.allocate();
+        }
     }
 }
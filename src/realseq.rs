@@ -25,6 +25,8 @@ pub enum Message48 {
     Edit(EventId, Edit48),
     /// Edit event in relative time.
     EditRelative(EventId, Edit48),
+    /// Set control value (time, channel, value).
+    Control(f48, usize, f48),
 }
 
 #[duplicate_item(
@@ -92,10 +94,13 @@ impl SequencerBackend48 {
     fn handle_messages(&mut self) {
         while let Ok(message) = self.receiver.try_recv() {
             match message {
-                Message48::Push(event) => {
+                Message48::Push(mut event) => {
+                    // The frontend does not know our sample rate.
+                    event.unit.set_sample_rate(self.sequencer.sample_rate());
                     self.sequencer.push_event(event);
                 }
-                Message48::PushRelative(event) => {
+                Message48::PushRelative(mut event) => {
+                    event.unit.set_sample_rate(self.sequencer.sample_rate());
                     self.sequencer.push_relative_event(event);
                 }
                 Message48::Edit(id, edit) => {
@@ -105,6 +110,9 @@ impl SequencerBackend48 {
                     self.sequencer
                         .edit_relative(id, edit.end_time, edit.fade_out);
                 }
+                Message48::Control(time, channel, value) => {
+                    self.sequencer.set_control(time, channel, value);
+                }
                 Message48::Null => {}
             }
         }
@@ -179,6 +187,10 @@ impl AudioUnit48 for SequencerBackend48 {
         self.sequencer.ping(probe, hash)
     }
 
+    fn has_side_effects(&self) -> bool {
+        true
+    }
+
     fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
         self.handle_messages();
         self.sequencer.route(input, frequency)
@@ -0,0 +1,359 @@
+//! Recording taps that capture signals from inside a playing graph.
+
+use super::audionode::*;
+use super::math::*;
+use super::signal::*;
+use super::wave::*;
+use super::*;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use thingbuf::ThingBuf;
+
+/// Number of frames in a recording chunk.
+const CHUNK_FRAMES: usize = 1024;
+
+/// Punch recording command sent to a recording tap.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+enum PunchCommand {
+    /// Record only between punch points.
+    Arm,
+    /// Record continuously.
+    #[default]
+    Disarm,
+    /// Start recording at the given tap time in samples.
+    In(u64),
+    /// Stop recording at the given tap time in samples.
+    Out(u64),
+    /// Set crossfade time at punch points in seconds.
+    Crossfade(f64),
+}
+
+/// Wave that is recorded from inside a playing graph with a `tap_record` node.
+/// The tap appends its input into preallocated chunks without locking or allocating.
+/// Filled chunks are collected into the wave from another thread with `SharedWave::update`.
+/// Each shared wave should be recorded by a single tap.
+///
+/// Samples are placed in the wave at their tap time, which counts samples processed
+/// by the tap since it was reset. Normally the tap records continuously.
+/// An armed tap records only between punch-in and punch-out points.
+/// At punch points, the recording is crossfaded with the material already in the wave.
+pub struct SharedWave {
+    wave: Wave64,
+    /// Empty chunks for the tap to fill.
+    empty: Arc<ThingBuf<Vec<f64>>>,
+    /// Filled chunks waiting to be collected. The first element of a chunk is its tap time.
+    /// It is followed by frames of samples, each followed by its crossfade gain.
+    filled: Arc<ThingBuf<Vec<f64>>>,
+    /// Requests the tap to deliver its partially filled chunk.
+    flush: Arc<AtomicBool>,
+    /// Number of frames dropped because no empty chunks were available.
+    dropped: Arc<AtomicU64>,
+    /// Punch recording commands for the tap.
+    commands: Arc<ThingBuf<PunchCommand>>,
+    /// Latest tap time in samples.
+    time: Arc<AtomicU64>,
+}
+
+impl SharedWave {
+    /// Create new shared wave with `channels` channels (`channels` > 0) at `sample_rate` Hz.
+    /// Chunks are preallocated for `buffer` seconds of audio.
+    /// The wave must be updated at least that often to avoid dropping frames.
+    pub fn new(channels: usize, sample_rate: f64, buffer: f64) -> Self {
+        assert!(channels > 0 && buffer >= 0.0);
+        let chunks = (ceil(buffer * sample_rate) as usize).div_ceil(CHUNK_FRAMES) + 1;
+        let empty = Arc::new(ThingBuf::new(chunks));
+        for _ in 0..chunks {
+            let _ = empty.push(Vec::with_capacity(1 + CHUNK_FRAMES * (channels + 1)));
+        }
+        Self {
+            wave: Wave64::new(channels, sample_rate),
+            empty,
+            filled: Arc::new(ThingBuf::new(chunks)),
+            flush: Arc::new(AtomicBool::new(false)),
+            dropped: Arc::new(AtomicU64::new(0)),
+            commands: Arc::new(ThingBuf::new(64)),
+            time: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Number of channels.
+    pub fn channels(&self) -> usize {
+        self.wave.channels()
+    }
+
+    /// Collect filled chunks into the wave and recycle them.
+    pub fn update(&mut self) {
+        let channels = self.wave.channels();
+        while let Some(mut chunk) = self.filled.pop() {
+            let start = chunk[0] as usize;
+            let frames = (chunk.len() - 1) / (channels + 1);
+            if self.wave.length() < start + frames {
+                self.wave.resize(start + frames);
+            }
+            for (i, frame) in chunk[1..].chunks_exact(channels + 1).enumerate() {
+                let gain = frame[channels];
+                for (channel, &x) in frame[..channels].iter().enumerate() {
+                    let y = self.wave.at(channel, start + i);
+                    self.wave
+                        .set(channel, start + i, y * (1.0 - gain) + x * gain);
+                }
+            }
+            chunk.clear();
+            let _ = self.empty.push(chunk);
+        }
+    }
+
+    /// Request the tap to deliver its partially filled chunk on its next processing call,
+    /// so that it can be collected with `update`.
+    /// The partial chunk is also delivered when the tap is dropped.
+    pub fn flush(&self) {
+        self.flush.store(true, Ordering::Release);
+    }
+
+    /// Recorded wave. Call `update` first to collect the latest chunks.
+    pub fn wave(&self) -> &Wave64 {
+        &self.wave
+    }
+
+    /// Number of frames dropped so far because no empty chunks were available.
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Current tap time in samples. This is the time of the next sample to be processed.
+    pub fn time(&self) -> u64 {
+        self.time.load(Ordering::Acquire)
+    }
+
+    /// Arm the tap: from now on, it records only between punch points.
+    pub fn arm(&self) {
+        self.command(PunchCommand::Arm);
+    }
+
+    /// Disarm the tap: from now on, it records continuously.
+    pub fn disarm(&self) {
+        self.command(PunchCommand::Disarm);
+    }
+
+    /// Start recording at tap time `time` samples if the tap is armed.
+    /// Times in the past take effect immediately.
+    pub fn punch_in(&self, time: u64) {
+        self.command(PunchCommand::In(time));
+    }
+
+    /// Stop recording at tap time `time` samples if the tap is armed.
+    /// Times in the past take effect immediately.
+    pub fn punch_out(&self, time: u64) {
+        self.command(PunchCommand::Out(time));
+    }
+
+    /// Set crossfade time at punch points in seconds (default is 0.01 seconds).
+    pub fn set_crossfade(&self, time: f64) {
+        assert!(time >= 0.0);
+        self.command(PunchCommand::Crossfade(time));
+    }
+
+    /// Send command to the tap. Commands are dropped if the tap does not keep up.
+    fn command(&self, command: PunchCommand) {
+        let _ = self.commands.push(command);
+    }
+}
+
+/// Recording tap. Passes through input and appends it to a `SharedWave`.
+/// Punch recording is controlled from the `SharedWave`.
+/// - Input(s): signal
+/// - Output(s): signal
+pub struct TapRecord<T: Float, N: Size<T>> {
+    _marker: PhantomData<(T, N)>,
+    empty: Arc<ThingBuf<Vec<f64>>>,
+    filled: Arc<ThingBuf<Vec<f64>>>,
+    flush: Arc<AtomicBool>,
+    dropped: Arc<AtomicU64>,
+    commands: Arc<ThingBuf<PunchCommand>>,
+    shared_time: Arc<AtomicU64>,
+    /// Chunk being filled. This has no capacity if we ran out of chunks.
+    chunk: Vec<f64>,
+    /// Tap time in samples.
+    time: u64,
+    /// Whether we record only between punch points.
+    armed: bool,
+    /// Whether we are between punch points.
+    punched: bool,
+    punch_in: Option<u64>,
+    punch_out: Option<u64>,
+    /// Crossfade gain of the recording.
+    gain: f64,
+    /// Crossfade time in seconds.
+    crossfade: f64,
+    sample_rate: f64,
+}
+
+impl<T: Float, N: Size<T>> Clone for TapRecord<T, N> {
+    fn clone(&self) -> Self {
+        Self {
+            _marker: PhantomData,
+            empty: self.empty.clone(),
+            filled: self.filled.clone(),
+            flush: self.flush.clone(),
+            dropped: self.dropped.clone(),
+            commands: self.commands.clone(),
+            shared_time: self.shared_time.clone(),
+            chunk: Vec::new(),
+            time: self.time,
+            armed: self.armed,
+            punched: self.punched,
+            punch_in: self.punch_in,
+            punch_out: self.punch_out,
+            gain: self.gain,
+            crossfade: self.crossfade,
+            sample_rate: self.sample_rate,
+        }
+    }
+}
+
+impl<T: Float, N: Size<T>> TapRecord<T, N> {
+    /// Create new recording tap that records into `wave`.
+    /// The number of channels in `wave` must be `N`.
+    pub fn new(wave: &SharedWave) -> Self {
+        assert!(wave.channels() == N::USIZE);
+        Self {
+            _marker: PhantomData,
+            empty: wave.empty.clone(),
+            filled: wave.filled.clone(),
+            flush: wave.flush.clone(),
+            dropped: wave.dropped.clone(),
+            commands: wave.commands.clone(),
+            shared_time: wave.time.clone(),
+            chunk: Vec::new(),
+            time: 0,
+            armed: false,
+            punched: false,
+            punch_in: None,
+            punch_out: None,
+            gain: 1.0,
+            crossfade: 0.01,
+            sample_rate: DEFAULT_SR,
+        }
+    }
+
+    /// Deliver the current chunk, if there is anything in it.
+    #[inline]
+    fn deliver(&mut self) {
+        if !self.chunk.is_empty() {
+            // There are as many slots as there are chunks, so this never fails.
+            let _ = self.filled.push(std::mem::take(&mut self.chunk));
+        }
+    }
+
+    /// Apply pending punch recording commands.
+    #[inline]
+    fn apply_commands(&mut self) {
+        while let Some(command) = self.commands.pop() {
+            match command {
+                PunchCommand::Arm => {
+                    self.armed = true;
+                    self.punched = false;
+                }
+                PunchCommand::Disarm => self.armed = false,
+                PunchCommand::In(time) => self.punch_in = Some(time),
+                PunchCommand::Out(time) => self.punch_out = Some(time),
+                PunchCommand::Crossfade(time) => self.crossfade = time,
+            }
+        }
+    }
+}
+
+impl<T: Float, N: Size<T>> Drop for TapRecord<T, N> {
+    fn drop(&mut self) {
+        self.deliver();
+    }
+}
+
+impl<T: Float, N: Size<T>> AudioNode for TapRecord<T, N> {
+    const ID: u64 = 116;
+    type Sample = T;
+    type Inputs = N;
+    type Outputs = N;
+    type Setting = ();
+
+    fn reset(&mut self) {
+        self.apply_commands();
+        self.deliver();
+        self.time = 0;
+        self.punched = false;
+        self.gain = if self.armed { 0.0 } else { 1.0 };
+        self.shared_time.store(0, Ordering::Release);
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate;
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        self.apply_commands();
+        if self.punch_in.is_some_and(|time| time <= self.time) {
+            self.punched = true;
+            self.punch_in = None;
+        }
+        if self.punch_out.is_some_and(|time| time <= self.time) {
+            self.punched = false;
+            self.punch_out = None;
+        }
+        let target = if !self.armed || self.punched {
+            1.0
+        } else {
+            0.0
+        };
+        let step = 1.0 / max(1.0, self.crossfade * self.sample_rate);
+        // Snap to the target to avoid accumulating rounding errors in the ramp.
+        self.gain = if abs(target - self.gain) <= step * 1.001 {
+            target
+        } else if target > self.gain {
+            self.gain + step
+        } else {
+            self.gain - step
+        };
+        if self.gain > 0.0 {
+            if self.chunk.capacity() == 0 {
+                if let Some(chunk) = self.empty.pop() {
+                    self.chunk = chunk;
+                }
+            }
+            if self.chunk.capacity() > 0 {
+                if self.chunk.is_empty() {
+                    self.chunk.push(self.time as f64);
+                }
+                self.chunk.extend(input.iter().map(|x| x.to_f64()));
+                self.chunk.push(self.gain);
+                // The chunk is full when it holds its tap time and `CHUNK_FRAMES` frames.
+                if self.chunk.len() > CHUNK_FRAMES * (N::USIZE + 1)
+                    || self.flush.load(Ordering::Acquire)
+                        && self.flush.swap(false, Ordering::AcqRel)
+                {
+                    self.deliver();
+                }
+            } else {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+            }
+        } else {
+            // Chunks hold contiguous frames, so a gap ends the chunk.
+            self.deliver();
+        }
+        self.time += 1;
+        self.shared_time.store(self.time, Ordering::Release);
+        input.clone()
+    }
+
+    fn has_side_effects(&self) -> bool {
+        true
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        input.clone()
+    }
+}
@@ -5,7 +5,13 @@ use super::math::*;
 use super::signal::*;
 use super::*;
 use numeric_array::typenum::*;
+use std::any::Any;
 
+/// Cubic variable speed resampler of a generator.
+/// The read position in samples of the enclosed generator starts at 1
+/// and is advanced by the speed input before each output sample is read.
+/// At speed 1, output sample `n` is therefore sample `n + 2` of the generator:
+/// the resampler reads 2 samples ahead, which gives the cubic interpolator the slope it needs.
 #[derive(Clone)]
 pub struct Resampler<T, X>
 where
@@ -41,6 +47,12 @@ where
         }
     }
 
+    /// Read position of the latest output sample in samples of the enclosed generator.
+    #[inline]
+    pub fn position(&self) -> f64 {
+        self.consumer
+    }
+
     // Access enclosed node.
     #[inline]
     pub fn node(&self) -> &X {
@@ -116,6 +128,14 @@ where
         self.x.ping(probe, hash.hash(Self::ID))
     }
 
+    fn visit(&mut self, tag: u64, f: &mut dyn FnMut(&mut dyn Any)) {
+        self.x.visit(tag, f);
+    }
+
+    fn has_side_effects(&self) -> bool {
+        self.x.has_side_effects()
+    }
+
     fn allocate(&mut self) {
         self.x.allocate();
     }
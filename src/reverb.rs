@@ -0,0 +1,402 @@
+//! Stereo feedback delay network reverb.
+
+use super::audionode::*;
+use super::feedback::*;
+use super::math::*;
+use super::signal::*;
+use super::*;
+use numeric_array::typenum::*;
+
+/// Maximum number of feedback delay lines.
+const LINES: usize = 32;
+
+/// Optimized delay times for a 32-channel FDN from a legacy project.
+/// These are applied unchanged for a 10 meter room.
+const DELAYS: [f64; LINES] = [
+    0.073904, 0.052918, 0.066238, 0.066387, 0.037783, 0.080073, 0.050961, 0.075900, 0.043646,
+    0.072095, 0.056194, 0.045961, 0.058934, 0.068016, 0.047529, 0.058156, 0.072972, 0.036084,
+    0.062715, 0.076377, 0.044339, 0.076725, 0.077884, 0.046126, 0.067741, 0.049800, 0.051709,
+    0.082923, 0.070121, 0.079315, 0.055039, 0.081859,
+];
+
+/// Allpass delay times in seconds of the left input decorrelator.
+const LEFT_DIFFUSION: [f64; 4] = [0.004771, 0.003595, 0.002734, 0.001987];
+
+/// Allpass delay times in seconds of the right input decorrelator.
+const RIGHT_DIFFUSION: [f64; 4] = [0.004931, 0.003307, 0.002459, 0.001753];
+
+/// Allpass gain of the input decorrelators.
+const DIFFUSION_GAIN: f64 = 0.5;
+
+/// Maximum modulation depth in seconds.
+const MAX_DEPTH: f64 = 0.01;
+
+/// Reverb quality tier. Tiers trade CPU use for density and smoothness of the reverb tail.
+/// Reverbs of all tiers have the same inputs and outputs,
+/// so they can be exchanged at runtime, for example, in a `Slot`.
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub enum ReverbQuality {
+    /// 8 delay lines with modulation updated every 32 samples.
+    Low,
+    /// 16 delay lines with modulation updated every 8 samples.
+    Medium,
+    /// 32 delay lines with modulation updated every sample.
+    #[default]
+    High,
+}
+
+impl ReverbQuality {
+    /// Number of feedback delay lines.
+    pub fn lines(&self) -> usize {
+        match self {
+            ReverbQuality::Low => 8,
+            ReverbQuality::Medium => 16,
+            ReverbQuality::High => 32,
+        }
+    }
+
+    /// Modulation update interval in samples.
+    pub fn update_interval(&self) -> usize {
+        match self {
+            ReverbQuality::Low => 32,
+            ReverbQuality::Medium => 8,
+            ReverbQuality::High => 1,
+        }
+    }
+}
+
+/// Schroeder allpass used for input decorrelation.
+#[derive(Clone)]
+struct Diffuser<T: Real> {
+    buffer: Vec<T>,
+    i: usize,
+}
+
+impl<T: Real> Diffuser<T> {
+    fn new() -> Self {
+        Self {
+            buffer: vec![T::zero()],
+            i: 0,
+        }
+    }
+
+    fn set_length(&mut self, length: usize) {
+        self.buffer.clear();
+        self.buffer.resize(max(1, length), T::zero());
+        self.i = 0;
+    }
+
+    fn reset(&mut self) {
+        self.buffer.fill(T::zero());
+        self.i = 0;
+    }
+
+    #[inline]
+    fn tick(&mut self, x: T) -> T {
+        let g = T::from_f64(DIFFUSION_GAIN);
+        let delayed = self.buffer[self.i];
+        let v = x - g * delayed;
+        self.buffer[self.i] = v;
+        self.i += 1;
+        if self.i == self.buffer.len() {
+            self.i = 0;
+        }
+        delayed + g * v
+    }
+}
+
+/// Modulated delay line followed by a 3-point lowpass FIR.
+#[derive(Clone)]
+struct ReverbLine<T: Real> {
+    buffer: Vec<T>,
+    i: usize,
+    /// Delay in samples before modulation.
+    delay: T,
+    /// Sine and cosine of the LFO phase offset of this line.
+    offset: (T, T),
+    z1: T,
+    z2: T,
+}
+
+impl<T: Real> ReverbLine<T> {
+    #[inline]
+    fn tick(&mut self, x: T, modulation: T, a: T) -> T {
+        let mask = self.buffer.len() - 1;
+        self.buffer[self.i] = x;
+        let tap = self.delay + modulation;
+        let tap_floor = tap.to_f64() as usize;
+        let tap_d = tap - T::new(tap_floor as i64);
+        let i0 = (self.i + self.buffer.len() - tap_floor) & mask;
+        let i1 = (i0 + self.buffer.len() - 1) & mask;
+        let y = lerp(self.buffer[i0], self.buffer[i1], tap_d);
+        self.i = (self.i + 1) & mask;
+        let output =
+            a * (y * T::from_f64(0.25) + self.z1 * T::from_f64(0.5) + self.z2 * T::from_f64(0.25));
+        self.z2 = self.z1;
+        self.z1 = y;
+        output
+    }
+}
+
+/// Stereo reverb. A feedback delay network of 8, 16 or 32 modulated delay lines,
+/// depending on the quality tier. Each input channel is decorrelated with a chain of allpass filters before it enters the network.
+/// Allocates: the delay lines.
+/// Setting: (modulation depth, modulation rate).
+/// - Input 0: left signal
+/// - Input 1: right signal
+/// - Output 0: reverberated left signal
+/// - Output 1: reverberated right signal
+#[derive(Clone)]
+pub struct Reverb<T: Real> {
+    room_size: f64,
+    time: f64,
+    sample_rate: f64,
+    depth: T,
+    rate: T,
+    phase: T,
+    /// Feedback gain of the lowpass filter in each line.
+    a: T,
+    quality: ReverbQuality,
+    lines: Vec<ReverbLine<T>>,
+    feedback: [T; LINES],
+    /// Current modulation of each line in samples.
+    modulation: [T; LINES],
+    /// Samples until the next modulation update.
+    countdown: usize,
+    left: [Diffuser<T>; 4],
+    right: [Diffuser<T>; 4],
+    /// Decay tracking of the tail.
+    decay: DecayTracker,
+}
+
+impl<T: Real> Reverb<T> {
+    /// Create new reverb. `room_size` is in meters. An average room size is 10 meters.
+    /// `time` is approximate reverberation time to -60 dB in seconds.
+    /// Delay lines are modulated with `depth` seconds at `rate` Hz.
+    /// `quality` selects the number of delay lines and the modulation update rate.
+    pub fn new(room_size: f64, time: f64, depth: T, rate: T, quality: ReverbQuality) -> Self {
+        assert!(room_size > 0.0 && time > 0.0);
+        let n = quality.lines();
+        let lines = (0..n)
+            .map(|i| {
+                let angle = i as f64 / n as f64 * TAU;
+                ReverbLine {
+                    buffer: vec![],
+                    i: 0,
+                    delay: T::zero(),
+                    offset: (T::from_f64(sin(angle)), T::from_f64(cos(angle))),
+                    z1: T::zero(),
+                    z2: T::zero(),
+                }
+            })
+            .collect();
+        let mut node = Self {
+            room_size,
+            time,
+            sample_rate: 0.0,
+            depth: T::zero(),
+            rate,
+            phase: T::zero(),
+            a: T::from_f64(pow(db_amp(-60.0), 0.03 * room_size / 10.0 / time)),
+            quality,
+            lines,
+            feedback: [T::zero(); LINES],
+            modulation: [T::zero(); LINES],
+            countdown: 0,
+            left: std::array::from_fn(|_| Diffuser::new()),
+            right: std::array::from_fn(|_| Diffuser::new()),
+            decay: DecayTracker::default(),
+        };
+        node.set_modulation(depth, rate);
+        node.set_sample_rate(DEFAULT_SR);
+        node
+    }
+
+    /// Room size in meters.
+    pub fn room_size(&self) -> f64 {
+        self.room_size
+    }
+
+    /// Reverberation time to -60 dB in seconds.
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+
+    /// Quality tier.
+    pub fn quality(&self) -> ReverbQuality {
+        self.quality
+    }
+
+    /// Modulation depth in seconds.
+    pub fn depth(&self) -> T {
+        self.depth
+    }
+
+    /// Modulation rate in Hz.
+    pub fn rate(&self) -> T {
+        self.rate
+    }
+
+    /// Enable decay tracking. When the input and the delay line outputs have stayed
+    /// at or below amplitude `threshold` for the length of the network, the tail is cleared
+    /// and processing is skipped until the input rises above the threshold again.
+    /// A zero threshold disables tracking, which is the default.
+    pub fn set_decay_tracking(&mut self, threshold: T) {
+        self.decay = DecayTracker::new(threshold.to_f64(), self.decay.hold());
+    }
+
+    /// Whether the tail has decayed and processing is being skipped.
+    pub fn is_quiescent(&self) -> bool {
+        self.decay.is_quiescent()
+    }
+
+    /// Clear delay lines and diffusers.
+    fn clear(&mut self) {
+        for line in self.lines.iter_mut() {
+            line.buffer.fill(T::zero());
+            line.i = 0;
+            line.z1 = T::zero();
+            line.z2 = T::zero();
+        }
+        self.left.iter_mut().for_each(|x| x.reset());
+        self.right.iter_mut().for_each(|x| x.reset());
+        self.feedback = [T::zero(); LINES];
+    }
+
+    /// Set modulation `depth` in seconds and modulation `rate` in Hz of the delay lines.
+    /// Depth is limited to 10 milliseconds.
+    pub fn set_modulation(&mut self, depth: T, rate: T) {
+        assert!(depth >= T::zero());
+        self.depth = min(depth, T::from_f64(MAX_DEPTH));
+        self.rate = rate;
+    }
+}
+
+impl<T: Real> AudioNode for Reverb<T> {
+    const ID: u64 = 122;
+    type Sample = T;
+    type Inputs = U2;
+    type Outputs = U2;
+    type Setting = (T, T);
+
+    fn set(&mut self, setting: Self::Setting) {
+        self.set_modulation(setting.0, setting.1);
+    }
+
+    fn reset(&mut self) {
+        self.clear();
+        self.modulation = [T::zero(); LINES];
+        self.countdown = 0;
+        self.phase = T::zero();
+        self.decay.reset();
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        if self.sample_rate != sample_rate {
+            self.sample_rate = sample_rate;
+            // Lower tiers use a spread out subset of the delay times.
+            let step = LINES / self.lines.len();
+            for (line, delay) in self.lines.iter_mut().zip(DELAYS.iter().step_by(step)) {
+                let delay = delay * self.room_size / 10.0 * sample_rate;
+                let length = ceil(delay + MAX_DEPTH * sample_rate) as usize + 2;
+                line.buffer.clear();
+                line.buffer.resize(length.next_power_of_two(), T::zero());
+                line.delay = T::from_f64(max(1.0, delay));
+            }
+            for (diffuser, time) in self.left.iter_mut().zip(LEFT_DIFFUSION.iter()) {
+                diffuser.set_length(round(time * sample_rate) as usize);
+            }
+            for (diffuser, time) in self.right.iter_mut().zip(RIGHT_DIFFUSION.iter()) {
+                diffuser.set_length(round(time * sample_rate) as usize);
+            }
+            // The tail is tracked over the longest path through the diffusers and delay lines.
+            let diffusion = max(
+                self.left.iter().map(|x| x.buffer.len()).sum::<usize>(),
+                self.right.iter().map(|x| x.buffer.len()).sum::<usize>(),
+            );
+            let line = self.lines.iter().map(|x| x.buffer.len()).max().unwrap_or(0);
+            self.decay.set_hold(diffusion + line);
+            self.reset();
+        }
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        if self.decay.is_quiescent() {
+            if self.decay.is_quiet(peak_level(input)) {
+                return [T::zero(), T::zero()].into();
+            }
+            self.decay.reset();
+        }
+        let left = self.left.iter_mut().fold(input[0], |x, d| d.tick(x));
+        let right = self.right.iter_mut().fold(input[1], |x, d| d.tick(x));
+        let n = self.lines.len();
+        if self.countdown == 0 {
+            let interval = self.quality.update_interval();
+            self.countdown = interval;
+            let angle = self.phase * T::from_f64(TAU);
+            let (sin_phase, cos_phase) = (sin(angle), cos(angle));
+            // Modulation is centered at half depth so the delay never falls below its base value.
+            let half_depth = self.depth * T::from_f64(0.5 * self.sample_rate);
+            for (modulation, line) in self.modulation.iter_mut().zip(self.lines.iter()) {
+                let lfo = sin_phase * line.offset.1 + cos_phase * line.offset.0;
+                *modulation = half_depth * (T::one() + lfo);
+            }
+            self.phase += self.rate * T::new(interval as i64) / T::from_f64(self.sample_rate);
+            self.phase -= floor(self.phase);
+        }
+        self.countdown -= 1;
+        let mut output = [T::zero(); LINES];
+        for (i, line) in self.lines.iter_mut().enumerate() {
+            let x = if i & 1 == 0 { left } else { right };
+            output[i] = line.tick(x + self.feedback[i], self.modulation[i], self.a);
+        }
+        self.feedback = output;
+        hadamard(&mut self.feedback[..n]);
+        if self
+            .decay
+            .track(max(peak_level(input), peak_level(&output[..n])))
+        {
+            // The tail has decayed: clear it before it reaches denormal levels.
+            self.clear();
+            return [T::zero(), T::zero()].into();
+        }
+        // Average even lines to the left channel and odd lines to the right channel.
+        let mut output_left = T::zero();
+        let mut output_right = T::zero();
+        for i in (0..n).step_by(2) {
+            output_left += output[i];
+            output_right += output[i + 1];
+        }
+        let normalize = T::from_f64(2.0 / n as f64);
+        [output_left * normalize, output_right * normalize].into()
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        Routing::Arbitrary.propagate(input, self.outputs())
+    }
+}
+
+/// In-place normalized Hadamard transform. The length of `x` must be a power of two.
+#[inline]
+fn hadamard<T: Real>(x: &mut [T]) {
+    let mut h = 1;
+    while h < x.len() {
+        let mut i = 0;
+        while i < x.len() {
+            for j in i..i + h {
+                let a = x[j];
+                let b = x[j + h];
+                x[j] = a + b;
+                x[j + h] = a - b;
+            }
+            i += h * 2;
+        }
+        h *= 2;
+    }
+    let normalize = T::from_f64(1.0 / sqrt(x.len() as f64));
+    x.iter_mut().for_each(|y| *y *= normalize);
+}
@@ -1,12 +1,17 @@
 //! The sequencer unit mixes together scheduled audio units with sample accurate timing.
 
+use super::audionode::*;
 use super::audiounit::*;
 use super::buffer::*;
+use super::combinator::*;
 use super::math::*;
+use super::net::*;
 use super::realseq::*;
 use super::signal::*;
+use super::tuning::*;
 use super::*;
 use duplicate::duplicate_item;
+use numeric_array::typenum::*;
 use std::cmp::Eq;
 use std::cmp::Ord;
 use std::cmp::Ordering;
@@ -24,6 +29,8 @@ pub enum Fade {
     /// Smooth polynomial fade.
     #[default]
     Smooth,
+    /// Linear fade.
+    Linear,
 }
 
 impl Fade {
@@ -33,10 +40,141 @@ impl Fade {
         match self {
             Fade::Power => sine_ease(x),
             Fade::Smooth => smooth5(x),
+            Fade::Linear => x,
         }
     }
 }
 
+/// Step timing template for sequencing patterns with `Sequencer64::push_step`
+/// and `Sequencer32::push_step`. A groove places steps on an even grid,
+/// then applies swing, a repeating template of timing and velocity offsets
+/// and optional randomized humanization.
+///
+/// ### Example: Swung Sixteenths
+/// ```
+/// use fundsp::hacker::*;
+/// let mut groove = Groove::from_tempo(120.0, 4);
+/// groove.set_swing(0.5);
+/// groove.set_template(&[(0.0, 1.0), (0.0, 0.6), (0.0, 0.8), (0.0, 0.6)]);
+/// groove.set_humanize(0.005, 0.1);
+/// assert!(groove.step_time(1) > 0.125);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Groove {
+    /// Duration of one step in seconds.
+    step_duration: f64,
+    /// Swing amount in 0...1.
+    swing: f64,
+    /// Repeating template of (timing offset in steps, velocity) pairs.
+    template: Vec<(f64, f64)>,
+    /// Maximum random timing offset in seconds.
+    humanize_time: f64,
+    /// Maximum random relative velocity offset.
+    humanize_velocity: f64,
+    seed: u64,
+}
+
+impl Groove {
+    /// Create a straight groove with steps of `step_duration` seconds.
+    pub fn new(step_duration: f64) -> Self {
+        assert!(step_duration > 0.0);
+        Self {
+            step_duration,
+            swing: 0.0,
+            template: vec![(0.0, 1.0)],
+            humanize_time: 0.0,
+            humanize_velocity: 0.0,
+            seed: 0,
+        }
+    }
+
+    /// Create a straight groove with `steps_per_beat` steps per beat at `bpm` beats per minute.
+    /// For example, `Groove::from_tempo(120.0, 4)` is a sixteenth note grid in 4/4 time.
+    pub fn from_tempo(bpm: f64, steps_per_beat: usize) -> Self {
+        assert!(bpm > 0.0 && steps_per_beat > 0);
+        Self::new(60.0 / (bpm * steps_per_beat as f64))
+    }
+
+    /// Duration of one step in seconds.
+    #[inline]
+    pub fn step_duration(&self) -> f64 {
+        self.step_duration
+    }
+
+    /// Swing amount in 0...1.
+    #[inline]
+    pub fn swing(&self) -> f64 {
+        self.swing
+    }
+
+    /// Set swing amount in 0...1. Every odd step is delayed by `swing` steps.
+    /// Zero is straight timing, 1/3 is triplet swing and 1/2 is dotted swing.
+    pub fn set_swing(&mut self, swing: f64) {
+        self.swing = clamp01(swing);
+    }
+
+    /// Set the groove template as a repeating pattern of (timing offset, velocity) pairs,
+    /// one per step. Timing offsets are measured in steps; positive offsets delay the step.
+    /// Velocities are gain multipliers. The default template is `&[(0.0, 1.0)]`.
+    pub fn set_template(&mut self, template: &[(f64, f64)]) {
+        assert!(!template.is_empty());
+        self.template = template.to_vec();
+    }
+
+    /// Set humanization ranges. Each step is moved by a random offset of at most
+    /// `time` seconds in either direction and its velocity is scaled by a random factor
+    /// in 1 - `velocity`...1 + `velocity`. Random offsets are a function of step number
+    /// and seed, so that rescheduling a pattern plays it identically.
+    pub fn set_humanize(&mut self, time: f64, velocity: f64) {
+        assert!(time >= 0.0 && velocity >= 0.0);
+        self.humanize_time = time;
+        self.humanize_velocity = velocity;
+    }
+
+    /// Set random seed of humanization.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+    }
+
+    /// Template entry of `step`.
+    #[inline]
+    fn template_at(&self, step: i64) -> (f64, f64) {
+        self.template[step.rem_euclid(self.template.len() as i64) as usize]
+    }
+
+    /// Random number in -1...1 for `step` and `stream`.
+    #[inline]
+    fn random(&self, step: i64, stream: i64) -> f64 {
+        let hash = AttoHash::new(self.seed)
+            .hash(step as u64)
+            .hash(stream as u64);
+        hash.hash11::<f64>()
+    }
+
+    /// Start time of `step` in seconds. Step 0 starts at time zero before offsets.
+    pub fn step_time(&self, step: i64) -> f64 {
+        let mut position = step as f64 + self.template_at(step).0;
+        if step.rem_euclid(2) == 1 {
+            position += self.swing;
+        }
+        let humanize = if self.humanize_time > 0.0 {
+            self.humanize_time * self.random(step, 0)
+        } else {
+            0.0
+        };
+        position * self.step_duration + humanize
+    }
+
+    /// Velocity (gain multiplier) of `step`.
+    pub fn step_velocity(&self, step: i64) -> f64 {
+        let mut velocity = self.template_at(step).1;
+        if self.humanize_velocity > 0.0 {
+            velocity *= 1.0 + self.humanize_velocity * self.random(step, 1);
+        }
+        max(0.0, velocity)
+    }
+}
+
 /// Globally unique ID for a sequencer event.
 #[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
 pub struct EventId(u64);
@@ -195,6 +333,15 @@ fn fade_in48(
                     }
                 }
             }
+            Fade::Linear => {
+                for channel in 0..output.len() {
+                    let mut fade = fade_phase;
+                    for x in output[channel][..fade_end_i].iter_mut() {
+                        *x *= fade;
+                        fade += fade_d;
+                    }
+                }
+            }
         }
     }
 }
@@ -248,6 +395,15 @@ fn fade_out48(
                     }
                 }
             }
+            Fade::Linear => {
+                for channel in 0..output.len() {
+                    let mut fade = fade_phase;
+                    for x in output[channel][fade_i..end_index].iter_mut() {
+                        *x *= 1.0 - fade;
+                        fade += fade_d;
+                    }
+                }
+            }
         }
     }
 }
@@ -278,6 +434,12 @@ pub struct Sequencer48 {
     sample_duration: f48,
     buffer: Buffer<f48>,
     tick_buffer: Vec<f48>,
+    /// Control changes (time, channel, value) sorted by time.
+    controls: Vec<(f48, usize, f48)>,
+    /// Index of the next control change to apply.
+    control_index: usize,
+    /// Current control value of each output channel.
+    control_level: Vec<f48>,
     /// Optional frontend.
     front: Option<(Sender<Message48>, Receiver<Option<Event48>>)>,
     replay_events: bool,
@@ -306,6 +468,9 @@ impl Clone for Sequencer48 {
             sample_duration: self.sample_duration,
             buffer: self.buffer.clone(),
             tick_buffer: self.tick_buffer.clone(),
+            controls: self.controls.clone(),
+            control_index: self.control_index,
+            control_level: self.control_level.clone(),
             front: None,
             replay_events: self.replay_events,
         }
@@ -314,9 +479,9 @@ impl Clone for Sequencer48 {
 
 #[allow(clippy::unnecessary_cast)]
 #[duplicate_item(
-    f48       Event48       AudioUnit48       Sequencer48       SequencerBackend48       Message48       Edit48;
-    [ f64 ]   [ Event64 ]   [ AudioUnit64 ]   [ Sequencer64 ]   [ SequencerBackend64 ]   [ Message64 ]   [ Edit64 ];
-    [ f32 ]   [ Event32 ]   [ AudioUnit32 ]   [ Sequencer32 ]   [ SequencerBackend32 ]   [ Message32 ]   [ Edit32 ];
+    f48       Event48       AudioUnit48       Sequencer48       SequencerBackend48       Message48       Edit48       Net48;
+    [ f64 ]   [ Event64 ]   [ AudioUnit64 ]   [ Sequencer64 ]   [ SequencerBackend64 ]   [ Message64 ]   [ Edit64 ]   [ Net64 ];
+    [ f32 ]   [ Event32 ]   [ AudioUnit32 ]   [ Sequencer32 ]   [ SequencerBackend32 ]   [ Message32 ]   [ Edit32 ]   [ Net32 ];
 )]
 impl Sequencer48 {
     /// Create a new sequencer. The sequencer has zero inputs.
@@ -338,6 +503,9 @@ impl Sequencer48 {
             sample_duration: 1.0 / DEFAULT_SR as f48,
             buffer: Buffer::with_channels(outputs),
             tick_buffer: vec![0.0; outputs],
+            controls: Vec::with_capacity(16384),
+            control_index: 0,
+            control_level: vec![0.0; outputs],
             front: None,
             replay_events,
         }
@@ -349,6 +517,11 @@ impl Sequencer48 {
         self.time
     }
 
+    /// Sample rate of the sequencer.
+    pub fn sample_rate(&self) -> f64 {
+        self.sample_rate as f64
+    }
+
     /// Add an event. All times are specified in seconds.
     /// Fade in and fade out may overlap but may not exceed the duration of the event.
     /// Returns the ID of the event.
@@ -470,6 +643,82 @@ impl Sequencer48 {
         )
     }
 
+    /// Add an event at `step` of `groove`, lasting `duration` steps.
+    /// The start time and velocity of the event come from the groove.
+    /// The duration is not affected by timing offsets.
+    /// Velocities other than 1 are applied by scaling the output of the unit.
+    /// Fade in and fade out may overlap but may not exceed the duration of the event.
+    /// Returns the ID of the event.
+    #[allow(clippy::too_many_arguments)]
+    pub fn push_step(
+        &mut self,
+        groove: &Groove,
+        step: i64,
+        duration: f48,
+        fade_ease: Fade,
+        fade_in_time: f48,
+        fade_out_time: f48,
+        unit: Box<dyn AudioUnit48>,
+    ) -> EventId {
+        // Events cannot start before time zero.
+        let start_time = max(0.0, groove.step_time(step)) as f48;
+        let velocity = groove.step_velocity(step) as f48;
+        let unit: Box<dyn AudioUnit48> = if velocity != 1.0 {
+            Box::new(Net48::wrap(unit) * velocity)
+        } else {
+            unit
+        };
+        self.push_duration(
+            start_time,
+            duration * groove.step_duration() as f48,
+            fade_ease,
+            fade_in_time,
+            fade_out_time,
+            unit,
+        )
+    }
+
+    /// Add an event playing fractional MIDI note number `note` in `tuning`.
+    /// The unit has one input, which receives the frequency of the note in Hz.
+    /// Fade in and fade out may overlap but may not exceed the duration of the event.
+    /// Returns the ID of the event.
+    ///
+    /// ### Example: Note In Just Intonation
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let scl = "Just major\n7\n9/8\n5/4\n4/3\n3/2\n5/3\n15/8\n2/1\n";
+    /// let tuning = Tuning::from_scl(scl).unwrap();
+    /// let mut sequencer = Sequencer64::new(false, 1);
+    /// sequencer.push_note(0.0, 1.0, Fade::Smooth, 0.01, 0.1, &tuning, 64.0, Box::new(saw()));
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    pub fn push_note(
+        &mut self,
+        start_time: f48,
+        end_time: f48,
+        fade_ease: Fade,
+        fade_in_time: f48,
+        fade_out_time: f48,
+        tuning: &Tuning,
+        note: f64,
+        unit: Box<dyn AudioUnit48>,
+    ) -> EventId {
+        assert!(unit.inputs() == 1);
+        let mut pitch = Net48::new(0, 1);
+        let id = pitch.push(Box::new(An(Constant::<U1, f48>::new(
+            [tuning.hz(note) as f48].into(),
+        ))));
+        pitch.connect_output(id, 0, 0);
+        self.push(
+            start_time,
+            end_time,
+            fade_ease,
+            fade_in_time,
+            fade_out_time,
+            Box::new(pitch >> Net48::wrap(unit)),
+        )
+    }
+
     /// Make a change to an existing event. Only the end time and fade out time
     /// of the event may be changed. The new end time can only be used to shorten events.
     /// Edits are intended to be used with events where we do not know ahead of time
@@ -550,6 +799,53 @@ impl Sequencer48 {
         }
     }
 
+    /// Set control value of output `channel` to `value` at `time` seconds, sample accurately.
+    /// Control values are held until changed and are added to the outputs of events.
+    /// With control values, the sequencer can drive control inputs of a persistent unit,
+    /// such as the gate and pitch of a synth voice, without pushing a unit per note.
+    /// Control values are replayed after a reset if `replay_events` is set.
+    ///
+    /// ### Example: Notes On A Persistent Voice
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let mut sequencer = Sequencer64::new(false, 2);
+    /// for (i, note) in [60.0, 64.0, 67.0].iter().enumerate() {
+    ///     let time = i as f64 * 0.25;
+    ///     sequencer.set_control(time, 0, midi_hz(*note));
+    ///     sequencer.push_control(time, time + 0.2, 1, 1.0);
+    /// }
+    /// let mut voice = Net64::wrap(Box::new(sequencer)) >> (saw() * adsr_live(0.01, 0.1, 0.5, 0.1));
+    /// voice.get_mono();
+    /// ```
+    pub fn set_control(&mut self, time: f48, channel: usize, value: f48) {
+        assert!(channel < self.outputs);
+        if let Some((sender, receiver)) = &mut self.front {
+            // Deallocate all past events.
+            while receiver.try_recv().is_ok() {}
+            // Send the control change over.
+            if sender
+                .try_send(Message48::Control(time, channel, value))
+                .is_ok()
+            {}
+        } else {
+            // Changes at equal times are applied in the order they were made.
+            // Changes in the past are applied as soon as possible.
+            let index = self
+                .controls
+                .partition_point(|control| control.0 <= time)
+                .max(self.control_index);
+            self.controls.insert(index, (time, channel, value));
+        }
+    }
+
+    /// Hold control value of output `channel` at `value` from `start_time` until `end_time`
+    /// (in seconds), after which it returns to zero. This is useful for gates, for example.
+    pub fn push_control(&mut self, start_time: f48, end_time: f48, channel: usize, value: f48) {
+        assert!(start_time <= end_time);
+        self.set_control(start_time, channel, value);
+        self.set_control(end_time, channel, 0.0);
+    }
+
     /// Move units that start before the end time to the active set.
     fn ready_to_active(&mut self, next_end_time: f48) {
         self.active_threshold = next_end_time - self.sample_duration * 0.5;
@@ -650,6 +946,11 @@ impl AudioUnit48 for Sequencer48 {
             self.edit_map.clear();
             self.active_map.clear();
         }
+        if !self.replay_events {
+            self.controls.clear();
+        }
+        self.control_index = 0;
+        self.control_level.fill(0.0);
         self.time = 0.0;
         self.active_threshold = -f48::INFINITY;
     }
@@ -682,10 +983,19 @@ impl AudioUnit48 for Sequencer48 {
     fn tick(&mut self, input: &[f48], output: &mut [f48]) {
         if !self.replay_events {
             while let Some(_past) = self.past.pop() {}
+            if self.control_index > 0 {
+                self.controls.drain(..self.control_index);
+                self.control_index = 0;
+            }
         }
-        for channel in 0..self.outputs {
-            output[channel] = 0.0;
+        while self.control_index < self.controls.len()
+            && self.controls[self.control_index].0 < self.time + 0.5 * self.sample_duration
+        {
+            let (_, channel, value) = self.controls[self.control_index];
+            self.control_level[channel] = value;
+            self.control_index += 1;
         }
+        output[..self.outputs].copy_from_slice(&self.control_level);
         let end_time = self.time + self.sample_duration;
         self.ready_to_active(end_time);
         let mut i = 0;
@@ -717,6 +1027,11 @@ impl AudioUnit48 for Sequencer48 {
                                     self.tick_buffer[channel] *= smooth5(fade_in);
                                 }
                             }
+                            Fade::Linear => {
+                                for channel in 0..self.outputs {
+                                    self.tick_buffer[channel] *= fade_in;
+                                }
+                            }
                         }
                     }
                 }
@@ -738,6 +1053,11 @@ impl AudioUnit48 for Sequencer48 {
                                     self.tick_buffer[channel] *= smooth5(1.0 - fade_out);
                                 }
                             }
+                            Fade::Linear => {
+                                for channel in 0..self.outputs {
+                                    self.tick_buffer[channel] *= 1.0 - fade_out;
+                                }
+                            }
                         }
                     }
                 }
@@ -753,11 +1073,33 @@ impl AudioUnit48 for Sequencer48 {
     fn process(&mut self, size: usize, input: &[&[f48]], output: &mut [&mut [f48]]) {
         if !self.replay_events {
             while let Some(_past) = self.past.pop() {}
+            if self.control_index > 0 {
+                self.controls.drain(..self.control_index);
+                self.control_index = 0;
+            }
         }
-        for channel in 0..self.outputs {
-            for i in 0..size {
-                output[channel][i] = 0.0;
+        // Control values are written first and events are added on top.
+        let mut start = 0;
+        while self.control_index < self.controls.len() {
+            let (time, channel, value) = self.controls[self.control_index];
+            let i = if time <= self.time {
+                0
+            } else {
+                round((time - self.time) * self.sample_rate) as usize
+            };
+            if i >= size {
+                break;
+            }
+            let i = i.max(start);
+            for (lane, level) in self.control_level.iter().enumerate() {
+                output[lane][start..i].fill(*level);
             }
+            start = i;
+            self.control_level[channel] = value;
+            self.control_index += 1;
+        }
+        for (lane, level) in self.control_level.iter().enumerate() {
+            output[lane][start..size].fill(*level);
         }
         let end_time = self.time + self.sample_duration * size as f48;
         self.ready_to_active(end_time);
@@ -841,6 +1183,14 @@ impl AudioUnit48 for Sequencer48 {
         signal
     }
 
+    fn has_side_effects(&self) -> bool {
+        self.active
+            .iter()
+            .chain(self.ready.iter())
+            .chain(self.past.iter())
+            .any(|event| event.unit.has_side_effects())
+    }
+
     fn footprint(&self) -> usize {
         std::mem::size_of::<Self>()
     }
@@ -4,6 +4,7 @@ use super::audionode::*;
 use super::combinator::*;
 use super::math::*;
 use super::signal::*;
+use std::any::Any;
 pub use thingbuf::mpsc::blocking::Sender;
 use thingbuf::mpsc::blocking::{channel, Receiver};
 
@@ -90,6 +91,14 @@ impl<X: AudioNode> AudioNode for Listen<X> {
         self.x.ping(probe, hash.hash(Self::ID))
     }
 
+    fn visit(&mut self, tag: u64, f: &mut dyn FnMut(&mut dyn Any)) {
+        self.x.visit(tag, f);
+    }
+
+    fn has_side_effects(&self) -> bool {
+        true
+    }
+
     fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
         self.receive_settings();
         self.x.route(input, frequency)
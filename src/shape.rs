@@ -25,6 +25,12 @@ where
             _marker: PhantomData::default(),
         }
     }
+
+    /// Sample the transfer curve into `curve`.
+    /// Inputs are spaced evenly from `-range` to `range`.
+    pub fn transfer_curve(&self, range: T, curve: &mut [T]) {
+        sample_curve(range, curve, &self.f);
+    }
 }
 
 impl<T, S> AudioNode for ShaperFn<T, S>
@@ -66,9 +72,10 @@ where
 }
 
 /// Waveshaping modes.
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub enum Shape<T: Real> {
     /// Clip signal to -1...1.
+    #[default]
     Clip,
     /// Clip signal between the two arguments.
     ClipTo(T, T),
@@ -91,12 +98,14 @@ pub enum Shape<T: Real> {
 }
 
 /// Waveshaper with various shaping modes.
+/// Setting: shape.
 #[derive(Clone)]
 pub struct Shaper<T: Real> {
     shape: Shape<T>,
     /// Per-sample smoothing factor.
     smoothing: T,
     state: T,
+    sample_rate: f64,
 }
 
 impl<T: Real> Shaper<T> {
@@ -105,10 +114,78 @@ impl<T: Real> Shaper<T> {
             shape,
             smoothing: T::zero(),
             state: T::zero(),
+            sample_rate: DEFAULT_SR,
         };
         shaper.set_sample_rate(DEFAULT_SR);
         shaper
     }
+
+    /// Set shaping mode. The level estimate of adaptive distortion is kept.
+    pub fn set_shape(&mut self, shape: Shape<T>) {
+        self.shape = shape;
+        self.set_sample_rate(self.sample_rate);
+    }
+
+    /// Shape `input` with the shape parameter scaled by `drive`.
+    /// Drive is input gain for the clipping and distortion modes
+    /// and a multiplier of the number of levels for the crushing modes.
+    #[inline]
+    fn shape_driven(&mut self, input: T, drive: T) -> T {
+        if let Shape::AdaptiveTanh(_, _) = self.shape {
+            self.state = self.smoothing * self.state
+                + (T::one() - self.smoothing) * (T::from_f32(1.0e-6) + squared(input));
+        }
+        self.shape_level(input, drive, sqrt(self.state))
+    }
+
+    /// Shape `input` with the shape parameter scaled by `drive`
+    /// and `level` as the RMS level estimate of adaptive distortion.
+    #[inline]
+    fn shape_level(&self, input: T, drive: T, level: T) -> T {
+        match self.shape {
+            Shape::Clip => clamp11(input * drive),
+            Shape::ClipTo(min, max) => clamp(min, max, input * drive),
+            Shape::Tanh(hardness) => tanh_dsp(input * hardness * drive),
+            Shape::Softsign(hardness) => softsign(input * hardness * drive),
+            Shape::Crush(levels) => {
+                let levels = max(T::from_f64(1.0e-9), levels * drive);
+                round(input * levels) / levels
+            }
+            Shape::SoftCrush(levels) => {
+                let levels = max(T::from_f64(1.0e-9), levels * drive);
+                let x = input * levels;
+                let y = floor(x);
+                (y + smooth9(smooth9(x - y))) / levels
+            }
+            Shape::AdaptiveTanh(_timescale, hardness) => tanh_dsp(input * hardness * drive / level),
+        }
+    }
+
+    /// Instantaneous transfer function: the response to `input`
+    /// with the shape parameter scaled by `drive`, without updating any state.
+    /// Adaptive distortion uses its current level estimate.
+    pub fn transfer(&self, input: T, drive: T) -> T {
+        self.shape_level(input, drive, sqrt(max(T::from_f32(1.0e-6), self.state)))
+    }
+
+    /// Sample the instantaneous transfer curve with unit drive into `curve`.
+    /// Inputs are spaced evenly from `-range` to `range`.
+    pub fn transfer_curve(&self, range: T, curve: &mut [T]) {
+        sample_curve(range, curve, |x| self.transfer(x, T::one()));
+    }
+}
+
+/// Sample `f` into `curve` at inputs spaced evenly from `-range` to `range`.
+fn sample_curve<T: Float>(range: T, curve: &mut [T], f: impl Fn(T) -> T) {
+    let n = curve.len();
+    for (i, y) in curve.iter_mut().enumerate() {
+        let x = if n > 1 {
+            range * (T::new(2 * i as i64) / T::new(n as i64 - 1) - T::one())
+        } else {
+            T::zero()
+        };
+        *y = f(x);
+    }
 }
 
 impl<T: Real> AudioNode for Shaper<T> {
@@ -116,13 +193,18 @@ impl<T: Real> AudioNode for Shaper<T> {
     type Sample = T;
     type Inputs = U1;
     type Outputs = U1;
-    type Setting = ();
+    type Setting = Shape<T>;
+
+    fn set(&mut self, setting: Self::Setting) {
+        self.set_shape(setting);
+    }
 
     fn reset(&mut self) {
         self.state = T::zero();
     }
 
     fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate;
         if let Shape::AdaptiveTanh(timescale, _) = self.shape {
             self.smoothing = T::from_f64(pow(0.5, 1.0 / (timescale.to_f64() * sample_rate)));
         }
@@ -137,7 +219,7 @@ impl<T: Real> AudioNode for Shaper<T> {
         match self.shape {
             Shape::Clip => [clamp11(input)].into(),
             Shape::ClipTo(min, max) => [clamp(min, max, input)].into(),
-            Shape::Tanh(hardness) => [tanh(input * hardness)].into(),
+            Shape::Tanh(hardness) => [tanh_dsp(input * hardness)].into(),
             Shape::Softsign(hardness) => [softsign(input * hardness)].into(),
             Shape::Crush(levels) => [round(input * levels) / levels].into(),
             Shape::SoftCrush(levels) => {
@@ -148,7 +230,7 @@ impl<T: Real> AudioNode for Shaper<T> {
             Shape::AdaptiveTanh(_timescale, hardness) => {
                 self.state = self.smoothing * self.state
                     + (T::one() - self.smoothing) * (T::from_f32(1.0e-6) + squared(input));
-                [tanh(input * hardness / sqrt(self.state))].into()
+                [tanh_dsp(input * hardness / sqrt(self.state))].into()
             }
         }
     }
@@ -174,7 +256,7 @@ impl<T: Real> AudioNode for Shaper<T> {
             }
             Shape::Tanh(hardness) => {
                 for (x, y) in output[0..size].iter_mut().zip(input[0..size].iter()) {
-                    *x = tanh(*y * hardness);
+                    *x = tanh_dsp(*y * hardness);
                 }
             }
             Shape::Softsign(hardness) => {
@@ -198,7 +280,7 @@ impl<T: Real> AudioNode for Shaper<T> {
                 for (x, y) in output[0..size].iter_mut().zip(input[0..size].iter()) {
                     self.state = self.smoothing * self.state
                         + (T::one() - self.smoothing) * (T::from_f32(1.0e-6) + squared(*y));
-                    *x = tanh(*y * hardness / sqrt(self.state));
+                    *x = tanh_dsp(*y * hardness / sqrt(self.state));
                 }
             }
         }
@@ -210,3 +292,202 @@ impl<T: Real> AudioNode for Shaper<T> {
         output
     }
 }
+
+/// Waveshaper with an audio-rate drive input.
+/// Drive is input gain for the clipping and distortion modes
+/// and a multiplier of the number of levels for the crushing modes.
+/// Setting: shape.
+/// - Input 0: input signal
+/// - Input 1: drive
+/// - Output 0: shaped signal
+#[derive(Clone)]
+pub struct ShaperMod<T: Real> {
+    shaper: Shaper<T>,
+    drive: T,
+}
+
+impl<T: Real> ShaperMod<T> {
+    pub fn new(shape: Shape<T>) -> Self {
+        Self {
+            shaper: Shaper::new(shape),
+            drive: T::one(),
+        }
+    }
+
+    /// Set shaping mode.
+    pub fn set_shape(&mut self, shape: Shape<T>) {
+        self.shaper.set_shape(shape);
+    }
+
+    /// Most recent drive input. Drive is 1 before any input has been processed.
+    pub fn drive(&self) -> T {
+        self.drive
+    }
+
+    /// Sample the instantaneous transfer curve at the most recent drive into `curve`.
+    /// Inputs are spaced evenly from `-range` to `range`.
+    pub fn transfer_curve(&self, range: T, curve: &mut [T]) {
+        sample_curve(range, curve, |x| self.shaper.transfer(x, self.drive));
+    }
+}
+
+impl<T: Real> AudioNode for ShaperMod<T> {
+    const ID: u64 = 95;
+    type Sample = T;
+    type Inputs = U2;
+    type Outputs = U1;
+    type Setting = Shape<T>;
+
+    fn set(&mut self, setting: Self::Setting) {
+        self.set_shape(setting);
+    }
+
+    fn reset(&mut self) {
+        self.shaper.reset();
+        self.drive = T::one();
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.shaper.set_sample_rate(sample_rate);
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        self.drive = input[1];
+        [self.shaper.shape_driven(input[0], input[1])].into()
+    }
+
+    fn process(
+        &mut self,
+        size: usize,
+        input: &[&[Self::Sample]],
+        output: &mut [&mut [Self::Sample]],
+    ) {
+        for i in 0..size {
+            output[0][i] = self.shaper.shape_driven(input[0][i], input[1][i]);
+        }
+        if size > 0 {
+            self.drive = input[1][size - 1];
+        }
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        Routing::Arbitrary.propagate(input, self.outputs())
+    }
+}
+
+/// Summing bus with soft saturation. The inputs are summed and the sum is passed through
+/// `tanh` with antiderivative antialiasing (ADAA), which adds gentle "analog summing" character
+/// without the aliasing of plain waveshaping. Small signals pass at unity gain;
+/// the output approaches 1 / `drive` in magnitude for large signals.
+/// Antialiasing delays the signal by half a sample.
+/// Setting: drive.
+/// - Input(s): signals to sum.
+/// - Output 0: saturated sum.
+#[derive(Clone)]
+pub struct BusSaturate<N: Size<T>, T: Real> {
+    _marker: PhantomData<N>,
+    drive: T,
+    /// Previous driven sum.
+    x1: T,
+    /// Antiderivative at the previous driven sum.
+    f1: T,
+}
+
+impl<N: Size<T>, T: Real> BusSaturate<N, T> {
+    /// Create a new saturating bus. Drive (`drive` > 0) is gain applied before saturation.
+    pub fn new(drive: T) -> Self {
+        assert!(drive > T::zero());
+        Self {
+            _marker: PhantomData,
+            drive,
+            x1: T::zero(),
+            f1: T::zero(),
+        }
+    }
+
+    /// Drive, that is, gain applied before saturation.
+    #[inline]
+    pub fn drive(&self) -> T {
+        self.drive
+    }
+
+    /// Set drive (`drive` > 0).
+    #[inline]
+    pub fn set_drive(&mut self, drive: T) {
+        assert!(drive > T::zero());
+        self.drive = drive;
+    }
+
+    /// Antiderivative of `tanh`, `log(cosh(x))`, in a form that does not overflow.
+    #[inline]
+    fn log_cosh(x: T) -> T {
+        let a = abs(x);
+        a + log(T::one() + exp(T::new(-2) * a)) - T::from_f64(std::f64::consts::LN_2)
+    }
+
+    /// Saturate the driven sum `x`.
+    #[inline]
+    fn saturate(&mut self, x: T) -> T {
+        let f = Self::log_cosh(x);
+        let dx = x - self.x1;
+        let y = if abs(dx) > T::from_f64(1.0e-5) {
+            (f - self.f1) / dx
+        } else {
+            // The difference quotient is ill-conditioned, use the midpoint instead.
+            tanh((x + self.x1) * T::from_f64(0.5))
+        };
+        self.x1 = x;
+        self.f1 = f;
+        y / self.drive
+    }
+}
+
+impl<N: Size<T>, T: Real> AudioNode for BusSaturate<N, T> {
+    const ID: u64 = 129;
+    type Sample = T;
+    type Inputs = N;
+    type Outputs = U1;
+    type Setting = T;
+
+    fn set(&mut self, setting: Self::Setting) {
+        self.set_drive(setting);
+    }
+
+    fn reset(&mut self) {
+        self.x1 = T::zero();
+        self.f1 = T::zero();
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let sum = input.iter().fold(T::zero(), |sum, x| sum + *x);
+        [self.saturate(sum * self.drive)].into()
+    }
+
+    fn process(
+        &mut self,
+        size: usize,
+        input: &[&[Self::Sample]],
+        output: &mut [&mut [Self::Sample]],
+    ) {
+        for i in 0..size {
+            let sum = input.iter().fold(T::zero(), |sum, x| sum + x[i]);
+            output[0][i] = self.saturate(sum * self.drive);
+        }
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        output[0] = (1..N::USIZE)
+            .fold(input[0], |sum, i| sum.combine_nonlinear(input[i], 0.0))
+            .distort(0.5);
+        output
+    }
+}
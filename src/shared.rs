@@ -294,4 +294,8 @@ impl<T: Atomic> AudioNode for Timer<T> {
         self.time += size as f64 * self.sample_duration;
         self.shared.set_value(T::from_f64(self.time));
     }
+
+    fn has_side_effects(&self) -> bool {
+        true
+    }
 }
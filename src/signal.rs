@@ -41,6 +41,14 @@ impl Signal {
         }
     }
 
+    /// Latency of the signal in samples, if known.
+    pub fn latency(&self) -> Option<f64> {
+        match self {
+            Signal::Latency(l) | Signal::Response(_, l) => Some(*l),
+            _ => None,
+        }
+    }
+
     /// Delay signal by `latency` samples.
     pub fn delay(&self, latency: f64) -> Signal {
         match self {
@@ -125,6 +133,120 @@ pub fn copy_signal_frame(source: &SignalFrame, i: usize, n: usize) -> SignalFram
     frame
 }
 
+/// Kind of signal expected at an input or produced at an output.
+/// Graph editors can use port kinds to flag obviously mismatched connections.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum PortKind {
+    /// Audio signal.
+    #[default]
+    Audio,
+    /// Generic control signal, for example, from an envelope or an LFO.
+    Control,
+    /// Frequency in Hz.
+    Frequency,
+    /// Filter Q.
+    Q,
+    /// Amplitude gain.
+    Gain,
+    /// Trigger or gate. Rising edges start events.
+    Trigger,
+}
+
+impl PortKind {
+    /// Whether an output of this kind can be connected to an input of kind `input`.
+    /// Audio and generic control signals can be connected anywhere,
+    /// for example, to modulate parameters, and anything can be connected to audio
+    /// and generic control inputs. Otherwise, the kinds must match.
+    ///
+    /// ### Example
+    /// ```
+    /// use fundsp::hacker::*;
+    /// assert!(PortKind::Audio.is_compatible(PortKind::Frequency));
+    /// assert!(!PortKind::Trigger.is_compatible(PortKind::Frequency));
+    /// ```
+    pub fn is_compatible(self, input: PortKind) -> bool {
+        self == input
+            || matches!(self, PortKind::Audio | PortKind::Control)
+            || matches!(input, PortKind::Audio | PortKind::Control)
+    }
+}
+
+/// Named layout of a group of channels. Multichannel units can declare the layout of
+/// their inputs and outputs, which networks use to catch mis-wired surround graphs.
+/// Speaker orders are: stereo (L, R); quad (L, R, Ls, Rs); 5.1 (L, R, C, LFE, Ls, Rs);
+/// and 7.1 (L, R, C, LFE, Lss, Rss, Lrs, Rrs).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ChannelLayout {
+    /// One channel.
+    Mono,
+    /// Left and right channels.
+    Stereo,
+    /// Front and surround pairs.
+    Quad,
+    /// 5.1 surround.
+    Surround51,
+    /// 7.1 surround.
+    Surround71,
+    /// Ambisonics of the given order in ACN channel order with (order + 1)^2 channels.
+    Ambisonic(usize),
+}
+
+impl ChannelLayout {
+    /// Number of channels in the layout.
+    ///
+    /// ### Example
+    /// ```
+    /// use fundsp::hacker::*;
+    /// assert!(ChannelLayout::Surround51.channels() == 6);
+    /// assert!(ChannelLayout::Ambisonic(1).channels() == 4);
+    /// ```
+    pub fn channels(self) -> usize {
+        match self {
+            ChannelLayout::Mono => 1,
+            ChannelLayout::Stereo => 2,
+            ChannelLayout::Quad => 4,
+            ChannelLayout::Surround51 => 6,
+            ChannelLayout::Surround71 => 8,
+            ChannelLayout::Ambisonic(order) => (order + 1) * (order + 1),
+        }
+    }
+
+    /// Speaker layout with `channels` channels, if any.
+    /// Ambisonic layouts are never returned as their channel counts are ambiguous.
+    pub fn speakers(channels: usize) -> Option<ChannelLayout> {
+        match channels {
+            1 => Some(ChannelLayout::Mono),
+            2 => Some(ChannelLayout::Stereo),
+            4 => Some(ChannelLayout::Quad),
+            6 => Some(ChannelLayout::Surround51),
+            8 => Some(ChannelLayout::Surround71),
+            _ => None,
+        }
+    }
+
+    /// Whether channels in an optional `output` layout can be connected to an optional `input` layout.
+    /// Undeclared layouts are compatible with anything. Otherwise, the layouts must match.
+    pub fn is_compatible(output: Option<ChannelLayout>, input: Option<ChannelLayout>) -> bool {
+        match (output, input) {
+            (Some(output), Some(input)) => output == input,
+            _ => true,
+        }
+    }
+}
+
+impl std::fmt::Display for ChannelLayout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChannelLayout::Mono => write!(f, "mono"),
+            ChannelLayout::Stereo => write!(f, "stereo"),
+            ChannelLayout::Quad => write!(f, "quad"),
+            ChannelLayout::Surround51 => write!(f, "5.1"),
+            ChannelLayout::Surround71 => write!(f, "7.1"),
+            ChannelLayout::Ambisonic(order) => write!(f, "ambisonic order {}", order),
+        }
+    }
+}
+
 /// Signal routing information. This is a dumping ground for signal routing
 /// functionality.
 #[derive(Clone)]
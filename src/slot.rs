@@ -159,9 +159,12 @@ impl Clone for SlotBackend48 {
 )]
 impl SlotBackend48 {
     /// Handle updates.
+    #[allow(clippy::unnecessary_cast)]
     fn handle_messages(&mut self) {
         while let Ok(message) = self.receiver.try_recv() {
-            if let SlotMessage48::Update(fade, fade_time, unit) = message {
+            if let SlotMessage48::Update(fade, fade_time, mut unit) = message {
+                // The frontend does not know our sample rate.
+                unit.set_sample_rate(self.sample_rate as f64);
                 if self.next.is_none() {
                     self.next = Some(unit);
                     self.fade_phase = 0.0;
@@ -264,6 +267,12 @@ impl AudioUnit48 for SlotBackend48 {
                             fade += 1.0 / (self.fade_time * self.sample_rate);
                         }
                     }
+                    Fade::Linear => {
+                        for x in output[i][..n].iter_mut() {
+                            *x *= 1.0 - fade;
+                            fade += 1.0 / (self.fade_time * self.sample_rate);
+                        }
+                    }
                 }
             }
             next.process(size, input, self.buffer.self_mut());
@@ -288,6 +297,15 @@ impl AudioUnit48 for SlotBackend48 {
                             fade += 1.0 / (self.fade_time * self.sample_rate);
                         }
                     }
+                    Fade::Linear => {
+                        for (x, y) in output[i][..n]
+                            .iter_mut()
+                            .zip(self.buffer.mut_at(i)[..n].iter())
+                        {
+                            *x += *y * fade;
+                            fade += 1.0 / (self.fade_time * self.sample_rate);
+                        }
+                    }
                 }
                 for (x, y) in output[i][n..size]
                     .iter_mut()
@@ -330,6 +348,10 @@ impl AudioUnit48 for SlotBackend48 {
         hash.hash(self.get_id())
     }
 
+    fn has_side_effects(&self) -> bool {
+        true
+    }
+
     fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
         self.current.route(input, frequency)
     }
@@ -112,6 +112,21 @@ pub struct SnoopBackend<T: Float> {
     sender: Sender<SnoopBuffer<T>>,
 }
 
+impl<T: Float> SnoopBackend<T> {
+    /// Send `data` to the snoop frontend.
+    #[inline]
+    pub fn record(&mut self, data: &[T]) {
+        for &x in data {
+            self.buffer.set(self.index, x);
+            self.index += 1;
+            if self.index == MAX_BUFFER_SIZE {
+                if self.sender.try_send(self.buffer.clone()).is_ok() {}
+                self.index = 0;
+            }
+        }
+    }
+}
+
 impl<T: Float> AudioNode for SnoopBackend<T> {
     const ID: u64 = 77;
     type Sample = T;
@@ -128,12 +143,7 @@ impl<T: Float> AudioNode for SnoopBackend<T> {
         &mut self,
         input: &Frame<Self::Sample, Self::Inputs>,
     ) -> Frame<Self::Sample, Self::Outputs> {
-        self.buffer.set(self.index, input[0]);
-        self.index += 1;
-        if self.index == MAX_BUFFER_SIZE {
-            if self.sender.try_send(self.buffer.clone()).is_ok() {}
-            self.index = 0;
-        }
+        self.record(input);
         *input
     }
 
@@ -144,15 +154,12 @@ impl<T: Float> AudioNode for SnoopBackend<T> {
         output: &mut [&mut [Self::Sample]],
     ) {
         output[0][..size].clone_from_slice(&input[0][..size]);
-        for i in 0..size {
-            self.buffer.set(self.index, input[0][i]);
-            self.index += 1;
-            if self.index == MAX_BUFFER_SIZE {
-                if self.sender.try_send(self.buffer.clone()).is_ok() {}
-                self.index = 0;
-            }
-        }
+        self.record(&input[0][..size]);
     }
+    fn has_side_effects(&self) -> bool {
+        true
+    }
+
     fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
         input.clone()
     }
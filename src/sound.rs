@@ -1,6 +1,7 @@
 //! FunDSP Sound Library. WIP.
 
 use super::hacker::*;
+use super::math::exp_dsp;
 use funutd::*;
 
 /// Sound 001. Risset Glissando, stereo.
@@ -72,7 +73,7 @@ pub fn bassdrum(
 ) -> An<impl AudioNode<Sample = f64, Inputs = U0, Outputs = U1>> {
     let sweep = lfo(move |t| xerp(pitch0, pitch1, clamp01(t * 50.0)) - 10.0 * t) >> sine();
 
-    let volume = lfo(|t| exp(-t * 9.0));
+    let volume = lfo(|t| exp_dsp(-t * 9.0));
 
     sweep * volume >> declick_s(xerp(0.002, 0.00002, sharpness))
 }
@@ -110,9 +111,9 @@ pub fn snaredrum(
         + bend_sine(f7)
         + bend_sine(f8);
 
-    let mix = modes01 * 0.2 * lfo(|t| exp(-t * 16.0))
-        + modes28 * 0.1 * lfo(|t| exp(-t * 14.0))
-        + pink() * 0.7 * lfo(|t| exp(-t * 12.0));
+    let mix = modes01 * 0.2 * lfo(|t| exp_dsp(-t * 16.0))
+        + modes28 * 0.1 * lfo(|t| exp_dsp(-t * 14.0))
+        + pink() * 0.7 * lfo(|t| exp_dsp(-t * 12.0));
 
     (mix | lfo(|t| xerp(15000.0, 1000.0, t)))
         >> lowpass_q(1.0)
@@ -136,7 +137,7 @@ pub fn cymbal(seed: i64) -> An<impl AudioNode<Sample = f64, Inputs = U0, Outputs
         + (square_hz(f3) * m2 + f4 >> square())
         + (square_hz(f5) * m3 + f6 >> square());
 
-    (complex * lfo(|t| exp(-t * 8.0)) | lfo(|t| xerp(20000.0, 2000.0, clamp01(t))))
+    (complex * lfo(|t| exp_dsp(-t * 8.0)) | lfo(|t| xerp(20000.0, 2000.0, clamp01(t))))
         >> lowpass_q(1.0)
         >> highpass_hz(2500.0, 1.0)
         >> declick_s(0.001)
@@ -0,0 +1,782 @@
+//! Spectral processing components.
+
+use super::audionode::*;
+use super::math::*;
+use super::signal::*;
+use super::*;
+use num_complex::Complex64;
+use numeric_array::typenum::*;
+use rustfft::algorithm::Radix4;
+use rustfft::Fft;
+use rustfft::FftDirection;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+/// Number of overlapping windows in the short-time Fourier transform.
+const OVERLAP: usize = 4;
+
+/// Window length of spectral effects in samples.
+const SPECTRAL_WINDOW: usize = 2048;
+
+/// Spectral frame of a short-time Fourier transform.
+/// Processing reads input bins and writes output bins.
+/// Output bins are zero at the start of processing.
+#[derive(Clone)]
+pub struct StftWindow {
+    input: Vec<Complex64>,
+    output: Vec<Complex64>,
+    length: usize,
+    sample_rate: f64,
+}
+
+impl StftWindow {
+    /// Number of bins from DC to Nyquist frequency, inclusive.
+    #[inline]
+    pub fn bins(&self) -> usize {
+        self.length / 2 + 1
+    }
+
+    /// Window length in samples.
+    #[inline]
+    pub fn length(&self) -> usize {
+        self.length
+    }
+
+    /// Distance between consecutive windows in samples.
+    #[inline]
+    pub fn hop(&self) -> usize {
+        self.length / OVERLAP
+    }
+
+    /// Sample rate in Hz.
+    #[inline]
+    pub fn sample_rate(&self) -> f64 {
+        self.sample_rate
+    }
+
+    /// Center frequency of `bin` in Hz.
+    #[inline]
+    pub fn frequency(&self, bin: usize) -> f64 {
+        bin as f64 * self.sample_rate / self.length as f64
+    }
+
+    /// Input spectrum at `bin`.
+    #[inline]
+    pub fn input(&self, bin: usize) -> Complex64 {
+        self.input[bin]
+    }
+
+    /// Output spectrum at `bin`.
+    #[inline]
+    pub fn output(&self, bin: usize) -> Complex64 {
+        self.output[bin]
+    }
+
+    /// Set output spectrum at `bin`.
+    #[inline]
+    pub fn set(&mut self, bin: usize, value: Complex64) {
+        self.output[bin] = value;
+    }
+}
+
+/// Short-time Fourier transform engine for building spectral effects.
+/// Input is analyzed in Hann windowed frames that overlap by a factor of 4.
+/// Each frame is processed in the frequency domain and resynthesized
+/// with a Hann window and overlap-add. Latency is equal to the window length.
+#[derive(Clone)]
+pub struct Stft {
+    window: StftWindow,
+    /// Hann window.
+    hann: Vec<f64>,
+    /// History of recent inputs (ring buffer).
+    history: Vec<f64>,
+    /// Index of the latest input in the history.
+    history_i: usize,
+    /// Overlap-add buffer. The first element is the next output sample.
+    accumulator: Vec<f64>,
+    /// Position inside the current hop.
+    j: usize,
+    forward: Arc<dyn Fft<f64>>,
+    inverse: Arc<dyn Fft<f64>>,
+    buffer: Vec<Complex64>,
+    scratch: Vec<Complex64>,
+}
+
+impl Stft {
+    /// Create new STFT engine. Window `length` is a power of two and at least 16.
+    pub fn new(length: usize) -> Self {
+        assert!(length.is_power_of_two() && length >= 16);
+        let forward: Arc<dyn Fft<f64>> = Arc::new(Radix4::new(length, FftDirection::Forward));
+        let inverse: Arc<dyn Fft<f64>> = Arc::new(Radix4::new(length, FftDirection::Inverse));
+        let scratch_length = std::cmp::max(
+            forward.get_inplace_scratch_len(),
+            inverse.get_inplace_scratch_len(),
+        );
+        Self {
+            window: StftWindow {
+                input: vec![Complex64::default(); length / 2 + 1],
+                output: vec![Complex64::default(); length / 2 + 1],
+                length,
+                sample_rate: DEFAULT_SR,
+            },
+            hann: (0..length)
+                .map(|i| 0.5 - 0.5 * cos(TAU * i as f64 / length as f64))
+                .collect(),
+            history: vec![0.0; length],
+            history_i: 0,
+            accumulator: vec![0.0; length],
+            j: 0,
+            forward,
+            inverse,
+            buffer: vec![Complex64::default(); length],
+            scratch: vec![Complex64::default(); scratch_length],
+        }
+    }
+
+    /// Window length in samples.
+    pub fn length(&self) -> usize {
+        self.window.length
+    }
+
+    /// Distance between consecutive windows in samples.
+    pub fn hop(&self) -> usize {
+        self.window.hop()
+    }
+
+    /// Set sample rate reported to processing.
+    pub fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.window.sample_rate = sample_rate;
+    }
+
+    /// Reset state.
+    pub fn reset(&mut self) {
+        self.history.fill(0.0);
+        self.history_i = 0;
+        self.accumulator.fill(0.0);
+        self.j = 0;
+    }
+
+    /// Process one sample `x`. Processing function `f` is called
+    /// once per hop with the latest spectral frame.
+    #[inline]
+    pub fn tick<F: FnMut(&mut StftWindow)>(&mut self, x: f64, f: F) -> f64 {
+        let mask = self.window.length - 1;
+        self.history_i = (self.history_i + 1) & mask;
+        self.history[self.history_i] = x;
+        let y = self.accumulator[self.j];
+        self.j += 1;
+        if self.j == self.hop() {
+            self.j = 0;
+            self.process(f);
+        }
+        y
+    }
+
+    /// Analyze, process and resynthesize the latest frame.
+    fn process<F: FnMut(&mut StftWindow)>(&mut self, mut f: F) {
+        let length = self.window.length;
+        let mask = length - 1;
+        let bins = self.window.bins();
+        for (i, x) in self.buffer.iter_mut().enumerate() {
+            let sample = self.history[(self.history_i + 1 + i) & mask];
+            *x = Complex64::new(sample * self.hann[i], 0.0);
+        }
+        self.forward
+            .process_with_scratch(&mut self.buffer, &mut self.scratch);
+        self.window.input.copy_from_slice(&self.buffer[..bins]);
+        self.window.output.fill(Complex64::default());
+        f(&mut self.window);
+        self.buffer[..bins].copy_from_slice(&self.window.output);
+        self.buffer[0].im = 0.0;
+        self.buffer[length / 2].im = 0.0;
+        for i in 1..length / 2 {
+            self.buffer[length - i] = self.buffer[i].conj();
+        }
+        self.inverse
+            .process_with_scratch(&mut self.buffer, &mut self.scratch);
+        let hop = self.hop();
+        self.accumulator.copy_within(hop.., 0);
+        self.accumulator[length - hop..].fill(0.0);
+        // Squared Hann windows overlapping by a factor of 4 sum to 1.5.
+        let z = 1.0 / (1.5 * length as f64);
+        for ((y, x), w) in self
+            .accumulator
+            .iter_mut()
+            .zip(self.buffer.iter())
+            .zip(self.hann.iter())
+        {
+            *y += x.re * w * z;
+        }
+    }
+}
+
+/// Spectral freeze. While frozen, holds the magnitude spectrum of the input
+/// and keeps advancing the phase of each bin at its measured frequency.
+/// Drift randomizes the phase advance, which smears the frozen sound.
+/// Latency is 2048 samples.
+/// - Input 0: input signal
+/// - Input 1: freeze (hold when > 0)
+/// - Input 2: drift amount in 0...1
+/// - Output 0: frozen signal
+#[derive(Clone)]
+pub struct SpectralFreeze<T: Float> {
+    _marker: PhantomData<T>,
+    stft: Stft,
+    /// Held magnitude of each bin.
+    magnitude: Vec<f64>,
+    /// Phase advance of each bin per hop.
+    advance: Vec<f64>,
+    /// Input phase of each bin in the previous frame.
+    previous: Vec<f64>,
+    /// Output phase of each bin.
+    phase: Vec<f64>,
+    /// Latest freeze control value.
+    freeze: f64,
+    /// Latest drift control value.
+    drift: f64,
+    /// Random number index.
+    rnd: i64,
+    hash: u64,
+}
+
+impl<T: Float> SpectralFreeze<T> {
+    /// Create new spectral freeze.
+    pub fn new() -> Self {
+        let stft = Stft::new(SPECTRAL_WINDOW);
+        let bins = SPECTRAL_WINDOW / 2 + 1;
+        Self {
+            _marker: PhantomData,
+            stft,
+            magnitude: vec![0.0; bins],
+            advance: vec![0.0; bins],
+            previous: vec![0.0; bins],
+            phase: vec![0.0; bins],
+            freeze: 0.0,
+            drift: 0.0,
+            rnd: 0,
+            hash: 0,
+        }
+    }
+}
+
+impl<T: Float> Default for SpectralFreeze<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Float> AudioNode for SpectralFreeze<T> {
+    const ID: u64 = 85;
+    type Sample = T;
+    type Inputs = U3;
+    type Outputs = U1;
+    type Setting = ();
+
+    fn reset(&mut self) {
+        self.stft.reset();
+        self.magnitude.fill(0.0);
+        self.advance.fill(0.0);
+        self.previous.fill(0.0);
+        self.phase.fill(0.0);
+        self.rnd = self.hash as i64;
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.stft.set_sample_rate(sample_rate);
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        self.freeze = input[1].to_f64();
+        self.drift = clamp01(input[2].to_f64());
+        let y = self.stft.tick(input[0].to_f64(), |window| {
+            let frozen = self.freeze > 0.0;
+            let ratio = window.hop() as f64 / window.length() as f64;
+            for i in 0..window.bins() {
+                let x = window.input(i);
+                let phase = x.arg();
+                if frozen {
+                    let jitter = self.drift * PI * (rnd(self.rnd) * 2.0 - 1.0);
+                    self.rnd += 1;
+                    self.phase[i] += self.advance[i] + jitter;
+                    window.set(i, Complex64::from_polar(self.magnitude[i], self.phase[i]));
+                } else {
+                    // Measure bin frequency from phase difference of consecutive frames.
+                    let expected = TAU * i as f64 * ratio;
+                    let deviation = phase - self.previous[i] - expected;
+                    self.advance[i] = expected + deviation - TAU * round(deviation / TAU);
+                    self.magnitude[i] = x.norm();
+                    self.phase[i] = phase;
+                    window.set(i, x);
+                }
+                self.previous[i] = phase;
+            }
+        });
+        [convert(y)].into()
+    }
+
+    fn set_hash(&mut self, hash: u64) {
+        self.hash = hash;
+        self.rnd = hash as i64;
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        output[0] = input[0].distort(self.stft.length() as f64);
+        output
+    }
+}
+
+/// Spectral delay. Each frequency bin is delayed by a different amount.
+/// With positive `tilt`, delay grows linearly with frequency from zero at DC
+/// to `tilt` seconds at the Nyquist frequency. With negative `tilt`,
+/// delay falls linearly from -`tilt` seconds at DC to zero at Nyquist.
+/// Delays are quantized to the hop size of 512 samples.
+/// Latency is 2048 samples.
+/// Allocates: spectral delay line.
+/// - Input 0: input signal
+/// - Output 0: delayed signal
+#[derive(Clone)]
+pub struct SpectralDelay<T: Float> {
+    _marker: PhantomData<T>,
+    stft: Stft,
+    tilt: f64,
+    /// Delay of each bin in frames.
+    delay: Vec<usize>,
+    /// Delay line of spectral frames.
+    frames: Vec<Vec<Complex64>>,
+    /// Index of the latest frame.
+    frame_i: usize,
+    sample_rate: f64,
+}
+
+impl<T: Float> SpectralDelay<T> {
+    /// Create new spectral delay with delay `tilt` in seconds.
+    pub fn new(tilt: T) -> Self {
+        let mut node = Self {
+            _marker: PhantomData,
+            stft: Stft::new(SPECTRAL_WINDOW),
+            tilt: tilt.to_f64(),
+            delay: Vec::new(),
+            frames: Vec::new(),
+            frame_i: 0,
+            sample_rate: DEFAULT_SR,
+        };
+        node.update_delays();
+        node
+    }
+
+    /// Calculate bin delays and allocate the delay line.
+    fn update_delays(&mut self) {
+        let bins = self.stft.length() / 2 + 1;
+        let hop_time = self.stft.hop() as f64 / self.sample_rate;
+        self.delay = (0..bins)
+            .map(|i| {
+                let x = i as f64 / (bins - 1) as f64;
+                let time = if self.tilt >= 0.0 {
+                    self.tilt * x
+                } else {
+                    -self.tilt * (1.0 - x)
+                };
+                round(time / hop_time) as usize
+            })
+            .collect();
+        let frames = self.delay.iter().fold(0, |a, &d| std::cmp::max(a, d)) + 1;
+        self.frames = vec![vec![Complex64::default(); bins]; frames];
+        self.frame_i = 0;
+    }
+}
+
+impl<T: Float> AudioNode for SpectralDelay<T> {
+    const ID: u64 = 86;
+    type Sample = T;
+    type Inputs = U1;
+    type Outputs = U1;
+    type Setting = ();
+
+    fn reset(&mut self) {
+        self.stft.reset();
+        for frame in self.frames.iter_mut() {
+            frame.fill(Complex64::default());
+        }
+        self.frame_i = 0;
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.stft.set_sample_rate(sample_rate);
+        if self.sample_rate != sample_rate {
+            self.sample_rate = sample_rate;
+            self.update_delays();
+            self.reset();
+        }
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let y = self.stft.tick(input[0].to_f64(), |window| {
+            let n = self.frames.len();
+            self.frame_i = (self.frame_i + 1) % n;
+            for i in 0..window.bins() {
+                self.frames[self.frame_i][i] = window.input(i);
+                let frame = (self.frame_i + n - self.delay[i]) % n;
+                window.set(i, self.frames[frame][i]);
+            }
+        });
+        [convert(y)].into()
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        output[0] = input[0].distort(self.stft.length() as f64);
+        output
+    }
+}
+
+/// Spectral noise reduction with a learned noise profile.
+/// The noise profile is learned from frames where the learn input is positive,
+/// or, if a gate is set with `set_gate`, from frames quieter than the gate.
+/// Noise is suppressed with a Wiener filter where the a priori signal-to-noise ratio
+/// is estimated with the decision-directed method, which reduces musical noise.
+/// Until a noise profile has been learned, the signal passes through unchanged.
+/// Resetting the node forgets the noise profile.
+/// Latency is 2048 samples.
+/// - Input 0: input signal
+/// - Input 1: learn noise profile (when > 0)
+/// - Output 0: denoised signal
+#[derive(Clone)]
+pub struct Denoise<T: Float> {
+    _marker: PhantomData<T>,
+    stft: Stft,
+    /// Minimum gain of each bin.
+    floor: f64,
+    /// Mean square level below which frames are learned as noise.
+    gate: f64,
+    /// Noise power profile.
+    noise: Vec<f64>,
+    /// Power of the cleaned signal in the previous frame.
+    clean: Vec<f64>,
+    /// Number of frames learned so far, saturating.
+    learned: usize,
+    /// Latest learn control value.
+    learn: f64,
+    sample_rate: f64,
+}
+
+impl<T: Float> Denoise<T> {
+    /// Weight of the previous frame in the decision-directed estimate.
+    const ALPHA: f64 = 0.98;
+    /// Timescale of noise profile averaging in seconds.
+    const PROFILE_TIME: f64 = 1.0;
+
+    /// Create new noise reduction with maximum `reduction` in dB (for example, 20.0).
+    pub fn new(reduction: T) -> Self {
+        let bins = SPECTRAL_WINDOW / 2 + 1;
+        let mut node = Self {
+            _marker: PhantomData,
+            stft: Stft::new(SPECTRAL_WINDOW),
+            floor: 1.0,
+            gate: 0.0,
+            noise: vec![0.0; bins],
+            clean: vec![0.0; bins],
+            learned: 0,
+            learn: 0.0,
+            sample_rate: DEFAULT_SR,
+        };
+        node.set_reduction(reduction);
+        node
+    }
+
+    /// Set maximum noise `reduction` in dB.
+    pub fn set_reduction(&mut self, reduction: T) {
+        self.floor = db_amp(-max(0.0, reduction.to_f64()));
+    }
+
+    /// Learn noise profile automatically from frames with RMS level below `gate` dB
+    /// (for example, -50.0). Negative infinity disables the gate, which is the default.
+    pub fn set_gate(&mut self, gate: T) {
+        self.gate = squared(db_amp(gate.to_f64()));
+    }
+
+    /// Forget the learned noise profile.
+    pub fn clear_profile(&mut self) {
+        self.noise.fill(0.0);
+        self.learned = 0;
+    }
+
+    /// Learned noise power profile from DC to the Nyquist frequency.
+    pub fn profile(&self) -> &[f64] {
+        &self.noise
+    }
+}
+
+impl<T: Float> AudioNode for Denoise<T> {
+    const ID: u64 = 87;
+    type Sample = T;
+    type Inputs = U2;
+    type Outputs = U1;
+    type Setting = ();
+
+    fn reset(&mut self) {
+        self.stft.reset();
+        self.clean.fill(0.0);
+        self.clear_profile();
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.stft.set_sample_rate(sample_rate);
+        self.sample_rate = sample_rate;
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        self.learn = input[1].to_f64();
+        let y = self.stft.tick(input[0].to_f64(), |window| {
+            let bins = window.bins();
+            let length = window.length() as f64;
+            // Estimate mean square level of the frame via Parseval's theorem.
+            // The mean square of a Hann window is 3/8.
+            let mut energy = 0.0;
+            for i in 0..bins {
+                let weight = if i == 0 || i == bins - 1 { 1.0 } else { 2.0 };
+                energy += weight * window.input(i).norm_sqr();
+            }
+            let level = energy / (length * length * 0.375);
+            if self.learn > 0.0 || level < self.gate {
+                let steady =
+                    1.0 - exp(-(window.hop() as f64) / (Self::PROFILE_TIME * self.sample_rate));
+                let rate = max(steady, 1.0 / (self.learned + 1) as f64);
+                for i in 0..bins {
+                    self.noise[i] += (window.input(i).norm_sqr() - self.noise[i]) * rate;
+                }
+                self.learned = std::cmp::min(self.learned + 1, 1 << 20);
+            }
+            for i in 0..bins {
+                let x = window.input(i);
+                let power = x.norm_sqr();
+                let gain = if self.noise[i] > 0.0 {
+                    let posterior = power / self.noise[i];
+                    let prior = Self::ALPHA * self.clean[i] / self.noise[i]
+                        + (1.0 - Self::ALPHA) * max(posterior - 1.0, 0.0);
+                    max(self.floor, prior / (1.0 + prior))
+                } else {
+                    1.0
+                };
+                self.clean[i] = gain * gain * power;
+                window.set(i, x * gain);
+            }
+        });
+        [convert(y)].into()
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        output[0] = input[0].distort(self.stft.length() as f64);
+        output
+    }
+}
+
+/// Default window length of paulstretch in seconds.
+pub const PAULSTRETCH_WINDOW: f64 = 0.25;
+
+/// Paulstretch window length in samples for a window of `time` seconds.
+pub(crate) fn paulstretch_length(time: f64, sample_rate: f64) -> usize {
+    (max(16.0, round(time * sample_rate)) as usize).next_power_of_two()
+}
+
+/// Extreme time stretching engine. Windows read from the source are resynthesized
+/// with randomized phases and overlap-added with a Hann window.
+#[derive(Clone)]
+pub(crate) struct Stretcher {
+    length: usize,
+    /// Hann window.
+    hann: Vec<f64>,
+    /// Overlap-add buffer. The first element is the next output sample.
+    accumulator: Vec<f64>,
+    forward: Arc<dyn Fft<f64>>,
+    inverse: Arc<dyn Fft<f64>>,
+    buffer: Vec<Complex64>,
+    scratch: Vec<Complex64>,
+    /// Random number index.
+    rnd: i64,
+}
+
+impl Stretcher {
+    /// Create new engine. Window `length` is a power of two and at least 16.
+    pub fn new(length: usize) -> Self {
+        assert!(length.is_power_of_two() && length >= 16);
+        let forward: Arc<dyn Fft<f64>> = Arc::new(Radix4::new(length, FftDirection::Forward));
+        let inverse: Arc<dyn Fft<f64>> = Arc::new(Radix4::new(length, FftDirection::Inverse));
+        let scratch_length = std::cmp::max(
+            forward.get_inplace_scratch_len(),
+            inverse.get_inplace_scratch_len(),
+        );
+        Self {
+            length,
+            hann: (0..length)
+                .map(|i| 0.5 - 0.5 * cos(TAU * i as f64 / length as f64))
+                .collect(),
+            accumulator: vec![0.0; length],
+            forward,
+            inverse,
+            buffer: vec![Complex64::default(); length],
+            scratch: vec![Complex64::default(); scratch_length],
+            rnd: 0,
+        }
+    }
+
+    /// Distance between consecutive windows in samples.
+    pub fn hop(&self) -> usize {
+        self.length / OVERLAP
+    }
+
+    /// Reset state. Phases are randomized starting from `seed`.
+    pub fn reset(&mut self, seed: i64) {
+        self.accumulator.fill(0.0);
+        self.rnd = seed;
+    }
+
+    /// Resynthesize the window of `source` that starts at `position`
+    /// and write the next hop of output into `output`.
+    pub fn process(&mut self, source: &[f64], position: usize, output: &mut [f64]) {
+        let length = self.length;
+        if position < source.len() {
+            for (i, x) in self.buffer.iter_mut().enumerate() {
+                let sample = source.get(position + i).copied().unwrap_or_default();
+                *x = Complex64::new(sample * self.hann[i], 0.0);
+            }
+            self.forward
+                .process_with_scratch(&mut self.buffer, &mut self.scratch);
+            self.buffer[0] = Complex64::new(self.buffer[0].norm(), 0.0);
+            self.buffer[length / 2] = Complex64::new(self.buffer[length / 2].norm(), 0.0);
+            for i in 1..length / 2 {
+                let phase = TAU * rnd(self.rnd);
+                self.rnd += 1;
+                self.buffer[i] = Complex64::from_polar(self.buffer[i].norm(), phase);
+                self.buffer[length - i] = self.buffer[i].conj();
+            }
+            self.inverse
+                .process_with_scratch(&mut self.buffer, &mut self.scratch);
+            // Frames with random phases add up incoherently. Squared Hann windows
+            // overlapping by a factor of 4 sum to 1.5 and the mean square
+            // of the analysis window is 0.375, so power is preserved with a gain of 1 / 0.75.
+            let z = 1.0 / (0.75 * length as f64);
+            for ((y, x), w) in self
+                .accumulator
+                .iter_mut()
+                .zip(self.buffer.iter())
+                .zip(self.hann.iter())
+            {
+                *y += x.re * w * z;
+            }
+        }
+        let hop = self.hop();
+        output[..hop].copy_from_slice(&self.accumulator[..hop]);
+        self.accumulator.copy_within(hop.., 0);
+        self.accumulator[length - hop..].fill(0.0);
+    }
+}
+
+/// Paulstretch player. Plays back samples stretched in time by a large factor
+/// with the paulstretch algorithm, which smears the sound into a texture.
+/// Setting: stretch factor.
+/// - Output 0: stretched samples
+#[derive(Clone)]
+pub struct PaulStretch<T: Float> {
+    _marker: PhantomData<T>,
+    samples: Arc<Vec<f64>>,
+    stretcher: Stretcher,
+    stretch: f64,
+    /// Read position in the samples.
+    position: f64,
+    /// Latest hop of output.
+    output: Vec<f64>,
+    /// Index of the next output sample in the hop.
+    index: usize,
+    hash: u64,
+}
+
+impl<T: Float> PaulStretch<T> {
+    /// Create new paulstretch player of `samples` at `sample_rate` Hz
+    /// with stretch factor `stretch` (`stretch` > 0).
+    /// Window length is `PAULSTRETCH_WINDOW` seconds.
+    pub fn new(samples: &[f64], sample_rate: f64, stretch: f64) -> Self {
+        assert!(stretch > 0.0);
+        let stretcher = Stretcher::new(paulstretch_length(PAULSTRETCH_WINDOW, sample_rate));
+        let hop = stretcher.hop();
+        Self {
+            _marker: PhantomData,
+            samples: Arc::new(samples.to_vec()),
+            stretcher,
+            stretch,
+            position: 0.0,
+            output: vec![0.0; hop],
+            index: hop,
+            hash: 0,
+        }
+    }
+
+    /// Stretch factor.
+    pub fn stretch(&self) -> f64 {
+        self.stretch
+    }
+
+    /// Set stretch factor (`stretch` > 0).
+    pub fn set_stretch(&mut self, stretch: f64) {
+        assert!(stretch > 0.0);
+        self.stretch = stretch;
+    }
+}
+
+impl<T: Float> AudioNode for PaulStretch<T> {
+    const ID: u64 = 113;
+    type Sample = T;
+    type Inputs = U0;
+    type Outputs = U1;
+    type Setting = f64;
+
+    fn set(&mut self, setting: Self::Setting) {
+        self.set_stretch(setting);
+    }
+
+    fn reset(&mut self) {
+        self.stretcher.reset(self.hash as i64);
+        self.position = 0.0;
+        self.index = self.output.len();
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        _input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        if self.index == self.output.len() {
+            self.stretcher
+                .process(&self.samples, self.position as usize, &mut self.output);
+            self.position += self.output.len() as f64 / self.stretch;
+            self.index = 0;
+        }
+        let y = self.output[self.index];
+        self.index += 1;
+        [convert(y)].into()
+    }
+
+    fn set_hash(&mut self, hash: u64) {
+        self.hash = hash;
+        self.reset();
+    }
+
+    fn route(&mut self, _input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        output[0] = Signal::Latency(0.0);
+        output
+    }
+}
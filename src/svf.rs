@@ -928,6 +928,15 @@ where
         self.mode.update_frequency(&self.params, &mut self.coeffs);
     }
 
+    fn input_kind(&self, input: usize) -> PortKind {
+        match input {
+            1 => PortKind::Frequency,
+            2 => PortKind::Q,
+            3 => PortKind::Gain,
+            _ => PortKind::Audio,
+        }
+    }
+
     #[inline]
     fn tick(
         &mut self,
@@ -1176,6 +1185,15 @@ impl<T: Float, F: Real> AudioNode for Morph<T, F> {
         self.filter.set_sample_rate(sample_rate);
     }
 
+    fn input_kind(&self, input: usize) -> PortKind {
+        match input {
+            1 => PortKind::Frequency,
+            2 => PortKind::Q,
+            3 => PortKind::Control,
+            _ => PortKind::Audio,
+        }
+    }
+
     #[inline]
     fn tick(
         &mut self,
@@ -1213,3 +1231,166 @@ impl<T: Float, F: Real> AudioNode for Morph<T, F> {
         self.filter.ping(probe, hash).hash(Self::ID)
     }
 }
+
+/// Bank of `N` parallel bandpass filters with a single input and summed output.
+/// Each band has a center frequency, Q and amplitude gain, which is also the peak gain of the band.
+/// Setting: (band index, (center, Q, gain)).
+/// - Input 0: audio
+/// - Output 0: filtered audio
+#[derive(Clone)]
+pub struct FilterBank<N, T, F>
+where
+    N: Size<F>,
+    T: Float,
+    F: Real,
+{
+    sample_rate: F,
+    center: Frame<F, N>,
+    q: Frame<F, N>,
+    gain: Frame<F, N>,
+    a1: Frame<F, N>,
+    a2: Frame<F, N>,
+    a3: Frame<F, N>,
+    m1: Frame<F, N>,
+    ic1eq: Frame<F, N>,
+    ic2eq: Frame<F, N>,
+    _marker: PhantomData<T>,
+}
+
+impl<N, T, F> FilterBank<N, T, F>
+where
+    N: Size<F>,
+    T: Float,
+    F: Real,
+{
+    /// Create new filter bank. Band parameters are given as (center, Q, gain) triples.
+    pub fn new(sample_rate: f64, bands: &[(F, F, F)]) -> Self {
+        assert_eq!(bands.len(), N::USIZE);
+        let mut node = FilterBank {
+            sample_rate: convert(sample_rate),
+            center: Frame::generate(|i| bands[i].0),
+            q: Frame::generate(|i| bands[i].1),
+            gain: Frame::generate(|i| bands[i].2),
+            a1: Frame::default(),
+            a2: Frame::default(),
+            a3: Frame::default(),
+            m1: Frame::default(),
+            ic1eq: Frame::default(),
+            ic2eq: Frame::default(),
+            _marker: PhantomData::default(),
+        };
+        for i in 0..N::USIZE {
+            node.update_band(i);
+        }
+        node
+    }
+
+    /// Sample rate in Hz.
+    #[inline]
+    pub fn sample_rate(&self) -> F {
+        self.sample_rate
+    }
+
+    /// Center frequency in Hz of band `index`.
+    #[inline]
+    pub fn center(&self, index: usize) -> F {
+        self.center[index]
+    }
+
+    /// Q of band `index`.
+    #[inline]
+    pub fn q(&self, index: usize) -> F {
+        self.q[index]
+    }
+
+    /// Amplitude gain of band `index`.
+    #[inline]
+    pub fn gain(&self, index: usize) -> F {
+        self.gain[index]
+    }
+
+    /// Set center frequency in Hz, Q and amplitude gain of band `index`.
+    #[inline]
+    pub fn set_band(&mut self, index: usize, center: F, q: F, gain: F) {
+        self.center[index] = center;
+        self.q[index] = q;
+        self.gain[index] = gain;
+        self.update_band(index);
+    }
+
+    /// Recalculate coefficients of band `index`.
+    fn update_band(&mut self, index: usize) {
+        let coeffs = SvfCoeffs::bandpass(self.sample_rate, self.center[index], self.q[index]);
+        self.a1[index] = coeffs.a1;
+        self.a2[index] = coeffs.a2;
+        self.a3[index] = coeffs.a3;
+        // Normalize bandpass peak gain to unity before applying band gain.
+        self.m1[index] = self.gain[index] / self.q[index];
+    }
+}
+
+impl<N, T, F> AudioNode for FilterBank<N, T, F>
+where
+    N: Size<F>,
+    T: Float,
+    F: Real,
+{
+    const ID: u64 = 121;
+    type Sample = T;
+    type Inputs = U1;
+    type Outputs = U1;
+    type Setting = (usize, (F, F, F));
+
+    fn set(&mut self, (index, (center, q, gain)): Self::Setting) {
+        self.set_band(index, center, q, gain);
+    }
+
+    fn reset(&mut self) {
+        self.ic1eq = Frame::default();
+        self.ic2eq = Frame::default();
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = convert(sample_rate);
+        for i in 0..N::USIZE {
+            self.update_band(i);
+        }
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let v0: F = convert(input[0]);
+        let mut output = F::zero();
+        for i in 0..N::USIZE {
+            let v3 = v0 - self.ic2eq[i];
+            let v1 = self.a1[i] * self.ic1eq[i] + self.a2[i] * v3;
+            let v2 = self.ic2eq[i] + self.a2[i] * self.ic1eq[i] + self.a3[i] * v3;
+            self.ic1eq[i] = F::new(2) * v1 - self.ic1eq[i];
+            self.ic2eq[i] = F::new(2) * v2 - self.ic2eq[i];
+            output += self.m1[i] * v1;
+        }
+        [convert(output)].into()
+    }
+
+    fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        let mode = BandpassMode::<F>::new();
+        output[0] = input[0].filter(0.0, |r| {
+            let mut response = Complex64::new(0.0, 0.0);
+            for i in 0..N::USIZE {
+                let params = SvfParams {
+                    sample_rate: self.sample_rate,
+                    cutoff: self.center[i],
+                    q: self.q[i],
+                    gain: self.gain[i],
+                };
+                response += mode.response(&params, frequency) * self.m1[i].to_f64();
+            }
+            r * response
+        });
+        output
+    }
+}
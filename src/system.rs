@@ -101,6 +101,18 @@ impl<T: Float, X: AudioNode, F: FnMut(T, T, &mut X) + Clone + Sync + Send> Audio
         self.x.ping(probe, hash.hash(Self::ID))
     }
 
+    fn has_side_effects(&self) -> bool {
+        self.x.has_side_effects()
+    }
+
+    fn input_kind(&self, input: usize) -> PortKind {
+        self.x.input_kind(input)
+    }
+
+    fn output_kind(&self, output: usize) -> PortKind {
+        self.x.output_kind(output)
+    }
+
     fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
         self.x.route(input, frequency)
     }
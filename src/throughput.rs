@@ -0,0 +1,98 @@
+//! Throughput measurement of audio units.
+
+use super::audiounit::*;
+use super::buffer::*;
+use super::math::*;
+use super::*;
+use duplicate::duplicate_item;
+use std::time::{Duration, Instant};
+
+/// Minimum wall clock time spent measuring each processing path.
+const MEASURE_TIME: Duration = Duration::from_millis(100);
+
+/// Throughput of an audio unit in samples per second of wall clock time,
+/// measured separately for single sample and block processing.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Throughput {
+    /// Samples per second processed one sample at a time with `tick`.
+    pub tick: f64,
+    /// Samples per second processed in blocks of `MAX_BUFFER_SIZE` samples with `process`.
+    pub process: f64,
+}
+
+impl Throughput {
+    /// How many times faster than real time the unit runs at `sample_rate` Hz with `tick`.
+    pub fn tick_realtime(&self, sample_rate: f64) -> f64 {
+        self.tick / sample_rate
+    }
+
+    /// How many times faster than real time the unit runs at `sample_rate` Hz with `process`.
+    pub fn process_realtime(&self, sample_rate: f64) -> f64 {
+        self.process / sample_rate
+    }
+
+    /// Speedup of block processing over single sample processing.
+    pub fn block_speedup(&self) -> f64 {
+        self.process / self.tick
+    }
+}
+
+#[duplicate_item(
+    f48       throughput48       AudioUnit48;
+    [ f64 ]   [ throughput64 ]   [ AudioUnit64 ];
+    [ f32 ]   [ throughput32 ]   [ AudioUnit32 ];
+)]
+/// Measure the throughput of `unit` with both `tick` and `process`.
+/// Inputs are fed with deterministic pseudorandom noise.
+/// Each path is measured for at least 0.1 seconds of wall clock time.
+/// The unit is reset before and after each measurement.
+/// Results depend on the machine and on the build profile: measure release builds.
+///
+/// ### Example
+/// ```
+/// use fundsp::hacker::*;
+/// let mut unit = noise() >> lowpass_hz(1000.0, 1.0);
+/// let throughput = throughput64(&mut unit);
+/// assert!(throughput.tick > 0.0 && throughput.process > 0.0);
+/// ```
+#[allow(clippy::unnecessary_cast)]
+pub fn throughput48(unit: &mut dyn AudioUnit48) -> Throughput {
+    let mut input = Buffer::<f48>::with_channels(unit.inputs());
+    let mut output = Buffer::<f48>::with_channels(unit.outputs());
+    for channel in 0..unit.inputs() {
+        for (i, x) in input.mut_at(channel).iter_mut().enumerate() {
+            *x = (rnd((channel * MAX_BUFFER_SIZE + i) as i64) * 2.0 - 1.0) as f48;
+        }
+    }
+    let mut tick_input = vec![0.0; unit.inputs()];
+    let mut tick_output = vec![0.0; unit.outputs()];
+    unit.allocate();
+
+    // Single sample processing.
+    unit.reset();
+    let start = Instant::now();
+    let mut samples = 0;
+    while start.elapsed() < MEASURE_TIME {
+        for i in 0..MAX_BUFFER_SIZE {
+            for (channel, x) in tick_input.iter_mut().enumerate() {
+                *x = input.at(channel)[i];
+            }
+            unit.tick(&tick_input, &mut tick_output);
+        }
+        samples += MAX_BUFFER_SIZE;
+    }
+    let tick = samples as f64 / start.elapsed().as_secs_f64();
+
+    // Block processing.
+    unit.reset();
+    let start = Instant::now();
+    let mut samples = 0;
+    while start.elapsed() < MEASURE_TIME {
+        unit.process(MAX_BUFFER_SIZE, input.self_ref(), output.self_mut());
+        samples += MAX_BUFFER_SIZE;
+    }
+    let process = samples as f64 / start.elapsed().as_secs_f64();
+
+    unit.reset();
+    Throughput { tick, process }
+}
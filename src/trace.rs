@@ -0,0 +1,201 @@
+//! Real-time safe tracing of events such as resets and settings.
+
+use super::audionode::*;
+use super::math::*;
+use super::net::NodeId;
+use super::signal::*;
+use std::any::Any;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use thingbuf::mpsc::blocking::{channel, Receiver, Sender};
+
+/// Kind of a traced event.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TraceKind {
+    /// Node or network was reset.
+    #[default]
+    Reset,
+    /// Sample rate was set.
+    SampleRate,
+    /// Setting was applied.
+    Setting,
+    /// Node was placed in the evaluation order of a network.
+    /// These events appear in evaluation order.
+    Order,
+    /// Network backend switched to a new version.
+    Commit,
+    /// Network watchdog muted a unit.
+    Mute,
+    /// User defined event.
+    Custom(u64),
+}
+
+/// Traced event.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TraceEvent {
+    /// Node the event concerns. This is `None` for events concerning a whole network.
+    pub node: Option<NodeId>,
+    /// Kind of event.
+    pub kind: TraceKind,
+    /// Time of the event since the trace was created.
+    pub time: Duration,
+}
+
+/// Tracer records events into a preallocated queue without allocating.
+/// It can be cloned and used from the audio thread.
+/// If the queue is full, then events are dropped.
+#[derive(Clone)]
+pub struct Tracer {
+    sender: Sender<TraceEvent>,
+    epoch: Instant,
+    dropped: Arc<AtomicU64>,
+}
+
+impl Tracer {
+    /// Record an event.
+    #[inline]
+    pub fn record(&self, node: Option<NodeId>, kind: TraceKind) {
+        let event = TraceEvent {
+            node,
+            kind,
+            time: self.epoch.elapsed(),
+        };
+        if self.sender.try_send(event).is_err() {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Receiver of traced events. Events can be drained from another thread.
+pub struct Trace {
+    receiver: Receiver<TraceEvent>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl Trace {
+    /// Create a new trace with room for `capacity` events (`capacity` > 0).
+    /// Returns a (frontend, tracer) pair.
+    pub fn new(capacity: usize) -> (Trace, Tracer) {
+        assert!(capacity > 0);
+        let (sender, receiver) = channel(capacity);
+        let dropped = Arc::new(AtomicU64::new(0));
+        let trace = Trace {
+            receiver,
+            dropped: dropped.clone(),
+        };
+        let tracer = Tracer {
+            sender,
+            epoch: Instant::now(),
+            dropped,
+        };
+        (trace, tracer)
+    }
+
+    /// Get the next event, if available.
+    pub fn get(&mut self) -> Option<TraceEvent> {
+        self.receiver.try_recv().ok()
+    }
+
+    /// Number of events dropped so far because the queue was full.
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+/// Traced node. Records resets, sample rate changes and settings of the enclosed node.
+#[derive(Clone)]
+pub struct Traced<X: AudioNode> {
+    x: X,
+    id: NodeId,
+    tracer: Tracer,
+}
+
+impl<X: AudioNode> Traced<X> {
+    /// Create new traced node. Events are recorded with a fresh node ID.
+    pub fn new(tracer: &Tracer, x: X) -> Self {
+        let mut node = Self {
+            x,
+            id: NodeId::new(),
+            tracer: tracer.clone(),
+        };
+        let hash = node.ping(true, AttoHash::new(Self::ID));
+        node.ping(false, hash);
+        node
+    }
+
+    /// Node ID used in traced events.
+    pub fn id(&self) -> NodeId {
+        self.id
+    }
+
+    /// Access enclosed node.
+    pub fn node(&self) -> &X {
+        &self.x
+    }
+
+    /// Access enclosed node.
+    pub fn node_mut(&mut self) -> &mut X {
+        &mut self.x
+    }
+}
+
+impl<X: AudioNode> AudioNode for Traced<X> {
+    const ID: u64 = 110;
+    type Sample = X::Sample;
+    type Inputs = X::Inputs;
+    type Outputs = X::Outputs;
+    type Setting = X::Setting;
+
+    fn set(&mut self, setting: Self::Setting) {
+        self.x.set(setting);
+        self.tracer.record(Some(self.id), TraceKind::Setting);
+    }
+
+    fn reset(&mut self) {
+        self.x.reset();
+        self.tracer.record(Some(self.id), TraceKind::Reset);
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.x.set_sample_rate(sample_rate);
+        self.tracer.record(Some(self.id), TraceKind::SampleRate);
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        self.x.tick(input)
+    }
+
+    fn process(
+        &mut self,
+        size: usize,
+        input: &[&[Self::Sample]],
+        output: &mut [&mut [Self::Sample]],
+    ) {
+        self.x.process(size, input, output);
+    }
+
+    fn ping(&mut self, probe: bool, hash: AttoHash) -> AttoHash {
+        self.x.ping(probe, hash.hash(Self::ID))
+    }
+
+    fn visit(&mut self, tag: u64, f: &mut dyn FnMut(&mut dyn Any)) {
+        self.x.visit(tag, f);
+    }
+
+    fn has_side_effects(&self) -> bool {
+        true
+    }
+
+    fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
+        self.x.route(input, frequency)
+    }
+
+    fn allocate(&mut self) {
+        self.x.allocate();
+    }
+}
@@ -0,0 +1,330 @@
+//! Microtonal tuning components.
+
+use super::audionode::*;
+use super::math::*;
+use super::signal::*;
+use super::*;
+use numeric_array::typenum::*;
+use std::io::{Error, ErrorKind, Result};
+use std::marker::PhantomData;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Tuning of MIDI keys to frequencies. It consists of a scale and a keyboard mapping
+/// as in the Scala tuning file formats (.scl and .kbm).
+/// The default tuning is 12-tone equal temperament with A_4 (note number 69) at 440 Hz.
+///
+/// ### Example: Pythagorean Pentatonic Scale
+/// ```
+/// use fundsp::hacker::*;
+/// let scl = "! pentatonic.scl\nPythagorean pentatonic\n5\n9/8\n81/64\n3/2\n27/16\n2/1\n";
+/// let tuning = Tuning::from_scl(scl).unwrap();
+/// assert!(tuning.notes() == 5);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Tuning {
+    description: String,
+    /// Ratios of scale degrees 1...N relative to the tonic. The last ratio is the period.
+    ratios: Vec<f64>,
+    /// Scale degree of each key in a mapping period, or `None` for unmapped keys.
+    /// If this is empty, keys are mapped linearly to scale degrees.
+    mapping: Vec<Option<usize>>,
+    /// First retuned key.
+    first_note: i64,
+    /// Last retuned key.
+    last_note: i64,
+    /// Key that is mapped to the tonic.
+    middle_note: i64,
+    /// Key with the reference frequency.
+    reference_note: i64,
+    /// Frequency of the reference key in Hz.
+    reference_hz: f64,
+    /// Scale degree that forms the period of the keyboard mapping.
+    octave_degree: usize,
+    /// Frequency ratio of the reference key relative to the tonic.
+    reference_ratio: f64,
+}
+
+impl Default for Tuning {
+    fn default() -> Self {
+        Self::equal(12)
+    }
+}
+
+/// Parse error in a tuning file.
+fn invalid(message: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, message)
+}
+
+/// Non-comment lines of a Scala file.
+fn scala_lines(text: &str) -> impl Iterator<Item = &str> {
+    text.lines()
+        .filter(|line| !line.starts_with('!'))
+        .map(|line| line.trim())
+}
+
+/// Parse the first word of a Scala file line.
+fn scala_number<T: std::str::FromStr>(line: Option<&str>) -> Result<T> {
+    line.and_then(|line| line.split_whitespace().next())
+        .and_then(|word| word.parse().ok())
+        .ok_or_else(|| invalid("Expected a number."))
+}
+
+/// Parse a Scala pitch, which is either in cents (contains a period) or a ratio.
+fn scala_pitch(line: &str) -> Result<f64> {
+    let word = line
+        .split_whitespace()
+        .next()
+        .ok_or_else(|| invalid("Expected a pitch."))?;
+    let ratio = if word.contains('.') {
+        word.parse::<f64>().ok().map(|cents| exp2(cents / 1200.0))
+    } else if let Some((numerator, denominator)) = word.split_once('/') {
+        match (numerator.parse::<f64>(), denominator.parse::<f64>()) {
+            (Ok(numerator), Ok(denominator)) => Some(numerator / denominator),
+            _ => None,
+        }
+    } else {
+        word.parse::<f64>().ok()
+    };
+    match ratio {
+        Some(ratio) if ratio > 0.0 && ratio.is_finite() => Ok(ratio),
+        _ => Err(invalid("Invalid pitch.")),
+    }
+}
+
+impl Tuning {
+    /// Equal temperament with `notes` (`notes` > 0) steps per octave.
+    /// The tonic is at note number 60 and A_4 (note number 69) is at 440 Hz.
+    pub fn equal(notes: usize) -> Self {
+        assert!(notes > 0);
+        let mut tuning = Self {
+            description: format!("{} equal divisions of the octave", notes),
+            ratios: (1..=notes).map(|i| exp2(i as f64 / notes as f64)).collect(),
+            mapping: Vec::new(),
+            first_note: 0,
+            last_note: 127,
+            middle_note: 60,
+            reference_note: 69,
+            reference_hz: 440.0,
+            octave_degree: notes,
+            reference_ratio: 1.0,
+        };
+        tuning.update_reference();
+        tuning
+    }
+
+    /// Parse a scale in Scala .scl format. The keyboard mapping is linear,
+    /// with the tonic at note number 60 and A_4 (note number 69) at 440 Hz.
+    pub fn from_scl(text: &str) -> Result<Self> {
+        let mut lines = scala_lines(text);
+        let description = lines
+            .next()
+            .ok_or_else(|| invalid("Missing description."))?
+            .to_string();
+        let notes: usize = scala_number(lines.next())?;
+        if notes == 0 {
+            return Err(invalid("Scale has no notes."));
+        }
+        let mut ratios = Vec::with_capacity(notes);
+        for _ in 0..notes {
+            ratios.push(scala_pitch(
+                lines.next().ok_or_else(|| invalid("Missing pitch."))?,
+            )?);
+        }
+        let mut tuning = Self::equal(notes);
+        tuning.description = description;
+        tuning.ratios = ratios;
+        tuning.update_reference();
+        Ok(tuning)
+    }
+
+    /// Load a scale from a Scala .scl file.
+    pub fn load_scl<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_scl(&std::fs::read_to_string(path)?)
+    }
+
+    /// Set keyboard mapping from Scala .kbm format.
+    ///
+    /// ### Example: Reference Pitch Of 432 Hz
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let mut tuning = Tuning::default();
+    /// tuning.set_kbm("0\n0\n127\n60\n69\n432.0\n12\n").unwrap();
+    /// assert!((tuning.hz(69.0) - 432.0).abs() < 1.0e-9);
+    /// ```
+    pub fn set_kbm(&mut self, text: &str) -> Result<()> {
+        let mut lines = scala_lines(text).filter(|line| !line.is_empty());
+        let size: usize = scala_number(lines.next())?;
+        let first_note = scala_number(lines.next())?;
+        let last_note = scala_number(lines.next())?;
+        let middle_note = scala_number(lines.next())?;
+        let reference_note = scala_number(lines.next())?;
+        let reference_hz: f64 = scala_number(lines.next())?;
+        let octave_degree: usize = scala_number(lines.next())?;
+        if reference_hz <= 0.0 || !reference_hz.is_finite() {
+            return Err(invalid("Invalid reference frequency."));
+        }
+        let mut mapping = Vec::with_capacity(size);
+        for _ in 0..size {
+            // Missing entries at the end of the mapping are unmapped.
+            match lines.next() {
+                None => mapping.push(None),
+                Some(line) if line.starts_with('x') => mapping.push(None),
+                line => mapping.push(Some(scala_number(line)?)),
+            }
+        }
+        let mut tuning = self.clone();
+        tuning.mapping = mapping;
+        tuning.first_note = first_note;
+        tuning.last_note = last_note;
+        tuning.middle_note = middle_note;
+        tuning.reference_note = reference_note;
+        tuning.reference_hz = reference_hz;
+        // Degree zero means the period of the scale.
+        tuning.octave_degree = if octave_degree == 0 {
+            self.notes()
+        } else {
+            octave_degree
+        };
+        tuning.reference_ratio = 1.0;
+        let reference_ratio = tuning
+            .key_ratio(reference_note)
+            .ok_or_else(|| invalid("Reference key is not mapped."))?;
+        tuning.reference_ratio = reference_ratio;
+        *self = tuning;
+        Ok(())
+    }
+
+    /// Load keyboard mapping from a Scala .kbm file.
+    pub fn load_kbm<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
+        self.set_kbm(&std::fs::read_to_string(path)?)
+    }
+
+    /// Description of the scale.
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    /// Number of notes in the scale.
+    pub fn notes(&self) -> usize {
+        self.ratios.len()
+    }
+
+    /// Frequency ratio of scale degree `degree` relative to the tonic.
+    /// Degrees past the period continue into the next periods.
+    fn degree_ratio(&self, degree: i64) -> f64 {
+        let notes = self.notes() as i64;
+        let period = degree.div_euclid(notes);
+        let degree = degree.rem_euclid(notes) as usize;
+        let ratio = if degree == 0 {
+            1.0
+        } else {
+            self.ratios[degree - 1]
+        };
+        ratio * pow(self.ratios[self.notes() - 1], period as f64)
+    }
+
+    /// Frequency ratio of `key` relative to the tonic,
+    /// or `None` if the key is not mapped.
+    fn key_ratio(&self, key: i64) -> Option<f64> {
+        if key < self.first_note || key > self.last_note {
+            return None;
+        }
+        let offset = key - self.middle_note;
+        if self.mapping.is_empty() {
+            return Some(self.degree_ratio(offset));
+        }
+        let size = self.mapping.len() as i64;
+        let period = offset.div_euclid(size);
+        self.mapping[offset.rem_euclid(size) as usize].map(|degree| {
+            self.degree_ratio(degree as i64)
+                * pow(self.degree_ratio(self.octave_degree as i64), period as f64)
+        })
+    }
+
+    /// Recompute the ratio of the reference key. Keeps the linear mapping valid.
+    fn update_reference(&mut self) {
+        self.reference_ratio = 1.0;
+        self.reference_ratio = self.key_ratio(self.reference_note).unwrap_or(1.0);
+    }
+
+    /// Frequency of MIDI `key` in Hz, or `None` if the key is not mapped.
+    pub fn key_hz(&self, key: i64) -> Option<f64> {
+        self.key_ratio(key)
+            .map(|ratio| self.reference_hz * ratio / self.reference_ratio)
+    }
+
+    /// Frequency of fractional MIDI note number `note` in Hz.
+    /// Fractional notes are interpolated exponentially between adjacent keys.
+    /// Unmapped keys have zero frequency.
+    pub fn hz(&self, note: f64) -> f64 {
+        let key = floor(note);
+        let fraction = note - key;
+        let key = key as i64;
+        match (self.key_hz(key), fraction > 0.0) {
+            (Some(f0), true) => match self.key_hz(key + 1) {
+                Some(f1) => f0 * pow(f1 / f0, fraction),
+                None => f0,
+            },
+            (Some(f0), false) => f0,
+            _ => 0.0,
+        }
+    }
+}
+
+/// Tuned pitch converter. Converts MIDI note numbers to frequencies in a `Tuning`.
+/// - Input 0: MIDI note number
+/// - Output 0: frequency (Hz)
+#[derive(Clone)]
+pub struct TunedPitch<T: Real> {
+    _marker: PhantomData<T>,
+    tuning: Arc<Tuning>,
+}
+
+impl<T: Real> TunedPitch<T> {
+    /// Create new tuned pitch converter.
+    pub fn new(tuning: &Arc<Tuning>) -> Self {
+        Self {
+            _marker: PhantomData,
+            tuning: tuning.clone(),
+        }
+    }
+
+    /// Tuning in use.
+    pub fn tuning(&self) -> &Arc<Tuning> {
+        &self.tuning
+    }
+}
+
+impl<T: Real> AudioNode for TunedPitch<T> {
+    const ID: u64 = 119;
+    type Sample = T;
+    type Inputs = U1;
+    type Outputs = U1;
+    type Setting = ();
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        [T::from_f64(self.tuning.hz(input[0].to_f64()))].into()
+    }
+
+    fn input_kind(&self, _input: usize) -> PortKind {
+        PortKind::Control
+    }
+
+    fn output_kind(&self, _output: usize) -> PortKind {
+        PortKind::Frequency
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        output[0] = match input[0] {
+            Signal::Value(x) => Signal::Value(self.tuning.hz(x)),
+            x => x.distort(0.0),
+        };
+        output
+    }
+}
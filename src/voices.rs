@@ -0,0 +1,181 @@
+//! Voice pool: a number of independent copies of a prototype unit with summed outputs.
+
+use super::audiounit::*;
+use super::buffer::*;
+use super::math::*;
+use super::signal::*;
+use duplicate::duplicate_item;
+
+#[duplicate_item(
+    f48       Voices48       AudioUnit48;
+    [ f64 ]   [ Voices64 ]   [ AudioUnit64 ];
+    [ f32 ]   [ Voices32 ]   [ AudioUnit32 ];
+)]
+/// Voice pool. Contains a number of independent clones of a prototype unit.
+/// Inputs are laid out per voice: if the prototype has `k` inputs,
+/// then inputs `i * k` to `i * k + k - 1` are sent to voice `i`.
+/// Voice outputs are summed. The number of outputs is that of the prototype.
+/// A mono patch with, e.g., gate and pitch inputs can be made polyphonic in this way.
+#[derive(Clone)]
+pub struct Voices48 {
+    voice: Vec<Box<dyn AudioUnit48>>,
+    voice_inputs: usize,
+    outputs: usize,
+    buffer: Buffer<f48>,
+    tick: Vec<f48>,
+}
+
+#[duplicate_item(
+    f48       Voices48       AudioUnit48;
+    [ f64 ]   [ Voices64 ]   [ AudioUnit64 ];
+    [ f32 ]   [ Voices32 ]   [ AudioUnit32 ];
+)]
+impl Voices48 {
+    const ID: u64 = 101;
+
+    /// Create a pool of `n` voices (`n` > 0) cloned from `prototype`.
+    /// Each voice is given its own pseudorandom phase.
+    pub fn new(n: usize, prototype: Box<dyn AudioUnit48>) -> Self {
+        assert!(n > 0);
+        let voice_inputs = prototype.inputs();
+        let outputs = prototype.outputs();
+        let mut voices = Self {
+            voice: (0..n).map(|_| prototype.clone_unit()).collect(),
+            voice_inputs,
+            outputs,
+            buffer: Buffer::with_channels(outputs),
+            tick: vec![0.0; outputs],
+        };
+        voices.ping(false, AttoHash::new(Self::ID));
+        voices
+    }
+
+    /// Number of voices.
+    pub fn size(&self) -> usize {
+        self.voice.len()
+    }
+
+    /// Access voice `i`.
+    pub fn voice(&self, i: usize) -> &dyn AudioUnit48 {
+        &*self.voice[i]
+    }
+
+    /// Access voice `i` mutably.
+    pub fn voice_mut(&mut self, i: usize) -> &mut dyn AudioUnit48 {
+        &mut *self.voice[i]
+    }
+}
+
+#[duplicate_item(
+    f48       Voices48       AudioUnit48;
+    [ f64 ]   [ Voices64 ]   [ AudioUnit64 ];
+    [ f32 ]   [ Voices32 ]   [ AudioUnit32 ];
+)]
+impl AudioUnit48 for Voices48 {
+    fn reset(&mut self) {
+        for voice in &mut self.voice {
+            voice.reset();
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        for voice in &mut self.voice {
+            voice.set_sample_rate(sample_rate);
+        }
+    }
+
+    fn tick(&mut self, input: &[f48], output: &mut [f48]) {
+        output.fill(0.0);
+        let k = self.voice_inputs;
+        for (i, voice) in self.voice.iter_mut().enumerate() {
+            voice.tick(&input[i * k..(i + 1) * k], &mut self.tick);
+            for (x, y) in output.iter_mut().zip(self.tick.iter()) {
+                *x += *y;
+            }
+        }
+    }
+
+    fn process(&mut self, size: usize, input: &[&[f48]], output: &mut [&mut [f48]]) {
+        for channel in output.iter_mut() {
+            channel[..size].fill(0.0);
+        }
+        let k = self.voice_inputs;
+        for (i, voice) in self.voice.iter_mut().enumerate() {
+            voice.process(
+                size,
+                &input[i * k..(i + 1) * k],
+                self.buffer.get_mut(self.outputs),
+            );
+            for (channel, x) in output.iter_mut().enumerate() {
+                for (x, y) in x[..size].iter_mut().zip(self.buffer.at(channel).iter()) {
+                    *x += *y;
+                }
+            }
+        }
+    }
+
+    fn inputs(&self) -> usize {
+        self.voice.len() * self.voice_inputs
+    }
+
+    fn outputs(&self) -> usize {
+        self.outputs
+    }
+
+    fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
+        let k = self.voice_inputs;
+        let mut output = new_signal_frame(self.outputs);
+        for (i, voice) in self.voice.iter_mut().enumerate() {
+            let mut voice_input = new_signal_frame(k);
+            voice_input.copy_from_slice(&input[i * k..(i + 1) * k]);
+            let voice_output = voice.route(&voice_input, frequency);
+            for channel in 0..self.outputs {
+                output[channel] = if i == 0 {
+                    voice_output[channel]
+                } else {
+                    output[channel].combine_linear(
+                        voice_output[channel],
+                        0.0,
+                        |x, y| x + y,
+                        |x, y| x + y,
+                    )
+                };
+            }
+        }
+        output
+    }
+
+    fn get_id(&self) -> u64 {
+        Self::ID
+    }
+
+    fn ping(&mut self, probe: bool, hash: AttoHash) -> AttoHash {
+        let mut hash = hash.hash(Self::ID);
+        for voice in &mut self.voice {
+            hash = voice.ping(probe, hash);
+        }
+        hash
+    }
+
+    fn has_side_effects(&self) -> bool {
+        self.voice.iter().any(|voice| voice.has_side_effects())
+    }
+
+    fn input_kind(&self, input: usize) -> PortKind {
+        self.voice[0].input_kind(input % self.voice_inputs)
+    }
+
+    fn output_kind(&self, output: usize) -> PortKind {
+        self.voice[0].output_kind(output)
+    }
+
+    fn footprint(&self) -> usize {
+        std::mem::size_of::<Voices48>()
+    }
+
+    fn allocate(&mut self) {
+        for voice in &mut self.voice {
+            voice.allocate();
+        }
+    }
+}
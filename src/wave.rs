@@ -3,7 +3,12 @@
 use super::audionode::*;
 use super::audiounit::*;
 use super::combinator::*;
+use super::dither::*;
+use super::feature::*;
 use super::math::*;
+use super::partials::*;
+use super::signal::*;
+use super::spectral::*;
 use super::*;
 use duplicate::duplicate_item;
 use numeric_array::typenum::Unsigned;
@@ -14,6 +19,7 @@ use std::io::prelude::*;
 use std::io::BufWriter;
 use std::marker::PhantomData;
 use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
 /// Write a 32-bit value to a WAV file.
@@ -31,11 +37,19 @@ fn write16<W: Write>(writer: &mut W, x: u16) -> std::io::Result<()> {
     std::io::Result::Ok(())
 }
 
+/// Write a 24-bit value to a WAV file.
+#[inline]
+fn write24<W: Write>(writer: &mut W, x: u32) -> std::io::Result<()> {
+    writer.write_all(&[x as u8, (x >> 8) as u8, (x >> 16) as u8])?;
+    std::io::Result::Ok(())
+}
+
 // Write WAV header, including the header of the data block.
 fn write_wav_header<W: Write>(
     writer: &mut W,
     data_length: usize,
     format: u16,
+    sample_bytes: u32,
     channels: usize,
     sample_rate: usize,
 ) -> std::io::Result<()> {
@@ -50,7 +64,6 @@ fn write_wav_header<W: Write>(
     write16(writer, channels as u16)?;
     write32(writer, sample_rate as u32)?;
     // Data rate in bytes per second.
-    let sample_bytes = if format == 1 { 2 } else { 4 };
     write32(writer, (sample_rate * channels) as u32 * sample_bytes)?;
     // Sample frame length in bytes.
     write16(writer, channels as u16 * sample_bytes as u16)?;
@@ -77,6 +90,8 @@ pub struct Wave48 {
     len: usize,
     /// Slice of references. This is only allocated if it is used.
     slice: Slice<[f48]>,
+    /// Declared channel layout, if any.
+    layout: Option<ChannelLayout>,
 }
 
 #[duplicate_item(
@@ -91,6 +106,7 @@ impl Clone for Wave48 {
             sr: self.sr,
             len: self.len,
             slice: Slice::new(),
+            layout: self.layout,
         }
     }
 }
@@ -119,6 +135,7 @@ impl Wave48 {
             sr: sample_rate,
             len: 0,
             slice: Slice::new(),
+            layout: None,
         }
     }
 
@@ -141,6 +158,7 @@ impl Wave48 {
             sr: sample_rate,
             len: 0,
             slice: Slice::new(),
+            layout: None,
         }
     }
 
@@ -164,6 +182,7 @@ impl Wave48 {
             sr: sample_rate,
             len: length,
             slice: Slice::new(),
+            layout: None,
         }
     }
 
@@ -181,6 +200,7 @@ impl Wave48 {
             sr: sample_rate,
             len: samples.len(),
             slice: Slice::new(),
+            layout: None,
         }
     }
 
@@ -207,6 +227,29 @@ impl Wave48 {
         &self.vec[channel]
     }
 
+    /// Declared channel layout of this wave, if any.
+    /// Waves rendered or filtered from units that declare an output layout inherit it.
+    pub fn layout(&self) -> Option<ChannelLayout> {
+        self.layout
+    }
+
+    /// Declare the channel layout of this wave. The number of channels must match.
+    /// The layout is cleared if channels are added or removed later.
+    ///
+    /// ### Example
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let mut wave = Wave64::render(44100.0, 1.0, &mut (noise() >> pan(0.0)));
+    /// wave.set_layout(Some(ChannelLayout::Stereo));
+    /// assert!(wave.layout() == Some(ChannelLayout::Stereo));
+    /// ```
+    pub fn set_layout(&mut self, layout: Option<ChannelLayout>) {
+        if let Some(layout) = layout {
+            assert_eq!(layout.channels(), self.channels());
+        }
+        self.layout = layout;
+    }
+
     /// Add a channel to the wave from a slice of samples.
     /// The length of the wave and the number of samples must match.
     /// If there are no channels yet, then the length of the wave
@@ -216,6 +259,7 @@ impl Wave48 {
         if self.channels() == 0 {
             self.len = samples.len();
         }
+        self.layout = None;
         self.vec.push(samples.into());
     }
 
@@ -227,12 +271,14 @@ impl Wave48 {
         if self.channels() == 0 {
             self.len = samples.len();
         }
+        self.layout = None;
         self.vec.insert(channel, samples.into());
     }
 
     /// Remove channel `channel` from this wave. Returns the removed channel.
     pub fn remove_channel(&mut self, channel: usize) -> Vec<f48> {
         assert!(channel < self.channels());
+        self.layout = None;
         self.vec.remove(channel)
     }
 
@@ -366,6 +412,182 @@ impl Wave48 {
         peak
     }
 
+    /// Stereo correlation of channels 0 and 1 over time, measured in consecutive
+    /// windows of `window` seconds. The last window may be shorter.
+    /// Correlation is in -1...1: 1 for identical channels, 0 for unrelated channels
+    /// and -1 for channels in opposite phase. Silent windows have correlation 0.
+    ///
+    /// ### Example
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let wave = Wave64::render(44100.0, 1.0, &mut (sine_hz(110.0) >> split::<U2>()));
+    /// let correlation = wave.correlation(0.1);
+    /// assert!(correlation.len() == 10 && correlation.iter().all(|&x| x > 0.999));
+    /// ```
+    pub fn correlation(&self, window: f64) -> Vec<f64> {
+        assert!(self.channels() >= 2);
+        let length = max(1.0, round(window * self.sample_rate())) as usize;
+        let mut correlation = Vec::new();
+        let mut i = 0;
+        while i < self.len() {
+            let end = min(self.len(), i + length);
+            let mut left = 0.0;
+            let mut right = 0.0;
+            let mut cross = 0.0;
+            for j in i..end {
+                let x = self.at(0, j) as f64;
+                let y = self.at(1, j) as f64;
+                left += x * x;
+                right += y * y;
+                cross += x * y;
+            }
+            let power = sqrt(left * right);
+            correlation.push(if power > 1.0e-20 {
+                clamp11(cross / power)
+            } else {
+                0.0
+            });
+            i = end;
+        }
+        correlation
+    }
+
+    /// Analyze the wave in windows of `window` samples spaced `hop` samples apart.
+    /// The closure `f` receives the channels of each window and returns a feature vector.
+    /// Windows start at multiples of `hop` and the last windows are padded with zeros.
+    /// Returns the feature vectors in window order.
+    /// With the `rayon` feature, windows are analyzed in parallel.
+    ///
+    /// ### Example: RMS Level Of 100 ms Windows Every 10 ms
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let wave = Wave64::render(44100.0, 1.0, &mut (sine_hz(110.0) * 0.5));
+    /// let rms = wave.analyze(4410, 441, |window| {
+    ///     vec![sqrt(window[0].iter().map(|x| x * x).sum::<f64>() / window[0].len() as f64)]
+    /// });
+    /// assert!(rms.len() == 100 && rms[50][0] > 0.35 && rms[50][0] < 0.36);
+    /// ```
+    pub fn analyze<F>(&self, window: usize, hop: usize, f: F) -> Vec<Vec<f64>>
+    where
+        F: Fn(&[&[f48]]) -> Vec<f64> + Sync,
+    {
+        self.analyze_progress(window, hop, f, |_, _| {})
+    }
+
+    /// Analyze the wave like `analyze` while reporting progress.
+    /// After each window, `progress` is called with the number of windows analyzed so far
+    /// and the total number of windows. With the `rayon` feature, `progress` is called
+    /// from worker threads.
+    ///
+    /// ### Example
+    /// ```
+    /// use fundsp::hacker::*;
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    /// let wave = Wave64::render(44100.0, 1.0, &mut pink());
+    /// let done = AtomicUsize::new(0);
+    /// let peak = wave.analyze_progress(
+    ///     1024,
+    ///     512,
+    ///     |window| vec![window[0].iter().fold(0.0, |peak, &x| max(peak, abs(x)))],
+    ///     |analyzed, _total| {
+    ///         done.fetch_max(analyzed, Ordering::Relaxed);
+    ///     },
+    /// );
+    /// assert!(done.load(Ordering::Relaxed) == peak.len());
+    /// ```
+    pub fn analyze_progress<F, P>(
+        &self,
+        window: usize,
+        hop: usize,
+        f: F,
+        progress: P,
+    ) -> Vec<Vec<f64>>
+    where
+        F: Fn(&[&[f48]]) -> Vec<f64> + Sync,
+        P: Fn(usize, usize) + Sync,
+    {
+        assert!(window > 0 && hop > 0);
+        let windows = self.len().div_ceil(hop);
+        let analyzed = AtomicUsize::new(0);
+        let analyze_window = |index: usize| {
+            let start = index * hop;
+            let end = min(self.len(), start + window);
+            let buffer: Vec<Vec<f48>> = self
+                .vec
+                .iter()
+                .map(|channel| {
+                    let mut samples = vec![0.0; window];
+                    samples[..end - start].copy_from_slice(&channel[start..end]);
+                    samples
+                })
+                .collect();
+            let channels: Vec<&[f48]> = buffer.iter().map(|samples| &samples[..]).collect();
+            let features = f(&channels);
+            progress(analyzed.fetch_add(1, Ordering::Relaxed) + 1, windows);
+            features
+        };
+        #[cfg(feature = "rayon")]
+        {
+            use rayon::prelude::*;
+            (0..windows).into_par_iter().map(analyze_window).collect()
+        }
+        #[cfg(not(feature = "rayon"))]
+        {
+            (0..windows).map(analyze_window).collect()
+        }
+    }
+
+    /// Compute spectral `features` of `channel` in windows of `window` samples
+    /// spaced `hop` samples apart. The window length is a power of two and at least 16.
+    /// Returns a vector of feature values per window, in the order given.
+    ///
+    /// ### Example: Brightness And Noisiness Over Time
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let wave = Wave64::render(44100.0, 1.0, &mut (sine_hz(1000.0) * 0.5));
+    /// let features = wave.features(0, 2048, 1024, &[Feature::Centroid, Feature::Flatness]);
+    /// assert!(features.len() == 44 && features[20][1] < 0.01);
+    /// ```
+    pub fn features(
+        &self,
+        channel: usize,
+        window: usize,
+        hop: usize,
+        features: &[Feature],
+    ) -> Vec<Vec<f64>> {
+        assert!(channel < self.channels());
+        let analyzer = SpectralFeatures::new(window, self.sample_rate());
+        self.analyze(window, hop, |frame| {
+            let mut analyzer = analyzer.clone();
+            analyzer.analyze(frame[channel]);
+            features
+                .iter()
+                .map(|&feature| analyzer.feature(feature))
+                .collect()
+        })
+    }
+
+    /// Estimate the musical key of `channel` from its chroma averaged over the whole wave.
+    ///
+    /// ### Example
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let chord = sine_hz(261.63) + sine_hz(329.63) + sine_hz(392.0);
+    /// let wave = Wave64::render(44100.0, 1.0, &mut (chord * 0.3));
+    /// assert!(wave.key(0) == Key { tonic: 0, mode: KeyMode::Major });
+    /// assert!(wave.key(0).to_string() == "C major");
+    /// ```
+    pub fn key(&self, channel: usize) -> Key {
+        let chroma: Vec<Feature> = (0..12).map(Feature::Chroma).collect();
+        let mut average = [0.0; 12];
+        for frame in self.features(channel, 8192, 4096, &chroma) {
+            for (a, x) in average.iter_mut().zip(frame.iter()) {
+                *a += x;
+            }
+        }
+        Key::estimate(&average)
+    }
+
     /// Scales the wave to the range -1..1. Does nothing if the wave is empty.
     ///
     /// ### Example
@@ -447,9 +669,151 @@ impl Wave48 {
         self.fade_out(time);
     }
 
+    /// Join sample ranges of waves into a new wave with equal power crossfades
+    /// of `crossfade` seconds between consecutive ranges. Each crossfade overlaps the end
+    /// of the previous range with the start of the next one, so the result is shorter
+    /// by the crossfade length at each seam. Crossfades are shortened to fit short ranges.
+    fn join(&self, parts: &[(&Self, usize, usize)], crossfade: f64) -> Self {
+        assert!(crossfade >= 0.0);
+        let fade_n = round(crossfade * self.sample_rate()) as usize;
+        let mut wave = Self::new(self.channels(), self.sample_rate());
+        wave.layout = self.layout;
+        for &(part, start, end) in parts {
+            assert!(part.channels() == self.channels());
+            assert!(part.sample_rate() == self.sample_rate());
+            assert!(start <= end && end <= part.len());
+            let overlap = min(
+                fade_n,
+                min(wave.vec.first().map_or(0, |x| x.len()), end - start),
+            );
+            for channel in 0..self.channels() {
+                let source = &part.vec[channel][start..end];
+                let target = &mut wave.vec[channel];
+                let offset = target.len() - overlap;
+                for (i, &x) in source[..overlap].iter().enumerate() {
+                    let angle = (i as f64 + 0.5) / overlap as f64 * PI * 0.5;
+                    target[offset + i] =
+                        target[offset + i] * cos(angle) as f48 + x * sin(angle) as f48;
+                }
+                target.extend_from_slice(&source[overlap..]);
+            }
+        }
+        wave.len = wave.vec.first().map_or(0, |x| x.len());
+        wave
+    }
+
+    /// Splice this wave at sample `at` with `other`: return this wave up to `at`
+    /// followed by `other`, joined with an equal power crossfade of `crossfade` seconds.
+    /// The crossfade overlaps the two parts, so the result is shorter than their sum
+    /// by the crossfade length. The waves must have the same number of channels and sample rate.
+    ///
+    /// ### Example
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let take1 = Wave64::render(44100.0, 1.0, &mut saw_hz(110.0));
+    /// let take2 = Wave64::render(44100.0, 1.0, &mut square_hz(110.0));
+    /// let edit = take1.splice(22050, &take2, 0.01);
+    /// assert!(edit.length() == 22050 + 44100 - 441);
+    /// ```
+    pub fn splice(&self, at: usize, other: &Self, crossfade: f64) -> Self {
+        self.join(&[(self, 0, at), (other, 0, other.len())], crossfade)
+    }
+
+    /// Insert `other` into this wave at sample `at` and return the result.
+    /// Both seams are joined with equal power crossfades of `crossfade` seconds,
+    /// which overlap the parts and shorten the result by the crossfade length at each seam.
+    /// The waves must have the same number of channels and sample rate.
+    ///
+    /// ### Example
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let wave = Wave64::render(44100.0, 1.0, &mut pink());
+    /// let click = Wave64::render(44100.0, 0.1, &mut noise());
+    /// let edit = wave.insert(22050, &click, 0.0);
+    /// assert!(edit.length() == 44100 + 4410);
+    /// ```
+    pub fn insert(&self, at: usize, other: &Self, crossfade: f64) -> Self {
+        self.join(
+            &[
+                (self, 0, at),
+                (other, 0, other.len()),
+                (self, at, self.len()),
+            ],
+            crossfade,
+        )
+    }
+
+    /// Delete samples `start..end` from this wave and return the result.
+    /// The seam is joined with an equal power crossfade of `crossfade` seconds,
+    /// which overlaps the remaining parts and shortens the result by the crossfade length.
+    ///
+    /// ### Example
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let wave = Wave64::render(44100.0, 1.0, &mut (pink() | pink()));
+    /// let edit = wave.delete(11025, 22050, 0.005);
+    /// assert!(edit.channels() == 2 && edit.length() == 44100 - 11025 - 221);
+    /// ```
+    pub fn delete(&self, start: usize, end: usize, crossfade: f64) -> Self {
+        self.join(&[(self, 0, start), (self, end, self.len())], crossfade)
+    }
+
+    /// Analyze `channel` into a sinusoidal model for resynthesis with `resynth_partials`.
+    /// At most `max_partials` partials are tracked at any time.
+    ///
+    /// ### Example: Transpose A Sound Up By A Fifth
+    /// ```
+    /// use fundsp::hacker::*;
+    /// use std::sync::Arc;
+    /// let wave = Wave64::render(44100.0, 1.0, &mut (saw_hz(220.0) | saw_hz(220.0)));
+    /// let partials = Arc::new(wave.analyze_partials(0, 40));
+    /// let transposed = dc((1.0, 1.5)) >> resynth_partials(&partials);
+    /// ```
+    pub fn analyze_partials(&self, channel: usize, max_partials: usize) -> Partials {
+        let samples: Vec<f64> = self.channel(channel).iter().map(|x| x.to_f64()).collect();
+        Partials::analyze(self.sample_rate(), &samples, max_partials)
+    }
+
+    /// Stretch the wave in time by factor `stretch` (`stretch` > 0) with the paulstretch algorithm.
+    /// Windows of `window` seconds are resynthesized with randomized phases,
+    /// which smears the sound into a texture. `PAULSTRETCH_WINDOW` (0.25 seconds) is a good default.
+    /// The length of the stretched wave is `stretch` times the original length.
+    ///
+    /// ### Example: Stretch A Second Of Sound To A Minute
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let wave = Wave64::render(44100.0, 1.0, &mut (saw_hz(110.0) | saw_hz(165.0)));
+    /// let stretched = wave.paulstretch(60.0, PAULSTRETCH_WINDOW);
+    /// assert!(stretched.channels() == 2 && stretched.length() == 60 * 44100);
+    /// ```
+    pub fn paulstretch(&self, stretch: f64, window: f64) -> Self {
+        assert!(stretch > 0.0 && window > 0.0);
+        let length = round(self.len() as f64 * stretch) as usize;
+        let mut wave = Self::with_capacity(self.channels(), self.sample_rate(), length);
+        wave.resize(length);
+        let mut stretcher = Stretcher::new(paulstretch_length(window, self.sample_rate()));
+        let hop = stretcher.hop();
+        let mut output = vec![0.0; hop];
+        for channel in 0..self.channels() {
+            let source: Vec<f64> = self.channel(channel).iter().map(|x| x.to_f64()).collect();
+            // The same phases are used in all channels.
+            stretcher.reset(0);
+            let mut i = 0;
+            while i < length {
+                let position = round(i as f64 / stretch) as usize;
+                stretcher.process(&source, position, &mut output);
+                for (j, &x) in output.iter().take(length - i).enumerate() {
+                    wave.set(channel, i + j, x as f48);
+                }
+                i += hop;
+            }
+        }
+        wave
+    }
+
     /// Render wave with length `duration` seconds from generator `node`.
     /// Sets the sample rate of `node`.
-    /// Does not discard pre-delay.
+    /// Does not discard pre-delay. The wave inherits the output channel layout of `node`.
     ///
     /// ### Example: Render 10 Seconds Of Stereo Brown Noise
     /// ```
@@ -465,6 +829,7 @@ impl Wave48 {
         let length = (duration * sample_rate).round() as usize;
         let mut wave = Self::with_capacity(node.outputs(), sample_rate, length);
         wave.len = length;
+        wave.layout = node.output_layout();
         let mut i = 0;
         let mut buffer = Self::new(node.outputs(), sample_rate);
         let mut reusable_slice = Slice::<[f48]>::with_capacity(node.outputs());
@@ -480,6 +845,40 @@ impl Wave48 {
         wave
     }
 
+    /// Render stems with length `duration` seconds from generator `node` in a single pass.
+    /// The outputs of `node` are split into consecutive groups: stem `i` receives
+    /// the next `stems[i]` channels. The number of channels in all stems
+    /// must add up to the number of outputs. All stems are time-aligned.
+    /// Sets the sample rate of `node`. Does not discard pre-delay.
+    ///
+    /// ### Example: Render Stereo Drum, Mono Bass And Stereo Pad Stems
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let drums = noise() >> pan(0.0);
+    /// let bass = saw_hz(55.0);
+    /// let pads = saw_hz(220.0) | saw_hz(330.0);
+    /// let stems = Wave64::render_stems(44100.0, 1.0, &mut (drums | bass | pads), &[2, 1, 2]);
+    /// assert!(stems.len() == 3 && stems[1].channels() == 1 && stems[2].duration() == 1.0);
+    /// ```
+    pub fn render_stems(
+        sample_rate: f64,
+        duration: f64,
+        node: &mut dyn AudioUnit48,
+        stems: &[usize],
+    ) -> Vec<Self> {
+        assert_eq!(stems.iter().sum::<usize>(), node.outputs());
+        let mut wave = Self::render(sample_rate, duration, node);
+        let mut result = Vec::with_capacity(stems.len());
+        for &channels in stems {
+            let mut stem = Self::new(0, sample_rate);
+            // Move channels over without copying.
+            stem.vec = wave.vec.drain(..channels).collect();
+            stem.len = wave.len;
+            result.push(stem);
+        }
+        result
+    }
+
     /// Render wave with length `duration` seconds from generator `node`.
     /// Any pre-delay, as measured by signal latency, is discarded.
     /// Resets `node` and sets its sample rate.
@@ -504,6 +903,7 @@ impl Wave48 {
         if latency_samples > 0 {
             let latency_wave = Self::render(sample_rate, duration + latency_duration, node);
             let mut wave = Self::silence(node.outputs(), sample_rate, duration);
+            wave.layout = latency_wave.layout;
             for channel in 0..wave.channels() {
                 for i in 0..duration_samples {
                     wave.set(channel, i, latency_wave.at(channel, i + latency_samples));
@@ -518,6 +918,8 @@ impl Wave48 {
     /// Filter this wave with `node` and return the resulting wave.
     /// Sets the sample rate of `node`. Does not discard pre-delay.
     /// The `node` must have as many inputs as there are channels in this wave.
+    /// If both this wave and the inputs of `node` declare a channel layout, then they must match.
+    /// The resulting wave inherits the output channel layout of `node`.
     /// All zeros input is used for the rest of the wave if
     /// the duration is greater than the duration of this wave.
     ///
@@ -533,11 +935,20 @@ impl Wave48 {
         assert_eq!(node.inputs(), self.channels());
         assert!(node.outputs() > 0);
         assert!(duration >= 0.0);
+        if let (Some(layout), Some(input)) = (self.layout, node.input_layout()) {
+            if layout != input {
+                panic!(
+                    "Filter: mismatched channel layouts ({} wave versus {} inputs).",
+                    layout, input
+                );
+            }
+        }
         node.set_sample_rate(self.sample_rate());
         let total_length = round(duration * self.sample_rate()) as usize;
         let input_length = min(total_length, self.length());
         let mut wave = Self::with_capacity(node.outputs(), self.sample_rate(), total_length);
         wave.len = total_length;
+        wave.layout = node.output_layout();
         let mut i = 0;
         let mut input_buffer = Self::new(self.channels(), self.sample_rate());
         let mut reusable_input_slice = Slice::<[f48]>::with_capacity(self.channels());
@@ -609,6 +1020,7 @@ impl Wave48 {
         if latency_samples > 0 {
             let latency_wave = self.filter(duration + latency_duration, node);
             let mut wave = Self::silence(node.outputs(), self.sample_rate(), duration);
+            wave.layout = latency_wave.layout;
             for channel in 0..wave.channels() {
                 for i in 0..duration_samples {
                     wave.set(channel, i, latency_wave.at(channel, i + latency_samples));
@@ -629,6 +1041,7 @@ impl Wave48 {
             &mut writer,
             2 * self.channels() * self.length(),
             1,
+            2,
             self.channels(),
             round(self.sample_rate()) as usize,
         )?;
@@ -641,6 +1054,66 @@ impl Wave48 {
         std::io::Result::Ok(())
     }
 
+    /// Write the wave as a 24-bit WAV to a buffer.
+    /// Individual samples are clipped to the range -1...1.
+    pub fn write_wav24<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        assert!(self.channels() > 0);
+        let mut writer = BufWriter::new(writer);
+        write_wav_header(
+            &mut writer,
+            3 * self.channels() * self.length(),
+            1,
+            3,
+            self.channels(),
+            round(self.sample_rate()) as usize,
+        )?;
+        for i in 0..self.length() {
+            for channel in 0..self.channels() {
+                let sample = round(clamp11(self.at(channel, i)) * 8388607.49);
+                write24(&mut writer, sample.to_i64() as u32)?;
+            }
+        }
+        std::io::Result::Ok(())
+    }
+
+    /// Write the wave as a 16-bit or 24-bit WAV to a buffer, applying TPDF dither
+    /// with noise `shaping`. Each channel is dithered independently.
+    /// The dither sequence is deterministic.
+    /// Individual samples are clipped to the range -1...1.
+    pub fn write_wav_dither<W: Write>(
+        &self,
+        writer: &mut W,
+        bits: u32,
+        shaping: NoiseShaping,
+    ) -> std::io::Result<()> {
+        assert!(self.channels() > 0);
+        assert!(bits == 16 || bits == 24);
+        let sample_bytes = bits / 8;
+        let mut writer = BufWriter::new(writer);
+        write_wav_header(
+            &mut writer,
+            sample_bytes as usize * self.channels() * self.length(),
+            1,
+            sample_bytes,
+            self.channels(),
+            round(self.sample_rate()) as usize,
+        )?;
+        let mut ditherer: Vec<Ditherer> = (0..self.channels())
+            .map(|channel| Ditherer::new(bits, shaping, channel as u64))
+            .collect();
+        for i in 0..self.length() {
+            for (channel, ditherer) in ditherer.iter_mut().enumerate() {
+                let sample = ditherer.quantize(self.at(channel, i).to_f64());
+                if bits == 16 {
+                    write16(&mut writer, sample as u16)?;
+                } else {
+                    write24(&mut writer, sample as u32)?;
+                }
+            }
+        }
+        std::io::Result::Ok(())
+    }
+
     /// Write the wave as a 32-bit float WAV to a buffer.
     /// Samples are not clipped to any range but some
     /// applications may expect the range to be -1...1.
@@ -651,6 +1124,7 @@ impl Wave48 {
             &mut writer,
             4 * self.channels() * self.length(),
             3,
+            4,
             self.channels(),
             round(self.sample_rate()) as usize,
         )?;
@@ -671,6 +1145,34 @@ impl Wave48 {
         self.write_wav16(&mut file)
     }
 
+    /// Save the wave as a 24-bit WAV file.
+    /// Individual samples are clipped to the range -1...1.
+    pub fn save_wav24<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        assert!(self.channels() > 0);
+        let mut file = File::create(path.as_ref())?;
+        self.write_wav24(&mut file)
+    }
+
+    /// Save the wave as a 16-bit or 24-bit WAV file, applying TPDF dither
+    /// with noise `shaping`. Individual samples are clipped to the range -1...1.
+    ///
+    /// ### Example: Save 16-Bit WAV With Noise Shaped Dither
+    /// ```no_run
+    /// use fundsp::hacker::*;
+    /// let wave = Wave64::render(44100.0, 1.0, &mut (sine_hz(110.0) * 0.5));
+    /// wave.save_wav_dither("test.wav", 16, NoiseShaping::Lipshitz).expect("Could not save wave.");
+    /// ```
+    pub fn save_wav_dither<P: AsRef<Path>>(
+        &self,
+        path: P,
+        bits: u32,
+        shaping: NoiseShaping,
+    ) -> std::io::Result<()> {
+        assert!(self.channels() > 0);
+        let mut file = File::create(path.as_ref())?;
+        self.write_wav_dither(&mut file, bits, shaping)
+    }
+
     /// Save the wave as a 32-bit float WAV file.
     /// Samples are not clipped to any range but some
     /// applications may expect the range to be -1...1.
@@ -681,7 +1183,28 @@ impl Wave48 {
     }
 }
 
+/// Loop mode of wave players. Looping happens between the loop point and the end point.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LoopMode {
+    /// Loop forward, jumping to the loop point at the end point.
+    /// The end of the loop is crossfaded linearly into the samples preceding
+    /// the loop point over the given number of samples (0 = no crossfade).
+    Forward(usize),
+    /// Alternate playback direction between the loop point and the end point.
+    PingPong,
+    /// Loop forward between the loop point and the given release point while the gate is on.
+    /// When the gate goes off, playback jumps to the release point and continues to the end point.
+    Release(usize),
+}
+
+impl Default for LoopMode {
+    fn default() -> Self {
+        LoopMode::Forward(0)
+    }
+}
+
 /// Play back one channel of a wave.
+/// Setting: gate (`false` releases the sustain loop in `LoopMode::Release`).
 /// - Output 0: wave
 #[duplicate_item(
     f48       Wave48       Wave48Player;
@@ -696,6 +1219,11 @@ pub struct Wave48Player<T: Float> {
     start_point: usize,
     end_point: usize,
     loop_point: Option<usize>,
+    mode: LoopMode,
+    /// Playback direction in ping-pong mode.
+    forward: bool,
+    /// Whether the sustain loop has been released.
+    released: bool,
     _marker: PhantomData<T>,
 }
 
@@ -721,7 +1249,90 @@ impl<T: Float> Wave48Player<T> {
             start_point,
             end_point,
             loop_point,
-            _marker: PhantomData::default(),
+            mode: LoopMode::default(),
+            forward: true,
+            released: false,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Loop mode.
+    pub fn loop_mode(&self) -> LoopMode {
+        self.mode
+    }
+
+    /// Set loop mode. The mode applies only if there is a loop point.
+    /// In `LoopMode::Release`, the release point must lie after the loop point
+    /// and not after the end point.
+    pub fn set_loop_mode(&mut self, mode: LoopMode) {
+        if let (LoopMode::Release(release), Some(point)) = (mode, self.loop_point) {
+            assert!(point < release && release <= self.end_point);
+        }
+        self.mode = mode;
+    }
+
+    /// Release the sustain loop. In `LoopMode::Release`, playback jumps to the release point.
+    pub fn release(&mut self) {
+        if !self.released {
+            self.released = true;
+            if let (LoopMode::Release(release), Some(_)) = (self.mode, self.loop_point) {
+                self.index = std::cmp::max(self.index, release);
+            }
+        }
+    }
+
+    /// Advance playback by one sample and return the sample played.
+    #[inline]
+    fn advance(&mut self) -> f48 {
+        let Some(point) = self.loop_point else {
+            let value = self.wave.at(self.channel, self.index);
+            self.index += 1;
+            return value;
+        };
+        match self.mode {
+            LoopMode::Forward(crossfade) => {
+                let mut value = self.wave.at(self.channel, self.index);
+                let crossfade =
+                    std::cmp::min(crossfade, std::cmp::min(point, self.end_point - point));
+                if crossfade > 0 && self.index >= point && self.index + crossfade >= self.end_point
+                {
+                    let k = self.index + crossfade - self.end_point;
+                    let t = (k as f48 + 0.5) / crossfade as f48;
+                    value += (self.wave.at(self.channel, point - crossfade + k) - value) * t;
+                }
+                self.index += 1;
+                if self.index == self.end_point {
+                    self.index = point;
+                }
+                value
+            }
+            LoopMode::PingPong => {
+                let value = self.wave.at(self.channel, self.index);
+                if self.forward {
+                    if self.index + 1 < self.end_point {
+                        self.index += 1;
+                    } else if self.index > point {
+                        self.forward = false;
+                        self.index -= 1;
+                    }
+                } else if self.index > point {
+                    self.index -= 1;
+                } else {
+                    self.forward = true;
+                    if self.index + 1 < self.end_point {
+                        self.index += 1;
+                    }
+                }
+                value
+            }
+            LoopMode::Release(release) => {
+                let value = self.wave.at(self.channel, self.index);
+                self.index += 1;
+                if !self.released && self.index == release {
+                    self.index = point;
+                }
+                value
+            }
         }
     }
 }
@@ -736,10 +1347,20 @@ impl<T: Float> AudioNode for Wave48Player<T> {
     type Sample = T;
     type Inputs = typenum::U0;
     type Outputs = typenum::U1;
-    type Setting = ();
+    type Setting = bool;
+
+    fn set(&mut self, setting: Self::Setting) {
+        if setting {
+            self.released = false;
+        } else {
+            self.release();
+        }
+    }
 
     fn reset(&mut self) {
         self.index = self.start_point;
+        self.forward = true;
+        self.released = false;
     }
 
     #[inline]
@@ -748,16 +1369,168 @@ impl<T: Float> AudioNode for Wave48Player<T> {
         _input: &Frame<Self::Sample, Self::Inputs>,
     ) -> Frame<Self::Sample, Self::Outputs> {
         if self.index < self.end_point {
-            let value = self.wave.at(self.channel, self.index);
-            self.index += 1;
-            if self.index == self.end_point {
-                if let Some(point) = self.loop_point {
-                    self.index = point;
-                }
-            }
-            [convert(value)].into()
+            [convert(self.advance())].into()
         } else {
             [T::zero()].into()
         }
     }
 }
+
+/// Play back one channel of a wave, restarting playback on each rising edge of the trigger input.
+/// The start of playback can be offset randomly to vary repeated hits.
+/// A retriggered sound is faded out over a short declick time while the new one starts.
+/// - Input 0: trigger (rising edge through zero restarts playback)
+/// - Output 0: wave
+#[duplicate_item(
+    f48       Wave48       Wave48Trigger;
+    [ f64 ]   [ Wave64 ]   [ Wave64Trigger ];
+    [ f32 ]   [ Wave32 ]   [ Wave32Trigger ];
+)]
+#[derive(Clone)]
+pub struct Wave48Trigger<T: Float> {
+    wave: Arc<Wave48>,
+    channel: usize,
+    /// Playback index. Playback is stopped when this is at the end of the wave.
+    index: usize,
+    /// Index of the sound that is being faded out.
+    fade_index: usize,
+    /// Remaining samples of fade out.
+    fade: usize,
+    /// Remaining samples of fade in.
+    fade_in: usize,
+    /// Maximum random start offset in samples.
+    jitter: usize,
+    /// Declick time in seconds.
+    declick: f64,
+    declick_samples: usize,
+    sample_rate: f64,
+    /// Previous trigger input.
+    previous: T,
+    hash: u64,
+    /// Random number index.
+    rnd: i64,
+}
+
+#[duplicate_item(
+    f48       Wave48       Wave48Trigger;
+    [ f64 ]   [ Wave64 ]   [ Wave64Trigger ];
+    [ f32 ]   [ Wave32 ]   [ Wave32Trigger ];
+)]
+impl<T: Float> Wave48Trigger<T> {
+    /// Create new triggered player of `channel` of `wave`.
+    /// Playback starts at a random offset of up to `jitter` seconds into the wave.
+    /// Sounds are declicked over `declick` seconds (for example, 0.002).
+    pub fn new(wave: &Arc<Wave48>, channel: usize, jitter: f64, declick: f64) -> Self {
+        assert!(channel < wave.channels());
+        assert!(jitter >= 0.0 && declick >= 0.0);
+        let mut node = Self {
+            wave: wave.clone(),
+            channel,
+            index: wave.length(),
+            fade_index: 0,
+            fade: 0,
+            fade_in: 0,
+            jitter: round(jitter * wave.sample_rate()) as usize,
+            declick,
+            declick_samples: 0,
+            sample_rate: DEFAULT_SR,
+            previous: T::zero(),
+            hash: 0,
+            rnd: 0,
+        };
+        node.set_sample_rate(DEFAULT_SR);
+        node
+    }
+
+    /// Restart playback.
+    pub fn trigger(&mut self) {
+        if self.index < self.wave.length() && self.declick_samples > 0 {
+            self.fade_index = self.index;
+            self.fade = self.declick_samples;
+        }
+        let offset = if self.jitter > 0 {
+            let offset = (rnd(self.rnd) * (self.jitter + 1) as f64) as usize;
+            self.rnd += 1;
+            std::cmp::min(offset, self.jitter)
+        } else {
+            0
+        };
+        self.index = std::cmp::min(offset, self.wave.length());
+        // Playback starting in the middle of the wave is faded in.
+        self.fade_in = if offset > 0 { self.declick_samples } else { 0 };
+    }
+}
+
+#[duplicate_item(
+    f48       Wave48       Wave48Trigger;
+    [ f64 ]   [ Wave64 ]   [ Wave64Trigger ];
+    [ f32 ]   [ Wave32 ]   [ Wave32Trigger ];
+)]
+impl<T: Float> AudioNode for Wave48Trigger<T> {
+    const ID: u64 = 114;
+    type Sample = T;
+    type Inputs = typenum::U1;
+    type Outputs = typenum::U1;
+    type Setting = ();
+
+    fn reset(&mut self) {
+        self.index = self.wave.length();
+        self.fade = 0;
+        self.fade_in = 0;
+        self.previous = T::zero();
+        self.rnd = self.hash as i64;
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate;
+        self.declick_samples = round(self.declick * sample_rate) as usize;
+    }
+
+    fn input_kind(&self, input: usize) -> PortKind {
+        match input {
+            0 => PortKind::Trigger,
+            _ => PortKind::Audio,
+        }
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        if input[0] > T::zero() && self.previous <= T::zero() {
+            self.trigger();
+        }
+        self.previous = input[0];
+        let length = self.wave.length();
+        let mut value: f48 = 0.0;
+        if self.index < length {
+            value = self.wave.at(self.channel, self.index);
+            if self.fade_in > 0 {
+                value *= 1.0 - self.fade_in as f48 / (self.declick_samples + 1) as f48;
+                self.fade_in -= 1;
+            }
+            self.index += 1;
+        }
+        if self.fade > 0 {
+            if self.fade_index < length {
+                value += self.wave.at(self.channel, self.fade_index) * self.fade as f48
+                    / (self.declick_samples + 1) as f48;
+                self.fade_index += 1;
+            }
+            self.fade -= 1;
+        }
+        [convert(value)].into()
+    }
+
+    fn set_hash(&mut self, hash: u64) {
+        self.hash = hash;
+        self.reset();
+    }
+
+    fn route(&mut self, _input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        output[0] = Signal::Latency(0.0);
+        output
+    }
+}
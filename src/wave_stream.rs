@@ -338,9 +338,9 @@ impl WaveStream48 {
     /// ```
     #[inline]
     pub fn duration(&self) -> f64 {
-        let len = if !self.loaded{
+        let len = if !self.loaded {
             self.theoric_len.unwrap_or(self.len)
-        }else{
+        } else {
             self.len
         } as f64;
         len / self.sample_rate()
@@ -699,7 +699,7 @@ impl WaveStream48 {
         self.write_wav32(&mut file)
     }
 
-    pub fn set_loaded(&mut self){
+    pub fn set_loaded(&mut self) {
         self.loaded = true;
         log::info!("Wave fully loaded: {} / {:?}", self.len, self.theoric_len);
     }
@@ -723,11 +723,11 @@ pub struct WaveStream48Player<T: Float> {
     sample_rate: Option<f64>,
     _marker: PhantomData<T>,
 }
-pub enum WaveStreamPlayerPositionError{
+pub enum WaveStreamPlayerPositionError {
     OutOfRange,
     NotFullyLoaded,
     NoWave,
-    NoSampleRate
+    NoSampleRate,
 }
 impl std::fmt::Display for WaveStreamPlayerPositionError {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
@@ -767,58 +767,57 @@ impl<T: Float> WaveStream48Player<T> {
         }
     }
 
-    pub fn set_loop(&mut self, loop_point: Option<usize>){
+    pub fn set_loop(&mut self, loop_point: Option<usize>) {
         self.loop_point = loop_point;
     }
 
-    pub fn set_wave(&mut self, wave: Arc<tokio::sync::RwLock<WaveStream48>>, repeat: bool){
+    pub fn set_wave(&mut self, wave: Arc<tokio::sync::RwLock<WaveStream48>>, repeat: bool) {
         self.sample_rate = Some(wave.blocking_read().sample_rate());
         self.wave = Some(wave);
         self.index = 0;
-        self.loop_point = if repeat{
-            Some(0)
-        }else{
-            None
-        };
+        self.loop_point = if repeat { Some(0) } else { None };
     }
 
-    pub fn is_end_reached(&self) -> bool{
-        if self.loop_point.is_some(){
+    pub fn is_end_reached(&self) -> bool {
+        if self.loop_point.is_some() {
             false
-        }else{
+        } else {
             (self.index == self.end_point) && self.index > 0
         }
     }
-    pub fn get_position(&self) -> f64{
-        if let Some(sr) = self.sample_rate{
+    pub fn get_position(&self) -> f64 {
+        if let Some(sr) = self.sample_rate {
             self.index as f64 / sr
-        }else{0.0}
+        } else {
+            0.0
+        }
     }
-    pub fn get_position_ms(&self) -> u64{
+    pub fn get_position_ms(&self) -> u64 {
         (self.get_position() * 1000.0) as u64
     }
-    pub fn set_position(&mut self, position: f64) -> Result<(), WaveStreamPlayerPositionError/*String*/>{
-        if let Some(sr) = self.sample_rate{
+    pub fn set_position(
+        &mut self,
+        position: f64,
+    ) -> Result<(), WaveStreamPlayerPositionError /*String*/> {
+        if let Some(sr) = self.sample_rate {
             let next_position: usize = (sr * position) as usize;
-            if let Some(wave) = &self.wave{
+            if let Some(wave) = &self.wave {
                 let len = wave.blocking_read().len();
-                if len > next_position{
+                if len > next_position {
                     self.index = next_position;
                     Ok(())
-                }else if wave.blocking_read().loaded{
+                } else if wave.blocking_read().loaded {
                     Err(WaveStreamPlayerPositionError::OutOfRange)
                     //Err("position out of range".to_string())
-                }
-                else{
+                } else {
                     Err(WaveStreamPlayerPositionError::NotFullyLoaded)
                     //Err("File not fully loaded".to_string())
                 }
-            }
-            else{
+            } else {
                 Err(WaveStreamPlayerPositionError::NoWave)
                 //Err("no wave loaded".to_string())
             }
-        }else{
+        } else {
             Err(WaveStreamPlayerPositionError::NoSampleRate)
             //Err("generic error".to_string())
         }
@@ -846,14 +845,14 @@ impl<T: Float> AudioNode for WaveStream48Player<T> {
         &mut self,
         _input: &Frame<Self::Sample, Self::Inputs>,
     ) -> Frame<Self::Sample, Self::Outputs> {
-        if let Some(wave) = self.wave.as_mut(){
+        if let Some(wave) = self.wave.as_mut() {
             let w = wave.blocking_read();
             self.end_point = w.length();
             if self.index < self.end_point {
                 let value_left = w.at(0, self.index);
-                let value_right = if w.channels() > 1{
+                let value_right = if w.channels() > 1 {
                     w.at(1, self.index)
-                }else{
+                } else {
                     value_left
                 };
                 self.index += 1;
@@ -866,9 +865,249 @@ impl<T: Float> AudioNode for WaveStream48Player<T> {
             } else {
                 [T::zero(), T::zero()].into()
             }
-        }
-        else{
+        } else {
             [T::zero(), T::zero()].into()
         }
     }
 }
+
+/// Sliding window of a multichannel wave that is streamed from disk.
+/// Only the window is kept in memory. It is refilled by a background task
+/// as playback proceeds. See `DiskStream64::open` and `DiskStream32::open`.
+#[duplicate_item(
+    f48       DiskStream48;
+    [ f64 ]   [ DiskStream64 ];
+    [ f32 ]   [ DiskStream32 ];
+)]
+pub struct DiskStream48 {
+    /// Samples in the window, one queue per channel.
+    window: Vec<std::collections::VecDeque<f48>>,
+    /// Index of the first sample of the window in the stream.
+    offset: usize,
+    /// Maximum number of samples kept in memory per channel.
+    capacity: usize,
+    /// Sample rate of the stream.
+    sr: f64,
+    /// Whether the end of the stream has been read into the window.
+    loaded: bool,
+    /// Number of samples that were not available in time for playback.
+    underruns: u64,
+    /// Notified when half of the window has been consumed.
+    space: Arc<tokio::sync::Notify>,
+}
+
+#[duplicate_item(
+    f48       DiskStream48;
+    [ f64 ]   [ DiskStream64 ];
+    [ f32 ]   [ DiskStream32 ];
+)]
+impl DiskStream48 {
+    /// Create an empty stream window with the given number of `channels`
+    /// that holds up to `capacity` samples per channel (`capacity` > 0).
+    pub fn new(channels: usize, sample_rate: f64, capacity: usize) -> Self {
+        assert!(capacity > 0);
+        Self {
+            window: (0..channels)
+                .map(|_| std::collections::VecDeque::with_capacity(capacity))
+                .collect(),
+            offset: 0,
+            capacity,
+            sr: sample_rate,
+            loaded: false,
+            underruns: 0,
+            space: Arc::new(tokio::sync::Notify::new()),
+        }
+    }
+
+    /// Sample rate of the stream.
+    #[inline]
+    pub fn sample_rate(&self) -> f64 {
+        self.sr
+    }
+
+    /// Number of channels in the stream.
+    #[inline]
+    pub fn channels(&self) -> usize {
+        self.window.len()
+    }
+
+    /// Maximum number of samples kept in memory per channel.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of samples per channel currently in the window.
+    #[inline]
+    pub fn available(&self) -> usize {
+        self.window.first().map_or(0, |channel| channel.len())
+    }
+
+    /// Index of the first sample of the window in the stream.
+    #[inline]
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Whether the end of the stream has been read into the window.
+    #[inline]
+    pub fn is_loaded(&self) -> bool {
+        self.loaded
+    }
+
+    /// Number of samples that were not available in time for playback so far.
+    #[inline]
+    pub fn underruns(&self) -> u64 {
+        self.underruns
+    }
+
+    /// Whether the window has room for `frames` more samples.
+    /// An empty window accepts any number of samples.
+    pub fn has_room(&self, frames: usize) -> bool {
+        self.available() == 0 || self.available() + frames <= self.capacity
+    }
+
+    /// Append samples to the end of the window. All channels must be extended by the same amount.
+    pub fn extend(&mut self, channel: usize, samples: &[f48]) {
+        self.window[channel].extend(samples.iter());
+    }
+
+    /// Mark the end of the stream as read.
+    pub fn set_loaded(&mut self) {
+        self.loaded = true;
+    }
+
+    /// Handle that is notified when there is room in the window.
+    pub fn space(&self) -> Arc<tokio::sync::Notify> {
+        self.space.clone()
+    }
+
+    /// Sample at the start of the window in `channel`, if available.
+    #[inline]
+    pub fn front(&self, channel: usize) -> Option<f48> {
+        self.window[channel].front().copied()
+    }
+
+    /// Remove the first sample of the window from all channels.
+    #[inline]
+    pub fn advance(&mut self) {
+        for channel in self.window.iter_mut() {
+            channel.pop_front();
+        }
+        self.offset += 1;
+        // Prefetch more samples when half of the window has been consumed.
+        // An empty window is signaled as well, as it accepts packets of any size.
+        if self.available() == self.capacity / 2 || self.available() == 0 {
+            self.space.notify_one();
+        }
+    }
+}
+
+/// Play back a wave streamed from disk.
+/// Missing samples are reported as underruns in the stream and played back as silence.
+/// Streamed samples cannot be rewound: resetting the player does not restart the stream.
+/// - Output 0: left channel
+/// - Output 1: right channel (the left channel is duplicated for mono streams)
+#[duplicate_item(
+    f48       DiskStream48       DiskStream48Player;
+    [ f64 ]   [ DiskStream64 ]   [ DiskStream64Player ];
+    [ f32 ]   [ DiskStream32 ]   [ DiskStream32Player ];
+)]
+pub struct DiskStream48Player<T: Float> {
+    stream: Arc<tokio::sync::RwLock<DiskStream48>>,
+    /// Underruns that happened while the stream was locked by the background task.
+    pending: u64,
+    /// Whether the end of the stream has been played.
+    end: bool,
+    _marker: PhantomData<T>,
+}
+
+#[duplicate_item(
+    f48       DiskStream48       DiskStream48Player;
+    [ f64 ]   [ DiskStream64 ]   [ DiskStream64Player ];
+    [ f32 ]   [ DiskStream32 ]   [ DiskStream32Player ];
+)]
+impl<T: Float> DiskStream48Player<T> {
+    /// Create a player for `stream`.
+    /// The stream is consumed as it is played, so each stream should have one player only.
+    pub fn new(stream: &Arc<tokio::sync::RwLock<DiskStream48>>) -> Self {
+        Self {
+            stream: stream.clone(),
+            pending: 0,
+            end: false,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Whether the end of the stream has been played. Clones report the end as reached.
+    pub fn is_end_reached(&self) -> bool {
+        self.end
+    }
+}
+
+/// The stream is consumed by the original player only.
+/// Clones play back silence, so that stream samples are not split between players.
+#[duplicate_item(
+    f48       DiskStream48       DiskStream48Player;
+    [ f64 ]   [ DiskStream64 ]   [ DiskStream64Player ];
+    [ f32 ]   [ DiskStream32 ]   [ DiskStream32Player ];
+)]
+impl<T: Float> Clone for DiskStream48Player<T> {
+    fn clone(&self) -> Self {
+        Self {
+            stream: self.stream.clone(),
+            pending: 0,
+            end: true,
+            _marker: PhantomData,
+        }
+    }
+}
+
+#[duplicate_item(
+    f48       DiskStream48       DiskStream48Player;
+    [ f64 ]   [ DiskStream64 ]   [ DiskStream64Player ];
+    [ f32 ]   [ DiskStream32 ]   [ DiskStream32Player ];
+)]
+impl<T: Float> AudioNode for DiskStream48Player<T> {
+    const ID: u64 = 115;
+    type Sample = T;
+    type Inputs = typenum::U0;
+    type Outputs = typenum::U2;
+    type Setting = ();
+
+    #[inline]
+    fn tick(
+        &mut self,
+        _input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        if self.end {
+            return [T::zero(), T::zero()].into();
+        }
+        // The background task holds the lock only briefly. We do not wait for it.
+        let Ok(mut stream) = self.stream.try_write() else {
+            self.pending += 1;
+            return [T::zero(), T::zero()].into();
+        };
+        stream.underruns += self.pending;
+        self.pending = 0;
+        match stream.front(0) {
+            Some(value_left) => {
+                let value_right = if stream.channels() > 1 {
+                    stream.front(1).unwrap_or(value_left)
+                } else {
+                    value_left
+                };
+                stream.advance();
+                [convert(value_left), convert(value_right)].into()
+            }
+            None => {
+                if stream.is_loaded() {
+                    self.end = true;
+                } else {
+                    stream.underruns += 1;
+                }
+                [T::zero(), T::zero()].into()
+            }
+        }
+    }
+}
@@ -227,6 +227,13 @@ where
         self.phase = self.initial_phase;
     }
 
+    fn input_kind(&self, input: usize) -> PortKind {
+        match input {
+            0 => PortKind::Frequency,
+            _ => PortKind::Audio,
+        }
+    }
+
     #[inline]
     fn tick(
         &mut self,
@@ -256,6 +263,116 @@ where
     }
 }
 
+/// Bandlimited wavetable synthesizer that scans across a sequence of wavetable frames.
+/// Adjacent frames are interpolated linearly. Frames should have matching partial phases
+/// for the interpolation to be smooth.
+/// - Input 0: frequency in Hz.
+/// - Input 1: table position in 0...1, where 0 is the first frame and 1 is the last frame.
+/// - Output 0: audio.
+#[derive(Clone)]
+pub struct WaveScan<'a, T>
+where
+    T: Float,
+{
+    frames: Vec<&'a Wavetable>,
+    /// Previously used transposition table of each frame.
+    table_hint: Vec<usize>,
+    /// Phase in 0...1.
+    phase: f32,
+    /// Initial phase in 0...1, seeded via pseudorandom phase system.
+    initial_phase: f32,
+    sample_rate: f32,
+    _marker: std::marker::PhantomData<T>,
+}
+
+impl<'a, T> WaveScan<'a, T>
+where
+    T: Float,
+{
+    /// Create new scanning synthesizer from wavetable `frames` (at least one).
+    pub fn new(sample_rate: f64, frames: &[&'a Wavetable]) -> Self {
+        assert!(!frames.is_empty());
+        WaveScan {
+            frames: frames.to_vec(),
+            table_hint: vec![0; frames.len()],
+            phase: 0.0,
+            initial_phase: 0.0,
+            sample_rate: sample_rate as f32,
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    /// Number of wavetable frames.
+    pub fn frames(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Read frame `i` at the current phase.
+    #[inline]
+    fn read_frame(&mut self, i: usize, frequency: f32) -> f32 {
+        let (output, hint) = self.frames[i].read(self.table_hint[i], frequency, self.phase);
+        self.table_hint[i] = hint;
+        output
+    }
+}
+
+impl<'a, T> AudioNode for WaveScan<'a, T>
+where
+    T: Float,
+{
+    const ID: u64 = 111;
+    type Sample = T;
+    type Inputs = numeric_array::typenum::U2;
+    type Outputs = numeric_array::typenum::U1;
+    type Setting = ();
+
+    fn reset(&mut self) {
+        self.phase = self.initial_phase;
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate as f32;
+    }
+
+    fn set_hash(&mut self, hash: u64) {
+        self.initial_phase = super::hacker::rnd(hash as i64) as f32;
+        self.phase = self.initial_phase;
+    }
+
+    fn input_kind(&self, input: usize) -> PortKind {
+        match input {
+            0 => PortKind::Frequency,
+            1 => PortKind::Control,
+            _ => PortKind::Audio,
+        }
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let frequency = input[0].to_f32();
+        let delta = frequency / self.sample_rate;
+        self.phase += delta;
+        self.phase -= floor(self.phase);
+        let position = clamp01(input[1].to_f32()) * (self.frames.len() - 1) as f32;
+        let i = min(position as usize, self.frames.len() - 1);
+        let w = position - i as f32;
+        let mut output = self.read_frame(i, abs(frequency));
+        if w > 0.0 {
+            output += w * (self.read_frame(i + 1, abs(frequency)) - output);
+        }
+        [convert(output)].into()
+    }
+
+    fn route(&mut self, _input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        output[0] = Signal::Latency(0.0);
+        output
+    }
+}
+
 /// Bandlimited wavetable synthesizer driven by a phase input.
 /// - Input 0: phase in 0...1.
 /// - Output 0: audio.
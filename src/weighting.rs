@@ -0,0 +1,224 @@
+//! Frequency weighting filters for level measurement.
+
+use super::audionode::*;
+use super::filter::BiquadCoefs;
+use super::math::*;
+use super::signal::*;
+use super::*;
+use num_complex::Complex64;
+use numeric_array::typenum::*;
+
+/// Standard frequency weighting curves.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Weighting {
+    /// A-weighting (IEC 61672), the common choice for sound levels. 0 dB at 1 kHz.
+    #[default]
+    A,
+    /// B-weighting, between A and C. 0 dB at 1 kHz.
+    B,
+    /// C-weighting (IEC 61672), nearly flat over the audible range. 0 dB at 1 kHz.
+    C,
+    /// K-weighting (ITU-R BS.1770) used in loudness measurement. About +0.7 dB at 1 kHz.
+    K,
+}
+
+/// Number of biquad sections in a weighting filter.
+const SECTIONS: usize = 3;
+
+/// Bilinear transform of the analog biquad `(b0 s^2 + b1 s + b2) / (a0 s^2 + a1 s + a2)`.
+fn bilinear(b: [f64; 3], a: [f64; 3], sample_rate: f64) -> BiquadCoefs<f64> {
+    let c = 2.0 * sample_rate;
+    let c2 = c * c;
+    let a0 = a[0] * c2 + a[1] * c + a[2];
+    BiquadCoefs::arbitrary(
+        2.0 * (a[2] - a[0] * c2) / a0,
+        (a[0] * c2 - a[1] * c + a[2]) / a0,
+        (b[0] * c2 + b[1] * c + b[2]) / a0,
+        2.0 * (b[2] - b[0] * c2) / a0,
+        (b[0] * c2 - b[1] * c + b[2]) / a0,
+    )
+}
+
+/// Angular frequency of an analog pole at `f` Hz, prewarped for the bilinear transform.
+fn prewarp(f: f64, sample_rate: f64) -> f64 {
+    2.0 * sample_rate * tan(PI * min(f, 0.45 * sample_rate) / sample_rate)
+}
+
+/// Biquad sections implementing `weighting` at `sample_rate` Hz, without gain normalization.
+fn weighting_sections(weighting: Weighting, sample_rate: f64) -> [BiquadCoefs<f64>; SECTIONS] {
+    let identity = BiquadCoefs::arbitrary(0.0, 0.0, 1.0, 0.0, 0.0);
+    if weighting == Weighting::K {
+        // Pre-filter shelf and RLB highpass from ITU-R BS.1770, generalized to any sample rate.
+        let k = tan(PI * 1681.974450955533 / sample_rate);
+        let q = 0.7071752369554196;
+        let vh = db_amp(3.999843853973347);
+        let vb = pow(vh, 0.4996667741545416);
+        let a0 = 1.0 + k / q + k * k;
+        let shelf = BiquadCoefs::arbitrary(
+            2.0 * (k * k - 1.0) / a0,
+            (1.0 - k / q + k * k) / a0,
+            (vh + vb * k / q + k * k) / a0,
+            2.0 * (k * k - vh) / a0,
+            (vh - vb * k / q + k * k) / a0,
+        );
+        let k = tan(PI * 38.13547087602444 / sample_rate);
+        let q = 0.5003270373238773;
+        let a0 = 1.0 + k / q + k * k;
+        let highpass = BiquadCoefs::arbitrary(
+            2.0 * (k * k - 1.0) / a0,
+            (1.0 - k / q + k * k) / a0,
+            1.0,
+            -2.0,
+            1.0,
+        );
+        return [shelf, highpass, identity];
+    }
+    // Analog poles of the IEC 61672 curves.
+    let w1 = prewarp(20.598997, sample_rate);
+    let w2 = prewarp(107.65265, sample_rate);
+    let w3 = prewarp(737.86223, sample_rate);
+    let w4 = prewarp(12194.217, sample_rate);
+    let w5 = prewarp(158.5, sample_rate);
+    let low = bilinear([1.0, 0.0, 0.0], [1.0, 2.0 * w1, w1 * w1], sample_rate);
+    let high = bilinear([0.0, 0.0, 1.0], [1.0, 2.0 * w4, w4 * w4], sample_rate);
+    let middle = match weighting {
+        Weighting::A => bilinear([1.0, 0.0, 0.0], [1.0, w2 + w3, w2 * w3], sample_rate),
+        Weighting::B => bilinear([0.0, 1.0, 0.0], [0.0, 1.0, w5], sample_rate),
+        _ => identity,
+    };
+    [low, middle, high]
+}
+
+/// Frequency weighting filter state. Used by `WeightingNode` and weighted meters.
+#[derive(Clone, Default)]
+pub struct WeightingFilter<F: Real> {
+    coefs: [BiquadCoefs<F>; SECTIONS],
+    /// Per section state (x1, x2, y1, y2).
+    state: [[F; 4]; SECTIONS],
+    gain: F,
+}
+
+impl<F: Real> WeightingFilter<F> {
+    /// Create weighting filter for the given curve and sample rate.
+    pub fn new(weighting: Weighting, sample_rate: f64) -> Self {
+        let sections = weighting_sections(weighting, sample_rate);
+        let gain = if weighting == Weighting::K {
+            1.0
+        } else {
+            let omega = 1000.0 / sample_rate;
+            1.0 / sections
+                .iter()
+                .fold(Complex64::new(1.0, 0.0), |r, s| r * s.response(omega))
+                .norm()
+        };
+        let coefs = |c: &BiquadCoefs<f64>| {
+            BiquadCoefs::arbitrary(
+                F::from_f64(c.a1),
+                F::from_f64(c.a2),
+                F::from_f64(c.b0),
+                F::from_f64(c.b1),
+                F::from_f64(c.b2),
+            )
+        };
+        Self {
+            coefs: [
+                coefs(&sections[0]),
+                coefs(&sections[1]),
+                coefs(&sections[2]),
+            ],
+            state: [[F::zero(); 4]; SECTIONS],
+            gain: F::from_f64(gain),
+        }
+    }
+
+    /// Reset filter state.
+    pub fn reset(&mut self) {
+        self.state = [[F::zero(); 4]; SECTIONS];
+    }
+
+    /// Filter the next sample.
+    #[inline]
+    pub fn tick(&mut self, x: F) -> F {
+        let mut x = x * self.gain;
+        for (c, s) in self.coefs.iter().zip(self.state.iter_mut()) {
+            let y = c.b0 * x + c.b1 * s[0] + c.b2 * s[1] - c.a1 * s[2] - c.a2 * s[3];
+            *s = [x, s[0], y, s[2]];
+            x = y;
+        }
+        x
+    }
+
+    /// Frequency response at frequency `omega` expressed as fraction of sampling rate.
+    pub fn response(&self, omega: f64) -> Complex64 {
+        self.coefs
+            .iter()
+            .fold(Complex64::new(self.gain.to_f64(), 0.0), |r, c| {
+                r * c.response(omega)
+            })
+    }
+}
+
+/// Frequency weighting filter for measurement, for example, before a meter.
+/// A-, B- and C-weighting are within IEC 61672 class 1 tolerances at sample rates of 44.1 kHz and above.
+/// - Input 0: input signal
+/// - Output 0: weighted signal
+#[derive(Clone)]
+pub struct WeightingNode<T: Float, F: Real> {
+    _marker: std::marker::PhantomData<T>,
+    weighting: Weighting,
+    filter: WeightingFilter<F>,
+    sample_rate: f64,
+}
+
+impl<T: Float, F: Real> WeightingNode<T, F> {
+    /// Create a new weighting filter.
+    pub fn new(weighting: Weighting) -> Self {
+        Self {
+            _marker: std::marker::PhantomData,
+            weighting,
+            filter: WeightingFilter::new(weighting, DEFAULT_SR),
+            sample_rate: DEFAULT_SR,
+        }
+    }
+
+    /// Weighting curve.
+    #[inline]
+    pub fn weighting(&self) -> Weighting {
+        self.weighting
+    }
+}
+
+impl<T: Float, F: Real> AudioNode for WeightingNode<T, F> {
+    const ID: u64 = 131;
+    type Sample = T;
+    type Inputs = U1;
+    type Outputs = U1;
+    type Setting = ();
+
+    fn reset(&mut self) {
+        self.filter.reset();
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        if self.sample_rate != sample_rate {
+            self.sample_rate = sample_rate;
+            self.filter = WeightingFilter::new(self.weighting, sample_rate);
+        }
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        [convert(self.filter.tick(convert(input[0])))].into()
+    }
+
+    fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        output[0] = input[0].filter(0.0, |r| {
+            r * self.filter.response(frequency / self.sample_rate)
+        });
+        output
+    }
+}
@@ -72,6 +72,18 @@ fn check_wave_filter(input: &Wave64, mut node: impl AudioUnit64) {
     }
 }
 
+/// Amplitude of the `frequency` Hz component of `wave`, sampled at 44.1 kHz.
+/// The wave should contain a whole number of cycles of `frequency`.
+fn amplitude_at(wave: &[f64], frequency: f64) -> f64 {
+    let (mut re, mut im) = (0.0, 0.0);
+    for (i, x) in wave.iter().enumerate() {
+        let phase = TAU * frequency * i as f64 / 44100.0;
+        re += x * cos(phase);
+        im += x * sin(phase);
+    }
+    2.0 * sqrt(re * re + im * im) / wave.len() as f64
+}
+
 /// Attempt to test two nodes for equality.
 fn is_equal<X, Y>(rnd: &mut Rnd, x: &mut An<X>, y: &mut An<Y>) -> bool
 where
@@ -172,6 +184,39 @@ fn test_basic() {
     check_wave(
         dc((880.0, 440.0)) >> pass() - pass() >> branchf::<U2, _, _>(|f| (f - 0.5) * triangle()),
     );
+    check_wave(
+        (dc(110.0) | lfo(|t| sin_hz(1.0, t) * 0.5 + 0.5))
+            >> wave_scan(&[&*SAW_TABLE, &*SQUARE_TABLE])
+            >> split::<U2>(),
+    );
+
+    check_wave(
+        dc(110.0) >> phase_distortion(PdCurve::Square, 0.7)
+            | dc((220.0, 0.6)) >> phase_distortion_mod(PdCurve::Resonance),
+    );
+
+    // Phase distortion curves start from a cosine wave.
+    for curve in [PdCurve::Saw, PdCurve::Square, PdCurve::Pulse] {
+        for i in 0..100 {
+            let phase = i as f64 / 100.0;
+            assert!(abs(curve.at(phase, 0.0) - cos(phase * TAU)) < 1.0e-12);
+        }
+    }
+    assert!(PdCurve::Square.at(0.3, 1.0) == -1.0 && PdCurve::Square.at(0.8, 1.0) == 1.0);
+    assert!(PdCurve::Pulse.at(0.5, 1.0) == 1.0);
+    assert!(PdCurve::Saw.at(0.02, 1.0) < 0.0);
+
+    // Wavetable scanning interpolates between frames.
+    let mut scan = WaveScan::<f64>::new(DEFAULT_SR, &[&*SAW_TABLE, &*SQUARE_TABLE]);
+    let mut saw_synth = WaveSynth::<f64, U1>::new(DEFAULT_SR, &SAW_TABLE);
+    let mut square_synth = WaveSynth::<f64, U1>::new(DEFAULT_SR, &SQUARE_TABLE);
+    for i in 0..1000 {
+        let position = (i % 3) as f64 * 0.5;
+        let x = scan.tick(&Frame::from([220.0, position]))[0];
+        let y = saw_synth.tick(&Frame::from([220.0]))[0];
+        let z = square_synth.tick(&Frame::from([220.0]))[0];
+        assert!(abs(x - lerp(y, z, position)) < 1.0e-6);
+    }
     check_wave(
         (noise() | dc(440.0)) >> pipe::<U3, _, _>(|_| !lowpole()) >> lowpole()
             | ((mls() | dc(880.0)) >> !butterpass() >> butterpass()),
@@ -219,31 +264,721 @@ fn test_basic() {
     sequencer.push(0.6, 0.7, Fade::Power, 0.01, 0.0, Box::new(noise() | mls()));
     check_wave(sequencer);
 
+    // Grooved steps: swing delays odd steps and velocity scales events.
+    let mut groove = Groove::new(0.1);
+    groove.set_swing(0.5);
+    groove.set_template(&[(0.0, 1.0), (0.0, 0.5), (0.1, 0.25)]);
+    assert!(abs(groove.step_time(1) - 0.15) < 1.0e-9);
+    assert!(abs(groove.step_time(2) - 0.21) < 1.0e-9);
+    assert!(abs(groove.step_time(3) - 0.35) < 1.0e-9);
+    assert!(groove.step_velocity(4) == 0.5);
+    let mut sequencer = Sequencer64::new(false, 1);
+    for step in 0..3 {
+        sequencer.push_step(
+            &groove,
+            step,
+            2.0,
+            Fade::Smooth,
+            0.0,
+            0.0,
+            Box::new(dc(1.0)),
+        );
+    }
+    let expected = [1.0, 1.0, 1.0, 1.5, 0.75, 0.75, 0.75, 0.25, 0.0, 0.0];
+    let mut samples = 0;
+    for (i, level) in expected.iter().enumerate() {
+        // Sample in the middle of each 0.05 second interval to avoid rounding at edges.
+        let target = round((0.025 + 0.05 * i as f64) * DEFAULT_SR) as usize;
+        while samples < target {
+            sequencer.get_mono();
+            samples += 1;
+        }
+        assert!(abs(sequencer.get_mono() - level) < 1.0e-6);
+        samples += 1;
+    }
+    // Control values drive persistent units sample accurately.
+    let mut sequencer = Sequencer64::new(true, 2);
+    sequencer.set_control(0.0, 0, 440.0);
+    sequencer.push_control(0.01, 0.02, 1, 1.0);
+    sequencer.set_control(0.016, 0, 220.0);
+    sequencer.push(0.0, 0.02, Fade::Smooth, 0.0, 0.0, Box::new(dc((1.0, 0.5))));
+    let gate_start = round(0.01 * DEFAULT_SR) as usize;
+    let gate_end = round(0.02 * DEFAULT_SR) as usize;
+    let pitch_change = round(0.016 * DEFAULT_SR) as usize;
+    let mut ticked = Vec::new();
+    for i in 0..1000 {
+        let (pitch, gate) = sequencer.get_stereo();
+        let expected_pitch =
+            if i < pitch_change { 440.0 } else { 220.0 } + if i < gate_end { 1.0 } else { 0.0 };
+        let expected_gate = if i >= gate_start && i < gate_end {
+            1.0
+        } else {
+            0.0
+        } + if i < gate_end { 0.5 } else { 0.0 };
+        assert!(pitch == expected_pitch && gate == expected_gate);
+        ticked.push((pitch, gate));
+    }
+    // Control values are replayed after a reset, also in block processing.
+    sequencer.reset();
+    let mut left = [0.0; 100];
+    let mut right = [0.0; 100];
+    for block in 0..10 {
+        sequencer.process(100, &[], &mut [&mut left, &mut right]);
+        for i in 0..100 {
+            assert!((left[i], right[i]) == ticked[block * 100 + i]);
+        }
+    }
+
+    let mut humanized = groove.clone();
+    humanized.set_humanize(0.01, 0.2);
+    for step in 0..100 {
+        assert!(abs(humanized.step_time(step) - groove.step_time(step)) <= 0.01);
+        let velocity = humanized.step_velocity(step) / groove.step_velocity(step);
+        assert!((0.8..=1.2).contains(&velocity));
+        assert!(humanized.step_time(step) == humanized.clone().step_time(step));
+    }
+
     let mut net = Net64::new(0, 2);
     let id = net.push(Box::new(
         noise() >> moog_hz(1500.0, 0.8) | noise() >> moog_hz(500.0, 0.4),
     ));
     net.connect_output(id, 0, 0);
     net.connect_output(id, 1, 1);
-    net.check();
+    net.check().unwrap();
     check_wave(net);
 
     let mut net = Net64::new(0, 2);
     net.chain(Box::new(noise() | noise()));
     net.chain(Box::new(moog_hz(1500.0, 0.5) | moog_hz(1000.0, 0.6)));
     net.chain(Box::new(lowpole_hz(1000.0) | lowpole_hz(500.0)));
-    net.check();
+    net.check().unwrap();
     check_wave(net);
 
     let mut net = Net64::new(0, 2);
     net.chain(Box::new(noise()));
     net.chain(Box::new(lowpole_hz(1000.0) ^ lowpole_hz(500.0)));
     net.chain(Box::new(lowpole_hz(1000.0) | lowpole_hz(500.0)));
-    net.check();
+    net.check().unwrap();
+    check_wave(net);
+
+    // Inputs sourced from crossed ports, several nodes and zeros.
+    let mut net = Net64::new(0, 2);
+    let a = net.push(Box::new(noise() | pink()));
+    let b = net.push(Box::new(brown()));
+    let c = net.push(Box::new(lowpole_hz(1000.0) | highpole_hz(500.0) | add(1.0)));
+    net.connect(a, 1, c, 0);
+    net.connect(b, 0, c, 1);
+    let d = net.push(Box::new(join::<U3>() | pass()));
+    net.connect(c, 2, d, 0);
+    net.connect(c, 1, d, 1);
+    net.connect(a, 0, d, 2);
+    net.connect(c, 0, d, 3);
+    net.connect_output(d, 0, 0);
+    net.connect_output(d, 1, 1);
+    net.check().unwrap();
     check_wave(net);
 
     check_wave((noise() | envelope(|t| spline_noise(1, t * 10.0))) >> panner());
 
+    // Crossfaded commits render identically with tick and process.
+    let mut front1 = Net64::new(0, 1);
+    let id1 = front1.chain(Box::new(sine_hz(110.0)));
+    let mut back1 = front1.backend();
+    let mut front2 = front1.clone();
+    let mut back2 = front2.backend();
+    front1.replace(id1, Box::new(saw_hz(220.0)));
+    front1.commit_fade(Fade::Power, 0.01);
+    front2.replace(id1, Box::new(saw_hz(220.0)));
+    front2.commit_fade(Fade::Power, 0.01);
+    let mut buffer = [0.0; MAX_BUFFER_SIZE];
+    let mut reference = saw_hz(220.0);
+    for block in 0..1280 / MAX_BUFFER_SIZE {
+        back2.process(MAX_BUFFER_SIZE, &[], &mut [&mut buffer[..]]);
+        for (i, y) in buffer.iter().enumerate() {
+            let x = back1.get_mono();
+            assert!(abs(x - y) < 1.0e-9);
+            // After the crossfade, the new version plays alone.
+            let s = reference.get_mono();
+            if block * MAX_BUFFER_SIZE + i > 441 {
+                assert!(x == s);
+            }
+        }
+    }
+
+    // Scheduled commits switch sample accurately, also in the middle of a block.
+    let mut front = Net64::new(0, 1);
+    let id = front.chain(Box::new(dc(1.0)));
+    let mut back = front.backend();
+    back.set_sample_rate(1000.0);
+    front.replace(id, Box::new(dc(2.0)));
+    assert!(front.commit_at(0.1));
+    // An immediate commit made later is held until the scheduled version has taken effect.
+    front.replace(id, Box::new(dc(3.0)));
+    front.commit();
+    let mut buffer = [0.0; MAX_BUFFER_SIZE];
+    for block in 0..4 {
+        back.process(MAX_BUFFER_SIZE, &[], &mut [&mut buffer[..]]);
+        for (i, y) in buffer.iter().enumerate() {
+            let t = block * MAX_BUFFER_SIZE + i;
+            assert!(*y == if t < 100 { 1.0 } else { 3.0 });
+        }
+    }
+    assert!(back.time() == 4.0 * MAX_BUFFER_SIZE as f64 / 1000.0);
+    // Pending scheduled commits are limited. Further commits are rejected until they take effect.
+    for i in 0..MAX_SCHEDULED {
+        assert!(front.commit_at(0.5 + i as f64 / 1000.0));
+    }
+    assert!(!front.commit_at(0.6));
+    for _ in 0..5 {
+        back.process(MAX_BUFFER_SIZE, &[], &mut [&mut buffer[..]]);
+    }
+    assert!(front.commit_at(0.6));
+
+    // Blocks split at a scheduled offset match processing one sample at a time.
+    let mut front1 = Net64::new(1, 1);
+    let id1 = front1.chain(Box::new(lowpole_hz(100.0)));
+    let mut back1 = front1.backend();
+    let mut front2 = Net64::new(1, 1);
+    let id2 = front2.chain(Box::new(lowpole_hz(100.0)));
+    let mut back2 = front2.backend();
+    front1.replace(id1, Box::new(highpole_hz(100.0)));
+    assert!(front1.commit_at(0.01));
+    front2.replace(id2, Box::new(highpole_hz(100.0)));
+    assert!(front2.commit_at(0.01));
+    let mut input = [0.0; MAX_BUFFER_SIZE];
+    let mut output = [0.0; MAX_BUFFER_SIZE];
+    for block in 0..10 {
+        for (i, x) in input.iter_mut().enumerate() {
+            *x = sin((block * MAX_BUFFER_SIZE + i) as f64 * 0.1);
+        }
+        back1.process(MAX_BUFFER_SIZE, &[&input[..]], &mut [&mut output[..]]);
+        for (x, y) in input.iter().zip(output.iter()) {
+            assert!(abs(back2.filter_mono(*x) - y) < 1.0e-12);
+        }
+    }
+
+    // Throughput is measured with both processing paths, leaving the unit reset.
+    let mut unit = noise() >> lowpole_hz(1000.0);
+    let first = unit.get_mono();
+    let throughput = throughput(&mut unit);
+    assert!(throughput.tick > 0.0 && throughput.process > 0.0);
+    assert!(unit.get_mono() == first);
+
+    // Block processing shares buffer arena slots between units whose outputs are not needed at the same time.
+    let mut net = Net64::new(0, 2);
+    let a = net.push(Box::new(noise()));
+    let b = net.push(Box::new(lowpole_hz(1000.0)));
+    let c = net.push(Box::new(highpole_hz(100.0)));
+    let d = net.push(Box::new(lowpole_hz(2000.0)));
+    let e = net.push(Box::new(pass() + pass()));
+    let f = net.push(Box::new(mul(0.5)));
+    net.connect(a, 0, b, 0);
+    net.connect(b, 0, c, 0);
+    net.connect(b, 0, d, 0);
+    net.connect(c, 0, e, 0);
+    net.connect_with_gain(d, 0, e, 1, 0.5);
+    net.connect(e, 0, f, 0);
+    net.connect_output(f, 0, 0);
+    net.connect_output(b, 0, 1);
+    net.check().unwrap();
+    check_wave(net.clone());
+    // Edge gains can be changed after the order has been determined.
+    net.set_edge_gain(c, 0, 2.0);
+    check_wave(net);
+
+    // Units are ordered after all of their sources, regardless of the order of edges.
+    let mut net = Net64::new(0, 1);
+    let a = net.push(Box::new(dc(1.0)));
+    let t = net.push(Box::new(pass() + pass()));
+    let b = net.push(Box::new(pass()));
+    let c = net.push(Box::new(pass()));
+    net.connect(a, 0, t, 0);
+    net.connect(b, 0, t, 1);
+    net.connect(c, 0, b, 0);
+    net.connect(a, 0, c, 0);
+    net.connect_output(t, 0, 0);
+    net.check().unwrap();
+    assert!(net.get_mono() == 2.0);
+
+    // Cloned units and networks carry over their state and are independent afterwards.
+    let mut prototype: Box<dyn AudioUnit64> = Box::new(noise() >> lowpole_hz(1000.0));
+    let mut net = Net64::new(0, 1);
+    net.chain(Box::new(noise() >> resonator_hz(1000.0, 100.0)));
+    for _ in 0..100 {
+        prototype.get_mono();
+        net.get_mono();
+    }
+    let mut voice = prototype.clone_unit();
+    let mut net_copy = net.clone();
+    for _ in 0..100 {
+        assert!(voice.get_mono() == prototype.get_mono());
+        assert!(net_copy.get_mono() == net.get_mono());
+    }
+    voice.reset();
+    assert!(voice.get_mono() != prototype.get_mono());
+    // Clones are muted by the watchdog independently of the original.
+    net_copy.set_watchdog(0.0, 1);
+    let mut output = [0.0; 8];
+    net_copy.process(8, &[], &mut [&mut output]);
+    assert!(net_copy.muted_nodes().len() == 1 && net.muted_nodes().is_empty());
+    assert!(net.clone().muted_nodes().is_empty() && net_copy.clone().muted_nodes().len() == 1);
+
+    // Optimization fuses gains and offsets and removes identity nodes.
+    let mut net = Net64::wrap(Box::new(mul(2.0))) >> add(1.0) >> pass() >> mul(-0.5);
+    let mut reference = net.clone();
+    net.optimize();
+    net.check().unwrap();
+    assert!(net.size() == 1);
+    for _ in 0..100 {
+        let x = rnd.f64() - 0.5;
+        assert!(abs(net.filter_mono(x) - reference.filter_mono(x)) < 1.0e-12);
+    }
+    // Constants are folded, but monitors keep monitoring.
+    let level = shared(0.0);
+    let mut net = Net64::wrap(Box::new(dc(0.5))) >> monitor(&level, Meter::Sample) >> mul(2.0);
+    net.optimize();
+    net.check().unwrap();
+    assert!(net.size() == 3);
+    assert!(net.get_mono() == 1.0 && level.value() == 0.5);
+    assert!(Net64::wrap(Box::new(monitor(&level, Meter::Sample) >> mul(2.0))).has_side_effects());
+    let mut net = Net64::wrap(Box::new(dc((1.0, 2.0)))) >> (mul(2.0) | mul(3.0)) >> join::<U2>();
+    net.optimize();
+    assert!(net.size() == 1);
+    assert!(net.get_mono() == 4.0);
+
+    // Units that reach no output are skipped, except for units with side effects.
+    let level = shared(0.0);
+    let mut net = Net64::new(0, 1);
+    let clock = net.push(Box::new(lfo(|t| t)));
+    let source = net.push(Box::new(dc(0.25)));
+    let meter = net.push(Box::new(monitor(&level, Meter::Sample)));
+    net.connect(source, 0, meter, 0);
+    for _ in 0..100 {
+        assert!(net.get_mono() == 0.0);
+    }
+    assert!(level.value() == 0.25);
+    net.connect_output(clock, 0, 0);
+    assert!(net.get_mono() == 0.0);
+    assert!(net.get_mono() > 0.0);
+
+    // Tracing records settings, resets and network commits in order.
+    let (mut events, tracer) = Trace::new(16);
+    let traced = trace(&tracer, fader());
+    let id = traced.0.id();
+    let (sender, mut node) = listen(traced);
+    sender.try_send(-6.0).unwrap();
+    assert!(node.filter_mono(1.0) < 1.0);
+    node.reset();
+    assert!(
+        events.get().map(|event| (event.node, event.kind)) == Some((Some(id), TraceKind::Setting))
+    );
+    assert!(
+        events.get().map(|event| (event.node, event.kind)) == Some((Some(id), TraceKind::Reset))
+    );
+    let mut net = Net64::new(0, 1);
+    net.set_tracer(&tracer);
+    let mut backend = net.backend();
+    net.chain(Box::new(dc(1.0)));
+    net.commit();
+    assert!(backend.get_mono() == 1.0);
+    let mut kinds = vec![];
+    while let Some(event) = events.get() {
+        kinds.push(event.kind);
+    }
+    assert!(kinds == [TraceKind::Order, TraceKind::Commit]);
+    assert!(events.dropped() == 0);
+
+    // Level metering keeps per-edge levels of the last block, also in the backend.
+    let mut net = Net64::new(1, 1);
+    net.set_metering(true);
+    let mut backend = net.backend();
+    let id = net.chain(Box::new(mul(0.5)));
+    net.commit();
+    let input = [1.0, -1.0, 1.0, -1.0];
+    let mut output = [0.0; 4];
+    backend.process(4, &[&input], &mut [&mut output]);
+    assert!(
+        net.input_level(0)
+            == SignalLevel {
+                peak: 1.0,
+                rms: 1.0
+            }
+    );
+    assert!(
+        net.output_level(id, 0)
+            == SignalLevel {
+                peak: 0.5,
+                rms: 0.5
+            }
+    );
+    assert!(
+        net.level_snapshot()[&id]
+            == [SignalLevel {
+                peak: 0.5,
+                rms: 0.5
+            }]
+    );
+    backend.tick(&[0.0], &mut output[..1]);
+    assert!(net.output_level(id, 0) == SignalLevel::default());
+    net.set_metering(false);
+    assert!(net.level_snapshot().is_empty());
+
+    // The watchdog mutes the most expensive unit after repeated overruns and reports it.
+    let mut net = Net64::new(0, 1);
+    net.chain(Box::new(dc(1.0)));
+    net.chain(Box::new(mul(2.0)));
+    let (mut events, tracer) = Trace::new(16);
+    net.set_tracer(&tracer);
+    net.set_watchdog(1.0e9, 1);
+    let mut backend = net.backend();
+    let mut output = [0.0; 8];
+    backend.process(8, &[], &mut [&mut output]);
+    assert!(output[0] == 2.0 && net.muted_nodes().is_empty());
+    net.set_watchdog(0.0, 2);
+    net.commit();
+    backend.process(8, &[], &mut [&mut output]);
+    assert!(net.muted_nodes().is_empty());
+    backend.process(8, &[], &mut [&mut output]);
+    let muted = net.muted_nodes();
+    assert!(muted.len() == 1 && net.is_muted(muted[0]));
+    backend.process(8, &[], &mut [&mut output]);
+    assert!(output == [0.0; 8]);
+    let mut reported = vec![];
+    while let Some(event) = events.get() {
+        if event.kind == TraceKind::Mute {
+            reported.push(event.node.unwrap());
+        }
+    }
+    assert!(reported == muted);
+    net.clear_watchdog();
+    net.unmute(muted[0]);
+    net.commit();
+    backend.process(8, &[], &mut [&mut output]);
+    assert!(output == [2.0; 8] && net.muted_nodes().is_empty());
+    // Reusing the vertex of a removed unit does not unmute the unit in the backend.
+    let mut net = Net64::with_capacity(0, 1, 4);
+    net.reserve(1, 0, 1);
+    let id = net.chain(Box::new(dc(1.0)));
+    let mut backend = net.backend();
+    net.set_watchdog(0.0, 1);
+    net.commit();
+    backend.process(8, &[], &mut [&mut output]);
+    assert!(net.is_muted(id));
+    net.remove(id);
+    let id = net.chain(Box::new(dc(2.0)));
+    assert!(!net.is_muted(id));
+    backend.process(8, &[], &mut [&mut output]);
+    assert!(output == [0.0; 8]);
+
+    // Removing units from a live network keeps the IDs of remaining units valid.
+    let mut net = Net64::new(0, 1);
+    let id1 = net.chain(Box::new(dc(1.0)));
+    let id2 = net.chain(Box::new(add(1.0)));
+    let id3 = net.chain(Box::new(mul(2.0)));
+    let mut backend = net.backend();
+    assert!(backend.get_mono() == 4.0);
+    net.remove_link(id2);
+    net.commit();
+    assert!(backend.get_mono() == 2.0);
+    net.replace(id3, Box::new(mul(3.0)));
+    net.commit();
+    assert!(backend.get_mono() == 3.0);
+    net.remove(id1);
+    net.commit();
+    assert!(net.size() == 1 && backend.get_mono() == 0.0);
+    net.check().unwrap();
+
+    // Cycles are reported as errors and the network outputs silence until they are fixed.
+    let mut net = Net64::new(0, 1);
+    let id1 = net.push(Box::new(add(1.0)));
+    let id2 = net.push(Box::new(mul(0.5)));
+    net.connect(id1, 0, id2, 0);
+    net.connect(id2, 0, id1, 0);
+    net.connect_output(id2, 0, 0);
+    assert!(net.check() == Err(NetError::Cycle));
+    let mut backend = net.backend();
+    assert!(net.error() == Some(NetError::Cycle));
+    let mut output = [1.0; 8];
+    backend.process(8, &[], &mut [&mut output]);
+    assert!(output == [0.0; 8]);
+    net.disconnect(id1, 0);
+    net.commit();
+    assert!(net.check().is_ok() && net.error().is_none());
+    assert!(backend.get_mono() == 0.5);
+
+    // Edge gains scale signals in both processing modes and survive combination and optimization.
+    let mut net = Net64::new(0, 1);
+    let id1 = net.push(Box::new(noise()));
+    let id2 = net.push(Box::new(pass() + pass()));
+    let id3 = net.push(Box::new(pass()));
+    net.connect_with_gain(id1, 0, id2, 0, 0.5);
+    net.connect_with_gain(id1, 0, id2, 1, -0.25);
+    net.connect(id2, 0, id3, 0);
+    net.connect_output(id3, 0, 0);
+    assert!(net.edge_gain(id2, 1) == -0.25);
+    let mut reference = noise() * 0.25;
+    let mut net2 = net.clone();
+    check_wave(net.clone() | net.clone());
+    for _ in 0..1000 {
+        assert!(abs(net.get_mono() - reference.get_mono()) < 1.0e-12);
+    }
+    net2.set_edge_gain(id3, 0, 2.0);
+    net2.optimize();
+    assert!(net2.size() == 2);
+    let mut reference = noise() * 0.5;
+    for _ in 0..1000 {
+        assert!(abs(net2.get_mono() - reference.get_mono()) < 1.0e-12);
+    }
+    let mut net3 = Net64::new(1, 1);
+    let id = net3.chain(Box::new(pass() * 2.0));
+    net3.set_edge_gain(id, 0, -3.0);
+    let mut net3 = Net64::wrap(Box::new(dc(1.0))) >> net3;
+    assert!(net3.get_mono() == -6.0);
+
+    // Bypass and mute ramp smoothly.
+    let mut net = Net64::new(1, 1);
+    let id = net.chain(Box::new(mul(2.0)));
+    net.set_bypass(id, true);
+    assert!(net.bypass(id) && !net.mute(id));
+    let mut previous = 2.0;
+    for _ in 0..1000 {
+        let y = net.filter_mono(1.0);
+        assert!(y <= previous && previous - y < 0.01);
+        previous = y;
+    }
+    assert!(previous == 1.0);
+    net.set_mute(id, true);
+    let mut buffer = [[1.0; 64]];
+    let mut output = [[0.0; 64]];
+    for _ in 0..10 {
+        net.process(64, &[&buffer[0]], &mut [&mut output[0]]);
+    }
+    assert!(output[0].iter().all(|&y| y == 0.0));
+    net.set_bypass(id, false);
+    net.set_mute(id, false);
+    buffer[0].fill(0.5);
+    for _ in 0..10 {
+        net.process(64, &[&buffer[0]], &mut [&mut output[0]]);
+    }
+    assert!(output[0].iter().all(|&y| y == 1.0));
+
+    // Global inputs and outputs can be added and removed.
+    let mut net = Net64::new(2, 1);
+    let id = net.push(Box::new(pass() + pass()));
+    net.pipe_input(id);
+    net.pipe_output(id);
+    net.set_outputs(2);
+    net.connect_output(id, 0, 1);
+    assert!(net.outputs() == 2);
+    let mut output = [0.0; 2];
+    net.tick(&[1.0, 2.0], &mut output);
+    assert!(output == [3.0, 3.0]);
+    net.set_inputs(1);
+    net.set_outputs(1);
+    net.tick(&[1.0], &mut output[..1]);
+    assert!(net.inputs() == 1 && output[0] == 1.0);
+    net.check().unwrap();
+    // In a network with a backend, the change takes effect with the next commit, without crossfading.
+    let mut net = Net64::new(1, 1);
+    let id = net.chain(Box::new(mul(2.0)));
+    let mut backend = net.backend();
+    net.set_outputs(2);
+    net.connect_output(id, 0, 1);
+    net.commit_fade(Fade::Smooth, 0.1);
+    assert!(backend.outputs() == 1);
+    backend.tick(&[1.0], &mut output);
+    assert!(backend.outputs() == 2 && output == [2.0, 2.0]);
+    net.set_inputs(0);
+    net.commit();
+    let mut left = [1.0; 8];
+    let mut right = [1.0; 8];
+    backend.process(8, &[], &mut [&mut left, &mut right]);
+    assert!(backend.inputs() == 0 && left == [0.0; 8] && right == [0.0; 8]);
+
+    // Networks can be built generically over sample type.
+    fn gain_net<T: UnitFloat>(gain: T) -> Net<T> {
+        let mut net = Net::<T>::new(1, 1);
+        net.chain(T::boxed(
+            fundsp::prelude::pass::<T>() * fundsp::prelude::dc(gain),
+        ));
+        assert!(net.size() == 1 && net.inputs() == 1 && net.outputs() == 1);
+        net
+    }
+    let mut output64 = [0.0];
+    gain_net(2.0f64).tick(&[3.0], &mut output64);
+    let mut output32 = [0.0];
+    gain_net(2.0f32).tick(&[3.0], &mut output32);
+    assert!(output64[0] == 6.0 && output32[0] == 6.0);
+
+    // Networks combine with boxed units.
+    let unit: Box<dyn AudioUnit64> = Box::new(dc(2.0));
+    let mut net4 = unit >> Net64::wrap(Box::new(mul(3.0)));
+    assert!(net4.get_mono() == 6.0);
+    let unit: Box<dyn AudioUnit64> = Box::new(dc(1.0));
+    let mut net4 = (net4 + unit) * Net64::from(Box::new(dc(0.5)) as Box<dyn AudioUnit64>);
+    assert!(net4.get_mono() == 3.5);
+    let unit: Box<dyn AudioUnit64> = Box::new(dc(4.0));
+    let net4 = net4 | unit;
+    assert!(net4.inputs() == 0 && net4.outputs() == 2);
+
+    // Nodes can be looked up by name, also in combined networks.
+    let mut net = Net64::new(0, 1);
+    let id1 = net.chain(Box::new(sine_hz(110.0)));
+    let id2 = net.chain(Box::new(lowpass_hz(1000.0, 1.0)));
+    net.set_name(id1, "osc");
+    net.set_name(id2, "filter");
+    assert!(net.node_by_name("osc") == Some(id1) && net.node_by_name("amp").is_none());
+    let mut net = net >> mul(0.5);
+    assert!(net.node_by_name("filter") == Some(id2) && net.name(id1) == Some("osc"));
+    net.clear_name(id2);
+    assert!(net.node_by_name("filter").is_none());
+    net.remove(id1);
+    assert!(net.node_by_name("osc").is_none());
+
+    // Flattened subnetworks behave the same as nested ones.
+    let mut inner = Net64::new(2, 1);
+    let id1 = inner.push(Box::new(pass() - pass()));
+    inner.pipe_input(id1);
+    inner.set_edge_gain(id1, 1, 0.5);
+    inner.pipe_output(id1);
+    inner.set_name(id1, "diff");
+    let inner = inner >> lowpass_hz(1000.0, 1.0);
+    let mut net = Net64::new(0, 2);
+    let id2 = net.push(Box::new(sine_hz(110.0) | sine_hz(330.0)));
+    let id3 = net.push(Box::new(inner));
+    let id4 = net.push(Box::new(pass()));
+    net.pipe(id2, id3);
+    net.connect_with_gain(id3, 0, id4, 0, -1.0);
+    net.connect_output(id3, 0, 0);
+    net.connect_output(id4, 0, 1);
+    let mut net2 = net.clone();
+    net2.flatten();
+    assert!(net.size() == 3 && net2.size() == 4);
+    assert!(net2.node_by_name("diff") == Some(id1));
+    net2.check().unwrap();
+    for _ in 0..1000 {
+        let (x0, x1) = net.get_stereo();
+        let (y0, y1) = net2.get_stereo();
+        assert!(x0 == y0 && x1 == y1 && x0 == -x1);
+    }
+    check_wave(net2);
+
+    // Edit commands reuse reserved vertices.
+    let mut net = Net64::with_capacity(0, 1, 4);
+    net.reserve(2, 0, 1);
+    let id1 = NodeId::new();
+    let id2 = NodeId::new();
+    net.apply(NetEdit64::Push(id1, Box::new(dc(1.0))));
+    net.apply(NetEdit64::Push(id2, Box::new(dc(2.0))));
+    net.apply(NetEdit64::ConnectOutput(id2, 0, 0));
+    assert!(net.get_mono() == 2.0);
+    assert!(net.apply(NetEdit64::Remove(id2)).is_some());
+    assert!(net.get_mono() == 0.0);
+    let id3 = NodeId::new();
+    net.apply(NetEdit64::Push(id3, Box::new(pass())));
+    net.apply(NetEdit64::Connect(id1, 0, id3, 0));
+    net.apply(NetEdit64::SetEdgeGain(id3, 0, 3.0));
+    net.apply(NetEdit64::ConnectOutput(id3, 0, 0));
+    assert!(net.get_mono() == 3.0);
+    assert!(net
+        .apply(NetEdit64::Replace(id1, Box::new(dc(-1.0))))
+        .is_some());
+    assert!(net.get_mono() == -3.0);
+    let id4 = NodeId::new();
+    net.apply(NetEdit64::Push(id4, Box::new(dc(4.0))));
+    net.apply(NetEdit64::ConnectOutput(id4, 0, 0));
+    assert!(net.get_mono() == 4.0);
+    net.apply(NetEdit64::DisconnectOutput(0));
+    assert!(net.get_mono() == 0.0 && net.size() == 3);
+    net.check().unwrap();
+
+    // Parallel processing renders identically to serial processing.
+    let mut net = Net64::new(1, 2);
+    let voices: Vec<NodeId> = (0..8)
+        .map(|i| {
+            let id = net.push(Box::new(
+                (pass() + sine_hz(100.0 * (i + 1) as f64)) >> lowpass_hz(1000.0, 1.0),
+            ));
+            net.connect_input(0, id, 0);
+            id
+        })
+        .collect();
+    let mix = net.push(Box::new(pass() + pass() + pass() + pass()));
+    for i in 0..4 {
+        let bus = net.push(Box::new(pass() + pass() + pass() + pass()));
+        for j in 0..4 {
+            net.connect(voices[(i * 4 + j) % 8], 0, bus, j);
+        }
+        net.connect(bus, 0, mix, i);
+    }
+    net.connect_output(mix, 0, 0);
+    net.connect_output(voices[0], 0, 1);
+    net.check().unwrap();
+    let mut net2 = net.clone();
+    net2.set_parallel(true);
+    assert!(net2.is_parallel() && !net.is_parallel());
+    let input = Wave64::render(44100.0, 0.1, &mut noise());
+    let serial = input.filter(0.1, &mut net);
+    let parallel = input.filter(0.1, &mut net2);
+    for channel in 0..2 {
+        for i in 0..serial.length() {
+            assert!(serial.at(channel, i) == parallel.at(channel, i));
+        }
+    }
+
+    // Feedback edges close cycles with a delay of one sample.
+    let mut net = Net64::new(0, 1);
+    let id1 = net.push(Box::new(add(1.0)));
+    let id2 = net.push(Box::new(mul(0.5)));
+    net.connect(id1, 0, id2, 0);
+    net.connect_feedback(id2, 0, id1, 0);
+    net.connect_output(id1, 0, 0);
+    assert!(net.check().is_ok());
+    let mut net2 = net.clone();
+    assert!(net.get_mono() == 1.0);
+    assert!(net.get_mono() == 1.5);
+    assert!(net.get_mono() == 1.75);
+    let mut output = [0.0; 3];
+    net2.process(3, &[], &mut [&mut output]);
+    assert!(output == [1.0, 1.5, 1.75]);
+    net.reset();
+    assert!(net.get_mono() == 1.0);
+    let mut net = Net64::new(0, 1);
+    let id = net.push(Box::new(add(1.0)));
+    net.connect_feedback(id, 0, id, 0);
+    net.connect_output(id, 0, 0);
+    assert!(net.check().is_ok());
+    assert!(net.get_mono() == 1.0 && net.get_mono() == 2.0);
+
+    // Port kinds are declared by nodes and propagated through combinators and networks.
+    assert!((pass() | lowpass()).input_kind(3) == PortKind::Q);
+    assert!((sine() >> pass()).input_kind(0) == PortKind::Frequency);
+    assert!((sine() | moog()).input_kind(3) == PortKind::Q);
+    let trigger = port_kind(PortKind::Trigger, impulse_train(4.0));
+    assert!((trigger.clone() | dc(1.0)).output_kind(0) == PortKind::Trigger);
+    let mut net = Net64::new(0, 1);
+    let id1 = net.push(Box::new(trigger));
+    let id2 = net.push(Box::new(sine()));
+    let id3 = net.push(Box::new(burst(0.005, 0.7)));
+    net.connect(id1, 0, id2, 0);
+    net.connect(id1, 0, id3, 0);
+    net.connect_output(id2, 0, 0);
+    assert!(net.port_mismatch(id1, 0, id3, 0).is_none());
+    let mismatches = net.port_mismatches();
+    assert!(mismatches.len() == 1);
+    assert!(mismatches[0].target == id2 && mismatches[0].target_kind == PortKind::Frequency);
+
+    // Voice pools sum independent voices.
+    check_wave(voices(3, Box::new(noise() >> lowpole_hz(1000.0) | pink())));
+    let mut pool = voices(3, Box::new(mul(2.0)));
+    assert!(pool.inputs() == 3 && pool.outputs() == 1);
+    let mut output = [0.0];
+    pool.tick(&[1.0, 2.0, 3.0], &mut output);
+    assert!(output[0] == 12.0);
+    let mut pool = voices(2, Box::new(noise()));
+    let mut single = noise();
+    assert!((0..100).any(|_| pool.get_mono() != 2.0 * single.get_mono()));
+
     // Wave filtering, tick vs. process rendering, node reseting.
     let input = Wave64::render(44100.0, 1.0, &mut (noise() | noise()));
     check_wave_filter(&input, butterpass_hz(1000.0) | lowpole_hz(100.0));
@@ -258,6 +993,362 @@ fn test_basic() {
         &input,
         resonator_hz(440.0, 110.0) | resonator_hz(880.0, 110.0),
     );
+    check_wave_filter(&input, spectral_delay(0.1) | spectral_delay(-0.2));
+    check_wave_filter(&input, ensemble(3, 0.003, 0.6) | ensemble(6, 0.001, 2.0));
+
+    // Unit conversion nodes convert control signals and propagate constants.
+    check_wave_filter(&input, db_to_amp() | bpm_to_hz(0.5));
+    assert!((dc(69.0) >> midi_to_hz()).get_mono() == 440.0);
+    assert!((dc(440.0) >> hz_to_midi()).get_mono() == 69.0);
+    assert!(((dc(-6.0) >> db_to_amp() >> amp_to_db()).get_mono() + 6.0).abs() < 1.0e-12);
+    assert!((dc(120.0) >> bpm_to_hz(0.25)).get_mono() == 2.0);
+    let mut converter = dc(69.0) >> midi_to_hz();
+    assert!(
+        matches!(converter.route(&new_signal_frame(0), 1.0)[0], Signal::Value(x) if x == 440.0)
+    );
+    assert!(converter.output_kind(0) == PortKind::Frequency);
+
+    // Tunings map keys to frequencies from Scala scales and keyboard mappings.
+    let tuning = Tuning::default();
+    assert!((0..128).all(|key| (tuning.hz(key as f64) - midi_hz(key as f64)).abs() < 1.0e-9));
+    assert!((tuning.hz(60.5) - midi_hz(60.5)).abs() < 1.0e-9);
+    assert!(Tuning::from_scl("Broken\n2\n9/8\n").is_err());
+    let scl =
+        "! just.scl\n!\nJust major\n 7\n 9/8\n 5/4\n 4/3\n 701.955 cents\n 5/3\n 15/8\n 2/1\n";
+    let mut tuning = Tuning::from_scl(scl).unwrap();
+    // Map white keys to the scale with A_4 at 440 Hz.
+    let kbm = "! white.kbm\n12\n0\n127\n60\n69\n440.0\n7\n0\nx\n1\nx\n2\n3\nx\n4\nx\n5\nx\n6\n";
+    tuning.set_kbm(kbm).unwrap();
+    assert!((tuning.hz(69.0) - 440.0).abs() < 1.0e-9);
+    assert!((tuning.hz(60.0) - 264.0).abs() < 1.0e-9);
+    assert!((tuning.hz(64.0) - 330.0).abs() < 1.0e-9);
+    assert!((tuning.hz(67.0) - 396.0).abs() < 1.0e-3);
+    assert!((tuning.hz(48.0) - 132.0).abs() < 1.0e-9);
+    assert!(tuning.hz(61.0) == 0.0);
+    let tuning = std::sync::Arc::new(tuning);
+    assert!((dc(64.0) >> midi_to_hz_tuned(&tuning)).get_mono() == tuning.hz(64.0));
+    let mut sequencer = Sequencer64::new(false, 1);
+    sequencer.push_note(
+        0.0,
+        1.0,
+        Fade::Smooth,
+        0.0,
+        0.0,
+        &tuning,
+        72.0,
+        Box::new(pass()),
+    );
+    assert!((sequencer.get_mono() - 528.0).abs() < 1.0e-9);
+
+    // Exponential envelope reaches full level at the end of the attack and retriggers smoothly.
+    let gate = shared(1.0);
+    let done = shared(0.0);
+    let mut adsr = var(&gate) >> adsr_exp_done(0.01, 0.1, 0.5, 0.1, &done);
+    let attack: Vec<f64> = (0..442).map(|_| adsr.get_mono()).collect();
+    let peak = attack.iter().position(|&x| x == 1.0).unwrap();
+    assert!((439..=441).contains(&peak) && attack[..peak].windows(2).all(|x| x[0] < x[1]));
+    for _ in 0..4410 {
+        adsr.get_mono();
+    }
+    assert!((adsr.get_mono() - 0.5).abs() < 0.5e-3);
+    gate.set_value(0.0);
+    for _ in 0..441 {
+        adsr.get_mono();
+    }
+    let level = adsr.get_mono();
+    assert!(level > 0.0 && level < 0.5);
+    gate.set_value(1.0);
+    let retriggered = adsr.get_mono();
+    assert!(retriggered > level && retriggered - level < 0.01);
+    gate.set_value(0.0);
+    for _ in 0..4410 {
+        adsr.get_mono();
+    }
+    assert!(adsr.get_mono() == 0.0 && done.value() == 1.0);
+
+    // Envelopes signal completion when their release has finished.
+    let gate = shared(1.0);
+    let done = shared(0.0);
+    let mut adsr = var(&gate) >> adsr_live_done(0.01, 0.01, 0.5, 0.02, &done);
+    for _ in 0..4410 {
+        adsr.get_mono();
+    }
+    assert!(done.value() == 0.0);
+    gate.set_value(0.0);
+    for _ in 0..441 {
+        adsr.get_mono();
+    }
+    assert!(done.value() == 0.0);
+    for _ in 0..882 {
+        adsr.get_mono();
+    }
+    assert!(done.value() == 1.0 && adsr.get_mono() == 0.0);
+    gate.set_value(1.0);
+    for _ in 0..441 {
+        adsr.get_mono();
+    }
+    assert!(done.value() == 0.0);
+
+    // Envelopes that signal completion keep running in a network when their output is unused.
+    assert!(!adsr_live(0.01, 0.01, 0.5, 0.02).has_side_effects());
+    assert!(!adsr_exp(0.01, 0.01, 0.5, 0.02).has_side_effects());
+    let gate = shared(1.0);
+    let done = shared(0.5);
+    let done_exp = shared(0.5);
+    let mut net = Net64::new(0, 1);
+    net.push(Box::new(
+        var(&gate) >> adsr_live_done(0.01, 0.01, 0.5, 0.02, &done),
+    ));
+    net.push(Box::new(
+        var(&gate) >> adsr_exp_done(0.01, 0.01, 0.5, 0.02, &done_exp),
+    ));
+    for _ in 0..441 {
+        assert!(net.get_mono() == 0.0);
+    }
+    assert!(done.value() == 0.0 && done_exp.value() == 0.0);
+    gate.set_value(0.0);
+    for _ in 0..44100 {
+        net.get_mono();
+    }
+    assert!(done.value() == 1.0 && done_exp.value() == 1.0);
+    check_wave_filter(&input, denoise(20.0) >> split::<U2>());
+    check_wave_filter(&input, suboctave(0.5, 0.5) | suboctave(1.0, 0.0));
+    check_wave_filter(
+        &input,
+        (multipass::<U2>() | dc((1000.0, 0.5))) >> per_channel::<U2, _, _>(|_| moog()),
+    );
+    check_wave_filter(
+        &input,
+        echo(0.1, 0.5, lowpole_hz(2000.0)) | echo(0.2, 0.7, pass()),
+    );
+
+    // Echo repeats decay geometrically with an identity insert.
+    let mut e = echo(10.0 / DEFAULT_SR, 0.5, pass());
+    for i in 0..40 {
+        let y = e.filter_mono(if i == 0 { 1.0 } else { 0.0 });
+        let expected = if i > 0 && i % 10 == 0 {
+            0.5f64.powi(i / 10 - 1)
+        } else {
+            0.0
+        };
+        assert!(y == expected);
+    }
+
+    // Spectral resynthesis is transparent apart from latency.
+    let mut freeze = (pass() | dc((0.0, 0.0))) >> spectral_freeze();
+    let mut spectral = spectral_delay(0.0);
+    for i in 0..input.length() {
+        let x = freeze.filter_mono(input.at(0, i));
+        let y = spectral.filter_mono(input.at(0, i));
+        if i >= 2048 {
+            assert!((x - input.at(0, i - 2048)).abs() < 1.0e-9);
+            assert!((y - input.at(0, i - 2048)).abs() < 1.0e-9);
+        }
+    }
+
+    // Wave edits join parts with crossfades that overlap the seams.
+    let ones = Wave64::render(44100.0, 0.1, &mut dc(1.0));
+    let twos = Wave64::render(44100.0, 0.1, &mut dc(2.0));
+    let edit = ones.insert(2205, &twos, 0.0);
+    assert!(edit.length() == 8820 && edit.at(0, 2204) == 1.0 && edit.at(0, 2205) == 2.0);
+    assert!(edit.at(0, 6615) == 1.0);
+    let edit = ones.splice(4410, &twos, 0.001);
+    assert!(edit.length() == 4410 + 4410 - 44);
+    for i in 0..edit.length() {
+        let x = edit.at(0, i);
+        assert!(x > 0.7 && x < 2.0 * 1.4143);
+    }
+    assert!(edit.at(0, 4365) == 1.0 && edit.at(0, 4410) == 2.0);
+    let edit = ones.delete(0, 2205, 0.01);
+    assert!(edit.length() == 2205 && edit.at(0, 0) == 1.0);
+    let edit = twos.delete(100, 4410, 0.0).delete(50, 100, 0.0);
+    assert!(edit.length() == 50);
+
+    // Stems are split from a single render.
+    let mut graph = noise() | sine_hz(110.0) | noise() | saw_hz(220.0);
+    let mut graph2 = graph.clone();
+    let stems = Wave64::render_stems(44100.0, 0.5, &mut graph, &[1, 3]);
+    let mixdown = Wave64::render(44100.0, 0.5, &mut graph2);
+    assert!(stems.len() == 2 && stems[0].channels() == 1 && stems[1].channels() == 3);
+    for i in 0..mixdown.length() {
+        assert!(stems[0].at(0, i) == mixdown.at(0, i));
+        assert!(stems[1].at(2, i) == mixdown.at(3, i));
+    }
+
+    // Dither quantizes to whole steps with an error of at most a couple of steps.
+    let mut dithered = sine_hz(110.0) * 0.5 >> dither(8, NoiseShaping::Flat);
+    let mut reference = sine_hz(110.0) * 0.5;
+    for _ in 0..1000 {
+        let x = dithered.get_mono() * 127.0;
+        assert!(x == round(x) && abs(x - reference.get_mono() * 127.0) <= 2.0);
+    }
+    check_wave(
+        (noise() >> dither(16, NoiseShaping::SecondOrder))
+            | (noise() >> dither(16, NoiseShaping::Lipshitz)),
+    );
+    let mut bytes16 = Vec::new();
+    let mut bytes24 = Vec::new();
+    mixdown
+        .write_wav_dither(&mut bytes16, 16, NoiseShaping::Lipshitz)
+        .unwrap();
+    mixdown.write_wav24(&mut bytes24).unwrap();
+    assert!(bytes16.len() == 44 + 2 * 4 * mixdown.length());
+    assert!(bytes24.len() == 44 + 3 * 4 * mixdown.length());
+
+    // Sinusoidal analysis finds the components of a two tone signal.
+    let wave = Wave64::render(
+        44100.0,
+        1.0,
+        &mut (sine_hz(440.0) * 0.5 | sine_hz(1000.0) * 0.25),
+    );
+    let model = wave.analyze_partials(0, 10);
+    assert!(model.partials.len() == 1);
+    let (f, a) = model.partials[0].at(model.frames as f64 * 0.5);
+    assert!((f - 440.0).abs() < 1.0 && (a - 0.5).abs() < 0.05);
+    let model = std::sync::Arc::new(wave.analyze_partials(1, 10));
+    check_wave_filter(
+        &input,
+        (mul(0.0) + 1.0 | mul(0.0) + 2.0) >> resynth_partials(&model) >> split::<U2>(),
+    );
+
+    // Paulstretch stretches waves offline and in streaming playback alike.
+    let stretched = wave.paulstretch(4.0, PAULSTRETCH_WINDOW);
+    assert!(stretched.channels() == 2 && stretched.length() == 4 * 44100);
+    let mut player = paulstretch(&wave, 0, 4.0);
+    let mut power = 0.0;
+    for i in 0..stretched.length() {
+        let x = stretched.at(0, i);
+        assert!(x.is_finite() && (player.get_mono() - x).abs() < 1.0e-9);
+        if (44100..3 * 44100).contains(&i) {
+            power += x * x / (2.0 * 44100.0);
+        }
+    }
+    assert!((power - 0.125).abs() < 0.0125);
+    check_wave(paulstretch(&wave, 0, 2.0) | paulstretch(&wave, 1, 8.0));
+
+    // Wave player loop modes.
+    let ramp: Vec<f64> = (0..10).map(|i| i as f64).collect();
+    let ramp = std::sync::Arc::new(Wave64::from_samples(44100.0, &ramp));
+    let mut player = wave64_at(&ramp, 0, 0, 8, Some(4));
+    let played: Vec<f64> = (0..12).map(|_| player.get_mono()).collect();
+    assert!(played == [0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 4.0, 5.0, 6.0, 7.0]);
+    let mut player = wave64_loop(&ramp, 0, 0, 8, 4, LoopMode::Forward(2));
+    let played: Vec<f64> = (0..12).map(|_| player.get_mono()).collect();
+    assert!(played == [0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 5.0, 4.0, 4.0, 5.0, 5.0, 4.0]);
+    let mut player = wave64_loop(&ramp, 0, 0, 8, 4, LoopMode::PingPong);
+    let played: Vec<f64> = (0..12).map(|_| player.get_mono()).collect();
+    assert!(played == [0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 6.0, 5.0, 4.0, 5.0]);
+    let mut player = wave64_loop(&ramp, 0, 0, 10, 2, LoopMode::Release(6));
+    let mut played: Vec<f64> = (0..8).map(|_| player.get_mono()).collect();
+    player.set(false);
+    played.extend((0..6).map(|_| player.get_mono()));
+    assert!(played == [0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 2.0, 3.0, 6.0, 7.0, 8.0, 9.0, 0.0, 0.0]);
+    player.reset();
+    assert!(player.get_mono() == 0.0 && player.0.loop_mode() == LoopMode::Release(6));
+
+    // Triggered wave player restarts on rising edges and declicks retriggered sounds.
+    let ramp: Vec<f64> = (1..=10).map(|i| i as f64).collect();
+    let ramp = std::sync::Arc::new(Wave64::from_samples(44100.0, &ramp));
+    let mut player = wave64_trigger(&ramp, 0, 0.0, 0.0);
+    let played: Vec<f64> = [0.0, 1.0, 1.0, 0.0, 1.0, 1.0]
+        .iter()
+        .map(|&x| player.filter_mono(x))
+        .collect();
+    assert!(played == [0.0, 1.0, 2.0, 3.0, 1.0, 2.0]);
+    let mut player = wave64_trigger(&ramp, 0, 0.0, 2.0 / 44100.0);
+    let played: Vec<f64> = [1.0, 1.0, -1.0, 1.0, 1.0, 1.0]
+        .iter()
+        .map(|&x| player.filter_mono(x))
+        .collect();
+    assert!(played == [1.0, 2.0, 3.0, 1.0 + 4.0 * 2.0 / 3.0, 2.0 + 5.0 / 3.0, 3.0]);
+    let mut player = wave64_trigger(&ramp, 0, 5.0 / 44100.0, 0.0);
+    let mut starts = vec![];
+    for _ in 0..100 {
+        starts.push(player.filter_mono(1.0));
+        player.filter_mono(0.0);
+    }
+    assert!(starts.iter().all(|&x| (1.0..=6.0).contains(&x)));
+    assert!(starts.iter().any(|&x| x != starts[0]));
+
+    // Disk streaming plays back the whole file while keeping only a sliding window in memory.
+    // Samples that were not available in time are played back as silence and reported.
+    let path = std::env::temp_dir().join("fundsp_disk_stream.wav");
+    let stereo = Wave64::render(44100.0, 1.0, &mut (noise() | noise()));
+    stereo.save_wav32(&path).unwrap();
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let stream = runtime.block_on(DiskStream64::open(&path, 0.1)).unwrap();
+    let mut player = An(DiskStream64Player::<f64>::new(&stream));
+    // Clones do not consume the stream: they play back silence.
+    let mut copy = player.clone();
+    assert!(copy.0.is_end_reached() && copy.get_stereo() == (0.0, 0.0));
+    let (mut index, mut skipped) = (0, 0);
+    while !player.0.is_end_reached() {
+        let window = stream.blocking_read();
+        assert!(window.available() <= window.capacity());
+        drop(window);
+        let (x, y) = player.get_stereo();
+        if index < stereo.length()
+            && x == stereo.at(0, index) as f32 as f64
+            && y == stereo.at(1, index) as f32 as f64
+        {
+            index += 1;
+        } else if index < stereo.length() {
+            assert!(x == 0.0 && y == 0.0);
+            skipped += 1;
+        }
+    }
+    assert!(index == stereo.length());
+    assert!(stream.blocking_read().underruns() == skipped);
+    std::fs::remove_file(&path).unwrap();
+
+    // Recording taps capture signals from inside a graph.
+    let mut stem = SharedWave::new(2, 44100.0, 1.0);
+    let mut graph = (noise() | noise()) >> tap_record_stereo(&stem) >> (pass() | mul(0.5));
+    let rendered = Wave64::render(44100.0, 0.5, &mut graph);
+    stem.flush();
+    graph.get_stereo();
+    stem.update();
+    assert!(stem.wave().length() == 22051 && stem.dropped() == 0);
+    for i in 0..22050 {
+        assert!(stem.wave().at(0, i) == rendered.at(0, i));
+        assert!(stem.wave().at(1, i) * 0.5 == rendered.at(1, i));
+    }
+    // Frames are dropped if the shared wave is not updated often enough.
+    let mut stem = SharedWave::new(1, 44100.0, 0.0);
+    let mut graph = noise() >> tap_record(&stem);
+    for _ in 0..3000 {
+        graph.get_mono();
+    }
+    drop(graph);
+    stem.update();
+    assert!(stem.wave().length() == 1024 && stem.dropped() == 3000 - 1024);
+
+    // Punch recording replaces material between punch points with crossfades.
+    let mut stem = SharedWave::new(1, 44100.0, 1.0);
+    let mut graph = tap_record(&stem);
+    for _ in 0..300 {
+        graph.filter_mono(1.0);
+    }
+    stem.arm();
+    stem.set_crossfade(10.0 / 44100.0);
+    stem.punch_in(100);
+    stem.punch_out(200);
+    graph.reset();
+    for _ in 0..250 {
+        graph.filter_mono(2.0);
+    }
+    assert!(stem.time() == 250);
+    drop(graph);
+    stem.update();
+    assert!(stem.wave().length() == 300);
+    for i in 0..300 {
+        let x = stem.wave().at(0, i);
+        match i {
+            100..=108 | 200..=208 => assert!(x > 1.0 && x < 2.0),
+            109..=199 => assert!(x == 2.0),
+            _ => assert!(x == 1.0),
+        }
+    }
 
     // Constants.
     let mut d = constant(1.0);
@@ -303,6 +1394,642 @@ fn test_basic() {
         &mut (add(z) & mul(y) >> sub(x) & add(w) >> mul(y) & sub(x) >> add(z))
     ));
 
+    // Test per channel processing vs. explicit routing of the shared parameter.
+    assert!(is_equal(
+        &mut rnd,
+        &mut per_channel::<U2, _, _>(|i| pass() * pass() + i as f64),
+        &mut map(|i: &Frame<f64, U3>| (i[0] * i[2], i[1] * i[2] + 1.0))
+    ));
+
+    // Multiband processing with identity bands matches the bare crossover.
+    assert!(is_equal(
+        &mut rnd,
+        &mut parallel_bands::<U3, _, _, _, _>(
+            crossover(&[200.0, 2000.0]),
+            |_| pass(),
+            recombine::<U3>()
+        ),
+        &mut (crossover::<U3>(&[200.0, 2000.0]) >> recombine::<U3>())
+    ));
+    assert!(is_equal(
+        &mut rnd,
+        &mut parallel_bands::<U2, _, _, _, _>(split::<U2>(), |i| mul(i as f64 + 1.0), join::<U2>()),
+        &mut mul(1.5)
+    ));
+
+    // Impulses fall on the nearest sample.
+    let mut train = impulse_train(100.0);
+    for i in 0..2000 {
+        assert!(train.get_mono() == if i % 441 == 0 { 1.0 } else { 0.0 });
+    }
+    // Noise bursts start at rising gate edges and last their duration.
+    let mut exciter = burst(0.01, 0.5);
+    exciter.set(0.5);
+    for i in 0..2000 {
+        let gate = if i >= 100 && i < 1000 { 1.0 } else { 0.0 };
+        let y = exciter.filter_mono(gate);
+        if i < 100 {
+            assert!(y == 0.0);
+        } else if i >= 100 + 441 * 3 / 2 {
+            // The longest possible burst with variability 0.5 is 1.5 times the duration.
+            assert!(y == 0.0);
+        }
+        assert!(abs(y) < 10.0);
+    }
+    assert!(exciter.filter_mono(1.0) != 0.0);
+
+    // Quarter note delay at 120 BPM is half a second.
+    let mut d = delay_sync(0.25);
+    let mut impulse = 1.0;
+    for i in 0..22051 {
+        let y = d.tick(&Frame::from([impulse, 120.0]))[0];
+        assert!(y == if i == 22050 { 1.0 } else { 0.0 });
+        impulse = 0.0;
+    }
+    // A tempo change crossfades between two heads that both read a constant signal.
+    d.reset();
+    for _ in 0..22100 {
+        d.tick(&Frame::from([1.0, 120.0]));
+    }
+    for _ in 0..10000 {
+        let y = d.tick(&Frame::from([1.0, 240.0]))[0];
+        assert!(abs(y - 1.0) < 1.0e-6);
+    }
+    // Changing the division changes the delay time.
+    d.set(0.125);
+    d.reset();
+    let mut impulse = 1.0;
+    for i in 0..11026 {
+        let y = d.tick(&Frame::from([impulse, 120.0]))[0];
+        assert!(y == if i == 11025 { 1.0 } else { 0.0 });
+        impulse = 0.0;
+    }
+
+    // All tap interpolators delay a ramp exactly.
+    for interpolation in [
+        Interpolation::Cubic,
+        Interpolation::Lagrange,
+        Interpolation::Thiran,
+    ] {
+        let mut t = tap_interpolated(0.0, 0.001, interpolation);
+        assert!(t.interpolation() == interpolation);
+        for i in 0..100 {
+            let y = t.tick(&Frame::from([i as f64, 2.5 / DEFAULT_SR]))[0];
+            assert!(i < 50 || abs(y - (i as f64 - 2.5)) < 1.0e-6);
+        }
+    }
+
+    // Feedback matrices preserve energy.
+    for matrix in [
+        FrameMatrix::<U8, f64>::hadamard(),
+        FrameMatrix::householder(),
+        FrameMatrix::orthogonal(1),
+    ] {
+        let x = Frame::<f64, U8>::generate(|_| rnd.f64() - 0.5);
+        let y = matrix.unop(&x);
+        let energy = |x: &Frame<f64, U8>| x.iter().map(|x| x * x).sum::<f64>();
+        assert!(abs(energy(&y) - energy(&x)) < 1.0e-12);
+    }
+    // Feedback delay networks with explicit delays decay through damping.
+    let mut f = fdn_matrix::<U4, _, _>(
+        &[
+            100.0 / DEFAULT_SR,
+            150.0 / DEFAULT_SR,
+            200.0 / DEFAULT_SR,
+            250.0 / DEFAULT_SR,
+        ],
+        FrameMatrix::householder(),
+        |_| pass() * 0.5,
+    );
+    for i in 0..44100 {
+        let x = if i == 0 { 1.0 } else { 0.0 };
+        let y = f.tick(&Frame::from([x, 0.0, 0.0, 0.0]));
+        assert!(i > 100 || y[0] == if i == 100 { 0.5 } else { 0.0 });
+        assert!(i < 40000 || abs(y[0]) < 1.0e-6);
+    }
+
+    // Settings of parameterized nodes match freshly constructed nodes.
+    let mut s = shape(Shape::Clip);
+    s.set(Shape::Tanh(2.0));
+    assert!(is_equal(&mut rnd, &mut s, &mut shape(Shape::Tanh(2.0))));
+    let mut s = shape_mod(Shape::Clip);
+    s.set(Shape::Crush(4.0));
+    assert!(is_equal(
+        &mut rnd,
+        &mut s,
+        &mut shape_mod(Shape::Crush(4.0))
+    ));
+    let mut c = chorus(1, 0.015, 0.005, 0.5);
+    c.set((0.01, 0.002, 0.3));
+    assert!(is_equal(&mut rnd, &mut c, &mut chorus(1, 0.01, 0.002, 0.3)));
+    let mut e = ensemble(3, 0.003, 0.6);
+    e.set((0.002, 0.4));
+    assert!(is_equal(&mut rnd, &mut e, &mut ensemble(3, 0.002, 0.4)));
+    let mut p = pluck(440.0, 0.5, 0.5);
+    p.set((0.8, 0.2));
+    assert!(is_equal(&mut rnd, &mut p, &mut pluck(440.0, 0.8, 0.2)));
+    let mut a = agc(-10.0, 20.0, 3.0);
+    a.set((-20.0, 10.0, 6.0));
+    assert!(is_equal(&mut rnd, &mut a, &mut agc(-20.0, 10.0, 6.0)));
+    let mut r = reverb_stereo(10.0, 2.0);
+    r.set((0.002, 1.0));
+    let mut r2 = An(Reverb::new(10.0, 2.0, 0.002, 1.0, ReverbQuality::High));
+    for _ in 0..10000 {
+        let x = rnd.f64() - 0.5;
+        assert!(r.filter_stereo(x, x) == r2.filter_stereo(x, x));
+    }
+    // Ensemble output converges to a constant input without overshooting it.
+    let mut e = ensemble(3, 0.003, 0.6);
+    for _ in 0..44100 {
+        let y = e.filter_mono(1.0);
+        assert!(y >= 0.0 && y <= 1.0 + 1.0e-12);
+    }
+    assert!(abs(e.filter_mono(1.0) - 1.0) < 1.0e-6);
+    // The number of voices and the modulation depth both change the wet signal.
+    let mut three = ensemble(3, 0.003, 0.6);
+    let mut six = ensemble(6, 0.003, 0.6);
+    let mut still = ensemble(3, 0.0, 0.6);
+    let (mut energy, mut dry_diff, mut voices_diff, mut depth_diff) = (0.0, 0.0, 0.0, 0.0);
+    for _ in 0..44100 {
+        let x = rnd.f64() - 0.5;
+        let y = three.filter_mono(x);
+        assert!(abs(y) <= 0.5 + 1.0e-12);
+        energy += squared(x);
+        dry_diff += squared(y - x);
+        voices_diff += squared(y - six.filter_mono(x));
+        depth_diff += squared(y - still.filter_mono(x));
+    }
+    assert!(dry_diff > 0.01 * energy);
+    assert!(voices_diff > 0.001 * energy);
+    assert!(depth_diff > 0.001 * energy);
+    // Exciter and bass enhancer are transparent at zero amount.
+    let mut e = exciter(0.0, 3000.0);
+    let mut b = bass_enhance(0.0);
+    for _ in 0..10000 {
+        let x = rnd.f64() - 0.5;
+        assert!(e.filter_mono(x) == x && b.filter_mono(x) == x);
+    }
+    // The bass enhancer adds the second and third harmonics of a low sine.
+    let mut source = sine_hz(50.0);
+    let mut b = bass_enhance(0.5);
+    let (dry, wet): (Vec<f64>, Vec<f64>) = (0..88200)
+        .map(|_| {
+            let x = source.get_mono();
+            (x, b.filter_mono(x))
+        })
+        .skip(44100)
+        .unzip();
+    for harmonic in [100.0, 150.0] {
+        assert!(amplitude_at(&dry, harmonic) < 1.0e-4);
+        assert!(amplitude_at(&wet, harmonic) > 0.01);
+    }
+    // Noise reduction attenuates noise by the reduction amount once the profile has been learned,
+    // either while the learn input is positive or automatically below the gate level.
+    let mut learned = denoise(20.0);
+    let mut gated = denoise(20.0);
+    gated.set_gate(-40.0);
+    let (mut energy, mut learned_energy, mut gated_energy) = (0.0, 0.0, 0.0);
+    for i in 0..3 * 44100 {
+        let x = (rnd.f64() - 0.5) * 0.02;
+        let learn = if i < 44100 { 1.0 } else { 0.0 };
+        let y = learned.tick(&Frame::from([x, learn]))[0];
+        let z = gated.tick(&Frame::from([x, 0.0]))[0];
+        if i >= 2 * 44100 {
+            energy += squared(x);
+            learned_energy += squared(y);
+            gated_energy += squared(z);
+        }
+    }
+    for reduced in [learned_energy, gated_energy] {
+        let reduction_db = -amp_db(sqrt(reduced / energy));
+        assert!(reduction_db > 17.0 && reduction_db < 23.0);
+    }
+    assert!(!learned.profile().is_empty() && learned.profile().iter().all(|&x| x > 0.0));
+    // The sub-octave generator adds layers at half and a quarter of the input frequency.
+    let layers = |mix1: f64, mix2: f64| {
+        let mut source = sine_hz(220.0);
+        let mut sub = suboctave(mix1, mix2);
+        let wave: Vec<f64> = (0..88200)
+            .map(|_| sub.filter_mono(source.get_mono()))
+            .skip(44100)
+            .collect();
+        (
+            amplitude_at(&wave, 220.0),
+            amplitude_at(&wave, 110.0),
+            amplitude_at(&wave, 55.0),
+        )
+    };
+    let (a, b, c) = layers(0.0, 0.0);
+    assert!(abs(a - 1.0) < 1.0e-3 && b < 1.0e-3 && c < 1.0e-3);
+    let (a, b, c) = layers(1.0, 0.0);
+    assert!(abs(a - 1.0) < 0.05 && b > 0.8 && c < 0.02);
+    let (a, b, c) = layers(0.0, 1.0);
+    assert!(abs(a - 1.0) < 0.05 && b < 0.02 && c > 0.8);
+    let (_, b, c) = layers(0.7, 0.3);
+    assert!(abs(b / c - 0.7 / 0.3) < 0.2);
+    // A mono input does not collapse into a mono reverb.
+    let (energy, difference) = (0..10000)
+        .map(|_| {
+            let x = rnd.f64() - 0.5;
+            r.filter_stereo(x, x)
+        })
+        .fold((0.0, 0.0), |(e, d), (x, y)| {
+            (e + x * x, d + (x - y) * (x - y))
+        });
+    assert!(difference > 0.1 * energy);
+    // Reverb quality tiers are interchangeable.
+    let (mut slot, mut backend) = Slot64::new(Box::new(reverb_stereo_quality(
+        10.0,
+        1.0,
+        ReverbQuality::High,
+    )));
+    for quality in [ReverbQuality::Low, ReverbQuality::Medium] {
+        slot.set(
+            Fade::Smooth,
+            0.01,
+            Box::new(reverb_stereo_quality(10.0, 1.0, quality)),
+        );
+        let energy: f64 = (0..20000)
+            .map(|i| {
+                let x = if i == 0 { 1.0 } else { 0.0 };
+                let (y, z) = backend.filter_stereo(x, x);
+                y * y + z * z
+            })
+            .sum();
+        assert!(energy > 0.0 && energy < 10.0);
+    }
+    // Decayed tails are cleared and processing is skipped until new input arrives.
+    let mut f = feedback(delay(0.01) * 0.5);
+    f.0.set_decay_tracking(1.0e-6, 0.02);
+    assert!(f.filter_mono(1.0) == 0.0);
+    let mut tail = 0;
+    while !f.0.is_quiescent() {
+        f.filter_mono(0.0);
+        tail += 1;
+        assert!(tail < 44100);
+    }
+    assert!(f.filter_mono(0.0) == 0.0);
+    f.filter_mono(1.0);
+    assert!(!f.0.is_quiescent());
+    let mut r = reverb_stereo(10.0, 1.0);
+    r.0.set_decay_tracking(1.0e-6);
+    r.filter_stereo(1.0, 1.0);
+    let mut tail = 0;
+    while !r.0.is_quiescent() {
+        r.filter_stereo(0.0, 0.0);
+        tail += 1;
+        assert!(tail < 44100 * 20);
+    }
+    assert!(r.filter_stereo(0.0, 0.0) == (0.0, 0.0));
+    // Tilt filters have the specified slope.
+    for slope in [-6.0, -3.0, 0.0, 1.5, 3.0, 6.0] {
+        let mut t = tilt(slope);
+        assert!(t.response_db(0, 1000.0).unwrap().abs() < 1.0e-6);
+        for f in [62.5, 125.0, 250.0, 2000.0, 4000.0] {
+            let db = slope * log2(f / 1000.0);
+            assert!((t.response_db(0, f).unwrap() - db).abs() < 0.5);
+        }
+    }
+    let mut w = pinkpass() >> whitepass();
+    for f in [50.0, 100.0, 1000.0, 5000.0, 10000.0] {
+        assert!(w.response_db(0, f).unwrap().abs() < 0.5);
+    }
+    // Correction filters flatten measured responses.
+    let csv = "Freq(Hz),SPL(dB),Phase(degrees)\n20,80,0\n1500,80,0\n2000,86,0\n3000,86,0\n4000,80,0\n20000,80,0\n";
+    let measurement = Measurement::from_text(csv).unwrap();
+    assert!(measurement.points().len() == 6);
+    assert!(measurement.level_db(2500.0) == 86.0);
+    let mut c = correction_fir(&measurement, 2047);
+    assert!((c.response_db(0, 2500.0).unwrap() + 5.4).abs() < 0.5);
+    assert!((c.response_db(0, 200.0).unwrap() + 0.6).abs() < 0.3);
+    assert!(Measurement::from_text("Freq,SPL\n").is_err());
+    assert!(Measurement::from_text("100,abc\n").is_err());
+    // Probes tap internal signals in both tick and block processing.
+    let mut net = Net64::new(1, 1);
+    let id1 = net.chain(Box::new(mul(2.0)));
+    net.chain(Box::new(add(1.0)));
+    let mut probe = net.probe(id1, 0, 1024);
+    for i in 0..MAX_BUFFER_SIZE {
+        assert!(net.filter_mono(i as f64) == i as f64 * 2.0 + 1.0);
+    }
+    let input = [1.0; MAX_BUFFER_SIZE];
+    let mut output = [0.0; MAX_BUFFER_SIZE];
+    net.process(MAX_BUFFER_SIZE, &[&input[..]], &mut [&mut output[..]]);
+    probe.update();
+    assert!(probe.total() == 2 * MAX_BUFFER_SIZE as u64);
+    assert!(probe.at(0) == 2.0);
+    assert!(probe.at(MAX_BUFFER_SIZE) == (MAX_BUFFER_SIZE - 1) as f64 * 2.0);
+    net.remove_probes(id1);
+    net.filter_mono(0.0);
+    // DOT export lists units and edges.
+    let mut net = Net64::new(1, 1);
+    let id1 = net.push(Box::new(lowpass_hz(1000.0, 1.0)));
+    let id2 = net.push(Box::new(pass() + pass()));
+    net.set_name(id1, "filter");
+    net.connect_input(0, id1, 0);
+    net.connect(id1, 0, id2, 0);
+    net.connect_feedback(id2, 0, id2, 1);
+    net.set_edge_gain(id2, 1, 0.5);
+    net.connect_output(id2, 0, 0);
+    let dot = net.to_dot();
+    assert!(dot.contains("n0 [label=\"{{<i0> 0}|filter|{<o0> 0}}\"];"));
+    assert!(dot.contains("input:o0 -> n0:i0;"));
+    assert!(dot.contains("n0:o0 -> n1:i0;"));
+    assert!(dot.contains("n1:o0 -> n1:i1 [style=dashed, label=\"0.5\"];"));
+    assert!(dot.contains("n1:o0 -> output:i0;"));
+    // Latency compensation aligns a dry path with a look-ahead limiter.
+    let mut net = Net64::new(1, 2);
+    let id1 = net.push(Box::new(limiter(0.01)));
+    net.connect_input(0, id1, 0);
+    net.connect_output(id1, 0, 0);
+    net.pass_through(0, 1);
+    assert!(net.compensate_latency() == 1);
+    assert!(net.compensate_latency() == 0);
+    let samples = limiter(0.01).latency().unwrap() as usize;
+    let mut output = [0.0; 2];
+    net.tick(&[0.5], &mut output);
+    for _ in 1..samples {
+        net.tick(&[0.0], &mut output);
+        assert!(output[1] == 0.0);
+    }
+    net.tick(&[0.0], &mut output);
+    assert!(output[1] == 0.5);
+    let mut l = limiter((0.01, 0.1));
+    l.set(0.5);
+    for _ in 0..4410 {
+        assert!(abs(l.filter_mono(1.0)) <= 0.5 + 1.0e-9);
+    }
+
+    // Square wave tremolo at full depth gates the signal on and off.
+    let mut t = tremolo(100.0, 1.0, LfoShape::Square);
+    for i in 0..882 {
+        let y = t.filter_mono(1.0);
+        let phase = (i % 441) as f64 / 441.0;
+        assert!(
+            y == if phase < 0.25 || phase >= 0.75 {
+                1.0
+            } else {
+                0.0
+            }
+        );
+    }
+    // Opposite phases in the stereo tremolo complement each other.
+    let mut t = tremolo_stereo(3.0, 1.0, LfoShape::Triangle, 0.5);
+    for _ in 0..44100 {
+        let (l, r) = t.filter_stereo(1.0, 1.0);
+        assert!(abs(l + r - 1.0) < 1.0e-6);
+    }
+    // A quarter note at 120 BPM lasts 0.5 seconds.
+    let mut t = tremolo_sync(0.25, 1.0, LfoShape::Saw);
+    let mut previous = 0.0;
+    for i in 0..44100 {
+        let y = t.tick(&Frame::from([1.0, 120.0]))[0];
+        if i > 0 && y < previous {
+            // The phase wraps around at the end of each quarter note.
+            assert!(i % 22050 <= 1 || i % 22050 == 22049);
+        }
+        previous = y;
+    }
+    // Auto-panning preserves power.
+    let mut a = autopan(1.0, 1.0, LfoShape::Sine);
+    for _ in 0..44100 {
+        let output = a.tick(&Frame::from([1.0]));
+        assert!(abs(output[0] * output[0] + output[1] * output[1] - 1.0) < 1.0e-9);
+    }
+    let mut a = autopan_sync(0.5, 1.0, LfoShape::Square);
+    for i in 0..88200 {
+        let output = a.tick(&Frame::from([1.0, 120.0]));
+        let phase = (i % 44100) as f64 / 44100.0;
+        assert!((output[0] < output[1]) == (phase < 0.25 || phase >= 0.75));
+    }
+
+    // Haas effect without delay or level change is a plain mono split.
+    let mut h = haas(0.0, 0.0);
+    for _ in 0..1000 {
+        let x = rnd.f64() - 0.5;
+        let output = h.tick(&Frame::from([x]));
+        assert!(output[0] == x && abs(output[1] - x) < 1.0e-12);
+    }
+    assert!(abs(h.0.mono_compatibility() - 1.0) < 1.0e-9);
+    // High frequencies are delayed in the right channel, bass is not.
+    let mut h = haas(10.0, 0.0);
+    assert!(h.response(0, 1000.0).unwrap().re == 1.0);
+    assert!(abs(h.response(1, 10.0).unwrap().norm() - 1.0) < 0.05);
+    let mut impulse = 1.0;
+    let mut peak = (0, 0.0);
+    for i in 0..1000 {
+        let output = h.tick(&Frame::from([impulse]));
+        if i > 0 && output[1] > peak.1 {
+            peak = (i, output[1]);
+        }
+        impulse = 0.0;
+    }
+    assert!(peak.0 == 441);
+    // Noise placed with a delay is partly decorrelated.
+    let mut h = noise() >> haas(-20.0, -3.0);
+    for _ in 0..44100 {
+        h.tick(&Frame::default());
+    }
+    let compatibility = h.tick(&Frame::default())[2];
+    assert!(compatibility > 0.4 && compatibility < 0.9);
+
+    // Downmix uses ITU coefficients and upmix places channels in their counterparts.
+    let h = std::f64::consts::FRAC_1_SQRT_2;
+    let mut d = downmix::<U6, U1>();
+    let output = d.tick(&Frame::from([1.0, 2.0, 3.0, 4.0, 5.0, 6.0]));
+    assert!(abs(output[0] - (h * 3.0 + 3.0 + 0.5 * 11.0)) < 1.0e-12);
+    let mut d = downmix::<U8, U2>();
+    let output = d.tick(&Frame::from([1.0, 0.0, 0.0, 1.0, 1.0, 0.0, 1.0, 0.0]));
+    assert!(abs(output[0] - 2.0) < 1.0e-12 && output[1] == 0.0);
+    let mut u = upmix::<U2, U6>(Upmix::Direct);
+    assert!(u.tick(&Frame::from([1.0, 2.0])) == Frame::from([1.0, 2.0, 0.0, 0.0, 0.0, 0.0]));
+    let mut u = upmix::<U1, U6>(Upmix::Spread);
+    let output = u.tick(&Frame::from([1.0]));
+    assert!(
+        output[2] == 1.0 && output[3] == 0.0 && output[0] == h && abs(output[4] - 0.5) < 1.0e-12
+    );
+    check_wave(noise() | noise() >> upmix::<U1, U8>(Upmix::Spread) >> downmix::<U8, U1>());
+
+    // Channel layouts are declared by adapters and checked when piping.
+    let surround = channel_layout(ChannelLayout::Surround51, multisplit::<U1, U6>());
+    assert!(surround.output_layout() == Some(ChannelLayout::Surround51));
+    assert!(surround.input_layout() == Some(ChannelLayout::Mono));
+    assert!(
+        (surround.clone() >> downmix::<U6, U2>()).output_layout() == Some(ChannelLayout::Stereo)
+    );
+    let mut net = Net64::new(1, 2);
+    let id1 = net.push(Box::new(surround.clone()));
+    let id2 = net.push(Box::new(downmix::<U6, U2>()));
+    net.pipe_input(id1);
+    net.pipe(id1, id2);
+    net.pipe_output(id2);
+    assert!(net.layout_mismatch(id1, id2).is_none());
+    assert!(net.output_layout() == Some(ChannelLayout::Stereo));
+    let id3 = net.push(Box::new(channel_layout(
+        ChannelLayout::Ambisonic(1),
+        multipass::<U4>(),
+    )));
+    assert!(
+        net.layout_mismatch(id1, id3)
+            == Some((ChannelLayout::Surround51, ChannelLayout::Ambisonic(1)))
+    );
+    let stereo = Wave64::render(
+        44100.0,
+        0.1,
+        &mut (noise() >> upmix::<U1, U2>(Upmix::Direct)),
+    );
+    assert!(stereo.layout() == Some(ChannelLayout::Stereo));
+    let mono = stereo.filter(0.1, &mut downmix::<U2, U1>());
+    assert!(mono.layout() == Some(ChannelLayout::Mono));
+
+    // Tagged nodes can be found and modified inside composed graphs.
+    let mut voice = (tagged(1, dc(1.0)) >> pass()) * tagged(2, dc(2.0)) + tagged(1, dc(3.0));
+    check_wave(tagged(3, noise()) | tagged(3, sine_hz(110.0)));
+    assert!(voice.get_mono() == 5.0);
+    let mut count = 0;
+    voice.visit_tagged(1, |x: &mut Constant<U1, f64>| {
+        x.set(Frame::from([4.0]));
+        count += 1;
+    });
+    assert!(count == 2);
+    assert!(voice.get_mono() == 12.0);
+    // Nodes of other types are skipped.
+    voice.visit_tagged(2, |x: &mut Sine<f64>| x.reset());
+    assert!(voice.get_mono() == 12.0);
+    let mut count = 0;
+    voice.visit(2, &mut |_| count += 1);
+    assert!(count == 1);
+
+    // Frozen generators repeat their rendering until refreshed.
+    check_wave(freeze(noise() | sine_hz(110.0), 0.3));
+    let mut f = freeze(noise(), 0.01);
+    assert!(f.length() == 441);
+    let first: Vec<f64> = (0..441).map(|_| f.get_mono()).collect();
+    assert!((0..441).all(|i| f.get_mono() == first[i]));
+    f.refresh();
+    assert!((0..441).any(|i| f.get_mono() != first[i]));
+
+    // Control rate evaluation interpolates linearly between evaluations.
+    check_wave(control_rate(8, lfo(|t| sin_hz(10.0, t))) | control_rate(3, noise()));
+    let mut c = control_rate(4, lfo(|t| t * 44100.0 / 4.0));
+    for _ in 0..4 {
+        assert!(c.get_mono() == 0.0);
+    }
+    for i in 1..=20 {
+        assert!(abs(c.get_mono() - i as f64 * 0.25) < 1.0e-9);
+    }
+    let mut c = control_rate(2, pass() * 2.0);
+    assert!(c.filter_mono(1.0) == 2.0);
+    assert!(c.filter_mono(5.0) == 2.0);
+    assert!(c.filter_mono(3.0) == 4.0);
+    assert!(c.filter_mono(7.0) == 6.0);
+
+    // Dry/wet mix aligns the dry path with the latency of the wet node.
+    let mut m = mix(tick(), 0.25);
+    assert!(m.dry_latency() == 1);
+    assert!(m.filter_mono(1.0) == 0.0);
+    assert!(m.filter_mono(0.0) == 1.0);
+    assert!(m.filter_mono(0.0) == 0.0);
+    assert!(is_equal(
+        &mut rnd,
+        &mut mix(pass() * 2.0, 0.25),
+        &mut mul(1.25)
+    ));
+    let mut m = mix(limiter((0.01, 0.1)), 0.5);
+    assert!(m.dry_latency() == 441);
+    m.set_sample_rate(48000.0);
+    assert!(m.dry_latency() == 480);
+
+    // Fades are sample accurate and can be scheduled at runtime.
+    check_wave(fade_in(Fade::Power, 0.1, noise()) | fade_out_at(Fade::Smooth, 0.5, 0.2, noise()));
+    let mut f = fade_in(Fade::Linear, 0.001, dc(1.0));
+    for i in 0..44 {
+        assert!(abs(f.get_mono() - i as f64 / 44.0) < 1.0e-9);
+    }
+    assert!(f.get_mono() == 1.0);
+    f.set(FadeSetting::Out(0.01, 0.001));
+    for _ in 45..441 {
+        assert!(f.get_mono() == 1.0);
+    }
+    for i in 0..44 {
+        assert!(abs(f.get_mono() - (44 - i) as f64 / 44.0) < 1.0e-9);
+    }
+    assert!(f.get_mono() == 0.0);
+    f.set(FadeSetting::Cancel);
+    assert!(f.get_mono() == 1.0);
+
+    // Golden checksums are reproducible, depend on the seed and tolerate tiny deviations.
+    let mut unit = noise() >> lowpass_hz(1000.0, 1.0);
+    let checksum = golden_checksum(&mut unit, 1, 5000);
+    assert!(verify_golden(&mut unit.clone(), 1, 5000, checksum));
+    assert!(!verify_golden(&mut unit, 2, 5000, checksum));
+    assert!(!verify_golden(&mut unit, 1, 5001, checksum));
+    let mut filter = lowpass_hz(1000.0, 1.0);
+    let checksum = golden_checksum(&mut filter, 3, 5000);
+    assert!(!verify_golden(
+        &mut lowpass_hz(1100.0, 1.0),
+        3,
+        5000,
+        checksum
+    ));
+    let checksum = golden_checksum(&mut (sine_hz(440.0) * 1.0), 1, 1000);
+    assert!(verify_golden(
+        &mut (sine_hz(440.0) * (1.0 + 1.0e-12)),
+        1,
+        1000,
+        checksum
+    ));
+    let mut unit32 = fundsp::hacker32::pink();
+    let checksum = golden_checksum32(&mut unit32, 1, 1000);
+    assert!(verify_golden32(&mut unit32, 1, 1000, checksum));
+
+    // Wave analysis visits zero padded windows in order.
+    let ramp: Vec<f64> = (0..10).map(|i| i as f64).collect();
+    let wave = Wave64::from_samples(44100.0, &ramp);
+    let total = std::sync::atomic::AtomicUsize::new(0);
+    let features = wave.analyze_progress(
+        4,
+        3,
+        |window| window[0].to_vec(),
+        |analyzed, windows| {
+            assert!(analyzed <= windows && windows == 4);
+            total.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        },
+    );
+    assert!(total.into_inner() == 4);
+    assert!(
+        features
+            == [
+                [0.0, 1.0, 2.0, 3.0],
+                [3.0, 4.0, 5.0, 6.0],
+                [6.0, 7.0, 8.0, 9.0],
+                [9.0, 0.0, 0.0, 0.0]
+            ]
+    );
+    assert!(wave.analyze(16, 16, |window| vec![window.len() as f64]) == [[1.0]]);
+
+    // Effects rack edits take effect after the crossfade.
+    let (mut rack, mut rack_backend) = EffectsRack64::new(1);
+    assert!(rack_backend.filter_mono(3.0) == 3.0);
+    rack.set_fade(Fade::Smooth, 0.001);
+    rack.push(Box::new(mul(2.0)));
+    rack.push(Box::new(add(1.0)));
+    let settle = |backend: &mut SlotBackend64, x: f64| {
+        for _ in 0..100 {
+            backend.filter_mono(x);
+        }
+        backend.filter_mono(x)
+    };
+    assert!(settle(&mut rack_backend, 3.0) == 7.0);
+    rack.swap(0, 1);
+    assert!(settle(&mut rack_backend, 3.0) == 8.0);
+    rack.set_bypass(1, true);
+    assert!(rack.is_bypassed(1) && !rack.is_bypassed(0));
+    assert!(settle(&mut rack_backend, 3.0) == 4.0);
+    rack.move_to(1, 0);
+    rack.remove(1);
+    assert!(rack.len() == 1 && settle(&mut rack_backend, 3.0) == 3.0);
+
     // Nodes vs. networks.
     let mut pass_through = pass() | pass();
     let mut pass_through_net = Net64::new(2, 2);
@@ -313,7 +2040,7 @@ fn test_basic() {
         &mut pass_through,
         &mut pass_through_net
     ));
-    pass_through_net.check();
+    pass_through_net.check().unwrap();
 
     let mut swap_through = reverse::<U2>();
     let mut swap_through_net = Net64::new(2, 2);
@@ -324,7 +2051,7 @@ fn test_basic() {
         &mut swap_through,
         &mut swap_through_net
     ));
-    swap_through_net.check();
+    swap_through_net.check().unwrap();
 
     let mut multiply_2_3 = mul(2.0) | mul(3.0);
     let mut multiply_net = Net64::new(2, 2);
@@ -343,7 +2070,7 @@ fn test_basic() {
     ));
     multiply_net.remove(idd);
     multiply_net.remove(ide);
-    multiply_net.check();
+    multiply_net.check().unwrap();
     assert!(is_equal_unit(
         &mut rnd,
         &mut multiply_2_3,
@@ -360,7 +2087,7 @@ fn test_basic() {
     add_net.pipe(id0, id1);
     add_net.pipe_output(id1);
     assert!(is_equal_unit(&mut rnd, &mut add_2_3, &mut add_net));
-    add_net.check();
+    add_net.check().unwrap();
 
     // Test multichannel constants vs. stacked constants.
     assert!(is_equal(
@@ -389,6 +2116,40 @@ fn test_basic() {
         &mut (constant((w, x, y, z, w)))
     ));
 
+    // Test fast approximations against their documented error bounds.
+    for i in 0..=60000 {
+        let t = i as f64 / 1000.0 - 30.0;
+        assert!(abs(sin_fast(t) - sin(t)) < 5.0e-6);
+        assert!(abs(cos_fast(t) - cos(t)) < 5.0e-6);
+        assert!(abs(exp2_fast(t) / exp2(t) - 1.0) < 1.0e-8);
+        assert!(abs(exp_fast(t) / exp(t) - 1.0) < 1.0e-8);
+        assert!(abs(tanh_fast(t) - tanh(t)) < 1.0e-8);
+        assert!(abs(sin_fast(t as f32) - sin(t as f32)) < 1.0e-4);
+        assert!(abs(cos_fast(t as f32) - cos(t as f32)) < 1.0e-4);
+        assert!(abs(exp_fast(t as f32) / exp(t as f32) - 1.0) < 1.0e-5);
+        assert!(abs(tanh_fast(t as f32) - tanh(t as f32)) < 1.0e-6);
+    }
+    assert!(exp2_fast(3.0) == 8.0 && tanh_fast(0.0) == 0.0);
+
+    // Oscillators, waveshapers and envelopes stay within the error bounds
+    // whether or not the `fast-math` feature is enabled.
+    let mut osc = dc(440.0) >> An(Sine::with_phase(DEFAULT_SR, Some(0.0)));
+    for i in 1..=1000 {
+        let phase = (i * 440 % 44100) as f64 / 44100.0;
+        assert!(abs(osc.get_mono() - sin(phase * TAU)) < 1.0e-5);
+    }
+    let mut shaper = shape(Shape::Tanh(1.0));
+    for i in 0..=1000 {
+        let x = i as f64 / 100.0 - 5.0;
+        assert!(abs(shaper.filter_mono(x) - tanh(x)) < 1.0e-8);
+    }
+    // The attack of the exponential envelope charges toward 1.5 and reaches 1.0 after 10 ms.
+    let mut adsr = dc(1.0) >> adsr_exp(0.01, 0.1, 0.5, 0.1);
+    let coefficient = exp(-log(3.0) / (0.01 * DEFAULT_SR));
+    for i in 1..400 {
+        assert!(abs(adsr.get_mono() - 1.5 * (1.0 - pow(coefficient, i as f64))) < 1.0e-5);
+    }
+
     // Test sinks and zeros.
     assert!(is_equal(
         &mut rnd,
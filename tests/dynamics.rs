@@ -46,6 +46,120 @@ fn test_dynamics() {
         assert!(value >= 0.90 && value <= 1.00);
     }
 
+    // Test true peak limiter. A sine wave at a quarter of the sample rate
+    // sampled at 45 degree phase offset has inter-sample peaks 3 dB above its sample peaks.
+    let ceiling = db_amp(-1.0);
+    let mut x = limiter_true_peak((0.01, 0.1), -1.0);
+    let mut peak = 0.0;
+    for i in 0..48000 {
+        let y = x.filter_mono(2.0 * sin((i as f64 + 0.5) * PI * 0.5));
+        assert!(abs(y) <= ceiling);
+        peak = max(peak, abs(y));
+    }
+    // Samples of the limited sine are at 1/sqrt(2) of its true peak.
+    assert!(peak <= ceiling * 0.75 && peak >= ceiling * 0.6);
+
+    // Test modulated limiter. A constant ceiling input matches the fixed ceiling limiter.
+    let mut x = limiter_mod((0.01, 0.1));
+    let mut y = limiter((0.01, 0.1));
+    y.0.set_ceiling(db_amp(-6.0));
+    for i in 0..48000 {
+        let s = 2.0 * sin(i as f64 * TAU * 100.0 / 44100.0);
+        assert!(x.tick(&Frame::from([s, -6.0]))[0] == y.filter_mono(s));
+    }
+
+    // Test shaper with drive input. Drive 1 matches the plain shaper.
+    for mode in [
+        Shape::Tanh(2.0),
+        Shape::Softsign(0.5),
+        Shape::Crush(8.0),
+        Shape::SoftCrush(8.0),
+        Shape::AdaptiveTanh(0.01, 1.0),
+    ] {
+        let mut x = (pass() | dc(1.0)) >> shape_mod(mode.clone());
+        let mut y = shape(mode);
+        for i in 0..1000 {
+            let s = sin(i as f64 * 0.1);
+            assert!(abs(x.filter_mono(s) - y.filter_mono(s)) < 1.0e-12);
+        }
+    }
+    // Drive acts as input gain.
+    let mut x = shape_mod(Shape::Clip);
+    assert!(x.tick(&Frame::from([0.25, 2.0]))[0] == 0.5);
+    assert!(x.tick(&Frame::from([0.75, 2.0]))[0] == 1.0);
+    // The transfer curve reflects the most recent drive.
+    let mut curve = [0.0; 5];
+    x.0.transfer_curve(1.0, &mut curve);
+    assert!(x.0.drive() == 2.0);
+    assert!(curve == [-1.0, -1.0, 0.0, 1.0, 1.0]);
+    let x = shape(Shape::Crush(2.0));
+    x.0.transfer_curve(0.5, &mut curve);
+    assert!(curve == [-0.5, -0.5, 0.0, 0.5, 0.5]);
+    let x = shape_fn(|x| x * x);
+    x.0.transfer_curve(2.0, &mut curve);
+    assert!(curve == [4.0, 1.0, 0.0, 1.0, 4.0]);
+    // Adaptive distortion curve follows the level estimate without changing it.
+    let mut x = shape(Shape::AdaptiveTanh(0.01, 1.0));
+    for i in 0..44100 {
+        x.filter_mono(0.1 * sin(i as f64 * 0.1));
+    }
+    x.0.transfer_curve(0.1, &mut curve);
+    assert!(curve[4] > 0.8 && curve[4] < 0.95 && curve[2] == 0.0);
+    assert!(x.clone().filter_mono(0.1) == x.filter_mono(0.1));
+
+    // Test saturating bus. Quiet sums pass at unity gain, loud sums are limited to 1 / drive.
+    // Antialiasing delays the signal by half a sample, so skip the first sample.
+    let mut x = bus_saturate::<U2>(2.0);
+    x.tick(&Frame::from([0.001, 0.002]));
+    for _ in 0..10 {
+        assert!(abs(x.tick(&Frame::from([0.001, 0.002]))[0] - 0.003) < 1.0e-6);
+    }
+    for i in 0..1000 {
+        let s = 4.0 * sin(i as f64 * 0.1);
+        let y = x.tick(&Frame::from([s, s]))[0];
+        assert!(abs(y) <= 0.5 + 1.0e-9);
+    }
+    for _ in 0..10 {
+        x.tick(&Frame::from([4.0, 4.0]));
+    }
+    assert!(abs(x.tick(&Frame::from([4.0, 4.0]))[0] - 0.5) < 1.0e-6);
+    x.set(1.0);
+    assert!(x.0.drive() == 1.0);
+
+    // Test AGC. A quiet sine is boosted toward the target level,
+    // then the gain is held during silence.
+    let mut x = agc(-20.0, 12.0, 10.0);
+    let amplitude = db_amp(-30.0) * SQRT_2;
+    for i in 0..44100 * 4 {
+        x.filter_mono(amplitude * sin(i as f64 * TAU * 440.0 / 44100.0));
+    }
+    assert!(abs(x.0.gain() - 10.0) < 0.5);
+    for _ in 0..44100 {
+        assert!(x.filter_mono(0.0) == 0.0);
+    }
+    assert!(abs(x.0.gain() - 10.0) < 0.5);
+
+    // Test fader. Gain changes ramp monotonically to the target in 20 ms.
+    let mut x = gain_db(-6.0);
+    assert!(abs(x.filter_mono(1.0) - db_amp(-6.0)) < 1.0e-12);
+    x.set(f64::NEG_INFINITY);
+    let mut previous = db_amp(-6.0);
+    for _ in 0..882 {
+        let y = x.filter_mono(1.0);
+        assert!(y < previous);
+        previous = y;
+    }
+    assert!(previous == 0.0);
+    assert!(x.0.gain_db() == f64::NEG_INFINITY);
+    let mut x = fader();
+    assert!(x.filter_mono(0.5) == 0.5);
+    x.set(6.0);
+    for _ in 0..882 {
+        x.filter_mono(1.0);
+    }
+    assert!(abs(x.filter_mono(1.0) - db_amp(6.0)) < 1.0e-12);
+    assert!(abs(x.response_db(0, 1000.0).unwrap() - 6.0) < 1.0e-9);
+
     // Test monitor and meter for consistency.
     let s1 = shared(0.0);
     let mut m1 = monitor(&s1, Meter::Sample);
@@ -67,4 +181,161 @@ fn test_dynamics() {
         assert!(x > 0.0 && x == x1 && x2 >= 0.0);
         assert_eq!(x2, s1.value());
     }
+
+    // Test stereo field meters.
+    let correlation = shared(0.0);
+    let balance = shared(0.0);
+    let mut m = monitor_stereo(&correlation, Meter::Correlation(0.1))
+        >> monitor_stereo(&balance, Meter::Balance(0.1));
+    for _ in 0..100000 {
+        let x = rnd.f64() - 0.5;
+        assert_eq!(m.filter_stereo(x, x), (x, x));
+    }
+    assert!(abs(correlation.value() - 1.0) < 1.0e-6 && abs(balance.value()) < 1.0e-6);
+    for _ in 0..100000 {
+        let x = rnd.f64() - 0.5;
+        m.filter_stereo(x, -0.5 * x);
+    }
+    assert!(abs(correlation.value() + 1.0) < 1.0e-6);
+    assert!(abs(balance.value() + 1.0 / 3.0) < 1.0e-6);
+    for _ in 0..100000 {
+        m.filter_stereo(rnd.f64() - 0.5, rnd.f64() - 0.5);
+    }
+    assert!(abs(correlation.value()) < 0.1 && abs(balance.value()) < 0.1);
+
+    // Test meter ballistics.
+    let mut vu = meter(Meter::Vu);
+    let mut window = meter(Meter::WindowRms(0.1));
+    let mut ppm = meter(Meter::Ppm);
+    for i in 0..44100 {
+        let x = sin(i as f64 * TAU * 1000.0 / 44100.0);
+        vu.filter_mono(x);
+        window.filter_mono(x);
+        ppm.filter_mono(x);
+    }
+    assert!(abs(vu.filter_mono(0.0) - SQRT_2 * 0.5) < 0.01);
+    assert!(abs(window.filter_mono(0.0) - SQRT_2 * 0.5) < 0.01);
+    assert!(ppm.filter_mono(0.0) > 0.95);
+    // A 10 ms tone burst reads 4 dB below steady state.
+    let mut ppm = meter(Meter::Ppm);
+    let mut level = 0.0;
+    for i in 0..441 {
+        level = ppm.filter_mono(sin(i as f64 * TAU * 5000.0 / 44100.0));
+    }
+    assert!(abs(amp_db(level) + 4.0) < 0.5);
+    let mut hold = meter(Meter::PeakHold(0.5, 0.1));
+    assert!(hold.filter_mono(1.0) == 1.0);
+    for _ in 0..22050 {
+        assert!(hold.filter_mono(0.0) == 1.0);
+    }
+    assert!(hold.filter_mono(0.0) < 1.0);
+
+    // Test weighting filters against tabulated values at 31.5 Hz and 100 Hz.
+    for (curve, db31, db100) in [
+        (Weighting::A, -39.4, -19.1),
+        (Weighting::B, -17.1, -5.6),
+        (Weighting::C, -3.0, -0.3),
+    ] {
+        let mut w = weighting(curve);
+        assert!(w.response_db(0, 1000.0).unwrap().abs() < 1.0e-6);
+        assert!((w.response_db(0, 31.5).unwrap() - db31).abs() < 0.2);
+        assert!((w.response_db(0, 100.0).unwrap() - db100).abs() < 0.2);
+    }
+    let mut w = weighting(Weighting::A);
+    for f in [50.0, 200.0, 500.0, 2000.0, 4000.0] {
+        assert!((w.response_db(0, f).unwrap() - amp_db(a_weight(f))).abs() < 0.4);
+    }
+    let mut k = weighting(Weighting::K);
+    k.set_sample_rate(48000.0);
+    assert!((k.response_db(0, 997.0).unwrap() - 0.691).abs() < 0.01);
+    // Weighted meters apply the weighting internally.
+    let mut weighted = meter(Meter::Weighted(Weighting::A, 0.1));
+    let mut reference = weighting(Weighting::A) >> meter(Meter::Rms(0.1));
+    for i in 0..44100 {
+        let x = sin(i as f64 * TAU * 100.0 / 44100.0);
+        assert!(abs(weighted.filter_mono(x) - reference.filter_mono(x)) < 1.0e-9);
+    }
+    assert!(abs(amp_db(weighted.filter_mono(0.0) * SQRT_2) + 19.1) < 0.2);
+
+    // Test phase metering and mono compatibility utilities.
+    let mut identical = noise() >> split::<U2>() >> phase_meter();
+    let mut opposite = noise() >> (pass() ^ invert_phase()) >> phase_meter();
+    let mut unrelated = (noise() | mls()) >> phase_meter();
+    for _ in 0..44100 {
+        identical.get_mono();
+        opposite.get_mono();
+        unrelated.get_mono();
+    }
+    assert!(identical.get_mono() > 0.999);
+    assert!(opposite.get_mono() < -0.999);
+    assert!(abs(unrelated.get_mono()) < 0.1);
+    let mut check = mono_check();
+    assert!(check.filter_stereo(0.5, -0.5) == (0.0, 0.0));
+    assert!(check.filter_stereo(1.0, 0.5) == (0.75, 0.75));
+    let mut balance = meter_stereo(Meter::Balance(0.1));
+    for _ in 0..4410 {
+        balance.tick(&Frame::from([0.0, 1.0]));
+    }
+    assert!(balance.tick(&Frame::from([0.0, 1.0]))[0] > 0.999);
+
+    // Wave correlation over time follows the channel relationship in each window.
+    let mut wave = Wave64::render(44100.0, 1.0, &mut (noise() | mls()));
+    for i in 22050..44100 {
+        wave.set(1, i, -wave.at(0, i));
+    }
+    let correlation = wave.correlation(0.25);
+    assert!(correlation.len() == 4);
+    assert!(abs(correlation[0]) < 0.1 && abs(correlation[1]) < 0.1);
+    assert!(correlation[2] < -0.999 && correlation[3] < -0.999);
+
+    // Spectral features of a tone and of noise.
+    let tone = Wave64::render(44100.0, 0.5, &mut sine_hz(1000.0));
+    let hiss = Wave64::render(44100.0, 0.5, &mut noise());
+    let features = [
+        Feature::Centroid,
+        Feature::Flatness,
+        Feature::Rolloff(0.5),
+        Feature::Mfcc(0),
+    ];
+    let tone_features = tone.features(0, 2048, 2048, &features);
+    let noise_features = hiss.features(0, 2048, 2048, &features);
+    assert!(tone_features.len() == 11 && tone_features[5].len() == 4);
+    assert!(abs(tone_features[5][0] - 1000.0) < 20.0);
+    assert!(tone_features[5][1] < 0.01 && noise_features[5][1] > 0.3);
+    assert!(abs(tone_features[5][2] - 1000.0) < 50.0);
+    assert!(noise_features[5][2] > 8000.0 && noise_features[5][2] < 14000.0);
+    let mut analyzer = SpectralFeatures::new(1024, 44100.0);
+    analyzer.analyze(&tone.channel(0)[..1024]);
+    let loud = analyzer.mfcc()[0];
+    let quiet: Vec<f64> = tone.channel(0)[..1024].iter().map(|x| x * 0.1).collect();
+    analyzer.analyze(&quiet);
+    assert!(analyzer.mfcc()[0] < loud && analyzer.mfcc().len() == MFCC_COEFFICIENTS);
+    analyzer.analyze::<f64>(&[]);
+    assert!(analyzer.centroid() == 0.0 && analyzer.flatness() == 0.0);
+
+    // Feature monitors pass through input and track features while streaming.
+    let centroid = shared(0.0);
+    let mut graph = sine_hz(1000.0) >> monitor_feature(&centroid, Feature::Centroid);
+    for _ in 0..4096 {
+        graph.get_mono();
+    }
+    assert!(abs(centroid.value() - 1000.0) < 20.0);
+    let mut monitor = monitor_features(&[(Feature::Flatness, shared(0.0))]);
+    assert!(monitor.filter_mono(0.5) == 0.5);
+
+    // Keys are estimated from chroma, offline and while streaming.
+    let triad = |a: f64, b: f64, c: f64| (sine_hz(a) + sine_hz(b) + sine_hz(c)) * 0.3;
+    let c_major = Wave64::render(44100.0, 1.0, &mut triad(261.63, 329.63, 392.0));
+    let a_minor = Wave64::render(44100.0, 1.0, &mut triad(220.0, 261.63, 329.63));
+    assert!(c_major.key(0) == Key::from_index(0));
+    assert!(a_minor.key(0) == Key::from_index(21));
+    assert!(a_minor.key(0).to_string() == "A minor");
+    let chroma = c_major.features(0, 4096, 4096, &[Feature::Chroma(0), Feature::Chroma(1)]);
+    assert!(chroma[5][0] > 0.5 && chroma[5][1] < 0.1);
+    let key = shared(-1.0);
+    let mut graph = triad(220.0, 261.63, 329.63) >> monitor_feature(&key, Feature::Key);
+    for _ in 0..44100 {
+        graph.get_mono();
+    }
+    assert!(key.value() == 21.0);
 }
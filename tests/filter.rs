@@ -161,6 +161,48 @@ fn test_misc() {
             .norm()
             > 0.1
     );
+    // Linear phase FIR reports its delay as latency.
+    assert!(fir_from_response(&[(0.0, 1.0)], 301).latency() == Some(150.0));
+    let mut fir = fir_from_response(&[(0.0, 1.0), (10000.0, 0.5)], 301);
+    let frequencies = [100.0, 1000.0, 5000.0];
+    for (f, r) in frequencies.iter().zip(fir.response_batch(0, &frequencies)) {
+        assert!(r == fir.response(0, *f));
+        assert!((fir.group_delay(0, *f).unwrap() * DEFAULT_SR - 150.0).abs() < 1.0e-6);
+    }
+    // Windowed sinc designs are linear phase.
+    let mut lowpass = fir_lowpass(2000.0, 101, FirWindow::Blackman);
+    for i in 0..101 {
+        assert!(lowpass.weights()[i] == lowpass.weights()[100 - i]);
+    }
+    assert!((lowpass.response(0, 0.0).unwrap().norm() - 1.0).abs() < 1.0e-9);
+    assert!(lowpass.response(0, 4000.0).unwrap().norm() < db_amp(-60.0));
+    // Hilbert transformer shifts phase by -90 degrees.
+    let mut hilbert = fir_hilbert(255);
+    assert!(hilbert.latency() == Some(127.0));
+    let response = hilbert.response(0, 1000.0).unwrap()
+        * Complex64::from_polar(1.0, 1000.0 * TAU * 127.0 / DEFAULT_SR);
+    assert!((response - Complex64::new(0.0, -1.0)).norm() < 0.01);
+    // Crossover bands sum to a flat magnitude response.
+    let mut bands = crossover::<U4>(&[100.0, 1000.0, 8000.0]) >> recombine::<U4>();
+    let mut f = 20.0;
+    while f < 20000.0 {
+        assert!((bands.response(0, f).unwrap().norm() - 1.0).abs() < 1.0e-6);
+        f *= 1.1;
+    }
+    // Bode plots from signal flow analysis and from impulse responses agree.
+    let exact = bode_plot(&(lowpass_hz(1000.0, 1.0) >> delay(0.001)), 50);
+    let measured = bode_plot(
+        &(lowpass_hz(1000.0, 1.0) >> delay(0.001) >> map(|i: &Frame<f64, U1>| i[0])),
+        50,
+    );
+    assert!(exact.method == BodeMethod::Route && measured.method == BodeMethod::Impulse);
+    for i in 0..50 {
+        assert!((exact.magnitude[i] - measured.magnitude[i]).abs() < 1.0e-3);
+        assert!((exact.phase[i] - measured.phase[i]).abs() < 1.0e-3);
+        assert!((exact.group_delay[i] - measured.group_delay[i]).abs() < 1.0e-6);
+    }
+    assert!((exact.group_delay[0] - 44.0 / DEFAULT_SR - 1.0 / (TAU * 1000.0)).abs() < 1.0e-6);
+    assert!((exact.phase[49] + TAU * 20000.0 * 44.0 / DEFAULT_SR + PI).abs() < 0.1);
 }
 
 /// Test frequency response system.
@@ -175,6 +217,9 @@ fn test_responses() {
     test_response(lowpass_hz(50.0, 1.0));
     test_response(highpass_hz(5000.0, 1.0));
     test_response(bandpass_hz(100.0, 1.0));
+    test_response(filter_bank::<U3, _>(|i| {
+        (xerp(300.0, 3000.0, i as f64 / 2.0), 5.0, 0.5)
+    }));
     test_response(highpass_hz(500.0, 1.0) & bandpass_hz(500.0, 2.0));
     test_response(pinkpass());
     test_response(follow(0.0002));
@@ -253,6 +298,24 @@ fn test_responses() {
         0.18195209,
     ));
     test_response(pass() + 1.0 >> lowpass_hz(1000.0, 1.0));
+    test_response(tone_stack(0.5, 0.5, 0.5));
+    test_response(tone_stack(0.9, 0.1, 0.3));
+    let impulse: Vec<f64> = (0..300)
+        .map(|i| (rnd(i) * 2.0 - 1.0) * exp(-0.01 * i as f64))
+        .collect();
+    test_response(convolve(&impulse));
+    test_response(convolve(&impulse[..40]) >> convolve(&[0.5, 0.5]));
+    test_response(crossover::<U3>(&[300.0, 3000.0]) >> (sink() | pass() | sink()));
+    test_response(fir_from_response(
+        &[(0.0, 0.5), (1000.0, 1.0), (5000.0, 0.1)],
+        101,
+    ));
+    test_response(fir_from_response(&[(2000.0, 1.0), (2200.0, 0.0)], 256));
+    test_response(fir_lowpass(1000.0, 63, FirWindow::Hann));
+    test_response(fir_highpass(3000.0, 101, FirWindow::Kaiser(6.0)));
+    test_response(fir_bandpass(500.0, 2000.0, 128, FirWindow::Hamming));
+    test_response(fir_hilbert(65) & fir_lowpass(5000.0, 10, FirWindow::Rectangular));
+    test_response(crossover::<U4>(&[100.0, 1000.0, 8000.0]) >> recombine::<U4>());
 
     let mut net1 = Net64::new(1, 1);
     net1.chain(Box::new(lowpole_hz(1500.0)));
@@ -324,3 +387,68 @@ fn test_responses() {
             >> (Net64::wrap(Box::new(pinkpass())) + pass() + pass()),
     );
 }
+
+/// Test filter bank band responses.
+#[test]
+fn test_filter_bank() {
+    let bands = [(200.0, 5.0, 1.0), (1000.0, 5.0, 0.5), (5000.0, 5.0, 0.25)];
+    let mut bank = filter_bank::<U3, _>(|i| bands[i]);
+    // The response peaks at each band center with the gain of the band.
+    for (center, _, gain) in bands {
+        let peak = bank.response(0, center).unwrap().norm();
+        assert!((peak - gain).abs() < 0.15 * gain);
+        for ratio in [0.8, 1.25] {
+            assert!(bank.response(0, center * ratio).unwrap().norm() < peak);
+        }
+    }
+    // A band with zero gain contributes nothing.
+    let mut muted = filter_bank::<U3, _>(|i| if i == 1 { (1000.0, 5.0, 0.0) } else { bands[i] });
+    let mut pair = filter_bank::<U2, _>(|i| bands[i * 2]);
+    for f in [100.0, 200.0, 1000.0, 5000.0, 10000.0] {
+        let difference = muted.response(0, f).unwrap() - pair.response(0, f).unwrap();
+        assert!(difference.norm() < 1.0e-12);
+    }
+    let mut rnd = Rnd::from_u64(1);
+    for _ in 0..10000 {
+        let x = rnd.f64() - 0.5;
+        assert!((muted.filter_mono(x) - pair.filter_mono(x)).abs() < 1.0e-12);
+    }
+}
+
+#[test]
+fn test_oversample() {
+    // Linear phase oversampling delays an impulse by exactly the reported latency.
+    let mut x = oversample_linear(pass());
+    assert!(x.latency() == Some(OVERSAMPLE_LINEAR_LATENCY as f64));
+    let impulse: Vec<f64> = (0..100)
+        .map(|i| x.filter_mono(if i == 0 { 1.0 } else { 0.0 }))
+        .collect();
+    let peak = (0..100)
+        .max_by(|&i, &j| abs(impulse[i]).total_cmp(&abs(impulse[j])))
+        .unwrap();
+    assert!(peak == OVERSAMPLE_LINEAR_LATENCY);
+
+    // Reported responses match measured responses below the filter transition band.
+    // (Above it, aliasing from the inner sample rate is not accounted for.)
+    let units: Vec<Box<dyn AudioUnit64>> = vec![
+        Box::new(oversample(lowpass_hz(2000.0, 1.0))),
+        Box::new(oversample_linear(
+            bell_hz(1000.0, 1.0, 2.0) >> delay(0.0001),
+        )),
+        Box::new(oversample_latency_compensated(lowpole_hz(1000.0)) >> (pass() - pass())),
+    ];
+    for mut unit in units {
+        let impulse: Vec<f64> = (0..0x4000)
+            .map(|i| unit.filter_mono(if i == 0 { 1.0 } else { 0.0 }))
+            .collect();
+        for f in [50.0, 500.0, 2000.0, 8000.0, 16000.0] {
+            let measured = impulse
+                .iter()
+                .enumerate()
+                .fold(Complex64::new(0.0, 0.0), |acc, (i, x)| {
+                    acc + Complex64::from_polar(*x, -TAU * f * i as f64 / DEFAULT_SR)
+                });
+            assert!(is_equal_response(unit.response(0, f).unwrap(), measured));
+        }
+    }
+}
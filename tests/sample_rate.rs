@@ -0,0 +1,238 @@
+use fundsp::hacker::*;
+
+/// Render the first output of `unit` at the given sample rate,
+/// feeding a short burst of deterministic noise into all inputs.
+fn render(unit: &mut dyn AudioUnit64, sample_rate: f64, seconds: f64) -> Vec<f64> {
+    unit.set_sample_rate(sample_rate);
+    unit.reset();
+    let mut seed = 1u64;
+    let mut input = vec![0.0; unit.inputs()];
+    let mut output = vec![0.0; unit.outputs()];
+    let mut v = Vec::new();
+    for i in 0..(seconds * sample_rate) as usize {
+        for x in input.iter_mut() {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            *x = if i < 10 {
+                (seed >> 40) as f64 / (1u64 << 24) as f64 - 0.5
+            } else {
+                0.0
+            };
+        }
+        unit.tick(&input, &mut output);
+        v.push(output[0]);
+    }
+    v
+}
+
+/// Check that a unit rendered at 44.1 kHz is unaffected by
+/// having been run at another sample rate before.
+fn check_consistency(unit: &dyn AudioUnit64) {
+    let mut a = unit.clone_unit();
+    let mut b = unit.clone_unit();
+    render(&mut *b, 96000.0, 0.05);
+    let x = render(&mut *a, 44100.0, 0.2);
+    let y = render(&mut *b, 44100.0, 0.2);
+    for i in 0..x.len() {
+        assert!((x[i] - y[i]).abs() <= 1.0e-9);
+    }
+}
+
+/// Time in seconds it takes the response to a unit step to reach half its peak.
+fn step_time(unit: &dyn AudioUnit64, sample_rate: f64) -> f64 {
+    let mut u = unit.clone_unit();
+    u.set_sample_rate(sample_rate);
+    u.reset();
+    let input = vec![1.0; u.inputs()];
+    let mut output = vec![0.0; u.outputs()];
+    let mut v = Vec::new();
+    for _ in 0..sample_rate as usize {
+        u.tick(&input, &mut output);
+        v.push(output[0]);
+    }
+    let peak = v.iter().fold(0.0f64, |acc, x| acc.max(x.abs()));
+    v.iter().position(|x| x.abs() >= 0.5 * peak).unwrap() as f64 / sample_rate
+}
+
+/// Check that step response timing in seconds does not depend on the sample rate.
+fn check_step_time(unit: &dyn AudioUnit64) {
+    let a = step_time(unit, 44100.0);
+    let b = step_time(unit, 96000.0);
+    assert!((a - b).abs() <= 0.0005 + 0.02 * a);
+}
+
+/// Check that frequency response magnitude does not depend on the sample rate.
+fn check_response(unit: &dyn AudioUnit64) {
+    for f in [100.0, 1000.0] {
+        let mut a = unit.clone_unit();
+        a.set_sample_rate(44100.0);
+        let mut b = unit.clone_unit();
+        b.set_sample_rate(96000.0);
+        let x = a.response(0, f).unwrap().norm();
+        let y = b.response(0, f).unwrap().norm();
+        assert!((x - y).abs() <= 0.02 * x.max(1.0e-3));
+    }
+}
+
+/// Number of zero crossings in one second of output.
+fn crossings(unit: &dyn AudioUnit64, sample_rate: f64) -> usize {
+    let mut u = unit.clone_unit();
+    let v = render(&mut *u, sample_rate, 1.0);
+    let mean = v.iter().sum::<f64>() / v.len() as f64;
+    (1..v.len())
+        .filter(|&i| (v[i - 1] <= mean) != (v[i] <= mean))
+        .count()
+}
+
+/// Check that generated frequency does not depend on the sample rate.
+fn check_frequency(unit: &dyn AudioUnit64) {
+    let a = crossings(unit, 44100.0) as f64;
+    let b = crossings(unit, 96000.0) as f64;
+    assert!((a - b).abs() <= 2.0 + 0.01 * a);
+}
+
+#[test]
+fn test_sample_rate() {
+    let filters: Vec<Box<dyn AudioUnit64>> = vec![
+        Box::new(lowpass_hz(1000.0, 1.0)),
+        Box::new(highpass_hz(1000.0, 1.0)),
+        Box::new(bandpass_hz(1000.0, 1.0)),
+        Box::new(notch_hz(1000.0, 1.0)),
+        Box::new(peak_hz(1000.0, 1.0)),
+        Box::new(bell_hz(1000.0, 1.0, 2.0)),
+        Box::new(lowshelf_hz(1000.0, 1.0, 2.0)),
+        Box::new(highshelf_hz(1000.0, 1.0, 2.0)),
+        Box::new(allpass_hz(1000.0, 1.0)),
+        Box::new(lowpole_hz(1000.0)),
+        Box::new(highpole_hz(100.0)),
+        Box::new(butterpass_hz(1000.0)),
+        Box::new(dcblock_hz(100.0)),
+        Box::new(morph_hz(1000.0, 1.0, 0.5)),
+        Box::new(crossover::<U2>(&[1000.0]) >> recombine::<U2>()),
+        Box::new(tone_stack(0.5, 0.5, 0.5)),
+        Box::new(delay(0.001)),
+    ];
+    for unit in filters.iter() {
+        check_response(unit.as_ref());
+        check_consistency(unit.as_ref());
+    }
+
+    let generators: Vec<Box<dyn AudioUnit64>> = vec![
+        Box::new(sine_hz(110.0)),
+        Box::new(saw_hz(110.0)),
+        Box::new(square_hz(110.0)),
+        Box::new(triangle_hz(110.0)),
+        Box::new(organ_hz(110.0)),
+        Box::new(soft_saw_hz(110.0)),
+        Box::new(hammond_hz(110.0)),
+        Box::new(dc((110.0, 0.5)) >> dsf_saw()),
+        Box::new(dc((110.0, 0.5)) >> pulse()),
+        Box::new(lfo(|t| sin_hz(110.0, t))),
+        Box::new(impulse_train(110.0) >> lowpass_hz(100.0, 1.0) >> dcblock()),
+        Box::new(oversample(sine_hz(110.0))),
+        Box::new(resample(sine_hz(110.0))),
+        Box::new(control_rate(4, sine_hz(110.0))),
+    ];
+    for unit in generators.iter() {
+        check_frequency(unit.as_ref());
+        check_consistency(unit.as_ref());
+    }
+
+    let timed: Vec<Box<dyn AudioUnit64>> = vec![
+        Box::new(follow(0.01)),
+        Box::new(follow((0.01, 0.02))),
+        Box::new(meter(Meter::Rms(0.01))),
+        Box::new(meter(Meter::Peak(0.01))),
+        Box::new(meter(Meter::WindowRms(0.01))),
+        Box::new(declick_s(0.01)),
+        Box::new(lowpole_hz(100.0)),
+        Box::new(envelope(|t| if t > 0.01 { 1.0 } else { 0.0 })),
+        Box::new(adsr_live(0.01, 0.01, 0.5, 0.1)),
+        Box::new(delay(0.01)),
+        Box::new(agc(-20.0, 10.0, 3.0)),
+        Box::new(limiter((0.01, 0.1))),
+        Box::new(mix(limiter((0.01, 0.1)), 1.0)),
+        Box::new(fdn::<U1, _>(delay(0.01) * 0.5)),
+        Box::new(feedback(delay(0.01) * 0.5)),
+        Box::new((pass() | dc(120.0)) >> delay_sync(0.01)),
+    ];
+    for unit in timed.iter() {
+        check_step_time(unit.as_ref());
+        check_consistency(unit.as_ref());
+    }
+
+    let others: Vec<Box<dyn AudioUnit64>> = vec![
+        Box::new(split::<U2>() >> reverb_stereo(10.0, 1.0) >> join::<U2>()),
+        Box::new(flanger(0.5, 0.001, 0.005, |t| sin_hz(1.0, t) * 0.5 + 0.5)),
+        Box::new(phaser(0.5, |t| sin_hz(1.0, t) * 0.5 + 0.5)),
+        Box::new(chorus(0, 0.0, 0.001, 0.2)),
+        Box::new(echo(0.1, 0.5, pass())),
+        Box::new(limiter_true_peak((0.01, 0.1), -1.0)),
+        Box::new(shape(Shape::AdaptiveTanh(0.01, 1.0))),
+        Box::new(burst(0.01, 0.5)),
+        Box::new(hold_hz(100.0, 0.0)),
+        Box::new(moog_hz(1000.0, 0.1)),
+        Box::new(lowrez_hz(1000.0, 0.5)),
+        Box::new(bandrez_hz(1000.0, 0.5)),
+        Box::new(tremolo(5.0, 0.5, LfoShape::Triangle)),
+        Box::new(autopan(1.0, 1.0, LfoShape::Sine)),
+    ];
+    for unit in others.iter() {
+        check_consistency(unit.as_ref());
+    }
+
+    // Units added to a network after its sample rate has been set run at the network sample rate.
+    let mut net = Net64::new(1, 1);
+    net.set_sample_rate(96000.0);
+    let id = net.push(Box::new(pass()));
+    net.pipe_input(id);
+    net.pipe_output(id);
+    net.replace(id, Box::new(delay(0.01)));
+    assert!(net.sample_rate() == 96000.0);
+    for i in 0..2000 {
+        let y = net.filter_mono(if i == 0 { 1.0 } else { 0.0 });
+        assert!(y == if i == 960 { 1.0 } else { 0.0 });
+    }
+
+    // Combining networks adopts the sample rate of the left hand operand.
+    let mut left = Net64::wrap(Box::new(pass()));
+    left.set_sample_rate(96000.0);
+    let mut net = left >> Net64::wrap(Box::new(delay(0.01)));
+    assert!(net.sample_rate() == 96000.0);
+    for i in 0..2000 {
+        let y = net.filter_mono(if i == 0 { 1.0 } else { 0.0 });
+        assert!(y == if i == 960 { 1.0 } else { 0.0 });
+    }
+
+    // Units sent to a slot backend run at the backend sample rate.
+    let (mut slot, mut backend) = Slot64::new(Box::new(pass()));
+    backend.set_sample_rate(96000.0);
+    slot.set(Fade::Smooth, 0.0, Box::new(delay(0.01)));
+    let mut output = [0.0];
+    // Let the backend receive the unit and finish the fade.
+    for _ in 0..10 {
+        backend.tick(&[0.0], &mut output);
+    }
+    for i in 0..2000 {
+        backend.tick(&[if i == 0 { 1.0 } else { 0.0 }], &mut output);
+        assert!(output[0] == if i == 960 { 1.0 } else { 0.0 });
+    }
+
+    // Events sent to a sequencer backend run at the backend sample rate.
+    let mut sequencer = Sequencer64::new(false, 1);
+    let mut backend = sequencer.backend();
+    backend.set_sample_rate(96000.0);
+    sequencer.push(
+        0.0,
+        1.0,
+        Fade::Smooth,
+        0.0,
+        0.0,
+        Box::new(dc(1.0) >> delay(0.01)),
+    );
+    for i in 0..2000 {
+        let y = backend.get_mono();
+        assert!(y == if i >= 960 { 1.0 } else { 0.0 });
+    }
+}